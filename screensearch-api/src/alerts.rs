@@ -0,0 +1,235 @@
+//! Keyword watch alerts
+//!
+//! Alerts are keywords or regexes matched against the OCR text of every frame
+//! the capture pipeline stores. Each alert matching a frame is recorded as an
+//! alert hit and published on the event stream.
+
+use crate::events::ServerEvent;
+use regex::{Regex, RegexBuilder};
+use screensearch_db::{AlertHitRecord, AlertRecord, DatabaseManager, NewAlertHit};
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+
+/// Maximum stored length of the OCR text surrounding a match (characters)
+const MAX_CONTEXT_CHARS: usize = 200;
+
+/// Compiled form of a stored alert
+#[derive(Debug)]
+pub struct AlertMatcher {
+    alert_id: i64,
+    regex: Regex,
+    app_filter: Option<String>,
+}
+
+impl AlertMatcher {
+    /// Compile an alert
+    ///
+    /// Keywords match case-insensitively; regexes are used as given.
+    pub fn new(alert: &AlertRecord) -> Result<Self, regex::Error> {
+        Self::compile(
+            alert.id,
+            &alert.pattern,
+            alert.is_regex,
+            alert.app_filter.as_deref(),
+        )
+    }
+
+    /// Compile an alert pattern without a stored alert (validation)
+    pub fn compile(
+        alert_id: i64,
+        pattern: &str,
+        is_regex: bool,
+        app_filter: Option<&str>,
+    ) -> Result<Self, regex::Error> {
+        let regex = if is_regex {
+            Regex::new(pattern)?
+        } else {
+            RegexBuilder::new(&regex::escape(pattern))
+                .case_insensitive(true)
+                .build()?
+        };
+
+        Ok(Self {
+            alert_id,
+            regex,
+            app_filter: app_filter
+                .map(|app| app.trim().to_lowercase())
+                .filter(|app| !app.is_empty()),
+        })
+    }
+
+    /// Whether the alert watches frames of the given application
+    ///
+    /// The app filter matches any part of the process name, ignoring case.
+    pub fn applies_to(&self, app_name: Option<&str>) -> bool {
+        match (&self.app_filter, app_name) {
+            (None, _) => true,
+            (Some(filter), Some(app)) => app.to_lowercase().contains(filter.as_str()),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// First match in the given OCR text regions, with the region it was found in
+    pub fn find<'t>(&self, texts: &'t [String]) -> Option<(&'t str, &'t str)> {
+        texts
+            .iter()
+            .find_map(|text| self.regex.find(text).map(|m| (m.as_str(), text.as_str())))
+    }
+}
+
+/// Evaluates alerts against frames as they are stored
+pub struct AlertWatcher {
+    db: Arc<DatabaseManager>,
+    events: broadcast::Sender<ServerEvent>,
+    /// Compiled alerts; `None` until loaded or after alerts change
+    matchers: RwLock<Option<Arc<Vec<AlertMatcher>>>>,
+}
+
+impl AlertWatcher {
+    /// Create a watcher publishing hits on `events`
+    pub fn new(db: Arc<DatabaseManager>, events: broadcast::Sender<ServerEvent>) -> Self {
+        Self {
+            db,
+            events,
+            matchers: RwLock::new(None),
+        }
+    }
+
+    /// Reload alerts before the next frame is checked
+    pub fn invalidate(&self) {
+        *self.matchers.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Check a stored frame against all alerts
+    ///
+    /// Records at most one hit per alert and returns the hits. Failures are
+    /// logged so alerting never blocks frame storage.
+    pub async fn check_frame(
+        &self,
+        frame_id: i64,
+        app_name: Option<&str>,
+        texts: &[String],
+    ) -> Vec<AlertHitRecord> {
+        if texts.is_empty() {
+            return Vec::new();
+        }
+
+        let matchers = match self.matchers().await {
+            Ok(matchers) => matchers,
+            Err(e) => {
+                tracing::error!("Failed to load alerts: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut hits = Vec::new();
+        for matcher in matchers.iter().filter(|m| m.applies_to(app_name)) {
+            let Some((matched_text, context)) = matcher.find(texts) else {
+                continue;
+            };
+
+            let hit = NewAlertHit {
+                alert_id: matcher.alert_id,
+                frame_id,
+                matched_text: matched_text.to_string(),
+                context: context.chars().take(MAX_CONTEXT_CHARS).collect(),
+                app_name: app_name.map(str::to_string),
+            };
+
+            match self.db.insert_alert_hit(hit).await {
+                Ok(hit) => {
+                    tracing::info!(
+                        "Alert {} matched frame {}: {:?}",
+                        hit.alert_id,
+                        frame_id,
+                        hit.matched_text
+                    );
+                    // No subscribers is not an error
+                    let _ = self.events.send(ServerEvent::AlertHit { hit: hit.clone() });
+                    hits.push(hit);
+                }
+                Err(e) => tracing::error!("Failed to store alert hit: {}", e),
+            }
+        }
+
+        hits
+    }
+
+    /// Compiled alerts, loading them from the database if needed
+    async fn matchers(&self) -> screensearch_db::Result<Arc<Vec<AlertMatcher>>> {
+        if let Some(matchers) = self
+            .matchers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+        {
+            return Ok(Arc::clone(matchers));
+        }
+
+        let matchers: Vec<_> = self
+            .db
+            .list_alerts()
+            .await?
+            .iter()
+            .filter_map(|alert| match AlertMatcher::new(alert) {
+                Ok(matcher) => Some(matcher),
+                Err(e) => {
+                    tracing::warn!("Skipping alert {} with invalid pattern: {}", alert.id, e);
+                    None
+                }
+            })
+            .collect();
+        let matchers = Arc::new(matchers);
+
+        *self.matchers.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::clone(&matchers));
+        Ok(matchers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_keyword_matches_case_insensitively() {
+        let matcher = AlertMatcher::compile(1, "OutOfMemoryError", false, None).unwrap();
+        let regions = texts(&["Build started", "java.lang.outofmemoryerror: heap"]);
+
+        let (matched, context) = matcher.find(&regions).unwrap();
+        assert_eq!(matched, "outofmemoryerror");
+        assert_eq!(context, "java.lang.outofmemoryerror: heap");
+    }
+
+    #[test]
+    fn test_keyword_is_not_a_regex() {
+        let matcher = AlertMatcher::compile(1, "a.b", false, None).unwrap();
+        assert!(matcher.find(&texts(&["axb"])).is_none());
+        assert!(matcher.find(&texts(&["see a.b"])).is_some());
+    }
+
+    #[test]
+    fn test_regex_pattern() {
+        let matcher = AlertMatcher::compile(1, r"ERR-\d{3}", true, None).unwrap();
+        assert_eq!(
+            matcher.find(&texts(&["code ERR-404"])).unwrap().0,
+            "ERR-404"
+        );
+        assert!(matcher.find(&texts(&["code ERR-4"])).is_none());
+        assert!(AlertMatcher::compile(1, "(", true, None).is_err());
+    }
+
+    #[test]
+    fn test_app_filter() {
+        let matcher = AlertMatcher::compile(1, "error", false, Some(" Code ")).unwrap();
+        assert!(matcher.applies_to(Some("Code.exe")));
+        assert!(!matcher.applies_to(Some("chrome.exe")));
+        assert!(!matcher.applies_to(None));
+
+        let matcher = AlertMatcher::compile(1, "error", false, None).unwrap();
+        assert!(matcher.applies_to(None));
+    }
+}
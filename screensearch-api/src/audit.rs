@@ -0,0 +1,211 @@
+//! Automation audit log
+//!
+//! Records every `/automation/*` call, and every automation tool call made by
+//! the agent, in the append-only `automation_audit` table: the endpoint, its
+//! parameters, the resolved UI element, the result and the caller.
+
+use crate::models::ElementInfo;
+use crate::state::AppState;
+use axum::body::{to_bytes, Body};
+use axum::extract::{OriginalUri, Request, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use screensearch_db::{DatabaseManager, NewAuditEntry};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::error;
+
+/// Header a client can set to identify itself in the audit log
+pub const CALLER_HEADER: &str = "x-caller";
+
+/// Maximum stored length of parameters, element and error text
+const MAX_FIELD_LEN: usize = 4096;
+/// Maximum request body buffered for the audit entry
+const MAX_REQUEST_BODY: usize = 1024 * 1024;
+/// Maximum error response body buffered to extract the error message
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+/// UI element an automation handler resolved
+///
+/// Handlers add this to their response extensions so the audit entry records
+/// which element was acted on.
+#[derive(Debug, Clone)]
+pub struct AuditedElement(pub ElementInfo);
+
+/// Middleware that records each automation request in the audit log
+pub async fn audit_automation(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.0.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let endpoint = format!("{} {}", request.method(), path);
+    let caller = caller_from_headers(request.headers());
+    let query = request.uri().query().map(str::to_string);
+
+    // Buffer the body so its parameters can be recorded
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_REQUEST_BODY).await {
+        Ok(body) => body,
+        Err(_) => return (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response(),
+    };
+    let parameters = if body.is_empty() {
+        query
+    } else {
+        Some(String::from_utf8_lossy(&body).into_owned())
+    };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    let element = response
+        .extensions()
+        .get::<AuditedElement>()
+        .and_then(|element| serde_json::to_string(&element.0).ok());
+    let success = response.status().is_success();
+
+    // Keep the error message of failed calls
+    let (response, error) = if success {
+        (response, None)
+    } else {
+        let (parts, body) = response.into_parts();
+        let body = to_bytes(body, MAX_ERROR_BODY).await.unwrap_or_default();
+        let error = error_message(parts.status, &body);
+        (Response::from_parts(parts, Body::from(body)), Some(error))
+    };
+
+    record(
+        &state.db,
+        NewAuditEntry {
+            endpoint,
+            parameters,
+            element,
+            success,
+            error,
+            caller,
+            duration_ms: start.elapsed().as_millis() as i64,
+        },
+    )
+    .await;
+
+    response
+}
+
+/// Record an automation tool call made by the agent
+pub async fn record_agent_action(
+    db: &DatabaseManager,
+    tool: &str,
+    arguments: &str,
+    error: Option<&str>,
+    duration: Duration,
+) {
+    record(
+        db,
+        NewAuditEntry {
+            endpoint: format!("agent:{}", tool),
+            parameters: Some(arguments.to_string()),
+            element: None,
+            success: error.is_none(),
+            error: error.map(str::to_string),
+            caller: Some("agent".to_string()),
+            duration_ms: duration.as_millis() as i64,
+        },
+    )
+    .await;
+}
+
+/// Append an entry, truncating long fields; failures are logged, not returned
+async fn record(db: &DatabaseManager, mut entry: NewAuditEntry) {
+    entry.parameters = entry.parameters.map(truncate);
+    entry.element = entry.element.map(truncate);
+    entry.error = entry.error.map(truncate);
+
+    if let Err(e) = db.insert_audit_entry(entry).await {
+        error!("Failed to record automation audit entry: {}", e);
+    }
+}
+
+/// Caller identity from the `X-Caller` header, falling back to `User-Agent`
+fn caller_from_headers(headers: &HeaderMap) -> Option<String> {
+    [CALLER_HEADER, "user-agent"]
+        .iter()
+        .filter_map(|name| headers.get(*name))
+        .filter_map(|value| value.to_str().ok())
+        .map(str::trim)
+        .find(|value| !value.is_empty())
+        .map(|value| truncate(value.to_string()))
+}
+
+/// Error message of a failed response: the `error` field of a JSON body, a
+/// plain text body, or the status
+fn error_message(status: StatusCode, body: &[u8]) -> String {
+    if let Ok(json) = serde_json::from_slice::<serde_json::Value>(body) {
+        if let Some(error) = json.get("error").and_then(|e| e.as_str()) {
+            return error.to_string();
+        }
+    }
+
+    match std::str::from_utf8(body).map(str::trim) {
+        Ok(text) if !text.is_empty() => text.to_string(),
+        _ => status.to_string(),
+    }
+}
+
+fn truncate(mut value: String) -> String {
+    if value.len() > MAX_FIELD_LEN {
+        let mut end = MAX_FIELD_LEN;
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        value.truncate(end);
+        value.push('…');
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_caller_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(caller_from_headers(&headers), None);
+
+        headers.insert("user-agent", HeaderValue::from_static("curl/8.0"));
+        assert_eq!(caller_from_headers(&headers).as_deref(), Some("curl/8.0"));
+
+        headers.insert(CALLER_HEADER, HeaderValue::from_static("my-agent"));
+        assert_eq!(caller_from_headers(&headers).as_deref(), Some("my-agent"));
+    }
+
+    #[test]
+    fn test_error_message() {
+        let body = br#"{"error": "Automation error: element not found", "status": 500}"#;
+        assert_eq!(
+            error_message(StatusCode::INTERNAL_SERVER_ERROR, body),
+            "Automation error: element not found"
+        );
+        assert_eq!(
+            error_message(StatusCode::UNPROCESSABLE_ENTITY, b"missing field `x`"),
+            "missing field `x`"
+        );
+        assert_eq!(error_message(StatusCode::NOT_FOUND, b""), "404 Not Found");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short".to_string()), "short");
+
+        let long = "é".repeat(MAX_FIELD_LEN);
+        let truncated = truncate(long);
+        assert!(truncated.len() <= MAX_FIELD_LEN + '…'.len_utf8());
+        assert!(truncated.ends_with('…'));
+    }
+}
@@ -0,0 +1,592 @@
+//! Capture file storage
+//!
+//! Frame images and video chunks are written and read through a
+//! [`CaptureStore`]: the local captures directory, or a bucket of an
+//! S3-compatible service (AWS S3, MinIO) or an Azure Blob Storage container.
+//! A frame's `file_path` says where its file is, a local path or a
+//! `s3://bucket/key` or `az://container/key` URL, so captures written before
+//! the backend changed stay readable. Files read from a bucket are cached on
+//! local disk, up to a size limit, evicting the least recently used first.
+
+use anyhow::{bail, Context};
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+/// Future returned by [`CaptureStore`] methods
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = anyhow::Result<T>> + Send + 'a>>;
+
+/// A place capture files are stored
+pub trait CaptureStore: Send + Sync {
+    /// Store `data` as `name`, a `/`-separated relative path, returning the
+    /// path to record for it
+    fn put<'a>(&'a self, name: &'a str, data: Vec<u8>) -> StoreFuture<'a, String>;
+
+    /// Read the file at a path returned by [`put`](Self::put)
+    fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>>;
+
+    /// Delete the file at a path returned by [`put`](Self::put)
+    fn delete<'a>(&'a self, path: &'a str) -> StoreFuture<'a, ()>;
+
+    /// A local file with the content of `path`, for tools such as ffmpeg
+    fn local_file<'a>(&'a self, path: &'a str) -> StoreFuture<'a, PathBuf>;
+
+    /// Whether `path` belongs to this store
+    fn owns(&self, path: &str) -> bool;
+}
+
+/// Whether `path` is the URL of a file in a bucket rather than a local path
+pub fn is_remote_path(path: &str) -> bool {
+    path.contains("://")
+}
+
+// ============================================================
+// Configuration
+// ============================================================
+
+/// Capture storage configuration
+#[derive(Debug, Clone, Default)]
+pub struct CaptureStorageConfig {
+    /// Directory of local captures
+    pub captures_dir: PathBuf,
+    /// Bucket new captures are written to (None = `captures_dir`)
+    pub remote: Option<RemoteStorageConfig>,
+}
+
+/// Bucket or container holding captures
+#[derive(Debug, Clone)]
+pub struct RemoteStorageConfig {
+    pub backend: RemoteBackend,
+    /// Key prefix of the captures, without a trailing `/`
+    pub prefix: String,
+    /// Directory of the read cache
+    pub cache_dir: PathBuf,
+    /// Size of the read cache in bytes
+    pub cache_bytes: u64,
+}
+
+/// Object storage service
+#[derive(Debug, Clone)]
+pub enum RemoteBackend {
+    /// AWS S3 or an S3-compatible service such as MinIO
+    S3 {
+        bucket: String,
+        region: String,
+        /// Endpoint of a service other than AWS, e.g. `http://nas:9000`
+        endpoint: Option<String>,
+        /// Credentials (None = from the environment)
+        access_key_id: Option<String>,
+        secret_access_key: Option<String>,
+    },
+    /// Azure Blob Storage
+    Azure {
+        account: String,
+        container: String,
+        /// Account access key (None = from the environment)
+        access_key: Option<String>,
+    },
+}
+
+// ============================================================
+// Storage
+// ============================================================
+
+/// Capture files of the running profile
+///
+/// Writes go to the configured store; reads and deletes go to the store a
+/// path belongs to.
+pub struct CaptureStorage {
+    writer: Arc<dyn CaptureStore>,
+    stores: Vec<Arc<dyn CaptureStore>>,
+}
+
+impl CaptureStorage {
+    /// Open the stores described by `config`
+    pub fn new(config: &CaptureStorageConfig) -> anyhow::Result<Self> {
+        let local: Arc<dyn CaptureStore> = Arc::new(LocalStore::new(&config.captures_dir));
+        let Some(remote) = &config.remote else {
+            return Ok(Self {
+                writer: Arc::clone(&local),
+                stores: vec![local],
+            });
+        };
+
+        let remote: Arc<dyn CaptureStore> = Arc::new(RemoteStore::new(remote)?);
+        Ok(Self {
+            writer: Arc::clone(&remote),
+            stores: vec![remote, local],
+        })
+    }
+
+    /// Local storage only, in `captures_dir`
+    pub fn local(captures_dir: &Path) -> Self {
+        let local: Arc<dyn CaptureStore> = Arc::new(LocalStore::new(captures_dir));
+        Self {
+            writer: Arc::clone(&local),
+            stores: vec![local],
+        }
+    }
+
+    /// Whether new captures are written to a bucket
+    pub fn is_remote(&self) -> bool {
+        self.stores.len() > 1
+    }
+
+    /// Store `data` as `name`, returning the path to record for it
+    pub async fn put(&self, name: &str, data: Vec<u8>) -> anyhow::Result<String> {
+        self.writer.put(name, data).await
+    }
+
+    /// Store the local file `file` as `name`
+    pub async fn put_file(&self, name: &str, file: &Path) -> anyhow::Result<String> {
+        let data = tokio::fs::read(file)
+            .await
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        self.put(name, data).await
+    }
+
+    /// Read a stored file
+    pub async fn get(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+        self.store(path)?.get(path).await
+    }
+
+    /// Delete a stored file
+    pub async fn delete(&self, path: &str) -> anyhow::Result<()> {
+        self.store(path)?.delete(path).await
+    }
+
+    /// A local file with the content of a stored file
+    pub async fn local_file(&self, path: &str) -> anyhow::Result<PathBuf> {
+        self.store(path)?.local_file(path).await
+    }
+
+    fn store(&self, path: &str) -> anyhow::Result<&Arc<dyn CaptureStore>> {
+        match self.stores.iter().find(|store| store.owns(path)) {
+            Some(store) => Ok(store),
+            None => bail!("No configured storage holds {}", path),
+        }
+    }
+}
+
+impl Default for CaptureStorage {
+    /// Local storage that reads and deletes files by their full path
+    fn default() -> Self {
+        Self::local(Path::new(""))
+    }
+}
+
+// ============================================================
+// Local files
+// ============================================================
+
+/// Captures directory on a local or mapped drive
+pub struct LocalStore {
+    dir: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+}
+
+impl CaptureStore for LocalStore {
+    fn put<'a>(&'a self, name: &'a str, data: Vec<u8>) -> StoreFuture<'a, String> {
+        Box::pin(async move {
+            let path = self.dir.join(name);
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, data)
+                .await
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Ok(path.to_string_lossy().to_string())
+        })
+    }
+
+    fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            tokio::fs::read(path)
+                .await
+                .with_context(|| format!("Failed to read {}", path))
+        })
+    }
+
+    fn delete<'a>(&'a self, path: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            tokio::fs::remove_file(path)
+                .await
+                .with_context(|| format!("Failed to delete {}", path))
+        })
+    }
+
+    fn local_file<'a>(&'a self, path: &'a str) -> StoreFuture<'a, PathBuf> {
+        Box::pin(async move { Ok(PathBuf::from(path)) })
+    }
+
+    fn owns(&self, path: &str) -> bool {
+        !is_remote_path(path)
+    }
+}
+
+// ============================================================
+// Buckets
+// ============================================================
+
+/// Bucket of an S3-compatible service or Azure Blob Storage container
+pub struct RemoteStore {
+    store: Arc<dyn ObjectStore>,
+    /// `s3://bucket/` or `az://container/`, followed by the key in paths
+    url: String,
+    prefix: String,
+    cache: ReadCache,
+}
+
+impl RemoteStore {
+    pub fn new(config: &RemoteStorageConfig) -> anyhow::Result<Self> {
+        let (store, url): (Arc<dyn ObjectStore>, String) = match &config.backend {
+            RemoteBackend::S3 {
+                bucket,
+                region,
+                endpoint,
+                access_key_id,
+                secret_access_key,
+            } => {
+                let mut builder = object_store::aws::AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .with_region(region);
+                if let Some(endpoint) = endpoint {
+                    builder = builder
+                        .with_endpoint(endpoint)
+                        .with_allow_http(endpoint.starts_with("http://"));
+                }
+                if let (Some(id), Some(secret)) = (access_key_id, secret_access_key) {
+                    builder = builder
+                        .with_access_key_id(id)
+                        .with_secret_access_key(secret);
+                }
+                let store = builder
+                    .build()
+                    .with_context(|| format!("Invalid S3 storage for bucket {}", bucket))?;
+                (Arc::new(store), format!("s3://{}/", bucket))
+            }
+            RemoteBackend::Azure {
+                account,
+                container,
+                access_key,
+            } => {
+                let mut builder = object_store::azure::MicrosoftAzureBuilder::from_env()
+                    .with_account(account)
+                    .with_container_name(container);
+                if let Some(key) = access_key {
+                    builder = builder.with_access_key(key);
+                }
+                let store = builder.build().with_context(|| {
+                    format!("Invalid Azure storage for container {}", container)
+                })?;
+                (Arc::new(store), format!("az://{}/", container))
+            }
+        };
+
+        info!("Storing captures in {}{}", url, config.prefix);
+        Ok(Self {
+            store,
+            url,
+            prefix: config.prefix.trim_matches('/').to_string(),
+            cache: ReadCache::new(&config.cache_dir, config.cache_bytes),
+        })
+    }
+
+    fn key<'a>(&self, path: &'a str) -> anyhow::Result<&'a str> {
+        match path.strip_prefix(&self.url) {
+            Some(key) => Ok(key),
+            None => bail!("{} is not in {}", path, self.url),
+        }
+    }
+
+    /// Download `path` into the cache, returning its content
+    async fn fetch(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+        let key = ObjectPath::from(self.key(path)?);
+        let data = self
+            .store
+            .get(&key)
+            .await
+            .with_context(|| format!("Failed to download {}", path))?
+            .bytes()
+            .await
+            .with_context(|| format!("Failed to download {}", path))?
+            .to_vec();
+
+        if let Err(e) = self.cache.insert(path, &data).await {
+            warn!("Failed to cache {}: {}", path, e);
+        }
+        Ok(data)
+    }
+}
+
+impl CaptureStore for RemoteStore {
+    fn put<'a>(&'a self, name: &'a str, data: Vec<u8>) -> StoreFuture<'a, String> {
+        Box::pin(async move {
+            let key = match self.prefix.as_str() {
+                "" => name.to_string(),
+                prefix => format!("{}/{}", prefix, name),
+            };
+            self.store
+                .put(&ObjectPath::from(key.as_str()), PutPayload::from(data))
+                .await
+                .with_context(|| format!("Failed to upload {}{}", self.url, key))?;
+            Ok(format!("{}{}", self.url, key))
+        })
+    }
+
+    fn get<'a>(&'a self, path: &'a str) -> StoreFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            if let Some(cached) = self.cache.lookup(path) {
+                match tokio::fs::read(&cached).await {
+                    Ok(data) => return Ok(data),
+                    Err(e) => debug!("Cached copy of {} unreadable: {}", path, e),
+                }
+            }
+            self.fetch(path).await
+        })
+    }
+
+    fn delete<'a>(&'a self, path: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.cache.remove(path).await;
+            self.store
+                .delete(&ObjectPath::from(self.key(path)?))
+                .await
+                .with_context(|| format!("Failed to delete {}", path))
+        })
+    }
+
+    fn local_file<'a>(&'a self, path: &'a str) -> StoreFuture<'a, PathBuf> {
+        Box::pin(async move {
+            if let Some(cached) = self.cache.lookup(path) {
+                if tokio::fs::metadata(&cached).await.is_ok() {
+                    return Ok(cached);
+                }
+            }
+            self.fetch(path).await?;
+            Ok(self.cache.file_path(path))
+        })
+    }
+
+    fn owns(&self, path: &str) -> bool {
+        path.starts_with(&self.url)
+    }
+}
+
+// ============================================================
+// Read cache
+// ============================================================
+
+/// Downloaded files kept on local disk, least recently used evicted first
+struct ReadCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    index: Mutex<CacheIndex>,
+}
+
+/// Sizes and uses of the cached files
+#[derive(Debug, Default)]
+struct CacheIndex {
+    /// Size and last use of each file, by file name
+    entries: HashMap<String, (u64, u64)>,
+    total_bytes: u64,
+    clock: u64,
+}
+
+impl CacheIndex {
+    /// Record a use of `name`, `size` bytes long
+    fn touch(&mut self, name: &str, size: u64) {
+        self.clock += 1;
+        if let Some((old_size, _)) = self.entries.insert(name.to_string(), (size, self.clock)) {
+            self.total_bytes -= old_size;
+        }
+        self.total_bytes += size;
+    }
+
+    fn remove(&mut self, name: &str) -> bool {
+        match self.entries.remove(name) {
+            Some((size, _)) => {
+                self.total_bytes -= size;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop the least recently used files until at most `max_bytes` are
+    /// cached, keeping at least the most recent one; returns their names
+    fn evict(&mut self, max_bytes: u64) -> Vec<String> {
+        let mut evicted = Vec::new();
+        while self.total_bytes > max_bytes && self.entries.len() > 1 {
+            let Some(name) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(name, _)| name.clone())
+            else {
+                break;
+            };
+            self.remove(&name);
+            evicted.push(name);
+        }
+        evicted
+    }
+}
+
+impl ReadCache {
+    /// Open the cache in `dir`, indexing the files left by earlier runs
+    /// oldest first
+    fn new(dir: &Path, max_bytes: u64) -> Self {
+        let mut files: Vec<(std::time::SystemTime, String, u64)> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                Some((metadata.modified().ok()?, name, metadata.len()))
+            })
+            .collect();
+        files.sort();
+
+        let mut index = CacheIndex::default();
+        for (_, name, size) in &files {
+            index.touch(name, *size);
+        }
+
+        Self {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            index: Mutex::new(index),
+        }
+    }
+
+    /// Name of the cached copy of `path`, keeping its extension
+    fn file_name(path: &str) -> String {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        match Path::new(path).extension() {
+            Some(ext) => format!("{:016x}.{}", hasher.finish(), ext.to_string_lossy()),
+            None => format!("{:016x}", hasher.finish()),
+        }
+    }
+
+    fn file_path(&self, path: &str) -> PathBuf {
+        self.dir.join(Self::file_name(path))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheIndex> {
+        self.index.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Cached copy of `path`, marked as just used
+    fn lookup(&self, path: &str) -> Option<PathBuf> {
+        let name = Self::file_name(path);
+        let mut index = self.lock();
+        let size = index.entries.get(&name)?.0;
+        index.touch(&name, size);
+        Some(self.dir.join(name))
+    }
+
+    /// Cache `data` as the copy of `path`, evicting older files
+    async fn insert(&self, path: &str, data: &[u8]) -> std::io::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let name = Self::file_name(path);
+        tokio::fs::write(self.dir.join(&name), data).await?;
+
+        let evicted = {
+            let mut index = self.lock();
+            index.touch(&name, data.len() as u64);
+            index.evict(self.max_bytes)
+        };
+        for name in evicted {
+            let _ = tokio::fs::remove_file(self.dir.join(name)).await;
+        }
+        Ok(())
+    }
+
+    /// Drop the cached copy of `path`
+    async fn remove(&self, path: &str) {
+        let name = Self::file_name(path);
+        if self.lock().remove(&name) {
+            let _ = tokio::fs::remove_file(self.dir.join(name)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let mut index = CacheIndex::default();
+        index.touch("a", 40);
+        index.touch("b", 40);
+        index.touch("c", 40);
+        // Reading "a" again makes "b" the least recently used
+        index.touch("a", 40);
+
+        assert_eq!(index.evict(100), vec!["b".to_string()]);
+        assert_eq!(index.total_bytes, 80);
+        assert_eq!(index.evict(0), vec!["c".to_string()]);
+        assert!(index.entries.contains_key("a"));
+    }
+
+    #[tokio::test]
+    async fn test_storage_dispatches_by_path() {
+        let dir = std::env::temp_dir().join(format!("screensearch-store-{}", std::process::id()));
+        let storage = CaptureStorage::local(&dir);
+        assert!(!storage.is_remote());
+
+        let path = storage
+            .put("archive/chunk.mp4", b"chunk".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(PathBuf::from(&path), dir.join("archive").join("chunk.mp4"));
+        assert_eq!(storage.get(&path).await.unwrap(), b"chunk");
+        assert_eq!(
+            storage.local_file(&path).await.unwrap(),
+            PathBuf::from(&path)
+        );
+
+        storage.delete(&path).await.unwrap();
+        assert!(storage.get(&path).await.is_err());
+        assert!(storage.get("s3://bucket/frame.jpg").await.is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_read_cache() {
+        let dir = std::env::temp_dir().join(format!("screensearch-cache-{}", std::process::id()));
+        let cache = ReadCache::new(&dir, 10);
+
+        cache.insert("s3://b/frame_1.jpg", b"123456").await.unwrap();
+        cache.insert("s3://b/frame_2.jpg", b"123456").await.unwrap();
+        assert!(cache.lookup("s3://b/frame_1.jpg").is_none());
+        let cached = cache.lookup("s3://b/frame_2.jpg").unwrap();
+        assert_eq!(cached.extension().unwrap(), "jpg");
+        assert_eq!(std::fs::read(&cached).unwrap(), b"123456");
+
+        // Files left by an earlier run are indexed again
+        let reopened = ReadCache::new(&dir, 10);
+        assert!(reopened.lookup("s3://b/frame_2.jpg").is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
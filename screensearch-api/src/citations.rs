@@ -0,0 +1,139 @@
+//! Report citations
+//!
+//! Each piece of context sent with a report prompt is labelled `[S1]`,
+//! `[S2]`, ... and the model is asked to cite the labels behind its claims.
+//! The labels in the report are then turned into links to the cited frames,
+//! and the cited sources are returned with the report, so that every claim
+//! can be checked against the screen it came from.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use screensearch_db::{ActivitySummaryRecord, ContextChunk, FrameRecord};
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Matches a citation of one or more labels, e.g. `[S3]` or `[S1, S4]`
+static CITATION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(S\d+(?:\s*[,;]\s*S\d+)*)\]").unwrap());
+
+/// Added to the system prompt of reports with labelled context
+pub const CITATION_INSTRUCTIONS: &str =
+    "Each context entry starts with a source label such as [S1]. \
+After every statement based on the context, cite the labels it relies on in square brackets, \
+e.g. \"Reviewed the migration plan [S3][S7].\". Only cite labels that appear in the context.";
+
+/// A piece of report context the model can cite
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSource {
+    /// Label of the source in the prompt and report, e.g. "S3"
+    pub label: String,
+    pub frame_id: i64,
+    /// Embedding chunk of the frame, when only part of its text was used
+    pub chunk_index: Option<i32>,
+    pub timestamp: DateTime<Utc>,
+    pub app: Option<String>,
+    pub window: Option<String>,
+}
+
+impl ReportSource {
+    /// Source for the `index`th context entry, taken from `frame`
+    pub fn new(index: usize, frame: &FrameRecord, chunk_index: Option<i32>) -> Self {
+        Self {
+            label: format!("S{}", index + 1),
+            frame_id: frame.id,
+            chunk_index,
+            timestamp: frame.timestamp,
+            app: frame.active_process.clone(),
+            window: frame.active_window.clone(),
+        }
+    }
+
+    /// Source for the `index`th context entry, taken from the summary of an
+    /// hour and linked to the first frame of that hour
+    pub fn from_summary(index: usize, summary: &ActivitySummaryRecord) -> Self {
+        Self {
+            label: format!("S{}", index + 1),
+            frame_id: summary.frame_id,
+            chunk_index: None,
+            timestamp: summary.period_start,
+            app: None,
+            window: None,
+        }
+    }
+
+    /// Frame and chunk of the source as recorded in `/ai/history`
+    pub fn chunk(&self) -> ContextChunk {
+        ContextChunk {
+            frame_id: self.frame_id,
+            chunk_index: self.chunk_index,
+        }
+    }
+}
+
+/// Link the citations in `report` to their frames
+///
+/// Each cited label becomes a link to `/api/frames/{id}`. Returns the linked
+/// report and the cited sources in order of first citation. Citations with
+/// a label that does not name a source are left as written.
+pub fn link_citations(report: &str, sources: &[ReportSource]) -> (String, Vec<ReportSource>) {
+    let mut cited: Vec<ReportSource> = Vec::new();
+
+    let linked = CITATION_REGEX.replace_all(report, |caps: &regex::Captures| {
+        let labels: Vec<&str> = caps[1].split([',', ';']).map(str::trim).collect();
+        let known: Vec<&ReportSource> = labels
+            .iter()
+            .filter_map(|label| sources.iter().find(|source| source.label == *label))
+            .collect();
+        if known.len() != labels.len() {
+            return caps[0].to_string();
+        }
+
+        let links: Vec<String> = known
+            .into_iter()
+            .map(|source| {
+                if !cited.iter().any(|c| c.label == source.label) {
+                    cited.push(source.clone());
+                }
+                format!("[{}](/api/frames/{})", source.label, source.frame_id)
+            })
+            .collect();
+        links.join(", ")
+    });
+
+    (linked.into_owned(), cited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(index: usize, frame_id: i64) -> ReportSource {
+        ReportSource {
+            label: format!("S{}", index + 1),
+            frame_id,
+            chunk_index: Some(0),
+            timestamp: Utc::now(),
+            app: None,
+            window: None,
+        }
+    }
+
+    #[test]
+    fn test_link_citations() {
+        let sources = vec![source(0, 120), source(1, 87), source(2, 301)];
+        let report = "Fixed the parser [S2]. Reviewed the plan [S3, S1] and again [S2][S3]. \
+                      Guessed [S9]. Arrays [0] stay.";
+
+        let (linked, cited) = link_citations(report, &sources);
+
+        assert_eq!(
+            linked,
+            "Fixed the parser [S2](/api/frames/87). \
+             Reviewed the plan [S3](/api/frames/301), [S1](/api/frames/120) \
+             and again [S2](/api/frames/87)[S3](/api/frames/301). \
+             Guessed [S9]. Arrays [0] stay."
+        );
+        let labels: Vec<&str> = cited.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, vec!["S2", "S3", "S1"]);
+    }
+}
@@ -0,0 +1,189 @@
+//! Cold-tier archives of old frame images
+//!
+//! The archival worker moves the images of old frames into one `tar.zst`
+//! file per day, so the captures directory does not hold hundreds of
+//! thousands of loose files. Each image is compressed as its own zstd frame
+//! holding its tar entry: the file is an ordinary `tar.zst` that `zstd -d`
+//! and `tar` unpack, and a single image is read by decompressing only its
+//! frame, at the offset recorded for it in the database.
+
+use crate::capture_store::CaptureStorage;
+use anyhow::Context;
+use screensearch_db::ArchivedFrameRecord;
+use std::io::{Read, SeekFrom};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+/// Extension of archive files
+pub const ARCHIVE_EXTENSION: &str = ".tar.zst";
+
+/// Tar block size; entries and the end-of-archive marker are padded to it
+const BLOCK_SIZE: usize = 512;
+
+/// Whether a stored path is an archive rather than an image
+pub fn is_frame_archive(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(ARCHIVE_EXTENSION)
+}
+
+/// Writes an archive one image at a time
+pub struct ArchiveWriter {
+    file: tokio::fs::File,
+    level: i32,
+    written: u64,
+}
+
+impl ArchiveWriter {
+    /// Create an archive at `path`, compressed at zstd `level`
+    pub async fn create(path: &std::path::Path, level: i32) -> anyhow::Result<Self> {
+        let file = tokio::fs::File::create(path)
+            .await
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self {
+            file,
+            level,
+            written: 0,
+        })
+    }
+
+    /// Append an image as `name`, modified at `mtime` (Unix seconds)
+    ///
+    /// Returns the offset and length of its zstd frame in the archive.
+    pub async fn append(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        mtime: u64,
+    ) -> anyhow::Result<(u64, u64)> {
+        let entry = tar_entry(name, data, mtime)?;
+        let frame = zstd::bulk::compress(&entry, self.level)?;
+        self.write_frame(&frame).await
+    }
+
+    /// Write the end-of-archive marker and flush the file
+    ///
+    /// Returns the size of the archive.
+    pub async fn finish(mut self) -> anyhow::Result<u64> {
+        let marker = zstd::bulk::compress(&[0; 2 * BLOCK_SIZE], self.level)?;
+        self.write_frame(&marker).await?;
+        self.file.flush().await?;
+        self.file.sync_all().await?;
+        Ok(self.written)
+    }
+
+    async fn write_frame(&mut self, frame: &[u8]) -> anyhow::Result<(u64, u64)> {
+        self.file.write_all(frame).await?;
+        let offset = self.written;
+        self.written += frame.len() as u64;
+        Ok((offset, frame.len() as u64))
+    }
+}
+
+/// Read the image of an archived frame
+pub async fn read_archived_image(
+    storage: &CaptureStorage,
+    entry: &ArchivedFrameRecord,
+) -> anyhow::Result<Vec<u8>> {
+    let path = storage.local_file(&entry.file_path).await?;
+    let mut file = tokio::fs::File::open(&path)
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.seek(SeekFrom::Start(entry.entry_offset.max(0) as u64))
+        .await?;
+    let mut frame = vec![0; entry.entry_length.max(0) as usize];
+    file.read_exact(&mut frame).await.with_context(|| {
+        format!(
+            "Frame {} is truncated in {}",
+            entry.frame_id, entry.file_path
+        )
+    })?;
+
+    read_entry(&frame).with_context(|| {
+        format!(
+            "Failed to read frame {} from {}",
+            entry.frame_id, entry.file_path
+        )
+    })
+}
+
+/// A tar entry (header, data and padding) for a regular file
+fn tar_entry(name: &str, data: &[u8], mtime: u64) -> anyhow::Result<Vec<u8>> {
+    let mut header = tar::Header::new_ustar();
+    header.set_path(name)?;
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_cksum();
+
+    let padded = data.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+    let mut entry = Vec::with_capacity(BLOCK_SIZE + padded);
+    entry.extend_from_slice(header.as_bytes());
+    entry.extend_from_slice(data);
+    entry.resize(BLOCK_SIZE + padded, 0);
+    Ok(entry)
+}
+
+/// The file data of the tar entry in a zstd frame
+fn read_entry(frame: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let entry = zstd::stream::decode_all(frame)?;
+    let mut archive = tar::Archive::new(entry.as_slice());
+    let mut file = archive
+        .entries()?
+        .next()
+        .context("The archive entry is empty")??;
+
+    let mut data = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_archive_round_trip() {
+        let dir = std::env::temp_dir().join(format!("screensearch-archive-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("day{}", ARCHIVE_EXTENSION));
+
+        let images: [(&str, Vec<u8>); 3] = [
+            ("frame_0_a.jpg", vec![0xFF, 0xD8, 1, 2, 3]),
+            ("frame_0_b.jpg", vec![7; BLOCK_SIZE]),
+            ("frame_1_c.png", Vec::new()),
+        ];
+        let mut writer = ArchiveWriter::create(&path, 3).await.unwrap();
+        let mut entries = Vec::new();
+        for (i, (name, data)) in images.iter().enumerate() {
+            let (offset, length) = writer.append(name, data, 1_700_000_000).await.unwrap();
+            entries.push(ArchivedFrameRecord {
+                frame_id: i as i64,
+                archive_id: 1,
+                file_path: path.to_string_lossy().to_string(),
+                entry_offset: offset as i64,
+                entry_length: length as i64,
+            });
+        }
+        let size = writer.finish().await.unwrap();
+        assert_eq!(size, std::fs::metadata(&path).unwrap().len());
+
+        // Each image on its own
+        let storage = CaptureStorage::default();
+        for (entry, (_, data)) in entries.iter().zip(&images).rev() {
+            assert_eq!(&read_archived_image(&storage, entry).await.unwrap(), data);
+        }
+
+        // The whole file is a regular tar.zst
+        let tar = zstd::stream::decode_all(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut archive = tar::Archive::new(tar.as_slice());
+        let names: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["frame_0_a.jpg", "frame_0_b.jpg", "frame_1_c.png"]);
+
+        assert!(is_frame_archive(&path.to_string_lossy()));
+        assert!(!is_frame_archive("captures/frame_0_a.jpg"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
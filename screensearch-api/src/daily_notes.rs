@@ -0,0 +1,462 @@
+//! Markdown daily notes
+//!
+//! Renders one Markdown note per day for a notes app such as Obsidian: the
+//! hourly activity summaries, the apps used longest, the sessions spent in
+//! each app, a few screenshots and the daily report when one was generated.
+//! The generated part of a note sits between two comment markers, so text
+//! written around it in the notes app is kept when the day is exported again.
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use screensearch_db::{
+    ActivitySummaryRecord, DatabaseManager, FrameFilter, FrameRecord, Pagination,
+};
+use std::collections::HashMap;
+use std::fmt::{Display, Write as _};
+
+/// Marker before the generated part of a note
+pub const NOTE_START: &str = "<!-- screensearch:start -->";
+/// Marker after the generated part of a note
+pub const NOTE_END: &str = "<!-- screensearch:end -->";
+
+/// Gap between frames after which the user is considered away
+const IDLE_GAP_MINUTES: i64 = 5;
+/// Sessions shorter than this only count towards the app times
+const MIN_SESSION_MINUTES: i64 = 2;
+/// Apps listed in a note
+const TOP_APPS: usize = 10;
+/// Frames read per day by [`load_sessions`]
+const MAX_DAY_FRAMES: i64 = 50_000;
+
+/// Time spent in one app without a break
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub app: String,
+    /// Window title seen most often
+    pub window: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Frame in the middle of the session, shown as its screenshot
+    pub frame_id: i64,
+}
+
+impl Session {
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+}
+
+/// Screenshot embedded in a note
+#[derive(Debug, Clone)]
+pub struct Screenshot {
+    pub timestamp: DateTime<Utc>,
+    pub app: String,
+    /// Link to the image, relative to the note
+    pub link: String,
+}
+
+/// Activity of one day
+#[derive(Debug, Clone)]
+pub struct DailyNote {
+    pub date: NaiveDate,
+    pub frame_count: i64,
+    pub summaries: Vec<ActivitySummaryRecord>,
+    pub sessions: Vec<Session>,
+    pub screenshots: Vec<Screenshot>,
+    /// Content of the daily report
+    pub report: Option<String>,
+}
+
+/// Split frames, ordered by capture time, into sessions
+///
+/// Frames of unfocused monitors and without an app are left out. A session
+/// ends when the app changes or after a gap of more than a few minutes; the
+/// time until the next frame counts towards it unless the user was away.
+pub fn sessions(frames: &[FrameRecord]) -> Vec<Session> {
+    let idle = Duration::minutes(IDLE_GAP_MINUTES);
+    let frames: Vec<(&FrameRecord, &str)> = frames
+        .iter()
+        .filter(|frame| frame.focused != Some(false))
+        .filter_map(|frame| Some((frame, frame.active_process.as_deref()?)))
+        .collect();
+
+    let mut sessions = Vec::new();
+    let mut start = 0;
+    for i in 0..frames.len() {
+        let (frame, app) = frames[i];
+        let next = frames
+            .get(i + 1)
+            .filter(|(next, _)| next.timestamp - frame.timestamp <= idle);
+        if next.is_some_and(|(_, next_app)| *next_app == app) {
+            continue;
+        }
+
+        let run = &frames[start..=i];
+        let mut windows: HashMap<&str, usize> = HashMap::new();
+        for (frame, _) in run {
+            if let Some(window) = frame.active_window.as_deref() {
+                *windows.entry(window).or_default() += 1;
+            }
+        }
+        let window = windows
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(window, _)| window.to_string());
+
+        sessions.push(Session {
+            app: app.to_string(),
+            window,
+            start: run[0].0.timestamp,
+            end: next.map_or(frame.timestamp, |(next, _)| next.timestamp),
+            frame_id: run[run.len() / 2].0.id,
+        });
+        start = i + 1;
+    }
+
+    sessions
+}
+
+/// Sessions between `start` and `end`, reading a day of frames at a time
+///
+/// A session running over the end of a day is split there.
+pub async fn load_sessions(
+    db: &DatabaseManager,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> screensearch_db::Result<Vec<Session>> {
+    let mut sessions = Vec::new();
+    let mut day_start = start;
+    while day_start < end {
+        let day_end = (day_start + Duration::days(1)).min(end);
+        let last = day_end - Duration::milliseconds(1);
+        let filter = FrameFilter {
+            start_time: Some(day_start),
+            end_time: Some(last),
+            app_name: None,
+            device_name: None,
+            tag_ids: None,
+            monitor_index: None,
+            exclude_apps: None,
+            exclude_tags: None,
+        };
+        let pagination = Pagination {
+            limit: MAX_DAY_FRAMES,
+            offset: 0,
+        };
+        let mut frames = db
+            .get_frames_in_range(day_start, last, filter, pagination)
+            .await?;
+        frames.reverse();
+        sessions.extend(self::sessions(&frames));
+        day_start = day_end;
+    }
+
+    Ok(sessions)
+}
+
+/// Total time per app, longest first
+pub fn app_times(sessions: &[Session]) -> Vec<(String, Duration)> {
+    let mut times: HashMap<&str, Duration> = HashMap::new();
+    for session in sessions {
+        *times.entry(&session.app).or_insert_with(Duration::zero) += session.duration();
+    }
+
+    let mut times: Vec<(String, Duration)> = times
+        .into_iter()
+        .map(|(app, time)| (app.to_string(), time))
+        .collect();
+    times.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    times
+}
+
+/// The `count` longest sessions, in time order
+pub fn key_sessions(sessions: &[Session], count: usize) -> Vec<&Session> {
+    let mut longest: Vec<&Session> = sessions
+        .iter()
+        .filter(|session| session.duration() > Duration::zero())
+        .collect();
+    longest.sort_by_key(|session| std::cmp::Reverse(session.duration()));
+    longest.truncate(count);
+    longest.sort_by_key(|session| session.start);
+    longest
+}
+
+/// Render the generated part of a note, with its markers, times shown in `tz`
+pub fn render_note<Tz>(note: &DailyNote, tz: &Tz) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    let time = |timestamp: &DateTime<Utc>| timestamp.with_timezone(tz).format("%H:%M").to_string();
+    let active = note
+        .sessions
+        .iter()
+        .fold(Duration::zero(), |total, session| {
+            total + session.duration()
+        });
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", NOTE_START);
+    let _ = writeln!(out, "# {}", note.date.format("%A, %-d %B %Y"));
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "{} frames · {} active",
+        note.frame_count,
+        format_duration(active)
+    );
+
+    if !note.summaries.is_empty() {
+        let _ = writeln!(out, "\n## Summary\n");
+        for summary in &note.summaries {
+            let _ = writeln!(
+                out,
+                "- **{}** {}",
+                time(&summary.period_start),
+                escape_markdown(&summary.summary.replace('\n', " "))
+            );
+        }
+    }
+
+    let apps = app_times(&note.sessions);
+    if !apps.is_empty() {
+        let _ = writeln!(out, "\n## Top Apps\n");
+        let _ = writeln!(out, "| App | Time |");
+        let _ = writeln!(out, "|-----|------|");
+        for (app, duration) in apps.iter().take(TOP_APPS) {
+            let _ = writeln!(
+                out,
+                "| {} | {} |",
+                escape_markdown(app),
+                format_duration(*duration)
+            );
+        }
+    }
+
+    let min_session = Duration::minutes(MIN_SESSION_MINUTES);
+    let sessions: Vec<&Session> = note
+        .sessions
+        .iter()
+        .filter(|session| session.duration() >= min_session)
+        .collect();
+    if !sessions.is_empty() {
+        let _ = writeln!(out, "\n## Sessions\n");
+        for session in sessions {
+            let _ = write!(
+                out,
+                "- {}–{} **{}**",
+                time(&session.start),
+                time(&session.end),
+                escape_markdown(&session.app)
+            );
+            if let Some(window) = &session.window {
+                let _ = write!(out, " · {}", escape_markdown(window));
+            }
+            let _ = writeln!(out, " ({})", format_duration(session.duration()));
+        }
+    }
+
+    if !note.screenshots.is_empty() {
+        let _ = writeln!(out, "\n## Screenshots\n");
+        for screenshot in &note.screenshots {
+            let _ = writeln!(
+                out,
+                "![{} {}]({})\n",
+                time(&screenshot.timestamp),
+                escape_markdown(&screenshot.app),
+                screenshot.link
+            );
+        }
+    }
+
+    if let Some(report) = &note.report {
+        let _ = writeln!(out, "\n## Daily Report\n");
+        let _ = writeln!(out, "{}", demote_headings(report.trim()));
+    }
+
+    out.truncate(out.trim_end().len());
+    let _ = write!(out, "\n{}", NOTE_END);
+    out
+}
+
+/// Replace the generated part of an existing note with `generated`
+///
+/// Notes without markers, written by hand, get it appended.
+pub fn merge_note(existing: Option<&str>, generated: &str) -> String {
+    let Some(existing) = existing else {
+        return format!("{}\n", generated);
+    };
+
+    if let Some(start) = existing.find(NOTE_START) {
+        if let Some(end) = existing[start..].find(NOTE_END) {
+            let end = start + end + NOTE_END.len();
+            return format!("{}{}{}", &existing[..start], generated, &existing[end..]);
+        }
+    }
+
+    if existing.trim().is_empty() {
+        format!("{}\n", generated)
+    } else {
+        format!("{}\n\n{}\n", existing.trim_end(), generated)
+    }
+}
+
+/// Duration as `1h 05m` or `12m`
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Escape captured text so it shows as written, without creating links,
+/// tags or table cells in the note
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Move the headings of a report two levels down, below the note's own
+fn demote_headings(markdown: &str) -> String {
+    let mut in_code = false;
+    markdown
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_code = !in_code;
+            }
+            if !in_code && line.starts_with('#') {
+                format!("##{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: i64, minute: i64, app: Option<&str>, window: &str) -> FrameRecord {
+        let timestamp = DateTime::parse_from_rfc3339("2025-12-10T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + Duration::minutes(minute);
+        FrameRecord {
+            id,
+            chunk_id: None,
+            timestamp,
+            monitor_index: 0,
+            device_name: "monitor-0".to_string(),
+            file_path: format!("captures/frame_{}.jpg", id),
+            active_window: Some(window.to_string()),
+            active_process: app.map(str::to_string),
+            browser_url: None,
+            width: 1920,
+            height: 1080,
+            offset_index: 0,
+            focused: None,
+            pinned: false,
+            created_at: timestamp,
+        }
+    }
+
+    #[test]
+    fn test_sessions() {
+        let mut unfocused = frame(4, 11, Some("slack.exe"), "general");
+        unfocused.focused = Some(false);
+        let frames = vec![
+            frame(1, 0, Some("code.exe"), "main.rs"),
+            frame(2, 5, Some("code.exe"), "lib.rs"),
+            frame(3, 10, Some("code.exe"), "lib.rs"),
+            unfocused,
+            frame(5, 12, Some("firefox.exe"), "Docs"),
+            frame(6, 14, None, "Desktop"),
+            // Away for an hour
+            frame(7, 74, Some("firefox.exe"), "Docs"),
+        ];
+
+        let sessions = sessions(&frames);
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[0].app, "code.exe");
+        assert_eq!(sessions[0].window.as_deref(), Some("lib.rs"));
+        assert_eq!(sessions[0].duration(), Duration::minutes(12));
+        assert_eq!(sessions[0].frame_id, 2);
+        assert_eq!(sessions[1].app, "firefox.exe");
+        assert_eq!(sessions[1].duration(), Duration::zero());
+        assert_eq!(sessions[2].start, frames[6].timestamp);
+
+        let times = app_times(&sessions);
+        assert_eq!(times[0], ("code.exe".to_string(), Duration::minutes(12)));
+        assert_eq!(key_sessions(&sessions, 5).len(), 1);
+    }
+
+    #[test]
+    fn test_render_note() {
+        let frames = vec![
+            frame(1, 0, Some("code.exe"), "main.rs"),
+            frame(2, 4, Some("code.exe"), "main.rs"),
+            frame(3, 8, Some("firefox.exe"), "#1 [bug] | notes"),
+            frame(4, 11, Some("firefox.exe"), "#1 [bug] | notes"),
+        ];
+        let note = DailyNote {
+            date: NaiveDate::from_ymd_opt(2025, 12, 10).unwrap(),
+            frame_count: 4,
+            summaries: Vec::new(),
+            sessions: sessions(&frames),
+            screenshots: vec![Screenshot {
+                timestamp: frames[1].timestamp,
+                app: "code.exe".to_string(),
+                link: "attachments/2025-12-10_2.jpg".to_string(),
+            }],
+            report: Some("# Daily Report\n\nShipped the parser.".to_string()),
+        };
+
+        let rendered = render_note(&note, &Utc);
+        assert!(rendered.starts_with(NOTE_START));
+        assert!(rendered.ends_with(NOTE_END));
+        assert!(rendered.contains("# Wednesday, 10 December 2025"));
+        assert!(rendered.contains("4 frames · 11m active"));
+        assert!(rendered.contains("| code.exe | 8m |"));
+        assert!(rendered.contains("- 09:08–09:11 **firefox.exe** · \\#1 \\[bug\\] \\| notes (3m)"));
+        assert!(rendered.contains("![09:04 code.exe](attachments/2025-12-10_2.jpg)"));
+        assert!(rendered.contains("### Daily Report\n\nShipped the parser."));
+        assert!(!rendered.contains("## Summary"));
+    }
+
+    #[test]
+    fn test_merge_note() {
+        let generated = format!("{}\nnew\n{}", NOTE_START, NOTE_END);
+
+        assert_eq!(merge_note(None, &generated), format!("{}\n", generated));
+        assert_eq!(
+            merge_note(Some("My plans\n"), &generated),
+            format!("My plans\n\n{}\n", generated)
+        );
+
+        let existing = format!("Before\n{}\nold\n{}\nAfter\n", NOTE_START, NOTE_END);
+        assert_eq!(
+            merge_note(Some(&existing), &generated),
+            format!("Before\n{}\nAfter\n", generated)
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::seconds(59)), "0m");
+        assert_eq!(format_duration(Duration::minutes(45)), "45m");
+        assert_eq!(format_duration(Duration::minutes(125)), "2h 05m");
+    }
+}
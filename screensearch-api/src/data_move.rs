@@ -0,0 +1,477 @@
+//! Data directory migration
+//!
+//! Moves everything in the data directory (the database and captures of
+//! every profile, logs and downloaded updates) to another directory:
+//!
+//! 1. every file is copied, reporting progress,
+//! 2. the image paths stored in each database copy are rewritten to point
+//!    into the new directory,
+//! 3. each copy must pass SQLite's integrity check and keep no path that
+//!    resolves into the old directory, such as a path relative to the
+//!    working directory or through a symbolic link,
+//! 4. `storage.data_dir` in `config.toml` is replaced in one step.
+//!
+//! The old directory is left untouched until the switch, so a failure at any
+//! step keeps the application on its old data. The data must not be in use
+//! while it moves: `screensearch move-data` refuses to run next to the
+//! application, and `/admin/move-data` has the application move its data
+//! while it restarts, after capture has stopped and queued frames are saved.
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::Notify;
+
+/// Metadata key of the last [`MoveReport`], stored in the moved database
+pub const REPORT_KEY: &str = "last_data_move";
+
+/// Move requested through `/admin/move-data`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MoveRequest {
+    /// New data directory
+    pub to: PathBuf,
+
+    /// Delete the old data directory once the move succeeded
+    pub remove_old: bool,
+}
+
+/// Outcome of a completed move
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveReport {
+    pub from: String,
+    pub to: String,
+    pub files: u64,
+    pub bytes: u64,
+    /// Databases whose paths were rewritten, one per profile
+    pub databases: usize,
+    /// Frame and video chunk paths rewritten
+    pub paths_rewritten: u64,
+    pub moved_at: DateTime<Utc>,
+}
+
+/// Copy progress
+#[derive(Debug, Clone, Default)]
+pub struct MoveProgress {
+    pub files_copied: u64,
+    pub files_total: u64,
+    pub bytes_copied: u64,
+    pub bytes_total: u64,
+}
+
+/// Move requests shared between the application and `/admin/move-data`
+#[derive(Debug, Default)]
+pub struct DataMoveControl {
+    /// Data directory that can be moved, set by the full application
+    source: OnceLock<PathBuf>,
+    requested: Mutex<Option<MoveRequest>>,
+    pending: Mutex<Option<MoveRequest>>,
+    notify: Notify,
+}
+
+impl DataMoveControl {
+    /// Allow moving `data_dir` through `/admin/move-data`
+    pub fn set_source(&self, data_dir: PathBuf) {
+        let _ = self.source.set(data_dir);
+    }
+
+    /// Data directory that can be moved, when known
+    pub fn source(&self) -> Option<&Path> {
+        self.source.get().map(PathBuf::as_path)
+    }
+
+    /// Ask the application to restart and move its data
+    pub fn request_move(&self, request: MoveRequest) {
+        *self.pending.lock().unwrap_or_else(|e| e.into_inner()) = Some(request.clone());
+        *self.requested.lock().unwrap_or_else(|e| e.into_inner()) = Some(request);
+        self.notify.notify_one();
+    }
+
+    /// Move requested and not yet carried out
+    pub fn pending(&self) -> Option<MoveRequest> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Wait for the next move request
+    pub async fn move_requested(&self) -> MoveRequest {
+        loop {
+            self.notify.notified().await;
+            if let Some(request) = self
+                .requested
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .take()
+            {
+                return request;
+            }
+        }
+    }
+}
+
+/// Why the data in `from` cannot move to `to`, if it cannot
+///
+/// `to` must be an absolute path outside `from`, and either not exist yet or
+/// be an empty directory.
+pub fn check_target(from: &Path, to: &Path) -> Result<(), String> {
+    if !to.is_absolute() {
+        return Err("The new data directory must be an absolute path".to_string());
+    }
+    if !from.is_dir() {
+        return Err(format!("{} is not a directory", from.display()));
+    }
+    let from_abs = std::path::absolute(from).unwrap_or_else(|_| from.to_path_buf());
+    if to.starts_with(&from_abs) || from_abs.starts_with(to) {
+        return Err(format!(
+            "{} and {} must not contain each other",
+            from_abs.display(),
+            to.display()
+        ));
+    }
+    if to.exists() {
+        let empty = std::fs::read_dir(to)
+            .map_err(|e| format!("Cannot read {}: {}", to.display(), e))?
+            .next()
+            .is_none();
+        if !empty {
+            return Err(format!("{} is not an empty directory", to.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Move the data directory `from` to `to`
+///
+/// `database_file` is the database's file name within each profile's
+/// directory, and `config_path` the configuration file pointed at the new
+/// directory. `progress` is called after each copied file.
+pub async fn move_data(
+    from: &Path,
+    to: &Path,
+    database_file: &str,
+    config_path: &Path,
+    mut progress: impl FnMut(&MoveProgress),
+) -> anyhow::Result<MoveReport> {
+    check_target(from, to).map_err(anyhow::Error::msg)?;
+    if !config_path.is_file() {
+        bail!(
+            "{} not found; the new data directory is recorded there",
+            config_path.display()
+        );
+    }
+
+    // Copy every file; the databases are checked after the copy, while the
+    // log file may still grow with the messages of the move itself
+    let files = list_files(from)?;
+    let mut state = MoveProgress {
+        files_total: files.len() as u64,
+        bytes_total: files.iter().map(|(_, len)| len).sum(),
+        ..Default::default()
+    };
+    for (file, len) in &files {
+        let target = to.join(file);
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        // A database's shared-memory file may go away once it is closed
+        match tokio::fs::copy(from.join(file), &target).await {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            copied => {
+                copied.with_context(|| format!("Failed to copy {}", from.join(file).display()))?;
+            }
+        }
+        state.files_copied += 1;
+        state.bytes_copied += len;
+        progress(&state);
+    }
+
+    // Point the copied paths at the new directory and check each database
+    let (old_prefix, new_prefix) = (dir_prefix(from), dir_prefix(to));
+    let databases = databases(to, database_file);
+    let mut report = MoveReport {
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+        files: state.files_copied,
+        bytes: state.bytes_copied,
+        databases: databases.len(),
+        paths_rewritten: 0,
+        moved_at: Utc::now(),
+    };
+    for path in &databases {
+        let db = open_database(path).await?;
+        let verified = verify_database(&db, from, &old_prefix, &new_prefix).await;
+        db.close().await;
+        report.paths_rewritten +=
+            verified.with_context(|| format!("Failed to verify {}", path.display()))?;
+    }
+
+    // Each profile reports the move through its own database
+    let json = serde_json::to_string(&report)?;
+    for path in &databases {
+        let db = open_database(path).await?;
+        let saved = db.set_metadata(REPORT_KEY, &json).await;
+        db.close().await;
+        saved?;
+    }
+
+    crate::setup::write_data_dir(config_path, &report.to)?;
+    Ok(report)
+}
+
+async fn open_database(path: &Path) -> anyhow::Result<screensearch_db::DatabaseManager> {
+    screensearch_db::DatabaseManager::new(path.to_string_lossy())
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+/// Rewrite the paths in a database copy and check it
+///
+/// Fails if a frame path still resolves into `from`, whether it was stored
+/// relative to the working directory or through another spelling of the
+/// directory. Returns the number of paths rewritten.
+async fn verify_database(
+    db: &screensearch_db::DatabaseManager,
+    from: &Path,
+    old_prefix: &str,
+    new_prefix: &str,
+) -> anyhow::Result<u64> {
+    let rewritten = db.rewrite_file_paths(old_prefix, new_prefix).await?;
+
+    let problems = db.integrity_check().await?;
+    if !problems.is_empty() {
+        bail!("Integrity check failed: {}", problems.join("; "));
+    }
+    let from_resolved = resolve(from);
+    let stale = db
+        .get_frame_file_paths()
+        .await?
+        .iter()
+        .filter(|path| {
+            let resolved = resolve(Path::new(path));
+            resolved.starts_with(from) || resolved.starts_with(&from_resolved)
+        })
+        .count();
+    if stale > 0 {
+        bail!(
+            "{} frames still point into the old directory; their paths are not \
+             stored below {}",
+            stale,
+            old_prefix
+        );
+    }
+    Ok(rewritten)
+}
+
+/// `path` with symbolic links resolved, or made absolute if it does not exist
+fn resolve(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Delete the old data directory after a move
+pub fn remove_old(from: &Path) -> anyhow::Result<()> {
+    std::fs::remove_dir_all(from).with_context(|| format!("Failed to delete {}", from.display()))
+}
+
+/// Files below `dir` with their sizes, relative to `dir`
+fn list_files(dir: &Path) -> anyhow::Result<Vec<(PathBuf, u64)>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative) = dirs.pop() {
+        let path = dir.join(&relative);
+        let entries = std::fs::read_dir(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let relative = relative.join(entry.file_name());
+            if metadata.is_dir() {
+                dirs.push(relative);
+            } else {
+                files.push((relative, metadata.len()));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Database files of the default profile and the other profiles in `dir`
+fn databases(dir: &Path, database_file: &str) -> Vec<PathBuf> {
+    let profiles = std::fs::read_dir(dir.join("profiles"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path().join(database_file));
+    std::iter::once(dir.join(database_file))
+        .chain(profiles)
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// `dir` as a path prefix ending in a separator, so `/data/ss` does not
+/// match `/data/ss2`
+fn dir_prefix(dir: &Path) -> String {
+    let mut prefix = dir.to_string_lossy().to_string();
+    if !prefix.ends_with(MAIN_SEPARATOR) && !prefix.ends_with('/') {
+        prefix.push(MAIN_SEPARATOR);
+    }
+    prefix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("screensearch-move-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_check_target() {
+        let from = temp_dir("check");
+        std::fs::create_dir_all(from.join("captures")).unwrap();
+        let to = temp_dir("check-target");
+
+        assert!(check_target(&from, &to).is_ok());
+        assert!(check_target(&from, Path::new("relative")).is_err());
+        assert!(check_target(&from, &from.join("captures")).is_err());
+        assert!(check_target(&from.join("captures"), &from).is_err());
+
+        std::fs::create_dir_all(&to).unwrap();
+        assert!(check_target(&from, &to).is_ok());
+        std::fs::write(to.join("file"), "").unwrap();
+        assert!(check_target(&from, &to).is_err());
+
+        let _ = std::fs::remove_dir_all(&from);
+        let _ = std::fs::remove_dir_all(&to);
+    }
+
+    #[tokio::test]
+    async fn test_move_data_rewrites_paths_and_config() {
+        let from = temp_dir("data");
+        let to = temp_dir("data-target");
+        let config = temp_dir("config").with_extension("toml");
+        std::fs::create_dir_all(from.join("captures")).unwrap();
+        std::fs::write(from.join("captures").join("frame.jpg"), "jpeg").unwrap();
+        std::fs::write(&config, "[storage]\ndata_dir = \"\"\n").unwrap();
+
+        let db = screensearch_db::DatabaseManager::new(from.join("test.db").to_string_lossy())
+            .await
+            .unwrap();
+        db.insert_frame(screensearch_db::NewFrame {
+            chunk_id: None,
+            timestamp: Utc::now(),
+            monitor_index: 0,
+            device_name: "monitor-0".to_string(),
+            file_path: from
+                .join("captures")
+                .join("frame.jpg")
+                .to_string_lossy()
+                .to_string(),
+            active_window: None,
+            active_process: None,
+            browser_url: None,
+            width: 1,
+            height: 1,
+            offset_index: 0,
+            focused: None,
+        })
+        .await
+        .unwrap();
+        db.close().await;
+
+        let mut updates = 0;
+        let report = move_data(&from, &to, "test.db", &config, |_| updates += 1)
+            .await
+            .unwrap();
+        assert_eq!(updates, report.files);
+        assert_eq!(report.databases, 1);
+        assert_eq!(report.paths_rewritten, 1);
+
+        let moved = screensearch_db::DatabaseManager::new(to.join("test.db").to_string_lossy())
+            .await
+            .unwrap();
+        let paths = moved.get_frame_file_paths().await.unwrap();
+        assert_eq!(
+            paths,
+            [to.join("captures").join("frame.jpg").to_string_lossy()]
+        );
+        assert!(moved.get_metadata(REPORT_KEY).await.unwrap().is_some());
+        moved.close().await;
+
+        let doc: toml_edit::DocumentMut =
+            std::fs::read_to_string(&config).unwrap().parse().unwrap();
+        assert_eq!(
+            doc["storage"]["data_dir"].as_str(),
+            Some(to.to_string_lossy().as_ref())
+        );
+
+        for path in [&from, &to] {
+            let _ = std::fs::remove_dir_all(path);
+        }
+        let _ = std::fs::remove_file(&config);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_move_data_refuses_paths_resolving_into_old_directory() {
+        let from = temp_dir("linked");
+        let to = temp_dir("linked-target");
+        let link = temp_dir("linked-alias");
+        let config = temp_dir("linked-config").with_extension("toml");
+        std::fs::create_dir_all(from.join("captures")).unwrap();
+        std::fs::write(from.join("captures").join("frame.jpg"), "jpeg").unwrap();
+        std::os::unix::fs::symlink(&from, &link).unwrap();
+        std::fs::write(&config, "[storage]\ndata_dir = \"\"\n").unwrap();
+
+        // Stored through another spelling of the data directory, so the
+        // rewrite does not match it
+        let db = screensearch_db::DatabaseManager::new(from.join("test.db").to_string_lossy())
+            .await
+            .unwrap();
+        db.insert_frame(screensearch_db::NewFrame {
+            chunk_id: None,
+            timestamp: Utc::now(),
+            monitor_index: 0,
+            device_name: "monitor-0".to_string(),
+            file_path: link
+                .join("captures")
+                .join("frame.jpg")
+                .to_string_lossy()
+                .to_string(),
+            active_window: None,
+            active_process: None,
+            browser_url: None,
+            width: 1,
+            height: 1,
+            offset_index: 0,
+            focused: None,
+        })
+        .await
+        .unwrap();
+        db.close().await;
+
+        let result = move_data(&from, &to, "test.db", &config, |_| {}).await;
+        assert!(result.is_err());
+        assert_eq!(
+            std::fs::read_to_string(&config).unwrap(),
+            "[storage]\ndata_dir = \"\"\n"
+        );
+        assert!(from.join("captures").join("frame.jpg").is_file());
+
+        for path in [&from, &to] {
+            let _ = std::fs::remove_dir_all(path);
+        }
+        let _ = std::fs::remove_file(&link);
+        let _ = std::fs::remove_file(&config);
+    }
+}
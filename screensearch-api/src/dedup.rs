@@ -0,0 +1,171 @@
+//! Search result deduplication
+//!
+//! A search for a common UI string matches every frame of a screen that did
+//! not change for minutes. Results whose frames come from the same app within
+//! a few seconds of each other and show nearly the same text are collapsed
+//! into the most relevant one, which counts the others in `duplicates_count`.
+
+use screensearch_db::SearchResult;
+use std::collections::HashSet;
+
+/// Default gap between frames of one group of duplicates, in seconds
+pub const DEFAULT_DEDUP_WINDOW_SECS: i64 = 60;
+
+/// Share of words two texts must have in common to be duplicates
+const MIN_TEXT_SIMILARITY: f64 = 0.9;
+
+/// A kept result and what it stands for
+struct Group {
+    index: usize,
+    words: HashSet<String>,
+    timestamps: Vec<i64>,
+}
+
+/// Collapse near-identical results
+///
+/// Results are taken in order, so the first (most relevant) result of each
+/// group is kept. A result joins a group when it has the same app, was
+/// captured within `window_secs` of one of the group's frames, and its text
+/// shares at least 90% of its words with the kept result.
+pub fn collapse_duplicates(results: Vec<SearchResult>, window_secs: i64) -> Vec<SearchResult> {
+    let mut kept: Vec<SearchResult> = Vec::with_capacity(results.len());
+    let mut groups: Vec<Group> = Vec::new();
+
+    for result in results {
+        let words = word_set(&result);
+        let timestamp = result.frame.timestamp.timestamp();
+
+        let group = groups.iter_mut().find(|group| {
+            let representative = &kept[group.index];
+            representative.frame.active_process == result.frame.active_process
+                && group
+                    .timestamps
+                    .iter()
+                    .any(|t| (t - timestamp).abs() <= window_secs)
+                && similarity(&group.words, &words) >= MIN_TEXT_SIMILARITY
+        });
+
+        match group {
+            Some(group) => {
+                group.timestamps.push(timestamp);
+                kept[group.index].duplicates_count += 1 + result.duplicates_count;
+            }
+            None => {
+                groups.push(Group {
+                    index: kept.len(),
+                    words,
+                    timestamps: vec![timestamp],
+                });
+                kept.push(result);
+            }
+        }
+    }
+
+    kept
+}
+
+/// Lowercased words of the OCR text of a result
+fn word_set(result: &SearchResult) -> HashSet<String> {
+    result
+        .ocr_matches
+        .iter()
+        .flat_map(|ocr| ocr.text.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Jaccard similarity of two word sets
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let shared = a.intersection(b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use screensearch_db::{FrameRecord, OcrTextRecord};
+
+    fn result(id: i64, seconds: i64, app: &str, text: &str) -> SearchResult {
+        let timestamp =
+            Utc.with_ymd_and_hms(2025, 12, 10, 9, 0, 0).unwrap() + Duration::seconds(seconds);
+        SearchResult {
+            frame: FrameRecord {
+                id,
+                chunk_id: None,
+                timestamp,
+                monitor_index: 0,
+                device_name: "monitor-1".to_string(),
+                file_path: format!("/tmp/{}.png", id),
+                active_window: None,
+                active_process: Some(app.to_string()),
+                browser_url: None,
+                width: 1920,
+                height: 1080,
+                offset_index: 0,
+                focused: None,
+                pinned: false,
+                created_at: timestamp,
+            },
+            ocr_matches: vec![OcrTextRecord {
+                id,
+                frame_id: id,
+                text: text.to_string(),
+                text_json: None,
+                x: 0,
+                y: 0,
+                width: 100,
+                height: 20,
+                confidence: 0.9,
+                created_at: timestamp,
+            }],
+            relevance_score: 1.0,
+            tags: Vec::new(),
+            highlights: Vec::new(),
+            duplicates_count: 0,
+        }
+    }
+
+    const SCREEN: &str = "Settings Account Privacy Notifications Save changes Cancel";
+
+    #[test]
+    fn test_collapses_unchanged_screen() {
+        let results = vec![
+            result(1, 0, "app.exe", SCREEN),
+            result(2, 5, "app.exe", SCREEN),
+            // Chained through frame 2
+            result(3, 50, "app.exe", SCREEN),
+        ];
+
+        let collapsed = collapse_duplicates(results, 45);
+        assert_eq!(collapsed.len(), 1);
+        assert_eq!(collapsed[0].frame.id, 1);
+        assert_eq!(collapsed[0].duplicates_count, 2);
+    }
+
+    #[test]
+    fn test_keeps_distinct_results() {
+        let results = vec![
+            result(1, 0, "app.exe", SCREEN),
+            // Other app
+            result(2, 5, "other.exe", SCREEN),
+            // Too late
+            result(3, 600, "app.exe", SCREEN),
+            // Different text
+            result(
+                4,
+                10,
+                "app.exe",
+                "Settings Save changes Cancel Billing Invoices Plans",
+            ),
+        ];
+
+        let collapsed = collapse_duplicates(results, DEFAULT_DEDUP_WINDOW_SECS);
+        assert_eq!(collapsed.len(), 4);
+        assert!(collapsed.iter().all(|r| r.duplicates_count == 0));
+    }
+}
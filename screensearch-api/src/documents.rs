@@ -0,0 +1,814 @@
+//! Receipt and invoice extraction
+//!
+//! Frames whose OCR text reads like a receipt or invoice are parsed into
+//! structured documents: vendor, printed date, total and line items. OCR
+//! regions are first grouped into rows, since a label and its amount are
+//! often recognized as separate regions on the same line.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use regex::Regex;
+use screensearch_capture::TextRegion;
+use screensearch_db::{DatabaseManager, DocumentRecord, NewDocument, OcrTextRecord};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+
+/// Maximum line items kept per document
+const MAX_LINE_ITEMS: usize = 100;
+
+/// Maximum length of an extracted vendor name (characters)
+const MAX_VENDOR_CHARS: usize = 80;
+
+/// How long an undated document counts as the same document when seen again
+const UNDATED_MATCH_WINDOW_MINUTES: i64 = 60;
+
+/// Kind of structured document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentType {
+    Receipt,
+    Invoice,
+}
+
+impl DocumentType {
+    /// Name stored in the `doc_type` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Receipt => "receipt",
+            Self::Invoice => "invoice",
+        }
+    }
+}
+
+/// One recognized line of text with its bounding box
+#[derive(Debug, Clone)]
+pub struct TextLine {
+    pub text: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl From<&TextRegion> for TextLine {
+    fn from(region: &TextRegion) -> Self {
+        Self {
+            text: region.text.clone(),
+            x: region.x as i32,
+            y: region.y as i32,
+            width: region.width as i32,
+            height: region.height as i32,
+        }
+    }
+}
+
+impl From<&OcrTextRecord> for TextLine {
+    fn from(record: &OcrTextRecord) -> Self {
+        Self {
+            text: record.text.clone(),
+            x: record.x,
+            y: record.y,
+            width: record.width,
+            height: record.height,
+        }
+    }
+}
+
+/// Purchased item on a receipt or invoice
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineItem {
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<u32>,
+    pub amount: f64,
+}
+
+/// Fields extracted from a frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedDocument {
+    pub doc_type: DocumentType,
+    pub vendor: Option<String>,
+    pub date: Option<NaiveDate>,
+    pub total: Option<f64>,
+    pub currency: Option<String>,
+    pub line_items: Vec<LineItem>,
+    /// Rows of text the fields were extracted from, one per line
+    pub text: String,
+}
+
+/// Money amount found in text
+#[derive(Debug, Clone, PartialEq)]
+struct Amount {
+    value: f64,
+    currency: Option<&'static str>,
+    /// Byte offset where the amount starts in its row
+    start: usize,
+}
+
+/// Extract a receipt or invoice from the OCR text of a frame
+///
+/// Without `doc_type` the text must classify as a receipt or invoice and
+/// contain a total; with it (e.g. a frame the user marked as a receipt)
+/// whatever fields can be found are returned.
+pub fn extract(lines: &[TextLine], doc_type: Option<DocumentType>) -> Option<ExtractedDocument> {
+    let rows = group_rows(lines);
+    let total = find_total(&rows);
+
+    let doc_type = match doc_type {
+        Some(doc_type) => doc_type,
+        None => {
+            total.as_ref()?;
+            classify(&rows.join("\n").to_lowercase())?
+        }
+    };
+
+    let start = rows
+        .iter()
+        .position(|row| doc_keyword_regex().is_match(row))
+        .map_or(0, |i| i + 1);
+    let end = total.as_ref().map_or(rows.len(), |(row, _)| *row);
+    let line_items = rows
+        .get(start..end.max(start))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|row| parse_line_item(row))
+        .take(MAX_LINE_ITEMS)
+        .collect();
+
+    let currency = total
+        .as_ref()
+        .and_then(|(_, amount)| amount.currency)
+        .or_else(|| {
+            rows.iter()
+                .flat_map(|row| find_amounts(row))
+                .find_map(|amount| amount.currency)
+        })
+        .map(str::to_string);
+
+    Some(ExtractedDocument {
+        doc_type,
+        vendor: find_vendor(&rows),
+        date: find_date(&rows),
+        total: total.map(|(_, amount)| amount.value),
+        currency,
+        line_items,
+        text: rows.join("\n"),
+    })
+}
+
+/// Classify lowercased document text
+fn classify(text: &str) -> Option<DocumentType> {
+    const INVOICE_CUES: &[&str] = &[
+        "invoice number",
+        "invoice #",
+        "invoice no",
+        "invoice date",
+        "bill to",
+        "due date",
+        "amount due",
+        "balance due",
+        "payment terms",
+    ];
+    const RECEIPT_CUES: &[&str] = &[
+        "subtotal",
+        "sub total",
+        "tax",
+        "vat",
+        "payment method",
+        "paid",
+        "change",
+        "cash",
+        "order #",
+        "order number",
+        "thank you for",
+    ];
+
+    if has_word(text, "invoice") && INVOICE_CUES.iter().any(|cue| text.contains(cue)) {
+        return Some(DocumentType::Invoice);
+    }
+
+    let receipt_cues = RECEIPT_CUES
+        .iter()
+        .filter(|cue| has_word(text, cue))
+        .count();
+    if has_word(text, "receipt") || receipt_cues >= 2 {
+        return Some(DocumentType::Receipt);
+    }
+    None
+}
+
+/// Group text lines into rows, top to bottom, joining regions on the same
+/// line left to right
+fn group_rows(lines: &[TextLine]) -> Vec<String> {
+    let mut lines: Vec<&TextLine> = lines
+        .iter()
+        .filter(|line| !line.text.trim().is_empty())
+        .collect();
+    lines.sort_by_key(|line| (line.y, line.x));
+
+    let mut rows: Vec<Vec<&TextLine>> = Vec::new();
+    for line in lines {
+        let center = line.y + line.height / 2;
+        match rows.last_mut() {
+            Some(row) if center >= row[0].y && center <= row[0].y + row[0].height => row.push(line),
+            _ => rows.push(vec![line]),
+        }
+    }
+
+    rows.into_iter()
+        .map(|mut row| {
+            row.sort_by_key(|line| line.x);
+            row.iter()
+                .map(|line| line.text.trim())
+                .collect::<Vec<_>>()
+                .join("  ")
+        })
+        .collect()
+}
+
+/// Row index and amount of the document total
+///
+/// Explicit labels like "Amount due" win over a plain "Total", and the last
+/// plain total wins over earlier ones. A label without an amount takes the
+/// amount on the next row.
+fn find_total(rows: &[String]) -> Option<(usize, Amount)> {
+    let mut best: Option<(bool, usize, Amount)> = None;
+
+    for (i, row) in rows.iter().enumerate() {
+        let Some(label) = total_label_regex().find(row) else {
+            continue;
+        };
+        if total_exclusion_regex().is_match(row) {
+            continue;
+        }
+
+        let amount = find_amounts(&row[label.end()..]).pop().or_else(|| {
+            rows.get(i + 1)
+                .filter(|next| !total_label_regex().is_match(next))
+                .and_then(|next| find_amounts(next).pop())
+        });
+        let Some(amount) = amount else {
+            continue;
+        };
+
+        let explicit = !label.as_str().eq_ignore_ascii_case("total");
+        if best
+            .as_ref()
+            .is_none_or(|(best_explicit, _, _)| explicit || !best_explicit)
+        {
+            best = Some((explicit, i, amount));
+        }
+    }
+
+    best.map(|(_, row, amount)| (row, amount))
+}
+
+/// Parse a row ending with an amount as a line item
+fn parse_line_item(row: &str) -> Option<LineItem> {
+    if total_label_regex().is_match(row) || summary_label_regex().is_match(row) {
+        return None;
+    }
+
+    let amount = find_amounts(row).pop()?;
+    let mut description = row[..amount.start]
+        .trim_end_matches(|c: char| c.is_whitespace() || "$€£¥:.-".contains(c))
+        .trim();
+
+    let mut quantity = None;
+    if let Some(captures) = quantity_regex().captures(description) {
+        quantity = captures[1].parse().ok();
+        description = description[captures.get(0).map_or(0, |m| m.end())..].trim();
+    }
+
+    if description.chars().filter(|c| c.is_alphabetic()).count() < 2 {
+        return None;
+    }
+
+    Some(LineItem {
+        description: description.to_string(),
+        quantity,
+        amount: amount.value,
+    })
+}
+
+/// Vendor named in phrases like "Receipt from Acme" or "Merchant: Acme",
+/// or the row just above the "Receipt"/"Invoice" heading
+fn find_vendor(rows: &[String]) -> Option<String> {
+    let named = rows.iter().find_map(|row| {
+        vendor_regex()
+            .captures(row)
+            .and_then(|captures| captures.get(1))
+            .map(|vendor| vendor.as_str())
+    });
+
+    let above_heading = || {
+        let heading = rows
+            .iter()
+            .position(|row| doc_keyword_regex().is_match(row))?;
+        rows[..heading]
+            .last()
+            .map(String::as_str)
+            .filter(|row| is_plausible_vendor(row))
+    };
+
+    named
+        .or_else(above_heading)
+        .map(|vendor| {
+            vendor
+                .trim_matches(|c: char| c.is_whitespace() || ".,:;!-".contains(c))
+                .chars()
+                .take(MAX_VENDOR_CHARS)
+                .collect::<String>()
+        })
+        .filter(|vendor| !vendor.is_empty())
+}
+
+/// Whether a row could be a business name
+fn is_plausible_vendor(row: &str) -> bool {
+    let letters = row.chars().filter(|c| c.is_alphabetic()).count();
+    letters >= 2
+        && row.chars().count() <= MAX_VENDOR_CHARS
+        && find_amounts(row).is_empty()
+        && parse_date(row).is_none()
+}
+
+/// Printed date, preferring rows labelled as the document date over due dates
+fn find_date(rows: &[String]) -> Option<NaiveDate> {
+    let labelled = rows.iter().find_map(|row| {
+        let lower = row.to_lowercase();
+        (lower.contains("date") && !lower.contains("due"))
+            .then(|| parse_date(row))
+            .flatten()
+    });
+    labelled.or_else(|| rows.iter().find_map(|row| parse_date(row)))
+}
+
+/// First date in the text
+///
+/// Numeric dates with slashes are read month first, falling back to day
+/// first when the month would be invalid; dotted dates are day first.
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    let number = |captures: &regex::Captures, i: usize| captures[i].parse::<u32>().ok();
+    let year = |value: u32| {
+        let value = value as i32;
+        if value < 100 {
+            2000 + value
+        } else {
+            value
+        }
+    };
+
+    if let Some(c) = iso_date_regex().captures(text) {
+        return NaiveDate::from_ymd_opt(year(number(&c, 1)?), number(&c, 2)?, number(&c, 3)?);
+    }
+
+    if let Some(c) = numeric_date_regex().captures(text) {
+        let (first, second, y) = (number(&c, 1)?, number(&c, 3)?, year(number(&c, 4)?));
+        return if &c[2] == "." {
+            NaiveDate::from_ymd_opt(y, second, first)
+        } else {
+            NaiveDate::from_ymd_opt(y, first, second)
+                .or_else(|| NaiveDate::from_ymd_opt(y, second, first))
+        };
+    }
+
+    if let Some(c) = month_first_date_regex().captures(text) {
+        return NaiveDate::from_ymd_opt(number(&c, 3)? as i32, month(&c[1])?, number(&c, 2)?);
+    }
+
+    if let Some(c) = day_first_date_regex().captures(text) {
+        return NaiveDate::from_ymd_opt(number(&c, 3)? as i32, month(&c[2])?, number(&c, 1)?);
+    }
+
+    None
+}
+
+/// Month number of an English month name or abbreviation
+fn month(name: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let prefix: String = name.to_lowercase().chars().take(3).collect();
+    MONTHS
+        .iter()
+        .position(|m| *m == prefix)
+        .map(|i| i as u32 + 1)
+}
+
+/// Money amounts in the text, in order
+///
+/// Amounts need two decimals, so quantities, years and phone numbers are
+/// not mistaken for prices, and parts of dates like `03.09.2026` are
+/// skipped. Both `1,234.56` and `1.234,56` are understood.
+fn find_amounts(text: &str) -> Vec<Amount> {
+    amount_regex()
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let number = captures.name("number")?;
+            if is_date_part(text, number.start(), number.end()) {
+                return None;
+            }
+            let digits = number.as_str();
+            let (integer, fraction) = digits.split_at(digits.len() - 3);
+            let integer: String = integer.chars().filter(char::is_ascii_digit).collect();
+            let value = format!("{}.{}", integer, &fraction[1..]).parse().ok()?;
+
+            let currency = captures
+                .name("symbol")
+                .or_else(|| captures.name("code"))
+                .and_then(|c| currency_code(c.as_str()));
+
+            Some(Amount {
+                value,
+                currency,
+                start: captures.get(0).map_or(number.start(), |m| m.start()),
+            })
+        })
+        .collect()
+}
+
+/// Whether the number at `start..end` continues into a date separator and
+/// more digits on either side
+fn is_date_part(text: &str, start: usize, end: usize) -> bool {
+    let is_separator = |c: char| matches!(c, '.' | ',' | '/' | '-');
+
+    let mut after = text[end..].chars();
+    let continues_after =
+        after.next().is_some_and(is_separator) && after.next().is_some_and(|c| c.is_ascii_digit());
+
+    let mut before = text[..start].chars().rev();
+    let continues_before = before.next().is_some_and(is_separator)
+        && before.next().is_some_and(|c| c.is_ascii_digit());
+
+    continues_after || continues_before
+}
+
+/// ISO 4217 code of a currency symbol or code
+fn currency_code(symbol: &str) -> Option<&'static str> {
+    match symbol.to_uppercase().as_str() {
+        "$" | "USD" => Some("USD"),
+        "€" | "EUR" => Some("EUR"),
+        "£" | "GBP" => Some("GBP"),
+        "¥" | "JPY" => Some("JPY"),
+        "CAD" => Some("CAD"),
+        "AUD" => Some("AUD"),
+        "CHF" => Some("CHF"),
+        _ => None,
+    }
+}
+
+/// Whether lowercased text contains a word or phrase on word boundaries
+fn has_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn amount_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)(?:(?P<symbol>[$€£¥])\s?)?\b(?P<number>\d{1,3}(?:[,.]\d{3})+[.,]\d{2}|\d+[.,]\d{2})\b(?:\s?(?P<code>USD|EUR|GBP|JPY|CAD|AUD|CHF|€|£))?",
+        )
+        .unwrap()
+    })
+}
+
+fn total_label_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:grand total|total due|total amount|amount due|balance due|amount paid|total)\b",
+        )
+        .unwrap()
+    })
+}
+
+/// Rows mentioning "total" that are not the document total
+fn total_exclusion_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:sub[\s-]?total|total (?:tax|savings|discount|items?))\b").unwrap()
+    })
+}
+
+/// Summary rows that are not line items
+fn summary_label_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:sub[\s-]?total|tax|vat|gst|hst|tip|gratuity|discount|savings|change|cash|balance|shipping|delivery fee|service fee|paid|payment|visa|mastercard|amex|debit|credit)\b",
+        )
+        .unwrap()
+    })
+}
+
+/// "Receipt"/"Invoice" headings that open a document
+fn doc_keyword_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"(?i)\b(?:receipt|invoice)\b").unwrap())
+}
+
+fn vendor_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)(?:\b(?:receipt|invoice|order|purchase|payment) (?:from|to)|\bthank you for (?:shopping|ordering|dining|your (?:order|purchase)) (?:at|with|from)|^\s*(?:vendor|merchant|seller|sold by|from)\s*:)\s+(.+)",
+        )
+        .unwrap()
+    })
+}
+
+/// Leading quantity of a line item, e.g. "2 x" or "3"
+fn quantity_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^(\d{1,3})\s*(?:[x×@]\s*)?\s").unwrap())
+}
+
+fn iso_date_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\b(\d{4})-(\d{1,2})-(\d{1,2})\b").unwrap())
+}
+
+fn numeric_date_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\b(\d{1,2})([/.])(\d{1,2})[/.](\d{4}|\d{2})\b").unwrap())
+}
+
+fn month_first_date_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?\s+(\d{1,2})(?:st|nd|rd|th)?,?\s+(\d{4})\b",
+        )
+        .unwrap()
+    })
+}
+
+fn day_first_date_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(\d{1,2})(?:st|nd|rd|th)?\s+(jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?,?\s+(\d{4})\b",
+        )
+        .unwrap()
+    })
+}
+
+/// Extracts receipts and invoices from frames as they are stored
+pub struct DocumentExtractor {
+    db: Arc<DatabaseManager>,
+}
+
+impl DocumentExtractor {
+    /// Create an extractor storing documents in `db`
+    pub fn new(db: Arc<DatabaseManager>) -> Self {
+        Self { db }
+    }
+
+    /// Extract a document from a stored frame, if it shows one
+    ///
+    /// Failures are logged so extraction never blocks frame storage.
+    pub async fn check_frame(
+        &self,
+        frame_id: i64,
+        captured_at: DateTime<Utc>,
+        app_name: Option<&str>,
+        regions: &[TextRegion],
+    ) -> Option<DocumentRecord> {
+        let lines: Vec<TextLine> = regions.iter().map(TextLine::from).collect();
+        let document = extract(&lines, None)?;
+
+        match self.store(frame_id, captured_at, app_name, document).await {
+            Ok(record) => Some(record),
+            Err(e) => {
+                tracing::error!("Failed to store document from frame {}: {}", frame_id, e);
+                None
+            }
+        }
+    }
+
+    /// Store an extracted document unless the same one is already stored
+    ///
+    /// A receipt stays on screen for many frames, so documents with the same
+    /// type, vendor, date and total are stored once; the existing record is
+    /// returned instead.
+    pub async fn store(
+        &self,
+        frame_id: i64,
+        captured_at: DateTime<Utc>,
+        app_name: Option<&str>,
+        document: ExtractedDocument,
+    ) -> screensearch_db::Result<DocumentRecord> {
+        let line_items = serde_json::to_string(&document.line_items)
+            .map_err(|e| screensearch_db::DatabaseError::QueryError(e.to_string()))?;
+        let new_document = NewDocument {
+            frame_id,
+            doc_type: document.doc_type.as_str().to_string(),
+            vendor: document.vendor,
+            document_date: document.date,
+            total: document.total,
+            currency: document.currency,
+            line_items,
+            text: document.text,
+            app_name: app_name.map(str::to_string),
+            captured_at,
+        };
+
+        let undated_since = captured_at - Duration::minutes(UNDATED_MATCH_WINDOW_MINUTES);
+        if let Some(existing) = self
+            .db
+            .find_matching_document(&new_document, undated_since)
+            .await?
+        {
+            return Ok(existing);
+        }
+
+        let id = self.db.insert_document(new_document).await?;
+        let record =
+            self.db.get_document(id).await?.ok_or_else(|| {
+                screensearch_db::DatabaseError::NotFound(format!("Document {}", id))
+            })?;
+        tracing::info!(
+            "Extracted {} {} from frame {}: vendor={:?}, total={:?}",
+            record.doc_type,
+            id,
+            frame_id,
+            record.vendor,
+            record.total
+        );
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Text lines stacked top to bottom, 20px apart
+    fn lines(rows: &[&str]) -> Vec<TextLine> {
+        rows.iter()
+            .enumerate()
+            .map(|(i, text)| TextLine {
+                text: text.to_string(),
+                x: 10,
+                y: i as i32 * 20,
+                width: 200,
+                height: 16,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extracts_receipt() {
+        let document = extract(
+            &lines(&[
+                "Inbox - Mail",
+                "Blue Bottle Coffee",
+                "Receipt",
+                "Oct 16, 2026 8:42 AM",
+                "2 x Latte $10.50",
+                "Croissant $4.25",
+                "Subtotal $14.75",
+                "Tax $1.33",
+                "Total $16.08",
+                "Paid with Visa",
+            ]),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(document.doc_type, DocumentType::Receipt);
+        assert_eq!(document.vendor.as_deref(), Some("Blue Bottle Coffee"));
+        assert_eq!(document.date, NaiveDate::from_ymd_opt(2026, 10, 16));
+        assert_eq!(document.total, Some(16.08));
+        assert_eq!(document.currency.as_deref(), Some("USD"));
+        assert_eq!(
+            document.line_items,
+            vec![
+                LineItem {
+                    description: "Latte".to_string(),
+                    quantity: Some(2),
+                    amount: 10.50,
+                },
+                LineItem {
+                    description: "Croissant".to_string(),
+                    quantity: None,
+                    amount: 4.25,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extracts_invoice_with_split_columns() {
+        // Labels and amounts recognized as separate regions on the same row
+        let mut input = lines(&[
+            "Invoice from Acme Hosting Ltd.",
+            "Invoice date: 03.09.2026",
+            "Due date: 17.09.2026",
+            "VPS plan",
+            "Backups",
+            "Amount due",
+        ]);
+        for (row, amount) in [(3, "100,00 €"), (4, "20,00 €"), (5, "1.120,00 €")] {
+            let y = input[row].y;
+            input.push(TextLine {
+                text: amount.to_string(),
+                x: 400,
+                y: y + 2,
+                width: 80,
+                height: 16,
+            });
+        }
+
+        let document = extract(&input, None).unwrap();
+        assert_eq!(document.doc_type, DocumentType::Invoice);
+        assert_eq!(document.vendor.as_deref(), Some("Acme Hosting Ltd"));
+        assert_eq!(document.date, NaiveDate::from_ymd_opt(2026, 9, 3));
+        assert_eq!(document.total, Some(1120.0));
+        assert_eq!(document.currency.as_deref(), Some("EUR"));
+        assert_eq!(document.line_items.len(), 2);
+        assert_eq!(document.line_items[0].description, "VPS plan");
+        assert_eq!(document.line_items[1].amount, 20.0);
+    }
+
+    #[test]
+    fn test_ignores_ordinary_text() {
+        assert!(extract(
+            &lines(&["fn main() {", "    println!(\"1.50\");", "}"]),
+            None
+        )
+        .is_none());
+        // Mentions receipts but has no total
+        assert!(extract(&lines(&["Email me the receipt please"]), None).is_none());
+        // A total alone is not a receipt
+        assert!(extract(&lines(&["Total downloads 12.50"]), None).is_none());
+    }
+
+    #[test]
+    fn test_forced_type_skips_classification() {
+        let document = extract(
+            &lines(&["Lunch with team", "Total 42.00"]),
+            Some(DocumentType::Receipt),
+        )
+        .unwrap();
+        assert_eq!(document.doc_type, DocumentType::Receipt);
+        assert_eq!(document.total, Some(42.0));
+        assert!(extract(&[], Some(DocumentType::Invoice)).is_some());
+    }
+
+    #[test]
+    fn test_total_prefers_explicit_labels() {
+        let rows: Vec<String> = [
+            "Total 10.00",
+            "Amount due 8.00",
+            "Total 12.00",
+            "Subtotal 20.00",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let (row, amount) = find_total(&rows).unwrap();
+        assert_eq!(row, 1);
+        assert_eq!(amount.value, 8.0);
+    }
+
+    #[test]
+    fn test_parses_amounts() {
+        let values = |text| {
+            find_amounts(text)
+                .into_iter()
+                .map(|a| (a.value, a.currency))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values("$1,234.56"), vec![(1234.56, Some("USD"))]);
+        assert_eq!(values("1.234,56 EUR"), vec![(1234.56, Some("EUR"))]);
+        assert_eq!(
+            values("£3.50 and 7,25"),
+            vec![(3.5, Some("GBP")), (7.25, None)]
+        );
+        assert!(values("2026 Order 123 555-0100").is_empty());
+        assert!(values("Invoice date: 03.09.2026").is_empty());
+    }
+
+    #[test]
+    fn test_parses_dates() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        assert_eq!(parse_date("2026-10-16"), date(2026, 10, 16));
+        assert_eq!(parse_date("10/16/2026"), date(2026, 10, 16));
+        assert_eq!(parse_date("16/10/26"), date(2026, 10, 16));
+        assert_eq!(parse_date("16.10.2026"), date(2026, 10, 16));
+        assert_eq!(parse_date("October 16th, 2026"), date(2026, 10, 16));
+        assert_eq!(parse_date("16 Oct 2026"), date(2026, 10, 16));
+        assert_eq!(parse_date("Order 1234"), None);
+    }
+
+    #[test]
+    fn test_document_type_names() {
+        assert_eq!(DocumentType::Receipt.as_str(), "receipt");
+        let doc_type: DocumentType = serde_json::from_str("\"invoice\"").unwrap();
+        assert_eq!(doc_type, DocumentType::Invoice);
+    }
+}
@@ -0,0 +1,186 @@
+//! Element handle registry
+//!
+//! `/automation/find-elements` registers every element it returns under an
+//! opaque handle, so follow-up requests such as `/automation/click` act on
+//! exactly that element instead of re-resolving the selector.
+
+use screensearch_automation::UIElement;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long an unused handle stays valid
+pub const HANDLE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Maximum number of live handles; the least recently used is evicted first
+pub const MAX_HANDLES: usize = 2000;
+
+/// Registry of UI element handles
+pub type ElementHandles = HandleRegistry<UIElement>;
+
+/// Why a handle could not be resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandleError {
+    /// The handle was never issued, expired or was evicted
+    Unknown,
+    /// The element no longer exists in the UI
+    Stale,
+}
+
+struct Entry<T> {
+    value: T,
+    last_used: Instant,
+}
+
+/// Handle registry with idle expiry and a size cap
+pub struct HandleRegistry<T> {
+    /// Distinguishes handles of this process from those of a previous run
+    prefix: String,
+    next_id: AtomicU64,
+    entries: Mutex<HashMap<String, Entry<T>>>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl<T: Clone> HandleRegistry<T> {
+    /// Create an empty registry
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        let started = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Self {
+            prefix: format!("{:x}", started.as_millis()),
+            next_id: AtomicU64::new(1),
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Register a value and return its new handle
+    pub fn insert(&self, value: T) -> String {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let handle = format!("el-{}-{}", self.prefix, id);
+        let now = Instant::now();
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, entry| now.duration_since(entry.last_used) < self.ttl);
+
+        if entries.len() >= self.capacity {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(handle, _)| handle.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(
+            handle.clone(),
+            Entry {
+                value,
+                last_used: now,
+            },
+        );
+        handle
+    }
+
+    /// Resolve a handle, checking the value is still alive
+    ///
+    /// Stale values are dropped from the registry. A successful lookup
+    /// restarts the handle's idle timer.
+    pub fn get(&self, handle: &str, is_alive: impl Fn(&T) -> bool) -> Result<T, HandleError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+
+        let entry = match entries.get_mut(handle) {
+            Some(entry) if now.duration_since(entry.last_used) < self.ttl => entry,
+            Some(_) => {
+                entries.remove(handle);
+                return Err(HandleError::Unknown);
+            }
+            None => return Err(HandleError::Unknown),
+        };
+
+        if !is_alive(&entry.value) {
+            entries.remove(handle);
+            return Err(HandleError::Stale);
+        }
+
+        entry.last_used = now;
+        Ok(entry.value.clone())
+    }
+
+    /// Number of registered handles, including expired ones not yet pruned
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Whether no handles are registered
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for ElementHandles {
+    fn default() -> Self {
+        Self::new(HANDLE_TTL, MAX_HANDLES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_get() {
+        let registry = HandleRegistry::new(Duration::from_secs(60), 10);
+        let first = registry.insert(1);
+        let second = registry.insert(2);
+
+        assert_ne!(first, second);
+        assert_eq!(registry.get(&first, |_| true), Ok(1));
+        assert_eq!(registry.get(&second, |_| true), Ok(2));
+        assert_eq!(
+            registry.get("el-unknown", |_| true),
+            Err(HandleError::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_stale_values_are_dropped() {
+        let registry = HandleRegistry::new(Duration::from_secs(60), 10);
+        let handle = registry.insert(1);
+
+        assert_eq!(registry.get(&handle, |_| false), Err(HandleError::Stale));
+        assert_eq!(registry.get(&handle, |_| true), Err(HandleError::Unknown));
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn test_expired_handles() {
+        let registry = HandleRegistry::new(Duration::ZERO, 10);
+        let handle = registry.insert(1);
+
+        assert_eq!(registry.get(&handle, |_| true), Err(HandleError::Unknown));
+    }
+
+    #[test]
+    fn test_capacity_evicts_least_recently_used() {
+        let registry = HandleRegistry::new(Duration::from_secs(60), 2);
+        let first = registry.insert(1);
+        let second = registry.insert(2);
+
+        // Touch the first handle so the second becomes the oldest
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(registry.get(&first, |_| true), Ok(1));
+
+        let third = registry.insert(3);
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get(&second, |_| true), Err(HandleError::Unknown));
+        assert_eq!(registry.get(&first, |_| true), Ok(1));
+        assert_eq!(registry.get(&third, |_| true), Ok(3));
+    }
+}
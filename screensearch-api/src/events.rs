@@ -0,0 +1,237 @@
+//! Server events
+//!
+//! Events pushed to clients connected to the `/events` WebSocket stream.
+
+use crate::handlers::OllamaPullProgress;
+use screensearch_automation::{AutomationError, UiEvent, UiEventKind, UiEventSubscription};
+use screensearch_db::AlertHitRecord;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Capacity of the event channel; slow subscribers skip older events
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Event published to WebSocket subscribers
+///
+/// Serialized as JSON with a `type` field, e.g.
+/// `{"type": "alert_hit", "hit": {...}}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    /// A keyword watch alert matched a captured frame
+    AlertHit { hit: AlertHitRecord },
+    /// A UIAutomation event, sent only to clients that asked for its kind
+    UiEvent { event: UiEvent },
+    /// Progress of a model download started with `/ai/ollama/pull`
+    OllamaPull { progress: OllamaPullProgress },
+}
+
+/// Shares one UIAutomation event subscription between `/events` clients
+///
+/// The subscription covers the union of the kinds requested by connected
+/// clients. It is replaced when that union changes and removed when the last
+/// interested client disconnects.
+pub struct UiEventForwarder {
+    events: broadcast::Sender<ServerEvent>,
+    inner: Mutex<ForwarderState>,
+}
+
+#[derive(Default)]
+struct ForwarderState {
+    /// Number of connected clients per requested kind
+    listeners: HashMap<UiEventKind, usize>,
+    subscription: Option<UiEventSubscription>,
+}
+
+/// Keeps a client's event kinds subscribed until dropped
+pub struct UiEventListener {
+    forwarder: Arc<UiEventForwarder>,
+    kinds: Vec<UiEventKind>,
+}
+
+impl UiEventListener {
+    /// Event kinds this client receives
+    pub fn kinds(&self) -> &[UiEventKind] {
+        &self.kinds
+    }
+}
+
+impl Drop for UiEventListener {
+    fn drop(&mut self) {
+        self.forwarder.release(&self.kinds);
+    }
+}
+
+impl UiEventForwarder {
+    /// Create a forwarder publishing to `events`
+    pub fn new(events: broadcast::Sender<ServerEvent>) -> Self {
+        Self {
+            events,
+            inner: Mutex::new(ForwarderState::default()),
+        }
+    }
+
+    /// Start forwarding `kinds` for one client
+    pub fn listen(
+        self: &Arc<Self>,
+        kinds: &[UiEventKind],
+    ) -> Result<UiEventListener, AutomationError> {
+        let mut kinds = kinds.to_vec();
+        kinds.sort_by_key(|kind| kind.as_str());
+        kinds.dedup();
+
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        for kind in &kinds {
+            *state.listeners.entry(*kind).or_default() += 1;
+        }
+        if let Err(e) = self.resubscribe(&mut state) {
+            for kind in &kinds {
+                decrement(&mut state.listeners, *kind);
+            }
+            return Err(e);
+        }
+
+        Ok(UiEventListener {
+            forwarder: Arc::clone(self),
+            kinds,
+        })
+    }
+
+    fn release(&self, kinds: &[UiEventKind]) {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        for kind in kinds {
+            decrement(&mut state.listeners, *kind);
+        }
+        if let Err(e) = self.resubscribe(&mut state) {
+            tracing::warn!("Failed to update UI event subscription: {}", e);
+        }
+    }
+
+    /// Make the subscription match the kinds clients are listening to
+    fn resubscribe(&self, state: &mut ForwarderState) -> Result<(), AutomationError> {
+        let mut wanted: Vec<UiEventKind> = state.listeners.keys().copied().collect();
+        wanted.sort_by_key(|kind| kind.as_str());
+
+        let current = state.subscription.as_ref().map(|s| s.kinds().to_vec());
+        if current.as_deref().unwrap_or_default() == wanted.as_slice() {
+            return Ok(());
+        }
+
+        // Stop the old handlers first so events are not delivered twice
+        state.subscription = None;
+        if wanted.is_empty() {
+            return Ok(());
+        }
+
+        match self.subscribe(&wanted) {
+            Ok(subscription) => {
+                state.subscription = Some(subscription);
+                Ok(())
+            }
+            Err(e) => {
+                // Restore the previous kinds so existing clients keep working
+                if let Some(kinds) = current.filter(|kinds| !kinds.is_empty()) {
+                    state.subscription = self.subscribe(&kinds).ok();
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn subscribe(&self, kinds: &[UiEventKind]) -> Result<UiEventSubscription, AutomationError> {
+        let events = self.events.clone();
+        UiEventSubscription::start(
+            kinds,
+            Box::new(move |event| {
+                // No receivers is not an error: clients may be reconnecting
+                let _ = events.send(ServerEvent::UiEvent { event });
+            }),
+        )
+    }
+}
+
+fn decrement(listeners: &mut HashMap<UiEventKind, usize>, kind: UiEventKind) {
+    if let Some(count) = listeners.get_mut(&kind) {
+        *count -= 1;
+        if *count == 0 {
+            listeners.remove(&kind);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_event_serialization() {
+        let event = ServerEvent::AlertHit {
+            hit: AlertHitRecord {
+                id: 7,
+                alert_id: 2,
+                frame_id: 42,
+                matched_text: "OutOfMemoryError".to_string(),
+                context: "java.lang.OutOfMemoryError".to_string(),
+                app_name: None,
+                created_at: Utc::now(),
+            },
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "alert_hit");
+        assert_eq!(json["hit"]["frame_id"], 42);
+    }
+
+    #[test]
+    fn test_ui_event_serialization() {
+        let event = ServerEvent::UiEvent {
+            event: UiEvent {
+                kind: UiEventKind::WindowOpened,
+                role: Some("Window".to_string()),
+                name: Some("Save As".to_string()),
+                automation_id: None,
+                process_id: Some(4242),
+                change: None,
+            },
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "ui_event");
+        assert_eq!(json["event"]["kind"], "window_opened");
+        assert_eq!(json["event"]["name"], "Save As");
+    }
+
+    #[test]
+    fn test_ollama_pull_serialization() {
+        let event = ServerEvent::OllamaPull {
+            progress: OllamaPullProgress {
+                model: "llama3.2".to_string(),
+                status: "pulling dde5aa3fc5ff".to_string(),
+                completed: Some(1_048_576),
+                total: Some(2_019_377_376),
+                done: false,
+                error: None,
+            },
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "ollama_pull");
+        assert_eq!(json["progress"]["model"], "llama3.2");
+        assert_eq!(json["progress"]["completed"], 1_048_576);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_failed_listen_releases_kinds() {
+        let (events, _) = broadcast::channel(4);
+        let forwarder = Arc::new(UiEventForwarder::new(events));
+
+        assert!(forwarder.listen(&[UiEventKind::FocusChanged]).is_err());
+        let state = forwarder.inner.lock().unwrap();
+        assert!(state.listeners.is_empty());
+        assert!(state.subscription.is_none());
+    }
+}
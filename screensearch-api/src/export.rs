@@ -0,0 +1,718 @@
+//! Report export
+//!
+//! Renders Markdown reports to standalone HTML and PDF documents so they can be
+//! shared outside the app. Frames referenced by image links
+//! (`![..](/api/frames/{id}/image)`) are embedded as JPEG thumbnails.
+//!
+//! PDF output is produced by a small built-in writer using the standard PDF
+//! fonts (Helvetica/Courier), so no font files or native libraries are needed.
+//! Text outside Latin-1 is approximated or replaced.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use pulldown_cmark::{CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::LazyLock;
+
+/// Matches frame image URLs referenced from reports
+static FRAME_IMAGE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"/api/frames/(\d+)/image").unwrap());
+
+/// Maximum thumbnail dimension in pixels
+const THUMBNAIL_SIZE: u32 = 640;
+
+/// JPEG thumbnail of a frame referenced by a report
+#[derive(Debug, Clone)]
+pub struct FrameThumbnail {
+    pub jpeg: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Create an RGB JPEG thumbnail from encoded frame image data
+pub fn make_thumbnail(data: &[u8]) -> image::ImageResult<FrameThumbnail> {
+    let rgb = image::load_from_memory(data)?
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .to_rgb8();
+
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 80).encode(
+        rgb.as_raw(),
+        rgb.width(),
+        rgb.height(),
+        image::ColorType::Rgb8,
+    )?;
+
+    Ok(FrameThumbnail {
+        jpeg,
+        width: rgb.width(),
+        height: rgb.height(),
+    })
+}
+
+/// Frame IDs referenced by `/api/frames/{id}/image` URLs, in order of first use
+pub fn referenced_frame_ids(markdown: &str) -> Vec<i64> {
+    let mut ids = Vec::new();
+    for caps in FRAME_IMAGE_REGEX.captures_iter(markdown) {
+        if let Ok(id) = caps[1].parse::<i64>() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+fn frame_id_from_url(url: &str) -> Option<i64> {
+    FRAME_IMAGE_REGEX
+        .captures(url)
+        .and_then(|caps| caps[1].parse().ok())
+}
+
+/// Whether a link to `url` may be kept: http, https, mailto or relative
+fn is_safe_link(url: &str) -> bool {
+    // Browsers skip control characters and spaces when reading a scheme
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_control() && *c != ' ')
+        .collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(i) if url[i..].starts_with(':') => matches!(
+            url[..i].to_ascii_lowercase().as_str(),
+            "http" | "https" | "mailto"
+        ),
+        _ => true,
+    }
+}
+
+fn markdown_parser(markdown: &str) -> Parser<'_> {
+    Parser::new_ext(markdown, Options::ENABLE_TABLES)
+}
+
+// ============================================================
+// HTML
+// ============================================================
+
+const HTML_STYLE: &str = r#"body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; max-width: 820px; margin: 40px auto; padding: 0 20px; color: #1f2328; line-height: 1.55; }
+h1, h2, h3 { line-height: 1.25; }
+h1 { border-bottom: 1px solid #d0d7de; padding-bottom: .3em; }
+code, pre { font-family: Consolas, "Courier New", monospace; background: #f6f8fa; border-radius: 4px; }
+code { padding: .1em .3em; }
+pre { padding: 12px; overflow-x: auto; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d0d7de; padding: 6px 12px; }
+img { max-width: 100%; border: 1px solid #d0d7de; border-radius: 4px; }
+.meta { color: #656d76; font-size: .9em; }"#;
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render a Markdown report to a standalone HTML document
+///
+/// Raw HTML in the Markdown is escaped rather than passed through, and links
+/// other than http, https, mailto or relative ones are rendered as their text,
+/// since report content comes from an LLM.
+pub fn render_html(
+    title: &str,
+    subtitle: &str,
+    markdown: &str,
+    thumbnails: &HashMap<i64, FrameThumbnail>,
+) -> String {
+    // Links do not nest, so this covers the one being rendered
+    let mut in_unsafe_link = false;
+    let events = markdown_parser(markdown).filter_map(move |event| match event {
+        Event::Start(Tag::Link { ref dest_url, .. }) if !is_safe_link(dest_url) => {
+            in_unsafe_link = true;
+            None
+        }
+        Event::End(TagEnd::Link) if in_unsafe_link => {
+            in_unsafe_link = false;
+            None
+        }
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => {
+            let dest_url = match frame_id_from_url(&dest_url).and_then(|fid| thumbnails.get(&fid)) {
+                Some(thumb) => CowStr::from(format!(
+                    "data:image/jpeg;base64,{}",
+                    BASE64.encode(&thumb.jpeg)
+                )),
+                None => dest_url,
+            };
+            Some(Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }))
+        }
+        Event::Html(html) | Event::InlineHtml(html) => Some(Event::Text(html)),
+        other => Some(other),
+    });
+
+    let mut body = String::new();
+    pulldown_cmark::html::push_html(&mut body, events);
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{style}\n</style>\n</head>\n<body>\n<p class=\"meta\">{title} &middot; {subtitle}</p>\n{body}</body>\n</html>\n",
+        title = escape_html(title),
+        subtitle = escape_html(subtitle),
+        style = HTML_STYLE,
+        body = body,
+    )
+}
+
+// ============================================================
+// PDF
+// ============================================================
+
+/// A4 page size in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const LINE_SPACING: f32 = 1.4;
+const MAX_IMAGE_HEIGHT: f32 = 300.0;
+
+#[derive(Debug, Clone, Copy)]
+enum PdfFont {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl PdfFont {
+    fn resource(self) -> &'static str {
+        match self {
+            PdfFont::Regular => "F1",
+            PdfFont::Bold => "F2",
+            PdfFont::Mono => "F3",
+        }
+    }
+
+    /// Approximate average glyph width as a fraction of the font size
+    fn char_width(self) -> f32 {
+        match self {
+            PdfFont::Regular => 0.5,
+            PdfFont::Bold => 0.55,
+            PdfFont::Mono => 0.6,
+        }
+    }
+}
+
+/// Encode text as a PDF literal string body in WinAnsi encoding
+fn pdf_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' | '(' | ')' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            ' '..='~' => out.push(ch),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(out, "\\{:03o}", ch as u32);
+            }
+            '\u{2018}' | '\u{2019}' => out.push('\''),
+            '\u{201c}' | '\u{201d}' => out.push('"'),
+            '\u{2013}' | '\u{2014}' | '\u{2022}' => out.push('-'),
+            '\u{2026}' => out.push_str("..."),
+            '\t' => out.push_str("    "),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+/// Greedy word wrap to a maximum number of characters per line
+fn wrap_text(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word = word;
+        // Hard-split words longer than a whole line (URLs, hashes)
+        while word.chars().count() > max_chars {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            let split = word
+                .char_indices()
+                .nth(max_chars)
+                .map(|(i, _)| i)
+                .unwrap_or(word.len());
+            lines.push(word[..split].to_string());
+            word = &word[split..];
+        }
+
+        if line.is_empty() {
+            line.push_str(word);
+        } else if line.chars().count() + 1 + word.chars().count() <= max_chars {
+            line.push(' ');
+            line.push_str(word);
+        } else {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Lays out text and images onto A4 pages as PDF content streams
+struct PdfLayout<'a> {
+    pages: Vec<String>,
+    content: String,
+    y: f32,
+    images: Vec<&'a FrameThumbnail>,
+}
+
+impl<'a> PdfLayout<'a> {
+    fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            content: String::new(),
+            y: PAGE_HEIGHT - MARGIN,
+            images: Vec::new(),
+        }
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(std::mem::take(&mut self.content));
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Start a new page if `height` points don't fit on the current one
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < MARGIN && self.y < PAGE_HEIGHT - MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn text(&mut self, text: &str, font: PdfFont, size: f32, indent: f32) {
+        let available = PAGE_WIDTH - 2.0 * MARGIN - indent;
+        let max_chars = (available / (size * font.char_width())) as usize;
+        let line_height = size * LINE_SPACING;
+
+        for line in wrap_text(text, max_chars) {
+            self.ensure(line_height);
+            self.y -= line_height;
+            let _ = writeln!(
+                self.content,
+                "BT /{} {} Tf {:.1} {:.1} Td ({}) Tj ET",
+                font.resource(),
+                size,
+                MARGIN + indent,
+                self.y,
+                pdf_string(&line)
+            );
+        }
+    }
+
+    fn rule(&mut self) {
+        self.ensure(12.0);
+        self.y -= 6.0;
+        let _ = writeln!(
+            self.content,
+            "0.8 G {:.1} {:.1} m {:.1} {:.1} l S 0 G",
+            MARGIN,
+            self.y,
+            PAGE_WIDTH - MARGIN,
+            self.y
+        );
+        self.y -= 6.0;
+    }
+
+    fn image(&mut self, thumb: &'a FrameThumbnail) {
+        let max_width = PAGE_WIDTH - 2.0 * MARGIN;
+        let scale = (max_width / thumb.width as f32)
+            .min(MAX_IMAGE_HEIGHT / thumb.height as f32)
+            .min(1.0);
+        let width = thumb.width as f32 * scale;
+        let height = thumb.height as f32 * scale;
+
+        self.ensure(height + 8.0);
+        self.y -= height + 4.0;
+
+        let index = self.images.len();
+        self.images.push(thumb);
+        let _ = writeln!(
+            self.content,
+            "q {:.1} 0 0 {:.1} {:.1} {:.1} cm /Im{} Do Q",
+            width, height, MARGIN, self.y, index
+        );
+        self.y -= 4.0;
+    }
+
+    fn finish(mut self) -> (Vec<String>, Vec<&'a FrameThumbnail>) {
+        if !self.content.is_empty() || self.pages.is_empty() {
+            self.new_page();
+        }
+        (self.pages, self.images)
+    }
+}
+
+/// Inline text collected for the block currently being parsed
+struct PendingBlock {
+    text: String,
+    font: PdfFont,
+    size: f32,
+    indent: f32,
+    prefix: String,
+}
+
+impl PendingBlock {
+    fn paragraph() -> Self {
+        Self {
+            text: String::new(),
+            font: PdfFont::Regular,
+            size: 11.0,
+            indent: 0.0,
+            prefix: String::new(),
+        }
+    }
+}
+
+fn heading_size(level: HeadingLevel) -> f32 {
+    match level {
+        HeadingLevel::H1 => 18.0,
+        HeadingLevel::H2 => 15.0,
+        HeadingLevel::H3 => 13.0,
+        _ => 12.0,
+    }
+}
+
+/// Render a Markdown report to a PDF document
+pub fn render_pdf(
+    title: &str,
+    subtitle: &str,
+    markdown: &str,
+    thumbnails: &HashMap<i64, FrameThumbnail>,
+) -> Vec<u8> {
+    let mut layout = PdfLayout::new();
+    layout.text(title, PdfFont::Bold, 20.0, 0.0);
+    layout.text(subtitle, PdfFont::Regular, 9.0, 0.0);
+    layout.space(10.0);
+
+    let mut block = PendingBlock::paragraph();
+    // Next item number per nesting level (None = bulleted list)
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut in_code_block = false;
+    let mut in_image = false;
+
+    fn flush(layout: &mut PdfLayout<'_>, block: &mut PendingBlock) {
+        let text = std::mem::take(&mut block.text);
+        if text.trim().is_empty() {
+            return;
+        }
+        let text = format!("{}{}", block.prefix, text.trim());
+        layout.text(&text, block.font, block.size, block.indent);
+        block.prefix.clear();
+    }
+
+    for event in markdown_parser(markdown) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                flush(&mut layout, &mut block);
+                layout.space(6.0);
+                block.font = PdfFont::Bold;
+                block.size = heading_size(level);
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                flush(&mut layout, &mut block);
+                layout.space(4.0);
+                block = PendingBlock::paragraph();
+            }
+            Event::End(TagEnd::Paragraph) => {
+                flush(&mut layout, &mut block);
+                if lists.is_empty() {
+                    layout.space(6.0);
+                }
+            }
+            Event::Start(Tag::List(start)) => {
+                flush(&mut layout, &mut block);
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                flush(&mut layout, &mut block);
+                lists.pop();
+                if lists.is_empty() {
+                    layout.space(6.0);
+                }
+            }
+            Event::Start(Tag::Item) => {
+                flush(&mut layout, &mut block);
+                block.indent = 15.0 * lists.len().saturating_sub(1) as f32;
+                block.prefix = match lists.last_mut() {
+                    Some(Some(number)) => {
+                        let prefix = format!("{}. ", number);
+                        *number += 1;
+                        prefix
+                    }
+                    _ => "- ".to_string(),
+                };
+            }
+            Event::End(TagEnd::Item) => {
+                flush(&mut layout, &mut block);
+                block.indent = 0.0;
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                flush(&mut layout, &mut block);
+                in_code_block = true;
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                layout.space(6.0);
+            }
+            Event::Start(Tag::TableCell) => {
+                if !block.text.is_empty() {
+                    block.text.push_str(" | ");
+                }
+            }
+            Event::End(TagEnd::TableHead) => {
+                block.font = PdfFont::Bold;
+                flush(&mut layout, &mut block);
+                block.font = PdfFont::Regular;
+            }
+            Event::End(TagEnd::TableRow) => flush(&mut layout, &mut block),
+            Event::End(TagEnd::Table) => layout.space(6.0),
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                flush(&mut layout, &mut block);
+                if let Some(thumb) = frame_id_from_url(&dest_url).and_then(|id| thumbnails.get(&id))
+                {
+                    layout.image(thumb);
+                }
+                in_image = true;
+            }
+            Event::End(TagEnd::Image) => in_image = false,
+            Event::Text(text) | Event::Code(text) if in_code_block => {
+                for line in text.lines() {
+                    layout.text(line, PdfFont::Mono, 9.0, 10.0);
+                }
+            }
+            Event::Text(text) | Event::Code(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                if !in_image {
+                    block.text.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => block.text.push(' '),
+            Event::Rule => {
+                flush(&mut layout, &mut block);
+                layout.rule();
+            }
+            _ => {}
+        }
+    }
+    flush(&mut layout, &mut block);
+
+    let (pages, images) = layout.finish();
+    write_pdf(&pages, &images)
+}
+
+/// Serialize page content streams and images into a PDF file
+fn write_pdf(pages: &[String], images: &[&FrameThumbnail]) -> Vec<u8> {
+    // Object layout: 1 catalog, 2 page tree, 3-5 fonts, then images, then page/content pairs
+    let first_image = 6;
+    let first_page = first_image + images.len();
+    let page_id = |i: usize| first_page + i * 2;
+
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+
+    let kids = (0..pages.len())
+        .map(|i| format!("{} 0 R", page_id(i)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    objects
+        .push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids, pages.len()).into_bytes());
+
+    for base_font in ["Helvetica", "Helvetica-Bold", "Courier"] {
+        objects.push(
+            format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                base_font
+            )
+            .into_bytes(),
+        );
+    }
+
+    for image in images {
+        let mut obj = format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+            image.width,
+            image.height,
+            image.jpeg.len()
+        )
+        .into_bytes();
+        obj.extend_from_slice(&image.jpeg);
+        obj.extend_from_slice(b"\nendstream");
+        objects.push(obj);
+    }
+
+    let xobjects = (0..images.len())
+        .map(|i| format!("/Im{} {} 0 R", i, first_image + i))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> /XObject << {} >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                xobjects,
+                page_id(i) + 1
+            )
+            .into_bytes(),
+        );
+        objects.push(
+            format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                content.len(),
+                content
+            )
+            .into_bytes(),
+        );
+    }
+
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, obj) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(obj);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref_offset = pdf.len();
+    let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(xref, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        xref,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    );
+    pdf.extend_from_slice(xref.as_bytes());
+
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thumbnail() -> FrameThumbnail {
+        FrameThumbnail {
+            jpeg: vec![0xff, 0xd8, 0xff, 0xd9],
+            width: 320,
+            height: 180,
+        }
+    }
+
+    #[test]
+    fn test_referenced_frame_ids() {
+        let markdown = "See ![a](/api/frames/12/image) and ![b](http://localhost:3131/api/frames/7/image), again ![c](/api/frames/12/image)";
+        assert_eq!(referenced_frame_ids(markdown), vec![12, 7]);
+    }
+
+    #[test]
+    fn test_html_embeds_thumbnails_and_escapes_html() {
+        let mut thumbnails = HashMap::new();
+        thumbnails.insert(12, thumbnail());
+
+        let html = render_html(
+            "Daily <report>",
+            "2025-12-10",
+            "# Summary\n\n![frame](/api/frames/12/image)\n\n<script>alert(1)</script>",
+            &thumbnails,
+        );
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Daily &lt;report&gt;</title>"));
+        assert!(html.contains("<h1>Summary</h1>"));
+        assert!(html.contains("src=\"data:image/jpeg;base64,/9j/2Q==\""));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_html_keeps_only_safe_links() {
+        let html = render_html(
+            "Report",
+            "2025-12-10",
+            "[web](https://example.com) [mail](mailto:a@example.com) [frame](/api/frames/12) \
+             [anchor](#summary) [js](javascript:alert(1)) [upper](JavaScript:alert(1)) \
+             [data](data:text/html,x) [spaced](<java\tscript:alert(1)>)",
+            &HashMap::new(),
+        );
+
+        assert!(html.contains("<a href=\"https://example.com\">web</a>"));
+        assert!(html.contains("<a href=\"mailto:a@example.com\">mail</a>"));
+        assert!(html.contains("<a href=\"/api/frames/12\">frame</a>"));
+        assert!(html.contains("<a href=\"#summary\">anchor</a>"));
+        assert!(!html.contains("script:"));
+        assert!(!html.contains("data:text"));
+        assert!(html.contains(" js upper data spaced"));
+    }
+
+    #[test]
+    fn test_pdf_structure() {
+        let mut thumbnails = HashMap::new();
+        thumbnails.insert(3, thumbnail());
+
+        let pdf = render_pdf(
+            "Weekly report",
+            "2025-12-01 to 2025-12-07",
+            "# Summary\n\n- one\n- two (with parens)\n\n![frame](/api/frames/3/image)",
+            &thumbnails,
+        );
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        assert!(text.contains("/Subtype /Image"));
+        assert!(text.contains("(- two \\(with parens\\)) Tj"));
+    }
+
+    #[test]
+    fn test_pdf_paginates_long_reports() {
+        let markdown = "Lorem ipsum dolor sit amet.\n\n".repeat(200);
+        let pdf = render_pdf("Report", "", &markdown, &HashMap::new());
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.matches("/Type /Page ").count() > 1);
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(
+            wrap_text("the quick brown fox jumps", 10),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+        assert_eq!(wrap_text("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_pdf_string_escapes() {
+        assert_eq!(pdf_string("a(b)\\c"), "a\\(b\\)\\\\c");
+        assert_eq!(pdf_string("café – ok"), "caf\\351 - ok");
+        assert_eq!(pdf_string("日本"), "??");
+    }
+}
@@ -0,0 +1,521 @@
+//! GraphQL API
+//!
+//! `POST /api/graphql` answers GraphQL queries over frames, their OCR text
+//! and tags, app sessions and full-text search, so a client can fetch nested
+//! data (a frame with its tags, OCR text and similar frames) in one request
+//! instead of one REST call per frame. `GET /api/graphql` returns the schema
+//! in SDL.
+//!
+//! Only compiled with the `graphql` feature. The schema is read-only; pins,
+//! tags and deletions stay on the REST endpoints. Tags and OCR text of all
+//! the frames in a response are loaded together, one query per level of
+//! nesting.
+
+use crate::daily_notes::{self, Session};
+use crate::state::AppState;
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject, ID};
+use chrono::{DateTime, Duration, Utc};
+use screensearch_db::{
+    FrameFilter, FrameRecord, OcrTextRecord, Pagination, SearchRanking, SearchResult, TagRecord,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use tracing::warn;
+
+/// The GraphQL schema
+pub type ScreenSearchSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Deepest nesting a query may use
+const MAX_DEPTH: usize = 12;
+/// Most fields a query may resolve, counting each list once
+const MAX_COMPLEXITY: usize = 2000;
+/// Most results a list field returns
+const MAX_LIMIT: i64 = 1000;
+/// Days of sessions a query covers at most
+const MAX_SESSION_DAYS: i64 = 31;
+
+/// The schema, built on first use
+pub fn schema() -> &'static ScreenSearchSchema {
+    static SCHEMA: OnceLock<ScreenSearchSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .limit_depth(MAX_DEPTH)
+            .limit_complexity(MAX_COMPLEXITY)
+            .finish()
+    })
+}
+
+/// Execute a request against the state of the server
+///
+/// Each request gets its own loaders, so nothing is cached between
+/// requests.
+pub async fn execute(
+    state: Arc<AppState>,
+    request: async_graphql::Request,
+) -> async_graphql::Response {
+    let tags = DataLoader::new(TagLoader(Arc::clone(&state)), tokio::spawn);
+    let ocr = DataLoader::new(OcrLoader(Arc::clone(&state)), tokio::spawn);
+    schema()
+        .execute(request.data(state).data(tags).data(ocr))
+        .await
+}
+
+fn state<'a>(ctx: &Context<'a>) -> &'a Arc<AppState> {
+    ctx.data_unchecked::<Arc<AppState>>()
+}
+
+fn pagination(limit: i64, offset: i64) -> Pagination {
+    Pagination {
+        limit: limit.clamp(1, MAX_LIMIT),
+        offset: offset.max(0),
+    }
+}
+
+async fn load_ocr(ctx: &Context<'_>, frame_id: i64) -> async_graphql::Result<Vec<OcrTextRecord>> {
+    let records = ctx
+        .data_unchecked::<DataLoader<OcrLoader>>()
+        .load_one(frame_id)
+        .await?;
+    Ok(records.unwrap_or_default())
+}
+
+fn frame_id(id: &ID) -> async_graphql::Result<i64> {
+    id.parse()
+        .map_err(|_| async_graphql::Error::new(format!("Invalid frame ID: {}", id.as_str())))
+}
+
+/// Tags of frames, by frame ID
+struct TagLoader(Arc<AppState>);
+
+impl Loader<i64> for TagLoader {
+    type Value = Vec<TagRecord>;
+    type Error = Arc<screensearch_db::DatabaseError>;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Self::Value>, Self::Error> {
+        self.0.db.get_tags_for_frames(keys).await.map_err(Arc::new)
+    }
+}
+
+/// OCR text of frames, by frame ID
+struct OcrLoader(Arc<AppState>);
+
+impl Loader<i64> for OcrLoader {
+    type Value = Vec<OcrTextRecord>;
+    type Error = Arc<screensearch_db::DatabaseError>;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Self::Value>, Self::Error> {
+        self.0
+            .db
+            .get_ocr_text_for_frames(keys)
+            .await
+            .map_err(Arc::new)
+    }
+}
+
+/// Root of every query
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// A frame by ID
+    async fn frame(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<Option<Frame>> {
+        let frame = state(ctx).db.get_frame(frame_id(&id)?).await?;
+        Ok(frame.map(Frame))
+    }
+
+    /// Frames captured in a time range, newest first
+    #[allow(clippy::too_many_arguments)]
+    async fn frames(
+        &self,
+        ctx: &Context<'_>,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        #[graphql(desc = "Process name of the active app")] app: Option<String>,
+        monitor_index: Option<i32>,
+        #[graphql(default = 100)] limit: i64,
+        #[graphql(default)] offset: i64,
+    ) -> async_graphql::Result<Vec<Frame>> {
+        let filter = FrameFilter {
+            app_name: app,
+            monitor_index,
+            ..Default::default()
+        };
+        let frames = state(ctx)
+            .db
+            .get_frames_in_range(
+                start_time.unwrap_or_default(),
+                end_time.unwrap_or_else(Utc::now),
+                filter,
+                pagination(limit, offset),
+            )
+            .await?;
+        Ok(frames.into_iter().map(Frame).collect())
+    }
+
+    /// Full-text search over OCR text, one hit per frame, best first
+    ///
+    /// Ranked with the weights kept in the settings, like `GET /search`.
+    #[allow(clippy::too_many_arguments)]
+    async fn search(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        #[graphql(desc = "Process name of the active app")] app: Option<String>,
+        #[graphql(default = 20)] limit: i64,
+        #[graphql(default)] offset: i64,
+    ) -> async_graphql::Result<Vec<SearchHit>> {
+        if query.trim().is_empty() {
+            return Err("Search query cannot be empty".into());
+        }
+
+        let db = &state(ctx).db;
+        let settings = db.get_settings().await?;
+        let ranking = serde_json::from_str::<SearchRanking>(&settings.search_ranking)
+            .unwrap_or_else(|e| {
+                warn!("Ignoring invalid search_ranking setting: {}", e);
+                SearchRanking::default()
+            });
+        let filter = FrameFilter {
+            start_time,
+            end_time,
+            app_name: app,
+            ..Default::default()
+        };
+        let results = db
+            .search_ocr_text_ranked(&query, filter, pagination(limit, offset), &ranking)
+            .await?;
+        Ok(results.into_iter().map(SearchHit).collect())
+    }
+
+    /// Tags by name
+    async fn tags(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 100)] limit: i64,
+        #[graphql(default)] offset: i64,
+    ) -> async_graphql::Result<Vec<Tag>> {
+        let tags = state(ctx).db.list_tags(pagination(limit, offset)).await?;
+        Ok(tags.into_iter().map(Tag).collect())
+    }
+
+    /// A tag by ID
+    async fn tag(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<Option<Tag>> {
+        let id = id
+            .parse()
+            .map_err(|_| async_graphql::Error::new(format!("Invalid tag ID: {}", id.as_str())))?;
+        Ok(state(ctx).db.get_tag(id).await?.map(Tag))
+    }
+
+    /// Time spent in one app without a break, oldest first
+    ///
+    /// Covers the day before `end` unless `start` is given, and 31 days at
+    /// most.
+    async fn sessions(
+        &self,
+        ctx: &Context<'_>,
+        start: Option<DateTime<Utc>>,
+        #[graphql(desc = "End of the range (default: now)")] end: Option<DateTime<Utc>>,
+    ) -> async_graphql::Result<Vec<AppSession>> {
+        let end = end.unwrap_or_else(Utc::now);
+        let start = start.unwrap_or_else(|| end - Duration::days(1));
+        if start >= end {
+            return Err("start must be before end".into());
+        }
+        if end - start > Duration::days(MAX_SESSION_DAYS) {
+            return Err(format!("The range can span {} days at most", MAX_SESSION_DAYS).into());
+        }
+
+        let sessions = daily_notes::load_sessions(&state(ctx).db, start, end).await?;
+        Ok(sessions.into_iter().map(AppSession).collect())
+    }
+}
+
+/// A captured screen
+pub struct Frame(FrameRecord);
+
+#[Object]
+impl Frame {
+    async fn id(&self) -> ID {
+        self.0.id.into()
+    }
+
+    async fn timestamp(&self) -> DateTime<Utc> {
+        self.0.timestamp
+    }
+
+    async fn monitor_index(&self) -> i32 {
+        self.0.monitor_index
+    }
+
+    async fn device_name(&self) -> &str {
+        &self.0.device_name
+    }
+
+    /// Process name of the active app
+    async fn app(&self) -> Option<&str> {
+        self.0.active_process.as_deref()
+    }
+
+    /// Title of the active window
+    async fn window(&self) -> Option<&str> {
+        self.0.active_window.as_deref()
+    }
+
+    async fn browser_url(&self) -> Option<&str> {
+        self.0.browser_url.as_deref()
+    }
+
+    async fn width(&self) -> i32 {
+        self.0.width
+    }
+
+    async fn height(&self) -> i32 {
+        self.0.height
+    }
+
+    /// Whether the monitor had the focused window, when known
+    async fn focused(&self) -> Option<bool> {
+        self.0.focused
+    }
+
+    /// Exempt from retention, compaction, archiving and purges
+    async fn pinned(&self) -> bool {
+        self.0.pinned
+    }
+
+    /// Path of the image, served by `GET /api/frames/{id}/image`
+    async fn image_url(&self) -> String {
+        format!("/api/frames/{}/image", self.0.id)
+    }
+
+    /// Text regions read by OCR
+    async fn ocr(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<OcrText>> {
+        let records = load_ocr(ctx, self.0.id).await?;
+        Ok(records.into_iter().map(OcrText).collect())
+    }
+
+    /// All OCR text of the frame, space-separated
+    async fn text(&self, ctx: &Context<'_>) -> async_graphql::Result<String> {
+        let records = load_ocr(ctx, self.0.id).await?;
+        Ok(records
+            .into_iter()
+            .map(|r| r.text)
+            .collect::<Vec<_>>()
+            .join(" "))
+    }
+
+    async fn tags(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Tag>> {
+        let tags = ctx
+            .data_unchecked::<DataLoader<TagLoader>>()
+            .load_one(self.0.id)
+            .await?;
+        Ok(tags.unwrap_or_default().into_iter().map(Tag).collect())
+    }
+
+    /// Frames whose text is closest in meaning, by embedding similarity
+    ///
+    /// Empty until the frame has been embedded. Compares against the stored
+    /// embedding of the frame's first text chunk.
+    async fn similar(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 5)] limit: i64,
+    ) -> async_graphql::Result<Vec<SimilarFrame>> {
+        let state = state(ctx);
+        let Some(embedding) = state.db.get_frame_embedding(self.0.id).await? else {
+            return Ok(Vec::new());
+        };
+
+        // Results come per chunk, best first: skip this frame and the weaker
+        // chunks of frames already listed
+        let limit = limit.clamp(1, MAX_LIMIT);
+        let results = state
+            .db
+            .semantic_search(embedding, limit * 4 + 1, DateTime::default(), Utc::now())
+            .await?;
+        let mut similar: Vec<SimilarFrame> = Vec::new();
+        for result in results {
+            if result.frame.id == self.0.id
+                || similar.iter().any(|s| s.frame.0.id == result.frame.id)
+            {
+                continue;
+            }
+            similar.push(SimilarFrame {
+                frame: Frame(result.frame),
+                score: result.similarity_score,
+            });
+            if similar.len() as i64 == limit {
+                break;
+            }
+        }
+        Ok(similar)
+    }
+}
+
+/// A text region read by OCR, in frame pixels
+pub struct OcrText(OcrTextRecord);
+
+#[Object]
+impl OcrText {
+    async fn id(&self) -> ID {
+        self.0.id.into()
+    }
+
+    async fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    async fn x(&self) -> i32 {
+        self.0.x
+    }
+
+    async fn y(&self) -> i32 {
+        self.0.y
+    }
+
+    async fn width(&self) -> i32 {
+        self.0.width
+    }
+
+    async fn height(&self) -> i32 {
+        self.0.height
+    }
+
+    /// OCR confidence from 0.0 to 1.0
+    async fn confidence(&self) -> f32 {
+        self.0.confidence
+    }
+}
+
+/// A tag and the frames carrying it
+pub struct Tag(TagRecord);
+
+#[Object]
+impl Tag {
+    async fn id(&self) -> ID {
+        self.0.id.into()
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.tag_name
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn color(&self) -> Option<&str> {
+        self.0.color.as_deref()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// Frames carrying the tag, newest first
+    async fn frames(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 100)] limit: i64,
+        #[graphql(default)] offset: i64,
+    ) -> async_graphql::Result<Vec<Frame>> {
+        let frames = state(ctx)
+            .db
+            .get_frames_by_tag(self.0.id, pagination(limit, offset))
+            .await?;
+        Ok(frames.into_iter().map(Frame).collect())
+    }
+}
+
+/// A full-text search hit
+pub struct SearchHit(SearchResult);
+
+#[Object]
+impl SearchHit {
+    async fn frame(&self) -> Frame {
+        Frame(self.0.frame.clone())
+    }
+
+    /// BM25 relevance, higher is better
+    async fn score(&self) -> f32 {
+        self.0.relevance_score
+    }
+
+    /// OCR text regions that matched
+    async fn matches(&self) -> Vec<OcrText> {
+        self.0.ocr_matches.iter().cloned().map(OcrText).collect()
+    }
+
+    /// Excerpts around the matches, terms wrapped in `<mark>` tags
+    async fn snippets(&self) -> Vec<&str> {
+        self.0
+            .highlights
+            .iter()
+            .map(|h| h.snippet.as_str())
+            .collect()
+    }
+}
+
+/// A frame similar to another
+#[derive(SimpleObject)]
+pub struct SimilarFrame {
+    frame: Frame,
+    /// Cosine similarity of the embeddings, from -1.0 to 1.0
+    score: f32,
+}
+
+/// Time spent in one app without a break
+pub struct AppSession(Session);
+
+#[Object(name = "Session")]
+impl AppSession {
+    /// Process name of the app
+    async fn app(&self) -> &str {
+        &self.0.app
+    }
+
+    /// Window title seen most often
+    async fn window(&self) -> Option<&str> {
+        self.0.window.as_deref()
+    }
+
+    async fn start(&self) -> DateTime<Utc> {
+        self.0.start
+    }
+
+    async fn end(&self) -> DateTime<Utc> {
+        self.0.end
+    }
+
+    async fn duration_seconds(&self) -> i64 {
+        self.0.duration().num_seconds()
+    }
+
+    /// Frame in the middle of the session
+    async fn frame(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Frame>> {
+        Ok(state(ctx).db.get_frame(self.0.frame_id).await?.map(Frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema() {
+        let sdl = schema().sdl();
+        for field in [
+            "frame(id: ID!): Frame",
+            "search(",
+            "sessions(",
+            "similar(limit: Int! = 5): [SimilarFrame!]!",
+            "tags: [Tag!]!",
+            "type Session",
+        ] {
+            assert!(sdl.contains(field), "missing {}", field);
+        }
+    }
+}
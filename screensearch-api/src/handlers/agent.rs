@@ -0,0 +1,800 @@
+//! Agent Handlers
+//!
+//! Runs an LLM tool-calling loop that combines screen history retrieval with
+//! UI automation. The model is given a fixed tool schema and every returned
+//! tool call is executed against the database or automation engine.
+
+use crate::error::{AppError, Result};
+use crate::handlers::ai::{
+    add_auth_header, record_interaction, validate_provider_url, OpenAIUsage,
+};
+use crate::local_llm::BUILTIN_PROVIDER;
+use crate::models::ElementInfo;
+use crate::state::AppState;
+use crate::time_expr::resolve_when;
+use axum::extract::{Json, State};
+use chrono::{DateTime, Utc};
+use screensearch_automation::{MouseButton as ClickButton, Selector as ElementSelector};
+use screensearch_db::{ContextChunk, FrameFilter, NewAiInteraction, Pagination};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, error, info, warn};
+
+/// Default number of model turns per run
+const DEFAULT_MAX_STEPS: u32 = 10;
+/// Hard upper bound on model turns per run
+const MAX_AGENT_STEPS: u32 = 25;
+/// Maximum characters of tool output returned to the model
+const MAX_TOOL_OUTPUT_CHARS: usize = 4000;
+/// Maximum search results / elements returned by a single tool call
+const MAX_TOOL_RESULTS: i64 = 10;
+
+const AGENT_SYSTEM_PROMPT: &str = r#"You are ScreenSearch Agent, an assistant that can look through the user's recorded screen history and operate their desktop.
+
+TOOLS:
+- Use search_history and read_frame to find information the user has seen before.
+- Use find_element to locate UI elements and their screen coordinates before clicking.
+- Only click, type or open applications when the task requires it.
+
+Call one tool at a time and check its result before continuing. When the task is complete, reply with a short summary of what you did and what you found, without calling any tools."#;
+
+// ============================================================
+// Models
+// ============================================================
+
+#[derive(Debug, Deserialize)]
+pub struct AgentRunRequest {
+    pub provider_url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+
+    /// Task description for the agent
+    pub task: String,
+    /// Maximum model turns (default: 10, max: 25)
+    pub max_steps: Option<u32>,
+    /// Only expose retrieval tools (no clicking, typing or launching apps)
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgentRunResponse {
+    /// "completed", "stopped", "max_steps"
+    pub status: String,
+    /// Final answer from the model, if it finished
+    pub answer: Option<String>,
+    pub steps: Vec<AgentStep>,
+    pub model_used: String,
+}
+
+/// A single executed tool call
+#[derive(Debug, Serialize)]
+pub struct AgentStep {
+    pub step: u32,
+    pub tool: String,
+    pub arguments: Value,
+    pub success: bool,
+    pub output: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgentStopResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+// OpenAI Chat Completion schema with tool calling (Simplified)
+#[derive(Debug, Serialize)]
+struct ToolChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [ToolMessage],
+    tools: &'a [Value],
+    tool_choice: &'static str,
+    temperature: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ToolMessage {
+    role: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl ToolMessage {
+    fn text(role: &str, content: impl Into<String>) -> Self {
+        Self {
+            role: role.to_string(),
+            content: Some(content.into()),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    fn tool_result(tool_call_id: &str, content: String) -> Self {
+        Self {
+            role: "tool".to_string(),
+            content: Some(content),
+            tool_calls: None,
+            tool_call_id: Some(tool_call_id.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ToolCall {
+    id: String,
+    #[serde(rename = "type", default = "default_tool_type")]
+    kind: String,
+    function: ToolFunction,
+}
+
+fn default_tool_type() -> String {
+    "function".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ToolFunction {
+    name: String,
+    #[serde(default)]
+    arguments: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolChatResponse {
+    choices: Vec<ToolChoice>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolChoice {
+    message: ToolMessage,
+}
+
+// Tool arguments
+#[derive(Debug, Deserialize)]
+struct SearchHistoryArgs {
+    query: String,
+    limit: Option<i64>,
+    /// Natural-language time range, e.g. "yesterday afternoon"
+    when: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadFrameArgs {
+    frame_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct FindElementArgs {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClickArgs {
+    x: i32,
+    y: i32,
+    button: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TypeTextArgs {
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAppArgs {
+    app_name: String,
+}
+
+// ============================================================
+// Tool Schema
+// ============================================================
+
+/// Tools that only read data and never touch the desktop
+const READ_ONLY_TOOLS: &[&str] = &["search_history", "read_frame", "find_element"];
+
+/// Tools that drive UI automation and are recorded in the audit log
+const AUTOMATION_TOOLS: &[&str] = &["find_element", "click", "type_text", "open_app"];
+
+fn function_tool(name: &str, description: &str, parameters: Value) -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": name,
+            "description": description,
+            "parameters": parameters,
+        }
+    })
+}
+
+/// Build the OpenAI tool schema offered to the model
+fn tool_schema(read_only: bool) -> Vec<Value> {
+    let tools = vec![
+        function_tool(
+            "search_history",
+            "Full-text search over OCR text captured from the user's screen. Returns matching frames with timestamp, app, window and snippet.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Search text" },
+                    "limit": { "type": "integer", "description": "Maximum results (1-10)" },
+                    "when": { "type": "string", "description": "Optional time range in plain words, e.g. \"yesterday afternoon\", \"last tuesday morning\" or \"past 3 hours\"" }
+                },
+                "required": ["query"]
+            }),
+        ),
+        function_tool(
+            "read_frame",
+            "Read the metadata and full OCR text of a captured frame.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "frame_id": { "type": "integer", "description": "Frame ID from search_history" }
+                },
+                "required": ["frame_id"]
+            }),
+        ),
+        function_tool(
+            "find_element",
+            "Find UI elements on the current screen by name. Returns control type and screen bounds.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Element name or label" }
+                },
+                "required": ["name"]
+            }),
+        ),
+        function_tool(
+            "click",
+            "Click at absolute screen coordinates.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "x": { "type": "integer" },
+                    "y": { "type": "integer" },
+                    "button": { "type": "string", "enum": ["left", "right", "middle"] }
+                },
+                "required": ["x", "y"]
+            }),
+        ),
+        function_tool(
+            "type_text",
+            "Type text into the focused element.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string" }
+                },
+                "required": ["text"]
+            }),
+        ),
+        function_tool(
+            "open_app",
+            "Launch an application by name (e.g. \"notepad\", \"chrome\").",
+            json!({
+                "type": "object",
+                "properties": {
+                    "app_name": { "type": "string" }
+                },
+                "required": ["app_name"]
+            }),
+        ),
+    ];
+
+    tools
+        .into_iter()
+        .filter(|tool| {
+            !read_only
+                || tool["function"]["name"]
+                    .as_str()
+                    .is_some_and(|name| READ_ONLY_TOOLS.contains(&name))
+        })
+        .collect()
+}
+
+/// Truncate tool output to keep the conversation within the model's context
+fn truncate_output(mut output: String) -> String {
+    if let Some((idx, _)) = output.char_indices().nth(MAX_TOOL_OUTPUT_CHARS) {
+        output.truncate(idx);
+        output.push_str("\n[truncated]");
+    }
+    output
+}
+
+// ============================================================
+// Tool Execution
+// ============================================================
+
+fn parse_args<T: for<'de> Deserialize<'de>>(arguments: &str) -> std::result::Result<T, String> {
+    let arguments = if arguments.trim().is_empty() {
+        "{}"
+    } else {
+        arguments
+    };
+    serde_json::from_str(arguments).map_err(|e| format!("Invalid arguments: {}", e))
+}
+
+/// Execute a single tool call, returning its textual output
+///
+/// Frames whose content the output includes are added to `context`.
+async fn execute_tool(
+    state: &AppState,
+    name: &str,
+    arguments: &str,
+    read_only: bool,
+    context: &mut Vec<ContextChunk>,
+) -> std::result::Result<String, String> {
+    if read_only && !READ_ONLY_TOOLS.contains(&name) {
+        return Err(format!("Tool '{}' is not allowed in read-only mode", name));
+    }
+
+    match name {
+        "search_history" => {
+            let args: SearchHistoryArgs = parse_args(arguments)?;
+            let pagination = Pagination {
+                limit: args.limit.unwrap_or(5).clamp(1, MAX_TOOL_RESULTS),
+                offset: 0,
+            };
+            let mut filter = FrameFilter::default();
+            if let Some(when) = args.when.as_deref().filter(|w| !w.trim().is_empty()) {
+                let range = resolve_when(when, None).map_err(|e| e.to_string())?;
+                filter.start_time = Some(range.start);
+                filter.end_time = Some(range.end);
+            }
+            let results = state
+                .db
+                .search_ocr_text(&args.query, filter, pagination)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            if results.is_empty() {
+                return Ok("No matching frames found.".to_string());
+            }
+            context.extend(results.iter().map(|result| ContextChunk {
+                frame_id: result.frame.id,
+                chunk_index: None,
+            }));
+
+            let lines: Vec<String> = results
+                .iter()
+                .map(|result| {
+                    let snippet = result
+                        .highlights
+                        .first()
+                        .map(|h| h.snippet.clone())
+                        .or_else(|| result.ocr_matches.first().map(|o| o.text.clone()))
+                        .unwrap_or_default();
+                    format!(
+                        "frame_id={} time={} app={} window={} snippet={}",
+                        result.frame.id,
+                        result.frame.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                        result.frame.active_process.as_deref().unwrap_or("unknown"),
+                        result.frame.active_window.as_deref().unwrap_or("unknown"),
+                        snippet.replace('\n', " ")
+                    )
+                })
+                .collect();
+            Ok(lines.join("\n"))
+        }
+        "read_frame" => {
+            let args: ReadFrameArgs = parse_args(arguments)?;
+            let frame = state
+                .db
+                .get_frame(args.frame_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Frame {} not found", args.frame_id))?;
+            let ocr = state
+                .db
+                .get_ocr_text_for_frame(frame.id)
+                .await
+                .map_err(|e| e.to_string())?;
+
+            context.push(ContextChunk {
+                frame_id: frame.id,
+                chunk_index: None,
+            });
+            let text: Vec<&str> = ocr.iter().map(|o| o.text.as_str()).collect();
+            Ok(format!(
+                "frame_id={} time={} app={} window={} url={}\n\n{}",
+                frame.id,
+                frame.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                frame.active_process.as_deref().unwrap_or("unknown"),
+                frame.active_window.as_deref().unwrap_or("unknown"),
+                frame.browser_url.as_deref().unwrap_or("none"),
+                text.join("\n")
+            ))
+        }
+        "find_element" => {
+            let args: FindElementArgs = parse_args(arguments)?;
+            let selector = ElementSelector::name(&args.name);
+            let elements = state
+                .automation
+                .find_elements(&selector)
+                .map_err(|e| e.to_string())?;
+
+            let infos = elements
+                .iter()
+                .take(MAX_TOOL_RESULTS as usize)
+                .map(ElementInfo::from_ui_element)
+                .collect::<Result<Vec<_>>>()
+                .map_err(|e| e.to_string())?;
+
+            if infos.is_empty() {
+                return Ok(format!("No elements named '{}' found.", args.name));
+            }
+            serde_json::to_string(&infos).map_err(|e| e.to_string())
+        }
+        "click" => {
+            let args: ClickArgs = parse_args(arguments)?;
+            let button = match args.button.as_deref() {
+                Some("right") => ClickButton::Right,
+                Some("middle") => ClickButton::Middle,
+                _ => ClickButton::Left,
+            };
+            state
+                .automation
+                .click(args.x, args.y, button)
+                .map_err(|e| e.to_string())?;
+            Ok(format!("Clicked at ({}, {})", args.x, args.y))
+        }
+        "type_text" => {
+            let args: TypeTextArgs = parse_args(arguments)?;
+            state
+                .automation
+                .type_text(&args.text, None)
+                .map_err(|e| e.to_string())?;
+            Ok(format!("Typed {} characters", args.text.chars().count()))
+        }
+        "open_app" => {
+            let args: OpenAppArgs = parse_args(arguments)?;
+            state
+                .automation
+                .open_app(&args.app_name)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(format!("Opened application: {}", args.app_name))
+        }
+        other => Err(format!("Unknown tool '{}'", other)),
+    }
+}
+
+/// Send the conversation to the provider and return the assistant message
+/// with the tokens it used
+async fn request_completion(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &AgentRunRequest,
+    messages: &[ToolMessage],
+    tools: &[Value],
+) -> Result<(ToolMessage, Option<OpenAIUsage>)> {
+    let body = ToolChatRequest {
+        model: &payload.model,
+        messages,
+        tools,
+        tool_choice: "auto",
+        temperature: 0.2,
+    };
+
+    let request_builder = add_auth_header(client.post(url).json(&body), &payload.api_key);
+    let res = request_builder.send().await.map_err(|e| {
+        error!("Failed to contact AI provider: {}", e);
+        AppError::Internal(format!("Failed to contact AI provider: {}", e))
+    })?;
+
+    let status = res.status();
+    if !status.is_success() {
+        let error_text = res.text().await.unwrap_or_default();
+        error!(
+            "AI Provider Error. Status: {}, Response: {}",
+            status, error_text
+        );
+        return Err(AppError::Internal(format!(
+            "AI Provider returned HTTP {}. Ensure the model supports tool calling.",
+            status
+        )));
+    }
+
+    let response_text = res
+        .text()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to read response body: {}", e)))?;
+
+    let response: ToolChatResponse = serde_json::from_str(&response_text).map_err(|e| {
+        error!(
+            "Failed to parse AI response. Parse error: {}, Body preview: {}",
+            e,
+            response_text.chars().take(200).collect::<String>()
+        );
+        AppError::Internal("Failed to parse AI response (invalid JSON format).".to_string())
+    })?;
+
+    let message = response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message)
+        .ok_or_else(|| AppError::Internal("AI provider returned no choices".to_string()))?;
+    Ok((message, response.usage))
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// POST /agent/run - Run an agent task
+///
+/// Gives the configured LLM a tool schema (search history, read frame, find
+/// element, click, type, open app) and executes the returned tool calls until
+/// the model answers, the step limit is reached or `/agent/stop` is called.
+///
+/// # Request Body
+/// - provider_url: OpenAI-compatible provider URL
+/// - api_key: Optional API key
+/// - model: Model name (must support tool calling)
+/// - task: Task description
+/// - max_steps: Optional maximum model turns (default: 10, max: 25)
+/// - read_only: Only expose retrieval tools (default: false)
+pub async fn run_agent(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<AgentRunRequest>,
+) -> Result<Json<AgentRunResponse>> {
+    debug!("Agent run request: task={}", payload.task);
+
+    if payload.task.trim().is_empty() {
+        return Err(AppError::InvalidRequest(
+            "Task must not be empty".to_string(),
+        ));
+    }
+    if payload.provider_url == BUILTIN_PROVIDER {
+        return Err(AppError::InvalidRequest(
+            "The built-in model does not support tool calling; use an OpenAI-compatible provider"
+                .to_string(),
+        ));
+    }
+    if let Err(err_msg) = validate_provider_url(&payload.provider_url) {
+        return Err(AppError::InvalidRequest(format!(
+            "Invalid provider URL: {}",
+            err_msg
+        )));
+    }
+
+    let max_steps = payload
+        .max_steps
+        .unwrap_or(DEFAULT_MAX_STEPS)
+        .clamp(1, MAX_AGENT_STEPS);
+
+    info!(
+        "Starting agent run with model {} ({} max steps)",
+        payload.model, max_steps
+    );
+
+    let started = Instant::now();
+    let mut steps: Vec<AgentStep> = Vec::new();
+    let mut usage = OpenAIUsage::default();
+    let mut context = Vec::new();
+    let outcome = run_steps(
+        &state,
+        &payload,
+        max_steps,
+        &mut steps,
+        &mut usage,
+        &mut context,
+    )
+    .await;
+
+    // One history entry per run, with the tokens of all its model turns
+    let (prompt_tokens, completion_tokens, total_tokens) = usage.columns();
+    record_interaction(
+        &state,
+        NewAiInteraction {
+            kind: "agent".to_string(),
+            provider_url: payload.provider_url.clone(),
+            model: payload.model.clone(),
+            system_prompt: Some(AGENT_SYSTEM_PROMPT.to_string()),
+            prompt: payload.task.clone(),
+            context_chunks: context,
+            response: outcome.as_ref().ok().and_then(|(_, answer)| answer.clone()),
+            error: outcome.as_ref().err().map(ToString::to_string),
+            latency_ms: started.elapsed().as_millis() as i64,
+            prompt_tokens,
+            completion_tokens,
+            total_tokens,
+        },
+    )
+    .await;
+
+    let (status, answer) = outcome?;
+    Ok(Json(finish(&payload, status, answer, steps)))
+}
+
+/// Run model turns and their tool calls until the model answers, the step
+/// limit is reached or the run is stopped
+///
+/// Returns the run status and the model's answer. Executed tool calls are
+/// added to `steps`, the tokens of each turn to `usage` and the frames tools
+/// returned to `context`.
+async fn run_steps(
+    state: &AppState,
+    payload: &AgentRunRequest,
+    max_steps: u32,
+    steps: &mut Vec<AgentStep>,
+    usage: &mut OpenAIUsage,
+    context: &mut Vec<ContextChunk>,
+) -> Result<(&'static str, Option<String>)> {
+    // Any call to /agent/stop after this point aborts the run
+    let generation = state.agent_stop_generation.load(Ordering::SeqCst);
+    let stopped = || state.agent_stop_generation.load(Ordering::SeqCst) != generation;
+
+    let client = reqwest::Client::new();
+    let url = format!(
+        "{}/chat/completions",
+        payload.provider_url.trim_end_matches('/')
+    );
+    let tools = tool_schema(payload.read_only);
+
+    let mut messages = vec![
+        ToolMessage::text("system", AGENT_SYSTEM_PROMPT),
+        ToolMessage::text("user", payload.task.clone()),
+    ];
+
+    for turn in 0..max_steps {
+        if stopped() {
+            warn!("Agent run stopped by kill switch");
+            return Ok(("stopped", None));
+        }
+
+        let (reply, turn_usage) =
+            request_completion(&client, &url, payload, &messages, &tools).await?;
+        if let Some(turn_usage) = turn_usage {
+            usage.add(&turn_usage);
+        }
+        let tool_calls = reply.tool_calls.clone().unwrap_or_default();
+
+        if tool_calls.is_empty() {
+            info!("Agent run completed after {} turns", turn + 1);
+            return Ok(("completed", reply.content));
+        }
+
+        messages.push(reply);
+
+        for call in tool_calls {
+            if stopped() {
+                warn!("Agent run stopped by kill switch");
+                return Ok(("stopped", None));
+            }
+
+            let step = steps.len() as u32 + 1;
+            let started_at = Utc::now();
+            let timer = Instant::now();
+            info!(
+                "Agent step {}: {}({})",
+                step, call.function.name, call.function.arguments
+            );
+
+            let result = execute_tool(
+                state,
+                &call.function.name,
+                &call.function.arguments,
+                payload.read_only,
+                context,
+            )
+            .await;
+
+            if AUTOMATION_TOOLS.contains(&call.function.name.as_str()) {
+                crate::audit::record_agent_action(
+                    &state.db,
+                    &call.function.name,
+                    &call.function.arguments,
+                    result.as_ref().err().map(String::as_str),
+                    timer.elapsed(),
+                )
+                .await;
+            }
+
+            let (success, output) = match result {
+                Ok(output) => (true, truncate_output(output)),
+                Err(e) => {
+                    warn!("Agent step {} failed: {}", step, e);
+                    (false, format!("Error: {}", e))
+                }
+            };
+
+            messages.push(ToolMessage::tool_result(&call.id, output.clone()));
+            steps.push(AgentStep {
+                step,
+                tool: call.function.name,
+                arguments: serde_json::from_str(&call.function.arguments).unwrap_or(Value::Null),
+                success,
+                output,
+                started_at,
+                duration_ms: timer.elapsed().as_millis() as u64,
+            });
+        }
+    }
+
+    warn!("Agent run reached the step limit ({})", max_steps);
+    Ok(("max_steps", None))
+}
+
+fn finish(
+    payload: &AgentRunRequest,
+    status: &str,
+    answer: Option<String>,
+    steps: Vec<AgentStep>,
+) -> AgentRunResponse {
+    AgentRunResponse {
+        status: status.to_string(),
+        answer,
+        steps,
+        model_used: payload.model.clone(),
+    }
+}
+
+/// POST /agent/stop - Kill switch
+///
+/// Aborts every running agent before its next model turn or tool call.
+pub async fn stop_agent(State(state): State<Arc<AppState>>) -> Json<AgentStopResponse> {
+    state.agent_stop_generation.fetch_add(1, Ordering::SeqCst);
+    info!("Agent kill switch triggered");
+
+    Json(AgentStopResponse {
+        success: true,
+        message: "All running agents will stop before their next step".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_names(tools: &[Value]) -> Vec<&str> {
+        tools
+            .iter()
+            .filter_map(|t| t["function"]["name"].as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_tool_schema_read_only() {
+        assert_eq!(tool_schema(false).len(), 6);
+        assert_eq!(tool_names(&tool_schema(true)), READ_ONLY_TOOLS);
+    }
+
+    #[test]
+    fn test_parse_tool_call_response() {
+        let body = r#"{"choices":[{"message":{"role":"assistant","content":null,
+            "tool_calls":[{"id":"call_1","type":"function",
+            "function":{"name":"click","arguments":"{\"x\":10,\"y\":20}"}}]}}]}"#;
+        let response: ToolChatResponse = serde_json::from_str(body).unwrap();
+        let calls = response.choices[0].message.tool_calls.clone().unwrap();
+        assert_eq!(calls[0].function.name, "click");
+
+        let args: ClickArgs = parse_args(&calls[0].function.arguments).unwrap();
+        assert_eq!((args.x, args.y), (10, 20));
+        assert!(args.button.is_none());
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        assert_eq!(truncate_output("short".to_string()), "short");
+        let long = "é".repeat(MAX_TOOL_OUTPUT_CHARS + 10);
+        let truncated = truncate_output(long);
+        assert!(truncated.ends_with("[truncated]"));
+        assert_eq!(
+            truncated.chars().count(),
+            MAX_TOOL_OUTPUT_CHARS + "\n[truncated]".chars().count()
+        );
+    }
+}
@@ -1,24 +1,34 @@
 //! AI Integration Handlers
 //!
-//! Handles communication with LLM providers (OpenAI, Ollama) and report generation.
+//! Handles communication with LLM providers (OpenAI, Ollama, or the built-in
+//! model), report generation and chat. Every request sent to a provider is
+//! recorded in `/ai/history`.
 
+use crate::citations::{link_citations, ReportSource, CITATION_INSTRUCTIONS};
 use crate::error::{AppError, Result};
+use crate::local_llm::BUILTIN_PROVIDER;
 use crate::state::AppState;
 use axum::extract::{Json, State};
 use chrono::{DateTime, Duration, Utc};
 use reqwest::RequestBuilder;
+use screensearch_db::NewAiInteraction;
 
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, error, info, warn};
 
+pub use crate::models::{
+    AiChatRequest, AiChatResponse, AiReportRequest, AiReportResponse, OpenAIMessage,
+};
+
 // ============================================================
 // Helper Functions
 // ============================================================
 
 /// Validates that a provider URL is safe to use
 /// Returns Ok(()) if valid, Err with descriptive message if invalid
-fn validate_provider_url(url: &str) -> std::result::Result<(), String> {
+pub(crate) fn validate_provider_url(url: &str) -> std::result::Result<(), String> {
     // Parse the URL
     let parsed = url::Url::parse(url).map_err(|e| format!("Invalid URL format: {}", e))?;
 
@@ -42,7 +52,7 @@ fn validate_provider_url(url: &str) -> std::result::Result<(), String> {
 }
 
 /// Adds Authorization header with Bearer token to request if API key is provided
-fn add_auth_header(builder: RequestBuilder, api_key: &Option<String>) -> RequestBuilder {
+pub(crate) fn add_auth_header(builder: RequestBuilder, api_key: &Option<String>) -> RequestBuilder {
     if let Some(key) = api_key {
         if !key.is_empty() {
             return builder.header("Authorization", format!("Bearer {}", key));
@@ -51,6 +61,127 @@ fn add_auth_header(builder: RequestBuilder, api_key: &Option<String>) -> Request
     builder
 }
 
+/// Reject provider URLs that are neither "builtin" nor valid, see
+/// [`validate_provider_url`]
+pub(crate) fn check_provider(provider_url: &str) -> Result<()> {
+    if provider_url == BUILTIN_PROVIDER {
+        return Ok(());
+    }
+    validate_provider_url(provider_url)
+        .map_err(|err_msg| AppError::InvalidRequest(format!("Invalid provider URL: {}", err_msg)))
+}
+
+/// Name of the model that answers requests for `provider_url` and `model`
+///
+/// The built-in provider always answers with its configured model.
+pub(crate) fn model_used(state: &AppState, provider_url: &str, model: &str) -> String {
+    if provider_url == BUILTIN_PROVIDER {
+        state.local_llm.model_name()
+    } else {
+        model.to_string()
+    }
+}
+
+/// Record a request to an AI provider in `/ai/history`
+///
+/// Returns the ID of the recorded interaction. A failure to record is logged
+/// and does not fail the request.
+pub(crate) async fn record_interaction(
+    state: &AppState,
+    interaction: NewAiInteraction,
+) -> Option<i64> {
+    match state.db.insert_ai_interaction(interaction).await {
+        Ok(id) => Some(id),
+        Err(e) => {
+            error!("Failed to record AI interaction: {}", e);
+            None
+        }
+    }
+}
+
+/// Default report system prompt (Senior Productivity Analyst persona)
+const DEFAULT_REPORT_PROMPT: &str = r#"You are ScreenSearch Intelligence, a Senior Productivity Analyst.
+Your goal is to reconstruct a cohesive narrative of the user's work session based on fragmented screen capture logs and OCR text.
+
+INPUT DATA EXPLANATION:
+- You will receive a list of "Frames" or "Context Chunks".
+- Each item contains Timestamp, App Name, Window Title, and OCR Text (text visible on screen).
+- OCR text may be fragmented or partial.
+- RAG (retrieval) has prioritized relevant chunks based on the user's query.
+
+ANALYSIS INSTRUCTIONS:
+1. SYNTHESIZE, DON'T LIST: Do not just list what the user opened. Explain *what they were doing*. (e.g., instead of "User opened VS Code, then Chrome", say "User was implementing the login feature in VS Code, referencing documentation in Chrome").
+2. USE OCR CONTEXT: Use the OCR text to identify specific topics, document names, or code functions being worked on.
+3. IDENTIFY FLOWS: Group related activities into workflows (e.g., "Research Phase", "Coding Phase", "Communication").
+4. HIGHLIGHT INTERRUPTIONS: Note if the user was frequently context-switching between unrelated apps.
+
+OUTPUT FORMAT (Markdown):
+# Executive Summary
+(2-3 sentences summarizing the main focus of the period)
+
+## Key Activities
+- **[Activity Name]**: Description of work done, citing specific apps and context found in OCR.
+
+## Productivity Analysis
+- **Focus**: [High/Medium/Low] - Explanation.
+- **Tools Used**: List primary tools.
+
+## Timeline
+(Bulleted list of major state changes or milestones)
+"#;
+
+/// Substitute `{name}` variables in a template, leaving unknown ones untouched
+fn render_template(template: &str, vars: &[(&str, String)]) -> String {
+    let mut rendered = template.to_string();
+    for (name, value) in vars {
+        rendered = rendered.replace(&format!("{{{}}}", name), value);
+    }
+    rendered
+}
+
+/// Load a report template and fill in its variables for the given period
+///
+/// Supported variables: `{start}`, `{end}`, `{top_apps}`, `{frame_count}`.
+async fn render_report_template(
+    state: &AppState,
+    template_id: i64,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> Result<String> {
+    let template = state
+        .db
+        .get_report_template(template_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Report template {} not found", template_id)))?;
+
+    let top_apps = state
+        .db
+        .get_top_apps(start_time, end_time, 5)
+        .await?
+        .into_iter()
+        .map(|(app, count)| format!("{} ({} frames)", app, count))
+        .collect::<Vec<_>>();
+    let top_apps = if top_apps.is_empty() {
+        "none recorded".to_string()
+    } else {
+        top_apps.join(", ")
+    };
+
+    let frame_count = state.db.count_frames_in_range(start_time, end_time).await?;
+
+    debug!("Using report template '{}'", template.name);
+
+    Ok(render_template(
+        &template.system_prompt,
+        &[
+            ("start", start_time.format("%Y-%m-%d %H:%M").to_string()),
+            ("end", end_time.format("%Y-%m-%d %H:%M").to_string()),
+            ("top_apps", top_apps),
+            ("frame_count", frame_count.to_string()),
+        ],
+    ))
+}
+
 // ============================================================
 // Models
 // ============================================================
@@ -68,26 +199,6 @@ pub struct AiConnectionResponse {
     pub message: String,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct AiReportRequest {
-    pub provider_url: String,
-    pub api_key: Option<String>,
-    pub model: String,
-
-    // Report Context
-    pub start_time: Option<DateTime<Utc>>,
-    pub end_time: Option<DateTime<Utc>>,
-    pub prompt: Option<String>, // Custom system prompt or overridden instruction
-}
-
-#[derive(Debug, Serialize)]
-pub struct AiReportResponse {
-    pub report: String,
-    pub model_used: String,
-    pub tokens_used: Option<u32>,
-    pub context_source: String,
-}
-
 // OpenAI Chat Completion Request Schema (Simplified)
 #[derive(Debug, Serialize)]
 struct OpenAIChatRequest {
@@ -95,12 +206,8 @@ struct OpenAIChatRequest {
     messages: Vec<OpenAIMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct OpenAIMessage {
-    role: String,
-    content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -114,9 +221,37 @@ struct OpenAIChoice {
     message: OpenAIMessage,
 }
 
-#[derive(Debug, Deserialize)]
-struct OpenAIUsage {
-    total_tokens: u32,
+/// Token usage reported by an OpenAI-compatible provider
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct OpenAIUsage {
+    #[serde(default)]
+    pub prompt_tokens: Option<u32>,
+    #[serde(default)]
+    pub completion_tokens: Option<u32>,
+    #[serde(default)]
+    pub total_tokens: Option<u32>,
+}
+
+impl OpenAIUsage {
+    /// Add the usage of another request of the same conversation
+    pub fn add(&mut self, other: &OpenAIUsage) {
+        let sum = |a: Option<u32>, b: Option<u32>| match (a, b) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+        };
+        self.prompt_tokens = sum(self.prompt_tokens, other.prompt_tokens);
+        self.completion_tokens = sum(self.completion_tokens, other.completion_tokens);
+        self.total_tokens = sum(self.total_tokens, other.total_tokens);
+    }
+
+    /// Prompt, completion and total tokens as stored in `/ai/history`
+    pub fn columns(&self) -> (Option<i64>, Option<i64>, Option<i64>) {
+        (
+            self.prompt_tokens.map(i64::from),
+            self.completion_tokens.map(i64::from),
+            self.total_tokens.map(i64::from),
+        )
+    }
 }
 
 // ============================================================
@@ -126,11 +261,19 @@ struct OpenAIUsage {
 /// POST /ai/validate
 /// Tests connection to the configured AI provider
 pub async fn validate_connection(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
     Json(payload): Json<AiConnectionRequest>,
 ) -> Result<Json<AiConnectionResponse>> {
     debug!("Validating AI connection to {}", payload.provider_url);
 
+    if payload.provider_url == BUILTIN_PROVIDER {
+        let availability = state.local_llm.availability();
+        return Ok(Json(AiConnectionResponse {
+            success: availability.is_ok(),
+            message: availability.unwrap_or_else(|e| e),
+        }));
+    }
+
     // Validate URL format and security
     if let Err(err_msg) = validate_provider_url(&payload.provider_url) {
         return Ok(Json(AiConnectionResponse {
@@ -207,6 +350,16 @@ pub async fn generate_report(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<AiReportRequest>,
 ) -> Result<Json<AiReportResponse>> {
+    run_report(&state, payload).await.map(Json)
+}
+
+/// Build context for the requested period and ask the AI provider for a report
+///
+/// Shared by the `/ai/generate` handler and the scheduled report worker.
+pub async fn run_report(
+    state: &Arc<AppState>,
+    payload: AiReportRequest,
+) -> Result<AiReportResponse> {
     debug!("Generating AI report with model {}", payload.model);
 
     // 1. Fetch Data Context using RAG
@@ -225,83 +378,238 @@ pub async fn generate_report(
     });
 
     // Build context using RAG (hybrid search) or traditional approach
-    let (context_text, context_source) = crate::handlers::rag_helpers::build_rag_context(
-        &state,
+    let context = crate::handlers::rag_helpers::build_rag_context(
+        state,
         &user_query,
+        &payload.model,
         start_time,
         end_time,
     )
     .await?;
 
-    // 2. Construct Prompt (Senior Productivity Analyst Persona)
-    let system_prompt = r#"You are ScreenSearch Intelligence, a Senior Productivity Analyst.
-Your goal is to reconstruct a cohesive narrative of the user's work session based on fragmented screen capture logs and OCR text.
-
-INPUT DATA EXPLANATION:
-- You will receive a list of "Frames" or "Context Chunks".
-- Each item contains Timestamp, App Name, Window Title, and OCR Text (text visible on screen).
-- OCR text may be fragmented or partial.
-- RAG (retrieval) has prioritized relevant chunks based on the user's query.
+    // 2. Construct Prompt (user template or Senior Productivity Analyst persona)
+    let system_prompt = match payload.template_id {
+        Some(template_id) => {
+            render_report_template(state, template_id, start_time, end_time).await?
+        }
+        None => DEFAULT_REPORT_PROMPT.to_string(),
+    };
+    let system_prompt = format!("{}\n\n{}", system_prompt, CITATION_INSTRUCTIONS);
 
-ANALYSIS INSTRUCTIONS:
-1. SYNTHESIZE, DON'T LIST: Do not just list what the user opened. Explain *what they were doing*. (e.g., instead of "User opened VS Code, then Chrome", say "User was implementing the login feature in VS Code, referencing documentation in Chrome").
-2. USE OCR CONTEXT: Use the OCR text to identify specific topics, document names, or code functions being worked on.
-3. IDENTIFY FLOWS: Group related activities into workflows (e.g., "Research Phase", "Coding Phase", "Communication").
-4. HIGHLIGHT INTERRUPTIONS: Note if the user was frequently context-switching between unrelated apps.
+    let user_prompt = format!("{}\n\nContext:\n{}", user_query, context.text);
 
-OUTPUT FORMAT (Markdown):
-# Executive Summary
-(2-3 sentences summarizing the main focus of the period)
+    // 3. Call AI Provider
+    check_provider(&payload.provider_url)?;
+    let messages = vec![
+        OpenAIMessage {
+            role: "system".to_string(),
+            content: system_prompt.clone(),
+        },
+        OpenAIMessage {
+            role: "user".to_string(),
+            content: user_prompt.clone(),
+        },
+    ];
+    let model = model_used(state, &payload.provider_url, &payload.model);
+
+    let started = Instant::now();
+    let outcome = complete_chat(
+        state,
+        &payload.provider_url,
+        &payload.api_key,
+        &payload.model,
+        messages,
+        Some(0.7),
+        None,
+    )
+    .await;
+    let report_content = outcome.as_ref().ok().map(|(content, _)| {
+        if content.is_empty() {
+            "No report generated.".to_string()
+        } else {
+            content.clone()
+        }
+    });
+    let usage = outcome
+        .as_ref()
+        .ok()
+        .and_then(|(_, usage)| usage.as_ref())
+        .map(OpenAIUsage::columns)
+        .unwrap_or_default();
+
+    let interaction_id = record_interaction(
+        state,
+        NewAiInteraction {
+            kind: "report".to_string(),
+            provider_url: payload.provider_url.clone(),
+            model: model.clone(),
+            system_prompt: Some(system_prompt),
+            prompt: user_prompt,
+            context_chunks: context.sources.iter().map(ReportSource::chunk).collect(),
+            response: report_content.clone(),
+            error: outcome.as_ref().err().map(ToString::to_string),
+            latency_ms: started.elapsed().as_millis() as i64,
+            prompt_tokens: usage.0,
+            completion_tokens: usage.1,
+            total_tokens: usage.2,
+        },
+    )
+    .await;
 
-## Key Activities
-- **[Activity Name]**: Description of work done, citing specific apps and context found in OCR.
+    let (_, usage) = outcome?;
+    let (report_content, sources) =
+        link_citations(&report_content.unwrap_or_default(), &context.sources);
+    let final_report = format!("{}\n\n---\n*Context: {}*", report_content, context.source);
 
-## Productivity Analysis
-- **Focus**: [High/Medium/Low] - Explanation.
-- **Tools Used**: List primary tools.
+    Ok(AiReportResponse {
+        report: final_report,
+        model_used: model,
+        tokens_used: usage.and_then(|u| u.total_tokens),
+        context_source: context.source,
+        sources,
+        interaction_id,
+    })
+}
 
-## Timeline
-(Bulleted list of major state changes or milestones)
-"#;
+/// POST /ai/chat
+/// Answers a conversation with the AI provider or the built-in model
+pub async fn ai_chat(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<AiChatRequest>,
+) -> Result<Json<AiChatResponse>> {
+    debug!(
+        "AI chat request with model {}: {} messages",
+        payload.model,
+        payload.messages.len()
+    );
 
-    let user_prompt = format!("{}\n\nContext:\n{}", user_query, context_text);
+    check_provider(&payload.provider_url)?;
+    if payload.messages.is_empty() {
+        return Err(AppError::InvalidRequest(
+            "messages must contain at least one message".to_string(),
+        ));
+    }
 
-    // 3. Call AI Provider
+    // The history keeps the system prompt apart from the conversation
+    let system_prompt = payload
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let prompt = payload
+        .messages
+        .iter()
+        .filter(|m| m.role != "system")
+        .map(|m| format!("{}: {}", m.role, m.content))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let model = model_used(&state, &payload.provider_url, &payload.model);
+
+    let started = Instant::now();
+    let outcome = complete_chat(
+        &state,
+        &payload.provider_url,
+        &payload.api_key,
+        &payload.model,
+        payload.messages,
+        payload.temperature,
+        payload.max_tokens,
+    )
+    .await;
+    let usage = outcome
+        .as_ref()
+        .ok()
+        .and_then(|(_, usage)| usage.as_ref())
+        .map(OpenAIUsage::columns)
+        .unwrap_or_default();
+
+    let interaction_id = record_interaction(
+        &state,
+        NewAiInteraction {
+            kind: "chat".to_string(),
+            provider_url: payload.provider_url.clone(),
+            model: model.clone(),
+            system_prompt: (!system_prompt.is_empty()).then_some(system_prompt),
+            prompt,
+            context_chunks: Vec::new(),
+            response: outcome.as_ref().ok().map(|(content, _)| content.clone()),
+            error: outcome.as_ref().err().map(ToString::to_string),
+            latency_ms: started.elapsed().as_millis() as i64,
+            prompt_tokens: usage.0,
+            completion_tokens: usage.1,
+            total_tokens: usage.2,
+        },
+    )
+    .await;
+
+    let (content, usage) = outcome?;
+    Ok(Json(AiChatResponse {
+        message: OpenAIMessage {
+            role: "assistant".to_string(),
+            content,
+        },
+        model_used: model,
+        tokens_used: usage.and_then(|u| u.total_tokens),
+        interaction_id,
+    }))
+}
 
-    // Validate URL format and security
-    if let Err(err_msg) = validate_provider_url(&payload.provider_url) {
-        return Err(AppError::InvalidRequest(format!(
-            "Invalid provider URL: {}",
-            err_msg
-        )));
+/// Answer `messages` with the provider at `provider_url`, or with the
+/// built-in model for the provider "builtin"
+///
+/// Returns the answer and the token usage, if the provider reports it.
+pub(crate) async fn complete_chat(
+    state: &AppState,
+    provider_url: &str,
+    api_key: &Option<String>,
+    model: &str,
+    messages: Vec<OpenAIMessage>,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+) -> Result<(String, Option<OpenAIUsage>)> {
+    if provider_url == BUILTIN_PROVIDER {
+        info!("Generating with the built-in model...");
+        let (content, usage) = state
+            .local_llm
+            .generate(&messages, temperature, max_tokens)
+            .await?;
+        return Ok((content, Some(usage)));
     }
 
     let client = reqwest::Client::new();
     // Ensure we handle URL construction carefully. Most providers need /chat/completions
-    let url = format!(
-        "{}/chat/completions",
-        payload.provider_url.trim_end_matches('/')
-    );
+    let url = format!("{}/chat/completions", provider_url.trim_end_matches('/'));
 
     let request_body = OpenAIChatRequest {
-        model: payload.model.clone(),
-        messages: vec![
-            OpenAIMessage {
-                role: "system".to_string(),
-                content: system_prompt.to_string(),
-            },
-            OpenAIMessage {
-                role: "user".to_string(),
-                content: user_prompt,
-            },
-        ],
-        temperature: Some(0.7),
+        model: model.to_string(),
+        messages,
+        temperature,
+        max_tokens,
     };
 
-    let request_builder = client.post(&url).json(&request_body);
-    let request_builder = add_auth_header(request_builder, &payload.api_key);
-
     info!("Sending request to AI provider at {}...", url);
+    let body = send_chat_request(&client, &url, &request_body, api_key).await?;
+    let content = body
+        .choices
+        .into_iter()
+        .next()
+        .map(|c| c.message.content)
+        .unwrap_or_default();
+    Ok((content, body.usage))
+}
+
+/// Send a chat completion request and parse the provider's answer
+async fn send_chat_request(
+    client: &reqwest::Client,
+    url: &str,
+    request_body: &OpenAIChatRequest,
+    api_key: &Option<String>,
+) -> Result<OpenAIChatResponse> {
+    let request_builder = client.post(url).json(request_body);
+    let request_builder = add_auth_header(request_builder, api_key);
+
     let res = request_builder.send().await.map_err(|e| {
         error!("Failed to contact AI provider: {}", e);
         AppError::Internal(format!(
@@ -343,7 +651,7 @@ OUTPUT FORMAT (Markdown):
         .await
         .map_err(|e| AppError::Internal(format!("Failed to read response body: {}", e)))?;
 
-    let response_body: OpenAIChatResponse = serde_json::from_str(&response_text).map_err(|e| {
+    serde_json::from_str(&response_text).map_err(|e| {
         error!(
             "Failed to parse AI response. Parse error: {}, Body preview: {}",
             e,
@@ -352,20 +660,26 @@ OUTPUT FORMAT (Markdown):
         AppError::Internal(
             "Failed to parse AI response (invalid JSON format). Check server logs for response details.".to_string()
         )
-    })?;
-
-    let report_content = response_body
-        .choices
-        .first()
-        .map(|c| c.message.content.clone())
-        .unwrap_or_else(|| "No report generated.".to_string());
-
-    let final_report = format!("{}\n\n---\n*Context: {}*", report_content, context_source);
+    })
+}
 
-    Ok(Json(AiReportResponse {
-        report: final_report,
-        model_used: payload.model,
-        tokens_used: response_body.usage.map(|u| u.total_tokens),
-        context_source,
-    }))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let rendered = render_template(
+            "From {start} to {end}: {top_apps} {unknown}",
+            &[
+                ("start", "2025-12-10 09:00".to_string()),
+                ("end", "2025-12-10 17:00".to_string()),
+                ("top_apps", "code (12 frames)".to_string()),
+            ],
+        );
+        assert_eq!(
+            rendered,
+            "From 2025-12-10 09:00 to 2025-12-10 17:00: code (12 frames) {unknown}"
+        );
+    }
 }
@@ -0,0 +1,241 @@
+//! AI History Handlers
+//!
+//! Browse the requests sent to AI providers by report generation, chat and
+//! agent runs, recorded by [`crate::handlers::ai::record_interaction`], and the
+//! tokens they used.
+
+use crate::error::{AppError, Result};
+use crate::models::{Paginated, PaginationInfo};
+use crate::state::AppState;
+use axum::extract::{Json, Path, Query, State};
+use chrono::{DateTime, Utc};
+use screensearch_db::{AiInteractionRecord, AiUsageRecord, ContextChunk, Pagination};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, error};
+
+// ============================================================
+// Models
+// ============================================================
+
+/// AI history query parameters
+#[derive(Debug, Deserialize)]
+pub struct AiHistoryQuery {
+    /// Only interactions recorded at or after this time (ISO 8601)
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// Only interactions recorded at or before this time (ISO 8601)
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+
+    /// Only "report", "chat", "ask", "summary" or "agent" interactions
+    #[serde(default)]
+    pub kind: Option<String>,
+
+    /// Only interactions with this model
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Maximum results (default: 100, max: 1000)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Pagination offset
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// AI usage query parameters
+#[derive(Debug, Deserialize)]
+pub struct AiUsageQuery {
+    /// Only interactions recorded at or after this time (ISO 8601)
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// Only interactions recorded at or before this time (ISO 8601)
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+
+    /// Maximum models (default: 100, max: 1000)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Pagination offset
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Recorded AI interaction with decoded context chunks
+#[derive(Debug, Serialize)]
+pub struct AiInteractionResponse {
+    pub id: i64,
+    pub kind: String,
+    pub provider_url: String,
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    pub prompt: String,
+    pub context_chunks: Vec<ContextChunk>,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: i64,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub total_tokens: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<AiInteractionRecord> for AiInteractionResponse {
+    type Error = AppError;
+
+    fn try_from(record: AiInteractionRecord) -> Result<Self> {
+        let context_chunks = serde_json::from_str(&record.context_chunks).map_err(|e| {
+            AppError::Internal(format!(
+                "AI interaction {} has invalid context chunks: {}",
+                record.id, e
+            ))
+        })?;
+
+        Ok(Self {
+            id: record.id,
+            kind: record.kind,
+            provider_url: record.provider_url,
+            model: record.model,
+            system_prompt: record.system_prompt,
+            prompt: record.prompt,
+            context_chunks,
+            response: record.response,
+            error: record.error,
+            latency_ms: record.latency_ms,
+            prompt_tokens: record.prompt_tokens,
+            completion_tokens: record.completion_tokens,
+            total_tokens: record.total_tokens,
+            created_at: record.created_at,
+        })
+    }
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /ai/history - List recorded AI interactions, newest first
+///
+/// Returns one page of interactions with the total number of matching
+/// interactions.
+///
+/// # Query Parameters
+/// - start / end: Optional time range (ISO 8601)
+/// - kind: "report", "chat", "ask", "summary" or "agent"
+/// - model: Only interactions with this model
+/// - limit: Maximum results (default: 100, max: 1000)
+/// - offset: Pagination offset
+pub async fn list_ai_history(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AiHistoryQuery>,
+) -> Result<Json<Paginated<AiInteractionResponse>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(100).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "List AI history request: kind={:?}, model={:?}, limit={}, offset={}",
+        params.kind, params.model, pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let (kind, model) = (params.kind.as_deref(), params.model.as_deref());
+    let interactions = match state
+        .db
+        .list_ai_interactions(params.start, params.end, kind, model, pagination)
+        .await
+    {
+        Ok(interactions) => state
+            .db
+            .count_ai_interactions(params.start, params.end, kind, model)
+            .await
+            .map(|total| (interactions, total)),
+        Err(e) => Err(e),
+    };
+    let (interactions, total) = interactions.map_err(|e| {
+        error!("Failed to list AI interactions: {}", e);
+        AppError::Database(e)
+    })?;
+
+    let data = interactions
+        .into_iter()
+        .map(AiInteractionResponse::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Json(Paginated {
+        pagination: PaginationInfo::counted(limit, offset, data.len(), total),
+        data,
+    }))
+}
+
+/// GET /ai/history/:id - Get a recorded AI interaction
+pub async fn get_ai_interaction(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<AiInteractionResponse>> {
+    debug!("Get AI interaction request: id={}", id);
+
+    let interaction = state
+        .db
+        .get_ai_interaction(id)
+        .await
+        .map_err(|e| {
+            error!("Failed to get AI interaction {}: {}", id, e);
+            AppError::Database(e)
+        })?
+        .ok_or_else(|| AppError::NotFound(format!("AI interaction {} not found", id)))?;
+
+    Ok(Json(interaction.try_into()?))
+}
+
+/// GET /ai/history/usage - Token usage per model
+///
+/// Returns one page of models, by tokens used, with the total number of
+/// models used.
+///
+/// # Query Parameters
+/// - start / end: Optional time range (ISO 8601)
+/// - limit: Maximum models (default: 100, max: 1000)
+/// - offset: Pagination offset
+pub async fn get_ai_usage(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AiUsageQuery>,
+) -> Result<Json<Paginated<AiUsageRecord>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(100).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "AI usage request: start={:?}, end={:?}, limit={}, offset={}",
+        params.start, params.end, pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let usage = match state
+        .db
+        .get_ai_usage(params.start, params.end, pagination)
+        .await
+    {
+        Ok(usage) => state
+            .db
+            .count_ai_usage_models(params.start, params.end)
+            .await
+            .map(|total| (usage, total)),
+        Err(e) => Err(e),
+    };
+    match usage {
+        Ok((usage, total)) => Ok(Json(Paginated {
+            pagination: PaginationInfo::counted(limit, offset, usage.len(), total),
+            data: usage,
+        })),
+        Err(e) => {
+            error!("Failed to get AI usage: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Alert Handlers
+//!
+//! Manage keyword watch alerts, list their hits and stream hits to clients
+//! over the `/events` WebSocket.
+
+use crate::alerts::AlertMatcher;
+use crate::error::{AppError, Result};
+use crate::events::{ServerEvent, UiEventListener};
+use crate::models::{Paginated, PaginationInfo};
+use crate::state::AppState;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Json, Path, Query, State};
+use axum::response::Response;
+use screensearch_automation::UiEventKind;
+use screensearch_db::{AlertHitRecord, AlertRecord, NewAlert, Pagination};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, info, warn};
+
+/// Maximum alert pattern length
+const MAX_PATTERN_LEN: usize = 500;
+/// Maximum app filter length
+const MAX_APP_FILTER_LEN: usize = 200;
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Create alert request
+#[derive(Debug, Deserialize)]
+pub struct CreateAlertRequest {
+    /// Keyword (matched case-insensitively) or regex to watch for
+    pub pattern: String,
+
+    /// Treat `pattern` as a regex (default: false)
+    #[serde(default)]
+    pub regex: bool,
+
+    /// Only match frames whose process name contains this text
+    #[serde(default)]
+    pub app: Option<String>,
+}
+
+/// Alert hit query parameters
+#[derive(Debug, Deserialize)]
+pub struct AlertHitQuery {
+    /// Only hits of this alert
+    #[serde(default)]
+    pub alert_id: Option<i64>,
+
+    /// Maximum results to return (default: 50)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Offset for pagination (default: 0)
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// POST /alerts - Create a keyword watch alert
+///
+/// The alert is checked against every frame stored from now on.
+///
+/// # Request Body
+/// - pattern: Keyword or regex to watch for
+/// - regex: Treat the pattern as a regex (default: false)
+/// - app: Optional application filter (matches part of the process name)
+pub async fn create_alert(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateAlertRequest>,
+) -> Result<Json<AlertRecord>> {
+    debug!(
+        "Create alert request: pattern={:?}, regex={}",
+        req.pattern, req.regex
+    );
+
+    if req.pattern.trim().is_empty() {
+        return Err(AppError::InvalidRequest(
+            "Pattern cannot be empty".to_string(),
+        ));
+    }
+    if req.pattern.len() > MAX_PATTERN_LEN {
+        return Err(AppError::InvalidRequest(format!(
+            "Pattern too long (max {} characters)",
+            MAX_PATTERN_LEN
+        )));
+    }
+
+    let app_filter = req
+        .app
+        .map(|app| app.trim().to_string())
+        .filter(|app| !app.is_empty());
+    if app_filter
+        .as_ref()
+        .is_some_and(|app| app.len() > MAX_APP_FILTER_LEN)
+    {
+        return Err(AppError::InvalidRequest(format!(
+            "App filter too long (max {} characters)",
+            MAX_APP_FILTER_LEN
+        )));
+    }
+
+    AlertMatcher::compile(0, &req.pattern, req.regex, app_filter.as_deref())
+        .map_err(|e| AppError::InvalidRequest(format!("Invalid regex: {}", e)))?;
+
+    let id = state
+        .db
+        .insert_alert(NewAlert {
+            pattern: req.pattern,
+            is_regex: req.regex,
+            app_filter,
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to create alert: {}", e);
+            AppError::Database(e)
+        })?;
+
+    state.alerts.invalidate();
+    info!("Created alert {}", id);
+
+    match state.db.get_alert(id).await {
+        Ok(Some(alert)) => Ok(Json(alert)),
+        Ok(None) => Err(AppError::NotFound(format!("Alert {} not found", id))),
+        Err(e) => {
+            error!("Failed to get alert {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// GET /alerts - List keyword watch alerts
+pub async fn list_alerts(State(state): State<Arc<AppState>>) -> Result<Json<Vec<AlertRecord>>> {
+    debug!("List alerts request");
+
+    match state.db.list_alerts().await {
+        Ok(alerts) => Ok(Json(alerts)),
+        Err(e) => {
+            error!("Failed to list alerts: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// DELETE /alerts/:id - Delete an alert and its hits
+///
+/// # Path Parameters
+/// - id: Alert ID
+pub async fn delete_alert(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    debug!("Delete alert request: id={}", id);
+
+    match state.db.delete_alert(id).await {
+        Ok(0) => Err(AppError::NotFound(format!("Alert {} not found", id))),
+        Ok(_) => {
+            state.alerts.invalidate();
+            Ok(Json(serde_json::json!({ "success": true })))
+        }
+        Err(e) => {
+            error!("Failed to delete alert {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// GET /alerts/hits - List alert hits, newest first
+///
+/// Returns one page of hits with the total number of matching hits.
+///
+/// # Query Parameters
+/// - alert_id: Only hits of this alert
+/// - limit: Maximum results (default: 50, max: 1000)
+/// - offset: Pagination offset
+pub async fn list_alert_hits(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AlertHitQuery>,
+) -> Result<Json<Paginated<AlertHitRecord>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(50).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "List alert hits request: alert_id={:?}, limit={}, offset={}",
+        params.alert_id, pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let hits = match state.db.list_alert_hits(params.alert_id, pagination).await {
+        Ok(hits) => state
+            .db
+            .count_alert_hits(params.alert_id)
+            .await
+            .map(|total| (hits, total)),
+        Err(e) => Err(e),
+    };
+    match hits {
+        Ok((hits, total)) => Ok(Json(Paginated {
+            pagination: PaginationInfo::counted(limit, offset, hits.len(), total),
+            data: hits,
+        })),
+        Err(e) => {
+            error!("Failed to list alert hits: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// Event stream query parameters
+#[derive(Debug, Deserialize)]
+pub struct EventStreamQuery {
+    /// Comma-separated UIAutomation event kinds to receive ("focus_changed",
+    /// "window_opened", "window_closed", "structure_changed" or "all")
+    #[serde(default)]
+    pub ui_events: Option<String>,
+}
+
+/// Parse the `ui_events` query parameter
+fn parse_ui_event_kinds(value: &str) -> Result<Vec<UiEventKind>> {
+    let mut kinds = Vec::new();
+    for name in value.split(',').filter(|name| !name.trim().is_empty()) {
+        if name.trim().eq_ignore_ascii_case("all") {
+            kinds.extend(UiEventKind::ALL);
+            continue;
+        }
+        let kind = UiEventKind::from_name(name).ok_or_else(|| {
+            AppError::InvalidRequest(format!(
+                "Unknown UI event '{}'. Use focus_changed, window_opened, window_closed, structure_changed or all",
+                name.trim()
+            ))
+        })?;
+        kinds.push(kind);
+    }
+    Ok(kinds)
+}
+
+/// GET /events - WebSocket stream of server events
+///
+/// Sends each event (e.g. `{"type": "alert_hit", "hit": {...}}`) as a JSON
+/// text message. Messages from the client are ignored.
+///
+/// # Query Parameters
+/// - ui_events: Optional comma-separated UIAutomation event kinds to also
+///   receive as `ui_event` messages (or "all")
+pub async fn event_stream(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<EventStreamQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<Response> {
+    debug!("Event stream connection: ui_events={:?}", params.ui_events);
+
+    let kinds = match params.ui_events.as_deref() {
+        Some(value) => parse_ui_event_kinds(value)?,
+        None => Vec::new(),
+    };
+    // Subscribe before upgrading so the client learns about failures
+    let listener = if kinds.is_empty() {
+        None
+    } else {
+        Some(state.ui_events.listen(&kinds).map_err(|e| {
+            error!("Failed to subscribe to UI events: {}", e);
+            AppError::Automation(e)
+        })?)
+    };
+
+    Ok(ws.on_upgrade(move |socket| forward_events(socket, state, listener)))
+}
+
+async fn forward_events(
+    mut socket: WebSocket,
+    state: Arc<AppState>,
+    listener: Option<UiEventListener>,
+) {
+    let mut events = state.events.subscribe();
+    let ui_kinds = listener
+        .as_ref()
+        .map(|listener| listener.kinds().to_vec())
+        .unwrap_or_default();
+
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Event stream subscriber lagged, skipped {} event(s)", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
+                if let ServerEvent::UiEvent { event } = &event {
+                    if !ui_kinds.contains(&event.kind) {
+                        continue;
+                    }
+                }
+
+                let text = match serde_json::to_string(&event) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        error!("Failed to serialize event: {}", e);
+                        continue;
+                    }
+                };
+
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    // Dropping the listener unsubscribes this client's UI event kinds
+    drop(listener);
+    debug!("Event stream connection closed");
+}
@@ -0,0 +1,173 @@
+//! Grounded Question-Answering Handler
+//!
+//! Answers a short question from the most relevant captured screen content,
+//! e.g. "what was that Zoom link?". Unlike `/ai/generate`, the model is told
+//! to answer only from the retrieved content, and the answer comes back with
+//! the frames it is based on.
+
+use crate::citations::{link_citations, ReportSource, CITATION_INSTRUCTIONS};
+use crate::error::{AppError, Result};
+use crate::handlers::ai::{
+    check_provider, complete_chat, model_used, record_interaction, OpenAIMessage, OpenAIUsage,
+};
+use crate::handlers::rag_helpers::build_answer_context;
+use crate::state::AppState;
+use crate::time_expr::apply_when;
+use axum::extract::{Json, State};
+use chrono::{DateTime, Utc};
+use screensearch_db::NewAiInteraction;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::debug;
+
+pub use crate::models::{AskRequest, AskResponse};
+
+/// Reply the model is told to give when the context does not answer the
+/// question
+const NOT_FOUND_REPLY: &str = "NOT_FOUND";
+
+const ASK_PROMPT: &str = "You answer questions about what the user saw on their screen. \
+Answer briefly, using only the screen content given with the question. \
+Quote links, numbers and names exactly as they appear. \
+If the screen content does not contain the answer, reply with exactly NOT_FOUND and nothing else.";
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// POST /ask - Answer a question from captured screen content
+///
+/// The model is only asked when relevant screen content was found.
+pub async fn ask(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<AskRequest>,
+) -> Result<Json<AskResponse>> {
+    let question = payload.question.trim();
+    if question.is_empty() {
+        return Err(AppError::InvalidRequest(
+            "question must not be empty".to_string(),
+        ));
+    }
+    check_provider(&payload.provider_url)?;
+    debug!("Ask request with model {}: {}", payload.model, question);
+
+    let (start_time, end_time) = apply_when(
+        payload.when.as_deref(),
+        payload.tz.as_deref(),
+        payload.start_time,
+        payload.end_time,
+    )?;
+    let end_time = end_time.unwrap_or_else(Utc::now);
+    let start_time = start_time.unwrap_or(DateTime::UNIX_EPOCH);
+    let context = build_answer_context(
+        &state,
+        question,
+        &payload.model,
+        start_time,
+        end_time,
+        payload.rerank,
+        payload.max_sources.unwrap_or(10).clamp(1, 50),
+    )
+    .await?;
+    let model = model_used(&state, &payload.provider_url, &payload.model);
+
+    if context.sources.is_empty() {
+        return Ok(Json(AskResponse {
+            answer: not_found_answer(),
+            found: false,
+            sources: Vec::new(),
+            context_source: context.source,
+            model_used: model,
+            tokens_used: None,
+            interaction_id: None,
+        }));
+    }
+
+    let system_prompt = format!("{}\n\n{}", ASK_PROMPT, CITATION_INSTRUCTIONS);
+    let user_prompt = format!("Question: {}\n\n{}", question, context.text);
+    let messages = vec![
+        OpenAIMessage {
+            role: "system".to_string(),
+            content: system_prompt.clone(),
+        },
+        OpenAIMessage {
+            role: "user".to_string(),
+            content: user_prompt.clone(),
+        },
+    ];
+
+    let started = Instant::now();
+    let outcome = complete_chat(
+        &state,
+        &payload.provider_url,
+        &payload.api_key,
+        &payload.model,
+        messages,
+        Some(0.1),
+        None,
+    )
+    .await;
+    let usage = outcome
+        .as_ref()
+        .ok()
+        .and_then(|(_, usage)| usage.as_ref())
+        .map(OpenAIUsage::columns)
+        .unwrap_or_default();
+
+    let interaction_id = record_interaction(
+        &state,
+        NewAiInteraction {
+            kind: "ask".to_string(),
+            provider_url: payload.provider_url.clone(),
+            model: model.clone(),
+            system_prompt: Some(system_prompt),
+            prompt: user_prompt,
+            context_chunks: context.sources.iter().map(ReportSource::chunk).collect(),
+            response: outcome.as_ref().ok().map(|(answer, _)| answer.clone()),
+            error: outcome.as_ref().err().map(ToString::to_string),
+            latency_ms: started.elapsed().as_millis() as i64,
+            prompt_tokens: usage.0,
+            completion_tokens: usage.1,
+            total_tokens: usage.2,
+        },
+    )
+    .await;
+
+    let (answer, usage) = outcome?;
+    let tokens_used = usage.and_then(|u| u.total_tokens);
+    let answer = answer.trim();
+    if answer.is_empty() || answer.starts_with(NOT_FOUND_REPLY) {
+        return Ok(Json(AskResponse {
+            answer: not_found_answer(),
+            found: false,
+            sources: Vec::new(),
+            context_source: context.source,
+            model_used: model,
+            tokens_used,
+            interaction_id,
+        }));
+    }
+
+    let (answer, cited) = link_citations(answer, &context.sources);
+    Ok(Json(AskResponse {
+        answer,
+        found: true,
+        sources: if cited.is_empty() {
+            context.sources
+        } else {
+            cited
+        },
+        context_source: context.source,
+        model_used: model,
+        tokens_used,
+        interaction_id,
+    }))
+}
+
+// ============================================================
+// Helper Functions
+// ============================================================
+
+fn not_found_answer() -> String {
+    "No captured screen content answers this question.".to_string()
+}
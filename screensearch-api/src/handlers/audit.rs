@@ -0,0 +1,91 @@
+//! Automation Audit Handlers
+//!
+//! Browse the append-only log of automation actions recorded by
+//! [`crate::audit`].
+
+use crate::error::{AppError, Result};
+use crate::models::{Paginated, PaginationInfo};
+use crate::state::AppState;
+use axum::extract::{Json, Query, State};
+use chrono::{DateTime, Utc};
+use screensearch_db::{AuditEntryRecord, Pagination};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+/// Audit log query parameters
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    /// Only entries recorded at or after this time (ISO 8601)
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// Only entries recorded at or before this time (ISO 8601)
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+
+    /// Only entries whose endpoint contains this text (e.g. `click`)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// Only entries whose caller contains this text
+    #[serde(default)]
+    pub caller: Option<String>,
+
+    /// Maximum results (default: 100, max: 1000)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Pagination offset
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// GET /automation/audit - List automation audit entries, newest first
+///
+/// Returns one page of entries with the total number of matching entries.
+///
+/// # Query Parameters
+/// - start / end: Optional time range (ISO 8601)
+/// - endpoint: Only entries whose endpoint contains this text
+/// - caller: Only entries whose caller contains this text
+/// - limit: Maximum results (default: 100, max: 1000)
+/// - offset: Pagination offset
+pub async fn list_audit_entries(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AuditQuery>,
+) -> Result<Json<Paginated<AuditEntryRecord>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(100).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "List audit entries request: endpoint={:?}, caller={:?}, limit={}, offset={}",
+        params.endpoint, params.caller, pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let (endpoint, caller) = (params.endpoint.as_deref(), params.caller.as_deref());
+    let entries = match state
+        .db
+        .list_audit_entries(params.start, params.end, endpoint, caller, pagination)
+        .await
+    {
+        Ok(entries) => state
+            .db
+            .count_audit_entries(params.start, params.end, endpoint, caller)
+            .await
+            .map(|total| (entries, total)),
+        Err(e) => Err(e),
+    };
+    match entries {
+        Ok((entries, total)) => Ok(Json(Paginated {
+            pagination: PaginationInfo::counted(limit, offset, entries.len(), total),
+            data: entries,
+        })),
+        Err(e) => {
+            error!("Failed to list audit entries: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
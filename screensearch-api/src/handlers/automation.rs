@@ -1,26 +1,77 @@
 //! Computer automation endpoint handlers
 
+use crate::audit::AuditedElement;
+use crate::element_handles::HandleError;
 use crate::error::{AppError, Result};
 use crate::models::{
-    AutomationResponse, ClickRequest, ElementInfo, FindElementsRequest, GetTextRequest,
-    GetTextResponse, KeyPressRequest, ListElementsRequest, OpenAppRequest, OpenUrlRequest,
-    ScrollRequest, TypeRequest,
+    AutomationResponse, ClickRequest, ClickTextRequest, ClickTextResponse, ClipboardResponse,
+    ClipboardSetRequest, DragRequest, DumpTreeRequest, ElementInfo, FindElementsRequest,
+    GetTableRequest, GetTextRequest, GetTextResponse, HighlightRequest, HoverRequest,
+    KeyPressRequest, ListElementsRequest, OpenAppRequest, OpenUrlRequest, ScreenshotElementRequest,
+    ScrollRequest, SelectRequest, TypeRequest, WaitRequest, WaitResponse, WindowDesktopRequest,
+    WindowMoveRequest, WindowQuery, WindowResponse,
 };
 use crate::state::AppState;
-use axum::extract::State;
-use axum::Json;
+use crate::text_locator::locate_text;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
 use screensearch_automation::{
-    KeyCode, MouseButton as ClickButton, ScrollDirection, Selector as ElementSelector,
+    dpi, highlight_rect, parse_color, AutomationError, ElementNode, KeyCode,
+    MouseButton as ClickButton, ScrollDirection, SelectOption, Selector as ElementSelector,
+    TableData, UIElement, VirtualDesktop, WaitCondition, DEFAULT_HIGHLIGHT_COLOR,
 };
+use screensearch_capture::ScreenCapture;
+use std::io::Cursor;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, error};
 
+/// Parse a selector string from a request (see [`ElementSelector::parse`])
+///
+/// Plain element names parse as name selectors, as before.
+pub(crate) fn parse_selector(selector: &str) -> Result<ElementSelector> {
+    ElementSelector::parse(selector).map_err(|e| AppError::InvalidRequest(e.to_string()))
+}
+
+/// Convert found elements to `ElementInfo`, registering a handle for each
+fn register_elements(state: &AppState, elements: Vec<UIElement>) -> Result<Vec<ElementInfo>> {
+    elements
+        .into_iter()
+        .map(|element| {
+            let mut info = ElementInfo::from_ui_element(&element)?;
+            info.element_handle = Some(state.element_handles.insert(element));
+            Ok(info)
+        })
+        .collect()
+}
+
+/// Resolve an element handle returned by a prior find
+///
+/// Fails when the handle is unknown or expired, or its element no longer
+/// exists; the client should find the element again.
+pub(crate) fn resolve_element_handle(state: &AppState, handle: &str) -> Result<UIElement> {
+    state
+        .element_handles
+        .get(handle, UIElement::is_alive)
+        .map_err(|e| match e {
+            HandleError::Unknown => {
+                AppError::NotFound(format!("Unknown or expired element handle '{}'", handle))
+            }
+            HandleError::Stale => AppError::NotFound(format!(
+                "Element handle '{}' is stale: the element no longer exists",
+                handle
+            )),
+        })
+}
+
 /// POST /automation/find-elements - Locate UI elements
 ///
 /// Finds UI elements matching the provided selector string.
 ///
 /// # Request Body
-/// - selector: Element selector string (e.g., name, automation ID)
+/// - selector: Element selector string, e.g. a name or `role=button[name='OK'] >> nth=1`
 /// - timeout_ms: Optional timeout in milliseconds (default: 5000)
 pub async fn find_elements(
     State(state): State<Arc<AppState>>,
@@ -28,17 +79,13 @@ pub async fn find_elements(
 ) -> Result<Json<Vec<ElementInfo>>> {
     debug!("Find elements request: selector={}", req.selector);
 
-    // Create selector (use name-based selector for now)
-    let selector = ElementSelector::name(&req.selector);
+    let selector = parse_selector(&req.selector)?;
 
     // Find elements
     match state.automation.find_elements(&selector) {
         Ok(elements) => {
             debug!("Found {} elements", elements.len());
-            // Convert UIElements to ElementInfo
-            let element_infos: Result<Vec<ElementInfo>> =
-                elements.iter().map(ElementInfo::from_ui_element).collect();
-            Ok(Json(element_infos?))
+            Ok(Json(register_elements(&state, elements)?))
         }
         Err(e) => {
             error!("Failed to find elements: {}", e);
@@ -47,36 +94,409 @@ pub async fn find_elements(
     }
 }
 
-/// POST /automation/click - Click at coordinates
+/// POST /automation/click - Click at coordinates or a found element
 ///
-/// Performs a mouse click at the specified screen coordinates.
+/// Performs a mouse click at the specified screen coordinates, or at the
+/// center of an element returned by a prior `/automation/find-elements`.
 ///
 /// # Request Body
-/// - x: X coordinate
-/// - y: Y coordinate
+/// - x: X coordinate (physical pixels)
+/// - y: Y coordinate (physical pixels)
+/// - logical: Optional, coordinates are logical (96 DPI) pixels of their monitor
+/// - element_handle: Handle from a prior find (overrides coordinates)
 /// - button: Optional button type ("left", "right", "middle", default: "left")
 pub async fn click(
     State(state): State<Arc<AppState>>,
     Json(req): Json<ClickRequest>,
+) -> Result<impl IntoResponse> {
+    debug!(
+        "Click request: ({:?}, {:?}), element_handle={:?}, button={:?}",
+        req.x, req.y, req.element_handle, req.button
+    );
+
+    let button = match req.button.as_deref() {
+        Some("right") => ClickButton::Right,
+        Some("middle") => ClickButton::Middle,
+        _ => ClickButton::Left,
+    };
+
+    let (x, y, audited) = match (req.element_handle.as_deref(), req.x, req.y) {
+        (Some(handle), _, _) => {
+            let element = resolve_element_handle(&state, handle)?;
+            let (x, y) = element_center(&element)?;
+            let mut info = ElementInfo::from_ui_element(&element)?;
+            info.element_handle = Some(handle.to_string());
+            (x, y, Some(Extension(AuditedElement(info))))
+        }
+        (None, Some(x), Some(y)) => {
+            let (x, y) = screen_point(x, y, req.logical);
+            (x, y, None)
+        }
+        _ => {
+            return Err(AppError::InvalidRequest(
+                "Click requires either element_handle or both x and y".to_string(),
+            ))
+        }
+    };
+
+    match state.automation.click(x, y, button) {
+        Ok(_) => Ok((
+            audited,
+            Json(AutomationResponse {
+                success: true,
+                message: Some(format!("Clicked at ({}, {})", x, y)),
+            }),
+        )),
+        Err(e) => {
+            error!("Click failed: {}", e);
+            Err(AppError::Automation(e))
+        }
+    }
+}
+
+/// Physical screen point for request coordinates
+fn screen_point(x: i32, y: i32, logical: bool) -> (i32, i32) {
+    if logical {
+        dpi::logical_to_physical(x, y)
+    } else {
+        (x, y)
+    }
+}
+
+/// Center of an element's bounding rectangle
+fn element_center(element: &UIElement) -> Result<(i32, i32)> {
+    let (left, top, width, height) = element.bounds().map_err(AppError::Automation)?;
+    Ok(((left + width / 2.0) as i32, (top + height / 2.0) as i32))
+}
+
+/// Maximum duration of a hover movement or rest in milliseconds
+const MAX_HOVER_DURATION_MS: u64 = 10_000;
+
+/// POST /automation/hover - Move the mouse onto a target and rest there
+///
+/// Glides the cursor to the target instead of teleporting it, then waits so
+/// hover-only menus and tooltips have time to appear before the response.
+///
+/// # Request Body
+/// - element_handle / selector / x, y: Target, in that order of precedence
+/// - logical: Optional, x and y are logical (96 DPI) pixels of their monitor
+/// - duration_ms: Optional movement duration (default: 200, max: 10000; 0 jumps)
+/// - hover_ms: Optional rest on the target (default: 500, max: 10000)
+pub async fn hover(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<HoverRequest>,
+) -> Result<Json<AutomationResponse>> {
+    debug!("Hover request: {:?}", req);
+
+    let (x, y) = match (
+        req.element_handle.as_deref(),
+        req.selector.as_deref(),
+        req.x,
+        req.y,
+    ) {
+        (Some(handle), _, _, _) => element_center(&resolve_element_handle(&state, handle)?)?,
+        (None, Some(selector), _, _) => {
+            let element = state
+                .automation
+                .find_element(&parse_selector(selector)?)
+                .await
+                .map_err(AppError::Automation)?;
+            element_center(&element)?
+        }
+        (None, None, Some(x), Some(y)) => screen_point(x, y, req.logical),
+        _ => {
+            return Err(AppError::InvalidRequest(
+                "Hover requires element_handle, selector or both x and y".to_string(),
+            ))
+        }
+    };
+
+    let duration_ms = req.duration_ms.unwrap_or(200).min(MAX_HOVER_DURATION_MS);
+    let hover_ms = req.hover_ms.unwrap_or(500).min(MAX_HOVER_DURATION_MS);
+
+    // The movement sleeps between moves, keep it off the async runtime
+    let automation = Arc::clone(&state.automation);
+    tokio::task::spawn_blocking(move || automation.move_mouse(x, y, duration_ms))
+        .await
+        .map_err(|e| AppError::Internal(format!("Hover task failed: {}", e)))?
+        .map_err(|e| {
+            error!("Hover failed: {}", e);
+            AppError::Automation(e)
+        })?;
+
+    tokio::time::sleep(Duration::from_millis(hover_ms)).await;
+
+    Ok(Json(AutomationResponse {
+        success: true,
+        message: Some(format!("Hovered at ({}, {})", x, y)),
+    }))
+}
+
+/// Default time a highlight frame stays on screen in milliseconds
+const DEFAULT_HIGHLIGHT_MS: u64 = 2_000;
+/// Maximum time a highlight frame stays on screen in milliseconds
+const MAX_HIGHLIGHT_MS: u64 = 30_000;
+
+/// POST /automation/highlight - Frame an element or rectangle on screen
+///
+/// Draws a temporary colored frame in a click-through topmost window, to see
+/// which element a selector or handle resolves to. Responds once the frame
+/// is shown; it disappears after `duration_ms`.
+///
+/// # Request Body
+/// - element_handle / selector / x, y, width, height: Target, in that order of precedence
+/// - color: Optional frame color as `#RRGGBB` (default: red)
+/// - duration_ms: Optional display time (default: 2000, max: 30000)
+pub async fn highlight(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<HighlightRequest>,
 ) -> Result<Json<AutomationResponse>> {
+    debug!("Highlight request: {:?}", req);
+
+    let color = match req.color.as_deref() {
+        Some(color) => parse_color(color).ok_or_else(|| {
+            AppError::InvalidRequest(format!("Invalid color '{}', use #RRGGBB", color))
+        })?,
+        None => DEFAULT_HIGHLIGHT_COLOR,
+    };
+    let duration = Duration::from_millis(
+        req.duration_ms
+            .unwrap_or(DEFAULT_HIGHLIGHT_MS)
+            .min(MAX_HIGHLIGHT_MS),
+    );
+
+    let element = match (req.element_handle.as_deref(), req.selector.as_deref()) {
+        (Some(handle), _) => Some(resolve_element_handle(&state, handle)?),
+        (None, Some(selector)) => Some(
+            state
+                .automation
+                .find_element(&parse_selector(selector)?)
+                .await
+                .map_err(AppError::Automation)?,
+        ),
+        (None, None) => None,
+    };
+    let (x, y, width, height) = match (element, req.x, req.y, req.width, req.height) {
+        (Some(element), ..) => {
+            let (x, y, width, height) = element.bounds().map_err(AppError::Automation)?;
+            (x as i32, y as i32, width as i32, height as i32)
+        }
+        (None, Some(x), Some(y), Some(width), Some(height)) => (x, y, width, height),
+        _ => {
+            return Err(AppError::InvalidRequest(
+                "Highlight requires element_handle, selector or x, y, width and height".to_string(),
+            ))
+        }
+    };
+
+    highlight_rect(x, y, width, height, color, duration).map_err(|e| {
+        error!("Highlight failed: {}", e);
+        match e {
+            AutomationError::InvalidArgument(msg) => AppError::InvalidRequest(msg),
+            e => AppError::Automation(e),
+        }
+    })?;
+
+    Ok(Json(AutomationResponse {
+        success: true,
+        message: Some(format!(
+            "Highlighted {}x{} at ({}, {})",
+            width, height, x, y
+        )),
+    }))
+}
+
+/// How long UIAutomation may search before click-text falls back to OCR
+const CLICK_TEXT_UIA_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// POST /automation/click-text - Click visible text
+///
+/// Looks for a visible element whose name contains the text with
+/// UIAutomation first. When there is none (Electron apps, games, remote
+/// desktops), the monitors are captured, OCR'd and the center of the
+/// matching text is clicked.
+///
+/// # Request Body
+/// - text: Text to click
+/// - index: Optional occurrence when the text appears more than once (default: 0)
+/// - monitor: Optional monitor to search
+/// - ocr_only: Skip UIAutomation (default: false)
+/// - button: Optional button type ("left", "right", "middle", default: "left")
+pub async fn click_text(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ClickTextRequest>,
+) -> Result<Json<ClickTextResponse>> {
     debug!(
-        "Click request: ({}, {}), button={:?}",
-        req.x, req.y, req.button
+        "Click text request: text={:?}, index={:?}, monitor={:?}, ocr_only={}",
+        req.text, req.index, req.monitor, req.ocr_only
     );
 
+    if req.text.trim().is_empty() {
+        return Err(AppError::InvalidRequest(
+            "text must not be empty".to_string(),
+        ));
+    }
+
+    let button = match req.button.as_deref() {
+        Some("right") => ClickButton::Right,
+        Some("middle") => ClickButton::Middle,
+        _ => ClickButton::Left,
+    };
+    let index = req.index.unwrap_or(0);
+
+    // UIAutomation resolves the first match only, so later occurrences and
+    // monitor restrictions go straight to OCR
+    if !req.ocr_only && index == 0 && req.monitor.is_none() {
+        let found = state
+            .automation
+            .find_element_with_timeout(&ElementSelector::name(&req.text), CLICK_TEXT_UIA_TIMEOUT)
+            .await;
+        if let Ok(element) = found {
+            if let (Ok((left, top, width, height)), Ok(true)) =
+                (element.bounds(), element.is_visible())
+            {
+                if width >= 1.0 && height >= 1.0 {
+                    let (x, y) = ((left + width / 2.0) as i32, (top + height / 2.0) as i32);
+                    state
+                        .automation
+                        .click(x, y, button)
+                        .map_err(AppError::Automation)?;
+
+                    return Ok(Json(ClickTextResponse {
+                        success: true,
+                        method: "uiautomation".to_string(),
+                        text: element.name().unwrap_or_else(|| req.text.clone()),
+                        x,
+                        y,
+                        matches: None,
+                    }));
+                }
+            }
+        }
+        debug!(
+            "UIAutomation found no visible '{}', falling back to OCR",
+            req.text
+        );
+    }
+
+    let matches = locate_text(&req.text, req.monitor).await.map_err(|e| {
+        error!("Failed to locate text on screen: {}", e);
+        AppError::Internal(format!("Failed to locate text on screen: {}", e))
+    })?;
+
+    let target = matches.get(index).ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Text '{}' not found on screen ({} occurrence(s), requested index {})",
+            req.text,
+            matches.len(),
+            index
+        ))
+    })?;
+
+    let (x, y) = target.center();
+    state
+        .automation
+        .click(x, y, button)
+        .map_err(AppError::Automation)?;
+
+    Ok(Json(ClickTextResponse {
+        success: true,
+        method: "ocr".to_string(),
+        text: target.text.clone(),
+        x,
+        y,
+        matches: Some(matches.len()),
+    }))
+}
+
+/// Maximum number of intermediate moves for a drag
+const MAX_DRAG_STEPS: u32 = 500;
+/// Maximum duration of a drag in milliseconds
+const MAX_DRAG_DURATION_MS: u64 = 10_000;
+
+/// Resolve one end of a drag to screen coordinates
+///
+/// A selector resolves to the center of the matched element; otherwise both
+/// coordinates must be provided.
+async fn resolve_drag_point(
+    state: &AppState,
+    label: &str,
+    x: Option<i32>,
+    y: Option<i32>,
+    selector: Option<&str>,
+) -> Result<(i32, i32)> {
+    if let Some(selector) = selector {
+        let element = state
+            .automation
+            .find_element(&parse_selector(selector)?)
+            .await
+            .map_err(AppError::Automation)?;
+        let (left, top, width, height) = element.bounds().map_err(AppError::Automation)?;
+        return Ok(((left + width / 2.0) as i32, (top + height / 2.0) as i32));
+    }
+
+    match (x, y) {
+        (Some(x), Some(y)) => Ok((x, y)),
+        _ => Err(AppError::InvalidRequest(format!(
+            "Drag {} requires either {}_selector or both {}_x and {}_y",
+            label, label, label, label
+        ))),
+    }
+}
+
+/// POST /automation/drag - Drag and drop
+///
+/// Presses a mouse button at the source, moves smoothly to the target and
+/// releases it there.
+///
+/// # Request Body
+/// - from_x, from_y / from_selector: Source coordinates or element selector
+/// - to_x, to_y / to_selector: Target coordinates or element selector
+/// - button: Optional button type ("left", "right", "middle", default: "left")
+/// - steps: Optional number of intermediate moves (default: 20, max: 500)
+/// - duration_ms: Optional movement duration (default: 300, max: 10000)
+pub async fn drag(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DragRequest>,
+) -> Result<Json<AutomationResponse>> {
+    debug!("Drag request: {:?}", req);
+
+    let from = resolve_drag_point(
+        &state,
+        "from",
+        req.from_x,
+        req.from_y,
+        req.from_selector.as_deref(),
+    )
+    .await?;
+    let to =
+        resolve_drag_point(&state, "to", req.to_x, req.to_y, req.to_selector.as_deref()).await?;
+
     let button = match req.button.as_deref() {
         Some("right") => ClickButton::Right,
         Some("middle") => ClickButton::Middle,
         _ => ClickButton::Left,
     };
+    let steps = req.steps.unwrap_or(20).clamp(1, MAX_DRAG_STEPS);
+    let duration_ms = req.duration_ms.unwrap_or(300).min(MAX_DRAG_DURATION_MS);
 
-    match state.automation.click(req.x, req.y, button) {
+    // The drag sleeps between moves, keep it off the async runtime
+    let automation = Arc::clone(&state.automation);
+    let result =
+        tokio::task::spawn_blocking(move || automation.drag(from, to, button, steps, duration_ms))
+            .await
+            .map_err(|e| AppError::Internal(format!("Drag task failed: {}", e)))?;
+
+    match result {
         Ok(_) => Ok(Json(AutomationResponse {
             success: true,
-            message: Some(format!("Clicked at ({}, {})", req.x, req.y)),
+            message: Some(format!(
+                "Dragged from ({}, {}) to ({}, {})",
+                from.0, from.1, to.0, to.1
+            )),
         })),
         Err(e) => {
-            error!("Click failed: {}", e);
+            error!("Drag failed: {}", e);
             Err(AppError::Automation(e))
         }
     }
@@ -202,7 +622,7 @@ pub async fn get_text(
 ) -> Result<Json<GetTextResponse>> {
     debug!("Get text request: selector={}", req.selector);
 
-    let selector = ElementSelector::name(&req.selector);
+    let selector = parse_selector(&req.selector)?;
 
     match state.automation.get_text(&selector).await {
         Ok(text) => Ok(Json(GetTextResponse { text })),
@@ -213,6 +633,290 @@ pub async fn get_text(
     }
 }
 
+/// POST /automation/select - Select an option of a combo box or list
+///
+/// Expands a drop-down, scrolls the option into view and selects it in one
+/// call, instead of a click, wait and click-by-name sequence.
+///
+/// # Request Body
+/// - element_handle / selector: Combo box or list, in that order of precedence
+/// - value: Option text (exact match preferred, else partial; case-insensitive)
+/// - index: 0-based option position, if no value
+pub async fn select_option(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SelectRequest>,
+) -> Result<Json<AutomationResponse>> {
+    debug!("Select request: {:?}", req);
+
+    let option = match (req.value, req.index) {
+        (Some(value), _) => SelectOption::Text(value),
+        (None, Some(index)) => SelectOption::Index(index),
+        (None, None) => {
+            return Err(AppError::InvalidRequest(
+                "Select requires value or index".to_string(),
+            ))
+        }
+    };
+
+    let element = match (req.element_handle.as_deref(), req.selector.as_deref()) {
+        (Some(handle), _) => resolve_element_handle(&state, handle)?,
+        (None, Some(selector)) => state
+            .automation
+            .find_element(&parse_selector(selector)?)
+            .await
+            .map_err(AppError::Automation)?,
+        (None, None) => {
+            return Err(AppError::InvalidRequest(
+                "Select requires element_handle or selector".to_string(),
+            ))
+        }
+    };
+
+    // Waits for the drop-down to populate
+    let selected = tokio::task::spawn_blocking(move || element.select_option(&option))
+        .await
+        .map_err(|e| AppError::Internal(format!("Select task failed: {}", e)))?
+        .map_err(|e| {
+            error!("Select failed: {}", e);
+            match e {
+                AutomationError::ElementNotFound(msg) => AppError::NotFound(msg),
+                e => AppError::Automation(e),
+            }
+        })?;
+
+    Ok(Json(AutomationResponse {
+        success: true,
+        message: Some(format!("Selected '{}'", selected)),
+    }))
+}
+
+/// Default number of rows read by a table extraction
+const DEFAULT_TABLE_ROWS: usize = 500;
+/// Maximum number of rows read by a table extraction
+const MAX_TABLE_ROWS: usize = 10_000;
+
+/// POST /automation/get-table - Extract a data grid or table
+///
+/// Reads headers and cell text through the Grid and Table patterns, for
+/// data grids such as spreadsheets, mail lists and admin panels.
+///
+/// # Request Body
+/// - element_handle / selector: Grid element, in that order of precedence
+/// - max_rows: Optional maximum rows to extract (default: 500, max: 10000)
+pub async fn get_table(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<GetTableRequest>,
+) -> Result<Json<TableData>> {
+    let max_rows = req
+        .max_rows
+        .unwrap_or(DEFAULT_TABLE_ROWS)
+        .min(MAX_TABLE_ROWS);
+    debug!(
+        "Get table request: selector={:?}, handle={:?}, max_rows={}",
+        req.selector, req.element_handle, max_rows
+    );
+
+    let element = match (req.element_handle.as_deref(), req.selector.as_deref()) {
+        (Some(handle), _) => resolve_element_handle(&state, handle)?,
+        (None, Some(selector)) => state
+            .automation
+            .find_element(&parse_selector(selector)?)
+            .await
+            .map_err(AppError::Automation)?,
+        (None, None) => {
+            return Err(AppError::InvalidRequest(
+                "Get table requires element_handle or selector".to_string(),
+            ))
+        }
+    };
+
+    // Every cell is a cross-process call
+    let table = tokio::task::spawn_blocking(move || element.extract_table(max_rows))
+        .await
+        .map_err(|e| AppError::Internal(format!("Table extraction task failed: {}", e)))?
+        .map_err(|e| {
+            error!("Get table failed: {}", e);
+            AppError::Automation(e)
+        })?;
+
+    Ok(Json(table))
+}
+
+/// Maximum time a single wait request may block, in milliseconds
+const MAX_WAIT_TIMEOUT_MS: u64 = 60_000;
+
+/// POST /automation/wait - Wait for an element condition
+///
+/// Polls the UI tree server-side until an element matching the selector
+/// satisfies the condition or the timeout expires. A timeout is not an error:
+/// the response has `success: false`.
+///
+/// # Request Body
+/// - selector: Element selector string
+/// - condition: Optional condition ("exists", "visible", "enabled", "text-contains", default: "exists")
+/// - text: Text to look for (required for "text-contains")
+/// - timeout_ms: Optional timeout in milliseconds (default: 5000, max: 60000)
+pub async fn wait_for_element(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<WaitRequest>,
+) -> Result<(Option<Extension<AuditedElement>>, Json<WaitResponse>)> {
+    debug!(
+        "Wait request: selector={}, condition={:?}",
+        req.selector, req.condition
+    );
+
+    let condition_name = req.condition.as_deref().unwrap_or("exists");
+    let condition =
+        WaitCondition::from_name(condition_name, req.text.as_deref()).ok_or_else(|| {
+            AppError::InvalidRequest(format!(
+                "Invalid condition '{}'. Use exists, visible, enabled or text-contains (with text)",
+                condition_name
+            ))
+        })?;
+
+    let selector = parse_selector(&req.selector)?;
+    let timeout = Duration::from_millis(req.timeout_ms.unwrap_or(5000).min(MAX_WAIT_TIMEOUT_MS));
+    let start = Instant::now();
+
+    match state
+        .automation
+        .wait_for_element(&selector, &condition, timeout)
+        .await
+    {
+        Ok(element) => {
+            let info = ElementInfo::from_ui_element(&element)?;
+            Ok((
+                Some(Extension(AuditedElement(info.clone()))),
+                Json(WaitResponse {
+                    success: true,
+                    elapsed_ms: start.elapsed().as_millis() as u64,
+                    element: Some(info),
+                }),
+            ))
+        }
+        Err(e) if e.is_timeout() => {
+            debug!("Wait timed out: {}", e);
+            Ok((
+                None,
+                Json(WaitResponse {
+                    success: false,
+                    elapsed_ms: start.elapsed().as_millis() as u64,
+                    element: None,
+                }),
+            ))
+        }
+        Err(e) => {
+            error!("Wait failed: {}", e);
+            Err(AppError::Automation(e))
+        }
+    }
+}
+
+/// Maximum padding around an element screenshot in pixels
+const MAX_SCREENSHOT_PADDING: u32 = 200;
+
+/// POST /automation/screenshot-element - Capture a UI element as PNG
+///
+/// Resolves the selector, captures the monitor containing the element and
+/// returns the element's bounding rectangle cropped from that capture.
+///
+/// # Request Body
+/// - selector: Element selector string
+/// - padding: Optional extra pixels around the element (default: 0, max: 200)
+pub async fn screenshot_element(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ScreenshotElementRequest>,
+) -> Result<impl IntoResponse> {
+    debug!("Screenshot element request: selector={}", req.selector);
+
+    let selector = parse_selector(&req.selector)?;
+    let element = state
+        .automation
+        .find_element(&selector)
+        .await
+        .map_err(|e| {
+            error!("Screenshot element failed: {}", e);
+            AppError::Automation(e)
+        })?;
+
+    let (left, top, width, height) = element.bounds().map_err(AppError::Automation)?;
+    if width < 1.0 || height < 1.0 {
+        return Err(AppError::InvalidRequest(format!(
+            "Element '{}' has no visible area",
+            req.selector
+        )));
+    }
+
+    let padding = req.padding.unwrap_or(0).min(MAX_SCREENSHOT_PADDING);
+    let image = ScreenCapture::capture_region(
+        left as i32 - padding as i32,
+        top as i32 - padding as i32,
+        width as u32 + padding * 2,
+        height as u32 + padding * 2,
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to capture element region: {}", e);
+        AppError::Internal(format!("Failed to capture screen: {}", e))
+    })?;
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| AppError::Internal(format!("Failed to encode PNG: {}", e)))?;
+
+    Ok((
+        Extension(AuditedElement(ElementInfo::from_ui_element(&element)?)),
+        [(header::CONTENT_TYPE, "image/png")],
+        png,
+    ))
+}
+
+/// Default number of child levels in a tree dump
+const DEFAULT_TREE_DEPTH: usize = 5;
+/// Maximum number of child levels in a tree dump
+const MAX_TREE_DEPTH: usize = 20;
+
+/// POST /automation/dump-tree - Dump an element subtree as JSON
+///
+/// Returns the root element with its role, name, automation ID, bounds,
+/// supported patterns and enabled/visible state, and its children nested
+/// down to `depth` levels.
+///
+/// # Request Body
+/// - root_selector: Optional root element selector (defaults to the active window)
+/// - depth: Optional number of child levels (default: 5, max: 20)
+pub async fn dump_tree(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DumpTreeRequest>,
+) -> Result<Json<ElementNode>> {
+    let depth = req.depth.unwrap_or(DEFAULT_TREE_DEPTH).min(MAX_TREE_DEPTH);
+    debug!(
+        "Dump tree request: root={:?}, depth={}",
+        req.root_selector, depth
+    );
+
+    let root = match req.root_selector.as_deref() {
+        Some(selector) => {
+            let selector = parse_selector(selector)?;
+            state.automation.find_element(&selector).await
+        }
+        None => state.automation.active_window_element(),
+    }
+    .map_err(|e| {
+        error!("Failed to resolve tree root: {}", e);
+        AppError::Automation(e)
+    })?;
+
+    // Walking a large subtree makes many cross-process calls
+    let tree = tokio::task::spawn_blocking(move || root.to_tree(depth))
+        .await
+        .map_err(|e| AppError::Internal(format!("Tree dump task failed: {}", e)))?
+        .map_err(AppError::Automation)?;
+
+    Ok(Json(tree))
+}
+
 /// POST /automation/list-elements - List interactive elements
 ///
 /// Lists all interactive UI elements in the active window or under a root element.
@@ -227,7 +931,7 @@ pub async fn list_elements(
 
     // If root selector provided, create selector
     let selector = if let Some(root) = req.root_selector {
-        ElementSelector::name(&root)
+        parse_selector(&root)?
     } else {
         // Default to finding all elements (wildcard selector)
         ElementSelector::name("*")
@@ -237,10 +941,7 @@ pub async fn list_elements(
     match state.automation.find_elements(&selector) {
         Ok(elements) => {
             debug!("Found {} elements", elements.len());
-            // Convert UIElements to ElementInfo
-            let element_infos: Result<Vec<ElementInfo>> =
-                elements.iter().map(ElementInfo::from_ui_element).collect();
-            Ok(Json(element_infos?))
+            Ok(Json(register_elements(&state, elements)?))
         }
         Err(e) => {
             error!("Failed to list elements: {}", e);
@@ -305,6 +1006,310 @@ pub async fn open_url(
     }
 }
 
+/// GET /automation/clipboard - Read clipboard text
+///
+/// Returns the clipboard text, or null if the clipboard holds no text.
+pub async fn get_clipboard(State(state): State<Arc<AppState>>) -> Result<Json<ClipboardResponse>> {
+    debug!("Get clipboard request");
+
+    match state.automation.clipboard().get_text() {
+        Ok(text) => Ok(Json(ClipboardResponse { text })),
+        Err(e) => {
+            error!("Get clipboard failed: {}", e);
+            Err(AppError::Automation(e))
+        }
+    }
+}
+
+/// POST /automation/clipboard/set - Set clipboard text
+///
+/// Replaces the clipboard contents with the given text and optionally pastes
+/// it into the focused element, which is much faster than typing large text.
+///
+/// # Request Body
+/// - text: Text to place on the clipboard
+/// - paste: Optional flag to press Ctrl+V afterwards (default: false)
+pub async fn set_clipboard(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ClipboardSetRequest>,
+) -> Result<Json<AutomationResponse>> {
+    debug!(
+        "Set clipboard request: {} chars, paste={}",
+        req.text.len(),
+        req.paste
+    );
+
+    if let Err(e) = state.automation.clipboard().set_text(&req.text) {
+        error!("Set clipboard failed: {}", e);
+        return Err(AppError::Automation(e));
+    }
+
+    if req.paste {
+        if let Err(e) = state.automation.input().paste() {
+            error!("Paste failed: {}", e);
+            return Err(AppError::Automation(e));
+        }
+    }
+
+    let chars = req.text.chars().count();
+    Ok(Json(AutomationResponse {
+        success: true,
+        message: Some(if req.paste {
+            format!("Pasted {} characters", chars)
+        } else {
+            format!("Copied {} characters to clipboard", chars)
+        }),
+    }))
+}
+
+/// Map a window operation error, reporting unknown handles as 404
+fn window_error(handle: u64, e: AutomationError) -> AppError {
+    match e {
+        AutomationError::InvalidArgument(_) => {
+            AppError::NotFound(format!("Window {} not found", handle))
+        }
+        e => {
+            error!("Window operation on {} failed: {}", handle, e);
+            AppError::Automation(e)
+        }
+    }
+}
+
+/// GET /automation/windows - List top-level windows
+///
+/// Lists visible application windows with their handles, process and bounds.
+///
+/// # Query Parameters
+/// - title: Optional title filter (case-insensitive substring)
+/// - process: Optional process name filter (case-insensitive substring)
+pub async fn list_windows(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WindowQuery>,
+) -> Result<Json<Vec<WindowResponse>>> {
+    debug!("List windows request: {:?}", query);
+
+    let windows = state.automation.windows().enumerate().map_err(|e| {
+        error!("Failed to enumerate windows: {}", e);
+        AppError::Automation(e)
+    })?;
+
+    let title = query.title.map(|t| t.to_lowercase());
+    let process = query.process.map(|p| p.to_lowercase());
+    let windows = windows
+        .into_iter()
+        .filter(|w| {
+            title
+                .as_ref()
+                .map_or(true, |t| w.title.to_lowercase().contains(t))
+        })
+        .filter(|w| {
+            process
+                .as_ref()
+                .map_or(true, |p| w.process_name.to_lowercase().contains(p))
+        })
+        .map(WindowResponse::from)
+        .collect();
+
+    Ok(Json(windows))
+}
+
+/// GET /automation/windows/active - Get the foreground window
+///
+/// Returns null when no window has focus.
+pub async fn get_active_window(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Option<WindowResponse>>> {
+    debug!("Get active window request");
+
+    match state.automation.windows().get_active() {
+        Ok(window) => Ok(Json(window.map(WindowResponse::from))),
+        Err(e) => {
+            error!("Failed to get active window: {}", e);
+            Err(AppError::Automation(e))
+        }
+    }
+}
+
+/// POST /automation/windows/:handle/focus - Bring a window to the foreground
+///
+/// Restores the window first if it is minimized, and switches to its virtual
+/// desktop if it is on another one.
+///
+/// # Path Parameters
+/// - handle: Window handle from `/automation/windows`
+pub async fn focus_window(
+    State(state): State<Arc<AppState>>,
+    Path(handle): Path<u64>,
+) -> Result<Json<AutomationResponse>> {
+    debug!("Focus window request: {}", handle);
+
+    // Switching desktops waits for Explorer to catch up
+    let automation = Arc::clone(&state.automation);
+    tokio::task::spawn_blocking(move || automation.windows().focus_window(handle as usize))
+        .await
+        .map_err(|e| AppError::Internal(format!("Focus task failed: {}", e)))?
+        .map_err(|e| window_error(handle, e))?;
+
+    Ok(Json(AutomationResponse {
+        success: true,
+        message: Some(format!("Focused window {}", handle)),
+    }))
+}
+
+/// POST /automation/windows/:handle/move - Move and resize a window
+///
+/// Restores a minimized or maximized window before applying the new bounds.
+///
+/// # Path Parameters
+/// - handle: Window handle from `/automation/windows`
+///
+/// # Request Body
+/// - x, y: Optional new top-left corner
+/// - width, height: Optional new size in pixels
+pub async fn move_window(
+    State(state): State<Arc<AppState>>,
+    Path(handle): Path<u64>,
+    Json(req): Json<WindowMoveRequest>,
+) -> Result<Json<WindowResponse>> {
+    debug!("Move window request: {} {:?}", handle, req);
+
+    if req.x.is_none() && req.y.is_none() && req.width.is_none() && req.height.is_none() {
+        return Err(AppError::InvalidRequest(
+            "At least one of x, y, width or height is required".to_string(),
+        ));
+    }
+    if req.width.is_some_and(|w| w <= 0) || req.height.is_some_and(|h| h <= 0) {
+        return Err(AppError::InvalidRequest(
+            "Width and height must be positive".to_string(),
+        ));
+    }
+
+    let window = state
+        .automation
+        .windows()
+        .move_window(handle as usize, req.x, req.y, req.width, req.height)
+        .map_err(|e| window_error(handle, e))?;
+
+    Ok(Json(WindowResponse::from(window)))
+}
+
+/// POST /automation/windows/:handle/desktop - Move a window to another virtual desktop
+///
+/// # Path Parameters
+/// - handle: Window handle from `/automation/windows`
+///
+/// # Request Body
+/// - desktop_id: Target desktop ID from `/automation/desktops`
+pub async fn move_window_to_desktop(
+    State(state): State<Arc<AppState>>,
+    Path(handle): Path<u64>,
+    Json(req): Json<WindowDesktopRequest>,
+) -> Result<Json<WindowResponse>> {
+    debug!("Move window to desktop request: {} {:?}", handle, req);
+
+    let automation = Arc::clone(&state.automation);
+    let window = tokio::task::spawn_blocking(move || {
+        let windows = automation.windows();
+        // Check the handle first so an unknown desktop is not reported as a missing window
+        windows
+            .get_window(handle as usize)
+            .map_err(|e| window_error(handle, e))?;
+        windows
+            .move_window_to_desktop(handle as usize, &req.desktop_id)
+            .map_err(desktop_error)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Move window task failed: {}", e)))??;
+
+    Ok(Json(WindowResponse::from(window)))
+}
+
+/// Map a virtual desktop error, reporting unknown desktop IDs as 404
+fn desktop_error(e: AutomationError) -> AppError {
+    match e {
+        AutomationError::InvalidArgument(message) => AppError::NotFound(message),
+        e => {
+            error!("Virtual desktop operation failed: {}", e);
+            AppError::Automation(e)
+        }
+    }
+}
+
+/// GET /automation/desktops - List virtual desktops
+///
+/// Desktops are listed in task view order; `is_current` marks the shown one.
+pub async fn list_desktops(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<VirtualDesktop>>> {
+    debug!("List virtual desktops request");
+
+    let desktops = state
+        .automation
+        .windows()
+        .virtual_desktops()
+        .map_err(desktop_error)?;
+
+    Ok(Json(desktops))
+}
+
+/// POST /automation/desktops/:id/switch - Show another virtual desktop
+///
+/// # Path Parameters
+/// - id: Desktop ID from `/automation/desktops`
+pub async fn switch_desktop(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<AutomationResponse>> {
+    debug!("Switch virtual desktop request: {}", id);
+
+    let automation = Arc::clone(&state.automation);
+    let desktop_id = id.clone();
+    tokio::task::spawn_blocking(move || automation.windows().switch_virtual_desktop(&desktop_id))
+        .await
+        .map_err(|e| AppError::Internal(format!("Desktop switch task failed: {}", e)))?
+        .map_err(desktop_error)?;
+
+    Ok(Json(AutomationResponse {
+        success: true,
+        message: Some(format!("Switched to virtual desktop {}", id)),
+    }))
+}
+
+/// POST /automation/windows/:handle/:action - Change window state
+///
+/// # Path Parameters
+/// - handle: Window handle from `/automation/windows`
+/// - action: "minimize", "maximize", "restore" or "close"
+///
+/// Closing posts a close request, so the application may still prompt the
+/// user (e.g. to save changes) or refuse.
+pub async fn window_action(
+    State(state): State<Arc<AppState>>,
+    Path((handle, action)): Path<(u64, String)>,
+) -> Result<Json<AutomationResponse>> {
+    debug!("Window action request: {} {}", action, handle);
+
+    let windows = state.automation.windows();
+    let result = match action.as_str() {
+        "minimize" => windows.minimize_window(handle as usize),
+        "maximize" => windows.maximize_window(handle as usize),
+        "restore" => windows.restore_window(handle as usize),
+        "close" => windows.close_window(handle as usize),
+        _ => {
+            return Err(AppError::InvalidRequest(format!(
+                "Invalid window action '{}'. Use minimize, maximize, restore or close",
+                action
+            )))
+        }
+    };
+    result.map_err(|e| window_error(handle, e))?;
+
+    Ok(Json(AutomationResponse {
+        success: true,
+        message: Some(format!("Window {}: {}", handle, action)),
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,85 @@
+//! Calendar Export Handlers
+//!
+//! Work sessions detected from captured frames as an iCalendar feed (see
+//! [`crate::ical`]).
+
+use crate::daily_notes;
+use crate::error::{AppError, Result};
+use crate::ical::{render_calendar, work_sessions};
+use crate::state::AppState;
+use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Days covered by default
+const DEFAULT_DAYS: i64 = 30;
+/// Days covered at most
+const MAX_DAYS: i64 = 366;
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Calendar export query parameters
+#[derive(Debug, Deserialize)]
+pub struct CalendarExportQuery {
+    /// Start of the range (default: 30 days before `end`)
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// End of the range (default: now)
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /export/ical - Work sessions as an iCalendar feed
+///
+/// # Query Parameters
+/// - start: Start of the range (default: 30 days before end)
+/// - end: End of the range (default: now)
+pub async fn export_ical(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<CalendarExportQuery>,
+) -> Result<impl IntoResponse> {
+    let end = params.end.unwrap_or_else(Utc::now);
+    let start = params
+        .start
+        .unwrap_or_else(|| end - Duration::days(DEFAULT_DAYS));
+    debug!("Calendar export request: {} to {}", start, end);
+
+    if start >= end {
+        return Err(AppError::InvalidRequest(
+            "start must be before end".to_string(),
+        ));
+    }
+    if end - start > Duration::days(MAX_DAYS) {
+        return Err(AppError::InvalidRequest(format!(
+            "The range can span {} days at most",
+            MAX_DAYS
+        )));
+    }
+
+    // Work sessions join across the days
+    let sessions = daily_notes::load_sessions(&state.db, start, end).await?;
+
+    let calendar = render_calendar(&work_sessions(&sessions), Utc::now());
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "inline; filename=\"screensearch-sessions.ics\"",
+            ),
+        ],
+        calendar,
+    ))
+}
@@ -0,0 +1,88 @@
+//! Daily Notes Export Handlers
+//!
+//! Writes Markdown daily notes into the configured vault (see
+//! [`crate::workers::markdown_export`]).
+
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use crate::workers::enqueue_markdown_export;
+use crate::workers::markdown_export::MAX_EXPORT_DAYS;
+use axum::extract::{Json, State};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Daily notes export request
+#[derive(Debug, Default, Deserialize)]
+pub struct MarkdownExportRequest {
+    /// First day to export, local date (default: `to`)
+    #[serde(default)]
+    pub from: Option<NaiveDate>,
+
+    /// Last day to export, local date (default: today)
+    #[serde(default)]
+    pub to: Option<NaiveDate>,
+}
+
+/// Queued daily notes export
+#[derive(Debug, Serialize)]
+pub struct MarkdownExportResponse {
+    /// Job writing the notes (see `GET /jobs/:id`)
+    pub job_id: i64,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Folder the notes are written to
+    pub notes_dir: String,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// POST /export/markdown - Write the daily notes of a range of days
+///
+/// The notes are written by a `markdown_export` job.
+pub async fn export_markdown(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<MarkdownExportRequest>,
+) -> Result<Json<MarkdownExportResponse>> {
+    debug!("Markdown export request: {:?}", request);
+
+    let Some(config) = state.markdown_export.get() else {
+        return Err(AppError::NotFound(
+            "Daily notes export is not configured (markdown_export.vault_dir)".to_string(),
+        ));
+    };
+
+    let to = request.to.unwrap_or_else(|| Local::now().date_naive());
+    let from = request.from.unwrap_or(to);
+    if from > to {
+        return Err(AppError::InvalidRequest(
+            "from must not be after to".to_string(),
+        ));
+    }
+    if (to - from).num_days() >= MAX_EXPORT_DAYS {
+        return Err(AppError::InvalidRequest(format!(
+            "At most {} days can be exported at once",
+            MAX_EXPORT_DAYS
+        )));
+    }
+
+    let job_id = enqueue_markdown_export(&state.db, from, to).await?;
+    info!(
+        "Queued daily notes export of {} to {} (job {})",
+        from, to, job_id
+    );
+
+    Ok(Json(MarkdownExportResponse {
+        job_id,
+        from,
+        to,
+        notes_dir: config.notes_dir().to_string_lossy().to_string(),
+    }))
+}
@@ -0,0 +1,236 @@
+//! Document Handlers
+//!
+//! Search receipts and invoices extracted from captured frames by
+//! [`crate::documents`], and extract one from a chosen frame on request.
+
+use crate::documents::{self, DocumentType, LineItem, TextLine};
+use crate::error::{AppError, Result};
+use crate::models::{Paginated, PaginationInfo};
+use crate::state::AppState;
+use axum::extract::{Json, Path, Query, State};
+use chrono::{DateTime, NaiveDate, Utc};
+use screensearch_db::{DocumentRecord, Pagination};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, error};
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Document query parameters
+#[derive(Debug, Deserialize)]
+pub struct DocumentQuery {
+    /// Text matched against the vendor, line items and document text
+    #[serde(default)]
+    pub q: Option<String>,
+
+    /// Only documents of this type (`receipt` or `invoice`)
+    #[serde(default, rename = "type")]
+    pub doc_type: Option<DocumentType>,
+
+    /// Only documents captured at or after this time (ISO 8601)
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// Only documents captured at or before this time (ISO 8601)
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+
+    /// Maximum results (default: 50, max: 1000)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Pagination offset
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Extract document request
+#[derive(Debug, Deserialize)]
+pub struct ExtractDocumentRequest {
+    /// Frame showing the document
+    pub frame_id: i64,
+
+    /// Treat the frame as this type instead of classifying its text
+    #[serde(default, rename = "type")]
+    pub doc_type: Option<DocumentType>,
+}
+
+/// Stored document with decoded line items
+#[derive(Debug, Serialize)]
+pub struct DocumentResponse {
+    pub id: i64,
+    pub frame_id: i64,
+    #[serde(rename = "type")]
+    pub doc_type: String,
+    pub vendor: Option<String>,
+    pub date: Option<NaiveDate>,
+    pub total: Option<f64>,
+    pub currency: Option<String>,
+    pub line_items: Vec<LineItem>,
+    pub text: String,
+    pub app_name: Option<String>,
+    pub captured_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<DocumentRecord> for DocumentResponse {
+    type Error = AppError;
+
+    fn try_from(record: DocumentRecord) -> Result<Self> {
+        let line_items = serde_json::from_str(&record.line_items).map_err(|e| {
+            AppError::Internal(format!(
+                "Document {} has invalid line items: {}",
+                record.id, e
+            ))
+        })?;
+
+        Ok(Self {
+            id: record.id,
+            frame_id: record.frame_id,
+            doc_type: record.doc_type,
+            vendor: record.vendor,
+            date: record.document_date,
+            total: record.total,
+            currency: record.currency,
+            line_items,
+            text: record.text,
+            app_name: record.app_name,
+            captured_at: record.captured_at,
+            created_at: record.created_at,
+        })
+    }
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /documents - Search extracted receipts and invoices, newest first
+///
+/// Returns one page of documents with the total number of matching documents.
+///
+/// # Query Parameters
+/// - q: Text matched against vendor, line items and document text
+/// - type: `receipt` or `invoice`
+/// - start / end: Optional capture time range (ISO 8601)
+/// - limit: Maximum results (default: 50, max: 1000)
+/// - offset: Pagination offset
+pub async fn list_documents(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DocumentQuery>,
+) -> Result<Json<Paginated<DocumentResponse>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(50).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    let query = params.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    debug!(
+        "List documents request: q={:?}, type={:?}, limit={}, offset={}",
+        query, params.doc_type, pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let doc_type = params.doc_type.map(|doc_type| doc_type.as_str());
+    let documents = match state
+        .db
+        .list_documents(query, doc_type, params.start, params.end, pagination)
+        .await
+    {
+        Ok(documents) => state
+            .db
+            .count_documents(query, doc_type, params.start, params.end)
+            .await
+            .map(|total| (documents, total)),
+        Err(e) => Err(e),
+    };
+    let (documents, total) = documents.map_err(|e| {
+        error!("Failed to list documents: {}", e);
+        AppError::Database(e)
+    })?;
+
+    let data = documents
+        .into_iter()
+        .map(DocumentResponse::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Json(Paginated {
+        pagination: PaginationInfo::counted(limit, offset, data.len(), total),
+        data,
+    }))
+}
+
+/// GET /documents/:id - Get an extracted document
+///
+/// # Path Parameters
+/// - id: Document ID
+pub async fn get_document(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<DocumentResponse>> {
+    debug!("Get document request: id={}", id);
+
+    match state.db.get_document(id).await {
+        Ok(Some(document)) => Ok(Json(document.try_into()?)),
+        Ok(None) => Err(AppError::NotFound(format!("Document {} not found", id))),
+        Err(e) => {
+            error!("Failed to get document {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// POST /documents/extract - Extract a document from a stored frame
+///
+/// Frames are checked automatically as they are captured; this covers
+/// frames the automatic check missed. Returns the existing document when
+/// the same one is already stored.
+///
+/// # Request Body
+/// - frame_id: Frame showing the receipt or invoice
+/// - type: Optional `receipt` or `invoice` to skip classification
+pub async fn extract_document(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ExtractDocumentRequest>,
+) -> Result<Json<DocumentResponse>> {
+    debug!(
+        "Extract document request: frame_id={}, type={:?}",
+        req.frame_id, req.doc_type
+    );
+
+    let frame = state
+        .db
+        .get_frame(req.frame_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Frame {} not found", req.frame_id)))?;
+    let lines: Vec<TextLine> = state
+        .db
+        .get_ocr_text_for_frame(frame.id)
+        .await?
+        .iter()
+        .map(TextLine::from)
+        .collect();
+
+    let document = documents::extract(&lines, req.doc_type).ok_or_else(|| {
+        AppError::InvalidRequest(format!(
+            "Frame {} does not look like a receipt or invoice; pass a type to extract it anyway",
+            frame.id
+        ))
+    })?;
+
+    let record = state
+        .documents
+        .store(
+            frame.id,
+            frame.timestamp,
+            frame.active_process.as_deref(),
+            document,
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to store document from frame {}: {}", frame.id, e);
+            AppError::Database(e)
+        })?;
+
+    Ok(Json(record.try_into()?))
+}
@@ -3,12 +3,18 @@
 //! Provides endpoints for managing and querying vector embeddings
 //! used for RAG-enhanced intelligence reports.
 
-use crate::error::Result;
+use crate::error::{AppError, Result};
 use crate::state::AppState;
+use crate::workers::{ensure_embedding_job, EMBEDDINGS_JOB};
 use axum::extract::{Json, State};
+use chrono::{DateTime, Utc};
+use screensearch_db::{EmbeddingIndexStats, EmbeddingQueueStats, JobRecord, Pagination};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tracing::debug;
+use tracing::{debug, info};
+
+/// Window over which queue throughput is measured for the ETA, in minutes
+const THROUGHPUT_WINDOW_MINUTES: i64 = 10;
 
 // ============================================================
 // Models
@@ -23,6 +29,173 @@ pub struct EmbeddingStatusResponse {
     pub frames_with_embeddings: i64,
     pub coverage_percent: f32,
     pub last_processed_frame_id: i64,
+    pub queue: EmbeddingQueueStatus,
+}
+
+/// Embedding queue progress
+#[derive(Debug, Serialize)]
+pub struct EmbeddingQueueStatus {
+    /// Frames ever queued (pending, processing, completed, skipped or failed)
+    pub total: i64,
+    pub pending: i64,
+    pub processing: i64,
+    pub completed: i64,
+    /// Frames without OCR text, which have nothing to embed
+    pub skipped: i64,
+    /// Frames that failed every attempt
+    pub failed: i64,
+    /// Whether an `embeddings` job is queued or running
+    pub backfill_running: bool,
+    /// Frames finished per minute over the last 10 minutes
+    pub throughput_per_minute: f64,
+    /// Estimated seconds until the queue is empty, if there is recent throughput
+    pub eta_seconds: Option<u64>,
+}
+
+impl EmbeddingQueueStatus {
+    fn new(stats: &EmbeddingQueueStats, backfill_running: bool) -> Self {
+        let throughput_per_minute = stats.finished_since as f64 / THROUGHPUT_WINDOW_MINUTES as f64;
+        let remaining = stats.pending + stats.processing;
+        let eta_seconds = if remaining == 0 {
+            Some(0)
+        } else if throughput_per_minute > 0.0 {
+            Some((remaining as f64 / throughput_per_minute * 60.0).ceil() as u64)
+        } else {
+            None
+        };
+
+        Self {
+            total: stats.total(),
+            pending: stats.pending,
+            processing: stats.processing,
+            completed: stats.completed,
+            skipped: stats.skipped,
+            failed: stats.failed,
+            backfill_running,
+            throughput_per_minute,
+            eta_seconds,
+        }
+    }
+}
+
+/// Response for the embedding statistics endpoint
+#[derive(Debug, Serialize)]
+pub struct EmbeddingStatsResponse {
+    pub enabled: bool,
+    pub model: EmbeddingModelInfo,
+    pub frames: EmbeddingFrameStats,
+    pub chunks: EmbeddingChunkStats,
+    pub index: EmbeddingIndexSize,
+    /// Newest `embeddings` job, queued, running or finished
+    pub last_run: Option<EmbeddingRunInfo>,
+}
+
+/// Embedding model in use
+#[derive(Debug, Serialize)]
+pub struct EmbeddingModelInfo {
+    pub name: &'static str,
+    pub dimension: usize,
+    /// Whether the model is loaded in memory
+    pub loaded: bool,
+}
+
+/// Frames by embedding state
+///
+/// Pending and failed work is counted in frames, since a frame is only
+/// split into chunks when it is embedded.
+#[derive(Debug, Serialize)]
+pub struct EmbeddingFrameStats {
+    pub total: i64,
+    /// Frames with at least one embedded chunk
+    pub embedded: i64,
+    pub coverage_percent: f32,
+    pub pending: i64,
+    pub processing: i64,
+    /// Frames that failed every attempt
+    pub failed: i64,
+    /// Frames without OCR text, which have nothing to embed
+    pub skipped: i64,
+}
+
+/// Embedded text chunks
+#[derive(Debug, Serialize)]
+pub struct EmbeddingChunkStats {
+    pub embedded: i64,
+    /// Average chunks per embedded frame
+    pub per_frame: f64,
+    /// Chunks embedded per minute over the last 10 minutes
+    pub throughput_per_minute: f64,
+    pub last_embedded_at: Option<DateTime<Utc>>,
+}
+
+/// Storage used by the embeddings in the database
+#[derive(Debug, Serialize)]
+pub struct EmbeddingIndexSize {
+    pub vector_bytes: i64,
+    pub text_bytes: i64,
+    pub total_bytes: i64,
+}
+
+/// An `embeddings` job draining the queue
+#[derive(Debug, Serialize)]
+pub struct EmbeddingRunInfo {
+    pub job_id: i64,
+    pub state: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<JobRecord> for EmbeddingRunInfo {
+    fn from(job: JobRecord) -> Self {
+        Self {
+            job_id: job.id,
+            state: job.state,
+            started_at: job.started_at,
+            finished_at: job.finished_at,
+        }
+    }
+}
+
+impl EmbeddingChunkStats {
+    fn new(index: &EmbeddingIndexStats, frames_embedded: i64) -> Self {
+        let per_frame = if frames_embedded > 0 {
+            index.chunks as f64 / frames_embedded as f64
+        } else {
+            0.0
+        };
+
+        Self {
+            embedded: index.chunks,
+            per_frame,
+            throughput_per_minute: index.chunks_since as f64 / THROUGHPUT_WINDOW_MINUTES as f64,
+            last_embedded_at: index.last_embedded_at,
+        }
+    }
+}
+
+/// Request to backfill embeddings for existing frames
+#[derive(Debug, Default, Deserialize)]
+pub struct BackfillEmbeddingsRequest {
+    /// Only queue frames captured at or after this time
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>,
+    /// Only queue frames captured at or before this time
+    #[serde(default)]
+    pub end_time: Option<DateTime<Utc>>,
+    /// Frames processed per batch (default: 50)
+    #[serde(default)]
+    pub batch_size: Option<i64>,
+}
+
+/// Response from starting a backfill
+#[derive(Debug, Serialize)]
+pub struct BackfillEmbeddingsResponse {
+    pub success: bool,
+    pub message: String,
+    /// Frames added to the queue by this request
+    pub frames_queued: u64,
+    /// ID of the `embeddings` job draining the queue (see `GET /jobs/{id}`)
+    pub job_id: i64,
 }
 
 /// Request to trigger embedding generation
@@ -52,6 +225,9 @@ pub async fn get_embedding_status(
     debug!("Getting embedding status");
 
     let status = state.db.get_embedding_status().await?;
+    let since = Utc::now() - chrono::Duration::minutes(THROUGHPUT_WINDOW_MINUTES);
+    let queue_stats = state.db.get_embedding_queue_stats(since).await?;
+    let backfill_running = state.db.find_active_job(EMBEDDINGS_JOB).await?.is_some();
 
     Ok(Json(EmbeddingStatusResponse {
         enabled: status.enabled,
@@ -60,6 +236,114 @@ pub async fn get_embedding_status(
         frames_with_embeddings: status.frames_with_embeddings,
         coverage_percent: status.coverage_percent,
         last_processed_frame_id: status.last_processed_frame_id,
+        queue: EmbeddingQueueStatus::new(&queue_stats, backfill_running),
+    }))
+}
+
+/// GET /embeddings/stats
+/// Get embedding statistics for a dashboard
+///
+/// Tells whether semantic search covers the captured frames: how many are
+/// embedded, queued or failed, how fast the queue drains, the model and the
+/// storage used.
+pub async fn get_embedding_stats(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<EmbeddingStatsResponse>> {
+    debug!("Getting embedding stats");
+
+    let status = state.db.get_embedding_status().await?;
+    let since = Utc::now() - chrono::Duration::minutes(THROUGHPUT_WINDOW_MINUTES);
+    let queue = state.db.get_embedding_queue_stats(since).await?;
+    let index = state.db.get_embedding_index_stats(since).await?;
+    let last_run = state
+        .db
+        .list_jobs(
+            None,
+            Some(EMBEDDINGS_JOB),
+            Pagination {
+                limit: 1,
+                offset: 0,
+            },
+        )
+        .await?
+        .into_iter()
+        .next()
+        .map(EmbeddingRunInfo::from);
+    let loaded = state.embedding_engine.read().await.is_some();
+
+    Ok(Json(EmbeddingStatsResponse {
+        enabled: status.enabled,
+        model: EmbeddingModelInfo {
+            name: screensearch_embeddings::MODEL_NAME,
+            dimension: screensearch_embeddings::EMBEDDING_DIM,
+            loaded,
+        },
+        frames: EmbeddingFrameStats {
+            total: status.total_frames,
+            embedded: status.frames_with_embeddings,
+            coverage_percent: status.coverage_percent,
+            pending: queue.pending,
+            processing: queue.processing,
+            failed: queue.failed,
+            skipped: queue.skipped,
+        },
+        chunks: EmbeddingChunkStats::new(&index, status.frames_with_embeddings),
+        index: EmbeddingIndexSize {
+            vector_bytes: index.vector_bytes,
+            text_bytes: index.text_bytes,
+            total_bytes: index.vector_bytes + index.text_bytes,
+        },
+        last_run,
+    }))
+}
+
+/// POST /embeddings/backfill
+/// Queue frames missing embeddings and process them in the background
+///
+/// Frames in the optional time range are added to the embedding queue, which
+/// is drained by an `embeddings` job on the job queue. Only one such job is
+/// active at a time; calling again while it runs just adds frames to the
+/// queue. Progress is reported by `GET /embeddings/status`.
+pub async fn backfill_embeddings(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<BackfillEmbeddingsRequest>,
+) -> Result<Json<BackfillEmbeddingsResponse>> {
+    debug!(
+        "Embedding backfill request: {:?} - {:?}",
+        payload.start_time, payload.end_time
+    );
+
+    if let (Some(start), Some(end)) = (payload.start_time, payload.end_time) {
+        if start > end {
+            return Err(AppError::InvalidRequest(
+                "start_time must be before end_time".to_string(),
+            ));
+        }
+    }
+    let batch_size = payload.batch_size.unwrap_or(50).clamp(1, 500);
+
+    // Load the model before queuing so a failure is reported here
+    state
+        .get_embedding_engine()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to load embedding engine: {}", e)))?;
+
+    let frames_queued = state
+        .db
+        .enqueue_embedding_backfill(payload.start_time, payload.end_time)
+        .await?;
+
+    let job_id = ensure_embedding_job(&state.db, batch_size).await?;
+    info!(
+        "Queued {} frames for embedding backfill (job {})",
+        frames_queued, job_id
+    );
+
+    Ok(Json(BackfillEmbeddingsResponse {
+        success: true,
+        message: format!("Queued {} frames for embedding backfill", frames_queued),
+        frames_queued,
+        job_id,
     }))
 }
 
@@ -179,3 +463,53 @@ pub async fn toggle_embeddings(
     // Return updated status
     get_embedding_status(State(state)).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_status_eta() {
+        let stats = EmbeddingQueueStats {
+            pending: 90,
+            processing: 10,
+            completed: 40,
+            finished_since: 50,
+            ..Default::default()
+        };
+
+        // 50 frames in 10 minutes = 5/min, 100 remaining = 20 minutes
+        let status = EmbeddingQueueStatus::new(&stats, true);
+        assert_eq!(status.total, 140);
+        assert_eq!(status.throughput_per_minute, 5.0);
+        assert_eq!(status.eta_seconds, Some(1200));
+
+        // No recent progress means no estimate
+        let stalled = EmbeddingQueueStats {
+            pending: 10,
+            ..Default::default()
+        };
+        assert_eq!(EmbeddingQueueStatus::new(&stalled, false).eta_seconds, None);
+
+        // Empty queue is done
+        let done = EmbeddingQueueStats::default();
+        assert_eq!(EmbeddingQueueStatus::new(&done, false).eta_seconds, Some(0));
+    }
+
+    #[test]
+    fn test_chunk_stats() {
+        let index = EmbeddingIndexStats {
+            chunks: 120,
+            chunks_since: 30,
+            ..Default::default()
+        };
+
+        // 120 chunks over 40 frames, 30 chunks in 10 minutes
+        let stats = EmbeddingChunkStats::new(&index, 40);
+        assert_eq!(stats.per_frame, 3.0);
+        assert_eq!(stats.throughput_per_minute, 3.0);
+
+        let empty = EmbeddingChunkStats::new(&EmbeddingIndexStats::default(), 0);
+        assert_eq!(empty.per_frame, 0.0);
+    }
+}
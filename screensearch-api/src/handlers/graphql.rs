@@ -0,0 +1,37 @@
+//! GraphQL Handlers
+//!
+//! The GraphQL endpoint over frames, tags, sessions and search (see
+//! [`crate::graphql`]). Only compiled with the `graphql` feature.
+
+use crate::graphql;
+use crate::state::AppState;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::Json;
+use std::sync::Arc;
+use tracing::debug;
+
+/// POST /graphql - Execute a GraphQL query
+///
+/// Takes the standard `{"query", "operationName", "variables"}` body and
+/// returns `{"data", "errors"}`. Failing fields are reported in `errors`
+/// with status 200, as GraphQL clients expect.
+pub async fn graphql_query(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    debug!(
+        "GraphQL request: operation={:?}",
+        request.operation_name.as_deref()
+    );
+    Json(graphql::execute(state, request).await)
+}
+
+/// GET /graphql - The GraphQL schema in SDL
+pub async fn graphql_schema() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        graphql::schema().sdl(),
+    )
+}
@@ -0,0 +1,236 @@
+//! Job Queue Handlers
+//!
+//! Inspect and cancel background jobs (embedding backfills, scheduled reports)
+//! run by the persistent job queue.
+
+use crate::error::{AppError, Result};
+use crate::models::{Paginated, PaginationInfo};
+use crate::state::AppState;
+use axum::extract::{Json, Path, Query, State};
+use chrono::{DateTime, Utc};
+use screensearch_db::{JobRecord, Pagination};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{debug, error, info};
+
+/// Job states accepted by the `state` filter
+const JOB_STATES: [&str; 5] = ["queued", "running", "completed", "failed", "cancelled"];
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Job list query parameters
+#[derive(Debug, Deserialize)]
+pub struct JobQuery {
+    /// Only jobs in this state
+    #[serde(default)]
+    pub state: Option<String>,
+
+    /// Only jobs of this type
+    #[serde(default, rename = "type")]
+    pub job_type: Option<String>,
+
+    /// Maximum results to return (default: 50)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Offset for pagination (default: 0)
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Job with decoded payload and result
+#[derive(Debug, Serialize)]
+pub struct JobResponse {
+    pub id: i64,
+    #[serde(rename = "type")]
+    pub job_type: String,
+    pub state: String,
+    pub payload: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub run_after: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<JobRecord> for JobResponse {
+    fn from(record: JobRecord) -> Self {
+        // Payloads and results are written as JSON by the job queue; fall back
+        // to the raw string rather than failing the whole listing
+        let decode = |s: &str| serde_json::from_str(s).unwrap_or_else(|_| Value::from(s));
+
+        Self {
+            id: record.id,
+            job_type: record.job_type,
+            state: record.state,
+            payload: decode(record.payload.as_str()),
+            result: record.result.as_deref().map(decode),
+            attempts: record.attempts,
+            max_attempts: record.max_attempts,
+            last_error: record.last_error,
+            run_after: record.run_after,
+            created_at: record.created_at,
+            started_at: record.started_at,
+            finished_at: record.finished_at,
+        }
+    }
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /jobs - List background jobs, newest first
+///
+/// Returns one page of jobs with the total number of matching jobs.
+///
+/// # Query Parameters
+/// - state: Filter by state (queued, running, completed, failed, cancelled)
+/// - type: Filter by job type (e.g. "embeddings", "report")
+/// - limit: Maximum results (default: 50, max: 1000)
+/// - offset: Pagination offset
+pub async fn list_jobs(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<JobQuery>,
+) -> Result<Json<Paginated<JobResponse>>> {
+    if let Some(job_state) = params.state.as_deref() {
+        if !JOB_STATES.contains(&job_state) {
+            return Err(AppError::InvalidRequest(format!(
+                "Invalid state '{}' (expected one of: {})",
+                job_state,
+                JOB_STATES.join(", ")
+            )));
+        }
+    }
+
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(50).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "List jobs request: state={:?}, type={:?}, limit={}, offset={}",
+        params.state, params.job_type, pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let (job_state, job_type) = (params.state.as_deref(), params.job_type.as_deref());
+    let jobs = match state.db.list_jobs(job_state, job_type, pagination).await {
+        Ok(jobs) => state
+            .db
+            .count_jobs(job_state, job_type)
+            .await
+            .map(|total| (jobs, total)),
+        Err(e) => Err(e),
+    };
+    match jobs {
+        Ok((jobs, total)) => Ok(Json(Paginated {
+            pagination: PaginationInfo::counted(limit, offset, jobs.len(), total),
+            data: jobs.into_iter().map(JobResponse::from).collect(),
+        })),
+        Err(e) => {
+            error!("Failed to list jobs: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// GET /jobs/:id - Get a job
+///
+/// # Path Parameters
+/// - id: Job ID
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<JobResponse>> {
+    debug!("Get job request: id={}", id);
+
+    Ok(Json(fetch_job(&state, id).await?))
+}
+
+/// POST /jobs/:id/cancel - Cancel a queued or running job
+///
+/// Queued jobs never start. Running jobs stop at their next cancellation
+/// check; work already done (e.g. embeddings stored) is kept.
+///
+/// # Path Parameters
+/// - id: Job ID
+pub async fn cancel_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<JobResponse>> {
+    debug!("Cancel job request: id={}", id);
+
+    match state.db.cancel_job(id).await {
+        Ok(true) => info!("Cancelled job {}", id),
+        Ok(false) => {
+            let job = fetch_job(&state, id).await?;
+            return Err(AppError::InvalidRequest(format!(
+                "Job {} is already {}",
+                id, job.state
+            )));
+        }
+        Err(e) => {
+            error!("Failed to cancel job {}: {}", id, e);
+            return Err(AppError::Database(e));
+        }
+    }
+
+    Ok(Json(fetch_job(&state, id).await?))
+}
+
+async fn fetch_job(state: &AppState, id: i64) -> Result<JobResponse> {
+    match state.db.get_job(id).await {
+        Ok(Some(record)) => Ok(JobResponse::from(record)),
+        Ok(None) => Err(AppError::NotFound(format!("Job {} not found", id))),
+        Err(e) => {
+            error!("Failed to get job {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(payload: &str, result: Option<&str>) -> JobRecord {
+        JobRecord {
+            id: 1,
+            job_type: "embeddings".to_string(),
+            payload: payload.to_string(),
+            state: "completed".to_string(),
+            attempts: 1,
+            max_attempts: 3,
+            last_error: None,
+            result: result.map(str::to_string),
+            run_after: Utc::now(),
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    #[test]
+    fn test_job_response_decodes_json() {
+        let job = JobResponse::from(record(r#"{"batch_size":50}"#, Some(r#"{"frames":3}"#)));
+        assert_eq!(job.payload["batch_size"], 50);
+        assert_eq!(job.result.unwrap()["frames"], 3);
+    }
+
+    #[test]
+    fn test_job_response_keeps_invalid_json() {
+        let job = JobResponse::from(record("not json", None));
+        assert_eq!(job.payload, Value::from("not json"));
+        assert!(job.result.is_none());
+    }
+}
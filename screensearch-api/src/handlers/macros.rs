@@ -0,0 +1,361 @@
+//! Macro Handlers
+//!
+//! Record user input (clicks with the element under the cursor, keystrokes and
+//! window switches) into stored macros and replay them through the automation
+//! engine.
+
+use crate::error::{AppError, Result};
+use crate::models::{Paginated, PaginationInfo};
+use crate::state::AppState;
+use axum::extract::{Json, Path, Query, State};
+use chrono::{DateTime, Utc};
+use screensearch_automation::{MacroEvent, MacroRecorder};
+use screensearch_db::{MacroRecord, NewMacro, Pagination};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, error, info};
+
+/// Maximum macro name length
+const MAX_MACRO_NAME_LEN: usize = 100;
+/// Slowest and fastest allowed replay speed multipliers
+const MIN_REPLAY_SPEED: f64 = 0.1;
+const MAX_REPLAY_SPEED: f64 = 10.0;
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Stop recording request
+#[derive(Debug, Default, Deserialize)]
+pub struct RecordStopRequest {
+    /// Macro name (default: "Macro <timestamp>")
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Optional description
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Recording status response
+#[derive(Debug, Serialize)]
+pub struct RecordStatusResponse {
+    /// Whether a recording is in progress
+    pub recording: bool,
+
+    /// Time since the recording started in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
+}
+
+/// Macro list query parameters
+#[derive(Debug, Deserialize)]
+pub struct MacroQuery {
+    /// Maximum results to return (default: 50)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Offset for pagination (default: 0)
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Stored macro with decoded events
+#[derive(Debug, Serialize)]
+pub struct MacroResponse {
+    pub id: i64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub event_count: i64,
+    pub duration_ms: i64,
+    pub created_at: DateTime<Utc>,
+    pub events: Vec<MacroEvent>,
+}
+
+impl TryFrom<MacroRecord> for MacroResponse {
+    type Error = AppError;
+
+    fn try_from(record: MacroRecord) -> Result<Self> {
+        let events = serde_json::from_str(&record.events).map_err(|e| {
+            AppError::Internal(format!("Macro {} has invalid events: {}", record.id, e))
+        })?;
+
+        Ok(Self {
+            id: record.id,
+            name: record.name,
+            description: record.description,
+            event_count: record.event_count,
+            duration_ms: record.duration_ms,
+            created_at: record.created_at,
+            events,
+        })
+    }
+}
+
+/// Replay request
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplayRequest {
+    /// Speed multiplier (default: 1.0, range: 0.1-10.0)
+    #[serde(default)]
+    pub speed: Option<f64>,
+}
+
+/// Replay response
+#[derive(Debug, Serialize)]
+pub struct ReplayResponse {
+    pub success: bool,
+    pub events_replayed: usize,
+    pub duration_ms: u64,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// POST /automation/record/start - Start recording a macro
+///
+/// Installs global input hooks. Only one recording can run at a time.
+pub async fn start_recording(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RecordStatusResponse>> {
+    debug!("Start macro recording request");
+
+    let mut recorder = state
+        .macro_recorder
+        .lock()
+        .map_err(|e| AppError::Internal(format!("Macro recorder lock poisoned: {}", e)))?;
+
+    if recorder.is_some() {
+        return Err(AppError::InvalidRequest(
+            "A macro recording is already in progress".to_string(),
+        ));
+    }
+
+    *recorder = Some(MacroRecorder::start().map_err(|e| {
+        error!("Failed to start macro recording: {}", e);
+        AppError::Automation(e)
+    })?);
+
+    Ok(Json(RecordStatusResponse {
+        recording: true,
+        elapsed_ms: Some(0),
+    }))
+}
+
+/// GET /automation/record/status - Current recording status
+pub async fn recording_status(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<RecordStatusResponse>> {
+    let recorder = state
+        .macro_recorder
+        .lock()
+        .map_err(|e| AppError::Internal(format!("Macro recorder lock poisoned: {}", e)))?;
+
+    Ok(Json(RecordStatusResponse {
+        recording: recorder.is_some(),
+        elapsed_ms: recorder.as_ref().map(|r| r.elapsed_ms()),
+    }))
+}
+
+/// POST /automation/record/stop - Stop recording and store the macro
+///
+/// # Request Body
+/// - name: Optional macro name (default: "Macro <timestamp>")
+/// - description: Optional description
+pub async fn stop_recording(
+    State(state): State<Arc<AppState>>,
+    payload: Option<Json<RecordStopRequest>>,
+) -> Result<Json<MacroResponse>> {
+    let Json(req) = payload.unwrap_or_default();
+    debug!("Stop macro recording request: name={:?}", req.name);
+
+    let name = req
+        .name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("Macro {}", Utc::now().format("%Y-%m-%d %H:%M:%S")));
+    if name.len() > MAX_MACRO_NAME_LEN {
+        return Err(AppError::InvalidRequest(format!(
+            "Macro name must be <= {} characters",
+            MAX_MACRO_NAME_LEN
+        )));
+    }
+
+    let recorder = state
+        .macro_recorder
+        .lock()
+        .map_err(|e| AppError::Internal(format!("Macro recorder lock poisoned: {}", e)))?
+        .take()
+        .ok_or_else(|| AppError::InvalidRequest("No macro recording in progress".to_string()))?;
+
+    // Stopping joins the hook threads
+    let recorded = tokio::task::spawn_blocking(move || recorder.stop())
+        .await
+        .map_err(|e| AppError::Internal(format!("Macro recorder task failed: {}", e)))?
+        .map_err(AppError::Automation)?;
+
+    let events = serde_json::to_string(&recorded.events)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize macro: {}", e)))?;
+
+    let id = state
+        .db
+        .insert_macro(NewMacro {
+            name,
+            description: req.description,
+            events,
+            event_count: recorded.events.len() as i64,
+            duration_ms: recorded.duration_ms as i64,
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to store macro: {}", e);
+            AppError::Database(e)
+        })?;
+
+    info!("Stored macro {} with {} events", id, recorded.events.len());
+
+    Ok(Json(fetch_macro(&state, id).await?))
+}
+
+/// GET /automation/macros - List stored macros
+///
+/// Returns one page of macros, newest first, with the total number of macros.
+///
+/// # Query Parameters
+/// - limit: Maximum results to return (default: 50)
+/// - offset: Offset for pagination (default: 0)
+pub async fn list_macros(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<MacroQuery>,
+) -> Result<Json<Paginated<MacroResponse>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(50).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "List macros request: limit={}, offset={}",
+        pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let macros = match state.db.list_macros(pagination).await {
+        Ok(macros) => state.db.count_macros().await.map(|total| (macros, total)),
+        Err(e) => Err(e),
+    };
+    match macros {
+        Ok((macros, total)) => {
+            let data = macros
+                .into_iter()
+                .map(MacroResponse::try_from)
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Json(Paginated {
+                pagination: PaginationInfo::counted(limit, offset, data.len(), total),
+                data,
+            }))
+        }
+        Err(e) => {
+            error!("Failed to list macros: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// GET /automation/macros/:id - Get a stored macro
+///
+/// # Path Parameters
+/// - id: Macro ID
+pub async fn get_macro(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<MacroResponse>> {
+    debug!("Get macro request: id={}", id);
+
+    Ok(Json(fetch_macro(&state, id).await?))
+}
+
+/// DELETE /automation/macros/:id - Delete a stored macro
+///
+/// # Path Parameters
+/// - id: Macro ID
+pub async fn delete_macro(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    debug!("Delete macro request: id={}", id);
+
+    match state.db.delete_macro(id).await {
+        Ok(0) => Err(AppError::NotFound(format!("Macro {} not found", id))),
+        Ok(_) => Ok(Json(serde_json::json!({ "success": true }))),
+        Err(e) => {
+            error!("Failed to delete macro {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// POST /automation/macros/:id/replay - Replay a stored macro
+///
+/// # Path Parameters
+/// - id: Macro ID
+///
+/// # Request Body
+/// - speed: Optional speed multiplier (default: 1.0, range: 0.1-10.0)
+pub async fn replay_macro(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    payload: Option<Json<ReplayRequest>>,
+) -> Result<Json<ReplayResponse>> {
+    let Json(req) = payload.unwrap_or_default();
+    let speed = req
+        .speed
+        .unwrap_or(1.0)
+        .clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED);
+    debug!("Replay macro request: id={}, speed={}", id, speed);
+
+    if state
+        .macro_recorder
+        .lock()
+        .map(|recorder| recorder.is_some())
+        .unwrap_or(false)
+    {
+        return Err(AppError::InvalidRequest(
+            "Cannot replay while a macro recording is in progress".to_string(),
+        ));
+    }
+
+    let macro_record = fetch_macro(&state, id).await?;
+    let start = Instant::now();
+
+    match state
+        .automation
+        .replay_macro(&macro_record.events, speed)
+        .await
+    {
+        Ok(count) => {
+            info!("Replayed macro {} ({} events)", id, count);
+            Ok(Json(ReplayResponse {
+                success: true,
+                events_replayed: count,
+                duration_ms: start.elapsed().as_millis() as u64,
+            }))
+        }
+        Err(e) => {
+            error!("Macro replay failed: {}", e);
+            Err(AppError::Automation(e))
+        }
+    }
+}
+
+/// Load a macro or return 404
+async fn fetch_macro(state: &AppState, id: i64) -> Result<MacroResponse> {
+    match state.db.get_macro(id).await {
+        Ok(Some(record)) => MacroResponse::try_from(record),
+        Ok(None) => Err(AppError::NotFound(format!("Macro {} not found", id))),
+        Err(e) => {
+            error!("Failed to get macro {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
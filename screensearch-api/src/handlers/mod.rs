@@ -7,12 +7,60 @@ pub mod system;
 pub use automation::*;
 pub use search::*;
 pub use system::*;
+pub mod agent;
+pub use agent::*;
+pub mod alerts;
+pub use alerts::*;
+pub mod ask;
+pub use ask::*;
+pub mod audit;
+pub use audit::*;
+pub mod documents;
+pub use documents::*;
 pub mod ai;
 pub use ai::*;
+pub mod ai_history;
+pub use ai_history::*;
+pub mod calendar;
+pub use calendar::*;
+pub mod daily_notes;
+pub use daily_notes::*;
 pub mod embeddings;
 pub use embeddings::*;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "graphql")]
+pub use graphql::*;
+pub mod jobs;
+pub use jobs::*;
+pub mod macros;
+pub use macros::*;
+pub mod ollama;
+pub use ollama::*;
+pub mod privacy;
+pub use privacy::*;
+pub mod profiles;
+pub use profiles::*;
 pub mod rag_helpers;
+pub mod reports;
+pub use reports::*;
+pub mod replay;
+pub use replay::*;
 pub mod reranker;
+pub mod search_history;
+pub use search_history::*;
+pub mod setup;
+pub use setup::*;
+pub mod stats;
+pub use stats::*;
+pub mod summaries;
+pub use summaries::*;
+pub mod sync;
+pub use sync::*;
+pub mod timesheet;
+pub use timesheet::*;
+pub mod workflow;
+pub use workflow::*;
 
 
 
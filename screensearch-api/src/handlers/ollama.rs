@@ -0,0 +1,361 @@
+//! Ollama Model Management Handlers
+//!
+//! Proxies Ollama's model management API so clients can list, download and
+//! remove the models used for reports without a terminal. Download progress
+//! is published to `/events` as `ollama_pull` events.
+
+use crate::error::{AppError, Result};
+use crate::events::ServerEvent;
+use crate::handlers::ai::validate_provider_url;
+use crate::state::AppState;
+use axum::extract::{Json, Query, State};
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, error, info};
+
+/// Ollama server used when a request does not name one
+const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Minimum time between progress events of one download
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(500);
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Ollama server query parameters
+#[derive(Debug, Deserialize)]
+pub struct OllamaQuery {
+    /// Ollama server, or its OpenAI-compatible `/v1` URL
+    /// (default: http://localhost:11434)
+    #[serde(default)]
+    pub provider_url: Option<String>,
+}
+
+/// Request naming a model on an Ollama server
+#[derive(Debug, Deserialize)]
+pub struct OllamaModelRequest {
+    /// Ollama server, or its OpenAI-compatible `/v1` URL
+    /// (default: http://localhost:11434)
+    #[serde(default)]
+    pub provider_url: Option<String>,
+
+    /// Model name, e.g. "llama3.2" or "qwen2.5:7b"
+    pub model: String,
+}
+
+/// A model installed on the Ollama server
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    /// Size on disk in bytes
+    pub size: u64,
+    pub digest: String,
+    pub modified_at: DateTime<Utc>,
+    #[serde(default)]
+    pub details: Option<OllamaModelDetails>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OllamaModelDetails {
+    #[serde(default)]
+    pub family: Option<String>,
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+    #[serde(default)]
+    pub quantization_level: Option<String>,
+}
+
+/// Response of Ollama's `/api/tags`
+#[derive(Debug, Deserialize)]
+struct OllamaTagsResponse {
+    models: Vec<OllamaModel>,
+}
+
+/// Progress of a model download, published as an `ollama_pull` event
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaPullProgress {
+    pub model: String,
+    /// Ollama's description of the current step, e.g. "pulling manifest"
+    pub status: String,
+    /// Bytes of the current layer downloaded so far
+    pub completed: Option<u64>,
+    /// Bytes of the current layer
+    pub total: Option<u64>,
+    /// Set on the last event of the download
+    pub done: bool,
+    /// Why the download failed, on the last event
+    pub error: Option<String>,
+}
+
+/// One line of Ollama's streamed `/api/pull` response
+#[derive(Debug, Deserialize)]
+struct OllamaPullLine {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    completed: Option<u64>,
+    #[serde(default)]
+    total: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OllamaPullResponse {
+    pub model: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OllamaDeleteResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /ai/ollama/models - List the models installed on the Ollama server
+///
+/// # Query Parameters
+/// - provider_url: Ollama server (default: http://localhost:11434)
+pub async fn list_ollama_models(
+    Query(params): Query<OllamaQuery>,
+) -> Result<Json<Vec<OllamaModel>>> {
+    let base_url = ollama_base_url(params.provider_url.as_deref())?;
+    debug!("List Ollama models request: {}", base_url);
+
+    let res = reqwest::Client::new()
+        .get(format!("{}/api/tags", base_url))
+        .send()
+        .await
+        .map_err(|e| ollama_unreachable(&base_url, e))?;
+    let res = check_status(res).await?;
+
+    let tags: OllamaTagsResponse = res.json().await.map_err(|e| {
+        error!("Failed to parse Ollama model list: {}", e);
+        AppError::Internal(format!("Failed to parse Ollama model list: {}", e))
+    })?;
+    Ok(Json(tags.models))
+}
+
+/// POST /ai/ollama/pull - Download a model to the Ollama server
+///
+/// Returns 202 once the download has started. Progress, completion and
+/// failure are published to `/events` as `ollama_pull` events.
+pub async fn pull_ollama_model(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<OllamaModelRequest>,
+) -> Result<(StatusCode, Json<OllamaPullResponse>)> {
+    let base_url = ollama_base_url(payload.provider_url.as_deref())?;
+    let model = model_name(&payload.model)?;
+    info!("Pulling Ollama model {} on {}", model, base_url);
+
+    let res = reqwest::Client::new()
+        .post(format!("{}/api/pull", base_url))
+        .json(&serde_json::json!({ "model": model, "stream": true }))
+        .send()
+        .await
+        .map_err(|e| ollama_unreachable(&base_url, e))?;
+    let res = check_status(res).await?;
+
+    let events = state.events.clone();
+    tokio::spawn(stream_pull_progress(res, model.clone(), events));
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(OllamaPullResponse {
+            message: format!("Pulling {}; progress is sent to /events", model),
+            model,
+        }),
+    ))
+}
+
+/// POST /ai/ollama/delete - Remove a model from the Ollama server
+pub async fn delete_ollama_model(
+    Json(payload): Json<OllamaModelRequest>,
+) -> Result<Json<OllamaDeleteResponse>> {
+    let base_url = ollama_base_url(payload.provider_url.as_deref())?;
+    let model = model_name(&payload.model)?;
+    info!("Deleting Ollama model {} on {}", model, base_url);
+
+    let res = reqwest::Client::new()
+        .delete(format!("{}/api/delete", base_url))
+        .json(&serde_json::json!({ "model": model }))
+        .send()
+        .await
+        .map_err(|e| ollama_unreachable(&base_url, e))?;
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::NotFound(format!(
+            "Ollama model {} not found",
+            model
+        )));
+    }
+    check_status(res).await?;
+
+    Ok(Json(OllamaDeleteResponse {
+        success: true,
+        message: format!("Deleted {}", model),
+    }))
+}
+
+// ============================================================
+// Helper Functions
+// ============================================================
+
+/// Ollama's own API root for `provider_url`
+///
+/// Accepts the OpenAI-compatible URL used for reports (ending in `/v1`) as
+/// well as the server root.
+fn ollama_base_url(provider_url: Option<&str>) -> Result<String> {
+    let url = provider_url
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .unwrap_or(DEFAULT_OLLAMA_URL);
+    validate_provider_url(url)
+        .map_err(|e| AppError::InvalidRequest(format!("Invalid provider URL: {}", e)))?;
+
+    let url = url.trim_end_matches('/');
+    Ok(url.strip_suffix("/v1").unwrap_or(url).to_string())
+}
+
+fn model_name(model: &str) -> Result<String> {
+    let model = model.trim();
+    if model.is_empty() {
+        return Err(AppError::InvalidRequest(
+            "model must not be empty".to_string(),
+        ));
+    }
+    Ok(model.to_string())
+}
+
+fn ollama_unreachable(base_url: &str, e: reqwest::Error) -> AppError {
+    error!("Failed to contact Ollama at {}: {}", base_url, e);
+    AppError::Internal(format!(
+        "Failed to contact Ollama at {}. Ensure Ollama is running.",
+        base_url
+    ))
+}
+
+/// Turn an Ollama error response into an error with its message
+async fn check_status(res: reqwest::Response) -> Result<reqwest::Response> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    let body = res.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| v["error"].as_str().map(str::to_string))
+        .unwrap_or(body);
+    error!("Ollama returned HTTP {}: {}", status, message);
+    Err(AppError::Internal(format!(
+        "Ollama returned HTTP {}: {}",
+        status, message
+    )))
+}
+
+/// Publish the progress lines of a `/api/pull` response until it ends
+async fn stream_pull_progress(
+    mut res: reqwest::Response,
+    model: String,
+    events: broadcast::Sender<ServerEvent>,
+) {
+    let publish = |progress: OllamaPullProgress| {
+        // No receivers is not an error: the download continues unobserved
+        let _ = events.send(ServerEvent::OllamaPull { progress });
+    };
+
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut last_status = String::new();
+    let mut last_sent: Option<Instant> = None;
+    let mut failure = None;
+
+    loop {
+        let chunk = match res.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                failure = Some(format!("Download interrupted: {}", e));
+                break;
+            }
+        };
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let Ok(line) = serde_json::from_slice::<OllamaPullLine>(&line) else {
+                continue;
+            };
+            if let Some(error) = line.error {
+                failure = Some(error);
+                break;
+            }
+
+            // Send status changes, and byte counts at most every interval
+            let due = !matches!(last_sent, Some(sent) if sent.elapsed() < PROGRESS_EVENT_INTERVAL);
+            if line.status != last_status || due {
+                last_status = line.status.clone();
+                last_sent = Some(Instant::now());
+                publish(OllamaPullProgress {
+                    model: model.clone(),
+                    status: line.status,
+                    completed: line.completed,
+                    total: line.total,
+                    done: false,
+                    error: None,
+                });
+            }
+        }
+        if failure.is_some() {
+            break;
+        }
+    }
+
+    if failure.is_none() && last_status != "success" {
+        failure = Some("Download ended before Ollama reported success".to_string());
+    }
+    match &failure {
+        Some(e) => error!("Pulling Ollama model {} failed: {}", model, e),
+        None => info!("Pulled Ollama model {}", model),
+    }
+    publish(OllamaPullProgress {
+        model,
+        status: if failure.is_some() {
+            "failed"
+        } else {
+            "success"
+        }
+        .to_string(),
+        completed: None,
+        total: None,
+        done: true,
+        error: failure,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ollama_base_url() {
+        assert_eq!(ollama_base_url(None).unwrap(), "http://localhost:11434");
+        assert_eq!(
+            ollama_base_url(Some("http://localhost:11434/v1/")).unwrap(),
+            "http://localhost:11434"
+        );
+        assert_eq!(
+            ollama_base_url(Some("http://gpu-box:11434")).unwrap(),
+            "http://gpu-box:11434"
+        );
+        assert!(ollama_base_url(Some("ftp://localhost:11434")).is_err());
+    }
+}
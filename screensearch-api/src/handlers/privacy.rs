@@ -0,0 +1,469 @@
+//! Privacy Handlers
+//!
+//! Manage redaction rules: terms or regexes masked in the text of frames as
+//! they are stored, and in frames stored before through a `redaction` job.
+//! Purge every frame matching a query, with a signed report of the deletion.
+
+use crate::error::{AppError, Result};
+use crate::models::{Paginated, PaginationInfo};
+use crate::purge::{self, PurgeReport};
+use crate::redaction::RedactionMatcher;
+use crate::state::AppState;
+use crate::workers::enqueue_redaction;
+use axum::extract::{Json, Path, Query, State};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use screensearch_db::{
+    FrameRecord, NewRedactionRule, Pagination, PurgeFilter, RedactionRuleRecord,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+
+/// Maximum redaction pattern length
+const MAX_PATTERN_LEN: usize = 500;
+/// Frames listed in a purge preview
+const PREVIEW_SAMPLE: usize = 20;
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Create redaction rule request
+#[derive(Debug, Deserialize)]
+pub struct CreateRedactionRequest {
+    /// Term (matched case-insensitively) or regex to mask
+    pub pattern: String,
+
+    /// Treat `pattern` as a regex (default: false)
+    #[serde(default)]
+    pub regex: bool,
+
+    /// Also blur the images of matching frames (default: false)
+    #[serde(default)]
+    pub blur_images: bool,
+
+    /// Redact frames stored before the rule (default: true)
+    #[serde(default = "default_apply_to_history")]
+    pub apply_to_history: bool,
+}
+
+fn default_apply_to_history() -> bool {
+    true
+}
+
+/// A redaction rule, with the job applying it to stored frames if queued
+#[derive(Debug, Serialize)]
+pub struct RedactionRuleResponse {
+    #[serde(flatten)]
+    pub rule: RedactionRuleRecord,
+
+    /// `redaction` job redacting stored frames; follow it at `/jobs/{id}`
+    pub job_id: Option<i64>,
+}
+
+/// Purge request
+///
+/// A frame is purged when it matches every criterion given; at least one
+/// is required.
+#[derive(Debug, Deserialize)]
+pub struct PurgeRequest {
+    /// Text in the frame's OCR text or window title (case-insensitive)
+    #[serde(default)]
+    pub keyword: Option<String>,
+
+    /// Part of the process name (case-insensitive)
+    #[serde(default)]
+    pub app: Option<String>,
+
+    /// Part of the browser URL (case-insensitive)
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Only frames captured at or after this time
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// Only frames captured at or before this time
+    #[serde(default)]
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// Delete the frames; without it only a preview is returned
+    #[serde(default)]
+    pub confirm: bool,
+
+    /// Frame count of the preview; the purge is refused if the query now
+    /// matches a different number of frames
+    #[serde(default)]
+    pub expected_frames: Option<usize>,
+}
+
+/// Frames a purge would delete
+#[derive(Debug, Serialize)]
+pub struct PurgePreview {
+    pub frames: usize,
+    /// Pinned frames matching the query, which the purge keeps
+    pub pinned: usize,
+    /// The most recent matching frames
+    pub sample: Vec<FrameRecord>,
+}
+
+/// A purge and its signed report
+#[derive(Debug, Serialize)]
+pub struct PurgeResponse {
+    pub report_id: i64,
+    pub report: PurgeReport,
+    /// HMAC-SHA256 of the report as stored (hex)
+    pub signature: String,
+}
+
+/// Stored purge report with its verification
+#[derive(Debug, Serialize)]
+pub struct PurgeReportResponse {
+    pub id: i64,
+    pub report: serde_json::Value,
+    pub signature: String,
+    /// Whether the signature matches the report
+    pub valid: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Purge report list query parameters
+#[derive(Debug, Deserialize)]
+pub struct PurgeReportQuery {
+    /// Maximum results to return (default: 50)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Offset for pagination (default: 0)
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// POST /privacy/redactions - Create a redaction rule
+///
+/// The rule masks the text of every frame stored from now on and, unless
+/// `apply_to_history` is false, queues a job masking the frames stored
+/// before.
+///
+/// # Request Body
+/// - pattern: Term or regex to mask
+/// - regex: Treat the pattern as a regex (default: false)
+/// - blur_images: Also blur the images of matching frames (default: false)
+/// - apply_to_history: Redact frames stored before (default: true)
+pub async fn create_redaction_rule(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateRedactionRequest>,
+) -> Result<Json<RedactionRuleResponse>> {
+    debug!(
+        "Create redaction rule request: regex={}, blur_images={}",
+        req.regex, req.blur_images
+    );
+
+    if req.pattern.trim().is_empty() {
+        return Err(AppError::InvalidRequest(
+            "Pattern cannot be empty".to_string(),
+        ));
+    }
+    if req.pattern.len() > MAX_PATTERN_LEN {
+        return Err(AppError::InvalidRequest(format!(
+            "Pattern too long (max {} characters)",
+            MAX_PATTERN_LEN
+        )));
+    }
+
+    let matcher = RedactionMatcher::compile(0, &req.pattern, req.regex, req.blur_images)
+        .map_err(|e| AppError::InvalidRequest(format!("Invalid regex: {}", e)))?;
+    if matcher.matches_empty() {
+        return Err(AppError::InvalidRequest(
+            "Pattern matches empty text".to_string(),
+        ));
+    }
+
+    let id = state
+        .db
+        .insert_redaction_rule(NewRedactionRule {
+            pattern: req.pattern,
+            is_regex: req.regex,
+            blur_images: req.blur_images,
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to create redaction rule: {}", e);
+            AppError::Database(e)
+        })?;
+    state.redactions.invalidate();
+
+    let job_id = if req.apply_to_history {
+        Some(enqueue_redaction(&state.db, id).await?)
+    } else {
+        None
+    };
+    // The pattern itself is the secret, so it stays out of the logs
+    info!("Created redaction rule {} (job {:?})", id, job_id);
+
+    let rule = get_rule(&state, id).await?;
+    Ok(Json(RedactionRuleResponse { rule, job_id }))
+}
+
+/// GET /privacy/redactions - List redaction rules
+///
+/// Returns one page of rules, oldest first, with the total number of rules.
+///
+/// # Query Parameters
+/// - limit: Maximum rules to return (default: 100)
+/// - offset: Number of rules to skip (default: 0)
+pub async fn list_redaction_rules(
+    State(state): State<Arc<AppState>>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Paginated<RedactionRuleRecord>>> {
+    debug!("List redaction rules request");
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let rules = match state.db.list_redaction_rules_page(pagination).await {
+        Ok(rules) => state
+            .db
+            .count_redaction_rules()
+            .await
+            .map(|total| (rules, total)),
+        Err(e) => Err(e),
+    };
+    match rules {
+        Ok((rules, total)) => Ok(Json(Paginated {
+            pagination: PaginationInfo::counted(limit, offset, rules.len(), total),
+            data: rules,
+        })),
+        Err(e) => {
+            error!("Failed to list redaction rules: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// DELETE /privacy/redactions/:id - Delete a redaction rule
+///
+/// Text the rule masked stays masked.
+///
+/// # Path Parameters
+/// - id: Rule ID
+pub async fn delete_redaction_rule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    debug!("Delete redaction rule request: id={}", id);
+
+    match state.db.delete_redaction_rule(id).await {
+        Ok(0) => Err(AppError::NotFound(format!(
+            "Redaction rule {} not found",
+            id
+        ))),
+        Ok(_) => {
+            state.redactions.invalidate();
+            Ok(Json(serde_json::json!({ "success": true })))
+        }
+        Err(e) => {
+            error!("Failed to delete redaction rule {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// POST /privacy/redactions/:id/apply - Redact stored frames with a rule
+///
+/// Queues a `redaction` job, for instance for frames synced from another
+/// machine after the rule was created.
+///
+/// # Path Parameters
+/// - id: Rule ID
+pub async fn apply_redaction_rule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<RedactionRuleResponse>> {
+    debug!("Apply redaction rule request: id={}", id);
+
+    let rule = get_rule(&state, id).await?;
+    let job_id = enqueue_redaction(&state.db, id).await?;
+    info!("Queued redaction rule {} (job {})", id, job_id);
+
+    Ok(Json(RedactionRuleResponse {
+        rule,
+        job_id: Some(job_id),
+    }))
+}
+
+/// POST /privacy/purge - Preview or delete every frame matching a query
+///
+/// Without `confirm`, returns the number of matching frames and the most
+/// recent of them. With `confirm`, deletes the frames with their OCR text,
+/// embeddings, tags and image files, and returns a signed report of the
+/// deletion. The database rows go in one transaction; files are deleted
+/// after it, and any that fail are listed in the report. Pinned frames are
+/// never purged; the preview counts those the query matches.
+///
+/// # Request Body
+/// - keyword: Text in the OCR text or window title
+/// - app: Part of the process name
+/// - url: Part of the browser URL
+/// - start_time / end_time: Capture time range
+/// - confirm: Delete instead of previewing (default: false)
+/// - expected_frames: Frame count the preview showed
+pub async fn purge_frames(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<PurgeRequest>,
+) -> Result<Response> {
+    let non_empty = |text: Option<String>| {
+        text.map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+    let filter = PurgeFilter {
+        keyword: non_empty(req.keyword),
+        app: non_empty(req.app),
+        url: non_empty(req.url),
+        start_time: req.start_time,
+        end_time: req.end_time,
+    };
+    debug!("Purge request: confirm={}", req.confirm);
+
+    if filter.is_empty() {
+        return Err(AppError::InvalidRequest(
+            "Give a keyword, app, url or time range to purge".to_string(),
+        ));
+    }
+    if let (Some(start), Some(end)) = (filter.start_time, filter.end_time) {
+        if start > end {
+            return Err(AppError::InvalidRequest(
+                "start_time must be before end_time".to_string(),
+            ));
+        }
+    }
+
+    let frame_ids = state.db.find_purge_frames(&filter).await?;
+    if !req.confirm {
+        let pinned = state.db.find_pinned_purge_frames(&filter).await?.len();
+        let mut sample = Vec::new();
+        for &id in frame_ids.iter().rev().take(PREVIEW_SAMPLE) {
+            if let Some(frame) = state.db.get_frame(id).await? {
+                sample.push(frame);
+            }
+        }
+        return Ok(Json(PurgePreview {
+            frames: frame_ids.len(),
+            pinned,
+            sample,
+        })
+        .into_response());
+    }
+
+    if let Some(expected) = req.expected_frames {
+        if expected != frame_ids.len() {
+            return Err(AppError::InvalidRequest(format!(
+                "The query now matches {} frames, not {}; preview it again",
+                frame_ids.len(),
+                expected
+            )));
+        }
+    }
+
+    let outcome = state.db.purge_frames(&frame_ids).await.map_err(|e| {
+        error!("Failed to purge frames: {}", e);
+        AppError::Database(e)
+    })?;
+
+    let storage = state.capture_storage();
+    let mut files_failed = Vec::new();
+    for path in &outcome.files {
+        if let Err(e) = storage.delete(path).await {
+            warn!("Failed to delete purged file {}: {:#}", path, e);
+            files_failed.push(path.clone());
+        }
+    }
+
+    let key = state.db.purge_signing_key().await?;
+    let report = PurgeReport::new(
+        state.db.machine_id().await?,
+        &key,
+        &filter,
+        &outcome,
+        files_failed,
+    );
+    let report_json = serde_json::to_string(&report)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize report: {}", e)))?;
+    let signature = purge::sign(&key, &report_json);
+    let report_id = state
+        .db
+        .insert_purge_report(&report_json, &signature)
+        .await?;
+    // The query itself is what was forgotten, so it stays out of the logs
+    info!(
+        "Purged {} frames ({} files, {} failed, report {})",
+        report.frames_deleted,
+        report.files_deleted,
+        report.files_failed.len(),
+        report_id
+    );
+
+    Ok(Json(PurgeResponse {
+        report_id,
+        report,
+        signature,
+    })
+    .into_response())
+}
+
+/// GET /privacy/purges - List purge reports, newest first
+///
+/// Each report is verified against its signature. Returns one page of
+/// reports with the total number of reports.
+///
+/// # Query Parameters
+/// - limit: Maximum results (default: 50, max: 1000)
+/// - offset: Pagination offset
+pub async fn list_purge_reports(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PurgeReportQuery>,
+) -> Result<Json<Paginated<PurgeReportResponse>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(50).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "List purge reports request: limit={}, offset={}",
+        pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let key = state.db.purge_signing_key().await?;
+    let reports = state.db.list_purge_reports(pagination).await?;
+    let total = state.db.count_purge_reports().await?;
+    Ok(Json(Paginated {
+        pagination: PaginationInfo::counted(limit, offset, reports.len(), total),
+        data: reports
+            .into_iter()
+            .map(|record| PurgeReportResponse {
+                id: record.id,
+                valid: purge::verify(&key, &record.report, &record.signature),
+                report: serde_json::from_str(&record.report).unwrap_or(serde_json::Value::Null),
+                signature: record.signature,
+                created_at: record.created_at,
+            })
+            .collect(),
+    }))
+}
+
+async fn get_rule(state: &AppState, id: i64) -> Result<RedactionRuleRecord> {
+    match state.db.get_redaction_rule(id).await {
+        Ok(Some(rule)) => Ok(rule),
+        Ok(None) => Err(AppError::NotFound(format!(
+            "Redaction rule {} not found",
+            id
+        ))),
+        Err(e) => {
+            error!("Failed to get redaction rule {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
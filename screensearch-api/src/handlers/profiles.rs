@@ -0,0 +1,77 @@
+//! Profile Handlers
+//!
+//! List the capture profiles of [`crate::profiles`] and switch between them.
+
+use crate::error::{AppError, Result};
+use crate::models::{ProfilesResponse, SwitchProfileRequest};
+use crate::profiles::DEFAULT_PROFILE;
+use crate::state::AppState;
+use axum::extract::{Json, State};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// GET /profiles - Capture profiles
+///
+/// Returns the active profile and every configured profile with the
+/// directory holding its database and captures.
+pub async fn list_profiles(State(state): State<Arc<AppState>>) -> Json<ProfilesResponse> {
+    debug!("List profiles request");
+
+    Json(match state.profiles.profiles() {
+        Some(profiles) => ProfilesResponse {
+            active: profiles.active.clone(),
+            profiles: profiles.available.clone(),
+            switching_to: None,
+        },
+        None => ProfilesResponse {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: Vec::new(),
+            switching_to: None,
+        },
+    })
+}
+
+/// POST /profiles/active - Switch to another profile
+///
+/// The choice is remembered for the next start. The application stops like
+/// Quit does, finishing the frames already captured, and starts again with
+/// the profile's database and settings.
+///
+/// # Request Body
+/// - name: Profile to switch to
+pub async fn switch_profile(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SwitchProfileRequest>,
+) -> Result<Json<ProfilesResponse>> {
+    debug!("Switch profile request: {}", req.name);
+
+    let Some(profiles) = state.profiles.profiles() else {
+        return Err(AppError::InvalidRequest(
+            "Profiles can only be switched when running the full application".to_string(),
+        ));
+    };
+    if !profiles
+        .available
+        .iter()
+        .any(|profile| profile.name == req.name)
+    {
+        return Err(AppError::NotFound(format!(
+            "Profile '{}' not found",
+            req.name
+        )));
+    }
+    if profiles.active == req.name {
+        return Err(AppError::InvalidRequest(format!(
+            "Profile '{}' is already active",
+            req.name
+        )));
+    }
+
+    info!("Switching to profile '{}'", req.name);
+    state.profiles.request_switch(req.name.clone());
+    Ok(Json(ProfilesResponse {
+        active: profiles.active.clone(),
+        profiles: profiles.available.clone(),
+        switching_to: Some(req.name),
+    }))
+}
@@ -1,24 +1,106 @@
 //! RAG helper functions for enhanced report generation
 
+use crate::citations::ReportSource;
 use crate::error::{AppError, Result};
 use crate::state::AppState;
 use chrono::{DateTime, Utc};
-use screensearch_db::{FrameFilter, Pagination};
+use screensearch_db::{FrameFilter, Pagination, SemanticResult};
+use screensearch_embeddings::estimate_tokens;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{info, warn, error};
 
 /// Weight for semantic results in hybrid search (0.0 to 1.0)
 const SEMANTIC_WEIGHT: f32 = 0.3;
 /// Maximum number of results to fetch for RAG context
-const MAX_RAG_RESULTS: i64 = 50;
+const MAX_RAG_RESULTS: i64 = 100;
+/// Parts of the requested period that each contribute at least one chunk
+/// (or a share of the frames without embeddings) when they have any
+const COVERAGE_SLOTS: usize = 10;
+/// Frames sampled for the context without embeddings
+const MAX_FALLBACK_FRAMES: i64 = 100;
+/// Hourly summaries read for the context (a little over a month)
+const MAX_SUMMARY_HOURS: i64 = 800;
+/// Hybrid score below which a chunk does not count as relevant to a
+/// question (a cosine similarity of about 0.33 without keyword matches)
+const MIN_ANSWER_SCORE: f32 = 0.1;
+/// Question words searched for without embeddings
+const MAX_QUESTION_KEYWORDS: usize = 6;
+/// Matches read per question word without embeddings
+const KEYWORD_RESULTS: i64 = 20;
+/// Words too common to search for
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "was", "were", "that", "this", "what", "when", "where", "which", "who",
+    "why", "how", "did", "does", "with", "from", "about", "have", "had", "has", "are", "you",
+    "your", "can", "could", "would", "should", "there", "their", "then", "than", "into", "saw",
+    "see", "seen", "find", "show", "tell", "again", "some", "any",
+];
+/// Tokens reserved per context entry for its source label and list marker
+const ENTRY_OVERHEAD_TOKENS: usize = 6;
+/// Smallest remaining budget worth filling with the start of an entry
+const MIN_PARTIAL_TOKENS: usize = 32;
+
+/// RAG settings from `[embeddings]` and `[summaries]` in `config.toml`
+#[derive(Debug, Clone)]
+pub struct RagConfig {
+    /// Hours after which the recency boost of a chunk halves, measured from
+    /// the newest chunk found (0 = no preference for recent chunks)
+    pub recency_half_life_hours: f32,
+    /// Tokens of screen content sent with a report prompt
+    pub context_tokens: usize,
+    /// `context_tokens` for specific models, by model name
+    pub model_context_tokens: HashMap<String, usize>,
+    /// Maximum number of chunks sent, however small
+    pub max_context_chunks: usize,
+    /// Reports over periods of at least this many hours are built from
+    /// hourly activity summaries when they cover the period (0 = never)
+    pub summary_min_hours: f32,
+}
+
+impl RagConfig {
+    /// Context token budget for `model`
+    pub fn context_tokens_for(&self, model: &str) -> usize {
+        self.model_context_tokens
+            .get(model)
+            .copied()
+            .unwrap_or(self.context_tokens)
+    }
+}
+
+impl Default for RagConfig {
+    fn default() -> Self {
+        Self {
+            recency_half_life_hours: 24.0,
+            context_tokens: 2000,
+            model_context_tokens: HashMap::new(),
+            max_context_chunks: 50,
+            summary_min_hours: 12.0,
+        }
+    }
+}
+
+/// Context built for a report prompt
+pub struct RagContext {
+    /// Text added to the prompt
+    pub text: String,
+    /// How the context was retrieved, e.g. "Semantic Search"
+    pub source: String,
+    /// Labelled entries of the text, in order
+    pub sources: Vec<ReportSource>,
+}
 
 /// Build context for LLM using RAG-enhanced retrieval
+///
+/// The screen content is packed into the context token budget configured
+/// for `model`.
+#[tracing::instrument(skip(state, user_query))]
 pub async fn build_rag_context(
     state: &Arc<AppState>,
     user_query: &str,
+    model: &str,
     start_time: DateTime<Utc>,
     end_time: DateTime<Utc>,
-) -> Result<(String, String)> {
+) -> Result<RagContext> {
     // Check if embeddings are enabled
     let embedding_status = state.db.get_embedding_status().await.ok();
     let use_rag = embedding_status
@@ -26,20 +108,128 @@ pub async fn build_rag_context(
         .map(|s| s.enabled && s.frames_with_embeddings > 0)
         .unwrap_or(false);
 
+    let config = state.rag.get().cloned().unwrap_or_default();
+    let period_hours = (end_time - start_time).num_minutes() as f32 / 60.0;
+    if config.summary_min_hours > 0.0 && period_hours >= config.summary_min_hours {
+        if let Some(context) =
+            build_summary_context(state, &config, model, start_time, end_time).await?
+        {
+            return Ok(context);
+        }
+    }
+
     if use_rag {
-        build_rag_enhanced_context(state, user_query, start_time, end_time).await
+        build_rag_enhanced_context(state, &config, user_query, model, start_time, end_time).await
     } else {
-        build_traditional_context(state, start_time, end_time).await
+        build_traditional_context(state, &config, model, start_time, end_time).await
     }
 }
 
+/// Build context from the hourly activity summaries of the period
+///
+/// Returns `None` unless summaries exist for at least half of the hours with
+/// captured frames. When they do not all fit the budget, they are taken
+/// coarse to fine, so that the ones sent spread over the whole period.
+async fn build_summary_context(
+    state: &Arc<AppState>,
+    rag_config: &RagConfig,
+    model: &str,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> Result<Option<RagContext>> {
+    let pagination = Pagination {
+        limit: MAX_SUMMARY_HOURS,
+        offset: 0,
+    };
+    let summaries = state
+        .db
+        .list_activity_summaries(Some(start_time), Some(end_time), pagination)
+        .await?;
+    let active_hours = state.db.count_active_hours(start_time, end_time).await?;
+    if summaries.is_empty() || (summaries.len() as i64) * 2 < active_hours {
+        info!(
+            "{} hourly summaries for {} active hours, not using them",
+            summaries.len(),
+            active_hours
+        );
+        return Ok(None);
+    }
+    info!(
+        "Using {} hourly summaries for report context",
+        summaries.len()
+    );
+
+    let mut context = format!(
+        "Activity Period: {} to {}\n\nHourly Activity Summaries:\n",
+        start_time.format("%Y-%m-%d %H:%M"),
+        end_time.format("%Y-%m-%d %H:%M")
+    );
+
+    let mut order: Vec<usize> = (0..summaries.len()).collect();
+    order.sort_by_key(|&i| coarse_to_fine_rank(i));
+    order.truncate(rag_config.max_context_chunks);
+    let entries: Vec<String> = order
+        .iter()
+        .map(|&i| {
+            let summary = &summaries[i];
+            format!(
+                "[{}-{}] {}",
+                summary.period_start.format("%Y-%m-%d %H:%M"),
+                summary.period_end.format("%H:%M"),
+                summary
+                    .summary
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+        })
+        .collect();
+
+    let engine = state.embedding_engine.read().await.clone();
+    let count_tokens = |text: &str| match &engine {
+        Some(engine) => engine.count_tokens(text),
+        None => estimate_tokens(text),
+    };
+    let budget = rag_config
+        .context_tokens_for(model)
+        .saturating_sub(count_tokens(&context));
+    let mut packed: Vec<(usize, String)> = pack_to_budget(&entries, budget, count_tokens)
+        .into_iter()
+        .map(|(index, entry)| (order[index], entry))
+        .collect();
+    // Back in time order
+    packed.sort_by_key(|(index, _)| *index);
+
+    let mut sources = Vec::new();
+    for (i, (index, entry)) in packed.into_iter().enumerate() {
+        let source = ReportSource::from_summary(i, &summaries[index]);
+        context.push_str(&format!("- [{}] {}\n", source.label, entry));
+        sources.push(source);
+    }
+
+    Ok(Some(RagContext {
+        text: context,
+        source: "Hourly Summaries".to_string(),
+        sources,
+    }))
+}
+
+/// Position of entry `i` in coarse-to-fine order: the first entry, then
+/// every 64th, 32nd, ... entry, and finally the odd ones
+fn coarse_to_fine_rank(i: usize) -> (std::cmp::Reverse<u32>, usize) {
+    let level = if i == 0 { u32::MAX } else { i.trailing_zeros() };
+    (std::cmp::Reverse(level), i)
+}
+
 /// Build context using RAG with hybrid search
 async fn build_rag_enhanced_context(
     state: &Arc<AppState>,
+    rag_config: &RagConfig,
     user_query: &str,
+    model: &str,
     start_time: DateTime<Utc>,
     end_time: DateTime<Utc>,
-) -> Result<(String, String)> {
+) -> Result<RagContext> {
     info!("Using RAG-enhanced report generation");
 
     // Generate embedding for the query
@@ -83,7 +273,7 @@ async fn build_rag_enhanced_context(
         // Fallback to traditional context if RAG yields nothing? 
         // Or just let it be empty? 
         // Better to provide at least simple logs.
-        return build_traditional_context(state, start_time, end_time).await;
+        return build_traditional_context(state, rag_config, model, start_time, end_time).await;
     }
 
     // Apply keyword boosting for query terms
@@ -91,54 +281,89 @@ async fn build_rag_enhanced_context(
 
     // Rerank results for better relevance
     let config = super::reranker::RerankConfig {
-        top_k: 20,
+        top_k: MAX_RAG_RESULTS as usize,
         recency_weight: 0.1,
+        recency_half_life_hours: rag_config.recency_half_life_hours,
         length_weight: 0.05,
         min_score: 0.0,
     };
     let reranked_results = super::reranker::rerank_results(relevant_results, &config);
 
+    // Put chunks from across the period first rather than only its busiest hour
+    let reranked_results = super::reranker::spread_over_period(
+        reranked_results,
+        start_time,
+        end_time,
+        rag_config.max_context_chunks,
+        COVERAGE_SLOTS,
+    );
+
     // Build rich context from relevant OCR text chunks
-    let mut context = String::new();
-    context.push_str(&format!(
-        "Activity Period: {} to {}\n\n",
+    let mut context = format!(
+        "Activity Period: {} to {}\n\nRelevant Screen Content (OCR):\n",
         start_time.format("%Y-%m-%d %H:%M"),
         end_time.format("%Y-%m-%d %H:%M")
-    ));
+    );
 
-    let mut ocr_chunks = Vec::new();
-
-    for result in reranked_results.iter() {
-        let app = result
-            .frame
-            .active_process
-            .clone()
-            .unwrap_or_else(|| "Unknown".to_string());
-        let window = result.frame.active_window.clone().unwrap_or_default();
+    let ocr_chunks: Vec<String> = reranked_results
+        .iter()
+        .map(|result| {
+            let app = result
+                .frame
+                .active_process
+                .clone()
+                .unwrap_or_else(|| "Unknown".to_string());
+            let window = result.frame.active_window.clone().unwrap_or_default();
+            let text = result
+                .chunk_text
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!(
+                "[{}] {} - {}: {}",
+                result.frame.timestamp.format("%H:%M"),
+                app,
+                window,
+                text
+            )
+        })
+        .collect();
 
-        ocr_chunks.push(format!(
-            "[{}] {} - {}: {}",
-            result.frame.timestamp.format("%H:%M"),
-            app,
-            window,
-            result.chunk_text.chars().take(200).collect::<String>()
-        ));
-    }
+    let count_tokens = |text: &str| engine.count_tokens(text);
+    let budget = rag_config
+        .context_tokens_for(model)
+        .saturating_sub(count_tokens(&context));
+    let packed = pack_to_budget(&ocr_chunks, budget, count_tokens);
+    info!(
+        "Packed {} of {} chunks into a {} token context",
+        packed.len(),
+        ocr_chunks.len(),
+        budget
+    );
 
-    context.push_str("Relevant Screen Content (OCR):\n");
-    for chunk in ocr_chunks.iter().take(20) {
-        context.push_str(&format!("- {}\n", chunk));
+    let mut sources = Vec::new();
+    for (i, (index, chunk)) in packed.into_iter().enumerate() {
+        let result = &reranked_results[index];
+        let source = ReportSource::new(i, &result.frame, Some(result.chunk_index));
+        context.push_str(&format!("- [{}] {}\n", source.label, chunk));
+        sources.push(source);
     }
 
-    Ok((context, "Semantic Search".to_string()))
+    Ok(RagContext {
+        text: context,
+        source: "Semantic Search".to_string(),
+        sources,
+    })
 }
 
 /// Build context using traditional frame-based approach
 async fn build_traditional_context(
     state: &Arc<AppState>,
+    rag_config: &RagConfig,
+    model: &str,
     start_time: DateTime<Utc>,
     end_time: DateTime<Utc>,
-) -> Result<(String, String)> {
+) -> Result<RagContext> {
     warn!("Embeddings not available, using traditional report generation");
 
     let filter = FrameFilter {
@@ -148,24 +373,40 @@ async fn build_traditional_context(
         device_name: None,
         tag_ids: None,
         monitor_index: None,
+        exclude_apps: None,
+        exclude_tags: None,
     };
 
-    let pagination = Pagination {
-        limit: 100,
-        offset: 0,
-    };
-
-    let frames = state
-        .db
-        .get_frames_in_range(start_time, end_time, filter, pagination)
-        .await
-        .map_err(AppError::Database)?;
+    // Sample the latest frames of each part of the period, so that long
+    // periods are not represented by their last minutes only
+    let slot_length = (end_time - start_time) / COVERAGE_SLOTS as i32;
+    let per_slot = MAX_FALLBACK_FRAMES / COVERAGE_SLOTS as i64;
+    let mut frames = Vec::new();
+    for slot in (0..COVERAGE_SLOTS as i32).rev() {
+        let slot_start = start_time + slot_length * slot;
+        let slot_end = if slot == COVERAGE_SLOTS as i32 - 1 {
+            end_time
+        } else {
+            slot_start + slot_length
+        };
+        let pagination = Pagination {
+            limit: per_slot,
+            offset: 0,
+        };
+        let slot_frames = state
+            .db
+            .get_frames_in_range(slot_start, slot_end, filter.clone(), pagination)
+            .await
+            .map_err(AppError::Database)?;
+        frames.extend(slot_frames);
+    }
+    // A frame on the boundary of two parts is returned for both
+    frames.dedup_by_key(|frame| frame.id);
 
     // Summarize data for the prompt
     let total_frames = frames.len();
-    let mut app_counts: std::collections::HashMap<String, usize> =
-        std::collections::HashMap::new();
-    let mut timeline_text = String::new();
+    let mut app_counts: HashMap<String, usize> = HashMap::new();
+    let mut timeline = Vec::new();
 
     for frame in &frames {
         let app = frame
@@ -174,9 +415,10 @@ async fn build_traditional_context(
             .unwrap_or_else(|| "Unknown".to_string());
         *app_counts.entry(app.clone()).or_insert(0) += 1;
 
+        // Only window titles are sent, no OCR text
         let window = frame.active_window.clone().unwrap_or_default();
-        timeline_text.push_str(&format!(
-            "- [{}] App: {}, Window: {}\n",
+        timeline.push(format!(
+            "[{}] App: {}, Window: {}",
             frame.timestamp.format("%H:%M"),
             app,
             window
@@ -189,20 +431,336 @@ async fn build_traditional_context(
         .collect::<Vec<_>>()
         .join(", ");
 
-    Ok((
-        format!(
-            "Activity Period: {} to {}\n\n\
-            Summary Data:\n\
-            - Total Snapshots: {}\n\
-            - App Usage Distribution: {}\n\n\
-            Detailed Log (Sample):\n\
-            {}",
-            start_time.format("%Y-%m-%d %H:%M"),
-            end_time.format("%Y-%m-%d %H:%M"),
-            total_frames,
-            most_used_apps,
-            timeline_text
-        ),
-        "Recent Activity (Fallback)".to_string(),
-    ))
+    let mut context = format!(
+        "Activity Period: {} to {}\n\n\
+        Summary Data:\n\
+        - Total Snapshots: {}\n\
+        - App Usage Distribution: {}\n\n\
+        Detailed Log (Sample):\n",
+        start_time.format("%Y-%m-%d %H:%M"),
+        end_time.format("%Y-%m-%d %H:%M"),
+        total_frames,
+        most_used_apps,
+    );
+
+    // Count with the embeddings tokenizer if it is loaded, but do not load
+    // the model just for that
+    let engine = state.embedding_engine.read().await.clone();
+    let count_tokens = |text: &str| match &engine {
+        Some(engine) => engine.count_tokens(text),
+        None => estimate_tokens(text),
+    };
+    let budget = rag_config
+        .context_tokens_for(model)
+        .saturating_sub(count_tokens(&context));
+    let packed = pack_to_budget(&timeline, budget, count_tokens);
+
+    let mut sources = Vec::new();
+    for (i, (index, line)) in packed.into_iter().enumerate() {
+        let source = ReportSource::new(i, &frames[index], None);
+        context.push_str(&format!("- [{}] {}\n", source.label, line));
+        sources.push(source);
+    }
+
+    Ok(RagContext {
+        text: context,
+        source: "Recent Activity (Fallback)".to_string(),
+        sources,
+    })
+}
+
+/// Build context for answering `question` from the most relevant screen
+/// content between `start_time` and `end_time`
+///
+/// Uses hybrid search when embeddings are available and a search for the
+/// words of the question otherwise. With `rerank`, the chunks are reordered
+/// by keyword matches and recency before the best `max_sources` are kept.
+/// The context has no sources when nothing relevant was found.
+pub async fn build_answer_context(
+    state: &Arc<AppState>,
+    question: &str,
+    model: &str,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    rerank: bool,
+    max_sources: usize,
+) -> Result<RagContext> {
+    let embedding_status = state.db.get_embedding_status().await.ok();
+    let use_embeddings = embedding_status
+        .as_ref()
+        .map(|s| s.enabled && s.frames_with_embeddings > 0)
+        .unwrap_or(false);
+
+    let (mut results, source) = if use_embeddings {
+        let engine = state
+            .get_embedding_engine()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to load embedding engine: {}", e)))?;
+        let query_embedding = engine.embed(question).map_err(|e| {
+            AppError::Internal(format!("Failed to generate query embedding: {}", e))
+        })?;
+        let results = state
+            .db
+            .hybrid_search(
+                question,
+                query_embedding,
+                SEMANTIC_WEIGHT,
+                MAX_RAG_RESULTS,
+                start_time,
+                end_time,
+            )
+            .await?;
+        let results: Vec<SemanticResult> = results
+            .into_iter()
+            .filter(|r| r.similarity_score >= MIN_ANSWER_SCORE)
+            .collect();
+        (results, "Semantic Search")
+    } else {
+        let results = keyword_search(state, question, start_time, end_time).await?;
+        (results, "Keyword Search")
+    };
+    info!("Found {} relevant chunks for question", results.len());
+
+    if rerank {
+        super::reranker::boost_keyword_matches(&mut results, question, 0.2);
+        let config = super::reranker::RerankConfig {
+            top_k: max_sources,
+            recency_weight: 0.1,
+            recency_half_life_hours: state
+                .rag
+                .get()
+                .map(|c| c.recency_half_life_hours)
+                .unwrap_or(24.0),
+            length_weight: 0.05,
+            min_score: 0.0,
+        };
+        results = super::reranker::rerank_results(results, &config);
+    }
+    results.truncate(max_sources);
+
+    let mut context = String::from("Screen Content (OCR):\n");
+    let chunks: Vec<String> = results
+        .iter()
+        .map(|result| {
+            format!(
+                "[{}] {} - {}: {}",
+                result.frame.timestamp.format("%Y-%m-%d %H:%M"),
+                result.frame.active_process.as_deref().unwrap_or("Unknown"),
+                result.frame.active_window.as_deref().unwrap_or_default(),
+                result
+                    .chunk_text
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+        })
+        .collect();
+
+    let rag_config = state.rag.get().cloned().unwrap_or_default();
+    let engine = state.embedding_engine.read().await.clone();
+    let count_tokens = |text: &str| match &engine {
+        Some(engine) => engine.count_tokens(text),
+        None => estimate_tokens(text),
+    };
+    let budget = rag_config
+        .context_tokens_for(model)
+        .saturating_sub(count_tokens(&context));
+    let packed = pack_to_budget(&chunks, budget, count_tokens);
+
+    let mut sources = Vec::new();
+    for (i, (index, chunk)) in packed.into_iter().enumerate() {
+        let result = &results[index];
+        let source = ReportSource::new(i, &result.frame, Some(result.chunk_index));
+        context.push_str(&format!("- [{}] {}\n", source.label, chunk));
+        sources.push(source);
+    }
+
+    Ok(RagContext {
+        text: context,
+        source: source.to_string(),
+        sources,
+    })
+}
+
+/// Screen text containing the words of `question`, best matches first
+///
+/// Each OCR region found scores the share of the question words it
+/// contains.
+async fn keyword_search(
+    state: &Arc<AppState>,
+    question: &str,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> Result<Vec<SemanticResult>> {
+    let keywords = question_keywords(question);
+    let mut found: HashMap<i64, SemanticResult> = HashMap::new();
+
+    for keyword in &keywords {
+        let filter = FrameFilter {
+            start_time: Some(start_time),
+            end_time: Some(end_time),
+            ..Default::default()
+        };
+        let pagination = Pagination {
+            limit: KEYWORD_RESULTS,
+            offset: 0,
+        };
+        let matches = state
+            .db
+            .search_ocr_text(keyword, filter, pagination)
+            .await?;
+        for result in matches {
+            for (index, ocr) in result.ocr_matches.into_iter().enumerate() {
+                found.entry(ocr.id).or_insert_with(|| SemanticResult {
+                    frame: result.frame.clone(),
+                    chunk_text: ocr.text,
+                    chunk_index: index as i32,
+                    similarity_score: 0.0,
+                });
+            }
+        }
+    }
+
+    let mut results: Vec<SemanticResult> = found.into_values().collect();
+    for result in &mut results {
+        let text = result.chunk_text.to_lowercase();
+        let matches = keywords
+            .iter()
+            .filter(|k| text.contains(k.as_str()))
+            .count();
+        result.similarity_score = matches as f32 / keywords.len().max(1) as f32;
+    }
+    results.sort_by(|a, b| {
+        b.similarity_score
+            .total_cmp(&a.similarity_score)
+            .then(b.frame.timestamp.cmp(&a.frame.timestamp))
+    });
+
+    Ok(results)
+}
+
+/// Distinct lowercase words of `question` worth searching for
+fn question_keywords(question: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for word in question.split(|c: char| !c.is_alphanumeric()) {
+        let word = word.to_lowercase();
+        if word.chars().count() >= 3
+            && !STOP_WORDS.contains(&word.as_str())
+            && !keywords.contains(&word)
+        {
+            keywords.push(word);
+        }
+    }
+    keywords.truncate(MAX_QUESTION_KEYWORDS);
+    keywords
+}
+
+/// Fit context entries into `budget` tokens, keeping the earlier ones
+///
+/// Entries are taken in order while they fit, skipping those that do not.
+/// What is left of the budget then goes to the start of the first skipped
+/// entry, cut at a word boundary, if it is worth it. Returns the index of
+/// each kept entry with its text, in order.
+fn pack_to_budget(
+    entries: &[String],
+    budget: usize,
+    count_tokens: impl Fn(&str) -> usize,
+) -> Vec<(usize, String)> {
+    let mut remaining = budget;
+    let mut packed = Vec::new();
+    let mut first_skipped = None;
+
+    for (i, entry) in entries.iter().enumerate() {
+        let tokens = count_tokens(entry) + ENTRY_OVERHEAD_TOKENS;
+        if tokens <= remaining {
+            remaining -= tokens;
+            packed.push((i, entry.clone()));
+        } else if first_skipped.is_none() {
+            first_skipped = Some(i);
+        }
+    }
+
+    if let Some(i) = first_skipped {
+        if remaining >= MIN_PARTIAL_TOKENS + ENTRY_OVERHEAD_TOKENS {
+            let budget = remaining - ENTRY_OVERHEAD_TOKENS;
+            if let Some(partial) = truncate_to_tokens(&entries[i], budget, &count_tokens) {
+                let at = packed.partition_point(|(index, _)| *index < i);
+                packed.insert(at, (i, partial));
+            }
+        }
+    }
+
+    packed
+}
+
+/// Longest start of `text` ending at a word boundary that fits in `budget`
+/// tokens with an ellipsis appended
+fn truncate_to_tokens(
+    text: &str,
+    budget: usize,
+    count_tokens: &impl Fn(&str) -> usize,
+) -> Option<String> {
+    let words: Vec<&str> = text.split(' ').collect();
+    let shortened = |n: usize| format!("{} …", words[..n].join(" "));
+
+    // Binary search for the number of words that fits
+    let (mut low, mut high) = (0, words.len());
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if count_tokens(&shortened(mid)) <= budget {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    (low > 0).then(|| shortened(low))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_words(text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+
+    #[test]
+    fn test_question_keywords() {
+        assert_eq!(
+            question_keywords("What was that Zoom link Dana sent? zoom"),
+            vec!["zoom", "link", "dana", "sent"]
+        );
+        assert!(question_keywords("what was it?").is_empty());
+    }
+
+    #[test]
+    fn test_coarse_to_fine_rank() {
+        let mut order: Vec<usize> = (0..8).collect();
+        order.sort_by_key(|&i| coarse_to_fine_rank(i));
+        assert_eq!(order, vec![0, 4, 2, 6, 1, 3, 5, 7]);
+    }
+
+    #[test]
+    fn test_pack_to_budget_skips_and_truncates() {
+        let long = (0..100)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let entries = vec!["one two three".to_string(), long, "four five".to_string()];
+
+        // 3 + 6 and 2 + 6 tokens fit whole; the long entry gets the rest
+        let packed = pack_to_budget(&entries, 60, count_words);
+        let indices: Vec<usize> = packed.iter().map(|(i, _)| *i).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert!(packed[1].1.starts_with("0 1 2"));
+        assert!(packed[1].1.ends_with(" …"));
+        assert!(count_words(&packed[1].1) <= 60 - 9 - 8 - ENTRY_OVERHEAD_TOKENS);
+
+        // Too little left to be worth starting the long entry
+        let packed = pack_to_budget(&entries, 30, count_words);
+        let indices: Vec<usize> = packed.iter().map(|(i, _)| *i).collect();
+        assert_eq!(indices, vec![0, 2]);
+
+        assert!(pack_to_budget(&entries, 0, count_words).is_empty());
+    }
 }
@@ -0,0 +1,306 @@
+//! Replay Handler
+//!
+//! Streams the stored frames of a time window as MJPEG
+//! (`multipart/x-mixed-replace`), paced by their capture timestamps so a
+//! period can be watched back like a video in a browser `<img>` tag.
+
+use crate::capture_store::CaptureStorage;
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use axum::body::{Body, Bytes};
+use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use futures::stream;
+use screensearch_db::{DatabaseManager, FrameRecord};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+/// Multipart boundary separating frames in the stream
+const BOUNDARY: &str = "frame";
+/// Frames fetched from the database per page
+const PAGE_SIZE: i64 = 50;
+/// Slowest and fastest allowed replay speed multipliers
+const MIN_REPLAY_SPEED: f64 = 0.25;
+const MAX_REPLAY_SPEED: f64 = 64.0;
+/// Longest pause between two frames, so idle periods are skipped
+const MAX_FRAME_DELAY: Duration = Duration::from_secs(2);
+/// JPEG quality used when re-encoding non-JPEG frames
+const JPEG_QUALITY: u8 = 80;
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Replay query parameters
+#[derive(Debug, Deserialize)]
+pub struct ReplayQuery {
+    /// Start of the window (ISO 8601)
+    pub start: DateTime<Utc>,
+
+    /// End of the window (ISO 8601)
+    pub end: DateTime<Utc>,
+
+    /// Playback speed, e.g. `4x` or `0.5` (default: 1x, range: 0.25-64)
+    #[serde(default)]
+    pub speed: Option<String>,
+
+    /// Only replay frames from this monitor
+    #[serde(default)]
+    pub monitor: Option<i32>,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /api/replay - Stream a time window as MJPEG
+///
+/// Frames are sent oldest first. The pause between two frames is their
+/// capture gap divided by `speed`, capped at two seconds.
+///
+/// # Query Parameters
+/// - start: Start of the window (ISO 8601)
+/// - end: End of the window (ISO 8601)
+/// - speed: Optional speed multiplier such as `4x` (default: 1x, range: 0.25-64)
+/// - monitor: Optional monitor index
+pub async fn replay_frames(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReplayQuery>,
+) -> Result<impl IntoResponse> {
+    if query.end <= query.start {
+        return Err(AppError::InvalidRequest(
+            "end must be after start".to_string(),
+        ));
+    }
+
+    let speed = match query.speed.as_deref() {
+        Some(speed) => parse_speed(speed)?,
+        None => 1.0,
+    };
+
+    debug!(
+        "Replay request: start={}, end={}, speed={}, monitor={:?}",
+        query.start, query.end, speed, query.monitor
+    );
+
+    let replay = Replay {
+        db: Arc::clone(&state.db),
+        storage: state.capture_storage(),
+        ffmpeg: state.ffmpeg_path().to_string(),
+        cursor: (query.start, 0),
+        end: query.end,
+        monitor: query.monitor,
+        speed,
+        pending: VecDeque::new(),
+        exhausted: false,
+        last_timestamp: None,
+    };
+
+    // The stream ends when the window is exhausted; axum drops it as soon
+    // as the client disconnects
+    let parts = stream::unfold(replay, |mut replay| async move {
+        let part = replay.next_part().await?;
+        Some((Ok::<Bytes, std::io::Error>(part), replay))
+    });
+
+    Response::builder()
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/x-mixed-replace; boundary={}", BOUNDARY),
+        )
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from_stream(parts))
+        .map_err(|e| AppError::Internal(format!("Failed to build replay response: {}", e)))
+}
+
+// ============================================================
+// Streaming
+// ============================================================
+
+/// Cursor over the frames of a replay window
+struct Replay {
+    db: Arc<DatabaseManager>,
+    storage: Arc<CaptureStorage>,
+    /// ffmpeg executable decoding compacted frames
+    ffmpeg: String,
+    /// Timestamp and ID of the last frame fetched from the database
+    cursor: (DateTime<Utc>, i64),
+    end: DateTime<Utc>,
+    monitor: Option<i32>,
+    speed: f64,
+    pending: VecDeque<FrameRecord>,
+    exhausted: bool,
+    /// Capture time of the last frame sent
+    last_timestamp: Option<DateTime<Utc>>,
+}
+
+impl Replay {
+    /// Wait for the next frame's turn and return it as a multipart part
+    ///
+    /// Frames whose image cannot be read are skipped. Returns `None` at the
+    /// end of the window.
+    async fn next_part(&mut self) -> Option<Bytes> {
+        loop {
+            let frame = self.next_frame().await?;
+
+            if let Some(last) = self.last_timestamp {
+                tokio::time::sleep(frame_delay(last, frame.timestamp, self.speed)).await;
+            }
+            self.last_timestamp = Some(frame.timestamp);
+
+            match read_jpeg(&self.db, &self.storage, &self.ffmpeg, &frame).await {
+                Some(jpeg) => return Some(multipart_part(&frame, &jpeg)),
+                None => continue,
+            }
+        }
+    }
+
+    async fn next_frame(&mut self) -> Option<FrameRecord> {
+        if self.pending.is_empty() && !self.exhausted {
+            let (after_time, after_id) = self.cursor;
+            let page = match self
+                .db
+                .get_frames_after(after_time, after_id, self.end, self.monitor, PAGE_SIZE)
+                .await
+            {
+                Ok(page) => page,
+                Err(e) => {
+                    error!("Failed to fetch replay frames: {}", e);
+                    return None;
+                }
+            };
+
+            self.exhausted = (page.len() as i64) < PAGE_SIZE;
+            if let Some(last) = page.last() {
+                self.cursor = (last.timestamp, last.id);
+            }
+            self.pending.extend(page);
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+/// Read a frame image as JPEG, re-encoding other formats
+async fn read_jpeg(
+    db: &DatabaseManager,
+    storage: &CaptureStorage,
+    ffmpeg: &str,
+    frame: &FrameRecord,
+) -> Option<Vec<u8>> {
+    let path = &frame.file_path;
+    let data = match crate::video_archive::read_frame_image(db, storage, ffmpeg, frame).await {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Skipping replay frame {}: {:#}", path, e);
+            return None;
+        }
+    };
+
+    if crate::video_archive::image_content_type(&data) == "image/jpeg" {
+        return Some(data);
+    }
+
+    let encoded = tokio::task::spawn_blocking(move || -> image::ImageResult<Vec<u8>> {
+        let rgb = image::load_from_memory(&data)?.to_rgb8();
+        let mut jpeg = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY).encode(
+            rgb.as_raw(),
+            rgb.width(),
+            rgb.height(),
+            image::ColorType::Rgb8,
+        )?;
+        Ok(jpeg)
+    })
+    .await;
+
+    match encoded {
+        Ok(Ok(jpeg)) => Some(jpeg),
+        Ok(Err(e)) => {
+            warn!("Skipping replay frame {}: {}", path, e);
+            None
+        }
+        Err(e) => {
+            error!("Replay encoding task failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Encode one frame as a `multipart/x-mixed-replace` part
+fn multipart_part(frame: &FrameRecord, jpeg: &[u8]) -> Bytes {
+    let mut part = format!(
+        "--{}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\nX-Frame-Id: {}\r\nX-Timestamp: {}\r\n\r\n",
+        BOUNDARY,
+        jpeg.len(),
+        frame.id,
+        frame.timestamp.to_rfc3339()
+    )
+    .into_bytes();
+    part.extend_from_slice(jpeg);
+    part.extend_from_slice(b"\r\n");
+    Bytes::from(part)
+}
+
+/// Parse a speed multiplier such as `4x`, `0.5X` or `2`
+fn parse_speed(value: &str) -> Result<f64> {
+    let number = value.trim().trim_end_matches(['x', 'X']);
+    match number.parse::<f64>() {
+        Ok(speed) if speed.is_finite() && speed > 0.0 => {
+            Ok(speed.clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED))
+        }
+        _ => Err(AppError::InvalidRequest(format!(
+            "Invalid speed '{}': expected a positive multiplier such as 4x",
+            value
+        ))),
+    }
+}
+
+/// Pause before showing a frame captured at `next` after one captured at `previous`
+fn frame_delay(previous: DateTime<Utc>, next: DateTime<Utc>, speed: f64) -> Duration {
+    let gap = (next - previous).to_std().unwrap_or(Duration::ZERO);
+    gap.div_f64(speed).min(MAX_FRAME_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_speed() {
+        assert_eq!(parse_speed("4x").unwrap(), 4.0);
+        assert_eq!(parse_speed("0.5X").unwrap(), 0.5);
+        assert_eq!(parse_speed(" 2 ").unwrap(), 2.0);
+        assert_eq!(parse_speed("1000x").unwrap(), MAX_REPLAY_SPEED);
+        assert_eq!(parse_speed("0.01").unwrap(), MIN_REPLAY_SPEED);
+        assert!(parse_speed("0x").is_err());
+        assert!(parse_speed("-2").is_err());
+        assert!(parse_speed("fast").is_err());
+        assert!(parse_speed("NaN").is_err());
+    }
+
+    #[test]
+    fn test_frame_delay() {
+        let start = Utc::now();
+
+        assert_eq!(
+            frame_delay(start, start + chrono::Duration::seconds(4), 4.0),
+            Duration::from_secs(1)
+        );
+        // Idle gaps are capped
+        assert_eq!(
+            frame_delay(start, start + chrono::Duration::hours(1), 4.0),
+            MAX_FRAME_DELAY
+        );
+        // Out-of-order timestamps do not wait
+        assert_eq!(
+            frame_delay(start, start - chrono::Duration::seconds(1), 1.0),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,491 @@
+//! Report Handlers
+//!
+//! Provides endpoints for browsing and downloading stored AI reports,
+//! including those generated by the scheduled report worker, and for
+//! managing the report templates used to generate them.
+
+use crate::error::{AppError, Result};
+use crate::export::{self, FrameThumbnail};
+use crate::models::{Paginated, PaginationInfo};
+use crate::state::AppState;
+use axum::extract::{Json, Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use screensearch_db::{NewReportTemplate, Pagination, ReportRecord, ReportTemplateRecord};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, error, warn};
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Report list query parameters
+#[derive(Debug, Deserialize)]
+pub struct ReportQuery {
+    /// Optional period filter ("daily" or "weekly")
+    #[serde(default)]
+    pub period: Option<String>,
+
+    /// Maximum results to return (default: 50)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Offset for pagination (default: 0)
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Report download query parameters
+#[derive(Debug, Deserialize)]
+pub struct ReportDownloadQuery {
+    /// Output format: "md" (default), "html" or "pdf"
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Maximum number of frame thumbnails embedded in an exported report
+const MAX_EXPORT_THUMBNAILS: usize = 20;
+
+/// Create or update report template request
+#[derive(Debug, Deserialize)]
+pub struct ReportTemplateRequest {
+    /// Unique template name (e.g. "standup")
+    pub name: String,
+
+    /// Optional description shown in the UI
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// System prompt with {start}, {end}, {top_apps}, {frame_count} variables
+    pub system_prompt: String,
+}
+
+/// Maximum template name length
+const MAX_TEMPLATE_NAME_LEN: usize = 50;
+/// Maximum template system prompt length
+const MAX_TEMPLATE_PROMPT_LEN: usize = 20_000;
+
+impl ReportTemplateRequest {
+    /// Validate and convert into a database input
+    fn into_new_template(self) -> Result<NewReportTemplate> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err(AppError::InvalidRequest(
+                "Template name cannot be empty".to_string(),
+            ));
+        }
+        if name.len() > MAX_TEMPLATE_NAME_LEN {
+            return Err(AppError::InvalidRequest(format!(
+                "Template name must be <= {} characters",
+                MAX_TEMPLATE_NAME_LEN
+            )));
+        }
+        if self.system_prompt.trim().is_empty() {
+            return Err(AppError::InvalidRequest(
+                "System prompt cannot be empty".to_string(),
+            ));
+        }
+        if self.system_prompt.len() > MAX_TEMPLATE_PROMPT_LEN {
+            return Err(AppError::InvalidRequest(format!(
+                "System prompt must be <= {} characters",
+                MAX_TEMPLATE_PROMPT_LEN
+            )));
+        }
+
+        Ok(NewReportTemplate {
+            name,
+            description: self.description,
+            system_prompt: self.system_prompt,
+        })
+    }
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /reports - List stored reports
+///
+/// Returns one page of reports, newest first, with the total number of
+/// matching reports.
+///
+/// # Query Parameters
+/// - period: Optional period filter ("daily" or "weekly")
+/// - limit: Maximum results to return (default: 50)
+/// - offset: Offset for pagination (default: 0)
+pub async fn list_reports(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReportQuery>,
+) -> Result<Json<Paginated<ReportRecord>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(50).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "List reports request: period={:?}, limit={}, offset={}",
+        params.period, pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let period = params.period.as_deref();
+    let reports = match state.db.list_reports(period, pagination).await {
+        Ok(reports) => state
+            .db
+            .count_reports(period)
+            .await
+            .map(|total| (reports, total)),
+        Err(e) => Err(e),
+    };
+    match reports {
+        Ok((reports, total)) => {
+            debug!("Retrieved {} of {} reports", reports.len(), total);
+            Ok(Json(Paginated {
+                pagination: PaginationInfo::counted(limit, offset, reports.len(), total),
+                data: reports,
+            }))
+        }
+        Err(e) => {
+            error!("Failed to list reports: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// GET /reports/:id - Get a single report
+///
+/// # Path Parameters
+/// - id: Report ID
+pub async fn get_report(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<ReportRecord>> {
+    debug!("Get report request: id={}", id);
+
+    Ok(Json(fetch_report(&state, id).await?))
+}
+
+/// GET /reports/:id/download - Download a report as Markdown, HTML or PDF
+///
+/// HTML and PDF exports are standalone documents with thumbnails of frames
+/// referenced via `/api/frames/{id}/image` embedded.
+///
+/// # Path Parameters
+/// - id: Report ID
+///
+/// # Query Parameters
+/// - format: "md" (default), "html" or "pdf"
+pub async fn download_report(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(params): Query<ReportDownloadQuery>,
+) -> Result<impl IntoResponse> {
+    let format = params.format.as_deref().unwrap_or("md").to_lowercase();
+    debug!("Download report request: id={}, format={}", id, format);
+
+    let report = fetch_report(&state, id).await?;
+
+    let title = format!("ScreenSearch {} report", capitalize(&report.period));
+    let subtitle = format!(
+        "{} to {} - {}",
+        report.start_time.format("%Y-%m-%d %H:%M"),
+        report.end_time.format("%Y-%m-%d %H:%M"),
+        report.model
+    );
+
+    let (content_type, extension, body) = match format.as_str() {
+        "md" | "markdown" => (
+            "text/markdown; charset=utf-8",
+            "md",
+            report.content.into_bytes(),
+        ),
+        "html" => {
+            let thumbnails = load_thumbnails(&state, &report.content).await;
+            let html = export::render_html(&title, &subtitle, &report.content, &thumbnails);
+            ("text/html; charset=utf-8", "html", html.into_bytes())
+        }
+        "pdf" => {
+            let thumbnails = load_thumbnails(&state, &report.content).await;
+            let pdf = export::render_pdf(&title, &subtitle, &report.content, &thumbnails);
+            ("application/pdf", "pdf", pdf)
+        }
+        other => {
+            return Err(AppError::InvalidRequest(format!(
+                "Unsupported format '{}'. Use md, html or pdf",
+                other
+            )))
+        }
+    };
+
+    let filename = format!(
+        "screensearch-{}-report-{}.{}",
+        report.period,
+        report.end_time.format("%Y-%m-%d"),
+        extension
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    ))
+}
+
+/// Load thumbnails for frames referenced in a report, skipping any that fail
+async fn load_thumbnails(state: &AppState, content: &str) -> HashMap<i64, FrameThumbnail> {
+    let mut thumbnails = HashMap::new();
+
+    for frame_id in export::referenced_frame_ids(content)
+        .into_iter()
+        .take(MAX_EXPORT_THUMBNAILS)
+    {
+        let frame = match state.db.get_frame(frame_id).await {
+            Ok(Some(frame)) => frame,
+            Ok(None) => {
+                warn!("Report references missing frame {}", frame_id);
+                continue;
+            }
+            Err(e) => {
+                warn!("Failed to load frame {} for export: {}", frame_id, e);
+                continue;
+            }
+        };
+
+        let data = match crate::video_archive::read_frame_image(
+            &state.db,
+            &state.capture_storage(),
+            state.ffmpeg_path(),
+            &frame,
+        )
+        .await
+        {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Failed to read image for frame {}: {:#}", frame_id, e);
+                continue;
+            }
+        };
+
+        match tokio::task::spawn_blocking(move || export::make_thumbnail(&data)).await {
+            Ok(Ok(thumbnail)) => {
+                thumbnails.insert(frame_id, thumbnail);
+            }
+            Ok(Err(e)) => warn!("Failed to create thumbnail for frame {}: {}", frame_id, e),
+            Err(e) => warn!("Thumbnail task failed for frame {}: {}", frame_id, e),
+        }
+    }
+
+    thumbnails
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// DELETE /reports/:id - Delete a report
+///
+/// # Path Parameters
+/// - id: Report ID
+pub async fn delete_report(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    debug!("Delete report request: id={}", id);
+
+    match state.db.delete_report(id).await {
+        Ok(0) => Err(AppError::NotFound(format!("Report {} not found", id))),
+        Ok(affected) => {
+            debug!("Deleted report: id={}, rows_affected={}", id, affected);
+            Ok(Json(serde_json::json!({
+                "success": true,
+                "message": format!("Report {} deleted", id)
+            })))
+        }
+        Err(e) => {
+            error!("Failed to delete report {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// Load a report or return 404
+async fn fetch_report(state: &AppState, id: i64) -> Result<ReportRecord> {
+    match state.db.get_report(id).await {
+        Ok(Some(report)) => Ok(report),
+        Ok(None) => {
+            error!("Report {} not found", id);
+            Err(AppError::NotFound(format!("Report {} not found", id)))
+        }
+        Err(e) => {
+            error!("Failed to retrieve report {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// GET /ai/templates - List report templates
+///
+/// Returns one page of templates, by name, with the total number of templates.
+///
+/// # Query Parameters
+/// - limit: Maximum templates to return (default: 100)
+/// - offset: Number of templates to skip (default: 0)
+pub async fn list_report_templates(
+    State(state): State<Arc<AppState>>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Paginated<ReportTemplateRecord>>> {
+    debug!("List report templates request");
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let templates = match state.db.list_report_templates(pagination).await {
+        Ok(templates) => state
+            .db
+            .count_report_templates()
+            .await
+            .map(|total| (templates, total)),
+        Err(e) => Err(e),
+    };
+    match templates {
+        Ok((templates, total)) => Ok(Json(Paginated {
+            pagination: PaginationInfo::counted(limit, offset, templates.len(), total),
+            data: templates,
+        })),
+        Err(e) => {
+            error!("Failed to list report templates: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// GET /ai/templates/:id - Get a report template
+///
+/// # Path Parameters
+/// - id: Template ID
+pub async fn get_report_template(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<ReportTemplateRecord>> {
+    debug!("Get report template request: id={}", id);
+
+    Ok(Json(fetch_template(&state, id).await?))
+}
+
+/// POST /ai/templates - Create a report template
+///
+/// # Request Body
+/// - name: Unique template name
+/// - description: Optional description
+/// - system_prompt: Prompt with {start}, {end}, {top_apps}, {frame_count} variables
+pub async fn create_report_template(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ReportTemplateRequest>,
+) -> Result<Json<ReportTemplateRecord>> {
+    debug!("Create report template request: {}", req.name);
+
+    let template = req.into_new_template()?;
+
+    if let Ok(Some(_)) = state.db.get_report_template_by_name(&template.name).await {
+        return Err(AppError::InvalidRequest(format!(
+            "Template '{}' already exists",
+            template.name
+        )));
+    }
+
+    let id = match state.db.create_report_template(template).await {
+        Ok(id) => id,
+        Err(e) => {
+            error!("Failed to create report template: {}", e);
+            return Err(AppError::Database(e));
+        }
+    };
+
+    Ok(Json(fetch_template(&state, id).await?))
+}
+
+/// PUT /ai/templates/:id - Update a report template
+///
+/// # Path Parameters
+/// - id: Template ID
+///
+/// # Request Body
+/// - name: Unique template name
+/// - description: Optional description
+/// - system_prompt: Prompt with {start}, {end}, {top_apps}, {frame_count} variables
+pub async fn update_report_template(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(req): Json<ReportTemplateRequest>,
+) -> Result<Json<ReportTemplateRecord>> {
+    debug!("Update report template request: id={}", id);
+
+    let template = req.into_new_template()?;
+
+    fetch_template(&state, id).await?;
+
+    if let Ok(Some(existing)) = state.db.get_report_template_by_name(&template.name).await {
+        if existing.id != id {
+            return Err(AppError::InvalidRequest(format!(
+                "Template '{}' already exists",
+                template.name
+            )));
+        }
+    }
+
+    if let Err(e) = state.db.update_report_template(id, template).await {
+        error!("Failed to update report template {}: {}", id, e);
+        return Err(AppError::Database(e));
+    }
+
+    Ok(Json(fetch_template(&state, id).await?))
+}
+
+/// DELETE /ai/templates/:id - Delete a report template
+///
+/// # Path Parameters
+/// - id: Template ID
+pub async fn delete_report_template(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    debug!("Delete report template request: id={}", id);
+
+    match state.db.delete_report_template(id).await {
+        Ok(0) => Err(AppError::NotFound(format!("Template {} not found", id))),
+        Ok(affected) => {
+            debug!(
+                "Deleted report template: id={}, rows_affected={}",
+                id, affected
+            );
+            Ok(Json(serde_json::json!({
+                "success": true,
+                "message": format!("Template {} deleted", id)
+            })))
+        }
+        Err(e) => {
+            error!("Failed to delete report template {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// Load a report template or return 404
+async fn fetch_template(state: &AppState, id: i64) -> Result<ReportTemplateRecord> {
+    match state.db.get_report_template(id).await {
+        Ok(Some(template)) => Ok(template),
+        Ok(None) => Err(AppError::NotFound(format!("Template {} not found", id))),
+        Err(e) => {
+            error!("Failed to retrieve report template {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
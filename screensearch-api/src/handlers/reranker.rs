@@ -3,6 +3,7 @@
 //! Provides reranking functions to improve the ordering of search results
 //! before they're sent to the LLM for report generation.
 
+use chrono::{DateTime, Utc};
 use screensearch_db::SemanticResult;
 use tracing::debug;
 
@@ -13,6 +14,9 @@ pub struct RerankConfig {
     pub top_k: usize,
     /// Weight for recency (0.0 = ignore time, 1.0 = heavily favor recent)
     pub recency_weight: f32,
+    /// Hours before the newest result after which the recency boost halves
+    /// (0 = the same boost for all results)
+    pub recency_half_life_hours: f32,
     /// Weight for text length (prefer chunks with more content)
     pub length_weight: f32,
     /// Minimum similarity score threshold
@@ -24,6 +28,7 @@ impl Default for RerankConfig {
         Self {
             top_k: 20,
             recency_weight: 0.1,
+            recency_half_life_hours: 24.0,
             length_weight: 0.05,
             min_score: 0.0,
         }
@@ -33,7 +38,8 @@ impl Default for RerankConfig {
 /// Rerank search results based on multiple signals
 ///
 /// Combines the original similarity score with:
-/// - Recency: more recent frames get a boost
+/// - Recency: more recent frames get a boost that decays exponentially with
+///   their age relative to the newest result
 /// - Length: longer chunks get a small boost (more context)
 /// - Diversity: penalizes repeated content from same frame
 pub fn rerank_results(
@@ -49,14 +55,12 @@ pub fn rerank_results(
     // Filter by minimum score
     results.retain(|r| r.similarity_score >= config.min_score);
 
-    // Find time range for normalization
-    let timestamps: Vec<i64> = results
+    // Ages are measured from the newest result
+    let max_time = results
         .iter()
         .map(|r| r.frame.timestamp.timestamp())
-        .collect();
-    let min_time = *timestamps.iter().min().unwrap_or(&0);
-    let max_time = *timestamps.iter().max().unwrap_or(&0);
-    let time_range = (max_time - min_time).max(1) as f32;
+        .max()
+        .unwrap_or(0);
 
     // Find max text length for normalization
     let max_len = results
@@ -71,10 +75,14 @@ pub fn rerank_results(
         .map(|r| {
             let base_score = r.similarity_score;
 
-            // Recency boost: newer = higher
-            let recency_normalized =
-                (r.frame.timestamp.timestamp() - min_time) as f32 / time_range;
-            let recency_boost = recency_normalized * config.recency_weight;
+            // Recency boost: halves every `recency_half_life_hours`
+            let age_hours = (max_time - r.frame.timestamp.timestamp()) as f32 / 3600.0;
+            let decay = if config.recency_half_life_hours > 0.0 {
+                0.5_f32.powf(age_hours / config.recency_half_life_hours)
+            } else {
+                1.0
+            };
+            let recency_boost = decay * config.recency_weight;
 
             // Length boost: longer = higher (normalized)
             let length_normalized = r.chunk_text.len() as f32 / max_len;
@@ -110,6 +118,56 @@ pub fn rerank_results(
     deduped
 }
 
+/// Order results so that the first ones spread over the period from `start`
+/// to `end`, keeping at most `top_k`
+///
+/// The period is split into `slots` equal parts. The best result of each
+/// part comes first, so that a context filled from the front covers the
+/// whole period instead of the hour with the most matches; the other
+/// results follow. `results` must be sorted by score, best first, and both
+/// groups keep that order.
+pub fn spread_over_period(
+    results: Vec<SemanticResult>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    top_k: usize,
+    slots: usize,
+) -> Vec<SemanticResult> {
+    if slots == 0 {
+        return results.into_iter().take(top_k).collect();
+    }
+
+    let period_secs = (end - start).num_seconds().max(1) as f64;
+    let slot_of = |result: &SemanticResult| {
+        let offset = (result.frame.timestamp - start).num_seconds();
+        (offset >= 0 && result.frame.timestamp <= end)
+            .then(|| ((offset as f64 / period_secs * slots as f64) as usize).min(slots - 1))
+    };
+
+    // Best result of each part of the period, then the others
+    let mut covered = vec![false; slots];
+    let mut first = Vec::new();
+    let mut rest = Vec::new();
+    for result in results {
+        match slot_of(&result) {
+            Some(slot) if !covered[slot] => {
+                covered[slot] = true;
+                first.push(result);
+            }
+            _ => rest.push(result),
+        }
+    }
+
+    debug!(
+        "Spread context over {} of {} parts of the period",
+        first.len(),
+        slots
+    );
+    first.extend(rest);
+    first.truncate(top_k);
+    first
+}
+
 /// Simple keyword-based reranking boost
 ///
 /// Boosts results that contain query keywords in the chunk text.
@@ -147,15 +205,27 @@ mod tests {
     use screensearch_db::FrameRecord;
 
     fn make_result(id: i64, score: f32, text: &str) -> SemanticResult {
+        make_result_at(id, score, text, Utc::now())
+    }
+
+    fn make_result_at(id: i64, score: f32, text: &str, timestamp: DateTime<Utc>) -> SemanticResult {
         SemanticResult {
             frame: FrameRecord {
                 id,
-                timestamp: Utc::now(),
-                image_path: String::new(),
-                active_process: None,
+                chunk_id: None,
+                timestamp,
+                monitor_index: 0,
+                device_name: String::new(),
+                file_path: String::new(),
                 active_window: None,
-                device_name: None,
-                monitor_index: None,
+                active_process: None,
+                browser_url: None,
+                width: 0,
+                height: 0,
+                offset_index: 0,
+                focused: None,
+                pinned: false,
+                created_at: timestamp,
             },
             chunk_text: text.to_string(),
             chunk_index: 0,
@@ -199,4 +269,56 @@ mod tests {
         // Should only have 2 results (one per frame)
         assert_eq!(reranked.len(), 2);
     }
+
+    #[test]
+    fn test_rerank_recency_decays_with_half_life() {
+        let now = Utc::now();
+        let results = vec![
+            make_result_at(1, 0.5, "same", now),
+            make_result_at(2, 0.5, "same", now - chrono::Duration::hours(24)),
+        ];
+        let config = RerankConfig {
+            recency_weight: 0.2,
+            recency_half_life_hours: 24.0,
+            length_weight: 0.0,
+            ..Default::default()
+        };
+        let reranked = rerank_results(results, &config);
+
+        assert_eq!(reranked[0].frame.id, 1);
+        assert!((reranked[0].similarity_score - 0.7).abs() < 0.001);
+        assert!((reranked[1].similarity_score - 0.6).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_spread_over_period_covers_each_part() {
+        let start = Utc::now() - chrono::Duration::hours(10);
+        let end = start + chrono::Duration::hours(10);
+        let at = |hours: i64| start + chrono::Duration::minutes(hours * 60 + 30);
+
+        // Best matches all in the first hour, weaker ones later
+        let results = vec![
+            make_result_at(1, 0.9, "a", at(0)),
+            make_result_at(2, 0.8, "b", at(0)),
+            make_result_at(3, 0.7, "c", at(0)),
+            make_result_at(4, 0.3, "d", at(5)),
+            make_result_at(5, 0.2, "e", at(9)),
+        ];
+        let spread = spread_over_period(results, start, end, 3, 2);
+        let ids: Vec<i64> = spread.iter().map(|r| r.frame.id).collect();
+
+        // One result from each half, then the best remaining
+        assert_eq!(ids, vec![1, 4, 2]);
+
+        let results = vec![
+            make_result_at(1, 0.9, "a", at(0)),
+            make_result_at(2, 0.8, "b", at(0)),
+            make_result_at(3, 0.7, "c", at(0)),
+            make_result_at(4, 0.3, "d", at(5)),
+            make_result_at(5, 0.2, "e", at(9)),
+        ];
+        let spread = spread_over_period(results, start, end, 3, 10);
+        let ids: Vec<i64> = spread.iter().map(|r| r.frame.id).collect();
+        assert_eq!(ids, vec![1, 4, 5]);
+    }
 }
@@ -1,34 +1,71 @@
 //! Search endpoint handlers
 
+use crate::dedup::{collapse_duplicates, DEFAULT_DEDUP_WINDOW_SECS};
 use crate::error::{AppError, Result};
 use crate::models::{
-    FrameQuery, KeywordSearchQuery, PaginatedFramesResponse, PaginationInfo, SearchQuery,
+    BatchQueryResponse, BatchQueryResults, BatchSearchMode, BatchSearchRequest,
+    BatchSearchResponse, FrameContextQuery, FrameContextResponse, FrameQuery, FrameResponse,
+    KeywordSearchQuery, PaginatedFramesResponse, PaginationInfo, SearchMode, SearchQuery,
+    SearchResponse, SuggestQuery, TagResponse, TimeExpressionQuery,
 };
+use crate::regex_search::{compile_pattern, regex_search};
 use crate::state::AppState;
+use crate::suggest::{rank_suggestions, Suggestion, SuggestionSource};
+use crate::time_expr::{apply_when, resolve_when, TimeRange};
+use crate::video_archive::{image_content_type, read_frame_image};
 use axum::extract::{Path, Query, State};
-use axum::http::header;
+use axum::http::{header, HeaderMap, HeaderValue};
 use axum::response::IntoResponse;
 use axum::Json;
-use screensearch_db::{FrameFilter, Pagination, SearchResult};
+use chrono::Utc;
+use futures::future::try_join_all;
+use screensearch_db::{
+    FrameFilter, FrameRecord, NewSearchHistory, Pagination, SearchFacet, SearchRanking,
+};
 use std::sync::Arc;
-use tokio::fs;
-use tracing::{debug, error};
+use tracing::{debug, error, info, warn};
 
 /// GET /search - Full-text search with filters
 ///
 /// Searches OCR text using FTS5 with BM25 ranking. Supports time range,
 /// application, and keyword filters.
 ///
+/// In regex mode `q` is a regular expression matched against the OCR text of
+/// the frames selected by the filters, newest first. The scan stops after
+/// 50,000 text records or 5 seconds, so narrow it with a time range or app.
+///
 /// # Query Parameters
-/// - q: Search query string
+/// - q: Search query string, or a regular expression in regex mode
+/// - mode: `fts` (default) or `regex`
 /// - start_time: Optional start time filter (ISO 8601)
 /// - end_time: Optional end time filter (ISO 8601)
 /// - app: Optional application name filter
+/// - exclude_apps: Optional comma-separated applications to leave out
+/// - exclude_tags: Optional comma-separated tag names to leave out
+/// - when: Optional natural-language time range, e.g. "yesterday afternoon"
+/// - tz: UTC offset `when` is resolved in (default: server time zone)
 /// - limit: Maximum results to return (default: 100)
+/// - offset: Number of results to skip (default: 0)
+/// - facets: Optional comma-separated facets to count over all matches
+///   (`app`, `day`, `tag`, `monitor` or `all`), returned in `facets`
+/// - fuzzy: Also match words with typos or OCR errors (default: false)
+/// - dedup: Collapse near-identical results of the same app captured close
+///   together into one with a `duplicates_count` (default: false)
+/// - dedup_window: Maximum seconds between collapsed frames (default: 60)
+/// - text_weight, title_weight, recency_weight, recency_half_life_hours:
+///   Override the ranking weights kept in the settings for this search
+///
+/// Results come one per frame in a paginated envelope. Regex matches and
+/// collapsed duplicates are not counted, so their pages have no `total`,
+/// only `has_more`.
+///
+/// Each search is recorded in the search history; the `X-Search-Id`
+/// response header holds its ID.
+#[tracing::instrument(skip_all, fields(q = %params.q, fuzzy = params.fuzzy))]
 pub async fn search(
     State(state): State<Arc<AppState>>,
     Query(params): Query<SearchQuery>,
-) -> Result<Json<Vec<SearchResult>>> {
+) -> Result<(HeaderMap, Json<SearchResponse>)> {
     debug!("Search request: q={}, limit={:?}", params.q, params.limit);
 
     if params.q.is_empty() {
@@ -37,39 +74,499 @@ pub async fn search(
         ));
     }
 
+    let regex = match params.mode {
+        SearchMode::Fts => None,
+        SearchMode::Regex if params.facets.is_some() || params.fuzzy => {
+            return Err(AppError::InvalidRequest(
+                "Facets and fuzzy matching are not supported in regex mode".to_string(),
+            ));
+        }
+        SearchMode::Regex => Some(compile_pattern(&params.q)?),
+    };
+
+    let facets = match params.facets.as_deref() {
+        Some(names) => Some(parse_facets(names)?),
+        None => None,
+    };
+
+    let ranking = match regex {
+        Some(_) => SearchRanking::default(),
+        None => search_ranking(&state, &params).await?,
+    };
+
+    let (start_time, end_time) = apply_when(
+        params.when.as_deref(),
+        params.tz.as_deref(),
+        params.start_time,
+        params.end_time,
+    )?;
+
     // Build filter from query parameters
     let filter = FrameFilter {
-        start_time: params.start_time,
-        end_time: params.end_time,
+        start_time,
+        end_time,
         app_name: params.app,
         device_name: None,
         tag_ids: None,
         monitor_index: None,
+        exclude_apps: parse_list(params.exclude_apps.as_deref()),
+        exclude_tags: parse_list(params.exclude_tags.as_deref()),
     };
 
-    // Build pagination
-    let pagination = Pagination {
-        limit: params.limit.unwrap_or(100),
-        offset: 0,
+    let limit = params.limit.unwrap_or(100).max(0);
+    let offset = params.offset.unwrap_or(0).max(0);
+    // Regex matches and collapsed duplicates cannot be counted without
+    // finding them all: fetch the results up to the end of this page and
+    // one more, with extra candidates when duplicates collapse
+    let counted = regex.is_none() && !params.dedup;
+    let pagination = if counted {
+        Pagination { limit, offset }
+    } else {
+        let wanted = offset.saturating_add(limit).saturating_add(1);
+        Pagination {
+            limit: if params.dedup {
+                wanted.saturating_mul(DEDUP_FETCH_FACTOR)
+            } else {
+                wanted
+            },
+            offset: 0,
+        }
     };
 
     // Execute search
-    match state
-        .db
-        .search_ocr_text(&params.q, filter, pagination)
-        .await
-    {
+    let results = if let Some(regex) = regex {
+        Ok(regex_search(&state.db, &regex, &filter, pagination.limit.max(0) as usize).await?)
+    } else if params.fuzzy {
+        state
+            .db
+            .search_ocr_text_fuzzy_ranked(&params.q, filter.clone(), pagination, &ranking)
+            .await
+    } else {
+        state
+            .db
+            .search_ocr_text_ranked(&params.q, filter.clone(), pagination, &ranking)
+            .await
+    };
+    let results = match results {
         Ok(results) => {
             debug!("Found {} search results", results.len());
-            Ok(Json(results))
+            results
         }
         Err(e) => {
             error!("Search failed: {}", e);
+            return Err(AppError::Database(e));
+        }
+    };
+
+    let (results, page) = if counted {
+        let total = if params.fuzzy {
+            state
+                .db
+                .count_search_results_fuzzy(&params.q, &filter)
+                .await
+        } else {
+            state.db.count_search_results(&params.q, &filter).await
+        };
+        let total = total.map_err(|e| {
+            error!("Counting search results failed: {}", e);
+            AppError::Database(e)
+        })?;
+        let page = PaginationInfo::counted(limit, offset, results.len(), total);
+        (results, page)
+    } else {
+        let fetched = results.len() as i64;
+        let results = if params.dedup {
+            let window = params
+                .dedup_window
+                .unwrap_or(DEFAULT_DEDUP_WINDOW_SECS)
+                .max(0);
+            collapse_duplicates(results, window)
+        } else {
+            results
+        };
+        // A full batch of candidates may have more behind it
+        let has_more = results.len() as i64 > offset.saturating_add(limit)
+            || (params.dedup && fetched >= pagination.limit);
+        let results = results
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect();
+        (results, PaginationInfo::uncounted(limit, offset, has_more))
+    };
+
+    // Record the search, letting clients report the result opened
+    let mode = match params.mode {
+        SearchMode::Regex => "regex",
+        SearchMode::Fts if params.fuzzy => "fuzzy",
+        SearchMode::Fts => "fts",
+    };
+    let history = NewSearchHistory {
+        query: params.q.clone(),
+        mode: mode.to_string(),
+        result_count: page.total.unwrap_or(results.len() as i64),
+    };
+    let mut headers = HeaderMap::new();
+    match state.db.insert_search_history(history).await {
+        Ok(id) => {
+            headers.insert("x-search-id", HeaderValue::from(id));
+        }
+        Err(e) => warn!("Failed to record search history: {}", e),
+    }
+
+    let Some(facets) = facets else {
+        return Ok((
+            headers,
+            Json(SearchResponse {
+                data: results,
+                pagination: page,
+                facets: None,
+            }),
+        ));
+    };
+
+    let facets = if params.fuzzy {
+        state
+            .db
+            .search_facets_fuzzy(&params.q, &filter, &facets, MAX_FACET_VALUES)
+            .await
+    } else {
+        state
+            .db
+            .search_facets(&params.q, &filter, &facets, MAX_FACET_VALUES)
+            .await
+    };
+    match facets {
+        Ok(facets) => Ok((
+            headers,
+            Json(SearchResponse {
+                data: results,
+                pagination: page,
+                facets: Some(facets),
+            }),
+        )),
+        Err(e) => {
+            error!("Facet counting failed: {}", e);
             Err(AppError::Database(e))
         }
     }
 }
 
+/// Ranking weights kept in the settings, overridden by those of the request
+async fn search_ranking(state: &AppState, params: &SearchQuery) -> Result<SearchRanking> {
+    let settings = state.db.get_settings().await?;
+    let mut ranking = serde_json::from_str::<SearchRanking>(&settings.search_ranking)
+        .unwrap_or_else(|e| {
+            warn!("Ignoring invalid search_ranking setting: {}", e);
+            SearchRanking::default()
+        });
+
+    if let Some(weight) = params.text_weight {
+        ranking.text_weight = weight;
+    }
+    if let Some(weight) = params.title_weight {
+        ranking.title_weight = weight;
+    }
+    if let Some(weight) = params.recency_weight {
+        ranking.recency_weight = weight;
+    }
+    if let Some(hours) = params.recency_half_life_hours {
+        ranking.recency_half_life_hours = hours;
+    }
+    validate_ranking(&ranking).map_err(AppError::InvalidRequest)?;
+
+    Ok(ranking)
+}
+
+/// Check that ranking weights are usable
+pub(crate) fn validate_ranking(ranking: &SearchRanking) -> std::result::Result<(), String> {
+    let weights = [
+        ranking.text_weight,
+        ranking.title_weight,
+        ranking.recency_weight,
+    ];
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+        return Err("Ranking weights must be zero or more".to_string());
+    }
+    if !ranking.recency_half_life_hours.is_finite() || ranking.recency_half_life_hours <= 0.0 {
+        return Err("Recency half-life must be more than zero hours".to_string());
+    }
+    Ok(())
+}
+
+/// Maximum values returned per search facet
+const MAX_FACET_VALUES: i64 = 100;
+/// Candidates fetched per requested result when collapsing duplicates
+const DEDUP_FETCH_FACTOR: i64 = 4;
+
+/// GET /search/time - Resolve a natural-language time range
+///
+/// Lets clients show the range a `when` parameter stands for before
+/// searching with it.
+///
+/// # Query Parameters
+/// - when: Time expression, e.g. "last tuesday morning" or "past 3 hours"
+/// - tz: UTC offset to resolve in (default: server time zone)
+pub async fn resolve_time(Query(params): Query<TimeExpressionQuery>) -> Result<Json<TimeRange>> {
+    let range = resolve_when(&params.when, params.tz.as_deref())?;
+    debug!(
+        "Resolved time expression '{}' to {} - {}",
+        params.when, range.start, range.end
+    );
+    Ok(Json(range))
+}
+
+/// GET /search/suggest - Suggest completions for a search box
+///
+/// Combines past searches that found something, tag names, application
+/// names and frequent words of captured text starting with `prefix`, ranked
+/// by source and frequency. Words complete the last word of `prefix`.
+///
+/// # Query Parameters
+/// - prefix: Text typed so far
+/// - limit: Maximum suggestions (default: 10, max: 50)
+pub async fn suggest(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SuggestQuery>,
+) -> Result<Json<Vec<Suggestion>>> {
+    let prefix = params.prefix.trim_start();
+    if prefix.trim().is_empty() {
+        return Err(AppError::InvalidRequest(
+            "Prefix cannot be empty".to_string(),
+        ));
+    }
+    let limit = params.limit.unwrap_or(10).clamp(1, 50);
+    debug!("Suggest request: prefix={}, limit={}", prefix, limit);
+
+    let word = prefix
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default();
+    let terms = async {
+        if word.chars().count() < MIN_SUGGEST_WORD_LEN {
+            return Ok(Vec::new());
+        }
+        state
+            .db
+            .suggest_terms(word, MIN_SUGGEST_TERM_COUNT, limit)
+            .await
+    };
+    let (history, tags, apps, terms) = tokio::try_join!(
+        state.db.suggest_past_queries(prefix, limit),
+        state.db.suggest_tags(prefix, limit),
+        state.db.suggest_apps(prefix, limit),
+        terms,
+    )
+    .map_err(|e| {
+        error!("Failed to suggest completions: {}", e);
+        AppError::Database(e)
+    })?;
+
+    let suggestions = rank_suggestions(
+        prefix,
+        &[
+            (SuggestionSource::History, history),
+            (SuggestionSource::Tag, tags),
+            (SuggestionSource::App, apps),
+            (SuggestionSource::Term, terms),
+        ],
+        limit as usize,
+    );
+    Ok(Json(suggestions))
+}
+
+/// Characters of the last word before words of captured text are suggested
+const MIN_SUGGEST_WORD_LEN: usize = 2;
+/// OCR text records a word must appear in to be suggested
+const MIN_SUGGEST_TERM_COUNT: i64 = 2;
+
+/// Split a comma-separated list parameter, ignoring empty entries
+fn parse_list(value: Option<&str>) -> Option<Vec<String>> {
+    let items: Vec<String> = value?
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!items.is_empty()).then_some(items)
+}
+
+/// Parse a comma-separated facet list (`all` selects every facet)
+fn parse_facets(names: &str) -> Result<Vec<SearchFacet>> {
+    let mut facets = Vec::new();
+
+    for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let selected: &[SearchFacet] = if name.eq_ignore_ascii_case("all") {
+            &SearchFacet::ALL
+        } else {
+            match SearchFacet::from_name(name) {
+                Some(facet) => &[facet],
+                None => {
+                    return Err(AppError::InvalidRequest(format!(
+                        "Unknown facet '{}'. Use app, day, tag, monitor or all",
+                        name
+                    )))
+                }
+            }
+        };
+
+        for facet in selected {
+            if !facets.contains(facet) {
+                facets.push(*facet);
+            }
+        }
+    }
+
+    Ok(facets)
+}
+
+/// Maximum queries in one batch search
+const MAX_BATCH_QUERIES: usize = 20;
+/// Maximum results per query in a batch search
+const MAX_BATCH_LIMIT: i64 = 100;
+
+/// POST /search/batch - Resolve several queries in one request
+///
+/// All queries share the same filters and run concurrently. Semantic and
+/// hybrid modes embed every query in a single batch.
+///
+/// # Request Body
+/// - queries: Queries to resolve (1-20)
+/// - mode: Optional search mode ("fts", "semantic", "hybrid", default: "fts")
+/// - start_time / end_time: Optional time range (ISO 8601)
+/// - when / tz: Optional natural-language time range and its UTC offset
+/// - app: Optional application name filter
+/// - limit: Maximum results per query (default: 20, max: 100)
+/// - semantic_weight: Weight of the semantic score in hybrid mode (default: 0.5)
+#[tracing::instrument(skip_all, fields(queries = req.queries.len(), mode = ?req.mode))]
+pub async fn search_batch(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BatchSearchRequest>,
+) -> Result<Json<BatchSearchResponse>> {
+    debug!(
+        "Batch search request: {} queries, mode={:?}",
+        req.queries.len(),
+        req.mode
+    );
+
+    if req.queries.is_empty() || req.queries.len() > MAX_BATCH_QUERIES {
+        return Err(AppError::InvalidRequest(format!(
+            "Batch search takes 1 to {} queries",
+            MAX_BATCH_QUERIES
+        )));
+    }
+    if req.queries.iter().any(|q| q.trim().is_empty()) {
+        return Err(AppError::InvalidRequest(
+            "Search queries cannot be empty".to_string(),
+        ));
+    }
+
+    let limit = req.limit.unwrap_or(20).clamp(1, MAX_BATCH_LIMIT);
+    let (start_time, end_time) = apply_when(
+        req.when.as_deref(),
+        req.tz.as_deref(),
+        req.start_time,
+        req.end_time,
+    )?;
+
+    let results = match req.mode {
+        BatchSearchMode::Fts => {
+            let filter = FrameFilter {
+                start_time,
+                end_time,
+                app_name: req.app.clone(),
+                device_name: None,
+                tag_ids: None,
+                monitor_index: None,
+                exclude_apps: None,
+                exclude_tags: None,
+            };
+            let pagination = Pagination { limit, offset: 0 };
+
+            let searches = req.queries.iter().map(|query| {
+                state
+                    .db
+                    .search_ocr_text(query, filter.clone(), pagination.clone())
+            });
+            try_join_all(searches)
+                .await
+                .map_err(|e| {
+                    error!("Batch search failed: {}", e);
+                    AppError::Database(e)
+                })?
+                .into_iter()
+                .map(BatchQueryResults::Fts)
+                .collect::<Vec<_>>()
+        }
+        BatchSearchMode::Semantic | BatchSearchMode::Hybrid => {
+            let engine = state.get_embedding_engine().await.map_err(|e| {
+                AppError::Internal(format!("Failed to load embedding engine: {}", e))
+            })?;
+
+            let texts: Vec<&str> = req.queries.iter().map(String::as_str).collect();
+            let embeddings = engine.embed_batch(&texts).map_err(|e| {
+                AppError::Internal(format!("Failed to generate query embeddings: {}", e))
+            })?;
+
+            // Semantic search needs a bounded range; default to everything stored
+            let end_time = end_time.unwrap_or_else(Utc::now);
+            let start_time = start_time.unwrap_or_default();
+            let weight = req.semantic_weight.unwrap_or(0.5).clamp(0.0, 1.0);
+            // Fetch extra candidates when results are narrowed by app afterwards
+            let fetch_limit = if req.app.is_some() { limit * 4 } else { limit };
+            let hybrid = req.mode == BatchSearchMode::Hybrid;
+
+            let searches = req
+                .queries
+                .iter()
+                .zip(embeddings)
+                .map(|(query, embedding)| {
+                    let db = &state.db;
+                    async move {
+                        if hybrid {
+                            db.hybrid_search(
+                                query,
+                                embedding,
+                                weight,
+                                fetch_limit,
+                                start_time,
+                                end_time,
+                            )
+                            .await
+                        } else {
+                            db.semantic_search(embedding, fetch_limit, start_time, end_time)
+                                .await
+                        }
+                    }
+                });
+            try_join_all(searches)
+                .await
+                .map_err(|e| {
+                    error!("Batch search failed: {}", e);
+                    AppError::Database(e)
+                })?
+                .into_iter()
+                .map(|mut results| {
+                    if let Some(app) = &req.app {
+                        results.retain(|r| r.frame.active_process.as_deref() == Some(app.as_str()));
+                    }
+                    results.truncate(limit as usize);
+                    BatchQueryResults::Semantic(results)
+                })
+                .collect()
+        }
+    };
+
+    let results = req
+        .queries
+        .into_iter()
+        .zip(results)
+        .map(|(query, results)| BatchQueryResponse { query, results })
+        .collect();
+
+    Ok(Json(BatchSearchResponse { results }))
+}
+
 /// GET /search/keywords - Keyword-based search with ranking
 ///
 /// Searches for exact keyword matches in OCR text with confidence-based ranking.
@@ -77,6 +574,7 @@ pub async fn search(
 /// # Query Parameters
 /// - keywords: Comma-separated keywords to search for
 /// - limit: Maximum results to return (default: 100)
+#[tracing::instrument(skip_all, fields(keywords = %params.keywords))]
 pub async fn search_keywords(
     State(state): State<Arc<AppState>>,
     Query(params): Query<KeywordSearchQuery>,
@@ -128,7 +626,11 @@ pub async fn search_keywords(
 /// - start_time: Optional start time filter (ISO 8601)
 /// - end_time: Optional end time filter (ISO 8601)
 /// - monitor_index: Optional monitor index filter
+/// - exclude_apps / exclude_tags: Optional comma-separated applications and
+///   tag names to leave out
+/// - when / tz: Optional natural-language time range and its UTC offset
 /// - limit: Maximum results to return (default: 100)
+#[tracing::instrument(skip_all)]
 pub async fn get_frames(
     State(state): State<Arc<AppState>>,
     Query(params): Query<FrameQuery>,
@@ -143,13 +645,18 @@ pub async fn get_frames(
         params.q
     );
 
+    let (start_time, end_time) = apply_when(
+        params.when.as_deref(),
+        params.tz.as_deref(),
+        params.start_time,
+        params.end_time,
+    )?;
+
     // Use current time as default end time if not provided
-    let end_time = params.end_time.unwrap_or_else(chrono::Utc::now);
+    let end_time = end_time.unwrap_or_else(chrono::Utc::now);
 
     // Use 24 hours ago as default start time if not provided
-    let start_time = params
-        .start_time
-        .unwrap_or_else(|| end_time - chrono::Duration::hours(24));
+    let start_time = start_time.unwrap_or_else(|| end_time - chrono::Duration::hours(24));
 
     let filter = FrameFilter {
         start_time: Some(start_time),
@@ -158,6 +665,8 @@ pub async fn get_frames(
         device_name: None,
         tag_ids: None,
         monitor_index: params.monitor_index,
+        exclude_apps: parse_list(params.exclude_apps.as_deref()),
+        exclude_tags: parse_list(params.exclude_tags.as_deref()),
     };
 
     let limit = params.limit.unwrap_or(100);
@@ -170,6 +679,14 @@ pub async fn get_frames(
         if !query.is_empty() {
             debug!("Using FTS search for query: {}", query);
 
+            let total = match state.db.count_search_results(&query, &filter).await {
+                Ok(count) => count,
+                Err(e) => {
+                    error!("Failed to count FTS matches: {}", e);
+                    return Err(AppError::Database(e));
+                }
+            };
+
             let search_results = match state.db.search_ocr_text(&query, filter, pagination).await {
                 Ok(results) => results,
                 Err(e) => {
@@ -178,8 +695,6 @@ pub async fn get_frames(
                 }
             };
 
-            let total = search_results.len() as i64; // Note: This is approximate, FTS doesn't provide total count easily
-
             // Bulk load tags for all search results (performance optimization)
             let frame_ids: Vec<i64> = search_results.iter().map(|r| r.frame.id).collect();
             let tags_map = state
@@ -218,6 +733,7 @@ pub async fn get_frames(
                     file_path: frame.file_path,
                     app_name: frame.active_process.unwrap_or_default(),
                     window_name: frame.active_window.unwrap_or_default(),
+                    pinned: frame.pinned,
                     ocr_text,
                     tags,
                     thumbnail: None,
@@ -225,18 +741,18 @@ pub async fn get_frames(
             }
 
             return Ok(Json(PaginatedFramesResponse {
+                pagination: PaginationInfo::counted(limit, offset, enriched_frames.len(), total),
                 data: enriched_frames,
-                pagination: PaginationInfo {
-                    limit,
-                    offset,
-                    total,
-                },
             }));
         }
     }
 
     // Regular frame retrieval (no search)
-    let total = match state.db.count_frames_in_range(start_time, end_time).await {
+    let total = match state
+        .db
+        .count_frames_with_filter(start_time, end_time, &filter)
+        .await
+    {
         Ok(count) => count,
         Err(e) => {
             error!("Failed to count frames: {}", e);
@@ -294,6 +810,7 @@ pub async fn get_frames(
                     file_path: frame.file_path,
                     app_name: frame.active_process.unwrap_or_default(),
                     window_name: frame.active_window.unwrap_or_default(),
+                    pinned: frame.pinned,
                     ocr_text,
                     tags,
                     thumbnail: None,
@@ -301,12 +818,8 @@ pub async fn get_frames(
             }
 
             Ok(Json(PaginatedFramesResponse {
+                pagination: PaginationInfo::counted(limit, offset, enriched_frames.len(), total),
                 data: enriched_frames,
-                pagination: PaginationInfo {
-                    limit,
-                    offset,
-                    total,
-                },
             }))
         }
         Err(e) => {
@@ -364,6 +877,7 @@ pub async fn get_single_frame(
                 file_path: frame.file_path,
                 app_name: frame.active_process.unwrap_or_default(),
                 window_name: frame.active_window.unwrap_or_default(),
+                pinned: frame.pinned,
                 ocr_text,
                 tags,
                 thumbnail: None,
@@ -380,6 +894,172 @@ pub async fn get_single_frame(
     }
 }
 
+/// POST /frames/:id/pin - Pin a frame
+///
+/// Pinned frames are exempt from retention, compaction, archiving and
+/// purges until unpinned.
+///
+/// # Path Parameters
+/// - id: Frame ID
+pub async fn pin_frame(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    set_frame_pinned(&state, id, true).await
+}
+
+/// DELETE /frames/:id/pin - Unpin a frame
+///
+/// The frame is subject to cleanup again.
+///
+/// # Path Parameters
+/// - id: Frame ID
+pub async fn unpin_frame(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>> {
+    set_frame_pinned(&state, id, false).await
+}
+
+async fn set_frame_pinned(
+    state: &AppState,
+    id: i64,
+    pinned: bool,
+) -> Result<Json<serde_json::Value>> {
+    debug!("Set frame pinned: id={}, pinned={}", id, pinned);
+
+    match state.db.set_frame_pinned(id, pinned).await {
+        Ok(0) => Err(AppError::NotFound(format!("Frame {} not found", id))),
+        Ok(_) => {
+            info!(
+                "{} frame {}",
+                if pinned { "Pinned" } else { "Unpinned" },
+                id
+            );
+            Ok(Json(serde_json::json!({
+                "success": true,
+                "id": id,
+                "pinned": pinned,
+            })))
+        }
+        Err(e) => {
+            error!("Failed to update pin of frame {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// Maximum number of context frames returned on each side of a frame
+const MAX_CONTEXT_FRAMES: i64 = 50;
+
+/// GET /frames/:id/context - Get frames captured around a frame
+///
+/// Returns the frames captured immediately before and after the given frame
+/// on the same monitor, each with OCR text and tags.
+///
+/// # Path Parameters
+/// - id: Frame ID
+///
+/// # Query Parameters
+/// - before: Number of frames before (default: 5, max: 50)
+/// - after: Number of frames after (default: 5, max: 50)
+#[tracing::instrument(skip(state, params))]
+pub async fn get_frame_context(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Query(params): Query<FrameContextQuery>,
+) -> Result<Json<FrameContextResponse>> {
+    let before = params.before.unwrap_or(5).clamp(0, MAX_CONTEXT_FRAMES);
+    let after = params.after.unwrap_or(5).clamp(0, MAX_CONTEXT_FRAMES);
+    debug!(
+        "Get frame context request: id={}, before={}, after={}",
+        id, before, after
+    );
+
+    let frame = match state.db.get_frame(id).await {
+        Ok(Some(frame)) => frame,
+        Ok(None) => {
+            error!("Frame {} not found", id);
+            return Err(AppError::NotFound(format!("Frame {} not found", id)));
+        }
+        Err(e) => {
+            error!("Failed to retrieve frame {}: {}", id, e);
+            return Err(AppError::Database(e));
+        }
+    };
+
+    let (frames_before, frames_after) =
+        match state.db.get_surrounding_frames(&frame, before, after).await {
+            Ok(frames) => frames,
+            Err(e) => {
+                error!("Failed to retrieve context for frame {}: {}", id, e);
+                return Err(AppError::Database(e));
+            }
+        };
+
+    debug!(
+        "Retrieved {} frames before and {} after frame {}",
+        frames_before.len(),
+        frames_after.len(),
+        id
+    );
+
+    let mut before_responses = Vec::with_capacity(frames_before.len());
+    for f in frames_before {
+        before_responses.push(enrich_frame(&state, f).await);
+    }
+
+    let mut after_responses = Vec::with_capacity(frames_after.len());
+    for f in frames_after {
+        after_responses.push(enrich_frame(&state, f).await);
+    }
+
+    Ok(Json(FrameContextResponse {
+        frame: enrich_frame(&state, frame).await,
+        before: before_responses,
+        after: after_responses,
+    }))
+}
+
+/// Build a frame response with OCR text and tags loaded
+async fn enrich_frame(state: &AppState, frame: FrameRecord) -> FrameResponse {
+    let ocr_text = state
+        .db
+        .get_ocr_text_for_frame(frame.id)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| r.text)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let tags = state
+        .db
+        .get_tags_for_frame(frame.id)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| TagResponse {
+            id: t.id,
+            name: t.tag_name,
+            color: t.color,
+            created_at: t.created_at,
+        })
+        .collect();
+
+    FrameResponse {
+        id: frame.id,
+        timestamp: frame.timestamp,
+        file_path: frame.file_path,
+        app_name: frame.active_process.unwrap_or_default(),
+        window_name: frame.active_window.unwrap_or_default(),
+        pinned: frame.pinned,
+        ocr_text,
+        tags,
+        thumbnail: None,
+    }
+}
+
 /// GET /frames/:id/image - Get the image file for a specific frame
 ///
 /// Returns the captured screenshot image for a frame.
@@ -403,26 +1083,25 @@ pub async fn get_frame_image(
         }
     };
 
-    // Read image file
-    let image_data = match fs::read(&frame.file_path).await {
+    // Read the image file, or extract the frame from its archive or video chunk
+    let image_data = match read_frame_image(
+        &state.db,
+        &state.capture_storage(),
+        state.ffmpeg_path(),
+        &frame,
+    )
+    .await
+    {
         Ok(data) => data,
         Err(e) => {
-            error!("Failed to read image file {}: {}", frame.file_path, e);
+            error!("Failed to read image for frame {}: {:#}", id, e);
             return Err(AppError::NotFound(format!(
                 "Image file not found: {}",
                 frame.file_path
             )));
         }
     };
-
-    // Determine content type
-    let content_type = if frame.file_path.ends_with(".png") {
-        "image/png"
-    } else if frame.file_path.ends_with(".jpg") || frame.file_path.ends_with(".jpeg") {
-        "image/jpeg"
-    } else {
-        "application/octet-stream"
-    };
+    let content_type = image_content_type(&image_data);
 
     debug!(
         "Serving image for frame {}: {} ({} bytes)",
@@ -443,14 +1122,75 @@ mod tests {
         // Empty query should be rejected
         let query = SearchQuery {
             q: "".to_string(),
+            mode: SearchMode::Fts,
             start_time: None,
             end_time: None,
             app: None,
+            exclude_apps: None,
+            exclude_tags: None,
+            when: None,
+            tz: None,
             limit: None,
+            offset: None,
+            facets: None,
+            fuzzy: false,
+            dedup: false,
+            dedup_window: None,
+            text_weight: None,
+            title_weight: None,
+            recency_weight: None,
+            recency_half_life_hours: None,
         };
         assert!(query.q.is_empty());
     }
 
+    #[test]
+    fn test_parse_facets() {
+        assert_eq!(
+            parse_facets("app, Day,app").unwrap(),
+            vec![SearchFacet::App, SearchFacet::Day]
+        );
+        assert_eq!(parse_facets("all").unwrap(), SearchFacet::ALL.to_vec());
+        assert!(parse_facets("").unwrap().is_empty());
+        assert!(parse_facets("app,color").is_err());
+    }
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(
+            parse_list(Some("slack.exe, teams.exe,,")),
+            Some(vec!["slack.exe".to_string(), "teams.exe".to_string()])
+        );
+        assert_eq!(parse_list(Some(" , ")), None);
+        assert_eq!(parse_list(None), None);
+    }
+
+    #[test]
+    fn test_validate_ranking() {
+        assert!(validate_ranking(&SearchRanking::default()).is_ok());
+        assert!(validate_ranking(&SearchRanking {
+            title_weight: -1.0,
+            ..Default::default()
+        })
+        .is_err());
+        assert!(validate_ranking(&SearchRanking {
+            recency_half_life_hours: 0.0,
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_batch_request_defaults() {
+        let req: BatchSearchRequest = serde_json::from_str(r#"{"queries": ["a", "b"]}"#).unwrap();
+        assert_eq!(req.mode, BatchSearchMode::Fts);
+        assert_eq!(req.queries.len(), 2);
+
+        let req: BatchSearchRequest =
+            serde_json::from_str(r#"{"queries": ["a"], "mode": "hybrid"}"#).unwrap();
+        assert_eq!(req.mode, BatchSearchMode::Hybrid);
+    }
+
     #[test]
     fn test_keyword_parsing() {
         let keywords = "hello, world, test";
@@ -0,0 +1,208 @@
+//! Search History Handlers
+//!
+//! Browse the searches run through `GET /search`, which records each one
+//! with its mode and result count, and which queries come up most often or
+//! find nothing. Clients report the result opened after a search with the
+//! ID from the `X-Search-Id` response header.
+
+use crate::error::{AppError, Result};
+use crate::models::{Paginated, PaginationInfo};
+use crate::state::AppState;
+use axum::extract::{Json, Path, Query, State};
+use chrono::{DateTime, Utc};
+use screensearch_db::{Pagination, SearchHistoryRecord, SearchHistoryStats};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{debug, error, info};
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Search history query parameters
+#[derive(Debug, Deserialize)]
+pub struct SearchHistoryQuery {
+    /// Only searches run at or after this time (ISO 8601)
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// Only searches run at or before this time (ISO 8601)
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+
+    /// Only searches whose query contains this text (case-insensitive)
+    #[serde(default)]
+    pub q: Option<String>,
+
+    /// Maximum results (default: 100, max: 1000)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Pagination offset
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Search statistics query parameters
+#[derive(Debug, Deserialize)]
+pub struct SearchStatsQuery {
+    /// Only searches run at or after this time (ISO 8601)
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// Only searches run at or before this time (ISO 8601)
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+
+    /// Queries listed per ranking (default: 10, max: 100)
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// Clear search history query parameters
+#[derive(Debug, Deserialize)]
+pub struct ClearSearchHistoryQuery {
+    /// Only delete searches run before this time (default: all)
+    #[serde(default)]
+    pub before: Option<DateTime<Utc>>,
+}
+
+/// Result opened after a search
+#[derive(Debug, Deserialize)]
+pub struct SearchClickRequest {
+    pub frame_id: i64,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /search/history - List recorded searches, newest first
+///
+/// Returns one page of searches with the total number of matching searches.
+///
+/// # Query Parameters
+/// - start / end: Optional time range (ISO 8601)
+/// - q: Only searches whose query contains this text
+/// - limit: Maximum results (default: 100, max: 1000)
+/// - offset: Pagination offset
+pub async fn list_search_history(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchHistoryQuery>,
+) -> Result<Json<Paginated<SearchHistoryRecord>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(100).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "List search history request: q={:?}, limit={}, offset={}",
+        params.q, pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let contains = params.q.as_deref();
+    let searches = match state
+        .db
+        .list_search_history(params.start, params.end, contains, pagination)
+        .await
+    {
+        Ok(searches) => state
+            .db
+            .count_search_history(params.start, params.end, contains)
+            .await
+            .map(|total| (searches, total)),
+        Err(e) => Err(e),
+    };
+    match searches {
+        Ok((searches, total)) => Ok(Json(Paginated {
+            pagination: PaginationInfo::counted(limit, offset, searches.len(), total),
+            data: searches,
+        })),
+        Err(e) => {
+            error!("Failed to list search history: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// GET /search/history/stats - Aggregate search statistics
+///
+/// Counts searches, distinct queries, searches without results and searches
+/// after which a result was opened, and ranks the most frequent queries
+/// overall and among those that found nothing.
+///
+/// # Query Parameters
+/// - start / end: Optional time range (ISO 8601)
+/// - limit: Queries listed per ranking (default: 10, max: 100)
+pub async fn get_search_stats(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchStatsQuery>,
+) -> Result<Json<SearchHistoryStats>> {
+    let limit = params.limit.unwrap_or(10).clamp(1, 100);
+    debug!(
+        "Search stats request: start={:?}, end={:?}, limit={}",
+        params.start, params.end, limit
+    );
+
+    match state
+        .db
+        .get_search_history_stats(params.start, params.end, limit)
+        .await
+    {
+        Ok(stats) => Ok(Json(stats)),
+        Err(e) => {
+            error!("Failed to get search stats: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// POST /search/history/:id/click - Record the result opened after a search
+///
+/// # Request Body
+/// - frame_id: Frame of the opened result
+pub async fn record_search_click(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(request): Json<SearchClickRequest>,
+) -> Result<Json<serde_json::Value>> {
+    debug!(
+        "Search click request: id={}, frame_id={}",
+        id, request.frame_id
+    );
+
+    match state.db.record_search_click(id, request.frame_id).await {
+        Ok(true) => Ok(Json(serde_json::json!({
+            "success": true,
+            "message": format!("Click recorded for search {}", id)
+        }))),
+        Ok(false) => Err(AppError::NotFound(format!("Search {} not found", id))),
+        Err(e) => {
+            error!("Failed to record click for search {}: {}", id, e);
+            Err(AppError::Database(e))
+        }
+    }
+}
+
+/// DELETE /search/history - Clear the search history
+///
+/// # Query Parameters
+/// - before: Only delete searches run before this time (default: all)
+pub async fn clear_search_history(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ClearSearchHistoryQuery>,
+) -> Result<Json<serde_json::Value>> {
+    match state.db.clear_search_history(params.before).await {
+        Ok(deleted) => {
+            info!("Cleared {} searches from the history", deleted);
+            Ok(Json(serde_json::json!({
+                "success": true,
+                "deleted": deleted
+            })))
+        }
+        Err(e) => {
+            error!("Failed to clear search history: {}", e);
+            Err(AppError::Database(e))
+        }
+    }
+}
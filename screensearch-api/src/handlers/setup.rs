@@ -0,0 +1,264 @@
+//! First-run Setup Handlers
+//!
+//! Walk a new install through the choices of [`crate::setup`] and apply
+//! them to the settings table, `config.toml` and the data directory.
+
+use crate::error::{AppError, Result};
+use crate::models::{SetupMonitor, SetupStatusResponse};
+use crate::setup::{self, SetupAnswer, SetupChoices, SetupState, SetupStep};
+use crate::state::AppState;
+use crate::workers::ensure_retention_job;
+use axum::extract::{Json, State};
+use screensearch_capture::MonitorInfo;
+use screensearch_db::UpdateSettings;
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+
+/// GET /setup - First-run setup progress
+///
+/// Returns the next step to answer, the choices so far and what the steps
+/// offer: the available monitors and whether the embedding model is
+/// downloaded.
+pub async fn get_setup(State(state): State<Arc<AppState>>) -> Result<Json<SetupStatusResponse>> {
+    debug!("Get setup request");
+
+    let setup_state = load_state(&state).await?;
+    Ok(Json(status(&state, setup_state, false)))
+}
+
+/// POST /setup - Answer a setup step
+///
+/// Steps are answered in order; an earlier step can be answered again
+/// without losing the later answers.
+///
+/// # Request Body
+/// - step: `data_directory`, `retention`, `monitors`, `privacy`, `models` or `features`
+/// - data_dir: Absolute directory for the database and captures, or null for the default
+/// - retention_days: Days to keep captured frames
+/// - monitors: Monitor indices to capture (empty = all)
+/// - excluded_apps: Process names never to capture
+/// - download_model: Download the embedding model when setup completes
+/// - enable_embeddings: Generate embeddings for semantic search
+pub async fn answer_setup(
+    State(state): State<Arc<AppState>>,
+    Json(answer): Json<SetupAnswer>,
+) -> Result<Json<SetupStatusResponse>> {
+    debug!("Setup answer: {:?}", answer);
+
+    let _lock = state.setup.lock.lock().await;
+    let mut setup_state = load_state(&state).await?;
+    setup_state
+        .answer(answer)
+        .map_err(AppError::InvalidRequest)?;
+    save_state(&state, &setup_state).await?;
+
+    Ok(Json(status(&state, setup_state, false)))
+}
+
+/// POST /setup/complete - Save the setup choices
+///
+/// Writes the retention, monitors and excluded apps to the settings, the
+/// data directory, monitors and embeddings switch to `config.toml`, copies
+/// the database to a newly chosen data directory and starts downloading the
+/// embedding model when asked to. `restart_required` tells whether the
+/// application must be restarted for all choices to apply.
+pub async fn complete_setup(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SetupStatusResponse>> {
+    debug!("Complete setup request");
+
+    let _lock = state.setup.lock.lock().await;
+    let previous = load_state(&state).await?;
+    let mut setup_state = previous.clone();
+    setup_state.complete().map_err(AppError::InvalidRequest)?;
+    let choices = &setup_state.choices;
+
+    let paths = state.setup.paths();
+    if paths.is_none() && choices.data_dir.is_some() {
+        return Err(AppError::InvalidRequest(
+            "The data directory can only be chosen when running the full application".to_string(),
+        ));
+    }
+
+    // Settings table
+    let current = state.db.get_settings().await?;
+    let settings = state
+        .db
+        .update_settings(UpdateSettings {
+            capture_interval: current.capture_interval,
+            monitors: serde_json::to_string(&choices.monitors)
+                .map_err(|e| AppError::Internal(e.to_string()))?,
+            excluded_apps: serde_json::to_string(&choices.excluded_apps)
+                .map_err(|e| AppError::Internal(e.to_string()))?,
+            is_paused: current.is_paused,
+            retention_days: choices.retention_days,
+            audit_retention_days: None,
+            capture_schedule: None,
+            search_ranking: None,
+        })
+        .await?;
+    state.apply_capture_settings(&settings);
+    if settings.retention_days != current.retention_days {
+        if let Err(e) = ensure_retention_job(&state.db).await {
+            error!("Failed to queue retention job: {}", e);
+        }
+    }
+    state
+        .db
+        .set_metadata(
+            "embeddings_enabled",
+            if choices.enable_embeddings {
+                "true"
+            } else {
+                "false"
+            },
+        )
+        .await?;
+
+    // Marked complete before copying, so the copy is complete too
+    save_state(&state, &setup_state).await?;
+
+    let mut restart_required = false;
+    if let Some(paths) = paths {
+        let moved = match move_database(&state, choices, paths).await {
+            Ok(moved) => moved,
+            Err(e) => {
+                save_state(&state, &previous).await?;
+                return Err(e);
+            }
+        };
+        restart_required = setup::write_config(&paths.config_path, choices)
+            .map_err(|e| AppError::Internal(format!("{:#}", e)))?
+            || moved;
+    }
+
+    if choices.download_model {
+        let models_dir = screensearch_embeddings::get_models_dir();
+        if screensearch_embeddings::needs_download(&models_dir) {
+            tokio::spawn(async move {
+                info!(
+                    "Downloading the embedding model to {}",
+                    models_dir.display()
+                );
+                if let Err(e) = screensearch_embeddings::download_model(&models_dir).await {
+                    error!("Failed to download the embedding model: {}", e);
+                }
+            });
+        }
+    }
+
+    info!("First-run setup complete");
+    Ok(Json(status(&state, setup_state, restart_required)))
+}
+
+/// Stored setup progress, or a new one starting from the current settings
+async fn load_state(state: &AppState) -> Result<SetupState> {
+    if let Some(stored) = state.db.get_metadata(setup::STATE_KEY).await? {
+        match serde_json::from_str(&stored) {
+            Ok(setup_state) => return Ok(setup_state),
+            Err(e) => warn!("Ignoring invalid setup state: {}", e),
+        }
+    }
+
+    let settings = state.db.get_settings().await?;
+    let embeddings_enabled = state.db.get_metadata("embeddings_enabled").await?;
+    let mut setup_state = SetupState::new(SetupChoices {
+        data_dir: None,
+        retention_days: settings.retention_days,
+        monitors: serde_json::from_str(&settings.monitors).unwrap_or_default(),
+        excluded_apps: serde_json::from_str(&settings.excluded_apps).unwrap_or_default(),
+        download_model: !screensearch_embeddings::model_exists(
+            &screensearch_embeddings::get_models_dir(),
+        ),
+        enable_embeddings: embeddings_enabled.as_deref() == Some("true"),
+    });
+
+    // Installs that predate the setup flow are marked complete by a migration
+    let completed = state.db.get_metadata(setup::COMPLETED_KEY).await?;
+    if completed.as_deref() == Some("true") {
+        setup_state.step = SetupStep::Done;
+    }
+    Ok(setup_state)
+}
+
+/// Store the setup progress
+async fn save_state(state: &AppState, setup_state: &SetupState) -> Result<()> {
+    let json = serde_json::to_string(setup_state).map_err(|e| AppError::Internal(e.to_string()))?;
+    state.db.set_metadata(setup::STATE_KEY, &json).await?;
+    let completed = setup_state.step == SetupStep::Done;
+    state
+        .db
+        .set_metadata(
+            setup::COMPLETED_KEY,
+            if completed { "true" } else { "false" },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Copy the database to a newly chosen data directory
+///
+/// Returns whether it moved; the application uses the copy after a restart.
+/// An existing database there is used as it is. Frame images stay where
+/// they are and remain readable.
+async fn move_database(
+    state: &AppState,
+    choices: &SetupChoices,
+    paths: &setup::SetupPaths,
+) -> Result<bool> {
+    let Some(data_dir) = choices.data_dir.as_deref().map(std::path::Path::new) else {
+        return Ok(false);
+    };
+    if data_dir == paths.data_dir {
+        return Ok(false);
+    }
+
+    std::fs::create_dir_all(data_dir).map_err(|e| {
+        AppError::InvalidRequest(format!("Cannot create {}: {}", data_dir.display(), e))
+    })?;
+    let Some(file_name) = paths.database_path.file_name() else {
+        return Ok(false);
+    };
+    let target = data_dir.join(file_name);
+    if target.exists() {
+        info!("Using the existing database in {}", data_dir.display());
+        return Ok(true);
+    }
+
+    state.db.copy_to(&target.to_string_lossy()).await?;
+    Ok(true)
+}
+
+/// Setup progress with what the steps offer
+fn status(
+    state: &AppState,
+    setup_state: SetupState,
+    restart_required: bool,
+) -> SetupStatusResponse {
+    let available_monitors = MonitorInfo::enumerate()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|monitor| SetupMonitor {
+            index: monitor.index,
+            name: monitor.name,
+            width: monitor.width,
+            height: monitor.height,
+            is_primary: monitor.is_primary,
+        })
+        .collect();
+
+    SetupStatusResponse {
+        completed: setup_state.step == SetupStep::Done,
+        step: setup_state.step,
+        choices: setup_state.choices,
+        data_dir: state
+            .setup
+            .paths()
+            .map(|paths| paths.data_dir.to_string_lossy().to_string()),
+        available_monitors,
+        model_installed: screensearch_embeddings::model_exists(
+            &screensearch_embeddings::get_models_dir(),
+        ),
+        restart_required,
+    }
+}
@@ -0,0 +1,106 @@
+//! Metrics History Handlers
+//!
+//! Recorded OCR and capture pipeline metrics over time (see
+//! [`crate::metrics_history`]).
+
+use crate::error::{AppError, Result};
+use crate::metrics_history::{
+    downsample, metric_info, parse_range, MetricPoint, VersionChange, MAX_POINTS, METRICS,
+};
+use crate::state::AppState;
+use axum::extract::{Json, Query, State};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, error};
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Metrics history query parameters
+#[derive(Debug, Deserialize)]
+pub struct MetricsHistoryQuery {
+    /// Metric to return, e.g. `ocr_latency`
+    pub metric: String,
+
+    /// Time range ending now: `30m`, `24h`, `7d`, `4w` (default: `24h`)
+    #[serde(default)]
+    pub range: Option<String>,
+}
+
+/// Recorded values of one metric
+#[derive(Debug, Serialize)]
+pub struct MetricsHistoryResponse {
+    pub metric: String,
+    pub unit: String,
+    pub description: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Averages over equal time buckets, oldest first; buckets without
+    /// snapshots are left out
+    pub points: Vec<MetricPoint>,
+    /// App versions that recorded the values, in the order they appeared
+    pub versions: Vec<VersionChange>,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /stats/history - Recorded values of a metric over time
+///
+/// # Query Parameters
+/// - metric: Metric name (see `GET /stats/metrics`)
+/// - range: Time range ending now (default: 24h)
+pub async fn get_metrics_history(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<MetricsHistoryQuery>,
+) -> Result<Json<MetricsHistoryResponse>> {
+    let range = params.range.as_deref().unwrap_or("24h");
+    debug!(
+        "Metrics history request: metric={}, range={}",
+        params.metric, range
+    );
+
+    let Some(info) = metric_info(&params.metric) else {
+        let names: Vec<&str> = METRICS.iter().map(|metric| metric.name).collect();
+        return Err(AppError::InvalidRequest(format!(
+            "Unknown metric '{}', expected one of: {}",
+            params.metric,
+            names.join(", ")
+        )));
+    };
+
+    let end = Utc::now();
+    let Some(start) = parse_range(range).and_then(|range| end.checked_sub_signed(range)) else {
+        return Err(AppError::InvalidRequest(format!(
+            "Invalid range '{}', expected a number of minutes, hours, days or weeks such as 7d",
+            range
+        )));
+    };
+
+    let samples = match state.db.get_metric_history(info.name, start, end).await {
+        Ok(samples) => samples,
+        Err(e) => {
+            error!("Failed to get history of {}: {}", info.name, e);
+            return Err(AppError::Database(e));
+        }
+    };
+    let (points, versions) = downsample(&samples, start, end, MAX_POINTS);
+
+    Ok(Json(MetricsHistoryResponse {
+        metric: info.name.to_string(),
+        unit: info.unit.to_string(),
+        description: info.description.to_string(),
+        start,
+        end,
+        points,
+        versions,
+    }))
+}
+
+/// GET /stats/metrics - Metrics recorded in the history
+pub async fn list_metrics() -> Json<Vec<crate::metrics_history::MetricInfo>> {
+    Json(METRICS.to_vec())
+}
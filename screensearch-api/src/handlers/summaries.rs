@@ -0,0 +1,87 @@
+//! Activity Summary Handlers
+//!
+//! List the hourly activity summaries written by
+//! [`crate::workers::summarizer`].
+
+use crate::error::{AppError, Result};
+use crate::models::{Paginated, PaginationInfo};
+use crate::state::AppState;
+use axum::extract::{Json, Query, State};
+use chrono::{DateTime, Utc};
+use screensearch_db::{ActivitySummaryRecord, Pagination};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{debug, error};
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Activity summary query parameters
+#[derive(Debug, Deserialize)]
+pub struct SummaryQuery {
+    /// Only summaries of hours starting at or after this time (ISO 8601)
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// Only summaries of hours ending at or before this time (ISO 8601)
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+
+    /// Maximum results (default: 100, max: 1000)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Pagination offset
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /summaries - List hourly activity summaries, oldest first
+///
+/// Returns one page of summaries with the total number of matching summaries.
+///
+/// # Query Parameters
+/// - start / end: Optional time range (ISO 8601)
+/// - limit: Maximum results (default: 100, max: 1000)
+/// - offset: Pagination offset
+pub async fn list_summaries(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SummaryQuery>,
+) -> Result<Json<Paginated<ActivitySummaryRecord>>> {
+    let pagination = Pagination {
+        limit: params.limit.unwrap_or(100).clamp(1, 1000),
+        offset: params.offset.unwrap_or(0).max(0),
+    };
+    debug!(
+        "List summaries request: start={:?}, end={:?}, limit={}, offset={}",
+        params.start, params.end, pagination.limit, pagination.offset
+    );
+
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let summaries = match state
+        .db
+        .list_activity_summaries(params.start, params.end, pagination)
+        .await
+    {
+        Ok(summaries) => state
+            .db
+            .count_activity_summaries(params.start, params.end)
+            .await
+            .map(|total| (summaries, total)),
+        Err(e) => Err(e),
+    };
+    let (summaries, total) = summaries.map_err(|e| {
+        error!("Failed to list activity summaries: {}", e);
+        AppError::Database(e)
+    })?;
+
+    Ok(Json(Paginated {
+        pagination: PaginationInfo::counted(limit, offset, summaries.len(), total),
+        data: summaries,
+    }))
+}
@@ -0,0 +1,237 @@
+//! Sync Handlers
+//!
+//! Frames exchanged between two machines in batches (see [`crate::sync`]).
+
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use crate::sync::{image_name, origin_of, SyncBatch, SyncFrame, DEFAULT_BATCH, MAX_BATCH};
+use crate::video_archive::read_frame_image;
+use axum::extract::{Json, Query, State};
+use chrono::{DateTime, Utc};
+use screensearch_db::SyncPeerRecord;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Sync changes query parameters
+#[derive(Debug, Deserialize)]
+pub struct SyncChangesQuery {
+    /// Cursor of the previous batch (default: 0, from the first frame)
+    #[serde(default)]
+    pub since: i64,
+
+    /// Frames in the batch (default: 100, max: 1000)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Include image files, base64 encoded (default: false)
+    #[serde(default)]
+    pub images: bool,
+
+    /// Machine ID of the receiver; frames that came from it are left out
+    #[serde(default)]
+    pub peer: Option<String>,
+}
+
+/// Result of applying a batch
+#[derive(Debug, Serialize)]
+pub struct SyncApplyResponse {
+    /// Machine the batch came from
+    pub machine_id: String,
+    /// Frames stored
+    pub applied: usize,
+    /// Frames already here, or captured here
+    pub skipped: usize,
+    /// Cursor to ask the sender for the next batch with
+    pub cursor: i64,
+}
+
+/// Sync identity and received changes
+#[derive(Debug, Serialize)]
+pub struct SyncStatusResponse {
+    /// ID of this machine
+    pub machine_id: String,
+    /// Machines changes were received from
+    pub peers: Vec<SyncPeerRecord>,
+    pub checked_at: DateTime<Utc>,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /sync/changes - Frames captured or received after a cursor
+///
+/// # Query Parameters
+/// - since: Cursor of the previous batch (default: 0)
+/// - limit: Frames in the batch (default: 100, max: 1000)
+/// - images: Include image files (default: false)
+/// - peer: Machine ID of the receiver, whose own frames are left out
+pub async fn get_sync_changes(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SyncChangesQuery>,
+) -> Result<Json<SyncBatch>> {
+    let limit = params.limit.unwrap_or(DEFAULT_BATCH);
+    debug!(
+        "Sync changes request: since={}, limit={}, images={}",
+        params.since, limit, params.images
+    );
+
+    if params.since < 0 {
+        return Err(AppError::InvalidRequest(
+            "since must not be negative".to_string(),
+        ));
+    }
+    if !(1..=MAX_BATCH).contains(&limit) {
+        return Err(AppError::InvalidRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_BATCH
+        )));
+    }
+
+    let machine_id = state.db.machine_id().await?;
+    let records = state.db.get_frames_after_id(params.since, limit).await?;
+    let ids: Vec<i64> = records.iter().map(|f| f.id).collect();
+    let origins = state.db.get_frame_origins(&ids).await?;
+    let mut tags = state.db.get_tags_for_frames(&ids).await?;
+    let storage = state.capture_storage();
+
+    let mut frames = Vec::with_capacity(records.len());
+    for frame in &records {
+        let origin = origin_of(frame, &origins, &machine_id);
+        // The peer has these frames already
+        if params.peer.as_deref() == Some(origin.machine_id.as_str()) {
+            continue;
+        }
+
+        let text = state.db.get_ocr_text_for_frame(frame.id).await?;
+        let frame_tags = tags
+            .remove(&frame.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tag| tag.tag_name)
+            .collect();
+        let image = if params.images {
+            match read_frame_image(&state.db, &storage, state.ffmpeg_path(), frame).await {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    warn!("Sending frame {} without image: {:#}", frame.id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        frames.push(SyncFrame::new(
+            frame,
+            origin,
+            text,
+            frame_tags,
+            image.as_deref(),
+        ));
+    }
+
+    Ok(Json(SyncBatch {
+        machine_id,
+        cursor: records.last().map_or(params.since, |f| f.id),
+        has_more: records.len() as i64 == limit,
+        frames,
+    }))
+}
+
+/// POST /sync/apply - Store a batch from `GET /sync/changes` of another machine
+///
+/// Frames received before, or captured on this machine, are skipped, so a
+/// batch can be applied again.
+pub async fn apply_sync(
+    State(state): State<Arc<AppState>>,
+    Json(batch): Json<SyncBatch>,
+) -> Result<Json<SyncApplyResponse>> {
+    debug!(
+        "Sync apply request: {} frames from {}",
+        batch.frames.len(),
+        batch.machine_id
+    );
+
+    let machine_id = state.db.machine_id().await?;
+    if batch.machine_id.trim().is_empty() {
+        return Err(AppError::InvalidRequest(
+            "machine_id must not be empty".to_string(),
+        ));
+    }
+    if batch.machine_id == machine_id {
+        return Err(AppError::InvalidRequest(
+            "The batch comes from this machine".to_string(),
+        ));
+    }
+
+    let storage = state.capture_storage();
+    let (mut applied, mut skipped) = (0, 0);
+    for frame in batch.frames {
+        let origin = frame.origin();
+        if origin.machine_id == machine_id {
+            skipped += 1;
+            continue;
+        }
+
+        let file_path = match frame.image_data() {
+            Some(Ok(data)) => {
+                let name = image_name(&origin, &data);
+                storage
+                    .put(&name, data)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("Failed to store image: {:#}", e)))?
+            }
+            Some(Err(e)) => {
+                return Err(AppError::InvalidRequest(format!(
+                    "Invalid image of frame {} from {}: {}",
+                    origin.frame_id, origin.machine_id, e
+                )));
+            }
+            None => String::new(),
+        };
+
+        // Images are named after their origin, so applying a batch again
+        // rewrites the same file
+        match state
+            .db
+            .insert_synced_frame(frame.into_new(file_path))
+            .await?
+        {
+            Some(_) => applied += 1,
+            None => skipped += 1,
+        }
+    }
+
+    state
+        .db
+        .record_sync(&batch.machine_id, batch.cursor, applied as i64)
+        .await?;
+    info!(
+        "Applied {} frames from {} ({} skipped, cursor {})",
+        applied, batch.machine_id, skipped, batch.cursor
+    );
+
+    Ok(Json(SyncApplyResponse {
+        machine_id: batch.machine_id,
+        applied,
+        skipped,
+        cursor: batch.cursor,
+    }))
+}
+
+/// GET /sync/status - This machine's ID and the machines it received from
+pub async fn get_sync_status(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SyncStatusResponse>> {
+    Ok(Json(SyncStatusResponse {
+        machine_id: state.db.machine_id().await?,
+        peers: state.db.list_sync_peers().await?,
+        checked_at: Utc::now(),
+    }))
+}
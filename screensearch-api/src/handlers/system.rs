@@ -1,15 +1,25 @@
 //! System management endpoint handlers
 
+use crate::data_move::{self, MoveRequest};
 use crate::error::{AppError, Result};
-use crate::models::{AddTagToFrameRequest, CreateTagRequest, HealthResponse};
+use crate::handlers::search::validate_ranking;
+use crate::logs::LogFilter;
+use crate::models::{
+    AddTagToFrameRequest, CreateTagRequest, DataMoveStatus, HealthResponse, ImportImagesRequest,
+    ImportImagesResponse, InstallUpdateRequest, LogQuery, LogsResponse, MoveDataRequest, Paginated,
+    PaginationInfo,
+};
 use crate::state::AppState;
+use crate::updates::{UpdateState, UpdateStatus};
+use crate::workers::{enqueue_image_import, ensure_retention_job};
 use axum::extract::{Path, Query, State};
 use axum::Json;
 use regex::Regex;
-use screensearch_db::{NewTag, Pagination, SettingsRecord, UpdateSettings};
+use screensearch_capture::CaptureSchedule;
+use screensearch_db::{NewTag, Pagination, SearchRanking, SettingsRecord, UpdateSettings};
 use std::sync::Arc;
 use std::sync::LazyLock;
-use tracing::{debug, error};
+use tracing::{debug, error, info};
 
 // Validation constants
 const MAX_TAG_NAME_LEN: usize = 200;
@@ -44,8 +54,33 @@ pub async fn health(State(state): State<Arc<AppState>>) -> Result<Json<HealthRes
         }
     };
 
+    let memory_budget = state.capture.memory_budget();
+    let memory_budget = memory_budget.is_enabled().then(|| memory_budget.stats());
+    let disk_space = state.capture.disk_space();
+    let disk_space = disk_space.is_enabled().then(|| disk_space.stats());
+    let power = state.capture.power();
+    let power = power.is_enabled().then(|| power.stats());
+    let recovery = state.recovery.get().cloned();
+    // Capture is suspended while the frame backlog drains or the disk is full
+    let suspended = memory_budget
+        .as_ref()
+        .is_some_and(|stats| stats.capture_suspended)
+        || disk_space
+            .as_ref()
+            .is_some_and(|stats| stats.capture_suspended);
+    let corrupt = recovery
+        .as_ref()
+        .is_some_and(|report| !report.integrity_errors.is_empty());
+    let status = if corrupt {
+        "error"
+    } else if suspended {
+        "degraded"
+    } else {
+        "ok"
+    };
+
     Ok(Json(HealthResponse {
-        status: "ok".to_string(),
+        status: status.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_seconds: None, // TODO: Track server uptime
         frame_count: stats.frame_count,
@@ -53,9 +88,57 @@ pub async fn health(State(state): State<Arc<AppState>>) -> Result<Json<HealthRes
         tag_count: stats.tag_count,
         oldest_frame: stats.oldest_frame,
         newest_frame: stats.newest_frame,
+        memory_budget,
+        disk_space,
+        power,
+        frame_queue: state.capture.frame_backlog(),
+        recovery,
     }))
 }
 
+/// GET /logs - Recent log entries
+///
+/// Reads the rolling log files, in the text or JSON format, and returns the
+/// most recent entries matching the filters, oldest first.
+///
+/// # Query Parameters
+/// - level: Least severe level to include (default: info)
+/// - since: Only entries at or after this time (ISO 8601)
+/// - component: Only entries whose module path contains this text
+/// - limit: Maximum entries (default: 200, max: 5000)
+pub async fn get_logs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LogQuery>,
+) -> Result<Json<LogsResponse>> {
+    debug!("Log query: {:?}", query);
+
+    let Some(files) = state.log_files.get().cloned() else {
+        return Err(AppError::NotFound(
+            "Logging to a file is disabled (logging.log_to_file)".to_string(),
+        ));
+    };
+    let level = query.level.as_deref().unwrap_or("info");
+    let filter = LogFilter {
+        level: level.parse().map_err(|_| {
+            AppError::InvalidRequest(format!(
+                "Invalid level '{}': use error, warn, info, debug or trace",
+                level
+            ))
+        })?,
+        since: query.since,
+        component: query.component.filter(|component| !component.is_empty()),
+    };
+    let limit = query.limit.unwrap_or(200).clamp(1, 5000);
+
+    let log_dir = files.dir().display().to_string();
+    let entries = tokio::task::spawn_blocking(move || files.tail(&filter, limit))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map_err(|e| AppError::Internal(format!("Failed to read the log files: {}", e)))?;
+
+    Ok(Json(LogsResponse { entries, log_dir }))
+}
+
 /// POST /tags - Create a new tag
 ///
 /// Creates a new tag that can be applied to frames.
@@ -144,7 +227,7 @@ pub async fn create_tag(
 
 /// GET /tags - List all tags
 ///
-/// Returns all available tags with pagination.
+/// Returns one page of tags, by name, with the total number of tags.
 ///
 /// # Query Parameters
 /// - limit: Maximum tags to return (default: 100)
@@ -152,14 +235,19 @@ pub async fn create_tag(
 pub async fn list_tags(
     State(state): State<Arc<AppState>>,
     Query(pagination): Query<Pagination>,
-) -> Result<Json<Vec<crate::models::TagResponse>>> {
+) -> Result<Json<Paginated<crate::models::TagResponse>>> {
     debug!("List tags request");
 
-    match state.db.list_tags(pagination).await {
-        Ok(tags) => {
-            debug!("Retrieved {} tags", tags.len());
+    let (limit, offset) = (pagination.limit, pagination.offset);
+    let tags = match state.db.list_tags(pagination).await {
+        Ok(tags) => state.db.count_tags().await.map(|total| (tags, total)),
+        Err(e) => Err(e),
+    };
+    match tags {
+        Ok((tags, total)) => {
+            debug!("Retrieved {} of {} tags", tags.len(), total);
             // Convert TagRecord to TagResponse (tag_name -> name)
-            let response_tags = tags
+            let response_tags: Vec<_> = tags
                 .into_iter()
                 .map(|t| crate::models::TagResponse {
                     id: t.id,
@@ -168,7 +256,10 @@ pub async fn list_tags(
                     created_at: t.created_at,
                 })
                 .collect();
-            Ok(Json(response_tags))
+            Ok(Json(Paginated {
+                pagination: PaginationInfo::counted(limit, offset, response_tags.len(), total),
+                data: response_tags,
+            }))
         }
         Err(e) => {
             error!("Failed to list tags: {}", e);
@@ -476,7 +567,9 @@ pub async fn get_settings(State(state): State<Arc<AppState>>) -> Result<Json<Set
 
 /// POST /settings - Update application settings
 ///
-/// Updates application settings.
+/// Updates application settings and applies them to the running capture
+/// pipeline. A retention cleanup is queued when `retention_days` or
+/// `audit_retention_days` changes.
 ///
 /// # Request Body
 /// - capture_interval: Capture interval in seconds
@@ -484,6 +577,10 @@ pub async fn get_settings(State(state): State<Arc<AppState>>) -> Result<Json<Set
 /// - excluded_apps: JSON array of excluded application names
 /// - is_paused: Whether capture is paused (0/1)
 /// - retention_days: Number of days to retain data
+/// - audit_retention_days: Optional number of days to retain automation audit entries
+/// - capture_schedule: Optional JSON array of rules like "Mon-Fri 09:00-18:00"
+///   limiting capture to those hours (empty = always)
+/// - search_ranking: Optional JSON object of search ranking weights
 pub async fn update_settings(
     State(state): State<Arc<AppState>>,
     Json(settings): Json<UpdateSettings>,
@@ -503,9 +600,58 @@ pub async fn update_settings(
         ));
     }
 
+    if settings.audit_retention_days.is_some_and(|days| days < 1) {
+        return Err(AppError::InvalidRequest(
+            "Audit retention days must be at least 1 day".to_string(),
+        ));
+    }
+
+    if serde_json::from_str::<Vec<usize>>(&settings.monitors).is_err() {
+        return Err(AppError::InvalidRequest(
+            "Monitors must be a JSON array of monitor indices".to_string(),
+        ));
+    }
+
+    if serde_json::from_str::<Vec<String>>(&settings.excluded_apps).is_err() {
+        return Err(AppError::InvalidRequest(
+            "Excluded apps must be a JSON array of application names".to_string(),
+        ));
+    }
+
+    if let Some(schedule) = &settings.capture_schedule {
+        let rules = serde_json::from_str::<Vec<String>>(schedule).map_err(|_| {
+            AppError::InvalidRequest(
+                "Capture schedule must be a JSON array of rules like \"Mon-Fri 09:00-18:00\""
+                    .to_string(),
+            )
+        })?;
+        CaptureSchedule::parse(&rules).map_err(AppError::InvalidRequest)?;
+    }
+
+    if let Some(ranking) = &settings.search_ranking {
+        let ranking = serde_json::from_str::<SearchRanking>(ranking).map_err(|_| {
+            AppError::InvalidRequest(
+                "Search ranking must be a JSON object of ranking weights".to_string(),
+            )
+        })?;
+        validate_ranking(&ranking).map_err(AppError::InvalidRequest)?;
+    }
+
+    let previous = state.db.get_settings().await?;
+
     match state.db.update_settings(settings).await {
         Ok(updated_settings) => {
             debug!("Settings updated successfully");
+            state.apply_capture_settings(&updated_settings);
+
+            if updated_settings.retention_days != previous.retention_days
+                || updated_settings.audit_retention_days != previous.audit_retention_days
+            {
+                if let Err(e) = ensure_retention_job(&state.db).await {
+                    error!("Failed to queue retention job: {}", e);
+                }
+            }
+
             Ok(Json(updated_settings))
         }
         Err(e) => {
@@ -515,6 +661,151 @@ pub async fn update_settings(
     }
 }
 
+/// GET /admin/update - Update status
+///
+/// Returns the running and latest versions and where the updater is with
+/// downloading and verifying the latest release.
+pub async fn get_update_status(State(state): State<Arc<AppState>>) -> Json<UpdateStatus> {
+    Json(state.updates.status())
+}
+
+/// POST /admin/update - Install the latest release
+///
+/// Downloads and verifies the latest release in the background. It is
+/// installed on the next start, or right away (restarting the application)
+/// when `restart` is set.
+///
+/// # Request Body
+/// - restart: Restart once the update is verified (default: false)
+pub async fn install_update(
+    State(state): State<Arc<AppState>>,
+    payload: Option<Json<InstallUpdateRequest>>,
+) -> Result<Json<UpdateStatus>> {
+    let Json(req) = payload.unwrap_or_default();
+    debug!("Install update request (restart: {})", req.restart);
+
+    let status = state.updates.status();
+    if status.state == UpdateState::UpToDate {
+        return Err(AppError::InvalidRequest(format!(
+            "No update available; {} is the latest version",
+            status.current_version
+        )));
+    }
+
+    state.updates.request_install(req.restart);
+    Ok(Json(status))
+}
+
+/// GET /admin/move-data - Data directory move status
+///
+/// Returns the data directory in use, a move waiting for the restart and
+/// the last move into this directory.
+pub async fn get_data_move(State(state): State<Arc<AppState>>) -> Result<Json<DataMoveStatus>> {
+    Ok(Json(data_move_status(&state).await?))
+}
+
+/// POST /admin/move-data - Move the data directory
+///
+/// Checks the new directory, then restarts the application: capture stops,
+/// queued frames are saved, and the database, captures and other files are
+/// copied, their stored paths rewritten and verified before `config.toml`
+/// switches to the new directory. On failure the application keeps its
+/// old data; see the log for the outcome.
+///
+/// # Request Body
+/// - path: New data directory (absolute, new or empty)
+/// - remove_old: Delete the old directory after the move (default: false)
+pub async fn move_data(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MoveDataRequest>,
+) -> Result<Json<DataMoveStatus>> {
+    debug!("Move data request: {}", req.path);
+
+    let Some(from) = state.data_move.source() else {
+        return Err(AppError::InvalidRequest(
+            "The data directory can only be moved by the full application, outside portable mode"
+                .to_string(),
+        ));
+    };
+    let to = std::path::PathBuf::from(req.path.trim());
+    data_move::check_target(from, &to).map_err(AppError::InvalidRequest)?;
+
+    info!("Restarting to move the data directory to {}", to.display());
+    state.data_move.request_move(MoveRequest {
+        to,
+        remove_old: req.remove_old,
+    });
+    Ok(Json(data_move_status(&state).await?))
+}
+
+/// POST /admin/import-images - Import a folder of screenshots
+///
+/// Queues an `image_import` job that stores the images of the folder as
+/// frames, timestamped from their EXIF date, file name or modification
+/// time, with their text read by OCR. Images imported before are skipped.
+///
+/// # Request Body
+/// - path: Folder of the images (absolute)
+/// - recursive: Include subfolders (default: true)
+pub async fn import_images(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ImportImagesRequest>,
+) -> Result<Json<ImportImagesResponse>> {
+    debug!("Import images request: {}", req.path);
+
+    let dir = std::path::PathBuf::from(req.path.trim());
+    if !dir.is_absolute() || !dir.is_dir() {
+        return Err(AppError::InvalidRequest(format!(
+            "'{}' is not an absolute path to a folder",
+            req.path
+        )));
+    }
+
+    let search_dir = dir.clone();
+    let recursive = req.recursive;
+    let images = tokio::task::spawn_blocking(move || {
+        crate::image_import::find_images(&search_dir, recursive)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .map_err(|e| AppError::InvalidRequest(format!("Failed to read {}: {}", dir.display(), e)))?;
+    if images.is_empty() {
+        return Err(AppError::InvalidRequest(format!(
+            "No images found in {}",
+            dir.display()
+        )));
+    }
+
+    let job_id = enqueue_image_import(&state.db, &dir, req.recursive).await?;
+    info!(
+        "Queued import of {} images from {} (job {})",
+        images.len(),
+        dir.display(),
+        job_id
+    );
+    Ok(Json(ImportImagesResponse {
+        job_id,
+        path: dir.to_string_lossy().to_string(),
+        images: images.len(),
+    }))
+}
+
+async fn data_move_status(state: &AppState) -> Result<DataMoveStatus> {
+    let last_move = state
+        .db
+        .get_metadata(data_move::REPORT_KEY)
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok());
+    Ok(DataMoveStatus {
+        data_dir: state
+            .data_move
+            .source()
+            .map(|dir| dir.to_string_lossy().to_string()),
+        pending: state.data_move.pending(),
+        last_move,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
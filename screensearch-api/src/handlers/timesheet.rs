@@ -0,0 +1,116 @@
+//! Timesheet Export Handlers
+//!
+//! Time entries detected from captured frames as Toggl or Clockify CSV (see
+//! [`crate::timesheet`]).
+
+use crate::daily_notes;
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use crate::timesheet::{render_csv, time_entries, Grouping, TimesheetFormat};
+use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Days covered by default
+const DEFAULT_DAYS: i64 = 7;
+/// Days covered at most
+const MAX_DAYS: i64 = 366;
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Timesheet export query parameters
+#[derive(Debug, Deserialize)]
+pub struct TimesheetExportQuery {
+    /// CSV layout: `toggl` or `clockify` (default: `toggl`)
+    #[serde(default)]
+    pub format: Option<String>,
+
+    /// One entry per `session` or per `app` and day (default: `session`)
+    #[serde(default)]
+    pub group: Option<String>,
+
+    /// Start of the range (default: 7 days before `end`)
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// End of the range (default: now)
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /export/timesheet - Time entries as Toggl or Clockify CSV
+///
+/// # Query Parameters
+/// - format: `toggl` or `clockify` (default: toggl)
+/// - group: `session` or `app` (default: session)
+/// - start: Start of the range (default: 7 days before end)
+/// - end: End of the range (default: now)
+pub async fn export_timesheet(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<TimesheetExportQuery>,
+) -> Result<impl IntoResponse> {
+    let format_name = params.format.as_deref().unwrap_or("toggl");
+    let Some(format) = TimesheetFormat::parse(format_name) else {
+        return Err(AppError::InvalidRequest(format!(
+            "Unknown format '{}', expected toggl or clockify",
+            format_name
+        )));
+    };
+    let group_name = params.group.as_deref().unwrap_or("session");
+    let Some(grouping) = Grouping::parse(group_name) else {
+        return Err(AppError::InvalidRequest(format!(
+            "Unknown group '{}', expected session or app",
+            group_name
+        )));
+    };
+
+    let end = params.end.unwrap_or_else(Utc::now);
+    let start = params
+        .start
+        .unwrap_or_else(|| end - Duration::days(DEFAULT_DAYS));
+    debug!(
+        "Timesheet export request: format={}, group={}, {} to {}",
+        format_name, group_name, start, end
+    );
+
+    if start >= end {
+        return Err(AppError::InvalidRequest(
+            "start must be before end".to_string(),
+        ));
+    }
+    if end - start > Duration::days(MAX_DAYS) {
+        return Err(AppError::InvalidRequest(format!(
+            "The range can span {} days at most",
+            MAX_DAYS
+        )));
+    }
+
+    let sessions = daily_notes::load_sessions(&state.db, start, end).await?;
+    let frame_tags = state.db.get_frame_tags_in_range(start, end).await?;
+    let config = state.timesheet.get().cloned().unwrap_or_default();
+    let entries = time_entries(&sessions, &frame_tags, &config, grouping, &Local);
+    let csv = render_csv(&entries, format, &config, &Local);
+
+    let disposition = format!(
+        "attachment; filename=\"screensearch-{}-{}.csv\"",
+        format.name(),
+        start.with_timezone(&Local).format("%Y-%m-%d")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        csv,
+    ))
+}
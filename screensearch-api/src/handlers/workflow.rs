@@ -0,0 +1,748 @@
+//! Workflow Handlers
+//!
+//! Run an ordered list of automation steps in a single request. Steps share
+//! an element context (a `find` step can name its result for later steps),
+//! failed steps are retried, and a screenshot is attached when the workflow
+//! fails.
+
+use crate::error::{AppError, Result};
+use crate::models::ElementInfo;
+use crate::state::AppState;
+use axum::extract::{Json, State};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use screensearch_automation::{
+    KeyCode, MouseButton as ClickButton, Selector as ElementSelector, UIElement, WaitCondition,
+};
+use screensearch_capture::{MonitorInfo, ScreenCapture};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Maximum number of steps in one workflow
+const MAX_WORKFLOW_STEPS: usize = 100;
+/// Maximum retries for a single step
+const MAX_STEP_RETRIES: u32 = 5;
+/// Maximum delay between retries in milliseconds
+const MAX_RETRY_DELAY_MS: u64 = 10_000;
+/// Maximum timeout for find and wait steps in milliseconds
+const MAX_STEP_TIMEOUT_MS: u64 = 60_000;
+/// Longest side of the failure screenshot in pixels
+const FAILURE_SCREENSHOT_MAX_SIZE: u32 = 1280;
+
+// ============================================================
+// Models
+// ============================================================
+
+/// Workflow request
+#[derive(Debug, Deserialize)]
+pub struct WorkflowRequest {
+    /// Steps to execute in order
+    pub steps: Vec<WorkflowStep>,
+
+    /// Default number of retries per step (default: 0, max: 5)
+    #[serde(default)]
+    pub retries: Option<u32>,
+
+    /// Delay between retries in milliseconds (default: 500, max: 10000)
+    #[serde(default)]
+    pub retry_delay_ms: Option<u64>,
+
+    /// Attach a screenshot of the primary monitor on failure (default: true)
+    #[serde(default)]
+    pub screenshot_on_failure: Option<bool>,
+}
+
+/// A single workflow step
+#[derive(Debug, Deserialize)]
+pub struct WorkflowStep {
+    /// The action to perform
+    #[serde(flatten)]
+    pub action: WorkflowAction,
+
+    /// Retries for this step, overriding the workflow default
+    #[serde(default)]
+    pub retries: Option<u32>,
+}
+
+/// Workflow step actions
+///
+/// Steps that act on an element take either a `selector` or an `element`
+/// referencing the name given to an earlier `find` or `wait` step via `as`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum WorkflowAction {
+    /// Locate an element, optionally naming it for later steps
+    Find {
+        selector: String,
+        #[serde(default, rename = "as")]
+        alias: Option<String>,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    },
+    /// Click an element or screen coordinates
+    Click {
+        #[serde(default)]
+        selector: Option<String>,
+        #[serde(default)]
+        element: Option<String>,
+        #[serde(default)]
+        x: Option<i32>,
+        #[serde(default)]
+        y: Option<i32>,
+        #[serde(default)]
+        button: Option<String>,
+    },
+    /// Type text, focusing the target element first if one is given
+    Type {
+        text: String,
+        #[serde(default)]
+        selector: Option<String>,
+        #[serde(default)]
+        element: Option<String>,
+        #[serde(default)]
+        delay_ms: Option<u64>,
+    },
+    /// Press a key with optional modifiers
+    Press {
+        key: String,
+        #[serde(default)]
+        modifiers: Vec<String>,
+    },
+    /// Wait for an element condition, optionally naming the element
+    Wait {
+        selector: String,
+        #[serde(default)]
+        condition: Option<String>,
+        #[serde(default)]
+        text: Option<String>,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+        #[serde(default, rename = "as")]
+        alias: Option<String>,
+    },
+    /// Fail unless the element text contains the expected string
+    AssertText {
+        contains: String,
+        #[serde(default)]
+        selector: Option<String>,
+        #[serde(default)]
+        element: Option<String>,
+    },
+}
+
+impl WorkflowAction {
+    /// Action name as used in the request
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Find { .. } => "find",
+            Self::Click { .. } => "click",
+            Self::Type { .. } => "type",
+            Self::Press { .. } => "press",
+            Self::Wait { .. } => "wait",
+            Self::AssertText { .. } => "assert_text",
+        }
+    }
+}
+
+/// Result of a single step
+#[derive(Debug, Serialize)]
+pub struct WorkflowStepResult {
+    /// Step index (0-based)
+    pub index: usize,
+    /// Action name
+    pub action: String,
+    /// Whether the step succeeded
+    pub success: bool,
+    /// Number of attempts made
+    pub attempts: u32,
+    /// Time spent on the step including retries
+    pub elapsed_ms: u64,
+    /// Success message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Element the step resolved, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<ElementInfo>,
+    /// Error from the last attempt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Workflow response
+#[derive(Debug, Serialize)]
+pub struct WorkflowResponse {
+    /// Whether every step succeeded
+    pub success: bool,
+    /// Number of steps that succeeded
+    pub completed_steps: usize,
+    /// Per-step results, up to and including the failed step
+    pub steps: Vec<WorkflowStepResult>,
+    /// Total execution time in milliseconds
+    pub elapsed_ms: u64,
+    /// PNG screenshot as a data URL, captured when a step failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<String>,
+}
+
+/// Successful step outcome
+struct StepOutcome {
+    message: String,
+    element: Option<UIElement>,
+}
+
+// ============================================================
+// Validation
+// ============================================================
+
+/// Check a workflow before running it
+///
+/// Rejects unknown keys, buttons and conditions, steps without a target and
+/// element references that no earlier step defines, so that a malformed
+/// workflow fails before it touches the desktop.
+fn validate_workflow(steps: &[WorkflowStep]) -> Result<()> {
+    if steps.is_empty() {
+        return Err(AppError::InvalidRequest(
+            "Workflow must contain at least one step".to_string(),
+        ));
+    }
+    if steps.len() > MAX_WORKFLOW_STEPS {
+        return Err(AppError::InvalidRequest(format!(
+            "Workflow has {} steps, maximum is {}",
+            steps.len(),
+            MAX_WORKFLOW_STEPS
+        )));
+    }
+
+    let mut aliases = HashSet::new();
+    for (index, step) in steps.iter().enumerate() {
+        let invalid = |msg: String| AppError::InvalidRequest(format!("Step {}: {}", index, msg));
+
+        let (selector, element) = match &step.action {
+            WorkflowAction::Find {
+                selector, alias, ..
+            } => {
+                if let Some(alias) = alias {
+                    aliases.insert(alias.clone());
+                }
+                (Some(selector.as_str()), None)
+            }
+            WorkflowAction::Click {
+                selector,
+                element,
+                x,
+                y,
+                button,
+            } => {
+                if let Some(button) = button.as_deref() {
+                    parse_button(button).ok_or_else(|| {
+                        invalid(format!(
+                            "invalid button '{}', use left, right or middle",
+                            button
+                        ))
+                    })?;
+                }
+                if selector.is_none() && element.is_none() && (x.is_none() || y.is_none()) {
+                    return Err(invalid(
+                        "click requires selector, element or both x and y".to_string(),
+                    ));
+                }
+                (selector.as_deref(), element.as_deref())
+            }
+            WorkflowAction::Type {
+                selector, element, ..
+            } => (selector.as_deref(), element.as_deref()),
+            WorkflowAction::Press { key, modifiers } => {
+                parse_keys(key, modifiers).map_err(invalid)?;
+                (None, None)
+            }
+            WorkflowAction::Wait {
+                selector,
+                condition,
+                text,
+                alias,
+                ..
+            } => {
+                parse_condition(condition.as_deref(), text.as_deref()).map_err(invalid)?;
+                if let Some(alias) = alias {
+                    aliases.insert(alias.clone());
+                }
+                (Some(selector.as_str()), None)
+            }
+            WorkflowAction::AssertText {
+                selector, element, ..
+            } => {
+                if selector.is_none() && element.is_none() {
+                    return Err(invalid(
+                        "assert_text requires selector or element".to_string(),
+                    ));
+                }
+                (selector.as_deref(), element.as_deref())
+            }
+        };
+
+        if selector.is_some() && element.is_some() {
+            return Err(invalid(
+                "selector and element are mutually exclusive".to_string(),
+            ));
+        }
+        if let Some(selector) = selector {
+            ElementSelector::parse(selector).map_err(|e| invalid(e.to_string()))?;
+        }
+        if let Some(element) = element {
+            if !aliases.contains(element) {
+                return Err(invalid(format!(
+                    "element '{}' is not defined by an earlier find or wait step",
+                    element
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a mouse button name
+fn parse_button(name: &str) -> Option<ClickButton> {
+    match name {
+        "left" => Some(ClickButton::Left),
+        "right" => Some(ClickButton::Right),
+        "middle" => Some(ClickButton::Middle),
+        _ => None,
+    }
+}
+
+/// Parse a key and its modifiers
+fn parse_keys(
+    key: &str,
+    modifiers: &[String],
+) -> std::result::Result<(KeyCode, Vec<KeyCode>), String> {
+    let key = KeyCode::from_name(key).ok_or_else(|| format!("invalid key '{}'", key))?;
+    let modifiers = modifiers
+        .iter()
+        .map(|m| KeyCode::from_name(m).ok_or_else(|| format!("invalid modifier '{}'", m)))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok((key, modifiers))
+}
+
+/// Parse a wait condition (default: "exists")
+fn parse_condition(
+    condition: Option<&str>,
+    text: Option<&str>,
+) -> std::result::Result<WaitCondition, String> {
+    let name = condition.unwrap_or("exists");
+    WaitCondition::from_name(name, text).ok_or_else(|| {
+        format!(
+            "invalid condition '{}', use exists, visible, enabled or text-contains (with text)",
+            name
+        )
+    })
+}
+
+// ============================================================
+// Execution
+// ============================================================
+
+/// Resolve the element a step acts on
+///
+/// Returns `None` when the step has neither a selector nor an element
+/// reference.
+async fn resolve_target(
+    state: &AppState,
+    selector: Option<&str>,
+    element: Option<&str>,
+    elements: &HashMap<String, UIElement>,
+) -> std::result::Result<Option<UIElement>, String> {
+    if let Some(alias) = element {
+        return elements
+            .get(alias)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| format!("element '{}' was not found by an earlier step", alias));
+    }
+
+    match selector {
+        Some(selector) => state
+            .automation
+            .find_element(&ElementSelector::parse(selector).map_err(|e| e.to_string())?)
+            .await
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+/// Execute one attempt of a step
+async fn execute_step(
+    state: &AppState,
+    action: &WorkflowAction,
+    elements: &mut HashMap<String, UIElement>,
+) -> std::result::Result<StepOutcome, String> {
+    match action {
+        WorkflowAction::Find {
+            selector,
+            alias,
+            timeout_ms,
+        } => {
+            let timeout =
+                Duration::from_millis(timeout_ms.unwrap_or(5000).min(MAX_STEP_TIMEOUT_MS));
+            let element = state
+                .automation
+                .find_element_with_timeout(
+                    &ElementSelector::parse(selector).map_err(|e| e.to_string())?,
+                    timeout,
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+            if let Some(alias) = alias {
+                elements.insert(alias.clone(), element.clone());
+            }
+            Ok(StepOutcome {
+                message: format!("Found '{}'", selector),
+                element: Some(element),
+            })
+        }
+        WorkflowAction::Click {
+            selector,
+            element,
+            x,
+            y,
+            button,
+        } => {
+            let button = button
+                .as_deref()
+                .and_then(parse_button)
+                .unwrap_or(ClickButton::Left);
+            let target =
+                resolve_target(state, selector.as_deref(), element.as_deref(), elements).await?;
+
+            match target {
+                // UI Automation's own click only does a left click
+                Some(target) if button == ClickButton::Left => {
+                    target.click().map_err(|e| e.to_string())?;
+                    Ok(StepOutcome {
+                        message: "Clicked element".to_string(),
+                        element: Some(target),
+                    })
+                }
+                Some(target) => {
+                    let (left, top, width, height) = target.bounds().map_err(|e| e.to_string())?;
+                    let (cx, cy) = ((left + width / 2.0) as i32, (top + height / 2.0) as i32);
+                    state
+                        .automation
+                        .click(cx, cy, button)
+                        .map_err(|e| e.to_string())?;
+                    Ok(StepOutcome {
+                        message: format!("Clicked element at ({}, {})", cx, cy),
+                        element: Some(target),
+                    })
+                }
+                None => {
+                    let (x, y) = x.zip(*y).ok_or("click requires both x and y")?;
+                    state
+                        .automation
+                        .click(x, y, button)
+                        .map_err(|e| e.to_string())?;
+                    Ok(StepOutcome {
+                        message: format!("Clicked at ({}, {})", x, y),
+                        element: None,
+                    })
+                }
+            }
+        }
+        WorkflowAction::Type {
+            text,
+            selector,
+            element,
+            delay_ms,
+        } => {
+            let target =
+                resolve_target(state, selector.as_deref(), element.as_deref(), elements).await?;
+            if let Some(target) = &target {
+                target.focus().map_err(|e| e.to_string())?;
+            }
+            state
+                .automation
+                .type_text(text, *delay_ms)
+                .map_err(|e| e.to_string())?;
+            Ok(StepOutcome {
+                message: format!("Typed {} characters", text.chars().count()),
+                element: target,
+            })
+        }
+        WorkflowAction::Press { key, modifiers } => {
+            let (code, modifier_codes) = parse_keys(key, modifiers)?;
+            state
+                .automation
+                .press_key(code, &modifier_codes)
+                .map_err(|e| e.to_string())?;
+            Ok(StepOutcome {
+                message: format!("Pressed key: {}", key),
+                element: None,
+            })
+        }
+        WorkflowAction::Wait {
+            selector,
+            condition,
+            text,
+            timeout_ms,
+            alias,
+        } => {
+            let condition = parse_condition(condition.as_deref(), text.as_deref())?;
+            let timeout =
+                Duration::from_millis(timeout_ms.unwrap_or(5000).min(MAX_STEP_TIMEOUT_MS));
+            let element = state
+                .automation
+                .wait_for_element(
+                    &ElementSelector::parse(selector).map_err(|e| e.to_string())?,
+                    &condition,
+                    timeout,
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+            if let Some(alias) = alias {
+                elements.insert(alias.clone(), element.clone());
+            }
+            Ok(StepOutcome {
+                message: format!("Condition met for '{}'", selector),
+                element: Some(element),
+            })
+        }
+        WorkflowAction::AssertText {
+            contains,
+            selector,
+            element,
+        } => {
+            let target = resolve_target(state, selector.as_deref(), element.as_deref(), elements)
+                .await?
+                .ok_or("assert_text requires selector or element")?;
+            // Same depth as /automation/get-text
+            let text = target.text(5).map_err(|e| e.to_string())?;
+            if !text.contains(contains.as_str()) {
+                return Err(format!(
+                    "Expected text containing '{}', found '{}'",
+                    contains, text
+                ));
+            }
+            Ok(StepOutcome {
+                message: format!("Text contains '{}'", contains),
+                element: Some(target),
+            })
+        }
+    }
+}
+
+/// Capture the primary monitor as a downscaled PNG data URL
+async fn capture_failure_screenshot() -> Option<String> {
+    let monitor = match MonitorInfo::primary() {
+        Ok(monitor) => monitor,
+        Err(e) => {
+            warn!(
+                "Failed to find primary monitor for workflow screenshot: {}",
+                e
+            );
+            return None;
+        }
+    };
+
+    let image =
+        match ScreenCapture::capture_region(monitor.x, monitor.y, monitor.width, monitor.height)
+            .await
+        {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("Failed to capture workflow screenshot: {}", e);
+                return None;
+            }
+        };
+
+    let image = image::DynamicImage::ImageRgba8(image)
+        .thumbnail(FAILURE_SCREENSHOT_MAX_SIZE, FAILURE_SCREENSHOT_MAX_SIZE);
+    let mut png = Vec::new();
+    if let Err(e) = image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png) {
+        warn!("Failed to encode workflow screenshot: {}", e);
+        return None;
+    }
+
+    Some(format!("data:image/png;base64,{}", BASE64.encode(png)))
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// POST /automation/workflow - Run a multi-step workflow
+///
+/// Executes the steps in order and stops at the first step that still fails
+/// after its retries. Workflows run one at a time so their input does not
+/// interleave. A failed step is not an HTTP error: the response has
+/// `success: false`, the per-step results and, by default, a screenshot.
+///
+/// # Request Body
+/// - steps: Ordered steps, each with an `action` of find, click, type, press, wait or assert_text
+/// - retries: Optional default retries per step (default: 0, max: 5)
+/// - retry_delay_ms: Optional delay between retries (default: 500, max: 10000)
+/// - screenshot_on_failure: Optional flag to attach a screenshot on failure (default: true)
+pub async fn run_workflow(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<WorkflowRequest>,
+) -> Result<Json<WorkflowResponse>> {
+    debug!("Workflow request: {} steps", req.steps.len());
+
+    validate_workflow(&req.steps)?;
+
+    let default_retries = req.retries.unwrap_or(0).min(MAX_STEP_RETRIES);
+    let retry_delay =
+        Duration::from_millis(req.retry_delay_ms.unwrap_or(500).min(MAX_RETRY_DELAY_MS));
+
+    let _guard = state.workflow_lock.lock().await;
+    let start = Instant::now();
+    let mut elements = HashMap::new();
+    let mut results = Vec::with_capacity(req.steps.len());
+    let mut failed = false;
+
+    for (index, step) in req.steps.iter().enumerate() {
+        let retries = step
+            .retries
+            .map_or(default_retries, |r| r.min(MAX_STEP_RETRIES));
+        let step_start = Instant::now();
+        let mut attempts = 0;
+
+        let outcome = loop {
+            attempts += 1;
+            match execute_step(&state, &step.action, &mut elements).await {
+                Ok(outcome) => break Ok(outcome),
+                Err(e) if attempts <= retries => {
+                    debug!(
+                        "Workflow step {} ({}) attempt {} failed: {}",
+                        index,
+                        step.action.name(),
+                        attempts,
+                        e
+                    );
+                    tokio::time::sleep(retry_delay).await;
+                }
+                Err(e) => break Err(e),
+            }
+        };
+
+        let elapsed_ms = step_start.elapsed().as_millis() as u64;
+        match outcome {
+            Ok(outcome) => results.push(WorkflowStepResult {
+                index,
+                action: step.action.name().to_string(),
+                success: true,
+                attempts,
+                elapsed_ms,
+                message: Some(outcome.message),
+                element: outcome
+                    .element
+                    .as_ref()
+                    .and_then(|e| ElementInfo::from_ui_element(e).ok()),
+                error: None,
+            }),
+            Err(e) => {
+                error!(
+                    "Workflow step {} ({}) failed after {} attempts: {}",
+                    index,
+                    step.action.name(),
+                    attempts,
+                    e
+                );
+                results.push(WorkflowStepResult {
+                    index,
+                    action: step.action.name().to_string(),
+                    success: false,
+                    attempts,
+                    elapsed_ms,
+                    message: None,
+                    element: None,
+                    error: Some(e),
+                });
+                failed = true;
+                break;
+            }
+        }
+    }
+
+    let screenshot = if failed && req.screenshot_on_failure.unwrap_or(true) {
+        capture_failure_screenshot().await
+    } else {
+        None
+    };
+
+    let completed_steps = results.iter().filter(|r| r.success).count();
+    info!(
+        "Workflow finished: {}/{} steps completed",
+        completed_steps,
+        req.steps.len()
+    );
+
+    Ok(Json(WorkflowResponse {
+        success: !failed,
+        completed_steps,
+        steps: results,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+        screenshot,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_steps(json: &str) -> Vec<WorkflowStep> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_workflow_steps() {
+        let steps = parse_steps(
+            r#"[
+                {"action": "find", "selector": "Search", "as": "search", "timeout_ms": 2000},
+                {"action": "type", "element": "search", "text": "hello"},
+                {"action": "press", "key": "enter", "retries": 2},
+                {"action": "assert_text", "selector": "Results", "contains": "hello"}
+            ]"#,
+        );
+
+        assert_eq!(steps.len(), 4);
+        assert!(matches!(
+            &steps[0].action,
+            WorkflowAction::Find { alias: Some(a), timeout_ms: Some(2000), .. } if a == "search"
+        ));
+        assert_eq!(steps[2].retries, Some(2));
+        assert_eq!(steps[3].action.name(), "assert_text");
+        assert!(validate_workflow(&steps).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_steps() {
+        assert!(validate_workflow(&[]).is_err());
+
+        // Element referenced before it is defined
+        let steps = parse_steps(
+            r#"[
+                {"action": "click", "element": "ok"},
+                {"action": "find", "selector": "OK", "as": "ok"}
+            ]"#,
+        );
+        assert!(validate_workflow(&steps).is_err());
+
+        let steps = parse_steps(r#"[{"action": "click", "x": 10}]"#);
+        assert!(validate_workflow(&steps).is_err());
+
+        let steps = parse_steps(r#"[{"action": "press", "key": "nope"}]"#);
+        assert!(validate_workflow(&steps).is_err());
+
+        let steps =
+            parse_steps(r#"[{"action": "wait", "selector": "x", "condition": "text-contains"}]"#);
+        assert!(validate_workflow(&steps).is_err());
+
+        let steps = parse_steps(r#"[{"action": "find", "selector": "role=button >> nth=x"}]"#);
+        assert!(validate_workflow(&steps).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! iCalendar export of work sessions
+//!
+//! Joins the app sessions found by [`crate::daily_notes::sessions`] into
+//! work sessions, stretches of activity without a real break, and renders
+//! them as an RFC 5545 calendar with one event per work session, named
+//! after the app used most in it. Subscribing to the feed overlays the time
+//! log on a calendar.
+
+use crate::daily_notes::{format_duration, Session};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Break between app sessions that ends a work session
+const BREAK_MINUTES: i64 = 10;
+/// Work sessions shorter than this get no event
+const MIN_EVENT_MINUTES: i64 = 5;
+/// Apps listed in an event's description
+const DESCRIPTION_APPS: usize = 5;
+/// Longest content line in octets, without the line break
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Stretch of activity without a break
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkSession {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Time per app, longest first
+    pub apps: Vec<(String, Duration)>,
+    /// Window of the longest session of the main app
+    pub window: Option<String>,
+}
+
+impl WorkSession {
+    /// App used most
+    pub fn main_app(&self) -> &str {
+        self.apps.first().map_or("", |(app, _)| app.as_str())
+    }
+}
+
+/// Join app sessions, ordered by time, into work sessions
+///
+/// Work sessions shorter than a few minutes are left out.
+pub fn work_sessions(sessions: &[Session]) -> Vec<WorkSession> {
+    let gap = Duration::minutes(BREAK_MINUTES);
+    let mut runs: Vec<&[Session]> = Vec::new();
+    let mut start = 0;
+    for i in 1..=sessions.len() {
+        if i == sessions.len() || sessions[i].start - sessions[i - 1].end > gap {
+            runs.push(&sessions[start..i]);
+            start = i;
+        }
+    }
+
+    runs.into_iter()
+        .filter(|run| !run.is_empty())
+        .map(|run| {
+            let mut times: HashMap<&str, Duration> = HashMap::new();
+            for session in run {
+                *times.entry(&session.app).or_insert_with(Duration::zero) += session.duration();
+            }
+            let mut apps: Vec<(String, Duration)> = times
+                .into_iter()
+                .map(|(app, time)| (app.to_string(), time))
+                .collect();
+            apps.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+            let main_app = apps.first().map(|(app, _)| app.as_str());
+            let window = run
+                .iter()
+                .filter(|session| Some(session.app.as_str()) == main_app)
+                .max_by_key(|session| session.duration())
+                .and_then(|session| session.window.clone());
+
+            WorkSession {
+                start: run[0].start,
+                end: run
+                    .iter()
+                    .map(|session| session.end)
+                    .max()
+                    .unwrap_or(run[0].end),
+                apps,
+                window,
+            }
+        })
+        .filter(|session| session.end - session.start >= Duration::minutes(MIN_EVENT_MINUTES))
+        .collect()
+}
+
+/// Render work sessions as an iCalendar feed, stamped `now`
+pub fn render_calendar(sessions: &[WorkSession], now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    let mut line = |text: &str| push_line(&mut out, text);
+
+    line("BEGIN:VCALENDAR");
+    line("VERSION:2.0");
+    line("PRODID:-//ScreenSearch//Work Sessions//EN");
+    line("CALSCALE:GREGORIAN");
+    line("METHOD:PUBLISH");
+    line("X-WR-CALNAME:ScreenSearch sessions");
+
+    for session in sessions {
+        let mut description = session
+            .apps
+            .iter()
+            .take(DESCRIPTION_APPS)
+            .map(|(app, time)| format!("{} {}", app, format_duration(*time)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Some(window) = &session.window {
+            let _ = write!(description, "\n{}", window);
+        }
+
+        line("BEGIN:VEVENT");
+        line(&format!(
+            "UID:session-{}@screensearch",
+            session.start.timestamp()
+        ));
+        line(&format!("DTSTAMP:{}", format_time(now)));
+        line(&format!("DTSTART:{}", format_time(session.start)));
+        line(&format!("DTEND:{}", format_time(session.end)));
+        line(&format!("SUMMARY:{}", escape_text(session.main_app())));
+        line(&format!("DESCRIPTION:{}", escape_text(&description)));
+        line("CATEGORIES:ScreenSearch");
+        // A time log, not an appointment: do not show as busy
+        line("TRANSP:TRANSPARENT");
+        line("END:VEVENT");
+    }
+
+    line("END:VCALENDAR");
+    out
+}
+
+/// UTC date-time in the iCalendar basic format
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a TEXT value
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append a content line ending in CRLF, folded into lines of at most 75
+/// octets that continue with a space
+fn push_line(out: &mut String, text: &str) {
+    let mut length = 0;
+    for c in text.chars() {
+        if length + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // The space counts towards the continued line
+            length = 1;
+        }
+        out.push(c);
+        length += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(app: &str, start: i64, end: i64) -> Session {
+        let base = DateTime::parse_from_rfc3339("2025-12-10T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        Session {
+            app: app.to_string(),
+            window: Some(format!("{} window", app)),
+            start: base + Duration::minutes(start),
+            end: base + Duration::minutes(end),
+            frame_id: start,
+        }
+    }
+
+    #[test]
+    fn test_work_sessions() {
+        let sessions = vec![
+            session("code.exe", 0, 30),
+            session("firefox.exe", 30, 40),
+            session("code.exe", 45, 60),
+            // Lunch
+            session("slack.exe", 120, 121),
+            session("outlook.exe", 180, 200),
+        ];
+
+        let work = work_sessions(&sessions);
+        assert_eq!(work.len(), 2);
+        assert_eq!(work[0].main_app(), "code.exe");
+        assert_eq!(work[0].end - work[0].start, Duration::minutes(60));
+        assert_eq!(
+            work[0].apps,
+            vec![
+                ("code.exe".to_string(), Duration::minutes(45)),
+                ("firefox.exe".to_string(), Duration::minutes(10)),
+            ]
+        );
+        assert_eq!(work[0].window.as_deref(), Some("code.exe window"));
+        // The one-minute session got no event
+        assert_eq!(work[1].main_app(), "outlook.exe");
+        assert!(work_sessions(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_render_calendar() {
+        let mut sessions = work_sessions(&[session("code.exe", 0, 90)]);
+        sessions[0].window = Some("Fix parser; refactor, tests\\".repeat(4));
+        let now = DateTime::parse_from_rfc3339("2025-12-11T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let calendar = render_calendar(&sessions, now);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(calendar.contains("\r\nDTSTART:20251210T090000Z\r\n"));
+        assert!(calendar.contains("\r\nDTEND:20251210T103000Z\r\n"));
+        assert!(calendar.contains("\r\nDTSTAMP:20251211T080000Z\r\n"));
+        assert!(calendar.contains("\r\nSUMMARY:code.exe\r\n"));
+        assert!(
+            calendar.contains("DESCRIPTION:code.exe 1h 30m\\nFix parser\\; refactor\\, tests\\\\")
+        );
+        assert!(calendar
+            .split("\r\n")
+            .all(|line| line.len() <= MAX_LINE_OCTETS));
+
+        // Unfolding gives back the description
+        let unfolded = calendar.replace("\r\n ", "");
+        let expected = format!(
+            "DESCRIPTION:code.exe 1h 30m\\n{}\r\n",
+            "Fix parser\\; refactor\\, tests\\\\".repeat(4)
+        );
+        assert!(unfolded.contains(&expected));
+    }
+
+    #[test]
+    fn test_fold_multibyte() {
+        let mut out = String::new();
+        push_line(&mut out, &"é".repeat(80));
+        assert!(out.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert_eq!(out.replace("\r\n ", ""), format!("{}\r\n", "é".repeat(80)));
+    }
+}
@@ -0,0 +1,513 @@
+//! Import of existing screenshots
+//!
+//! Stores image files from a folder as frames, so screenshots taken before
+//! ScreenSearch (or with other tools) are searchable with the captured
+//! history. Each image is timestamped from its EXIF date, a date and time in
+//! its file name (as screenshot tools name them), or its modification time,
+//! in that order. Its text is read with the same OCR engine as captures,
+//! and the embedding worker picks the new frames up like captured ones.
+//!
+//! Imported frames have the device name [`IMPORT_DEVICE`], the file name as
+//! window title and no app, so they stay out of app sessions and time
+//! statistics. Importing a folder again skips the images already imported.
+
+use crate::capture_store::CaptureStorage;
+use anyhow::Context;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use regex::Regex;
+use screensearch_capture::OcrEngine;
+use screensearch_db::{DatabaseManager, NewFrame, NewOcrText};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::SystemTime;
+
+/// Device name of imported frames
+pub const IMPORT_DEVICE: &str = "import";
+
+/// File extensions imported, lowercase
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "gif", "webp", "tif", "tiff"];
+
+/// Longest file stem kept in a stored file name
+const MAX_STEM_CHARS: usize = 60;
+
+/// TIFF tags holding dates, in order of preference
+const TAG_DATE_TIME_ORIGINAL: u16 = 0x9003;
+const TAG_DATE_TIME_DIGITIZED: u16 = 0x9004;
+const TAG_DATE_TIME: u16 = 0x0132;
+/// TIFF tag pointing to the Exif sub-IFD
+const TAG_EXIF_IFD: u16 = 0x8769;
+
+/// Date and time in a screenshot file name, e.g. `Screenshot 2024-03-05 at
+/// 14.07.31`, `Screenshot_20240305-140731` or `Screen Shot 2019-11-02 at
+/// 9.15.02 PM`
+static FILE_NAME_TIME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)((?:19|20)\d{2})[-_.]?(\d{2})[-_.]?(\d{2})(?:[ _T-]|at)*(\d{1,2})[-_.:h]?(\d{2})[-_.:m]?(\d{2})(?:[ _]?([ap])\.?m\b)?",
+    )
+    .unwrap()
+});
+
+/// Where an image's timestamp came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampSource {
+    Exif,
+    FileName,
+    Modified,
+}
+
+/// Result of importing one image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// Stored as the frame with this ID
+    Imported(i64),
+    /// Imported before
+    Skipped,
+}
+
+/// Images in `dir`, and its subfolders when `recursive`, sorted by path
+///
+/// Hidden files and folders are left out.
+pub fn find_images(dir: &Path, recursive: bool) -> std::io::Result<Vec<PathBuf>> {
+    let mut images = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if recursive {
+                    dirs.push(path);
+                }
+            } else if file_type.is_file() && is_image(&path) {
+                images.push(path);
+            }
+        }
+    }
+    images.sort();
+    Ok(images)
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// When an image was taken
+///
+/// `data` is the file content and `modified` its modification time, the
+/// fallback. EXIF and file name times are local.
+pub fn image_timestamp(
+    path: &Path,
+    data: &[u8],
+    modified: Option<SystemTime>,
+) -> Option<(DateTime<Utc>, TimestampSource)> {
+    if let Some(time) = exif_datetime(data).and_then(local_to_utc) {
+        return Some((time, TimestampSource::Exif));
+    }
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy());
+    if let Some(time) = stem
+        .as_deref()
+        .and_then(file_name_datetime)
+        .and_then(local_to_utc)
+    {
+        return Some((time, TimestampSource::FileName));
+    }
+    modified.map(|time| (DateTime::<Utc>::from(time), TimestampSource::Modified))
+}
+
+fn local_to_utc(time: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Date and time in a file name
+fn file_name_datetime(name: &str) -> Option<NaiveDateTime> {
+    let captures = FILE_NAME_TIME.captures(name)?;
+    let number = |i: usize| captures.get(i)?.as_str().parse::<u32>().ok();
+    let mut hour = number(4)?;
+    if let Some(half) = captures.get(7) {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        let pm = half.as_str().eq_ignore_ascii_case("p");
+        hour = match (hour, pm) {
+            (12, false) => 0,
+            (12, true) => 12,
+            (hour, true) => hour + 12,
+            (hour, false) => hour,
+        };
+    }
+
+    NaiveDate::from_ymd_opt(number(1)? as i32, number(2)?, number(3)?)?.and_hms_opt(
+        hour,
+        number(5)?,
+        number(6)?,
+    )
+}
+
+/// Date and time from the EXIF data of a JPEG, PNG or TIFF file
+fn exif_datetime(data: &[u8]) -> Option<NaiveDateTime> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        return jpeg_exif(data).and_then(tiff_datetime);
+    }
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return png_exif(data).and_then(tiff_datetime);
+    }
+    tiff_datetime(data)
+}
+
+/// TIFF data in the APP1 segment of a JPEG file
+fn jpeg_exif(data: &[u8]) -> Option<&[u8]> {
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        // Start of scan: no metadata after it
+        if marker == 0xDA {
+            return None;
+        }
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let segment = data.get(pos + 4..pos + 2 + length)?;
+        if marker == 0xE1 {
+            if let Some(tiff) = segment.strip_prefix(b"Exif\0\0") {
+                return Some(tiff);
+            }
+        }
+        pos += 2 + length;
+    }
+    None
+}
+
+/// TIFF data in the eXIf chunk of a PNG file
+fn png_exif(data: &[u8]) -> Option<&[u8]> {
+    let mut pos = 8;
+    while pos + 8 <= data.len() {
+        let length = u32::from_be_bytes(data[pos..pos + 4].try_into().ok()?) as usize;
+        let kind = &data[pos + 4..pos + 8];
+        let chunk = data.get(pos + 8..(pos + 8).checked_add(length)?)?;
+        match kind {
+            b"eXIf" => return Some(chunk),
+            b"IEND" => return None,
+            _ => pos += 12 + length,
+        }
+    }
+    None
+}
+
+/// Best date in TIFF (EXIF) data
+fn tiff_datetime(tiff: &[u8]) -> Option<NaiveDateTime> {
+    let big_endian = match tiff.get(..4)? {
+        b"II*\0" => false,
+        b"MM\0*" => true,
+        _ => return None,
+    };
+    let u16_at = |pos: usize| {
+        let bytes = [*tiff.get(pos)?, *tiff.get(pos + 1)?];
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_at = |pos: usize| {
+        let bytes: [u8; 4] = tiff.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+
+    // Tag, and the value offset of each entry in an IFD
+    let entries = |ifd: usize| -> Vec<(u16, usize)> {
+        let count = u16_at(ifd).unwrap_or(0) as usize;
+        (0..count)
+            .map(|i| ifd + 2 + i * 12)
+            .filter_map(|entry| Some((u16_at(entry)?, entry + 8)))
+            .collect()
+    };
+    let text_at = |value: usize| {
+        // Date values are 20 ASCII bytes, always stored at an offset
+        let offset = u32_at(value)? as usize;
+        let text = std::str::from_utf8(tiff.get(offset..offset + 19)?).ok()?;
+        NaiveDateTime::parse_from_str(text, "%Y:%m:%d %H:%M:%S").ok()
+    };
+
+    let ifd0 = entries(u32_at(4)? as usize);
+    let exif = ifd0
+        .iter()
+        .find(|(tag, _)| *tag == TAG_EXIF_IFD)
+        .and_then(|(_, value)| u32_at(*value))
+        .map(|offset| entries(offset as usize))
+        .unwrap_or_default();
+
+    [
+        TAG_DATE_TIME_ORIGINAL,
+        TAG_DATE_TIME_DIGITIZED,
+        TAG_DATE_TIME,
+    ]
+    .into_iter()
+    .find_map(|wanted| {
+        exif.iter()
+            .chain(&ifd0)
+            .find(|(tag, _)| *tag == wanted)
+            .and_then(|(_, value)| text_at(*value))
+    })
+}
+
+/// Name an imported image is stored under
+fn stored_name(path: &Path, timestamp: DateTime<Utc>) -> String {
+    let stem: String = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_STEM_CHARS)
+        .collect();
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "png".to_string());
+    format!(
+        "import_{}_{}.{}",
+        timestamp.format("%Y%m%d_%H%M%S_%3f"),
+        stem,
+        ext
+    )
+}
+
+/// Import one image: store a copy, read its text and insert the frame
+pub async fn import_image(
+    db: &DatabaseManager,
+    storage: &CaptureStorage,
+    engine: &OcrEngine,
+    path: &Path,
+) -> anyhow::Result<ImportOutcome> {
+    let data = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let modified = tokio::fs::metadata(path)
+        .await
+        .ok()
+        .and_then(|metadata| metadata.modified().ok());
+    let (timestamp, _) = image_timestamp(path, &data, modified)
+        .with_context(|| format!("No timestamp for {}", path.display()))?;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if db
+        .find_frame_id(IMPORT_DEVICE, timestamp, &file_name)
+        .await?
+        .is_some()
+    {
+        return Ok(ImportOutcome::Skipped);
+    }
+
+    let decoded = data.clone();
+    let image = tokio::task::spawn_blocking(move || image::load_from_memory(&decoded))
+        .await?
+        .with_context(|| format!("Failed to decode {}", path.display()))?
+        .to_rgba8();
+    let ocr = engine.process_image(&image).await?;
+
+    let file_path = storage
+        .put(&stored_name(path, timestamp), data)
+        .await
+        .context("Failed to store image")?;
+    let frame_id = db
+        .insert_frame(NewFrame {
+            timestamp,
+            device_name: IMPORT_DEVICE.to_string(),
+            file_path,
+            monitor_index: 0,
+            width: image.width() as i32,
+            height: image.height() as i32,
+            offset_index: 0,
+            chunk_id: None,
+            active_window: Some(file_name),
+            active_process: None,
+            browser_url: None,
+            focused: None,
+        })
+        .await?;
+
+    for region in &ocr.regions {
+        db.insert_ocr_text(NewOcrText {
+            frame_id,
+            text: region.text.clone(),
+            text_json: Some(
+                serde_json::json!({
+                    "confidence": region.confidence,
+                    "x": region.x,
+                    "y": region.y,
+                    "width": region.width,
+                    "height": region.height,
+                })
+                .to_string(),
+            ),
+            x: region.x as i32,
+            y: region.y as i32,
+            width: region.width as i32,
+            height: region.height as i32,
+            confidence: region.confidence,
+        })
+        .await?;
+    }
+
+    Ok(ImportOutcome::Imported(frame_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian TIFF with DateTime in IFD0 and DateTimeOriginal in the
+    /// Exif sub-IFD
+    fn tiff() -> Vec<u8> {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend(8u32.to_le_bytes());
+        // IFD0 at 8: DateTime and the Exif pointer
+        tiff.extend(2u16.to_le_bytes());
+        tiff.extend(TAG_DATE_TIME.to_le_bytes());
+        tiff.extend(2u16.to_le_bytes());
+        tiff.extend(20u32.to_le_bytes());
+        tiff.extend(50u32.to_le_bytes());
+        tiff.extend(TAG_EXIF_IFD.to_le_bytes());
+        tiff.extend(4u16.to_le_bytes());
+        tiff.extend(1u32.to_le_bytes());
+        tiff.extend(70u32.to_le_bytes());
+        tiff.extend(0u32.to_le_bytes());
+        tiff.resize(50, 0);
+        tiff.extend(b"2021:06:01 08:00:00\0");
+        // Exif IFD at 70: DateTimeOriginal
+        tiff.extend(1u16.to_le_bytes());
+        tiff.extend(TAG_DATE_TIME_ORIGINAL.to_le_bytes());
+        tiff.extend(2u16.to_le_bytes());
+        tiff.extend(20u32.to_le_bytes());
+        tiff.extend(88u32.to_le_bytes());
+        tiff.extend(0u32.to_le_bytes());
+        tiff.extend(b"2021:05:31 23:59:58\0");
+        tiff
+    }
+
+    fn datetime(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_exif_datetime() {
+        let tiff = tiff();
+        assert_eq!(exif_datetime(&tiff), Some(datetime("2021-05-31 23:59:58")));
+
+        // In a JPEG APP1 segment, after another segment
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xE1];
+        jpeg.extend(((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend(b"Exif\0\0");
+        jpeg.extend(&tiff);
+        jpeg.extend([0xFF, 0xDA]);
+        assert_eq!(exif_datetime(&jpeg), Some(datetime("2021-05-31 23:59:58")));
+
+        // In a PNG eXIf chunk
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend((tiff.len() as u32).to_be_bytes());
+        png.extend(b"eXIf");
+        png.extend(&tiff);
+        png.extend(0u32.to_be_bytes());
+        assert_eq!(exif_datetime(&png), Some(datetime("2021-05-31 23:59:58")));
+
+        // Truncated data has no date
+        assert_eq!(exif_datetime(&tiff[..60]), None);
+        assert_eq!(exif_datetime(&jpeg[..20]), None);
+        assert_eq!(exif_datetime(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_file_name_datetime() {
+        let expected = Some(datetime("2024-03-05 14:07:31"));
+        assert_eq!(
+            file_name_datetime("Screenshot 2024-03-05 at 14.07.31"),
+            expected
+        );
+        assert_eq!(file_name_datetime("Screenshot_20240305-140731"), expected);
+        assert_eq!(file_name_datetime("Screenshot 2024-03-05 140731"), expected);
+        assert_eq!(file_name_datetime("2024-03-05_14-07-31"), expected);
+        assert_eq!(
+            file_name_datetime("Screen Shot 2024-03-05 at 2.07.31 PM"),
+            expected
+        );
+        assert_eq!(
+            file_name_datetime("Screen Shot 2024-03-05 at 12.07.31 AM"),
+            Some(datetime("2024-03-05 00:07:31"))
+        );
+        assert_eq!(
+            file_name_datetime("Screenshot 2024-13-05 at 14.07.31"),
+            None
+        );
+        assert_eq!(file_name_datetime("Screenshot (42)"), None);
+    }
+
+    #[test]
+    fn test_image_timestamp_fallbacks() {
+        let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        let named = Path::new("shots/Screenshot_20240305-140731.png");
+
+        let (_, source) = image_timestamp(named, &tiff(), Some(modified)).unwrap();
+        assert_eq!(source, TimestampSource::Exif);
+        let (time, source) = image_timestamp(named, b"", Some(modified)).unwrap();
+        assert_eq!(source, TimestampSource::FileName);
+        assert_eq!(
+            time.with_timezone(&Local).naive_local(),
+            datetime("2024-03-05 14:07:31")
+        );
+        let (time, source) = image_timestamp(Path::new("a.png"), b"", Some(modified)).unwrap();
+        assert_eq!(source, TimestampSource::Modified);
+        assert_eq!(time.timestamp(), 1_700_000_000);
+        assert!(image_timestamp(Path::new("a.png"), b"", None).is_none());
+    }
+
+    #[test]
+    fn test_find_images() {
+        let dir = std::env::temp_dir().join(format!("screensearch-import-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("2023")).unwrap();
+        std::fs::create_dir_all(dir.join(".thumbnails")).unwrap();
+        for file in ["a.PNG", "notes.txt", "2023/b.jpg", ".thumbnails/c.png"] {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+
+        assert_eq!(
+            find_images(&dir, true).unwrap(),
+            vec![dir.join("2023/b.jpg"), dir.join("a.PNG")]
+        );
+        assert_eq!(find_images(&dir, false).unwrap(), vec![dir.join("a.PNG")]);
+        assert_eq!(
+            stored_name(
+                Path::new("x/Screen Shot (1).PNG"),
+                DateTime::from_timestamp(0, 0).unwrap()
+            ),
+            "import_19700101_000000_000_Screen_Shot__1_.png"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -13,14 +13,21 @@
 //!
 //! ## Computer Automation
 //! - `POST /automation/find-elements` - Locate UI elements
-//! - `POST /automation/click` - Click at coordinates
+//! - `POST /automation/click` - Click at coordinates or a found element
+//! - `POST /automation/click-text` - Click visible text, falling back to OCR
+//! - `POST /automation/hover` - Glide the mouse onto a target and rest there
+//! - `POST /automation/highlight` - Frame an element on screen for debugging
 //! - `POST /automation/type` - Type text into active element
 //! - `POST /automation/scroll` - Scroll action
 //! - `POST /automation/press-key` - Press keyboard key
 //! - `POST /automation/get-text` - Extract text from UI element
+//! - `POST /automation/get-table` - Extract headers and rows of a data grid
+//! - `POST /automation/select` - Select an option of a combo box or list
 //! - `POST /automation/list-elements` - List interactive elements
+//! - `POST /automation/dump-tree` - Dump an element subtree as JSON
 //! - `POST /automation/open-app` - Launch application
 //! - `POST /automation/open-url` - Open URL in browser
+//! - `GET /automation/audit` - Audit log of automation calls
 //!
 //! ## System Management
 //! - `GET /tags` - List all tags
@@ -30,6 +37,14 @@
 //! - `POST /frames/{id}/tags` - Add tag to frame
 //! - `DELETE /frames/{id}/tags/{tag_id}` - Remove tag from frame
 //!
+//! # Cargo features
+//!
+//! - `server` (default): the server and everything it runs. Without it only
+//!   [`models`] and [`citations`] are built, the request and response types
+//!   shared with clients.
+//! - `builtin-llm`: serve reports and chat with a local model.
+//! - `graphql`: serve a GraphQL endpoint at `/api/graphql`.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -51,21 +66,97 @@
 //! }
 //! ```
 
+#[cfg(feature = "server")]
+pub mod alerts;
+#[cfg(feature = "server")]
+pub mod audit;
+#[cfg(feature = "server")]
+pub mod capture_store;
+pub mod citations;
+#[cfg(feature = "server")]
+pub mod cold_archive;
+#[cfg(feature = "server")]
+pub mod daily_notes;
+#[cfg(feature = "server")]
+pub mod data_move;
+#[cfg(feature = "server")]
+pub mod dedup;
+#[cfg(feature = "server")]
+pub mod documents;
+#[cfg(feature = "server")]
+pub mod element_handles;
+#[cfg(feature = "server")]
 pub mod embedded;
+#[cfg(feature = "server")]
 pub mod error;
+#[cfg(feature = "server")]
+pub mod events;
+#[cfg(feature = "server")]
+pub mod export;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "server")]
 pub mod handlers;
+#[cfg(feature = "server")]
+pub mod ical;
+#[cfg(feature = "server")]
+pub mod image_import;
+#[cfg(feature = "server")]
+pub mod local_llm;
+#[cfg(feature = "server")]
+pub mod logs;
+#[cfg(feature = "server")]
+pub mod metrics_history;
 pub mod models;
+#[cfg(feature = "server")]
+pub mod profiles;
+#[cfg(feature = "server")]
+pub mod purge;
+#[cfg(feature = "server")]
+pub mod redaction;
+#[cfg(feature = "server")]
+pub mod regex_search;
+#[cfg(feature = "server")]
 pub mod routes;
+#[cfg(feature = "server")]
 pub mod server;
+#[cfg(feature = "server")]
+pub mod setup;
+#[cfg(feature = "server")]
 pub mod state;
+#[cfg(feature = "server")]
+pub mod suggest;
+#[cfg(feature = "server")]
+pub mod sync;
+#[cfg(feature = "server")]
+pub mod text_locator;
+#[cfg(feature = "server")]
+pub mod time_expr;
+#[cfg(feature = "server")]
+pub mod timesheet;
+#[cfg(feature = "server")]
+pub mod tls;
+#[cfg(feature = "server")]
+pub mod transport;
+#[cfg(feature = "server")]
+pub mod updates;
+#[cfg(feature = "server")]
+pub mod video_archive;
+#[cfg(feature = "server")]
 pub mod workers;
 
+#[cfg(feature = "server")]
 pub use embedded::Assets;
+#[cfg(feature = "server")]
 pub use error::{AppError, Result};
+#[cfg(feature = "server")]
 pub use server::{ApiConfig, ApiServer};
+#[cfg(feature = "server")]
 pub use state::AppState;
+#[cfg(feature = "server")]
+pub use tls::TlsConfig;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "server"))]
 mod tests {
     use super::*;
 
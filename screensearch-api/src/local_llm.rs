@@ -0,0 +1,379 @@
+//! Built-in text generation with llama.cpp
+//!
+//! Reports and `/ai/chat` can use the provider `"builtin"` instead of an
+//! OpenAI-compatible server. The GGUF model is downloaded into the models
+//! directory on first use, loaded once, and runs in-process; generations run
+//! one at a time.
+//!
+//! The backend is only compiled with the `builtin-llm` feature. Without it,
+//! requests for the built-in provider fail with a message saying so.
+
+use crate::error::{AppError, Result};
+use crate::handlers::ai::{OpenAIMessage, OpenAIUsage};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Provider URL that selects the built-in model
+pub const BUILTIN_PROVIDER: &str = "builtin";
+
+/// Built-in model settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalLlmConfig {
+    /// Where to download the GGUF model from
+    pub model_url: String,
+    /// File name of the model in the models directory
+    pub model_file: String,
+    /// Context window in tokens, shared by the prompt and the answer
+    pub context_size: u32,
+    /// Threads used for generation (0 = llama.cpp default)
+    pub threads: u32,
+    /// Maximum tokens generated per answer, unless the request asks for fewer
+    pub max_tokens: u32,
+}
+
+impl Default for LocalLlmConfig {
+    fn default() -> Self {
+        Self {
+            model_url: "https://huggingface.co/Qwen/Qwen2.5-1.5B-Instruct-GGUF/resolve/main/qwen2.5-1.5b-instruct-q4_k_m.gguf".to_string(),
+            model_file: "qwen2.5-1.5b-instruct-q4_k_m.gguf".to_string(),
+            context_size: 8192,
+            threads: 0,
+            max_tokens: 1024,
+        }
+    }
+}
+
+/// The built-in model, shared by all requests
+#[derive(Default)]
+pub struct LocalLlmControl {
+    config: RwLock<LocalLlmConfig>,
+    #[cfg(feature = "builtin-llm")]
+    download: tokio::sync::Mutex<()>,
+    #[cfg(feature = "builtin-llm")]
+    model: std::sync::Arc<std::sync::Mutex<Option<engine::LoadedModel>>>,
+}
+
+impl LocalLlmControl {
+    /// Current settings
+    pub fn config(&self) -> LocalLlmConfig {
+        self.config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Use `config` for the next generations; a different model file is
+    /// loaded on the next request
+    pub fn set_config(&self, config: LocalLlmConfig) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    /// Name of the model reported in responses and `/ai/history`
+    pub fn model_name(&self) -> String {
+        self.config().model_file
+    }
+
+    /// Path of the model file in the models directory
+    pub fn model_path(&self) -> PathBuf {
+        screensearch_embeddings::get_models_dir().join(self.config().model_file)
+    }
+
+    /// Describe whether the built-in model can be used, for `/ai/validate`
+    pub fn availability(&self) -> std::result::Result<String, String> {
+        if !cfg!(feature = "builtin-llm") {
+            return Err(NOT_BUILT.to_string());
+        }
+
+        let path = self.model_path();
+        if path.exists() {
+            Ok(format!("Built-in model ready at {}", path.display()))
+        } else {
+            Ok(format!(
+                "Built-in model will be downloaded to {} on first use",
+                path.display()
+            ))
+        }
+    }
+
+    /// Answer the conversation in `messages` with the built-in model
+    ///
+    /// Downloads and loads the model if needed. `max_tokens` is capped by
+    /// the configured maximum.
+    #[cfg(feature = "builtin-llm")]
+    pub(crate) async fn generate(
+        &self,
+        messages: &[OpenAIMessage],
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+    ) -> Result<(String, OpenAIUsage)> {
+        let config = self.config();
+        let path = self.ensure_downloaded(&config).await?;
+
+        let messages: Vec<(String, String)> = messages
+            .iter()
+            .map(|m| (m.role.clone(), m.content.clone()))
+            .collect();
+        let params = engine::GenerateParams {
+            temperature: temperature.unwrap_or(0.7).max(0.0),
+            max_tokens: max_tokens
+                .unwrap_or(config.max_tokens)
+                .clamp(1, config.max_tokens.max(1)),
+            context_size: config.context_size,
+            threads: config.threads,
+        };
+
+        let model = std::sync::Arc::clone(&self.model);
+        tokio::task::spawn_blocking(move || {
+            let mut loaded = model.lock().unwrap_or_else(|e| e.into_inner());
+            if loaded.as_ref().map(|m| &m.path) != Some(&path) {
+                // Free the previous model before loading another one
+                *loaded = None;
+                *loaded = Some(engine::LoadedModel::load(path)?);
+            }
+            loaded
+                .as_ref()
+                .expect("model loaded above")
+                .generate(&messages, &params)
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("Built-in model task failed: {}", e)))?
+    }
+
+    /// Answer the conversation in `messages` with the built-in model
+    #[cfg(not(feature = "builtin-llm"))]
+    pub(crate) async fn generate(
+        &self,
+        _messages: &[OpenAIMessage],
+        _temperature: Option<f32>,
+        _max_tokens: Option<u32>,
+    ) -> Result<(String, OpenAIUsage)> {
+        Err(AppError::InvalidRequest(NOT_BUILT.to_string()))
+    }
+
+    /// Download the model file unless it is already in the models directory
+    #[cfg(feature = "builtin-llm")]
+    async fn ensure_downloaded(&self, config: &LocalLlmConfig) -> Result<PathBuf> {
+        use tokio::io::AsyncWriteExt;
+
+        let models_dir = screensearch_embeddings::get_models_dir();
+        let path = models_dir.join(&config.model_file);
+
+        // One download at a time; later requests find the finished file
+        let _guard = self.download.lock().await;
+        if path.exists() {
+            return Ok(path);
+        }
+
+        tracing::info!("Downloading built-in model from {}...", config.model_url);
+        let download_err =
+            |e: String| AppError::Internal(format!("Failed to download built-in model: {}", e));
+
+        tokio::fs::create_dir_all(&models_dir)
+            .await
+            .map_err(|e| download_err(e.to_string()))?;
+        let mut response = reqwest::get(&config.model_url)
+            .await
+            .map_err(|e| download_err(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(download_err(format!("HTTP {}", response.status())));
+        }
+
+        // Write to a temporary file so an interrupted download is not loaded
+        let partial = path.with_extension("gguf.part");
+        let mut file = tokio::fs::File::create(&partial)
+            .await
+            .map_err(|e| download_err(e.to_string()))?;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| download_err(e.to_string()))?
+        {
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| download_err(e.to_string()))?;
+        }
+        file.flush()
+            .await
+            .map_err(|e| download_err(e.to_string()))?;
+        drop(file);
+        tokio::fs::rename(&partial, &path)
+            .await
+            .map_err(|e| download_err(e.to_string()))?;
+
+        tracing::info!("Built-in model downloaded to {}", path.display());
+        Ok(path)
+    }
+}
+
+/// Error returned when the backend is not compiled in
+const NOT_BUILT: &str =
+    "The built-in model is not available in this build. Rebuild with `--features builtin-llm`";
+
+#[cfg(feature = "builtin-llm")]
+mod engine {
+    use super::{AppError, OpenAIUsage, Result};
+    use llama_cpp_2::context::params::LlamaContextParams;
+    use llama_cpp_2::llama_backend::LlamaBackend;
+    use llama_cpp_2::llama_batch::LlamaBatch;
+    use llama_cpp_2::model::params::LlamaModelParams;
+    use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel, Special};
+    use llama_cpp_2::sampling::LlamaSampler;
+    use std::num::NonZeroU32;
+    use std::path::PathBuf;
+
+    /// Prompt tokens decoded per batch
+    const BATCH_SIZE: usize = 512;
+
+    pub struct GenerateParams {
+        pub temperature: f32,
+        pub max_tokens: u32,
+        pub context_size: u32,
+        pub threads: u32,
+    }
+
+    /// A model loaded from a GGUF file
+    pub struct LoadedModel {
+        pub path: PathBuf,
+        // Dropped before the backend
+        model: LlamaModel,
+        backend: LlamaBackend,
+    }
+
+    fn llm_err(what: &str, e: impl std::fmt::Display) -> AppError {
+        AppError::Internal(format!("Built-in model failed to {}: {}", what, e))
+    }
+
+    impl LoadedModel {
+        pub fn load(path: PathBuf) -> Result<Self> {
+            tracing::info!("Loading built-in model from {}", path.display());
+            let mut backend = LlamaBackend::init().map_err(|e| llm_err("start", e))?;
+            backend.void_logs();
+            let model = LlamaModel::load_from_file(&backend, &path, &LlamaModelParams::default())
+                .map_err(|e| llm_err("load", e))?;
+            Ok(Self {
+                path,
+                model,
+                backend,
+            })
+        }
+
+        /// Answer `messages`, given as (role, content) pairs
+        pub fn generate(
+            &self,
+            messages: &[(String, String)],
+            params: &GenerateParams,
+        ) -> Result<(String, OpenAIUsage)> {
+            let prompt = self.render_chat(messages)?;
+            let tokens = self
+                .model
+                .str_to_token(&prompt, AddBos::Never)
+                .map_err(|e| llm_err("tokenize the prompt", e))?;
+
+            let context_size = params.context_size.max(512);
+            if tokens.len() + params.max_tokens as usize > context_size as usize {
+                return Err(AppError::InvalidRequest(format!(
+                    "Prompt of {} tokens plus {} answer tokens does not fit the built-in model's context of {} tokens",
+                    tokens.len(),
+                    params.max_tokens,
+                    context_size
+                )));
+            }
+
+            let mut context_params = LlamaContextParams::default()
+                .with_n_ctx(NonZeroU32::new(context_size))
+                .with_n_batch(BATCH_SIZE as u32);
+            if params.threads > 0 {
+                context_params = context_params
+                    .with_n_threads(params.threads as i32)
+                    .with_n_threads_batch(params.threads as i32);
+            }
+            let mut ctx = self
+                .model
+                .new_context(&self.backend, context_params)
+                .map_err(|e| llm_err("create a context", e))?;
+
+            // Decode the prompt, asking for logits of its last token only
+            let mut batch = LlamaBatch::new(BATCH_SIZE, 1);
+            let mut position = 0_i32;
+            for chunk in tokens.chunks(BATCH_SIZE) {
+                batch.clear();
+                for token in chunk {
+                    let last = position as usize == tokens.len() - 1;
+                    batch
+                        .add(*token, position, &[0], last)
+                        .map_err(|e| llm_err("batch the prompt", e))?;
+                    position += 1;
+                }
+                ctx.decode(&mut batch)
+                    .map_err(|e| llm_err("decode the prompt", e))?;
+            }
+
+            let mut sampler = if params.temperature > 0.0 {
+                LlamaSampler::chain_simple([
+                    LlamaSampler::temp(params.temperature),
+                    LlamaSampler::dist(rand_seed()),
+                ])
+            } else {
+                LlamaSampler::greedy()
+            };
+
+            let mut answer = Vec::new();
+            let mut completion_tokens = 0_u32;
+            while completion_tokens < params.max_tokens {
+                let token = sampler.sample(&ctx, batch.n_tokens() - 1);
+                sampler.accept(token);
+                if self.model.is_eog_token(token) {
+                    break;
+                }
+                let bytes = self
+                    .model
+                    .token_to_bytes(token, Special::Tokenize)
+                    .map_err(|e| llm_err("detokenize", e))?;
+                answer.extend_from_slice(&bytes);
+                completion_tokens += 1;
+
+                batch.clear();
+                batch
+                    .add(token, position, &[0], true)
+                    .map_err(|e| llm_err("batch the answer", e))?;
+                position += 1;
+                ctx.decode(&mut batch)
+                    .map_err(|e| llm_err("decode the answer", e))?;
+            }
+
+            let prompt_tokens = tokens.len() as u32;
+            Ok((
+                String::from_utf8_lossy(&answer).trim().to_string(),
+                OpenAIUsage {
+                    prompt_tokens: Some(prompt_tokens),
+                    completion_tokens: Some(completion_tokens),
+                    total_tokens: Some(prompt_tokens + completion_tokens),
+                },
+            ))
+        }
+
+        /// Format `messages` with the chat template stored in the model
+        fn render_chat(&self, messages: &[(String, String)]) -> Result<String> {
+            let template = self
+                .model
+                .chat_template(None)
+                .map_err(|e| llm_err("read its chat template", e))?;
+            let chat = messages
+                .iter()
+                .map(|(role, content)| LlamaChatMessage::new(role.clone(), content.clone()))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| llm_err("read the messages", e))?;
+            self.model
+                .apply_chat_template(&template, &chat, true)
+                .map_err(|e| llm_err("apply its chat template", e))
+        }
+    }
+
+    /// Seed for sampling, different for every generation
+    fn rand_seed() -> u32 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0)
+    }
+}
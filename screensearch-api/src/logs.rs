@@ -0,0 +1,309 @@
+//! Log file queries
+//!
+//! The application writes its log to daily rolling files named
+//! `<stem>.<YYYY-MM-DD>.log`, as text or as JSON lines. [`LogFiles`] reads
+//! them back for `/logs`, newest entries first, so problems can be looked
+//! into from the web interface.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::Level;
+
+/// Log entry read back from a log file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogEntry {
+    pub timestamp: DateTime<Utc>,
+
+    /// `ERROR`, `WARN`, `INFO`, `DEBUG` or `TRACE`
+    pub level: String,
+
+    /// Module that logged the entry, e.g. `screensearch_capture::ocr`
+    pub component: String,
+
+    /// Message followed by its fields, over several lines for multi-line
+    /// messages
+    pub message: String,
+}
+
+/// Which entries to return
+#[derive(Debug, Clone)]
+pub struct LogFilter {
+    /// Least severe level to include
+    pub level: Level,
+
+    /// Only entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only entries whose component contains this text
+    pub component: Option<String>,
+}
+
+impl LogFilter {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        entry
+            .level
+            .parse::<Level>()
+            .is_ok_and(|level| level <= self.level)
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self
+                .component
+                .as_deref()
+                .is_none_or(|component| entry.component.contains(component))
+    }
+}
+
+/// Rolling log files of the application
+#[derive(Debug, Clone)]
+pub struct LogFiles {
+    dir: PathBuf,
+    stem: String,
+}
+
+impl LogFiles {
+    /// Files rolled from the configured `log_file`, e.g. `logs/screensearch.log`
+    pub fn new(log_file: &Path) -> Self {
+        let dir = log_file
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        let stem = log_file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("screensearch")
+            .to_string();
+        Self { dir, stem }
+    }
+
+    /// Directory holding the log files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The last `limit` entries matching `filter`, oldest first
+    ///
+    /// Reads the files from the newest until enough entries are found,
+    /// skipping those from days before `filter.since`.
+    pub fn tail(&self, filter: &LogFilter, limit: usize) -> std::io::Result<Vec<LogEntry>> {
+        let since_day = filter.since.map(|since| since.date_naive());
+        let mut entries = Vec::new();
+
+        for (day, path) in self.files()?.into_iter().rev() {
+            if since_day.is_some_and(|since| day < since) {
+                break;
+            }
+
+            let bytes = std::fs::read(&path)?;
+            let mut matching: Vec<LogEntry> = parse_entries(&String::from_utf8_lossy(&bytes))
+                .into_iter()
+                .filter(|entry| filter.matches(entry))
+                .collect();
+            matching.append(&mut entries);
+            entries = matching;
+
+            if entries.len() >= limit {
+                break;
+            }
+        }
+
+        let skip = entries.len().saturating_sub(limit);
+        Ok(entries.split_off(skip))
+    }
+
+    /// Log files with their day, oldest first
+    fn files(&self) -> std::io::Result<Vec<(NaiveDate, PathBuf)>> {
+        let prefix = format!("{}.", self.stem);
+        let mut files: Vec<(NaiveDate, PathBuf)> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                let day = name
+                    .to_str()?
+                    .strip_prefix(&prefix)?
+                    .strip_suffix(".log")?
+                    .parse()
+                    .ok()?;
+                Some((day, entry.path()))
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// Parse the entries of a log file
+///
+/// Lines that do not start an entry continue the previous one's message.
+fn parse_entries(text: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        match parse_json_line(line).or_else(|| parse_text_line(line)) {
+            Some(entry) => entries.push(entry),
+            None => {
+                if let Some(last) = entries.last_mut() {
+                    last.message.push('\n');
+                    last.message.push_str(line);
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// Parse a line of the JSON format
+///
+/// `{"timestamp":"…","level":"INFO","fields":{"message":"…",…},"target":"…",…}`
+fn parse_json_line(line: &str) -> Option<LogEntry> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+
+    let fields = value["fields"].as_object();
+    let mut message = fields
+        .and_then(|fields| fields.get("message"))
+        .and_then(|message| message.as_str())
+        .unwrap_or_default()
+        .to_string();
+    for (name, field) in fields.into_iter().flatten() {
+        if name != "message" {
+            let field = field
+                .as_str()
+                .map_or_else(|| field.to_string(), str::to_string);
+            message.push_str(&format!(" {}={}", name, field));
+        }
+    }
+
+    Some(LogEntry {
+        timestamp: value["timestamp"].as_str()?.parse().ok()?,
+        level: value["level"].as_str()?.to_string(),
+        component: value["target"].as_str().unwrap_or_default().to_string(),
+        message: message.trim_start().to_string(),
+    })
+}
+
+/// Parse a line of the text format
+///
+/// `2026-10-16T09:30:12.123456Z  INFO ThreadId(03) [span{…}: ]target: 105: message`
+fn parse_text_line(line: &str) -> Option<LogEntry> {
+    let (timestamp, rest) = line.split_once(' ')?;
+    let timestamp = DateTime::parse_from_rfc3339(timestamp)
+        .ok()?
+        .with_timezone(&Utc);
+    let rest = rest.trim_start();
+    let (level, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    level.parse::<Level>().ok()?;
+
+    // The target is the module path right before the line number
+    let mut component = "";
+    let mut message = rest;
+    let mut tokens = rest.split_inclusive(' ').peekable();
+    let mut offset = 0;
+    while let Some(token) = tokens.next() {
+        offset += token.len();
+        let is_target = token
+            .strip_suffix(": ")
+            .is_some_and(|target| !target.is_empty() && target.chars().all(is_path_char));
+        let line_number = tokens
+            .peek()
+            .and_then(|next| next.strip_suffix(": "))
+            .is_some_and(|number| number.chars().all(|c| c.is_ascii_digit()));
+        if is_target && line_number {
+            component = &token[..token.len() - 2];
+            let number = tokens.next().unwrap_or_default();
+            message = &rest[offset + number.len()..];
+            break;
+        }
+    }
+
+    Some(LogEntry {
+        timestamp,
+        level: level.to_string(),
+        component: component.to_string(),
+        message: message.to_string(),
+    })
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == ':'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_text_lines() {
+        let entries = parse_entries(
+            "2026-10-16T09:30:12.123456Z  INFO ThreadId(03) screensearch::disk_monitor: 98: \
+             Low on disk space free_mb=812\n\
+             2026-10-16T09:30:13.000000Z ERROR ThreadId(07) store{frame=4}: \
+             screensearch_db::queries: 12: Failed to save: disk I/O error\n\
+             caused by: full\n",
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].level, "INFO");
+        assert_eq!(entries[0].component, "screensearch::disk_monitor");
+        assert_eq!(entries[0].message, "Low on disk space free_mb=812");
+        assert_eq!(entries[1].component, "screensearch_db::queries");
+        assert_eq!(
+            entries[1].message,
+            "Failed to save: disk I/O error\ncaused by: full"
+        );
+    }
+
+    #[test]
+    fn test_parse_json_line() {
+        let entry = parse_json_line(
+            r#"{"timestamp":"2026-10-16T09:30:12.123456Z","level":"WARN","fields":{"message":"Gave up","pending":3},"target":"screensearch","threadId":"ThreadId(1)"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(entry.level, "WARN");
+        assert_eq!(entry.component, "screensearch");
+        assert_eq!(entry.message, "Gave up pending=3");
+    }
+
+    #[test]
+    fn test_tail_filters_across_files() {
+        let dir = std::env::temp_dir().join(format!("screensearch-logs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("screensearch.2026-10-15.log"),
+            "2026-10-15T23:59:00Z ERROR ThreadId(01) screensearch: 1: old failure\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("screensearch.2026-10-16.log"),
+            "2026-10-16T08:00:00Z  INFO ThreadId(01) screensearch: 1: started\n\
+             2026-10-16T08:01:00Z  WARN ThreadId(01) screensearch_capture::ocr: 2: slow\n\
+             2026-10-16T08:02:00Z ERROR ThreadId(01) screensearch: 3: new failure\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("other.2026-10-16.log"), "ignored").unwrap();
+
+        let files = LogFiles::new(&dir.join("screensearch.log"));
+        let mut filter = LogFilter {
+            level: Level::WARN,
+            since: None,
+            component: None,
+        };
+
+        let entries = files.tail(&filter, 10).unwrap();
+        let messages: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["old failure", "slow", "new failure"]);
+
+        // The newest entries win
+        assert_eq!(files.tail(&filter, 1).unwrap()[0].message, "new failure");
+
+        filter.since = Some("2026-10-16T00:00:00Z".parse().unwrap());
+        filter.component = Some("capture".to_string());
+        let entries = files.tail(&filter, 10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message, "slow");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
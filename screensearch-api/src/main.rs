@@ -25,6 +25,9 @@ async fn main() -> anyhow::Result<()> {
     // Initialize and run server
     let server = ApiServer::new(config).await?;
 
+    // Run queued background jobs
+    server.start_job_queue();
+
     tracing::info!("Server initialized, listening on port 3131");
     tracing::info!("Press Ctrl+C to shut down");
 
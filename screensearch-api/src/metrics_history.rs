@@ -0,0 +1,330 @@
+//! History of OCR and capture pipeline metrics
+//!
+//! OCR counters and pipeline gauges only live in memory, so the app records
+//! a snapshot of them every few minutes into the `metrics_history` table,
+//! tagged with the app version. `GET /stats/history` returns one metric over
+//! a time range, averaged into at most [`MAX_POINTS`] points, to compare
+//! performance before and after an update or a settings change.
+
+use chrono::{DateTime, Duration, Utc};
+use screensearch_db::{MetricSampleRecord, NewMetricSample};
+use serde::Serialize;
+
+/// Points returned for a time range at most
+pub const MAX_POINTS: usize = 500;
+
+/// Recorded metrics with their unit and meaning
+pub const METRICS: &[MetricInfo] = &[
+    MetricInfo {
+        name: "ocr_latency",
+        unit: "ms",
+        description: "Average OCR time per frame",
+    },
+    MetricInfo {
+        name: "ocr_throughput",
+        unit: "frames/min",
+        description: "Frames processed by OCR per minute",
+    },
+    MetricInfo {
+        name: "ocr_errors",
+        unit: "frames",
+        description: "Frames whose OCR failed since the previous snapshot",
+    },
+    MetricInfo {
+        name: "ocr_empty_frames",
+        unit: "frames",
+        description: "Frames without text since the previous snapshot",
+    },
+    MetricInfo {
+        name: "capture_backlog",
+        unit: "frames",
+        description: "Frames waiting for OCR or to be stored",
+    },
+    MetricInfo {
+        name: "dropped_frames",
+        unit: "frames",
+        description: "Frames dropped over the memory budget since the previous snapshot",
+    },
+    MetricInfo {
+        name: "memory_usage_mb",
+        unit: "MB",
+        description: "Memory used by the capture pipeline, when a budget is set",
+    },
+    MetricInfo {
+        name: "disk_free_mb",
+        unit: "MB",
+        description: "Free space on the fullest data volume, when a threshold is set",
+    },
+];
+
+/// A recorded metric
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MetricInfo {
+    pub name: &'static str,
+    pub unit: &'static str,
+    pub description: &'static str,
+}
+
+/// Look up a recorded metric by name
+pub fn metric_info(name: &str) -> Option<&'static MetricInfo> {
+    METRICS.iter().find(|metric| metric.name == name)
+}
+
+/// Cumulative counters and current gauges of the pipeline at one moment
+#[derive(Debug, Clone, Default)]
+pub struct PipelineSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub frames_processed: u64,
+    pub processing_time_ms: u64,
+    pub ocr_errors: u64,
+    pub empty_frames: u64,
+    pub dropped_frames: u64,
+    pub backlog: usize,
+    pub memory_usage_mb: Option<u64>,
+    pub disk_free_mb: Option<u64>,
+}
+
+/// Metric values for the interval from `previous` to `current`
+///
+/// Counters become the change since `previous`; OCR latency is left out
+/// when no frame was processed.
+pub fn metric_samples(
+    previous: &PipelineSnapshot,
+    current: &PipelineSnapshot,
+) -> Vec<NewMetricSample> {
+    let frames = current
+        .frames_processed
+        .saturating_sub(previous.frames_processed);
+    let minutes = (current.taken_at - previous.taken_at).num_milliseconds() as f64 / 60_000.0;
+
+    let mut samples = Vec::new();
+    let mut push = |metric: &str, value: f64| {
+        samples.push(NewMetricSample {
+            metric: metric.to_string(),
+            value,
+        })
+    };
+
+    if frames > 0 {
+        let time = current
+            .processing_time_ms
+            .saturating_sub(previous.processing_time_ms);
+        push("ocr_latency", time as f64 / frames as f64);
+    }
+    if minutes > 0.0 {
+        push("ocr_throughput", frames as f64 / minutes);
+    }
+    push(
+        "ocr_errors",
+        current.ocr_errors.saturating_sub(previous.ocr_errors) as f64,
+    );
+    push(
+        "ocr_empty_frames",
+        current.empty_frames.saturating_sub(previous.empty_frames) as f64,
+    );
+    push("capture_backlog", current.backlog as f64);
+    push(
+        "dropped_frames",
+        current
+            .dropped_frames
+            .saturating_sub(previous.dropped_frames) as f64,
+    );
+    if let Some(usage) = current.memory_usage_mb {
+        push("memory_usage_mb", usage as f64);
+    }
+    if let Some(free) = current.disk_free_mb {
+        push("disk_free_mb", free as f64);
+    }
+
+    samples
+}
+
+/// Parse a time range ending now such as `30m`, `24h`, `7d` or `4w`
+pub fn parse_range(range: &str) -> Option<Duration> {
+    let range = range.trim();
+    let split = range.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = range.split_at(split);
+    let count: i64 = count.parse().ok().filter(|count| *count > 0)?;
+
+    match unit {
+        "m" => Duration::try_minutes(count),
+        "h" => Duration::try_hours(count),
+        "d" => Duration::try_days(count),
+        "w" => Duration::try_weeks(count),
+        _ => None,
+    }
+}
+
+/// Averaged value of a metric over a time bucket
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricPoint {
+    /// Start of the bucket
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Snapshots averaged into the point
+    pub samples: usize,
+}
+
+/// First snapshot recorded by an app version
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionChange {
+    pub version: String,
+    pub first_seen: DateTime<Utc>,
+}
+
+/// Average `samples`, ordered by time, into buckets of equal length from
+/// `start` to `end`, at most `max_points` of them
+///
+/// Returns the non-empty buckets and every change of app version.
+pub fn downsample(
+    samples: &[MetricSampleRecord],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    max_points: usize,
+) -> (Vec<MetricPoint>, Vec<VersionChange>) {
+    let span = (end - start).num_milliseconds().max(1);
+    let bucket_ms = (span + max_points.max(1) as i64 - 1) / max_points.max(1) as i64;
+
+    let mut points: Vec<MetricPoint> = Vec::new();
+    let mut versions: Vec<VersionChange> = Vec::new();
+    let mut bucket_start = None;
+
+    for sample in samples {
+        if versions.last().map(|v| v.version.as_str()) != Some(sample.version.as_str()) {
+            versions.push(VersionChange {
+                version: sample.version.clone(),
+                first_seen: sample.recorded_at,
+            });
+        }
+
+        let offset = (sample.recorded_at - start).num_milliseconds().max(0);
+        let bucket = offset / bucket_ms;
+        match points.last_mut() {
+            Some(point) if bucket_start == Some(bucket) => {
+                point.value += sample.value;
+                point.min = point.min.min(sample.value);
+                point.max = point.max.max(sample.value);
+                point.samples += 1;
+            }
+            _ => {
+                bucket_start = Some(bucket);
+                points.push(MetricPoint {
+                    timestamp: start + Duration::milliseconds(bucket * bucket_ms),
+                    value: sample.value,
+                    min: sample.value,
+                    max: sample.value,
+                    samples: 1,
+                });
+            }
+        }
+    }
+
+    // Sums become averages
+    for point in &mut points {
+        point.value /= point.samples as f64;
+    }
+
+    (points, versions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(minutes: i64, value: f64, version: &str, start: DateTime<Utc>) -> MetricSampleRecord {
+        MetricSampleRecord {
+            id: minutes,
+            metric: "ocr_latency".to_string(),
+            value,
+            version: version.to_string(),
+            recorded_at: start + Duration::minutes(minutes),
+        }
+    }
+
+    #[test]
+    fn test_metric_samples() {
+        let start = Utc::now();
+        let previous = PipelineSnapshot {
+            taken_at: start,
+            frames_processed: 100,
+            processing_time_ms: 10_000,
+            ocr_errors: 1,
+            ..Default::default()
+        };
+        let current = PipelineSnapshot {
+            taken_at: start + Duration::minutes(5),
+            frames_processed: 150,
+            processing_time_ms: 16_000,
+            ocr_errors: 3,
+            empty_frames: 4,
+            backlog: 7,
+            disk_free_mb: Some(2048),
+            ..Default::default()
+        };
+
+        let samples = metric_samples(&previous, &current);
+        let value = |name: &str| {
+            samples
+                .iter()
+                .find(|sample| sample.metric == name)
+                .map(|sample| sample.value)
+        };
+        assert_eq!(value("ocr_latency"), Some(120.0));
+        assert_eq!(value("ocr_throughput"), Some(10.0));
+        assert_eq!(value("ocr_errors"), Some(2.0));
+        assert_eq!(value("ocr_empty_frames"), Some(4.0));
+        assert_eq!(value("capture_backlog"), Some(7.0));
+        assert_eq!(value("disk_free_mb"), Some(2048.0));
+        assert_eq!(value("memory_usage_mb"), None);
+        assert!(samples.iter().all(|s| metric_info(&s.metric).is_some()));
+
+        // No frames, no latency
+        let idle = metric_samples(&current, &current);
+        assert!(idle.iter().all(|sample| sample.metric != "ocr_latency"));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_range("24h"), Some(Duration::hours(24)));
+        assert_eq!(parse_range("7d"), Some(Duration::days(7)));
+        assert_eq!(parse_range("2w"), Some(Duration::weeks(2)));
+        assert_eq!(parse_range("0d"), None);
+        assert_eq!(parse_range("d"), None);
+        assert_eq!(parse_range("7"), None);
+        assert_eq!(parse_range("7y"), None);
+    }
+
+    #[test]
+    fn test_downsample() {
+        let start = Utc::now();
+        let end = start + Duration::minutes(60);
+        let samples = vec![
+            sample(0, 100.0, "0.1.0", start),
+            sample(5, 200.0, "0.1.0", start),
+            sample(35, 50.0, "0.2.0", start),
+            sample(40, 70.0, "0.2.0", start),
+        ];
+
+        let (points, versions) = downsample(&samples, start, end, 2);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].timestamp, start);
+        assert_eq!(
+            (points[0].value, points[0].min, points[0].max),
+            (150.0, 100.0, 200.0)
+        );
+        assert_eq!(points[1].timestamp, start + Duration::minutes(30));
+        assert_eq!((points[1].value, points[1].samples), (60.0, 2));
+
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[1].version, "0.2.0");
+        assert_eq!(versions[1].first_seen, start + Duration::minutes(35));
+
+        // Empty buckets are left out
+        let (points, _) = downsample(&samples, start, end, 12);
+        assert_eq!(points.len(), 4);
+        assert!(downsample(&[], start, end, 10).0.is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 //! API request/response models
 
+use crate::citations::ReportSource;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -8,11 +9,15 @@ use serde::{Deserialize, Serialize};
 // ============================================================
 
 /// Search query parameters for full-text search
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SearchQuery {
-    /// Search query string for FTS5
+    /// Search query string for FTS5, or a regular expression in regex mode
     pub q: String,
 
+    /// How `q` is matched (default: fts)
+    #[serde(default)]
+    pub mode: SearchMode,
+
     /// Optional start time filter (ISO 8601 format)
     #[serde(default)]
     pub start_time: Option<DateTime<Utc>>,
@@ -25,13 +30,168 @@ pub struct SearchQuery {
     #[serde(default)]
     pub app: Option<String>,
 
+    /// Comma-separated applications to leave out, e.g. "slack.exe,teams.exe"
+    #[serde(default)]
+    pub exclude_apps: Option<String>,
+
+    /// Comma-separated tag names to leave out, e.g. "personal"
+    #[serde(default)]
+    pub exclude_tags: Option<String>,
+
+    /// Natural-language time range, e.g. "yesterday afternoon" or
+    /// "last tuesday morning"; explicit start/end times take precedence
+    #[serde(default)]
+    pub when: Option<String>,
+
+    /// UTC offset `when` is resolved in, e.g. "+02:00" (default: server time zone)
+    #[serde(default)]
+    pub tz: Option<String>,
+
     /// Maximum results to return (default: 100)
     #[serde(default)]
     pub limit: Option<i64>,
+
+    /// Number of results to skip (default: 0)
+    #[serde(default)]
+    pub offset: Option<i64>,
+
+    /// Comma-separated facets to count over all matches
+    /// (`app`, `day`, `tag`, `monitor` or `all`)
+    #[serde(default)]
+    pub facets: Option<String>,
+
+    /// Also match words with typos or OCR errors (default: false)
+    #[serde(default)]
+    pub fuzzy: bool,
+
+    /// Collapse near-identical results of the same app captured close
+    /// together into one (default: false)
+    #[serde(default)]
+    pub dedup: bool,
+
+    /// Maximum seconds between collapsed frames (default: 60)
+    #[serde(default)]
+    pub dedup_window: Option<i64>,
+
+    /// Weight of the BM25 relevance of the OCR text (default: from settings)
+    #[serde(default)]
+    pub text_weight: Option<f64>,
+
+    /// Weight of query words found in the window title (default: from settings)
+    #[serde(default)]
+    pub title_weight: Option<f64>,
+
+    /// Extra weight of recent frames (default: from settings)
+    #[serde(default)]
+    pub recency_weight: Option<f64>,
+
+    /// Age in hours at which the recency boost halves (default: from settings)
+    #[serde(default)]
+    pub recency_half_life_hours: Option<f64>,
 }
 
-/// Keyword search parameters
+/// How a search query is matched against OCR text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// FTS5 full-text search with BM25 ranking
+    #[default]
+    Fts,
+    /// Regular expression scanned over OCR text, newest first
+    Regex,
+}
+
+/// Full-text search response: one page of results, with facets when
+/// requested
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResponse {
+    /// Results of this page, one per frame
+    pub data: Vec<screensearch_db::SearchResult>,
+
+    /// Pagination metadata
+    pub pagination: PaginationInfo,
+
+    /// Counts over all matches, when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facets: Option<screensearch_db::SearchFacets>,
+}
+
+/// Search mode for batch search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchSearchMode {
+    /// FTS5 full-text search
+    #[default]
+    Fts,
+    /// Vector similarity over stored embeddings
+    Semantic,
+    /// FTS5 and vector similarity combined
+    Hybrid,
+}
+
+/// Batch search request: several queries sharing one set of filters
 #[derive(Debug, Deserialize)]
+pub struct BatchSearchRequest {
+    /// Queries to resolve (1-20)
+    pub queries: Vec<String>,
+
+    /// Search mode (default: fts)
+    #[serde(default)]
+    pub mode: BatchSearchMode,
+
+    /// Optional start time filter (ISO 8601 format)
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// Optional end time filter (ISO 8601 format)
+    #[serde(default)]
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// Optional application name filter
+    #[serde(default)]
+    pub app: Option<String>,
+
+    /// Natural-language time range, e.g. "yesterday afternoon" or
+    /// "last tuesday morning"; explicit start/end times take precedence
+    #[serde(default)]
+    pub when: Option<String>,
+
+    /// UTC offset `when` is resolved in, e.g. "+02:00" (default: server time zone)
+    #[serde(default)]
+    pub tz: Option<String>,
+
+    /// Maximum results per query (default: 20, max: 100)
+    #[serde(default)]
+    pub limit: Option<i64>,
+
+    /// Weight of the semantic score in hybrid mode (default: 0.5, range: 0.0-1.0)
+    #[serde(default)]
+    pub semantic_weight: Option<f32>,
+}
+
+/// Results of one query in a batch
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum BatchQueryResults {
+    Fts(Vec<screensearch_db::SearchResult>),
+    Semantic(Vec<screensearch_db::SemanticResult>),
+}
+
+/// Results of one query in a batch, in request order
+#[derive(Debug, Serialize)]
+pub struct BatchQueryResponse {
+    pub query: String,
+    pub results: BatchQueryResults,
+}
+
+/// Batch search response
+#[derive(Debug, Serialize)]
+pub struct BatchSearchResponse {
+    pub results: Vec<BatchQueryResponse>,
+}
+
+/// Keyword search parameters
+#[derive(Debug, Serialize, Deserialize)]
 pub struct KeywordSearchQuery {
     /// Comma-separated keywords to search for
     pub keywords: String,
@@ -41,8 +201,30 @@ pub struct KeywordSearchQuery {
     pub limit: Option<i64>,
 }
 
-/// Frame query parameters
+/// Search suggestion query parameters
+#[derive(Debug, Deserialize)]
+pub struct SuggestQuery {
+    /// Text typed so far
+    pub prefix: String,
+
+    /// Maximum suggestions to return (default: 10, max: 50)
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// Time expression query parameters
 #[derive(Debug, Deserialize)]
+pub struct TimeExpressionQuery {
+    /// Natural-language time range, e.g. "yesterday afternoon"
+    pub when: String,
+
+    /// UTC offset to resolve in, e.g. "+02:00" (default: server time zone)
+    #[serde(default)]
+    pub tz: Option<String>,
+}
+
+/// Frame query parameters
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct FrameQuery {
     /// Optional start time filter (ISO 8601 format)
     #[serde(default)]
@@ -56,6 +238,23 @@ pub struct FrameQuery {
     #[serde(default)]
     pub monitor_index: Option<i32>,
 
+    /// Comma-separated applications to leave out, e.g. "slack.exe,teams.exe"
+    #[serde(default)]
+    pub exclude_apps: Option<String>,
+
+    /// Comma-separated tag names to leave out, e.g. "personal"
+    #[serde(default)]
+    pub exclude_tags: Option<String>,
+
+    /// Natural-language time range, e.g. "yesterday afternoon" or
+    /// "last tuesday morning"; explicit start/end times take precedence
+    #[serde(default)]
+    pub when: Option<String>,
+
+    /// UTC offset `when` is resolved in, e.g. "+02:00" (default: server time zone)
+    #[serde(default)]
+    pub tz: Option<String>,
+
     /// Maximum results to return (default: 100)
     #[serde(default)]
     pub limit: Option<i64>,
@@ -70,7 +269,7 @@ pub struct FrameQuery {
 }
 
 /// Pagination information
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PaginationInfo {
     /// Maximum results per page
     pub limit: i64,
@@ -78,8 +277,46 @@ pub struct PaginationInfo {
     /// Number of results to skip
     pub offset: i64,
 
-    /// Total number of results available
-    pub total: i64,
+    /// Total number of results available, unless they cannot be counted
+    /// without finding them all (regex search, collapsed duplicates)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+
+    /// Whether more results follow this page
+    #[serde(default)]
+    pub has_more: bool,
+}
+
+impl PaginationInfo {
+    /// Metadata of a page of `returned` results out of `total`
+    pub fn counted(limit: i64, offset: i64, returned: usize, total: i64) -> Self {
+        Self {
+            limit,
+            offset,
+            total: Some(total),
+            has_more: offset.saturating_add(returned as i64) < total,
+        }
+    }
+
+    /// Metadata of a page whose total is unknown
+    pub fn uncounted(limit: i64, offset: i64, has_more: bool) -> Self {
+        Self {
+            limit,
+            offset,
+            total: None,
+            has_more,
+        }
+    }
+}
+
+/// One page of a list endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Paginated<T> {
+    /// Items of this page
+    pub data: Vec<T>,
+
+    /// Pagination metadata
+    pub pagination: PaginationInfo,
 }
 
 /// Frame response with enriched data (matches frontend expectations)
@@ -90,6 +327,9 @@ pub struct FrameResponse {
     pub file_path: String,
     pub app_name: String,
     pub window_name: String,
+    /// Exempt from retention, compaction, archiving and purges
+    #[serde(default)]
+    pub pinned: bool,
     pub ocr_text: String,
     pub tags: Vec<TagResponse>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -107,13 +347,31 @@ pub struct TagResponse {
 }
 
 /// Paginated frames response
-#[derive(Debug, Serialize)]
-pub struct PaginatedFramesResponse {
-    /// Frame data
-    pub data: Vec<FrameResponse>,
+pub type PaginatedFramesResponse = Paginated<FrameResponse>;
 
-    /// Pagination metadata
-    pub pagination: PaginationInfo,
+/// Frame context query parameters
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FrameContextQuery {
+    /// Number of frames to return before the frame (default: 5)
+    #[serde(default)]
+    pub before: Option<i64>,
+
+    /// Number of frames to return after the frame (default: 5)
+    #[serde(default)]
+    pub after: Option<i64>,
+}
+
+/// Frames captured around a given frame on the same monitor
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FrameContextResponse {
+    /// The requested frame
+    pub frame: FrameResponse,
+
+    /// Frames captured immediately before, oldest first
+    pub before: Vec<FrameResponse>,
+
+    /// Frames captured immediately after, oldest first
+    pub after: Vec<FrameResponse>,
 }
 
 // ============================================================
@@ -121,7 +379,7 @@ pub struct PaginatedFramesResponse {
 // ============================================================
 
 /// Find elements request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct FindElementsRequest {
     /// Element selector string
     pub selector: String,
@@ -132,7 +390,7 @@ pub struct FindElementsRequest {
 }
 
 /// UI element information
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElementInfo {
     /// Element name
     pub name: String,
@@ -140,10 +398,10 @@ pub struct ElementInfo {
     /// Element control type (e.g., "Button", "Edit", "Text")
     pub control_type: String,
 
-    /// X coordinate on screen
+    /// X coordinate on screen (physical pixels)
     pub x: i32,
 
-    /// Y coordinate on screen
+    /// Y coordinate on screen (physical pixels)
     pub y: i32,
 
     /// Element width
@@ -159,8 +417,17 @@ pub struct ElementInfo {
     /// Whether element is visible
     #[serde(default)]
     pub is_visible: bool,
+
+    /// Handle for acting on this exact element in later requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_handle: Option<String>,
+
+    /// Scale factor of the element's monitor (1.0 = 100%)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale_factor: Option<f64>,
 }
 
+#[cfg(feature = "server")]
 impl ElementInfo {
     /// Convert UIElement from screensearch-automation to ElementInfo DTO
     pub fn from_ui_element(
@@ -179,26 +446,86 @@ impl ElementInfo {
             height: bounds.3 as i32,
             is_enabled: element.is_enabled().unwrap_or(false),
             is_visible: element.is_visible().unwrap_or(false),
+            element_handle: None,
+            scale_factor: element.scale_factor().ok(),
         })
     }
 }
 
 /// Click request
-#[derive(Debug, Deserialize)]
+///
+/// Either `element_handle` or both coordinates must be given.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ClickRequest {
     /// X coordinate to click
-    pub x: i32,
+    #[serde(default)]
+    pub x: Option<i32>,
 
     /// Y coordinate to click
-    pub y: i32,
+    #[serde(default)]
+    pub y: Option<i32>,
+
+    /// Coordinates are logical (96 DPI) pixels of their monitor instead of physical ones
+    #[serde(default)]
+    pub logical: bool,
+
+    /// Handle returned by a prior find; the element's center is clicked
+    #[serde(default)]
+    pub element_handle: Option<String>,
+
+    /// Button to click ("left", "right", "middle")
+    #[serde(default)]
+    pub button: Option<String>,
+}
+
+/// Click-on-text request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClickTextRequest {
+    /// Visible text to click, e.g. "Accept all"
+    pub text: String,
+
+    /// Occurrence to click when the text appears more than once (0-based, default: 0)
+    #[serde(default)]
+    pub index: Option<usize>,
+
+    /// Only look on this monitor
+    #[serde(default)]
+    pub monitor: Option<usize>,
+
+    /// Skip UIAutomation and locate the text with OCR only
+    #[serde(default)]
+    pub ocr_only: bool,
 
     /// Button to click ("left", "right", "middle")
     #[serde(default)]
     pub button: Option<String>,
 }
 
+/// Click-on-text response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClickTextResponse {
+    /// Whether the click was performed
+    pub success: bool,
+
+    /// Strategy that located the text: `uiautomation` or `ocr`
+    pub method: String,
+
+    /// Text that was clicked
+    pub text: String,
+
+    /// X coordinate of the click
+    pub x: i32,
+
+    /// Y coordinate of the click
+    pub y: i32,
+
+    /// Number of on-screen occurrences found by OCR
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<usize>,
+}
+
 /// Type text request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TypeRequest {
     /// Text to type
     pub text: String,
@@ -209,7 +536,7 @@ pub struct TypeRequest {
 }
 
 /// Scroll request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ScrollRequest {
     /// Scroll direction ("up", "down", "left", "right")
     pub direction: String,
@@ -219,7 +546,7 @@ pub struct ScrollRequest {
 }
 
 /// Key press request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct KeyPressRequest {
     /// Key to press (e.g., "enter", "escape", "a")
     pub key: String,
@@ -230,43 +557,366 @@ pub struct KeyPressRequest {
 }
 
 /// Get text request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GetTextRequest {
     /// Element selector to get text from
     pub selector: String,
 }
 
 /// Get text response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GetTextResponse {
     /// Extracted text content
     pub text: String,
 }
 
-/// List elements request
+/// Highlight request
+///
+/// The target is an element handle, an element selector or a rectangle, in
+/// that order of precedence.
+#[derive(Debug, Deserialize)]
+pub struct HighlightRequest {
+    /// Element selector
+    #[serde(default)]
+    pub selector: Option<String>,
+
+    /// Handle returned by a prior find
+    #[serde(default)]
+    pub element_handle: Option<String>,
+
+    /// Rectangle X coordinate
+    #[serde(default)]
+    pub x: Option<i32>,
+
+    /// Rectangle Y coordinate
+    #[serde(default)]
+    pub y: Option<i32>,
+
+    /// Rectangle width
+    #[serde(default)]
+    pub width: Option<i32>,
+
+    /// Rectangle height
+    #[serde(default)]
+    pub height: Option<i32>,
+
+    /// Frame color as `#RRGGBB` (default: red)
+    #[serde(default)]
+    pub color: Option<String>,
+
+    /// How long the frame stays on screen in milliseconds (default: 2000)
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+/// Option selection request
+///
+/// The combo box or list is given by an element handle or a selector, the
+/// option by `value` or `index`.
+#[derive(Debug, Deserialize)]
+pub struct SelectRequest {
+    /// Combo box or list selector
+    #[serde(default)]
+    pub selector: Option<String>,
+
+    /// Handle returned by a prior find
+    #[serde(default)]
+    pub element_handle: Option<String>,
+
+    /// Option text (exact match preferred, else partial; case-insensitive)
+    #[serde(default)]
+    pub value: Option<String>,
+
+    /// 0-based option position
+    #[serde(default)]
+    pub index: Option<usize>,
+}
+
+/// Table extraction request
+///
+/// The table is given by an element handle or a selector.
 #[derive(Debug, Deserialize)]
+pub struct GetTableRequest {
+    /// Grid or table element selector
+    #[serde(default)]
+    pub selector: Option<String>,
+
+    /// Handle returned by a prior find
+    #[serde(default)]
+    pub element_handle: Option<String>,
+
+    /// Maximum rows to extract (default: 500, max: 10000)
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+}
+
+/// List elements request
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ListElementsRequest {
     /// Optional root element selector
     #[serde(default)]
     pub root_selector: Option<String>,
 }
 
-/// Open application request
+/// Accessibility tree dump request
 #[derive(Debug, Deserialize)]
+pub struct DumpTreeRequest {
+    /// Optional root element selector (defaults to the active window)
+    #[serde(default)]
+    pub root_selector: Option<String>,
+
+    /// Child levels to include (default: 5, max: 20)
+    #[serde(default)]
+    pub depth: Option<usize>,
+}
+
+/// Open application request
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OpenAppRequest {
     /// Application name or path to open
     pub app_name: String,
 }
 
 /// Open URL request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OpenUrlRequest {
     /// URL to open in default browser
     pub url: String,
 }
 
-/// Generic automation response
+/// Drag request
+///
+/// Each end of the drag is given either as coordinates or as an element
+/// selector, in which case the center of the element is used.
+#[derive(Debug, Deserialize)]
+pub struct DragRequest {
+    /// Source X coordinate
+    #[serde(default)]
+    pub from_x: Option<i32>,
+
+    /// Source Y coordinate
+    #[serde(default)]
+    pub from_y: Option<i32>,
+
+    /// Source element selector (overrides coordinates)
+    #[serde(default)]
+    pub from_selector: Option<String>,
+
+    /// Target X coordinate
+    #[serde(default)]
+    pub to_x: Option<i32>,
+
+    /// Target Y coordinate
+    #[serde(default)]
+    pub to_y: Option<i32>,
+
+    /// Target element selector (overrides coordinates)
+    #[serde(default)]
+    pub to_selector: Option<String>,
+
+    /// Button to hold ("left", "right", "middle", default: "left")
+    #[serde(default)]
+    pub button: Option<String>,
+
+    /// Number of intermediate mouse moves (default: 20)
+    #[serde(default)]
+    pub steps: Option<u32>,
+
+    /// Total duration of the movement in milliseconds (default: 300)
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+/// Hover request
+///
+/// The target is an element handle, an element selector (the element's
+/// center is used) or coordinates, in that order of precedence.
+#[derive(Debug, Deserialize)]
+pub struct HoverRequest {
+    /// X coordinate
+    #[serde(default)]
+    pub x: Option<i32>,
+
+    /// Y coordinate
+    #[serde(default)]
+    pub y: Option<i32>,
+
+    /// Coordinates are logical (96 DPI) pixels of their monitor instead of physical ones
+    #[serde(default)]
+    pub logical: bool,
+
+    /// Element selector
+    #[serde(default)]
+    pub selector: Option<String>,
+
+    /// Handle returned by a prior find
+    #[serde(default)]
+    pub element_handle: Option<String>,
+
+    /// Duration of the movement in milliseconds (default: 200)
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+
+    /// Time to rest on the target before responding in milliseconds (default: 500)
+    #[serde(default)]
+    pub hover_ms: Option<u64>,
+}
+
+/// Wait-for-element request
+#[derive(Debug, Deserialize)]
+pub struct WaitRequest {
+    /// Element selector string
+    pub selector: String,
+
+    /// Condition to wait for ("exists", "visible", "enabled", "text-contains", default: "exists")
+    #[serde(default)]
+    pub condition: Option<String>,
+
+    /// Text to look for with the "text-contains" condition
+    #[serde(default)]
+    pub text: Option<String>,
+
+    /// Timeout in milliseconds (default: 5000)
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Wait-for-element response
+#[derive(Debug, Serialize)]
+pub struct WaitResponse {
+    /// Whether the condition was met before the timeout
+    pub success: bool,
+
+    /// Time spent waiting in milliseconds
+    pub elapsed_ms: u64,
+
+    /// The matching element, if the condition was met
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<ElementInfo>,
+}
+
+/// Element screenshot request
+#[derive(Debug, Deserialize)]
+pub struct ScreenshotElementRequest {
+    /// Element selector string
+    pub selector: String,
+
+    /// Extra pixels to include around the element (default: 0)
+    #[serde(default)]
+    pub padding: Option<u32>,
+}
+
+/// Clipboard set request
+#[derive(Debug, Deserialize)]
+pub struct ClipboardSetRequest {
+    /// Text to place on the clipboard
+    pub text: String,
+
+    /// Paste into the focused element with Ctrl+V after setting (default: false)
+    #[serde(default)]
+    pub paste: bool,
+}
+
+/// Clipboard response
+#[derive(Debug, Serialize)]
+pub struct ClipboardResponse {
+    /// Clipboard text, or null if the clipboard holds no text
+    pub text: Option<String>,
+}
+
+/// Window list query parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct WindowQuery {
+    /// Filter by title (case-insensitive substring)
+    #[serde(default)]
+    pub title: Option<String>,
+
+    /// Filter by process name (case-insensitive substring)
+    #[serde(default)]
+    pub process: Option<String>,
+}
+
+/// Window move/resize request
+///
+/// Omitted fields keep their current value.
+#[derive(Debug, Deserialize)]
+pub struct WindowMoveRequest {
+    /// New X coordinate of the top-left corner
+    #[serde(default)]
+    pub x: Option<i32>,
+
+    /// New Y coordinate of the top-left corner
+    #[serde(default)]
+    pub y: Option<i32>,
+
+    /// New width in pixels
+    #[serde(default)]
+    pub width: Option<i32>,
+
+    /// New height in pixels
+    #[serde(default)]
+    pub height: Option<i32>,
+}
+
+/// Move a window to another virtual desktop
+#[derive(Debug, Deserialize)]
+pub struct WindowDesktopRequest {
+    /// Target desktop ID from `/automation/desktops`
+    pub desktop_id: String,
+}
+
+/// Top-level window information
 #[derive(Debug, Serialize)]
+pub struct WindowResponse {
+    /// Window handle, used to target the window in other window endpoints
+    pub handle: u64,
+    /// Window title
+    pub title: String,
+    /// Process name
+    pub process_name: String,
+    /// Process ID
+    pub process_id: u32,
+    /// X coordinate
+    pub x: i32,
+    /// Y coordinate
+    pub y: i32,
+    /// Width
+    pub width: i32,
+    /// Height
+    pub height: i32,
+    /// Is minimized
+    pub is_minimized: bool,
+    /// Is maximized
+    pub is_maximized: bool,
+    /// ID of the virtual desktop the window is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_id: Option<String>,
+    /// Whether the window is on the current virtual desktop
+    pub on_current_desktop: bool,
+}
+
+#[cfg(feature = "server")]
+impl From<screensearch_automation::WindowInfo> for WindowResponse {
+    fn from(window: screensearch_automation::WindowInfo) -> Self {
+        Self {
+            handle: window.handle as u64,
+            title: window.title,
+            process_name: window.process_name,
+            process_id: window.process_id,
+            x: window.x,
+            y: window.y,
+            width: window.width,
+            height: window.height,
+            is_minimized: window.is_minimized,
+            is_maximized: window.is_maximized,
+            desktop_id: window.desktop_id,
+            on_current_desktop: window.on_current_desktop,
+        }
+    }
+}
+
+/// Generic automation response
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AutomationResponse {
     /// Whether the operation succeeded
     pub success: bool,
@@ -281,7 +931,7 @@ pub struct AutomationResponse {
 // ============================================================
 
 /// Health check response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct HealthResponse {
     /// Server status ("ok", "degraded", "error")
     pub status: String,
@@ -309,10 +959,205 @@ pub struct HealthResponse {
     /// Timestamp of newest frame
     #[serde(skip_serializing_if = "Option::is_none")]
     pub newest_frame: Option<DateTime<Utc>>,
+
+    /// Capture pipeline memory budget, when `performance.max_memory_mb` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_budget: Option<screensearch_capture::MemoryBudgetStats>,
+
+    /// Free disk space, when a `storage` disk space threshold is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_space: Option<screensearch_capture::DiskSpaceStats>,
+
+    /// Power source and throttling, when a `performance` battery setting is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<screensearch_capture::PowerStats>,
+
+    /// Frames waiting for OCR per monitor, once capture runs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frame_queue: Vec<screensearch_capture::MonitorBacklog>,
+
+    /// Startup recovery findings, when the previous run did not shut down cleanly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<RecoveryReport>,
 }
 
-/// Create tag request
+/// Findings of the startup recovery after an unclean shutdown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// When the recovery ran
+    pub checked_at: DateTime<Utc>,
+
+    /// Problems reported by SQLite's integrity check (empty = none)
+    pub integrity_errors: Vec<String>,
+
+    /// Image files without a frame that were queued for OCR again
+    pub orphans_adopted: usize,
+
+    /// Image files without a frame that were deleted
+    pub orphans_deleted: usize,
+
+    /// Frames whose image file is missing
+    pub missing_files: usize,
+}
+
+/// Log query parameters
+#[derive(Debug, Deserialize)]
+pub struct LogQuery {
+    /// Least severe level to include: error, warn, info, debug or trace
+    /// (default: info)
+    #[serde(default)]
+    pub level: Option<String>,
+
+    /// Only entries at or after this time (ISO 8601)
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only entries whose component (module path) contains this text
+    #[serde(default)]
+    pub component: Option<String>,
+
+    /// Maximum entries, the most recent ones (default: 200, max: 5000)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Log query response
+#[cfg(feature = "server")]
+#[derive(Debug, Serialize)]
+pub struct LogsResponse {
+    /// Matching entries, oldest first
+    pub entries: Vec<crate::logs::LogEntry>,
+
+    /// Directory holding the log files
+    pub log_dir: String,
+}
+
+/// Install update request
+#[derive(Debug, Default, Deserialize)]
+pub struct InstallUpdateRequest {
+    /// Restart and install as soon as the update is verified, instead of on
+    /// the next start
+    #[serde(default)]
+    pub restart: bool,
+}
+
+/// Move the data directory request
+#[derive(Debug, Deserialize)]
+pub struct MoveDataRequest {
+    /// New data directory: an absolute path that does not exist yet or is
+    /// an empty directory
+    pub path: String,
+
+    /// Delete the old data directory once the move succeeded
+    #[serde(default)]
+    pub remove_old: bool,
+}
+
+/// Data directory move status returned by `/admin/move-data`
+#[cfg(feature = "server")]
+#[derive(Debug, Clone, Serialize)]
+pub struct DataMoveStatus {
+    /// Data directory in use (absent when running the API on its own)
+    pub data_dir: Option<String>,
+
+    /// Move carried out when the application restarts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending: Option<crate::data_move::MoveRequest>,
+
+    /// Last completed move into this data directory
+    pub last_move: Option<crate::data_move::MoveReport>,
+}
+
+/// Screenshot folder import request
+#[derive(Debug, Deserialize)]
+pub struct ImportImagesRequest {
+    /// Folder of the images (absolute)
+    pub path: String,
+
+    /// Also import the images in subfolders
+    #[serde(default = "default_recursive")]
+    pub recursive: bool,
+}
+
+fn default_recursive() -> bool {
+    true
+}
+
+/// Queued screenshot folder import returned by `/admin/import-images`
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportImagesResponse {
+    /// `image_import` job, followed through `/jobs/{id}`
+    pub job_id: i64,
+
+    /// Folder being imported
+    pub path: String,
+
+    /// Images found in the folder
+    pub images: usize,
+}
+
+/// Capture profiles returned by `/profiles`
+#[cfg(feature = "server")]
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfilesResponse {
+    /// Profile in use
+    pub active: String,
+
+    /// Configured profiles, the default first (empty when running the API
+    /// on its own)
+    pub profiles: Vec<crate::profiles::Profile>,
+
+    /// Profile the application is restarting with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub switching_to: Option<String>,
+}
+
+/// Switch profile request
 #[derive(Debug, Deserialize)]
+pub struct SwitchProfileRequest {
+    /// Profile to switch to
+    pub name: String,
+}
+
+/// First-run setup progress returned by `/setup`
+#[cfg(feature = "server")]
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupStatusResponse {
+    /// Whether setup has been completed
+    pub completed: bool,
+
+    /// Next step to answer
+    pub step: crate::setup::SetupStep,
+
+    /// Choices made so far, starting from the current settings
+    pub choices: crate::setup::SetupChoices,
+
+    /// Directory currently holding the database and captures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<String>,
+
+    /// Monitors that can be captured
+    pub available_monitors: Vec<SetupMonitor>,
+
+    /// Whether the embedding model is downloaded
+    pub model_installed: bool,
+
+    /// Whether the application must be restarted for the choices to apply
+    pub restart_required: bool,
+}
+
+/// Monitor offered by the setup's monitors step
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupMonitor {
+    pub index: usize,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub is_primary: bool,
+}
+
+/// Create tag request
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTagRequest {
     /// Tag name
     pub tag_name: String,
@@ -327,7 +1172,7 @@ pub struct CreateTagRequest {
 }
 
 /// Add tag to frame request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AddTagToFrameRequest {
     /// Tag ID to add to frame
     pub tag_id: i64,
@@ -340,6 +1185,120 @@ pub struct RemoveTagFromFrameRequest {
     pub tag_id: i64,
 }
 
+// ============================================================
+// AI Models
+// ============================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiReportRequest {
+    pub provider_url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+
+    // Report Context
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub prompt: Option<String>, // Custom system prompt or overridden instruction
+    #[serde(default)]
+    pub template_id: Option<i64>, // Report template to use instead of the default prompt
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AiChatRequest {
+    pub provider_url: String, // OpenAI-compatible URL, or "builtin"
+    pub api_key: Option<String>,
+    pub model: String,
+
+    /// Conversation so far, ending with the message to answer
+    pub messages: Vec<OpenAIMessage>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// Maximum tokens of the answer
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AiChatResponse {
+    pub message: OpenAIMessage,
+    pub model_used: String,
+    pub tokens_used: Option<u32>,
+    /// Entry of the request in `/ai/history`, if it was recorded
+    pub interaction_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AiReportResponse {
+    pub report: String,
+    pub model_used: String,
+    pub tokens_used: Option<u32>,
+    pub context_source: String,
+    /// Context entries cited in the report, in order of first citation
+    pub sources: Vec<ReportSource>,
+    /// Entry of the request in `/ai/history`, if it was recorded
+    pub interaction_id: Option<i64>,
+}
+
+/// A chat message: "system", "user" or "assistant" and its text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIMessage {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AskRequest {
+    pub question: String,
+    pub provider_url: String, // OpenAI-compatible URL, or "builtin"
+    pub api_key: Option<String>,
+    pub model: String,
+
+    /// Only search screen content captured at or after this time
+    #[serde(default)]
+    pub start_time: Option<DateTime<Utc>>,
+    /// Only search screen content captured at or before this time
+    /// (default: now)
+    #[serde(default)]
+    pub end_time: Option<DateTime<Utc>>,
+    /// Natural-language time range to search, e.g. "last tuesday morning";
+    /// explicit start/end times take precedence
+    #[serde(default)]
+    pub when: Option<String>,
+    /// UTC offset `when` is resolved in, e.g. "+02:00" (default: server
+    /// time zone)
+    #[serde(default)]
+    pub tz: Option<String>,
+    /// Reorder the retrieved content by keyword matches and recency
+    /// (default: true)
+    #[serde(default = "default_rerank")]
+    pub rerank: bool,
+    /// Maximum pieces of screen content sent with the question
+    /// (default: 10, max: 50)
+    #[serde(default)]
+    pub max_sources: Option<usize>,
+}
+
+fn default_rerank() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AskResponse {
+    pub answer: String,
+    /// False when no relevant screen content was found, or the model found
+    /// no answer in it
+    pub found: bool,
+    /// Screen content supporting the answer: the cited sources, or all
+    /// sources sent when the answer cites none
+    pub sources: Vec<ReportSource>,
+    /// How the screen content was retrieved, e.g. "Semantic Search"
+    pub context_source: String,
+    pub model_used: String,
+    pub tokens_used: Option<u32>,
+    /// Entry of the request in `/ai/history`, if the model was asked
+    pub interaction_id: Option<i64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -352,6 +1311,22 @@ mod tests {
         assert_eq!(query.limit, Some(50));
     }
 
+    #[test]
+    fn test_pagination_info() {
+        let page = PaginationInfo::counted(20, 40, 20, 61);
+        assert_eq!(page.total, Some(61));
+        assert!(page.has_more);
+        assert!(!PaginationInfo::counted(20, 60, 1, 61).has_more);
+        assert!(!PaginationInfo::counted(20, 80, 0, 61).has_more);
+
+        // Unknown totals are left out
+        let json = serde_json::to_value(PaginationInfo::uncounted(20, 0, true)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"limit": 20, "offset": 0, "has_more": true})
+        );
+    }
+
     #[test]
     fn test_automation_response_serialization() {
         let response = AutomationResponse {
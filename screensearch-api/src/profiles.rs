@@ -0,0 +1,114 @@
+//! Capture profiles
+//!
+//! Each profile has its own database, captures directory and privacy
+//! settings, so that e.g. personal browsing stays out of the database work
+//! reports are generated from. The main binary runs one profile at a time
+//! and publishes them here; `/profiles` lists them and asks for a switch,
+//! which restarts the application with the chosen profile.
+
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::Notify;
+
+/// Profile used when none is chosen, kept in the data directory itself
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Longest profile name, which is also a directory name
+const MAX_NAME_LEN: usize = 32;
+
+/// Whether `name` can name a profile
+///
+/// Lowercase letters, digits, `-` and `_`, so it is a valid directory name
+/// everywhere.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Profile reported by `/profiles`
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    pub name: String,
+
+    /// Directory holding the profile's database and captures
+    pub data_dir: String,
+}
+
+/// Profiles of the running application
+#[derive(Debug, Clone)]
+pub struct Profiles {
+    /// Profile in use
+    pub active: String,
+
+    /// All profiles, the default first
+    pub available: Vec<Profile>,
+}
+
+/// Profiles shared between the application and `/profiles`
+#[derive(Debug, Default)]
+pub struct ProfileControl {
+    profiles: OnceLock<Profiles>,
+    switch: Notify,
+    requested: Mutex<Option<String>>,
+}
+
+impl ProfileControl {
+    /// Publish the profiles of the running application
+    pub fn set_profiles(&self, profiles: Profiles) {
+        let _ = self.profiles.set(profiles);
+    }
+
+    /// Profiles of the running application, when known
+    pub fn profiles(&self) -> Option<&Profiles> {
+        self.profiles.get()
+    }
+
+    /// Ask the application to restart with the profile `name`
+    pub fn request_switch(&self, name: String) {
+        *self.requested.lock().unwrap_or_else(|e| e.into_inner()) = Some(name);
+        self.switch.notify_one();
+    }
+
+    /// Wait for the next switch request
+    ///
+    /// Returns the profile to switch to.
+    pub async fn switch_requested(&self) -> String {
+        loop {
+            self.switch.notified().await;
+            if let Some(name) = self
+                .requested
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .take()
+            {
+                return name;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_names() {
+        assert!(is_valid_name("work"));
+        assert!(is_valid_name("client-2_eu"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("Work"));
+        assert!(!is_valid_name("../work"));
+        assert!(!is_valid_name(&"a".repeat(MAX_NAME_LEN + 1)));
+    }
+
+    #[tokio::test]
+    async fn test_switch_request_is_kept_until_awaited() {
+        let control = ProfileControl::default();
+        control.request_switch("work".to_string());
+        control.request_switch("personal".to_string());
+        assert_eq!(control.switch_requested().await, "personal");
+    }
+}
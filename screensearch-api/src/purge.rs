@@ -0,0 +1,188 @@
+//! Subject data purge ("forget this")
+//!
+//! `POST /privacy/purge` deletes every frame matching a query, with its OCR
+//! text, embeddings, tags and image files, and records what it deleted in a
+//! signed report. The report carries counts and digests rather than the query
+//! or any deleted text, so it can be kept without keeping what was forgotten.
+//!
+//! Reports are signed with HMAC-SHA256 under a random key stored in the
+//! database (see [`screensearch_db::DatabaseManager::purge_signing_key`]), so
+//! a report edited afterwards no longer verifies on this installation. The
+//! query digest is an HMAC under the same key, so it cannot be reversed by
+//! hashing guessed queries without the key.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use screensearch_db::{PurgeFilter, PurgeOutcome};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Version of the report format
+pub const REPORT_VERSION: u32 = 1;
+
+/// What a purge deleted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PurgeReport {
+    pub version: u32,
+    /// Installation the frames were deleted from
+    pub machine_id: String,
+    pub purged_at: DateTime<Utc>,
+    /// HMAC-SHA256 of the query (JSON) under the signing key; the query is
+    /// not kept
+    pub query_hmac: String,
+    /// Time range of the query, if it had one
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub frames_deleted: usize,
+    /// SHA-256 of the deleted frame IDs, comma-separated in ID order
+    pub frame_ids_sha256: String,
+    pub ocr_rows_deleted: u64,
+    pub embeddings_deleted: u64,
+    pub summaries_deleted: u64,
+    pub ai_interactions_deleted: u64,
+    pub files_deleted: usize,
+    /// Files that could not be deleted
+    pub files_failed: Vec<String>,
+    /// Video chunks and archives still holding images of deleted frames,
+    /// because other frames are stored in them too
+    pub files_retained: Vec<String>,
+}
+
+impl PurgeReport {
+    /// Report of a purge whose files, except `files_failed`, were deleted
+    ///
+    /// `key` is the purge signing key the query digest is computed under.
+    pub fn new(
+        machine_id: String,
+        key: &str,
+        filter: &PurgeFilter,
+        outcome: &PurgeOutcome,
+        files_failed: Vec<String>,
+    ) -> Self {
+        let query = serde_json::to_vec(filter).unwrap_or_default();
+        let frame_ids = outcome
+            .frame_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        Self {
+            version: REPORT_VERSION,
+            machine_id,
+            purged_at: Utc::now(),
+            query_hmac: sign_bytes(key, &query),
+            start_time: filter.start_time,
+            end_time: filter.end_time,
+            frames_deleted: outcome.frame_ids.len(),
+            frame_ids_sha256: sha256_hex(frame_ids.as_bytes()),
+            ocr_rows_deleted: outcome.ocr_rows,
+            embeddings_deleted: outcome.embeddings,
+            summaries_deleted: outcome.summaries,
+            ai_interactions_deleted: outcome.ai_interactions,
+            files_deleted: outcome.files.len() - files_failed.len(),
+            files_failed,
+            files_retained: outcome.retained_files.clone(),
+        }
+    }
+}
+
+/// HMAC-SHA256 of a report (hex)
+pub fn sign(key: &str, report: &str) -> String {
+    sign_bytes(key, report.as_bytes())
+}
+
+fn sign_bytes(key: &str, data: &[u8]) -> String {
+    let mut mac = hmac(key);
+    mac.update(data);
+    to_hex(&mac.finalize().into_bytes())
+}
+
+/// Whether `signature` is the signature of `report` under `key`
+pub fn verify(key: &str, report: &str, signature: &str) -> bool {
+    let Some(signature) = from_hex(signature) else {
+        return false;
+    };
+    let mut mac = hmac(key);
+    mac.update(report.as_bytes());
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn hmac(key: &str) -> Hmac<Sha256> {
+    Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = "0f1e2d3c4b5a69780f1e2d3c4b5a6978";
+        let report = r#"{"frames_deleted":3}"#;
+        let signature = sign(key, report);
+        assert_eq!(signature.len(), 64);
+
+        assert!(verify(key, report, &signature));
+        assert!(!verify(key, r#"{"frames_deleted":2}"#, &signature));
+        assert!(!verify("another key", report, &signature));
+        assert!(!verify(key, report, "not hex"));
+        assert!(!verify(key, report, &signature[..62]));
+    }
+
+    #[test]
+    fn test_report_keeps_no_query_text() {
+        let filter = PurgeFilter {
+            keyword: Some("hunter2".to_string()),
+            ..Default::default()
+        };
+        let outcome = PurgeOutcome {
+            frame_ids: vec![4, 7],
+            ocr_rows: 5,
+            embeddings: 2,
+            summaries: 1,
+            ai_interactions: 0,
+            files: vec!["a.png".to_string(), "b.png".to_string()],
+            retained_files: vec!["chunk.mp4".to_string()],
+        };
+        let key = "0f1e2d3c4b5a69780f1e2d3c4b5a6978";
+        let report = PurgeReport::new(
+            "machine".to_string(),
+            key,
+            &filter,
+            &outcome,
+            vec!["b.png".to_string()],
+        );
+
+        assert_eq!(report.frames_deleted, 2);
+        assert_eq!(report.files_deleted, 1);
+        assert_eq!(report.files_retained, vec!["chunk.mp4"]);
+        assert_eq!(report.frame_ids_sha256, sha256_hex(b"4,7"));
+        assert_eq!(report.summaries_deleted, 1);
+        // The query digest depends on the key, so guessed queries cannot be
+        // checked against it without the key
+        let query = serde_json::to_vec(&filter).unwrap();
+        assert_ne!(report.query_hmac, sha256_hex(&query));
+        assert_ne!(report.query_hmac, sign_bytes("another key", &query));
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(!json.contains("hunter2"));
+    }
+}
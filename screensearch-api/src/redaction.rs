@@ -0,0 +1,360 @@
+//! Text redaction rules
+//!
+//! Redaction rules are terms or regexes masked in OCR text and window titles.
+//! Frames are redacted as the capture pipeline stores them, and a `redaction` job (see
+//! [`crate::workers::redaction`]) applies a rule to the frames stored before
+//! it existed. Rules with `blur_images` also blur the image: the matching
+//! lines of a new frame, and the whole image of a stored one, whose text
+//! boxes may not match a resized or re-encoded image.
+
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use regex::{Regex, RegexBuilder};
+use screensearch_capture::{ProcessedFrame, TextRegion};
+use screensearch_db::{DatabaseManager, RedactionRuleRecord};
+use std::ops::Range;
+use std::sync::{Arc, RwLock};
+
+/// Text put in place of each match
+pub const MASK: &str = "[redacted]";
+
+/// Pixel size of the blocks a blurred image is reduced to
+const BLUR_BLOCK_SIZE: u32 = 16;
+
+/// Compiled form of a stored redaction rule
+#[derive(Debug)]
+pub struct RedactionMatcher {
+    rule_id: i64,
+    regex: Regex,
+    blur_images: bool,
+}
+
+impl RedactionMatcher {
+    /// Compile a rule
+    ///
+    /// Terms match case-insensitively; regexes are used as given.
+    pub fn new(rule: &RedactionRuleRecord) -> Result<Self, regex::Error> {
+        Self::compile(rule.id, &rule.pattern, rule.is_regex, rule.blur_images)
+    }
+
+    /// Compile a rule pattern without a stored rule (validation)
+    pub fn compile(
+        rule_id: i64,
+        pattern: &str,
+        is_regex: bool,
+        blur_images: bool,
+    ) -> Result<Self, regex::Error> {
+        let regex = if is_regex {
+            Regex::new(pattern)?
+        } else {
+            RegexBuilder::new(&regex::escape(pattern))
+                .case_insensitive(true)
+                .build()?
+        };
+
+        Ok(Self {
+            rule_id,
+            regex,
+            blur_images,
+        })
+    }
+
+    pub fn rule_id(&self) -> i64 {
+        self.rule_id
+    }
+
+    pub fn blur_images(&self) -> bool {
+        self.blur_images
+    }
+
+    /// Whether the pattern matches empty text, which would mask between
+    /// every character
+    pub fn matches_empty(&self) -> bool {
+        self.regex.is_match("")
+    }
+}
+
+/// Byte ranges of `text` matched by any of the rules, sorted and merged
+pub fn match_ranges<'m>(
+    matchers: impl IntoIterator<Item = &'m RedactionMatcher>,
+    text: &str,
+) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = matchers
+        .into_iter()
+        .flat_map(|matcher| matcher.regex.find_iter(text).map(|m| m.range()))
+        .filter(|range| !range.is_empty())
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// `text` with each of the (merged) ranges replaced by [`MASK`]
+pub fn mask(text: &str, ranges: &[Range<usize>]) -> String {
+    let mut masked = String::with_capacity(text.len());
+    let mut pos = 0;
+    for range in ranges {
+        masked.push_str(&text[pos..range.start]);
+        masked.push_str(MASK);
+        pos = range.end;
+    }
+    masked.push_str(&text[pos..]);
+    masked
+}
+
+/// `text` with the matches of all rules masked, `None` if nothing matched
+pub fn redact_text(matchers: &[RedactionMatcher], text: &str) -> Option<String> {
+    let ranges = match_ranges(matchers, text);
+    (!ranges.is_empty()).then(|| mask(text, &ranges))
+}
+
+/// Mask the OCR text and window title of a frame before it is stored
+///
+/// Words overlapping a match are masked as a whole. Lines matched by a rule
+/// with `blur_images` are blurred in the image. Returns whether anything
+/// was masked.
+pub fn redact_frame(matchers: &[RedactionMatcher], processed: &mut ProcessedFrame) -> bool {
+    let mut redacted = false;
+    if let Some(title) = processed.frame.active_window.as_mut() {
+        if let Some(masked) = redact_text(matchers, title) {
+            *title = masked;
+            redacted = true;
+        }
+    }
+
+    let mut text_redacted = false;
+    for region in &mut processed.ocr_result.regions {
+        let ranges = match_ranges(matchers, &region.text);
+        if ranges.is_empty() {
+            continue;
+        }
+        text_redacted = true;
+
+        let blur_ranges = match_ranges(matchers.iter().filter(|m| m.blur_images), &region.text);
+        if !blur_ranges.is_empty() {
+            blur_region(&mut processed.frame.image, region);
+        }
+        mask_words(&region.text, &ranges, &mut region.words);
+        region.text = mask(&region.text, &ranges);
+    }
+
+    if text_redacted {
+        processed.ocr_result.full_text = processed
+            .ocr_result
+            .regions
+            .iter()
+            .map(|r| r.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+    redacted || text_redacted
+}
+
+/// Mask the words of a line that overlap a matched range of the line text
+fn mask_words(line: &str, ranges: &[Range<usize>], words: &mut [TextRegion]) {
+    let mut pos = 0;
+    for word in words {
+        let Some(start) = line[pos..].find(&word.text).map(|i| i + pos) else {
+            continue;
+        };
+        let end = start + word.text.len();
+        pos = end;
+        if ranges.iter().any(|r| r.start < end && start < r.end) {
+            word.text = MASK.to_string();
+        }
+    }
+}
+
+/// Pixelate an image beyond legibility
+pub fn blur(image: &RgbaImage) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let small = imageops::resize(
+        image,
+        (width / BLUR_BLOCK_SIZE).max(1),
+        (height / BLUR_BLOCK_SIZE).max(1),
+        FilterType::Triangle,
+    );
+    imageops::resize(&small, width, height, FilterType::Nearest)
+}
+
+/// Pixelate the box of a text region, clipped to the image
+fn blur_region(image: &mut RgbaImage, region: &TextRegion) {
+    let (width, height) = image.dimensions();
+    let x = region.x.min(width);
+    let y = region.y.min(height);
+    let w = region.width.min(width - x);
+    let h = region.height.min(height - y);
+    if w == 0 || h == 0 {
+        return;
+    }
+
+    let blurred = blur(&imageops::crop_imm(image, x, y, w, h).to_image());
+    imageops::replace(image, &blurred, x as i64, y as i64);
+}
+
+/// Applies redaction rules to frames as they are stored
+pub struct Redactor {
+    db: Arc<DatabaseManager>,
+    /// Compiled rules; `None` until loaded or after rules change
+    matchers: RwLock<Option<Arc<Vec<RedactionMatcher>>>>,
+}
+
+impl Redactor {
+    pub fn new(db: Arc<DatabaseManager>) -> Self {
+        Self {
+            db,
+            matchers: RwLock::new(None),
+        }
+    }
+
+    /// Reload rules before the next frame is redacted
+    pub fn invalidate(&self) {
+        *self.matchers.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Mask a frame with all rules (see [`redact_frame`])
+    ///
+    /// Failing to load the rules is logged and the frame stored as it is,
+    /// like alerts never block frame storage.
+    pub async fn redact(&self, mut processed: ProcessedFrame) -> ProcessedFrame {
+        match self.matchers().await {
+            Ok(matchers) if !matchers.is_empty() => {
+                if redact_frame(&matchers, &mut processed) {
+                    tracing::debug!(
+                        "Redacted text in a frame of monitor {}",
+                        processed.frame.monitor_index
+                    );
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to load redaction rules: {}", e),
+        }
+        processed
+    }
+
+    /// Compiled rules, loading them from the database if needed
+    async fn matchers(&self) -> screensearch_db::Result<Arc<Vec<RedactionMatcher>>> {
+        if let Some(matchers) = self
+            .matchers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+        {
+            return Ok(Arc::clone(matchers));
+        }
+
+        let matchers: Vec<_> = self
+            .db
+            .list_redaction_rules()
+            .await?
+            .iter()
+            .filter_map(|rule| match RedactionMatcher::new(rule) {
+                Ok(matcher) if !matcher.matches_empty() => Some(matcher),
+                Ok(_) => {
+                    tracing::warn!("Skipping redaction rule {} matching empty text", rule.id);
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Skipping redaction rule {} with invalid pattern: {}",
+                        rule.id,
+                        e
+                    );
+                    None
+                }
+            })
+            .collect();
+        let matchers = Arc::new(matchers);
+
+        *self.matchers.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::clone(&matchers));
+        Ok(matchers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screensearch_capture::{CapturedFrame, OcrResult};
+
+    #[test]
+    fn test_redact_text() {
+        let matchers = vec![
+            RedactionMatcher::compile(1, "hunter2", false, false).unwrap(),
+            RedactionMatcher::compile(2, r"sk-[A-Za-z0-9]{8,}", true, false).unwrap(),
+        ];
+
+        assert_eq!(
+            redact_text(&matchers, "password: HUNTER2 key sk-abcdef123456").as_deref(),
+            Some("password: [redacted] key [redacted]")
+        );
+        assert_eq!(redact_text(&matchers, "nothing to see"), None);
+
+        // Overlapping matches become one mask
+        let overlapping = vec![
+            RedactionMatcher::compile(1, "secret", false, false).unwrap(),
+            RedactionMatcher::compile(2, "cret value", false, false).unwrap(),
+        ];
+        assert_eq!(
+            redact_text(&overlapping, "a secret value here").as_deref(),
+            Some("a [redacted] here")
+        );
+
+        assert!(RedactionMatcher::compile(1, "a*", true, false)
+            .unwrap()
+            .matches_empty());
+        assert!(RedactionMatcher::compile(1, "(unclosed", true, false).is_err());
+    }
+
+    #[test]
+    fn test_redact_frame() {
+        let mut line = TextRegion::new("token abc123 set".to_string(), 8, 8, 48, 16, 0.9);
+        line.words = ["token", "abc123", "set"]
+            .iter()
+            .map(|word| TextRegion::new(word.to_string(), 0, 0, 0, 0, 0.9))
+            .collect();
+        let other = TextRegion::new("unrelated".to_string(), 0, 40, 48, 16, 0.9);
+        let image = RgbaImage::from_fn(64, 64, |x, _| {
+            image::Rgba([if x % 2 == 0 { 255 } else { 0 }, 0, 0, 255])
+        });
+        let mut processed = ProcessedFrame {
+            frame: CapturedFrame {
+                timestamp: chrono::Utc::now(),
+                monitor_index: 0,
+                image: image.clone(),
+                active_window: Some("Settings - token ABC123".to_string()),
+                active_process: None,
+            },
+            ocr_result: OcrResult::new(vec![line, other], (64, 64), 0),
+            frame_id: None,
+        };
+
+        let matchers = vec![RedactionMatcher::compile(1, "abc123", false, true).unwrap()];
+        assert!(redact_frame(&matchers, &mut processed));
+
+        let regions = &processed.ocr_result.regions;
+        assert_eq!(regions[0].text, "token [redacted] set");
+        let words: Vec<_> = regions[0].words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(words, ["token", "[redacted]", "set"]);
+        assert_eq!(regions[1].text, "unrelated");
+        assert_eq!(
+            processed.frame.active_window.as_deref(),
+            Some("Settings - token [redacted]")
+        );
+        assert_eq!(
+            processed.ocr_result.full_text,
+            "token [redacted] set unrelated"
+        );
+
+        // Only the matching line is blurred
+        let blurred = &processed.frame.image;
+        assert_ne!(blurred.get_pixel(9, 9), image.get_pixel(9, 9));
+        assert_eq!(blurred.get_pixel(9, 45), image.get_pixel(9, 45));
+    }
+}
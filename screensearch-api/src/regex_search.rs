@@ -0,0 +1,202 @@
+//! Regular-expression search
+//!
+//! FTS5 splits text into words, so structured strings such as ticket IDs
+//! (`[A-Z]{2,5}-\d+`), amounts or paths cannot be searched for by shape. This
+//! scans OCR text newest first and matches a user-supplied regular
+//! expression against it. The scan is bounded by the time and app filters,
+//! a maximum number of scanned rows and a time limit.
+
+use crate::error::{AppError, Result};
+use regex::{Regex, RegexBuilder};
+use screensearch_db::{
+    snippet_html, DatabaseManager, FrameFilter, MatchHighlight, MatchOffset, SearchResult,
+};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// OCR text records fetched per database round trip
+const SCAN_BATCH: i64 = 500;
+/// Maximum OCR text records scanned by one search
+pub const MAX_SCAN_ROWS: usize = 50_000;
+/// Maximum time spent scanning for one search
+pub const SCAN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Maximum compiled size of a pattern
+const MAX_PATTERN_SIZE: usize = 1 << 20;
+/// Characters of context around the first match in a snippet
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+
+/// Compile a user-supplied pattern
+pub fn compile_pattern(pattern: &str) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .size_limit(MAX_PATTERN_SIZE)
+        .build()
+        .map_err(|e| AppError::InvalidRequest(format!("Invalid regular expression: {}", e)))
+}
+
+/// Find the frames whose OCR text matches `regex`, newest first
+///
+/// Stops after `limit` frames, `MAX_SCAN_ROWS` scanned records or
+/// `SCAN_TIMEOUT`, whichever comes first. Each result's relevance score is
+/// its number of matches.
+pub async fn regex_search(
+    db: &DatabaseManager,
+    regex: &Regex,
+    filter: &FrameFilter,
+    limit: usize,
+) -> Result<Vec<SearchResult>> {
+    let started = Instant::now();
+    let mut results: Vec<SearchResult> = Vec::new();
+    let mut positions: HashMap<i64, usize> = HashMap::new();
+    let mut scanned = 0;
+    let mut before_id = None;
+
+    while results.len() < limit && scanned < MAX_SCAN_ROWS && started.elapsed() < SCAN_TIMEOUT {
+        let batch = SCAN_BATCH.min((MAX_SCAN_ROWS - scanned) as i64);
+        let rows = db.scan_ocr_text(filter, before_id, batch).await?;
+        let Some((_, last)) = rows.last() else {
+            break;
+        };
+        before_id = Some(last.id);
+        scanned += rows.len();
+
+        for (frame, ocr) in rows {
+            let Some(highlight) = highlight_matches(regex, ocr.id, &ocr.text) else {
+                continue;
+            };
+
+            let index = match positions.get(&frame.id) {
+                Some(&index) => index,
+                None => {
+                    if results.len() >= limit {
+                        continue;
+                    }
+                    positions.insert(frame.id, results.len());
+                    results.push(SearchResult {
+                        frame,
+                        ocr_matches: Vec::new(),
+                        relevance_score: 0.0,
+                        tags: Vec::new(),
+                        highlights: Vec::new(),
+                        duplicates_count: 0,
+                    });
+                    results.len() - 1
+                }
+            };
+            let result = &mut results[index];
+            result.relevance_score += highlight.offsets.len() as f32;
+            result.ocr_matches.push(ocr);
+            result.highlights.push(highlight);
+        }
+    }
+
+    debug!(
+        "Regex search scanned {} OCR records in {:?}, {} frames matched",
+        scanned,
+        started.elapsed(),
+        results.len()
+    );
+    Ok(results)
+}
+
+/// Highlight every match of `regex` in `text`, or None if nothing matches
+fn highlight_matches(regex: &Regex, ocr_text_id: i64, text: &str) -> Option<MatchHighlight> {
+    let ranges: Vec<(usize, usize)> = regex
+        .find_iter(text)
+        .filter(|m| !m.is_empty())
+        .map(|m| (m.start(), m.end()))
+        .collect();
+    let (first_start, first_end) = *ranges.first()?;
+
+    // Offsets are in characters, like those of FTS5 highlights
+    let char_index = |byte: usize| text[..byte].chars().count();
+    let offsets = ranges
+        .iter()
+        .map(|&(start, end)| MatchOffset {
+            start: char_index(start),
+            end: char_index(end),
+        })
+        .collect();
+
+    let snippet_start = text[..first_start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let snippet_end = text[first_end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT_CHARS)
+        .map(|(i, _)| first_end + i)
+        .unwrap_or(text.len());
+    let snippet = format!(
+        "{}{}\u{2}{}\u{3}{}{}",
+        if snippet_start > 0 { "..." } else { "" },
+        &text[snippet_start..first_start],
+        &text[first_start..first_end],
+        &text[first_end..snippet_end],
+        if snippet_end < text.len() { "..." } else { "" },
+    );
+
+    Some(MatchHighlight {
+        ocr_text_id,
+        snippet: snippet_html(&snippet),
+        offsets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_matches() {
+        let regex = compile_pattern(r"[A-Z]{2,5}-\d+").unwrap();
+        let highlight = highlight_matches(&regex, 7, "Résumé: fixed PROJ-142 and OPS-9").unwrap();
+
+        assert_eq!(highlight.ocr_text_id, 7);
+        assert_eq!(
+            highlight.snippet,
+            "Résumé: fixed <mark>PROJ-142</mark> and OPS-9"
+        );
+        let offsets: Vec<(usize, usize)> =
+            highlight.offsets.iter().map(|o| (o.start, o.end)).collect();
+        assert_eq!(offsets, vec![(14, 22), (27, 32)]);
+    }
+
+    #[test]
+    fn test_highlight_snippet_is_trimmed() {
+        let regex = compile_pattern("needle").unwrap();
+        let text = format!("{} needle {}", "a".repeat(100), "b".repeat(100));
+        let snippet = highlight_matches(&regex, 1, &text).unwrap().snippet;
+
+        assert_eq!(
+            snippet,
+            format!(
+                "...{} <mark>needle</mark> {}...",
+                "a".repeat(59),
+                "b".repeat(59)
+            )
+        );
+    }
+
+    #[test]
+    fn test_highlight_snippet_is_escaped() {
+        let regex = compile_pattern("alert").unwrap();
+        let snippet = highlight_matches(&regex, 1, "<script>alert(1)</script>")
+            .unwrap()
+            .snippet;
+
+        assert_eq!(
+            snippet,
+            "&lt;script&gt;<mark>alert</mark>(1)&lt;/script&gt;"
+        );
+    }
+
+    #[test]
+    fn test_no_match_and_invalid_pattern() {
+        let regex = compile_pattern(r"\d{4}").unwrap();
+        assert!(highlight_matches(&regex, 1, "no digits here").is_none());
+        assert!(compile_pattern("(unclosed").is_err());
+    }
+}
@@ -1,11 +1,15 @@
 //! Route definitions
 
+use crate::audit;
 use crate::handlers;
 use crate::state::AppState;
+use crate::sync;
 use crate::Assets;
+use axum::extract::DefaultBodyLimit;
 use axum::http::Uri;
 use axum::response::IntoResponse;
 use axum::{
+    middleware,
     routing::{delete, get, post, put},
     Router,
 };
@@ -20,15 +24,56 @@ pub fn build_router(state: Arc<AppState>) -> Router {
         // Frame endpoints
         .nest("/frames", frame_routes())
         // Automation endpoints
-        .nest("/automation", automation_routes())
+        .nest("/automation", automation_routes(Arc::clone(&state)))
         // Tag endpoints
         .nest("/tags", tag_routes())
         // Settings endpoints
         .nest("/settings", settings_routes())
         // AI endpoints
         .nest("/ai", ai_routes())
+        // Agent endpoints
+        .nest("/agent", agent_routes())
         // Embeddings endpoints (RAG)
-        .nest("/embeddings", embeddings_routes());
+        .nest("/embeddings", embeddings_routes())
+        // Stored report endpoints
+        .nest("/reports", report_routes())
+        // Hourly activity summary endpoints
+        .route("/summaries", get(handlers::list_summaries))
+        // Question answering from captured screen content
+        .route("/ask", post(handlers::ask))
+        // Background job endpoints
+        .nest("/jobs", job_routes())
+        // Keyword watch alert endpoints
+        .nest("/alerts", alert_routes())
+        // Redaction rules and purges
+        .nest("/privacy", privacy_routes())
+        // Extracted receipt and invoice endpoints
+        .nest("/documents", document_routes())
+        // Application administration endpoints
+        .nest("/admin", admin_routes())
+        // First-run setup endpoints
+        .nest("/setup", setup_routes())
+        // Capture profile endpoints
+        .nest("/profiles", profile_routes())
+        // Daily notes, calendar and timesheet exports
+        .nest("/export", export_routes())
+        // Recorded OCR and pipeline metrics
+        .nest("/stats", stats_routes())
+        // Delta sync between machines
+        .nest("/sync", sync_routes())
+        // Log file tail with filters
+        .route("/logs", get(handlers::get_logs))
+        // Time window replay (MJPEG)
+        .route("/replay", get(handlers::replay_frames))
+        // Server event stream (WebSocket)
+        .route("/events", get(handlers::event_stream));
+
+    // GraphQL over frames, tags, sessions and search
+    #[cfg(feature = "graphql")]
+    let api_routes = api_routes.route(
+        "/graphql",
+        get(handlers::graphql_schema).post(handlers::graphql_query),
+    );
 
     // Root level routes (no prefix)
     Router::new()
@@ -104,6 +149,13 @@ fn search_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(handlers::search))
         .route("/keywords", get(handlers::search_keywords))
+        .route("/batch", post(handlers::search_batch))
+        .route("/time", get(handlers::resolve_time))
+        .route("/suggest", get(handlers::suggest))
+        .route("/history", get(handlers::list_search_history))
+        .route("/history", delete(handlers::clear_search_history))
+        .route("/history/stats", get(handlers::get_search_stats))
+        .route("/history/:id/click", post(handlers::record_search_click))
 }
 
 /// Frame-related routes
@@ -112,23 +164,63 @@ fn frame_routes() -> Router<Arc<AppState>> {
         .route("/", get(handlers::get_frames))
         .route("/:id", get(handlers::get_single_frame))
         .route("/:id/image", get(handlers::get_frame_image))
+        .route("/:id/context", get(handlers::get_frame_context))
+        .route("/:id/pin", post(handlers::pin_frame))
+        .route("/:id/pin", delete(handlers::unpin_frame))
         .route("/:id/tags", post(handlers::add_tag_to_frame))
         .route("/:id/tags", get(handlers::get_frame_tags))
         .route("/:id/tags/:tag_id", delete(handlers::remove_tag_from_frame))
 }
 
 /// Automation routes
-fn automation_routes() -> Router<Arc<AppState>> {
+///
+/// Every route except `/audit` itself is recorded in the audit log.
+fn automation_routes(state: Arc<AppState>) -> Router<Arc<AppState>> {
     Router::new()
         .route("/find-elements", post(handlers::find_elements))
         .route("/click", post(handlers::click))
+        .route("/click-text", post(handlers::click_text))
+        .route("/drag", post(handlers::drag))
+        .route("/hover", post(handlers::hover))
+        .route("/highlight", post(handlers::highlight))
         .route("/type", post(handlers::type_text))
         .route("/scroll", post(handlers::scroll))
         .route("/press-key", post(handlers::press_key))
         .route("/get-text", post(handlers::get_text))
+        .route("/get-table", post(handlers::get_table))
+        .route("/select", post(handlers::select_option))
+        .route("/wait", post(handlers::wait_for_element))
+        .route("/screenshot-element", post(handlers::screenshot_element))
         .route("/list-elements", post(handlers::list_elements))
+        .route("/dump-tree", post(handlers::dump_tree))
         .route("/open-app", post(handlers::open_app))
         .route("/open-url", post(handlers::open_url))
+        .route("/workflow", post(handlers::run_workflow))
+        .route("/clipboard", get(handlers::get_clipboard))
+        .route("/clipboard/set", post(handlers::set_clipboard))
+        .route("/windows", get(handlers::list_windows))
+        .route("/windows/active", get(handlers::get_active_window))
+        .route("/windows/:handle/focus", post(handlers::focus_window))
+        .route("/windows/:handle/move", post(handlers::move_window))
+        .route(
+            "/windows/:handle/desktop",
+            post(handlers::move_window_to_desktop),
+        )
+        .route("/windows/:handle/:action", post(handlers::window_action))
+        .route("/desktops", get(handlers::list_desktops))
+        .route("/desktops/:id/switch", post(handlers::switch_desktop))
+        .route("/record/start", post(handlers::start_recording))
+        .route("/record/stop", post(handlers::stop_recording))
+        .route("/record/status", get(handlers::recording_status))
+        .route("/macros", get(handlers::list_macros))
+        .route("/macros/:id", get(handlers::get_macro))
+        .route("/macros/:id", delete(handlers::delete_macro))
+        .route("/macros/:id/replay", post(handlers::replay_macro))
+        .route_layer(middleware::from_fn_with_state(
+            state,
+            audit::audit_automation,
+        ))
+        .route("/audit", get(handlers::list_audit_entries))
 }
 
 /// Tag management routes
@@ -140,6 +232,34 @@ fn tag_routes() -> Router<Arc<AppState>> {
         .route("/:id", delete(handlers::delete_tag))
 }
 
+/// Redaction rule and purge routes
+fn privacy_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/redactions", post(handlers::create_redaction_rule))
+        .route("/redactions", get(handlers::list_redaction_rules))
+        .route("/redactions/:id", delete(handlers::delete_redaction_rule))
+        .route("/redactions/:id/apply", post(handlers::apply_redaction_rule))
+        .route("/purge", post(handlers::purge_frames))
+        .route("/purges", get(handlers::list_purge_reports))
+}
+
+/// Keyword watch alert routes
+fn alert_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(handlers::create_alert))
+        .route("/", get(handlers::list_alerts))
+        .route("/hits", get(handlers::list_alert_hits))
+        .route("/:id", delete(handlers::delete_alert))
+}
+
+/// Extracted document routes
+fn document_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(handlers::list_documents))
+        .route("/extract", post(handlers::extract_document))
+        .route("/:id", get(handlers::get_document))
+}
+
 /// Settings routes
 fn settings_routes() -> Router<Arc<AppState>> {
     Router::new()
@@ -152,13 +272,103 @@ fn ai_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/validate", post(handlers::validate_connection))
         .route("/generate", post(handlers::generate_report))
+        .route("/chat", post(handlers::ai_chat))
+        .route("/ollama/models", get(handlers::list_ollama_models))
+        .route("/ollama/pull", post(handlers::pull_ollama_model))
+        .route("/ollama/delete", post(handlers::delete_ollama_model))
+        .route("/templates", get(handlers::list_report_templates))
+        .route("/templates", post(handlers::create_report_template))
+        .route("/templates/:id", get(handlers::get_report_template))
+        .route("/templates/:id", put(handlers::update_report_template))
+        .route("/templates/:id", delete(handlers::delete_report_template))
+        .route("/history", get(handlers::list_ai_history))
+        .route("/history/usage", get(handlers::get_ai_usage))
+        .route("/history/:id", get(handlers::get_ai_interaction))
+}
+
+/// Agent routes (LLM tool calling)
+fn agent_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/run", post(handlers::run_agent))
+        .route("/stop", post(handlers::stop_agent))
 }
 
 /// Embeddings routes for RAG
 fn embeddings_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/status", get(handlers::get_embedding_status))
+        .route("/stats", get(handlers::get_embedding_stats))
         .route("/generate", post(handlers::generate_embeddings))
+        .route("/backfill", post(handlers::backfill_embeddings))
         .route("/enable", post(handlers::toggle_embeddings))
 }
 
+/// Stored report routes
+fn report_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(handlers::list_reports))
+        .route("/:id", get(handlers::get_report))
+        .route("/:id", delete(handlers::delete_report))
+        .route("/:id/download", get(handlers::download_report))
+}
+
+/// Application administration routes
+fn admin_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/update", get(handlers::get_update_status))
+        .route("/update", post(handlers::install_update))
+        .route("/move-data", get(handlers::get_data_move))
+        .route("/move-data", post(handlers::move_data))
+        .route("/import-images", post(handlers::import_images))
+}
+
+/// First-run setup routes
+fn setup_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(handlers::get_setup))
+        .route("/", post(handlers::answer_setup))
+        .route("/complete", post(handlers::complete_setup))
+}
+
+/// Capture profile routes
+fn profile_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(handlers::list_profiles))
+        .route("/active", post(handlers::switch_profile))
+}
+
+/// Export routes
+fn export_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/markdown", post(handlers::export_markdown))
+        .route("/ical", get(handlers::export_ical))
+        .route("/timesheet", get(handlers::export_timesheet))
+}
+
+/// Metrics history routes
+fn stats_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/metrics", get(handlers::list_metrics))
+        .route("/history", get(handlers::get_metrics_history))
+}
+
+/// Sync routes
+///
+/// Batches with images are far above the global body limit.
+fn sync_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/changes", get(handlers::get_sync_changes))
+        .route(
+            "/apply",
+            post(handlers::apply_sync).layer(DefaultBodyLimit::max(sync::MAX_APPLY_BYTES)),
+        )
+        .route("/status", get(handlers::get_sync_status))
+}
+
+/// Background job routes
+fn job_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(handlers::list_jobs))
+        .route("/:id", get(handlers::get_job))
+        .route("/:id/cancel", post(handlers::cancel_job))
+}
@@ -2,12 +2,17 @@
 
 use crate::routes;
 use crate::state::AppState;
+use crate::tls::TlsConfig;
+use crate::workers::{
+    ArchivalJob, CompactionJob, EmbeddingJob, ImageImportJob, MarkdownExportJob, RedactionJob,
+    ReportJob, RetentionJob, SummaryJob,
+};
 use axum::http::{HeaderName, Method};
 use screensearch_automation::AutomationEngine;
 use screensearch_db::DatabaseManager;
 use std::sync::Arc;
 use tower_http::cors::{AllowHeaders, CorsLayer};
-use tower_http::trace::TraceLayer;
+use tower_http::trace::{DefaultMakeSpan, TraceLayer};
 
 /// API server configuration
 #[derive(Debug, Clone)]
@@ -20,6 +25,13 @@ pub struct ApiConfig {
 
     /// Path to SQLite database file
     pub database_path: String,
+
+    /// Serve over HTTPS with this configuration (`None` = plain HTTP)
+    pub tls: Option<TlsConfig>,
+
+    /// Serve on this Windows named pipe (e.g. `\\.\pipe\screensearch`) or
+    /// Unix socket path instead of `host:port`; TLS does not apply
+    pub local_socket: Option<String>,
 }
 
 impl Default for ApiConfig {
@@ -28,6 +40,8 @@ impl Default for ApiConfig {
             host: "127.0.0.1".to_string(),
             port: 3131,
             database_path: "screensearch.db".to_string(),
+            tls: None,
+            local_socket: None,
         }
     }
 }
@@ -61,9 +75,146 @@ impl ApiServer {
         // Create application state
         let state = Arc::new(AppState::new(db, automation));
 
+        // Embedding jobs are queued by `/embeddings/backfill` even when the
+        // periodic worker is disabled, so their handler is always registered
+        state.jobs.register(Arc::new(EmbeddingJob));
+
+        // Retention jobs are queued by `/settings` when `retention_days` changes
+        state.jobs.register(Arc::new(RetentionJob));
+
+        // Screenshot folders are imported on request through `/admin/import-images`
+        state.jobs.register(Arc::new(ImageImportJob));
+
+        // Redaction rules are applied to stored frames through `/privacy/redactions`
+        state.jobs.register(Arc::new(RedactionJob));
+
         Ok(Self { config, state })
     }
 
+    /// Runtime controls of the capture pipeline
+    ///
+    /// Pass these to the capture engine so `/settings` updates reach it.
+    pub fn capture_control(&self) -> Arc<screensearch_capture::CaptureControl> {
+        Arc::clone(&self.state.capture)
+    }
+
+    /// Keyword watch alerts
+    ///
+    /// Call [`AlertWatcher::check_frame`](crate::alerts::AlertWatcher::check_frame)
+    /// for each stored frame so alerts are evaluated and hits published.
+    pub fn alert_watcher(&self) -> Arc<crate::alerts::AlertWatcher> {
+        Arc::clone(&self.state.alerts)
+    }
+
+    /// Redaction rules
+    ///
+    /// Call [`Redactor::redact`](crate::redaction::Redactor::redact) on each
+    /// frame before it is stored so its text is masked.
+    pub fn redactor(&self) -> Arc<crate::redaction::Redactor> {
+        Arc::clone(&self.state.redactions)
+    }
+
+    /// Receipt and invoice extraction
+    ///
+    /// Call [`DocumentExtractor::check_frame`](crate::documents::DocumentExtractor::check_frame)
+    /// for each stored frame to extract the documents it shows.
+    pub fn document_extractor(&self) -> Arc<crate::documents::DocumentExtractor> {
+        Arc::clone(&self.state.documents)
+    }
+
+    /// Embedding engine, once loaded by the worker or a request
+    pub async fn embedding_engine(
+        &self,
+    ) -> Option<Arc<screensearch_embeddings::EmbeddingEngine>> {
+        self.state.embedding_engine.read().await.clone()
+    }
+
+    /// Update status and install requests
+    ///
+    /// The updater publishes its progress here and waits for the install
+    /// requests made through `/admin/update`.
+    pub fn update_control(&self) -> Arc<crate::updates::UpdateControl> {
+        Arc::clone(&self.state.updates)
+    }
+
+    /// Capture profiles and switch requests
+    ///
+    /// The application publishes its profiles here and waits for the switch
+    /// requests made through `/profiles`.
+    pub fn profile_control(&self) -> Arc<crate::profiles::ProfileControl> {
+        Arc::clone(&self.state.profiles)
+    }
+
+    /// Data directory move requests
+    ///
+    /// The application sets the directory that can be moved and waits for
+    /// the move requests made through `/admin/move-data`.
+    pub fn data_move_control(&self) -> Arc<crate::data_move::DataMoveControl> {
+        Arc::clone(&self.state.data_move)
+    }
+
+    /// Report startup recovery findings through `/health`
+    pub fn set_recovery_report(&self, report: crate::models::RecoveryReport) {
+        let _ = self.state.recovery.set(report);
+    }
+
+    /// Serve the log files rolled from `log_file` through `/logs`
+    pub fn set_log_file(&self, log_file: &std::path::Path) {
+        let _ = self
+            .state
+            .log_files
+            .set(crate::logs::LogFiles::new(log_file));
+    }
+
+    /// Write and read capture files through `storage` instead of the local
+    /// files given by their full path
+    pub fn set_capture_storage(&self, storage: Arc<crate::capture_store::CaptureStorage>) {
+        let _ = self.state.storage.set(storage);
+    }
+
+    /// Retrieve report context with `config` instead of the defaults
+    pub fn set_rag_config(&self, config: crate::handlers::rag_helpers::RagConfig) {
+        let _ = self.state.rag.set(config);
+    }
+
+    /// Map time-tracking exports to projects with `config`
+    pub fn set_timesheet_config(&self, config: crate::timesheet::TimesheetConfig) {
+        let _ = self.state.timesheet.set(config);
+    }
+
+    /// Serve the provider "builtin" with the model described by `config`
+    pub fn set_local_llm_config(&self, config: crate::local_llm::LocalLlmConfig) {
+        self.state.local_llm.set_config(config);
+    }
+
+    /// Let first-run `/setup` write its choices to `config.toml` and move
+    /// the data to another directory
+    pub fn set_setup_paths(&self, paths: crate::setup::SetupPaths) {
+        self.state.setup.set_paths(paths);
+    }
+
+    /// Restore the stored pause state, excluded apps and capture schedule
+    ///
+    /// The capture interval and monitor list keep their configured values
+    /// until they are next changed through `/settings`.
+    pub async fn restore_capture_settings(&self) -> anyhow::Result<()> {
+        let settings = self
+            .state
+            .db
+            .get_settings()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load settings: {}", e))?;
+
+        self.state.capture.set_paused(settings.is_paused != 0);
+        match serde_json::from_str::<Vec<String>>(&settings.excluded_apps) {
+            Ok(apps) => self.state.capture.set_excluded_apps(apps),
+            Err(e) => tracing::warn!("Ignoring invalid excluded_apps setting: {}", e),
+        }
+        self.state.apply_capture_schedule(&settings);
+
+        Ok(())
+    }
+
     /// Build the Axum router with middleware
     fn build_router(&self) -> axum::Router {
         use axum::extract::DefaultBodyLimit;
@@ -76,9 +227,13 @@ impl ApiServer {
                 origin
                     .to_str()
                     .map(|s| {
-                        s.starts_with("http://localhost:")
-                            || s.starts_with("http://127.0.0.1:")
-                            || s.starts_with("http://[::1]:")
+                        let s = s
+                            .strip_prefix("http://")
+                            .or_else(|| s.strip_prefix("https://"))
+                            .unwrap_or("");
+                        s.starts_with("localhost:")
+                            || s.starts_with("127.0.0.1:")
+                            || s.starts_with("[::1]:")
                     })
                     .unwrap_or(false)
             }))
@@ -97,13 +252,19 @@ impl ApiServer {
                 HeaderName::from_static("user-agent"),
                 HeaderName::from_static("cache-control"),
                 HeaderName::from_static("pragma"),
+                HeaderName::from_static(crate::audit::CALLER_HEADER),
             ]))
             .allow_credentials(true);
 
         routes::build_router(Arc::clone(&self.state))
             .layer(DefaultBodyLimit::max(1024 * 1024)) // 1 MiB (1024 * 1024 bytes) max request body
             .layer(cors)
-            .layer(TraceLayer::new_for_http())
+            // Request spans at INFO so they reach the OTLP exporter at the
+            // default log level; handler and query spans nest under them
+            .layer(
+                TraceLayer::new_for_http()
+                    .make_span_with(DefaultMakeSpan::new().level(tracing::Level::INFO)),
+            )
     }
 
     /// Run the API server
@@ -111,10 +272,39 @@ impl ApiServer {
     /// Starts the HTTP server and blocks until shutdown.
     pub async fn run(self) -> anyhow::Result<()> {
         let app = self.build_router();
+
+        if let Some(name) = &self.config.local_socket {
+            return crate::transport::serve_local(app, name).await;
+        }
+
         let addr = format!("{}:{}", self.config.host, self.config.port);
+        let scheme = if self.config.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+
+        tracing::info!("Starting API server on {}://{}", scheme, addr);
+        tracing::info!(
+            "API documentation available at {}://{}/health",
+            scheme,
+            addr
+        );
+
+        if let Some(tls) = &self.config.tls {
+            let rustls_config = tls.load(&self.config.host).await?;
+
+            let listener = std::net::TcpListener::bind(&addr)
+                .map_err(|e| anyhow::anyhow!("Failed to bind to {}: {}", addr, e))?;
+            listener.set_nonblocking(true)?;
+
+            axum_server::from_tcp_rustls(listener, rustls_config)
+                .serve(app.into_make_service())
+                .await
+                .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
 
-        tracing::info!("Starting API server on http://{}", addr);
-        tracing::info!("API documentation available at http://{}/health", addr);
+            return Ok(());
+        }
 
         let listener = tokio::net::TcpListener::bind(&addr)
             .await
@@ -127,6 +317,24 @@ impl ApiServer {
         Ok(())
     }
 
+    /// Start the background job queue
+    ///
+    /// Runs queued jobs of every registered type, including jobs left over
+    /// from a previous run.
+    pub fn start_job_queue(&self) {
+        tracing::info!("Starting background job queue...");
+
+        let jobs = Arc::clone(&self.state.jobs);
+        tokio::spawn(jobs.run(Arc::clone(&self.state)));
+    }
+
+    /// Start the periodic data retention worker
+    pub fn start_retention_worker(&self) {
+        tracing::info!("Starting data retention worker...");
+
+        crate::workers::retention::spawn_retention_worker(Arc::clone(&self.state));
+    }
+
     /// Start the background embedding worker
     pub async fn start_embedding_worker(
         &self,
@@ -148,11 +356,101 @@ impl ApiServer {
         crate::workers::embedding_worker::spawn_embedding_worker(
             std::sync::Arc::clone(&self.state.db),
             engine,
+            std::sync::Arc::clone(&self.state.capture),
             config,
         );
 
         Ok(())
     }
+
+    /// Start the scheduled report worker
+    pub fn start_report_scheduler(
+        &self,
+        config: crate::workers::report_scheduler::ReportSchedulerConfig,
+    ) {
+        if !config.enabled {
+            return;
+        }
+
+        tracing::info!("Starting scheduled report worker...");
+
+        self.state
+            .jobs
+            .register(Arc::new(ReportJob::new(config.clone())));
+
+        crate::workers::report_scheduler::spawn_report_scheduler(Arc::clone(&self.state), config);
+    }
+
+    /// Start the frame compaction worker
+    ///
+    /// Frames compacted earlier are decoded with `config.ffmpeg_path` even
+    /// when compaction is disabled.
+    pub fn start_compaction_worker(&self, config: crate::workers::compaction::CompactionConfig) {
+        let _ = self.state.compaction.set(config.clone());
+        if !config.enabled {
+            return;
+        }
+
+        tracing::info!("Starting frame compaction worker...");
+
+        self.state
+            .jobs
+            .register(Arc::new(CompactionJob::new(config.clone())));
+
+        crate::workers::compaction::spawn_compaction_worker(Arc::clone(&self.state), config);
+    }
+
+    /// Start the cold-tier archival worker
+    ///
+    /// Frames archived earlier are read from their archive either way.
+    pub fn start_archival_worker(&self, config: crate::workers::archival::ArchivalConfig) {
+        if !config.enabled {
+            return;
+        }
+
+        tracing::info!("Starting cold-tier archival worker...");
+
+        self.state
+            .jobs
+            .register(Arc::new(ArchivalJob::new(config.clone())));
+
+        crate::workers::archival::spawn_archival_worker(Arc::clone(&self.state), config);
+    }
+
+    /// Start the daily notes export
+    ///
+    /// Notes are exported through `/export/markdown` once a vault is set,
+    /// and every day when the schedule is enabled.
+    pub fn start_markdown_export(&self, config: crate::workers::MarkdownExportConfig) {
+        if config.vault_dir.as_os_str().is_empty() {
+            return;
+        }
+        let _ = self.state.markdown_export.set(config.clone());
+
+        self.state
+            .jobs
+            .register(Arc::new(MarkdownExportJob::new(config.clone())));
+
+        if config.enabled {
+            tracing::info!("Starting daily notes export...");
+            crate::workers::spawn_markdown_export_scheduler(Arc::clone(&self.state), config);
+        }
+    }
+
+    /// Start the hourly activity summary worker
+    pub fn start_summarizer(&self, config: crate::workers::summarizer::SummarizerConfig) {
+        if !config.enabled {
+            return;
+        }
+
+        tracing::info!("Starting activity summary worker...");
+
+        self.state
+            .jobs
+            .register(Arc::new(SummaryJob::new(config.clone())));
+
+        crate::workers::summarizer::spawn_summarizer(Arc::clone(&self.state), config);
+    }
 }
 
 #[cfg(test)]
@@ -164,6 +462,7 @@ mod tests {
         let config = ApiConfig::default();
         assert_eq!(config.host, "127.0.0.1");
         assert_eq!(config.port, 3131);
+        assert!(config.tls.is_none());
     }
 
     #[test]
@@ -172,6 +471,8 @@ mod tests {
             host: "0.0.0.0".to_string(),
             port: 8080,
             database_path: "test.db".to_string(),
+            tls: None,
+            local_socket: None,
         };
         assert_eq!(config.port, 8080);
         assert_eq!(config.database_path, "test.db");
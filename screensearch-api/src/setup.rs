@@ -0,0 +1,417 @@
+//! First-run setup
+//!
+//! A new install asks a few questions before settling on defaults: where to
+//! keep its data, how long to keep it, which monitors to capture, which
+//! applications to exclude, whether to download the embedding model and
+//! whether to enable semantic search. `/setup` takes the answers one step at
+//! a time, in [`SetupStep`] order (earlier steps can be answered again), and
+//! completing it writes them to `config.toml` and the settings table.
+//!
+//! The progress is kept in the `setup_state` metadata entry, so the web
+//! interface can pick up where it left off after a restart.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokio::sync::Mutex;
+use toml_edit::{value, Array, DocumentMut};
+
+/// Metadata key of the setup progress
+pub const STATE_KEY: &str = "setup_state";
+
+/// Metadata key set once setup is complete
+pub const COMPLETED_KEY: &str = "setup_completed";
+
+/// Steps of the setup, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    /// Where to keep the database and captures
+    DataDirectory,
+    /// How many days of captures to keep
+    Retention,
+    /// Which monitors to capture
+    Monitors,
+    /// Which applications never to capture
+    Privacy,
+    /// Whether to download the embedding model now
+    Models,
+    /// Whether to enable semantic search
+    Features,
+    /// All steps answered, waiting for `/setup/complete`
+    Review,
+    /// Choices saved
+    Done,
+}
+
+impl SetupStep {
+    fn next(self) -> Self {
+        match self {
+            Self::DataDirectory => Self::Retention,
+            Self::Retention => Self::Monitors,
+            Self::Monitors => Self::Privacy,
+            Self::Privacy => Self::Models,
+            Self::Models => Self::Features,
+            Self::Features => Self::Review,
+            Self::Review | Self::Done => Self::Done,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::DataDirectory => "data_directory",
+            Self::Retention => "retention",
+            Self::Monitors => "monitors",
+            Self::Privacy => "privacy",
+            Self::Models => "models",
+            Self::Features => "features",
+            Self::Review => "review",
+            Self::Done => "done",
+        }
+    }
+}
+
+/// Choices made so far, starting from the current settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupChoices {
+    /// Directory for the database and captures (`None` = the default)
+    pub data_dir: Option<String>,
+
+    /// Days to keep captured frames
+    pub retention_days: i64,
+
+    /// Monitor indices to capture (empty = all)
+    pub monitors: Vec<usize>,
+
+    /// Applications never to capture, by process name
+    pub excluded_apps: Vec<String>,
+
+    /// Download the embedding model when setup completes
+    pub download_model: bool,
+
+    /// Generate embeddings for semantic search
+    pub enable_embeddings: bool,
+}
+
+/// Answer to one setup step
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum SetupAnswer {
+    DataDirectory { data_dir: Option<String> },
+    Retention { retention_days: i64 },
+    Monitors { monitors: Vec<usize> },
+    Privacy { excluded_apps: Vec<String> },
+    Models { download_model: bool },
+    Features { enable_embeddings: bool },
+}
+
+impl SetupAnswer {
+    /// Step this answers
+    pub fn step(&self) -> SetupStep {
+        match self {
+            Self::DataDirectory { .. } => SetupStep::DataDirectory,
+            Self::Retention { .. } => SetupStep::Retention,
+            Self::Monitors { .. } => SetupStep::Monitors,
+            Self::Privacy { .. } => SetupStep::Privacy,
+            Self::Models { .. } => SetupStep::Models,
+            Self::Features { .. } => SetupStep::Features,
+        }
+    }
+}
+
+/// Setup progress, stored under [`STATE_KEY`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupState {
+    /// Next step to answer
+    pub step: SetupStep,
+
+    pub choices: SetupChoices,
+}
+
+impl SetupState {
+    /// Start setup from the current settings
+    pub fn new(choices: SetupChoices) -> Self {
+        Self {
+            step: SetupStep::DataDirectory,
+            choices,
+        }
+    }
+
+    /// Record an answer and move past its step
+    ///
+    /// Returns why the answer was rejected: setup is already complete, an
+    /// earlier step is unanswered or the value is invalid.
+    pub fn answer(&mut self, answer: SetupAnswer) -> Result<(), String> {
+        if self.step == SetupStep::Done {
+            return Err("Setup is already complete".to_string());
+        }
+        let step = answer.step();
+        if step > self.step {
+            return Err(format!("Answer the {} step first", self.step.name()));
+        }
+
+        match answer {
+            SetupAnswer::DataDirectory { data_dir } => {
+                let data_dir = data_dir.filter(|dir| !dir.trim().is_empty());
+                if let Some(dir) = &data_dir {
+                    let path = Path::new(dir);
+                    if !path.is_absolute() {
+                        return Err("The data directory must be an absolute path".to_string());
+                    }
+                    if path.exists() && !path.is_dir() {
+                        return Err(format!("{} is not a directory", dir));
+                    }
+                }
+                self.choices.data_dir = data_dir;
+            }
+            SetupAnswer::Retention { retention_days } => {
+                if retention_days < 1 {
+                    return Err("Retention days must be at least 1 day".to_string());
+                }
+                self.choices.retention_days = retention_days;
+            }
+            SetupAnswer::Monitors { monitors } => self.choices.monitors = monitors,
+            SetupAnswer::Privacy { excluded_apps } => {
+                self.choices.excluded_apps = excluded_apps
+                    .into_iter()
+                    .map(|app| app.trim().to_string())
+                    .filter(|app| !app.is_empty())
+                    .collect();
+            }
+            SetupAnswer::Models { download_model } => self.choices.download_model = download_model,
+            SetupAnswer::Features { enable_embeddings } => {
+                self.choices.enable_embeddings = enable_embeddings
+            }
+        }
+
+        self.step = self.step.max(step.next());
+        Ok(())
+    }
+
+    /// Mark setup complete once every step is answered
+    pub fn complete(&mut self) -> Result<(), String> {
+        match self.step {
+            SetupStep::Review => {
+                self.step = SetupStep::Done;
+                Ok(())
+            }
+            SetupStep::Done => Err("Setup is already complete".to_string()),
+            step => Err(format!("Answer the {} step first", step.name())),
+        }
+    }
+}
+
+/// Where the running application keeps its files
+#[derive(Debug, Clone)]
+pub struct SetupPaths {
+    /// Configuration file the choices are written to
+    pub config_path: PathBuf,
+
+    /// Directory holding the database and captures
+    pub data_dir: PathBuf,
+
+    /// Database file
+    pub database_path: PathBuf,
+}
+
+/// Setup state shared by the `/setup` handlers
+#[derive(Debug, Default)]
+pub struct SetupControl {
+    paths: OnceLock<SetupPaths>,
+
+    /// Serializes reading and writing the stored progress
+    pub lock: Mutex<()>,
+}
+
+impl SetupControl {
+    /// Set where the application keeps its files
+    pub fn set_paths(&self, paths: SetupPaths) {
+        let _ = self.paths.set(paths);
+    }
+
+    /// Where the application keeps its files, when known
+    pub fn paths(&self) -> Option<&SetupPaths> {
+        self.paths.get()
+    }
+}
+
+/// Write the choices that live in `config.toml`, keeping its comments
+///
+/// Sets `storage.data_dir`, `capture.monitor_indices` and
+/// `embeddings.enabled`. Returns whether a setting that only applies after
+/// a restart changed.
+pub fn write_config(path: &Path, choices: &SetupChoices) -> anyhow::Result<bool> {
+    let mut doc = read_config(path)?;
+
+    let data_dir = choices.data_dir.clone().unwrap_or_default();
+    let restart = doc
+        .get("storage")
+        .and_then(|storage| storage.get("data_dir"))
+        .and_then(|dir| dir.as_str())
+        .unwrap_or_default()
+        != data_dir
+        || doc
+            .get("embeddings")
+            .and_then(|embeddings| embeddings.get("enabled"))
+            .and_then(|enabled| enabled.as_bool())
+            .unwrap_or(false)
+            != choices.enable_embeddings;
+
+    doc["storage"]["data_dir"] = value(data_dir);
+    doc["capture"]["monitor_indices"] = value(
+        choices
+            .monitors
+            .iter()
+            .map(|&i| i as i64)
+            .collect::<Array>(),
+    );
+    doc["embeddings"]["enabled"] = value(choices.enable_embeddings);
+
+    replace_config(path, &doc)?;
+    Ok(restart)
+}
+
+/// Set `storage.data_dir` in `config.toml`, keeping its comments
+pub fn write_data_dir(path: &Path, data_dir: &str) -> anyhow::Result<()> {
+    let mut doc = read_config(path)?;
+    doc["storage"]["data_dir"] = value(data_dir);
+    replace_config(path, &doc)
+}
+
+fn read_config(path: &Path) -> anyhow::Result<DocumentMut> {
+    use anyhow::Context;
+
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    text.parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Replace the file in one step, so the config watcher never sees half of it
+fn replace_config(path: &Path, doc: &DocumentMut) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let temp = path.with_extension("toml.tmp");
+    std::fs::write(&temp, doc.to_string())
+        .with_context(|| format!("Failed to write {}", temp.display()))?;
+    std::fs::rename(&temp, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choices() -> SetupChoices {
+        SetupChoices {
+            data_dir: None,
+            retention_days: 30,
+            monitors: Vec::new(),
+            excluded_apps: Vec::new(),
+            download_model: false,
+            enable_embeddings: false,
+        }
+    }
+
+    #[test]
+    fn test_steps_are_answered_in_order() {
+        let mut state = SetupState::new(choices());
+
+        let skip = state.answer(SetupAnswer::Monitors { monitors: vec![1] });
+        assert_eq!(
+            skip,
+            Err("Answer the data_directory step first".to_string())
+        );
+
+        state
+            .answer(SetupAnswer::DataDirectory { data_dir: None })
+            .unwrap();
+        assert!(state
+            .answer(SetupAnswer::Retention { retention_days: 0 })
+            .is_err());
+        state
+            .answer(SetupAnswer::Retention { retention_days: 7 })
+            .unwrap();
+        assert_eq!(state.step, SetupStep::Monitors);
+        assert!(state.complete().is_err());
+
+        // Going back keeps the progress
+        state
+            .answer(SetupAnswer::Retention { retention_days: 14 })
+            .unwrap();
+        assert_eq!(state.step, SetupStep::Monitors);
+        assert_eq!(state.choices.retention_days, 14);
+
+        state
+            .answer(SetupAnswer::Monitors { monitors: vec![0] })
+            .unwrap();
+        state
+            .answer(SetupAnswer::Privacy {
+                excluded_apps: vec![" keepass.exe ".to_string(), String::new()],
+            })
+            .unwrap();
+        state
+            .answer(SetupAnswer::Models {
+                download_model: true,
+            })
+            .unwrap();
+        state
+            .answer(SetupAnswer::Features {
+                enable_embeddings: true,
+            })
+            .unwrap();
+        assert_eq!(state.choices.excluded_apps, vec!["keepass.exe"]);
+
+        state.complete().unwrap();
+        assert_eq!(state.step, SetupStep::Done);
+        assert!(state.complete().is_err());
+        assert!(state
+            .answer(SetupAnswer::Models {
+                download_model: false
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_data_directory_must_be_absolute() {
+        let mut state = SetupState::new(choices());
+        assert!(state
+            .answer(SetupAnswer::DataDirectory {
+                data_dir: Some("screensearch".to_string()),
+            })
+            .is_err());
+
+        // Blank means the default
+        state
+            .answer(SetupAnswer::DataDirectory {
+                data_dir: Some(" ".to_string()),
+            })
+            .unwrap();
+        assert_eq!(state.choices.data_dir, None);
+    }
+
+    #[test]
+    fn test_write_config_keeps_comments() {
+        let path =
+            std::env::temp_dir().join(format!("screensearch-setup-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[capture]\n# Monitors to capture\nmonitor_indices = []\n\n\
+             [storage]\nformat = \"jpeg\"\n\n[embeddings]\nenabled = false\n",
+        )
+        .unwrap();
+
+        let mut choices = choices();
+        choices.monitors = vec![0, 2];
+        assert!(!write_config(&path, &choices).unwrap());
+
+        choices.enable_embeddings = true;
+        assert!(write_config(&path, &choices).unwrap());
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("# Monitors to capture"));
+        assert!(text.contains("monitor_indices = [0, 2]"));
+        assert!(text.contains("data_dir = \"\""));
+        assert!(text.contains("enabled = true"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,10 +1,28 @@
 //! Application state management
 
-use screensearch_automation::AutomationEngine;
-use screensearch_db::DatabaseManager;
+use crate::alerts::AlertWatcher;
+use crate::capture_store::CaptureStorage;
+use crate::data_move::DataMoveControl;
+use crate::documents::DocumentExtractor;
+use crate::element_handles::ElementHandles;
+use crate::events::{ServerEvent, UiEventForwarder, EVENT_CHANNEL_CAPACITY};
+use crate::handlers::rag_helpers::RagConfig;
+use crate::local_llm::LocalLlmControl;
+use crate::logs::LogFiles;
+use crate::models::RecoveryReport;
+use crate::profiles::ProfileControl;
+use crate::redaction::Redactor;
+use crate::setup::SetupControl;
+use crate::timesheet::TimesheetConfig;
+use crate::updates::UpdateControl;
+use crate::workers::{CompactionConfig, JobQueue, JobQueueConfig, MarkdownExportConfig};
+use screensearch_automation::{AutomationEngine, MacroRecorder};
+use screensearch_capture::{CaptureControl, CaptureSchedule};
+use screensearch_db::{DatabaseManager, SettingsRecord};
 use screensearch_embeddings::EmbeddingEngine;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{broadcast, Mutex as AsyncMutex, RwLock};
 
 /// Shared application state
 #[derive(Clone)]
@@ -17,16 +35,158 @@ pub struct AppState {
 
     /// Embedding engine for semantic search (lazy initialized)
     pub embedding_engine: Arc<RwLock<Option<Arc<EmbeddingEngine>>>>,
+
+    /// Runtime controls of the capture pipeline, updated by `/settings`
+    pub capture: Arc<CaptureControl>,
+
+    /// Events pushed to `/events` WebSocket subscribers
+    pub events: broadcast::Sender<ServerEvent>,
+
+    /// UIAutomation events forwarded to `/events` clients that ask for them
+    pub ui_events: Arc<UiEventForwarder>,
+
+    /// Keyword watch alerts evaluated against stored frames
+    pub alerts: Arc<AlertWatcher>,
+
+    /// Redaction rules applied to frames as they are stored
+    pub redactions: Arc<Redactor>,
+
+    /// Receipt and invoice extraction from stored frames
+    pub documents: Arc<DocumentExtractor>,
+
+    /// Background job queue and its registered job handlers
+    pub jobs: Arc<JobQueue>,
+
+    /// Agent kill switch; bumped by `/agent/stop` to abort all running agents
+    pub agent_stop_generation: Arc<AtomicU64>,
+
+    /// Active macro recording, if any
+    pub macro_recorder: Arc<Mutex<Option<MacroRecorder>>>,
+
+    /// Serializes `/automation/workflow` runs so their input does not interleave
+    pub workflow_lock: Arc<AsyncMutex<()>>,
+
+    /// Elements returned by `/automation/find-elements`, by handle
+    pub element_handles: Arc<ElementHandles>,
+
+    /// Startup recovery findings, set when the previous run crashed
+    pub recovery: Arc<OnceLock<RecoveryReport>>,
+
+    /// Rolling log files served by `/logs`, set when logging to a file
+    pub log_files: Arc<OnceLock<LogFiles>>,
+
+    /// Context retrieval settings for reports, defaults until set
+    pub rag: Arc<OnceLock<RagConfig>>,
+
+    /// Frame compaction settings, also used to decode compacted frames;
+    /// defaults until set
+    pub compaction: Arc<OnceLock<CompactionConfig>>,
+
+    /// Where capture files are written and read, local files until set
+    pub storage: Arc<OnceLock<Arc<CaptureStorage>>>,
+
+    /// Daily notes export settings, set when a vault is configured
+    pub markdown_export: Arc<OnceLock<MarkdownExportConfig>>,
+
+    /// Time-tracking export settings, defaults until set
+    pub timesheet: Arc<OnceLock<TimesheetConfig>>,
+
+    /// Built-in llama.cpp model, used by the provider "builtin"
+    pub local_llm: Arc<LocalLlmControl>,
+
+    /// Update status and install requests, served by `/admin/update`
+    pub updates: Arc<UpdateControl>,
+
+    /// Application paths and progress lock for first-run `/setup`
+    pub setup: Arc<SetupControl>,
+
+    /// Capture profiles and switch requests, served by `/profiles`
+    pub profiles: Arc<ProfileControl>,
+
+    /// Data directory move requests, served by `/admin/move-data`
+    pub data_move: Arc<DataMoveControl>,
 }
 
 impl AppState {
     /// Create new application state
     pub fn new(db: DatabaseManager, automation: AutomationEngine) -> Self {
+        let db = Arc::new(db);
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
         Self {
-            db: Arc::new(db),
+            alerts: Arc::new(AlertWatcher::new(Arc::clone(&db), events.clone())),
+            ui_events: Arc::new(UiEventForwarder::new(events.clone())),
+            redactions: Arc::new(Redactor::new(Arc::clone(&db))),
+            documents: Arc::new(DocumentExtractor::new(Arc::clone(&db))),
+            db,
             automation: Arc::new(automation),
             embedding_engine: Arc::new(RwLock::new(None)),
+            capture: Arc::new(CaptureControl::default()),
+            events,
+            jobs: Arc::new(JobQueue::new(JobQueueConfig::default())),
+            agent_stop_generation: Arc::new(AtomicU64::new(0)),
+            macro_recorder: Arc::new(Mutex::new(None)),
+            workflow_lock: Arc::new(AsyncMutex::new(())),
+            element_handles: Arc::new(ElementHandles::default()),
+            recovery: Arc::new(OnceLock::new()),
+            log_files: Arc::new(OnceLock::new()),
+            rag: Arc::new(OnceLock::new()),
+            compaction: Arc::new(OnceLock::new()),
+            storage: Arc::new(OnceLock::new()),
+            markdown_export: Arc::new(OnceLock::new()),
+            timesheet: Arc::new(OnceLock::new()),
+            local_llm: Arc::new(LocalLlmControl::default()),
+            updates: Arc::new(UpdateControl::default()),
+            setup: Arc::new(SetupControl::default()),
+            profiles: Arc::new(ProfileControl::default()),
+            data_move: Arc::new(DataMoveControl::default()),
+        }
+    }
+
+    /// Apply stored settings to the running capture pipeline
+    ///
+    /// Monitor and excluded app lists that are not valid JSON arrays, and
+    /// invalid capture schedules, are left unchanged.
+    pub fn apply_capture_settings(&self, settings: &SettingsRecord) {
+        self.capture
+            .set_interval_ms(settings.capture_interval.max(1) as u64 * 1000);
+        self.capture.set_paused(settings.is_paused != 0);
+
+        match serde_json::from_str::<Vec<usize>>(&settings.monitors) {
+            Ok(monitors) => self.capture.set_monitor_indices(monitors),
+            Err(e) => tracing::warn!("Ignoring invalid monitors setting: {}", e),
+        }
+
+        match serde_json::from_str::<Vec<String>>(&settings.excluded_apps) {
+            Ok(apps) => self.capture.set_excluded_apps(apps),
+            Err(e) => tracing::warn!("Ignoring invalid excluded_apps setting: {}", e),
         }
+
+        self.apply_capture_schedule(settings);
+    }
+
+    /// Apply the stored capture schedule to the capture loops
+    pub fn apply_capture_schedule(&self, settings: &SettingsRecord) {
+        let schedule = serde_json::from_str::<Vec<String>>(&settings.capture_schedule)
+            .map_err(|e| e.to_string())
+            .and_then(|rules| CaptureSchedule::parse(&rules));
+        match schedule {
+            Ok(schedule) => self.capture.set_schedule(schedule),
+            Err(e) => tracing::warn!("Ignoring invalid capture_schedule setting: {}", e),
+        }
+    }
+
+    /// ffmpeg executable used to decode frames from video chunks
+    pub fn ffmpeg_path(&self) -> &str {
+        self.compaction
+            .get()
+            .map(|config| config.ffmpeg_path.as_str())
+            .unwrap_or("ffmpeg")
+    }
+
+    /// Capture file storage
+    pub fn capture_storage(&self) -> Arc<CaptureStorage> {
+        Arc::clone(self.storage.get_or_init(Default::default))
     }
 
     /// Get or initialize the embedding engine
@@ -52,5 +212,3 @@ impl AppState {
         Ok(engine_arc)
     }
 }
-
-
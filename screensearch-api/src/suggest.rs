@@ -0,0 +1,159 @@
+//! Search suggestions
+//!
+//! Completions for a search box, drawn from past searches, tag names,
+//! application names and the words of captured text. Each source is ranked
+//! by how often its values occur, scaled to the most frequent one, and
+//! weighted by how likely it is to be what the user is typing: a query they
+//! searched for before ranks above a word that merely appears on screen.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Where a suggestion comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionSource {
+    History,
+    Tag,
+    App,
+    Term,
+}
+
+impl SuggestionSource {
+    /// Score of the most frequent value of this source
+    fn weight(self) -> f64 {
+        match self {
+            Self::History => 4.0,
+            Self::Tag => 3.0,
+            Self::App => 2.0,
+            Self::Term => 1.0,
+        }
+    }
+}
+
+/// A ranked completion
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub text: String,
+    pub source: SuggestionSource,
+    /// Past searches, tagged frames, captured frames or OCR text records
+    /// behind the suggestion
+    pub count: i64,
+    pub score: f64,
+}
+
+/// Rank the values found in each source into at most `limit` suggestions
+///
+/// `terms` complete the last word of `prefix`; the other sources complete
+/// all of it. A value offered by several sources is suggested once, with
+/// the scores added up, as the source that scored it highest.
+pub fn rank_suggestions(
+    prefix: &str,
+    sources: &[(SuggestionSource, Vec<(String, i64)>)],
+    limit: usize,
+) -> Vec<Suggestion> {
+    // Everything up to the word being typed
+    let head = match prefix.rfind(char::is_whitespace) {
+        Some(i) => &prefix[..=i],
+        None => "",
+    };
+
+    let mut ranked: Vec<Suggestion> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for (source, values) in sources {
+        let max_count = values.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        for (value, count) in values {
+            let text = match source {
+                SuggestionSource::Term => format!("{}{}", head, value),
+                _ => value.clone(),
+            };
+            let score = source.weight() * frequency(*count, max_count);
+
+            match positions.get(&text.to_lowercase()) {
+                Some(&index) => {
+                    let existing = &mut ranked[index];
+                    if score > existing.score {
+                        existing.source = *source;
+                        existing.count = *count;
+                    }
+                    existing.score += score;
+                }
+                None => {
+                    positions.insert(text.to_lowercase(), ranked.len());
+                    ranked.push(Suggestion {
+                        text,
+                        source: *source,
+                        count: *count,
+                        score,
+                    });
+                }
+            }
+        }
+    }
+
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.text.len().cmp(&b.text.len()))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Frequency of a value relative to the most frequent one, between 0.5 and 1
+fn frequency(count: i64, max_count: i64) -> f64 {
+    if max_count <= 0 {
+        return 0.5;
+    }
+    0.5 + 0.5 * (1.0 + count.max(0) as f64).ln() / (1.0 + max_count as f64).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(items: &[(&str, i64)]) -> Vec<(String, i64)> {
+        items.iter().map(|(v, c)| (v.to_string(), *c)).collect()
+    }
+
+    #[test]
+    fn test_history_ranks_above_frequent_terms() {
+        let suggestions = rank_suggestions(
+            "dep",
+            &[
+                (SuggestionSource::History, values(&[("deploy pipeline", 1)])),
+                (
+                    SuggestionSource::Term,
+                    values(&[("deployment", 5000), ("depot", 3)]),
+                ),
+            ],
+            10,
+        );
+
+        let texts: Vec<&str> = suggestions.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["deploy pipeline", "deployment", "depot"]);
+        assert_eq!(suggestions[0].source, SuggestionSource::History);
+    }
+
+    #[test]
+    fn test_terms_complete_last_word_and_merge() {
+        let suggestions = rank_suggestions(
+            "release not",
+            &[
+                (SuggestionSource::History, values(&[("Release notes", 2)])),
+                (
+                    SuggestionSource::Term,
+                    values(&[("notes", 40), ("notion", 9)]),
+                ),
+            ],
+            1,
+        );
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].text, "Release notes");
+        assert_eq!(suggestions[0].source, SuggestionSource::History);
+        assert!(suggestions[0].score > SuggestionSource::History.weight());
+    }
+}
@@ -0,0 +1,292 @@
+//! Delta sync between machines
+//!
+//! One machine lists its frames after a cursor with `GET /sync/changes`, and
+//! another stores them with `POST /sync/apply`, so two installs (say a
+//! desktop and a laptop) can share one searchable history. A batch carries
+//! each frame's metadata, OCR text and tag names, and optionally its image.
+//!
+//! Frame IDs are only unique within a database, so every frame travels with
+//! its origin: the ID of the machine that captured it (see
+//! [`screensearch_db::DatabaseManager::machine_id`]) and its frame ID there.
+//! The receiver stores the frame under a new ID and remembers the origin,
+//! which makes applying a batch twice harmless and lets frames pass through
+//! a machine to a third one without coming back to where they started.
+//!
+//! The cursor is the sender's frame ID: changes are frames captured (or
+//! received) since, so tags added later to frames already sent do not
+//! follow.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use screensearch_db::{
+    FrameRecord, NewFrame, NewOcrText, NewSyncedFrame, OcrTextRecord, SyncOrigin,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Frames in a batch by default
+pub const DEFAULT_BATCH: i64 = 100;
+/// Frames in a batch at most
+pub const MAX_BATCH: i64 = 1000;
+/// Largest `POST /sync/apply` body, room for a batch with images
+pub const MAX_APPLY_BYTES: usize = 256 * 1024 * 1024;
+
+/// Frames of a machine after a cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncBatch {
+    /// Machine the batch comes from
+    pub machine_id: String,
+    /// Cursor to ask for the next batch with
+    pub cursor: i64,
+    /// Whether more frames follow the cursor
+    pub has_more: bool,
+    pub frames: Vec<SyncFrame>,
+}
+
+/// A frame with its text and tags
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncFrame {
+    /// Machine that captured the frame
+    pub origin_machine: String,
+    /// Frame ID on that machine
+    pub origin_frame_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub device_name: String,
+    pub monitor_index: i32,
+    pub width: i32,
+    pub height: i32,
+    pub active_window: Option<String>,
+    pub active_process: Option<String>,
+    pub browser_url: Option<String>,
+    pub focused: Option<bool>,
+    pub text: Vec<SyncText>,
+    pub tags: Vec<String>,
+    /// Image file, base64 encoded, when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+/// OCR text of a frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncText {
+    pub text: String,
+    pub text_json: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub confidence: f32,
+}
+
+/// Origin of a local frame: where it was received from, or this machine
+pub fn origin_of(
+    frame: &FrameRecord,
+    origins: &HashMap<i64, SyncOrigin>,
+    machine_id: &str,
+) -> SyncOrigin {
+    origins
+        .get(&frame.id)
+        .cloned()
+        .unwrap_or_else(|| SyncOrigin {
+            machine_id: machine_id.to_string(),
+            frame_id: frame.id,
+        })
+}
+
+impl SyncFrame {
+    /// Frame to send, with its image file if given
+    pub fn new(
+        frame: &FrameRecord,
+        origin: SyncOrigin,
+        text: Vec<OcrTextRecord>,
+        tags: Vec<String>,
+        image: Option<&[u8]>,
+    ) -> Self {
+        Self {
+            origin_machine: origin.machine_id,
+            origin_frame_id: origin.frame_id,
+            timestamp: frame.timestamp,
+            device_name: frame.device_name.clone(),
+            monitor_index: frame.monitor_index,
+            width: frame.width,
+            height: frame.height,
+            active_window: frame.active_window.clone(),
+            active_process: frame.active_process.clone(),
+            browser_url: frame.browser_url.clone(),
+            focused: frame.focused,
+            text: text
+                .into_iter()
+                .map(|ocr| SyncText {
+                    text: ocr.text,
+                    text_json: ocr.text_json,
+                    x: ocr.x,
+                    y: ocr.y,
+                    width: ocr.width,
+                    height: ocr.height,
+                    confidence: ocr.confidence,
+                })
+                .collect(),
+            tags,
+            image: image.map(|data| BASE64.encode(data)),
+        }
+    }
+
+    pub fn origin(&self) -> SyncOrigin {
+        SyncOrigin {
+            machine_id: self.origin_machine.clone(),
+            frame_id: self.origin_frame_id,
+        }
+    }
+
+    /// Decoded image file, if the frame carries a valid one
+    pub fn image_data(&self) -> Option<Result<Vec<u8>, base64::DecodeError>> {
+        self.image.as_ref().map(|data| BASE64.decode(data))
+    }
+
+    /// Frame to store, with its image at `file_path` (empty without image)
+    pub fn into_new(self, file_path: String) -> NewSyncedFrame {
+        let origin = self.origin();
+        NewSyncedFrame {
+            frame: NewFrame {
+                timestamp: self.timestamp,
+                device_name: self.device_name,
+                file_path,
+                monitor_index: self.monitor_index,
+                width: self.width,
+                height: self.height,
+                offset_index: 0,
+                chunk_id: None,
+                active_window: self.active_window,
+                active_process: self.active_process,
+                browser_url: self.browser_url,
+                focused: self.focused,
+            },
+            ocr: self
+                .text
+                .into_iter()
+                .map(|text| NewOcrText {
+                    frame_id: 0,
+                    text: text.text,
+                    text_json: text.text_json,
+                    x: text.x,
+                    y: text.y,
+                    width: text.width,
+                    height: text.height,
+                    confidence: text.confidence,
+                })
+                .collect(),
+            tags: self.tags,
+            origin,
+        }
+    }
+}
+
+/// Name a received image is stored under
+pub fn image_name(origin: &SyncOrigin, data: &[u8]) -> String {
+    let machine: String = origin
+        .machine_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(16)
+        .collect();
+    let extension = image::guess_format(data)
+        .ok()
+        .and_then(|format| format.extensions_str().first().copied())
+        .unwrap_or("png");
+    format!("sync_{}_{}.{}", machine, origin.frame_id, extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: i64) -> FrameRecord {
+        FrameRecord {
+            id,
+            chunk_id: Some(3),
+            timestamp: DateTime::from_timestamp(1_765_357_200, 0).unwrap(),
+            monitor_index: 1,
+            device_name: "monitor-1".to_string(),
+            file_path: "C:\\captures\\frame.png".to_string(),
+            active_window: Some("main.rs".to_string()),
+            active_process: Some("code.exe".to_string()),
+            browser_url: None,
+            width: 1920,
+            height: 1080,
+            offset_index: 7,
+            focused: Some(true),
+            pinned: false,
+            created_at: DateTime::from_timestamp(1_765_357_201, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_origin_of() {
+        let mut origins = HashMap::new();
+        origins.insert(
+            2,
+            SyncOrigin {
+                machine_id: "laptop".to_string(),
+                frame_id: 90,
+            },
+        );
+
+        assert_eq!(
+            origin_of(&frame(1), &origins, "desktop"),
+            SyncOrigin {
+                machine_id: "desktop".to_string(),
+                frame_id: 1,
+            }
+        );
+        assert_eq!(origin_of(&frame(2), &origins, "desktop").frame_id, 90);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let ocr = OcrTextRecord {
+            id: 5,
+            frame_id: 1,
+            text: "fn main()".to_string(),
+            text_json: None,
+            x: 10,
+            y: 20,
+            width: 300,
+            height: 18,
+            confidence: 0.9,
+            created_at: Utc::now(),
+        };
+        let origin = SyncOrigin {
+            machine_id: "desktop".to_string(),
+            frame_id: 1,
+        };
+        let png = b"\x89PNG\r\n\x1a\n rest of the file";
+        let sent = SyncFrame::new(
+            &frame(1),
+            origin.clone(),
+            vec![ocr],
+            vec!["rust".to_string()],
+            Some(png),
+        );
+
+        // Through JSON, as between the two machines
+        let json = serde_json::to_string(&sent).unwrap();
+        let received: SyncFrame = serde_json::from_str(&json).unwrap();
+        assert_eq!(received, sent);
+        assert_eq!(received.image_data().unwrap().unwrap(), png);
+        assert_eq!(image_name(&origin, png), "sync_desktop_1.png");
+
+        let new = received.into_new("sync_desktop_1.png".to_string());
+        assert_eq!(new.origin, origin);
+        // Stored as a single image, outside any video chunk
+        assert_eq!(new.frame.chunk_id, None);
+        assert_eq!(new.frame.offset_index, 0);
+        assert_eq!(new.frame.active_process.as_deref(), Some("code.exe"));
+        assert_eq!(new.ocr[0].text, "fn main()");
+        assert_eq!(new.tags, vec!["rust"]);
+
+        // Without an image the field is left out
+        let bare = SyncFrame::new(&frame(1), origin, Vec::new(), Vec::new(), None);
+        assert!(!serde_json::to_string(&bare).unwrap().contains("image"));
+    }
+}
@@ -0,0 +1,230 @@
+//! On-screen text location
+//!
+//! Finds text on the monitors with the capture + OCR pipeline, for UIs whose
+//! controls UIAutomation cannot see (Electron apps, games, remote desktops).
+
+use screensearch_capture::{CaptureError, MonitorInfo, OcrEngine, ScreenCapture, TextRegion};
+use serde::Serialize;
+
+/// Text found on screen, in virtual-screen coordinates
+#[derive(Debug, Clone, Serialize)]
+pub struct TextMatch {
+    /// Matched text as recognized by OCR
+    pub text: String,
+
+    /// Index of the monitor the text is on
+    pub monitor: usize,
+
+    /// X coordinate of the bounding box
+    pub x: i32,
+
+    /// Y coordinate of the bounding box
+    pub y: i32,
+
+    /// Bounding box width
+    pub width: i32,
+
+    /// Bounding box height
+    pub height: i32,
+}
+
+impl TextMatch {
+    /// Center of the bounding box
+    pub fn center(&self) -> (i32, i32) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+}
+
+/// Bounding box of a match in OCR image coordinates
+#[derive(Debug, Clone, PartialEq)]
+struct ImageMatch {
+    text: String,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// Capture the monitors and find every occurrence of `text`
+///
+/// Matching ignores case and punctuation around words. Results are ordered
+/// by monitor, then top to bottom and left to right.
+pub async fn locate_text(
+    text: &str,
+    monitor: Option<usize>,
+) -> Result<Vec<TextMatch>, CaptureError> {
+    let needle = normalized_words(text);
+    if needle.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let monitors = match monitor {
+        Some(index) => vec![MonitorInfo::by_index(index)?],
+        None => MonitorInfo::enumerate()?,
+    };
+    let engine = OcrEngine::new().await?;
+
+    let mut matches = Vec::new();
+    for monitor in monitors {
+        let image =
+            ScreenCapture::capture_region(monitor.x, monitor.y, monitor.width, monitor.height)
+                .await?;
+        let result = engine.process_image(&image).await?;
+
+        // The capture may be in physical pixels while monitor bounds are not
+        let scale_x = monitor.width as f64 / image.width().max(1) as f64;
+        let scale_y = monitor.height as f64 / image.height().max(1) as f64;
+
+        let mut found: Vec<ImageMatch> = result
+            .regions
+            .iter()
+            .flat_map(|line| find_in_line(line, &needle))
+            .collect();
+        found.sort_by_key(|m| (m.y, m.x));
+
+        matches.extend(found.into_iter().map(|m| TextMatch {
+            text: m.text,
+            monitor: monitor.index,
+            x: monitor.x + (m.x as f64 * scale_x).round() as i32,
+            y: monitor.y + (m.y as f64 * scale_y).round() as i32,
+            width: (m.width as f64 * scale_x).round() as i32,
+            height: (m.height as f64 * scale_y).round() as i32,
+        }));
+    }
+
+    Ok(matches)
+}
+
+/// Occurrences of `needle` (normalized words) within an OCR line
+///
+/// Runs of consecutive word boxes are matched so a button label is located
+/// precisely even when OCR merges it with neighbouring text. Lines without
+/// word boxes match on their text and return the whole line box.
+fn find_in_line(line: &TextRegion, needle: &[String]) -> Vec<ImageMatch> {
+    if line.words.is_empty() {
+        let words = normalized_words(&line.text);
+        let found = words.windows(needle.len()).any(|window| window == needle);
+        return if found {
+            vec![ImageMatch {
+                text: line.text.clone(),
+                x: line.x,
+                y: line.y,
+                width: line.width,
+                height: line.height,
+            }]
+        } else {
+            Vec::new()
+        };
+    }
+
+    let words: Vec<String> = line.words.iter().map(|w| normalize_word(&w.text)).collect();
+    words
+        .windows(needle.len())
+        .enumerate()
+        .filter(|(_, window)| *window == needle)
+        .map(|(start, _)| {
+            let run = &line.words[start..start + needle.len()];
+            let left = run.iter().map(|w| w.x).min().unwrap_or(line.x);
+            let top = run.iter().map(|w| w.y).min().unwrap_or(line.y);
+            let right = run.iter().map(|w| w.x + w.width).max().unwrap_or(left);
+            let bottom = run.iter().map(|w| w.y + w.height).max().unwrap_or(top);
+            ImageMatch {
+                text: run
+                    .iter()
+                    .map(|w| w.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                x: left,
+                y: top,
+                width: right - left,
+                height: bottom - top,
+            }
+        })
+        .collect()
+}
+
+/// Split text into normalized words, dropping punctuation-only tokens
+fn normalized_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(normalize_word)
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Lowercase a word and strip punctuation around it
+fn normalize_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(words: &[(&str, u32)]) -> TextRegion {
+        let text = words.iter().map(|(w, _)| *w).collect::<Vec<_>>().join(" ");
+        let word_regions = words
+            .iter()
+            .map(|(w, x)| TextRegion::new(w.to_string(), *x, 100, 50, 20, 1.0))
+            .collect();
+        TextRegion::new(text, words[0].1, 100, 400, 20, 1.0).with_words(word_regions)
+    }
+
+    #[test]
+    fn test_find_in_line_words() {
+        let line = line(&[
+            ("Reject", 0),
+            ("all", 60),
+            ("|", 120),
+            ("Accept", 140),
+            ("all.", 200),
+        ]);
+        let needle = normalized_words("accept ALL");
+
+        let found = find_in_line(&line, &needle);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].text, "Accept all.");
+        assert_eq!((found[0].x, found[0].width), (140, 110));
+        assert_eq!((found[0].y, found[0].height), (100, 20));
+
+        assert!(find_in_line(&line, &normalized_words("Continue")).is_empty());
+    }
+
+    #[test]
+    fn test_find_in_line_without_words() {
+        let line = TextRegion::new(
+            "Click Continue to proceed".to_string(),
+            10,
+            20,
+            300,
+            18,
+            1.0,
+        );
+
+        let found = find_in_line(&line, &normalized_words("continue"));
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].x, found[0].y, found[0].width), (10, 20, 300));
+
+        // Whole words only
+        assert!(find_in_line(&line, &normalized_words("Cont")).is_empty());
+    }
+
+    #[test]
+    fn test_text_match_center() {
+        let m = TextMatch {
+            text: "OK".to_string(),
+            monitor: 1,
+            x: 1920,
+            y: 100,
+            width: 40,
+            height: 20,
+        };
+        assert_eq!(m.center(), (1940, 110));
+    }
+
+    #[test]
+    fn test_normalized_words() {
+        assert_eq!(normalized_words("  Accept  all! "), vec!["accept", "all"]);
+        assert!(normalized_words(" -- ").is_empty());
+    }
+}
@@ -0,0 +1,491 @@
+//! Natural-language time expressions
+//!
+//! Resolves phrases such as "yesterday afternoon", "last tuesday morning" or
+//! "past 3 hours" into a time range, so that the web UI, chat and the agent
+//! can pass `when=...` instead of computing timestamps themselves.
+//!
+//! Calendar phrases are resolved in the caller's time zone: the UTC offset
+//! sent with the request, or the server's local time zone. Weeks start on
+//! Monday. Supported phrases:
+//!
+//! - Days: `today`, `yesterday`, `day before yesterday`, `monday`,
+//!   `last friday`, `2024-03-05`
+//! - Parts of a day, alone or after a day: `morning` (05-12),
+//!   `afternoon` (12-17), `evening` (17-22), `night` (22-06), plus
+//!   `this morning`, `tonight` and `last night`
+//! - Calendar periods: `this week`, `last week`, `this month`,
+//!   `last month`, `this year`, `last year`
+//! - Rolling periods ending now: `last 30 minutes`, `past 2 hours`,
+//!   `last hour`, `past week`, `last 3 days`
+
+use crate::error::{AppError, Result};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc, Weekday,
+};
+use serde::Serialize;
+
+/// Time range a time expression resolved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TimeRange {
+    #[serde(rename = "start_time")]
+    pub start: DateTime<Utc>,
+    #[serde(rename = "end_time")]
+    pub end: DateTime<Utc>,
+}
+
+/// Optional start and end of a request's time range
+pub type TimeBounds = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// Part of a day, as local hours `[start, end)`; `end` may pass midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DayPart {
+    Morning,
+    Afternoon,
+    Evening,
+    Night,
+}
+
+impl DayPart {
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "morning" => Some(Self::Morning),
+            "afternoon" => Some(Self::Afternoon),
+            "evening" => Some(Self::Evening),
+            "night" => Some(Self::Night),
+            _ => None,
+        }
+    }
+
+    fn hours(self) -> (i64, i64) {
+        match self {
+            Self::Morning => (5, 12),
+            Self::Afternoon => (12, 17),
+            Self::Evening => (17, 22),
+            Self::Night => (22, 30),
+        }
+    }
+}
+
+/// Unit of a rolling period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Unit {
+    fn parse(word: &str) -> Option<Self> {
+        match word.strip_suffix('s').unwrap_or(word) {
+            "minute" | "min" => Some(Self::Minute),
+            "hour" | "hr" => Some(Self::Hour),
+            "day" => Some(Self::Day),
+            "week" => Some(Self::Week),
+            "month" => Some(Self::Month),
+            "year" => Some(Self::Year),
+            _ => None,
+        }
+    }
+}
+
+/// Resolve `expr` relative to the current time
+///
+/// `tz` is a UTC offset such as `+02:00`, `-0500` or `Z`; without it the
+/// server's local time zone is used.
+pub fn resolve_when(expr: &str, tz: Option<&str>) -> Result<TimeRange> {
+    let resolved = match tz.map(str::trim).filter(|tz| !tz.is_empty()) {
+        Some(tz) => {
+            let offset = parse_utc_offset(tz).ok_or_else(|| {
+                AppError::InvalidRequest(format!(
+                    "Invalid time zone '{}'. Use a UTC offset such as +02:00",
+                    tz
+                ))
+            })?;
+            resolve(expr, Utc::now().with_timezone(&offset))
+        }
+        None => resolve(expr, Local::now()),
+    };
+
+    resolved.ok_or_else(|| {
+        AppError::InvalidRequest(format!(
+            "Could not understand the time expression '{}'. \
+             Try e.g. 'yesterday afternoon', 'last tuesday morning' or 'past 3 hours'",
+            expr
+        ))
+    })
+}
+
+/// Apply `when` to an explicit start/end pair
+///
+/// Explicit bounds take precedence over the ones resolved from `when`.
+pub fn apply_when(
+    when: Option<&str>,
+    tz: Option<&str>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+) -> Result<TimeBounds> {
+    match when.map(str::trim).filter(|w| !w.is_empty()) {
+        Some(when) => {
+            let range = resolve_when(when, tz)?;
+            Ok((
+                start_time.or(Some(range.start)),
+                end_time.or(Some(range.end)),
+            ))
+        }
+        None => Ok((start_time, end_time)),
+    }
+}
+
+/// Parse a UTC offset: `Z`, `UTC`, `+02:00`, `+0200` or `+02`
+pub fn parse_utc_offset(s: &str) -> Option<FixedOffset> {
+    if s.eq_ignore_ascii_case("z") || s.eq_ignore_ascii_case("utc") {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, rest) = match s.as_bytes().first()? {
+        b'+' => (1, &s[1..]),
+        b'-' => (-1, &s[1..]),
+        _ => return None,
+    };
+    let digits = rest.replace(':', "");
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Resolve `expr` relative to `now`, in the time zone of `now`
+pub fn resolve<Tz: TimeZone>(expr: &str, now: DateTime<Tz>) -> Option<TimeRange> {
+    let lowered = expr.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty() && !matches!(*w, "the" | "in" | "on" | "during" | "of"))
+        .collect();
+    let today = now.date_naive();
+    let tz = now.timezone();
+
+    match words.as_slice() {
+        [] => None,
+        ["tonight"] => Some(local_range(&tz, today, 17, 30)),
+        ["last", "night"] => Some(local_range(&tz, today.pred_opt()?, 17, 30)),
+        ["this", word] => match (DayPart::parse(word), Unit::parse(word)) {
+            (Some(part), _) => {
+                let (start, end) = part.hours();
+                Some(local_range(&tz, today, start, end))
+            }
+            (None, Some(unit)) => calendar_period(&tz, today, unit, 0),
+            (None, None) => day_phrase(&tz, today, &words),
+        },
+        ["past", rest @ ..] => rolling_period(&now, rest, true),
+        ["last", rest @ ..] => rolling_period(&now, rest, false)
+            .or_else(|| match rest {
+                [word] => calendar_period(&tz, today, Unit::parse(word)?, 1),
+                _ => None,
+            })
+            .or_else(|| day_phrase(&tz, today, &words)),
+        _ => day_phrase(&tz, today, &words),
+    }
+}
+
+/// A day, optionally followed by a part of that day
+fn day_phrase<Tz: TimeZone>(tz: &Tz, today: NaiveDate, words: &[&str]) -> Option<TimeRange> {
+    let (day_words, part) = match words.split_last() {
+        Some((last, rest)) => match DayPart::parse(last) {
+            Some(part) => (rest, Some(part)),
+            None => (words, None),
+        },
+        None => return None,
+    };
+
+    let date = match day_words {
+        // A part of the day on its own refers to today
+        [] if part.is_some() => today,
+        ["today"] => today,
+        ["yesterday"] => today.pred_opt()?,
+        ["day", "before", "yesterday"] => today.pred_opt()?.pred_opt()?,
+        ["last", day] => last_weekday(today, day.parse::<Weekday>().ok()?, false),
+        ["this", day] | [day] => match day.parse::<Weekday>() {
+            Ok(weekday) => last_weekday(today, weekday, true),
+            Err(_) => NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?,
+        },
+        _ => return None,
+    };
+
+    let (start, end) = part.map(DayPart::hours).unwrap_or((0, 24));
+    Some(local_range(tz, date, start, end))
+}
+
+/// The most recent `weekday` before `today`, or on it when `inclusive`
+fn last_weekday(today: NaiveDate, weekday: Weekday, inclusive: bool) -> NaiveDate {
+    let back = (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+    let back = if back == 0 && !inclusive { 7 } else { back };
+    today - Duration::days(back as i64)
+}
+
+/// A period of `[count] unit` ending now, e.g. "3 hours" or "hour"
+///
+/// Without a count, only minutes and hours are rolling unless `any_unit`:
+/// "last week" is the previous calendar week, "past week" the last 7 days.
+fn rolling_period<Tz: TimeZone>(
+    now: &DateTime<Tz>,
+    words: &[&str],
+    any_unit: bool,
+) -> Option<TimeRange> {
+    let (count, unit) = match words {
+        [count, unit] => (parse_count(count)?, Unit::parse(unit)?),
+        [unit] => {
+            let unit = Unit::parse(unit)?;
+            if !any_unit && !matches!(unit, Unit::Minute | Unit::Hour) {
+                return None;
+            }
+            (1, unit)
+        }
+        _ => return None,
+    };
+
+    let start = match unit {
+        Unit::Minute => now.clone() - Duration::minutes(count),
+        Unit::Hour => now.clone() - Duration::hours(count),
+        Unit::Day => now.clone() - Duration::days(count),
+        Unit::Week => now.clone() - Duration::weeks(count),
+        Unit::Month => now.clone().checked_sub_months(Months::new(count as u32))?,
+        Unit::Year => now
+            .clone()
+            .checked_sub_months(Months::new(count as u32 * 12))?,
+    };
+    Some(TimeRange {
+        start: start.with_timezone(&Utc),
+        end: now.with_timezone(&Utc),
+    })
+}
+
+/// The calendar week, month or year containing `today`, `back` periods ago
+fn calendar_period<Tz: TimeZone>(
+    tz: &Tz,
+    today: NaiveDate,
+    unit: Unit,
+    back: u32,
+) -> Option<TimeRange> {
+    let (start, end) = match unit {
+        Unit::Day => {
+            let day = today - Duration::days(back as i64);
+            (day, day.succ_opt()?)
+        }
+        Unit::Week => {
+            let monday = today
+                - Duration::days(today.weekday().num_days_from_monday() as i64)
+                - Duration::weeks(back as i64);
+            (monday, monday + Duration::weeks(1))
+        }
+        Unit::Month => {
+            let first = today.with_day(1)? - Months::new(back);
+            (first, first + Months::new(1))
+        }
+        Unit::Year => {
+            let first = NaiveDate::from_ymd_opt(today.year() - back as i32, 1, 1)?;
+            (first, first + Months::new(12))
+        }
+        Unit::Minute | Unit::Hour => return None,
+    };
+
+    Some(TimeRange {
+        start: to_utc(tz, start.and_time(NaiveTime::MIN)),
+        end: to_utc(tz, end.and_time(NaiveTime::MIN)),
+    })
+}
+
+fn parse_count(word: &str) -> Option<i64> {
+    let count = match word {
+        "a" | "an" | "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        _ => word.parse().ok()?,
+    };
+    (1..=100_000).contains(&count).then_some(count)
+}
+
+/// Local hours `[start, end)` of `date`
+fn local_range<Tz: TimeZone>(tz: &Tz, date: NaiveDate, start: i64, end: i64) -> TimeRange {
+    let midnight = date.and_time(NaiveTime::MIN);
+    TimeRange {
+        start: to_utc(tz, midnight + Duration::hours(start)),
+        end: to_utc(tz, midnight + Duration::hours(end)),
+    }
+}
+
+/// Convert a local time to UTC, moving times inside a DST gap past the gap
+fn to_utc<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Thursday 2024-03-14 15:30 at UTC+02:00
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-03-14T15:30:00+02:00").unwrap()
+    }
+
+    fn range(start: &str, end: &str) -> TimeRange {
+        TimeRange {
+            start: DateTime::parse_from_rfc3339(start)
+                .unwrap()
+                .with_timezone(&Utc),
+            end: DateTime::parse_from_rfc3339(end)
+                .unwrap()
+                .with_timezone(&Utc),
+        }
+    }
+
+    #[test]
+    fn test_days_and_parts() {
+        assert_eq!(
+            resolve("today", now()),
+            Some(range(
+                "2024-03-14T00:00:00+02:00",
+                "2024-03-15T00:00:00+02:00"
+            ))
+        );
+        assert_eq!(
+            resolve("Yesterday afternoon", now()),
+            Some(range(
+                "2024-03-13T12:00:00+02:00",
+                "2024-03-13T17:00:00+02:00"
+            ))
+        );
+        assert_eq!(
+            resolve("this morning", now()),
+            Some(range(
+                "2024-03-14T05:00:00+02:00",
+                "2024-03-14T12:00:00+02:00"
+            ))
+        );
+        assert_eq!(
+            resolve("last night", now()),
+            Some(range(
+                "2024-03-13T17:00:00+02:00",
+                "2024-03-14T06:00:00+02:00"
+            ))
+        );
+        assert_eq!(
+            resolve("2024-02-29 evening", now()),
+            Some(range(
+                "2024-02-29T17:00:00+02:00",
+                "2024-02-29T22:00:00+02:00"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_weekdays() {
+        assert_eq!(
+            resolve("last tuesday morning", now()),
+            Some(range(
+                "2024-03-12T05:00:00+02:00",
+                "2024-03-12T12:00:00+02:00"
+            ))
+        );
+        // The current weekday is today, or a week ago after "last"
+        assert_eq!(
+            resolve("thursday", now()),
+            Some(range(
+                "2024-03-14T00:00:00+02:00",
+                "2024-03-15T00:00:00+02:00"
+            ))
+        );
+        assert_eq!(
+            resolve("last thursday", now()),
+            Some(range(
+                "2024-03-07T00:00:00+02:00",
+                "2024-03-08T00:00:00+02:00"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_calendar_and_rolling_periods() {
+        assert_eq!(
+            resolve("last week", now()),
+            Some(range(
+                "2024-03-04T00:00:00+02:00",
+                "2024-03-11T00:00:00+02:00"
+            ))
+        );
+        assert_eq!(
+            resolve("this month", now()),
+            Some(range(
+                "2024-03-01T00:00:00+02:00",
+                "2024-04-01T00:00:00+02:00"
+            ))
+        );
+        assert_eq!(
+            resolve("past 3 hours", now()),
+            Some(range(
+                "2024-03-14T12:30:00+02:00",
+                "2024-03-14T15:30:00+02:00"
+            ))
+        );
+        assert_eq!(
+            resolve("last hour", now()),
+            Some(range(
+                "2024-03-14T14:30:00+02:00",
+                "2024-03-14T15:30:00+02:00"
+            ))
+        );
+        assert_eq!(
+            resolve("past week", now()),
+            Some(range(
+                "2024-03-07T15:30:00+02:00",
+                "2024-03-14T15:30:00+02:00"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unknown_expressions() {
+        assert_eq!(resolve("", now()), None);
+        assert_eq!(resolve("next tuesday", now()), None);
+        assert_eq!(resolve("last blue moon", now()), None);
+        assert_eq!(resolve("past 0 days", now()), None);
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(parse_utc_offset("Z"), FixedOffset::east_opt(0));
+        assert_eq!(parse_utc_offset("+02:00"), FixedOffset::east_opt(7200));
+        assert_eq!(parse_utc_offset("-0530"), FixedOffset::west_opt(19800));
+        assert_eq!(parse_utc_offset("+09"), FixedOffset::east_opt(32400));
+        assert_eq!(parse_utc_offset("02:00"), None);
+        assert_eq!(parse_utc_offset("+25:00"), None);
+    }
+}
@@ -0,0 +1,546 @@
+//! Time-tracking CSV export
+//!
+//! Turns the app sessions found by [`crate::daily_notes::sessions`] into
+//! time entries and writes them in the CSV layouts that Toggl Track and
+//! Clockify import. Entries are either one per session, with back-to-back
+//! sessions on the same project joined, or one per app and day. Projects
+//! come from the `[time_tracking]` rules, matched on app, window title and
+//! the tags on the session's frames.
+
+use crate::daily_notes::Session;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Gap between sessions on the same project that still joins them
+const JOIN_GAP_MINUTES: i64 = 10;
+
+/// Maps activity to a project
+///
+/// A rule matches when any of its lists matches; an empty list never does.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectRule {
+    pub project: String,
+    #[serde(default)]
+    pub client: Option<String>,
+    /// App names, compared case-insensitively
+    #[serde(default)]
+    pub apps: Vec<String>,
+    /// Tags on the session's frames, compared case-insensitively
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Text contained in the window title, case-insensitive
+    #[serde(default)]
+    pub windows: Vec<String>,
+}
+
+impl ProjectRule {
+    fn matches(&self, session: &Session, tags: &BTreeSet<String>) -> bool {
+        let app = session.app.to_lowercase();
+        let window = session.window.as_deref().unwrap_or("").to_lowercase();
+        self.apps.iter().any(|a| a.to_lowercase() == app)
+            || self
+                .tags
+                .iter()
+                .any(|t| tags.iter().any(|tag| tag.eq_ignore_ascii_case(t)))
+            || self
+                .windows
+                .iter()
+                .any(|w| !w.is_empty() && window.contains(&w.to_lowercase()))
+    }
+}
+
+/// Time-tracking export settings
+#[derive(Debug, Clone)]
+pub struct TimesheetConfig {
+    /// User email written to each entry; Toggl requires one
+    pub email: String,
+    /// Mark entries as billable
+    pub billable: bool,
+    /// Project rules, the first match wins
+    pub projects: Vec<ProjectRule>,
+    /// Entries shorter than this are left out
+    pub min_entry_minutes: i64,
+}
+
+impl Default for TimesheetConfig {
+    fn default() -> Self {
+        Self {
+            email: String::new(),
+            billable: false,
+            projects: Vec::new(),
+            min_entry_minutes: 1,
+        }
+    }
+}
+
+/// CSV layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimesheetFormat {
+    Toggl,
+    Clockify,
+}
+
+impl TimesheetFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "toggl" => Some(Self::Toggl),
+            "clockify" => Some(Self::Clockify),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Toggl => "toggl",
+            Self::Clockify => "clockify",
+        }
+    }
+}
+
+/// What one time entry covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    /// A session, joined with back-to-back sessions on the same project
+    Session,
+    /// All time in an app on a day
+    App,
+}
+
+impl Grouping {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "session" => Some(Self::Session),
+            "app" => Some(Self::App),
+            _ => None,
+        }
+    }
+}
+
+/// One row of the export
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeEntry {
+    pub start: DateTime<Utc>,
+    pub duration: Duration,
+    pub project: Option<String>,
+    pub client: Option<String>,
+    pub description: String,
+    pub tags: Vec<String>,
+}
+
+impl TimeEntry {
+    pub fn end(&self) -> DateTime<Utc> {
+        self.start + self.duration
+    }
+}
+
+/// Build time entries from sessions ordered by time
+///
+/// `frame_tags` holds the tags of frames by frame time; a session gets the
+/// tags of the frames captured during it. Days for [`Grouping::App`] are
+/// taken in `tz`.
+pub fn time_entries<Tz: TimeZone>(
+    sessions: &[Session],
+    frame_tags: &[(DateTime<Utc>, String)],
+    config: &TimesheetConfig,
+    grouping: Grouping,
+    tz: &Tz,
+) -> Vec<TimeEntry> {
+    let tagged: Vec<(&Session, BTreeSet<String>)> = sessions
+        .iter()
+        .map(|session| {
+            let tags = frame_tags
+                .iter()
+                .filter(|(time, _)| *time >= session.start && *time < session.end)
+                .map(|(_, tag)| tag.clone())
+                .collect();
+            (session, tags)
+        })
+        .collect();
+
+    let mut entries = match grouping {
+        Grouping::Session => session_entries(&tagged, config),
+        Grouping::App => app_entries(&tagged, config, tz),
+    };
+    let min = Duration::minutes(config.min_entry_minutes);
+    entries.retain(|entry| entry.duration >= min && entry.duration > Duration::zero());
+    entries
+}
+
+fn project_for<'a>(
+    config: &'a TimesheetConfig,
+    session: &Session,
+    tags: &BTreeSet<String>,
+) -> Option<&'a ProjectRule> {
+    config
+        .projects
+        .iter()
+        .find(|rule| rule.matches(session, tags))
+}
+
+fn session_entries(
+    tagged: &[(&Session, BTreeSet<String>)],
+    config: &TimesheetConfig,
+) -> Vec<TimeEntry> {
+    let gap = Duration::minutes(JOIN_GAP_MINUTES);
+    let mut entries: Vec<TimeEntry> = Vec::new();
+    // Project, or app when unmapped, of the last entry, and where it ends
+    let mut last: Option<(String, DateTime<Utc>)> = None;
+    // Time per app in the last entry, for its description
+    let mut apps: Vec<(String, Duration)> = Vec::new();
+
+    for (session, tags) in tagged {
+        let rule = project_for(config, session, tags);
+        let key = rule.map_or_else(|| session.app.clone(), |rule| rule.project.clone());
+
+        let joins = matches!(&last, Some((last_key, end))
+            if *last_key == key && session.start - *end <= gap);
+        if joins {
+            let entry = entries.last_mut().expect("joined entry exists");
+            let end = entry.end().max(session.end);
+            entry.duration = end - entry.start;
+            for tag in tags {
+                if !entry.tags.contains(tag) {
+                    entry.tags.push(tag.clone());
+                }
+            }
+            add_time(&mut apps, &session.app, session.duration());
+            entry.description = describe(&apps, session.window.as_deref());
+            last = Some((key, end));
+            continue;
+        }
+
+        apps.clear();
+        add_time(&mut apps, &session.app, session.duration());
+        entries.push(TimeEntry {
+            start: session.start,
+            duration: session.duration(),
+            project: rule.map(|rule| rule.project.clone()),
+            client: rule.and_then(|rule| rule.client.clone()),
+            description: describe(&apps, session.window.as_deref()),
+            tags: tags.iter().cloned().collect(),
+        });
+        last = Some((key, session.end));
+    }
+
+    entries
+}
+
+fn app_entries<Tz: TimeZone>(
+    tagged: &[(&Session, BTreeSet<String>)],
+    config: &TimesheetConfig,
+    tz: &Tz,
+) -> Vec<TimeEntry> {
+    let mut index: HashMap<(NaiveDate, String, Option<String>), usize> = HashMap::new();
+    let mut entries: Vec<TimeEntry> = Vec::new();
+
+    for (session, tags) in tagged {
+        let rule = project_for(config, session, tags);
+        let day = session.start.with_timezone(tz).date_naive();
+        let project = rule.map(|rule| rule.project.clone());
+        let key = (day, session.app.clone(), project.clone());
+
+        match index.get(&key) {
+            Some(&i) => {
+                let entry = &mut entries[i];
+                entry.duration += session.duration();
+                for tag in tags {
+                    if !entry.tags.contains(tag) {
+                        entry.tags.push(tag.clone());
+                    }
+                }
+            }
+            None => {
+                index.insert(key, entries.len());
+                entries.push(TimeEntry {
+                    start: session.start,
+                    duration: session.duration(),
+                    project,
+                    client: rule.and_then(|rule| rule.client.clone()),
+                    description: session.app.clone(),
+                    tags: tags.iter().cloned().collect(),
+                });
+            }
+        }
+    }
+
+    for entry in &mut entries {
+        entry.tags.sort();
+    }
+    entries
+}
+
+fn add_time(apps: &mut Vec<(String, Duration)>, app: &str, time: Duration) {
+    match apps.iter_mut().find(|(name, _)| name == app) {
+        Some((_, total)) => *total += time,
+        None => apps.push((app.to_string(), time)),
+    }
+}
+
+/// Apps by time spent, then the window of the latest session
+fn describe(apps: &[(String, Duration)], window: Option<&str>) -> String {
+    let mut sorted: Vec<&(String, Duration)> = apps.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let names: Vec<&str> = sorted.iter().map(|(app, _)| app.as_str()).collect();
+    match window.filter(|window| !window.is_empty()) {
+        Some(window) => format!("{}: {}", names.join(", "), window),
+        None => names.join(", "),
+    }
+}
+
+/// Render entries as CSV in `format`, with dates and times in `tz`
+pub fn render_csv<Tz: TimeZone>(
+    entries: &[TimeEntry],
+    format: TimesheetFormat,
+    config: &TimesheetConfig,
+    tz: &Tz,
+) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let billable = if config.billable { "Yes" } else { "No" };
+    let mut out = String::new();
+
+    match format {
+        TimesheetFormat::Toggl => {
+            push_row(
+                &mut out,
+                &[
+                    "Email",
+                    "Start date",
+                    "Start time",
+                    "Duration",
+                    "Project",
+                    "Client",
+                    "Description",
+                    "Tags",
+                    "Billable",
+                ],
+            );
+            for entry in entries {
+                let start = entry.start.with_timezone(tz);
+                push_row(
+                    &mut out,
+                    &[
+                        &config.email,
+                        &start.format("%Y-%m-%d").to_string(),
+                        &start.format("%H:%M:%S").to_string(),
+                        &format_hms(entry.duration),
+                        entry.project.as_deref().unwrap_or(""),
+                        entry.client.as_deref().unwrap_or(""),
+                        &entry.description,
+                        &entry.tags.join(", "),
+                        billable,
+                    ],
+                );
+            }
+        }
+        TimesheetFormat::Clockify => {
+            push_row(
+                &mut out,
+                &[
+                    "Project",
+                    "Client",
+                    "Description",
+                    "Task",
+                    "Email",
+                    "Tags",
+                    "Billable",
+                    "Start Date",
+                    "Start Time",
+                    "End Date",
+                    "End Time",
+                    "Duration (h)",
+                ],
+            );
+            for entry in entries {
+                let start = entry.start.with_timezone(tz);
+                let end = entry.end().with_timezone(tz);
+                push_row(
+                    &mut out,
+                    &[
+                        entry.project.as_deref().unwrap_or(""),
+                        entry.client.as_deref().unwrap_or(""),
+                        &entry.description,
+                        "",
+                        &config.email,
+                        &entry.tags.join(", "),
+                        billable,
+                        &start.format("%Y-%m-%d").to_string(),
+                        &start.format("%H:%M:%S").to_string(),
+                        &end.format("%Y-%m-%d").to_string(),
+                        &end.format("%H:%M:%S").to_string(),
+                        &format_hms(entry.duration),
+                    ],
+                );
+            }
+        }
+    }
+
+    out
+}
+
+/// Duration as HH:MM:SS, hours not wrapping at a day
+fn format_hms(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Append a CSV row ending in CRLF, quoting fields as RFC 4180 requires
+fn push_row(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn base() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-12-10T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn session(app: &str, window: &str, start: i64, end: i64) -> Session {
+        Session {
+            app: app.to_string(),
+            window: Some(window.to_string()),
+            start: base() + Duration::minutes(start),
+            end: base() + Duration::minutes(end),
+            frame_id: start,
+        }
+    }
+
+    fn config() -> TimesheetConfig {
+        TimesheetConfig {
+            email: "me@example.com".to_string(),
+            billable: true,
+            projects: vec![
+                ProjectRule {
+                    project: "Parser".to_string(),
+                    client: Some("Acme, Inc.".to_string()),
+                    tags: vec!["parser".to_string()],
+                    windows: vec!["parser.rs".to_string()],
+                    ..Default::default()
+                },
+                ProjectRule {
+                    project: "Admin".to_string(),
+                    apps: vec!["Outlook.exe".to_string()],
+                    ..Default::default()
+                },
+            ],
+            min_entry_minutes: 2,
+        }
+    }
+
+    #[test]
+    fn test_session_entries() {
+        let sessions = vec![
+            session("code.exe", "parser.rs - crate", 0, 30),
+            session("firefox.exe", "Rust docs", 30, 40),
+            session("code.exe", "lexer.rs - crate", 45, 60),
+            session("outlook.exe", "Inbox", 60, 61),
+            session("outlook.exe", "Inbox", 90, 100),
+        ];
+        // The docs were tagged as part of the parser work
+        let tags = vec![
+            (base() + Duration::minutes(35), "Parser".to_string()),
+            (base() + Duration::minutes(50), "parser".to_string()),
+        ];
+
+        let entries = time_entries(&sessions, &tags, &config(), Grouping::Session, &Utc);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].project.as_deref(), Some("Parser"));
+        assert_eq!(entries[0].client.as_deref(), Some("Acme, Inc."));
+        assert_eq!(entries[0].duration, Duration::minutes(60));
+        assert_eq!(
+            entries[0].description,
+            "code.exe, firefox.exe: lexer.rs - crate"
+        );
+        assert_eq!(entries[0].tags, vec!["Parser", "parser"]);
+        // The one-minute Outlook session is too short on its own
+        assert_eq!(entries[1].project.as_deref(), Some("Admin"));
+        assert_eq!(entries[1].start, base() + Duration::minutes(90));
+    }
+
+    #[test]
+    fn test_app_entries() {
+        let sessions = vec![
+            session("code.exe", "main.rs", 0, 30),
+            session("slack.exe", "general", 30, 40),
+            session("code.exe", "main.rs", 40, 70),
+            // Next day in UTC+10
+            session("code.exe", "main.rs", 900, 930),
+        ];
+        let tz = FixedOffset::east_opt(10 * 3600).unwrap();
+
+        let entries = time_entries(&sessions, &[], &config(), Grouping::App, &tz);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].description, "code.exe");
+        assert_eq!(entries[0].duration, Duration::minutes(60));
+        assert_eq!(entries[0].project, None);
+        assert_eq!(entries[1].description, "slack.exe");
+        assert_eq!(entries[2].start, base() + Duration::minutes(900));
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let entries = vec![TimeEntry {
+            start: base(),
+            duration: Duration::seconds(5432),
+            project: Some("Parser".to_string()),
+            client: Some("Acme, Inc.".to_string()),
+            description: "Review \"lexer\"".to_string(),
+            tags: vec!["parser".to_string(), "review".to_string()],
+        }];
+        let tz = FixedOffset::east_opt(3600).unwrap();
+
+        let toggl = render_csv(&entries, TimesheetFormat::Toggl, &config(), &tz);
+        assert_eq!(
+            toggl,
+            "Email,Start date,Start time,Duration,Project,Client,Description,Tags,Billable\r\n\
+             me@example.com,2025-12-10,10:00:00,01:30:32,Parser,\"Acme, Inc.\",\
+             \"Review \"\"lexer\"\"\",\"parser, review\",Yes\r\n"
+        );
+
+        let clockify = render_csv(&entries, TimesheetFormat::Clockify, &config(), &tz);
+        let mut lines = clockify.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "Project,Client,Description,Task,Email,Tags,Billable,\
+                 Start Date,Start Time,End Date,End Time,Duration (h)"
+            )
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                "Parser,\"Acme, Inc.\",\"Review \"\"lexer\"\"\",,me@example.com,\
+                 \"parser, review\",Yes,2025-12-10,10:00:00,2025-12-10,11:30:32,01:30:32"
+            )
+        );
+        assert_eq!(
+            TimesheetFormat::parse("Clockify"),
+            Some(TimesheetFormat::Clockify)
+        );
+        assert_eq!(Grouping::parse("week"), None);
+    }
+}
@@ -0,0 +1,140 @@
+//! HTTPS support
+//!
+//! Loads the certificate and private key used to serve the API over TLS
+//! (rustls). When enabled, a self-signed certificate is generated on first run
+//! if the configured files do not exist yet.
+
+use axum_server::tls_rustls::RustlsConfig;
+use std::path::{Path, PathBuf};
+
+/// TLS configuration for the API server
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// PEM certificate (chain) file
+    pub cert_path: PathBuf,
+
+    /// PEM private key file
+    pub key_path: PathBuf,
+
+    /// Generate a self-signed certificate if the files do not exist
+    pub self_signed: bool,
+
+    /// Extra host names or IP addresses for a generated certificate
+    /// (`localhost`, `127.0.0.1`, `::1` and the bind host are always included)
+    pub subject_alt_names: Vec<String>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            cert_path: PathBuf::from("tls/cert.pem"),
+            key_path: PathBuf::from("tls/key.pem"),
+            self_signed: true,
+            subject_alt_names: Vec::new(),
+        }
+    }
+}
+
+impl TlsConfig {
+    /// Load the rustls server configuration
+    ///
+    /// Generates a self-signed certificate first if `self_signed` is set and
+    /// either file is missing.
+    pub async fn load(&self, host: &str) -> anyhow::Result<RustlsConfig> {
+        if !self.cert_path.exists() || !self.key_path.exists() {
+            if !self.self_signed {
+                anyhow::bail!(
+                    "TLS certificate {} or key {} not found",
+                    self.cert_path.display(),
+                    self.key_path.display()
+                );
+            }
+            self.generate_self_signed(host)?;
+        }
+
+        RustlsConfig::from_pem_file(&self.cert_path, &self.key_path)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to load TLS certificate {}: {}",
+                    self.cert_path.display(),
+                    e
+                )
+            })
+    }
+
+    /// Write a self-signed certificate and key to the configured paths
+    fn generate_self_signed(&self, host: &str) -> anyhow::Result<()> {
+        let names = self.certificate_names(host);
+        tracing::info!("Generating self-signed TLS certificate for {:?}", names);
+
+        let certified = rcgen::generate_simple_self_signed(names)
+            .map_err(|e| anyhow::anyhow!("Failed to generate TLS certificate: {}", e))?;
+
+        write_pem(&self.cert_path, &certified.cert.pem())?;
+        write_pem(&self.key_path, &certified.key_pair.serialize_pem())?;
+
+        tracing::info!(
+            "Wrote TLS certificate {} and key {}",
+            self.cert_path.display(),
+            self.key_path.display()
+        );
+        Ok(())
+    }
+
+    /// Subject alternative names for a generated certificate
+    fn certificate_names(&self, host: &str) -> Vec<String> {
+        let mut names = vec![
+            "localhost".to_string(),
+            "127.0.0.1".to_string(),
+            "::1".to_string(),
+        ];
+
+        // Wildcard bind addresses are not names clients connect to
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let extra = std::iter::once(host)
+            .filter(|host| !matches!(*host, "0.0.0.0" | "::" | ""))
+            .chain(self.subject_alt_names.iter().map(|name| name.trim()));
+
+        for name in extra {
+            if !name.is_empty() && !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                names.push(name.to_string());
+            }
+        }
+
+        names
+    }
+}
+
+fn write_pem(path: &Path, contents: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    std::fs::write(path, contents)
+        .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_certificate_names() {
+        let config = TlsConfig {
+            subject_alt_names: vec!["192.168.1.20".to_string(), "LOCALHOST".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.certificate_names("0.0.0.0"),
+            vec!["localhost", "127.0.0.1", "::1", "192.168.1.20"]
+        );
+        assert_eq!(
+            config.certificate_names("my-pc"),
+            vec!["localhost", "127.0.0.1", "::1", "my-pc", "192.168.1.20"]
+        );
+        assert_eq!(config.certificate_names("[::1]").len(), 4);
+    }
+}
@@ -0,0 +1,184 @@
+//! Local socket transport
+//!
+//! Serves the API on a Windows named pipe (e.g. `\\.\pipe\screensearch`) or,
+//! on other platforms, a Unix domain socket instead of a TCP port, so local
+//! tools can reach it without any network listener.
+
+use axum::Router;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Serve `app` on the named pipe or Unix socket `name` until an error occurs
+pub async fn serve_local(app: Router, name: &str) -> anyhow::Result<()> {
+    serve_platform(app, name).await
+}
+
+/// Serve HTTP/1.1 (with upgrades for the WebSocket event stream) on one connection
+async fn serve_connection<IO>(io: IO, app: Router)
+where
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let service = TowerToHyperService::new(app);
+
+    if let Err(e) = hyper::server::conn::http1::Builder::new()
+        .serve_connection(TokioIo::new(io), service)
+        .with_upgrades()
+        .await
+    {
+        tracing::debug!("Local connection closed with error: {}", e);
+    }
+}
+
+#[cfg(windows)]
+async fn serve_platform(app: Router, name: &str) -> anyhow::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    // The first instance fails if another process already owns the pipe name;
+    // remote (SMB) clients are rejected
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .reject_remote_clients(true)
+        .create(name)
+        .map_err(|e| anyhow::anyhow!("Failed to create named pipe {}: {}", name, e))?;
+
+    tracing::info!("Starting API server on named pipe {}", name);
+
+    loop {
+        server
+            .connect()
+            .await
+            .map_err(|e| anyhow::anyhow!("Named pipe error: {}", e))?;
+
+        // Create the next instance before handing this one off so clients
+        // never find the pipe missing
+        let connected = server;
+        server = ServerOptions::new()
+            .reject_remote_clients(true)
+            .create(name)
+            .map_err(|e| anyhow::anyhow!("Failed to create named pipe {}: {}", name, e))?;
+
+        tokio::spawn(serve_connection(connected, app.clone()));
+    }
+}
+
+#[cfg(unix)]
+async fn serve_platform(app: Router, name: &str) -> anyhow::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+    use std::path::Path;
+
+    let path = Path::new(name);
+
+    // A socket file left behind by a previous run blocks binding; anything
+    // else at that path is not ours to delete
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to remove stale socket {}: {}", name, e))?,
+        Ok(_) => anyhow::bail!("{} already exists and is not a socket", name),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to inspect {}: {}", name, e)),
+    }
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent)?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("{} is not a socket file path", name))?;
+
+    // Bind in a directory only the current user can enter, so nobody can
+    // connect before the socket is restricted, then move it into place
+    let private = parent.join(format!(".{}.tmp", std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private)
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", private.display(), e))?;
+    let bound = private.join(file_name);
+    let listener = tokio::net::UnixListener::bind(&bound)
+        .map_err(|e| anyhow::anyhow!("Failed to bind to {}: {}", name, e))
+        .and_then(|listener| {
+            // Only the current user may connect
+            std::fs::set_permissions(&bound, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::rename(&bound, path)?;
+            Ok(listener)
+        });
+    let _ = std::fs::remove_dir_all(&private);
+    let listener = listener?;
+
+    tracing::info!("Starting API server on Unix socket {}", name);
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| anyhow::anyhow!("Unix socket error: {}", e))?;
+
+        tokio::spawn(serve_connection(stream, app.clone()));
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+async fn serve_platform(_app: Router, name: &str) -> anyhow::Result<()> {
+    anyhow::bail!("Local socket {} is not supported on this platform", name)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_serves_http_over_unix_socket() {
+        let dir = std::env::temp_dir().join(format!("screensearch-uds-{}", std::process::id()));
+        let path = dir.join("api.sock");
+        let name = path.to_string_lossy().to_string();
+
+        let app = Router::new().route("/ping", get(|| async { "pong" }));
+        let server = tokio::spawn({
+            let name = name.clone();
+            async move { serve_local(app, &name).await }
+        });
+
+        // Wait for the socket to appear
+        let mut stream = loop {
+            if let Ok(stream) = tokio::net::UnixStream::connect(&path).await {
+                break stream;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        };
+
+        stream
+            .write_all(b"GET /ping HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("pong"));
+
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        server.abort();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_keeps_files_that_are_not_sockets() {
+        let dir =
+            std::env::temp_dir().join(format!("screensearch-uds-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("api.sock");
+        std::fs::write(&path, "data").unwrap();
+
+        let app = Router::new().route("/ping", get(|| async { "pong" }));
+        assert!(serve_local(app, &path.to_string_lossy()).await.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "data");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
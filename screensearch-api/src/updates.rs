@@ -0,0 +1,114 @@
+//! Application update status and install requests
+//!
+//! The updater in the main binary checks for releases, downloads and stages
+//! them, and publishes its progress here. `/admin/update` reads the status
+//! and asks the updater to install a release now, which restarts the
+//! application.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use tokio::sync::Notify;
+
+/// Where the updater is with the latest release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateState {
+    /// No newer release found (or not checked yet)
+    #[default]
+    UpToDate,
+    /// A newer release exists but is not downloaded
+    Available,
+    /// The release is being downloaded and verified
+    Downloading,
+    /// The release is verified and installs on the next restart
+    Ready,
+    /// Restarting to install the release
+    Installing,
+    /// Checking, downloading or verifying failed
+    Failed,
+}
+
+/// Update status reported by `/admin/update`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UpdateStatus {
+    pub state: UpdateState,
+
+    /// Running version
+    pub current_version: String,
+
+    /// Newest released version, when newer than the running one
+    pub latest_version: Option<String>,
+
+    /// Release page of the newest version
+    pub release_url: Option<String>,
+
+    /// Why the last check, download or verification failed
+    pub error: Option<String>,
+
+    /// Version that failed to start and was rolled back, if any
+    pub rolled_back_from: Option<String>,
+}
+
+/// Update status shared between the updater and the API
+#[derive(Debug, Default)]
+pub struct UpdateControl {
+    status: RwLock<UpdateStatus>,
+    install: Notify,
+    restart: AtomicBool,
+}
+
+impl UpdateControl {
+    /// Current status
+    pub fn status(&self) -> UpdateStatus {
+        self.status
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Change the status
+    pub fn update_status(&self, f: impl FnOnce(&mut UpdateStatus)) {
+        f(&mut self.status.write().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Ask the updater to download the latest release, and to restart and
+    /// install it once verified when `restart` is set
+    pub fn request_install(&self, restart: bool) {
+        if restart {
+            self.restart.store(true, Ordering::Relaxed);
+        }
+        self.install.notify_one();
+    }
+
+    /// Wait for the next install request
+    ///
+    /// Returns whether a restart was requested.
+    pub async fn install_requested(&self) -> bool {
+        self.install.notified().await;
+        self.restart.swap(false, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_install_request_is_kept_until_awaited() {
+        let control = UpdateControl::default();
+        control.request_install(true);
+        assert!(control.install_requested().await);
+
+        control.request_install(false);
+        assert!(!control.install_requested().await);
+    }
+
+    #[test]
+    fn test_status_serializes_state_in_snake_case() {
+        let control = UpdateControl::default();
+        control.update_status(|status| status.state = UpdateState::UpToDate);
+        let json = serde_json::to_value(control.status()).unwrap();
+        assert_eq!(json["state"], "up_to_date");
+    }
+}
@@ -0,0 +1,192 @@
+//! Video archives of old frames
+//!
+//! Frames are captured as individual images, which are fast to serve but
+//! take far more space than a video of the same screens. The compaction
+//! worker encodes old frames into H.264 video chunks with `ffmpeg` and points
+//! each frame at its chunk and position in it (`offset_index`). Images of
+//! such frames are decoded from the chunk when they are requested.
+
+use crate::capture_store::CaptureStorage;
+use crate::cold_archive::{is_frame_archive, read_archived_image};
+use anyhow::{bail, Context};
+use screensearch_db::{DatabaseManager, FrameRecord};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// JPEG quality of frames decoded from a chunk (ffmpeg scale, 2 = best)
+const EXTRACT_QUALITY: &str = "3";
+
+/// Seconds between keyframes, bounding the frames decoded to extract one
+const KEYFRAME_INTERVAL_SECS: u32 = 10;
+
+/// Image bytes of a frame, from its image file, its archive (see
+/// [`crate::cold_archive`]) or its video chunk
+///
+/// Frames decoded from a chunk are returned as JPEG.
+pub async fn read_frame_image(
+    db: &DatabaseManager,
+    storage: &CaptureStorage,
+    ffmpeg: &str,
+    frame: &FrameRecord,
+) -> anyhow::Result<Vec<u8>> {
+    let Some(chunk_id) = frame.chunk_id else {
+        if is_frame_archive(&frame.file_path) {
+            if let Some(entry) = db.get_archived_frame(frame.id).await? {
+                return read_archived_image(storage, &entry).await;
+            }
+        }
+        return storage.get(&frame.file_path).await;
+    };
+
+    let chunk = db
+        .get_video_chunk(chunk_id)
+        .await?
+        .with_context(|| format!("Video chunk {} not found", chunk_id))?;
+    let file = storage.local_file(&chunk.file_path).await?;
+    extract_frame(ffmpeg, &file, frame.offset_index, chunk.fps).await
+}
+
+/// Content type of an image returned by [`read_frame_image`]
+///
+/// Told from the data, since archived frames keep no file extension.
+pub fn image_content_type(data: &[u8]) -> &'static str {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Encode `images`, all of the same format and size, into an H.264 video
+/// at `output` showing `fps` of them per second
+pub async fn encode_chunk(
+    ffmpeg: &str,
+    storage: &CaptureStorage,
+    images: &[String],
+    output: &Path,
+    fps: u32,
+    crf: u32,
+) -> anyhow::Result<()> {
+    let mut child = ffmpeg_command(ffmpeg)
+        .args(["-f", "image2pipe", "-framerate"])
+        .arg(fps.to_string())
+        .args(["-i", "-", "-c:v", "libx264", "-preset", "slow", "-crf"])
+        .arg(crf.to_string())
+        .arg("-g")
+        .arg((fps * KEYFRAME_INTERVAL_SECS).to_string())
+        // H.264 with 4:2:0 chroma needs even dimensions
+        .args([
+            "-vf",
+            "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+            "-pix_fmt",
+            "yuv420p",
+            "-movflags",
+            "+faststart",
+        ])
+        .arg(output)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", ffmpeg))?;
+
+    let mut stdin = child.stdin.take().context("ffmpeg stdin unavailable")?;
+    let written = async {
+        for image in images {
+            let data = storage.get(image).await?;
+            stdin.write_all(&data).await?;
+        }
+        anyhow::Ok(())
+    }
+    .await;
+    drop(stdin);
+
+    let result = child.wait_with_output().await?;
+    written?;
+    if !result.status.success() {
+        bail!(
+            "ffmpeg failed to encode {}: {}",
+            output.display(),
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Decode the frame at `offset_index` of a chunk as JPEG
+pub async fn extract_frame(
+    ffmpeg: &str,
+    chunk_path: &Path,
+    offset_index: i32,
+    fps: i32,
+) -> anyhow::Result<Vec<u8>> {
+    let output = ffmpeg_command(ffmpeg)
+        .arg("-ss")
+        .arg(seek_position(offset_index, fps))
+        .arg("-i")
+        .arg(chunk_path)
+        .args(["-frames:v", "1", "-f", "image2pipe", "-c:v", "mjpeg"])
+        .args(["-q:v", EXTRACT_QUALITY, "-"])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .with_context(|| format!("Failed to run {}", ffmpeg))?;
+
+    if !output.status.success() || output.stdout.is_empty() {
+        bail!(
+            "ffmpeg failed to extract frame {} of {}: {}",
+            offset_index,
+            chunk_path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+fn ffmpeg_command(ffmpeg: &str) -> Command {
+    let mut command = Command::new(ffmpeg);
+    command.args(["-hide_banner", "-loglevel", "error", "-y"]);
+    command.kill_on_drop(true);
+    #[cfg(windows)]
+    {
+        // CREATE_NO_WINDOW: no console window flashing up from the tray app
+        command.creation_flags(0x0800_0000);
+    }
+    command
+}
+
+/// Seek position of a frame, in whole milliseconds rounded down
+///
+/// An accurate seek returns the first frame at or after the position, so it
+/// must not be rounded past the frame's timestamp.
+fn seek_position(offset_index: i32, fps: i32) -> String {
+    let millis = offset_index.max(0) as i64 * 1000 / fps.max(1) as i64;
+    format!("{}.{:03}", millis / 1000, millis % 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seek_position_rounds_down() {
+        assert_eq!(seek_position(0, 2), "0.000");
+        assert_eq!(seek_position(3, 2), "1.500");
+        assert_eq!(seek_position(2, 3), "0.666");
+        assert_eq!(seek_position(7, 0), "7.000");
+    }
+
+    #[test]
+    fn test_image_content_type() {
+        assert_eq!(
+            image_content_type(&[0xFF, 0xD8, 0xFF, 0xE0, 0]),
+            "image/jpeg"
+        );
+        assert_eq!(image_content_type(b"\x89PNG\r\n\x1a\n\0\0"), "image/png");
+        assert_eq!(image_content_type(b""), "application/octet-stream");
+    }
+}
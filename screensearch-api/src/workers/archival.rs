@@ -0,0 +1,345 @@
+//! Cold-tier archival worker
+//!
+//! Moves the images of frames older than `after_days` into one `tar.zst`
+//! archive per day (see [`crate::cold_archive`]) and deletes the loose
+//! files. Only whole days are archived, and frames already compacted into
+//! video chunks are left alone. Archival runs as an `archival` job on the
+//! job queue, queued periodically; each run also removes archives whose
+//! frames have all been deleted.
+
+use super::job_queue::{enqueue_job, JobContext, JobFuture, JobHandler};
+use crate::capture_store::CaptureStorage;
+use crate::cold_archive::{ArchiveWriter, ARCHIVE_EXTENSION};
+use crate::state::AppState;
+use chrono::{NaiveTime, Utc};
+use screensearch_db::{DatabaseManager, FrameRecord, NewArchivedFrame, NewFrameArchive};
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info, warn};
+
+/// Job type that moves old frame images into archives
+pub const ARCHIVAL_JOB: &str = "archival";
+
+/// Frames read per database round trip
+const ARCHIVAL_BATCH: i64 = 2000;
+
+/// Directory of the archives among the captures
+const COLD_DIR: &str = "cold";
+
+/// Configuration for the cold-tier archival worker
+#[derive(Debug, Clone)]
+pub struct ArchivalConfig {
+    /// Whether old frame images are archived
+    pub enabled: bool,
+    /// Days captured longer ago than this are archived
+    pub after_days: u32,
+    /// zstd compression level (1-22)
+    pub level: i32,
+    /// Maximum frames per archive; busier days get several
+    pub max_archive_frames: usize,
+    /// Interval between scheduled archival runs (seconds)
+    pub check_interval_secs: u64,
+}
+
+impl Default for ArchivalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            after_days: 30,
+            level: 3,
+            max_archive_frames: 1000,
+            check_interval_secs: 6 * 60 * 60,
+        }
+    }
+}
+
+/// Get the active `archival` job, queuing one if none is queued or running
+pub async fn ensure_archival_job(db: &DatabaseManager) -> screensearch_db::Result<i64> {
+    if let Some(job) = db.find_active_job(ARCHIVAL_JOB).await? {
+        return Ok(job.id);
+    }
+
+    enqueue_job(db, ARCHIVAL_JOB, &json!({})).await
+}
+
+/// Job handler that archives old frame images
+///
+/// Result: `{"frames_archived": <count>, "archives_created": <count>,
+/// "frames_skipped": <count>, "archives_deleted": <count>}`. Frames whose
+/// image cannot be read or whose archive fails to be written are skipped
+/// and retried by the next run.
+pub struct ArchivalJob {
+    config: ArchivalConfig,
+}
+
+impl ArchivalJob {
+    /// Create an archival job handler
+    pub fn new(config: ArchivalConfig) -> Self {
+        Self { config }
+    }
+
+    /// Write the images of `frames`, all captured on the same day, into an
+    /// archive, point the frames at it and delete the images
+    ///
+    /// Returns the number of frames archived.
+    async fn archive(
+        &self,
+        db: &DatabaseManager,
+        storage: &CaptureStorage,
+        frames: &[FrameRecord],
+    ) -> anyhow::Result<usize> {
+        let Some(first) = frames.first() else {
+            return Ok(0);
+        };
+        let day = first.timestamp.date_naive();
+
+        // Written locally, then stored next to the captures
+        let file_name = format!(
+            "frames_{}_{}{}",
+            day.format("%Y%m%d"),
+            first.id,
+            ARCHIVE_EXTENSION
+        );
+        let output = std::env::temp_dir().join(&file_name);
+
+        let written = self.write_archive(storage, frames, &output).await;
+        let stored = match written {
+            Ok((entries, size)) if !entries.is_empty() => storage
+                .put_file(&format!("{}/{}", COLD_DIR, file_name), &output)
+                .await
+                .map(|path| (entries, size, path)),
+            Ok(_) => {
+                let _ = tokio::fs::remove_file(&output).await;
+                return Ok(0);
+            }
+            Err(e) => Err(e),
+        };
+        let _ = tokio::fs::remove_file(&output).await;
+        let (entries, size, file_path) = stored?;
+
+        let archive = NewFrameArchive {
+            day,
+            file_path: file_path.clone(),
+            size_bytes: size as i64,
+        };
+        let ids: Vec<NewArchivedFrame> = entries.iter().map(|(entry, _)| *entry).collect();
+        if let Err(e) = db.insert_frame_archive(archive, &ids).await {
+            let _ = storage.delete(&file_path).await;
+            return Err(e.into());
+        }
+
+        for (_, image) in &entries {
+            if let Err(e) = storage.delete(image).await {
+                debug!("{:#}", e);
+            }
+        }
+        Ok(entries.len())
+    }
+
+    /// Write the readable images of `frames` into an archive at `output`
+    ///
+    /// Returns the position of each image with its original path, and the
+    /// size of the archive.
+    async fn write_archive(
+        &self,
+        storage: &CaptureStorage,
+        frames: &[FrameRecord],
+        output: &Path,
+    ) -> anyhow::Result<(Vec<(NewArchivedFrame, String)>, u64)> {
+        let mut writer = ArchiveWriter::create(output, self.config.level).await?;
+        let mut entries = Vec::with_capacity(frames.len());
+
+        for frame in frames {
+            let data = match storage.get(&frame.file_path).await {
+                Ok(data) => data,
+                Err(e) => {
+                    debug!("Not archiving frame {}: {:#}", frame.id, e);
+                    continue;
+                }
+            };
+            let name = Path::new(&frame.file_path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("frame_{}", frame.id));
+            let mtime = frame.timestamp.timestamp().max(0) as u64;
+
+            let (offset, length) = writer.append(&name, &data, mtime).await?;
+            entries.push((
+                NewArchivedFrame {
+                    frame_id: frame.id,
+                    entry_offset: offset as i64,
+                    entry_length: length as i64,
+                },
+                frame.file_path.clone(),
+            ));
+        }
+
+        let size = writer.finish().await?;
+        Ok((entries, size))
+    }
+}
+
+impl JobHandler for ArchivalJob {
+    fn job_type(&self) -> &'static str {
+        ARCHIVAL_JOB
+    }
+
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a> {
+        Box::pin(async move {
+            let db = &ctx.state.db;
+            let storage = ctx.state.capture_storage();
+
+            let empty_archives = db.delete_empty_frame_archives().await?;
+            for path in &empty_archives {
+                if let Err(e) = storage.delete(path).await {
+                    debug!("{:#}", e);
+                }
+            }
+
+            // Start of the most recent day to archive whole
+            let cutoff = Utc::now() - chrono::Duration::days(self.config.after_days as i64);
+            let before = cutoff.date_naive().and_time(NaiveTime::MIN).and_utc();
+            let mut archived = 0;
+            let mut archives = 0;
+            // Frames not archived stay first in the order, so they are read
+            // again and skipped
+            let mut skipped = 0;
+
+            loop {
+                if ctx.is_cancelled().await {
+                    break;
+                }
+
+                let frames = db
+                    .get_frames_to_archive(before, skipped as i64 + ARCHIVAL_BATCH)
+                    .await?;
+                if frames.len() <= skipped {
+                    break;
+                }
+
+                for group in plan_archives(&frames[skipped..], self.config.max_archive_frames) {
+                    match self.archive(db, &storage, group).await {
+                        Ok(count) => {
+                            archived += count;
+                            archives += usize::from(count > 0);
+                            skipped += group.len() - count;
+                        }
+                        Err(e) => {
+                            warn!(
+                                "Failed to archive {} frames from frame {}: {:#}",
+                                group.len(),
+                                group[0].id,
+                                e
+                            );
+                            skipped += group.len();
+                        }
+                    }
+                }
+            }
+
+            if archived > 0 {
+                info!(
+                    "Archived {} frame images into {} archives",
+                    archived, archives
+                );
+            }
+
+            Ok(Some(json!({
+                "frames_archived": archived,
+                "archives_created": archives,
+                "frames_skipped": skipped,
+                "archives_deleted": empty_archives.len(),
+            })))
+        })
+    }
+}
+
+/// Split frames, ordered by capture time, into runs captured on the same
+/// (UTC) day, at most `max_frames` long
+fn plan_archives(frames: &[FrameRecord], max_frames: usize) -> Vec<&[FrameRecord]> {
+    let max_frames = max_frames.max(1);
+    let mut archives = Vec::new();
+    let mut start = 0;
+
+    for i in 1..=frames.len() {
+        let split = i == frames.len()
+            || i - start >= max_frames
+            || frames[start].timestamp.date_naive() != frames[i].timestamp.date_naive();
+        if split {
+            archives.push(&frames[start..i]);
+            start = i;
+        }
+    }
+
+    archives
+}
+
+/// Start the periodic archival worker as a background task
+pub fn spawn_archival_worker(
+    state: Arc<AppState>,
+    config: ArchivalConfig,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        info!(
+            "Starting archival worker for frames older than {} days",
+            config.after_days
+        );
+
+        let mut tick = interval(Duration::from_secs(config.check_interval_secs));
+        loop {
+            tick.tick().await;
+
+            if let Err(e) = ensure_archival_job(&state.db).await {
+                error!("Failed to queue archival job: {}", e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn frame(id: i64, day: u32, hour: u32) -> FrameRecord {
+        let timestamp = Utc.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap();
+        FrameRecord {
+            id,
+            chunk_id: None,
+            timestamp,
+            monitor_index: 0,
+            device_name: "monitor-0".to_string(),
+            file_path: format!("captures/frame_{}.jpg", id),
+            active_window: None,
+            active_process: None,
+            browser_url: None,
+            width: 1920,
+            height: 1080,
+            offset_index: 0,
+            focused: None,
+            pinned: false,
+            created_at: timestamp,
+        }
+    }
+
+    #[test]
+    fn test_plan_archives() {
+        let frames = vec![
+            frame(1, 1, 9),
+            frame(2, 1, 23),
+            frame(3, 2, 0),
+            frame(4, 2, 8),
+            frame(5, 2, 9),
+            frame(6, 4, 12),
+        ];
+
+        let ids: Vec<Vec<i64>> = plan_archives(&frames, 2)
+            .iter()
+            .map(|archive| archive.iter().map(|f| f.id).collect())
+            .collect();
+        assert_eq!(ids, vec![vec![1, 2], vec![3, 4], vec![5], vec![6]]);
+        assert!(plan_archives(&[], 10).is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+//! Frame compaction worker
+//!
+//! Encodes frames older than `after_days` into video chunks (see
+//! [`crate::video_archive`]) and deletes their individual images, so recent
+//! frames stay fast to open while the long tail takes a fraction of the
+//! space. Compaction runs as a `compaction` job on the job queue, queued
+//! periodically; each run also removes chunk files whose frames have all
+//! been deleted.
+
+use super::job_queue::{enqueue_job, JobContext, JobFuture, JobHandler};
+use crate::capture_store::CaptureStorage;
+use crate::state::AppState;
+use crate::video_archive::encode_chunk;
+use chrono::Utc;
+use screensearch_db::{DatabaseManager, FrameRecord, NewVideoChunk};
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info, warn};
+
+/// Job type that compacts old frames into video chunks
+pub const COMPACTION_JOB: &str = "compaction";
+
+/// Frames read per database round trip
+const COMPACTION_BATCH: i64 = 2000;
+
+/// Directory of the chunks among the captures
+const ARCHIVE_DIR: &str = "archive";
+
+/// Configuration for the frame compaction worker
+#[derive(Debug, Clone)]
+pub struct CompactionConfig {
+    /// Whether old frames are compacted
+    pub enabled: bool,
+    /// Frames older than this many days are compacted
+    pub after_days: u32,
+    /// ffmpeg executable, used to encode chunks and to decode frames from
+    /// them
+    pub ffmpeg_path: String,
+    /// Frames per second of video
+    pub fps: u32,
+    /// x264 constant rate factor (lower = better quality, larger chunks)
+    pub crf: u32,
+    /// Maximum frames per chunk
+    pub max_chunk_frames: usize,
+    /// Interval between scheduled compaction runs (seconds)
+    pub check_interval_secs: u64,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            after_days: 7,
+            ffmpeg_path: "ffmpeg".to_string(),
+            fps: 2,
+            crf: 23,
+            max_chunk_frames: 600,
+            check_interval_secs: 6 * 60 * 60,
+        }
+    }
+}
+
+/// Get the active `compaction` job, queuing one if none is queued or running
+pub async fn ensure_compaction_job(db: &DatabaseManager) -> screensearch_db::Result<i64> {
+    if let Some(job) = db.find_active_job(COMPACTION_JOB).await? {
+        return Ok(job.id);
+    }
+
+    enqueue_job(db, COMPACTION_JOB, &json!({})).await
+}
+
+/// Job handler that compacts old frames
+///
+/// Result: `{"frames_compacted": <count>, "chunks_created": <count>,
+/// "frames_skipped": <count>, "chunks_deleted": <count>}`. Frames whose chunk
+/// fails to encode are skipped and retried by the next run.
+pub struct CompactionJob {
+    config: CompactionConfig,
+}
+
+impl CompactionJob {
+    /// Create a compaction job handler
+    pub fn new(config: CompactionConfig) -> Self {
+        Self { config }
+    }
+
+    /// Encode `frames` into a chunk, point them at it and delete their images
+    async fn compact(
+        &self,
+        db: &DatabaseManager,
+        storage: &CaptureStorage,
+        frames: &[FrameRecord],
+    ) -> anyhow::Result<()> {
+        let (first, last) = match (frames.first(), frames.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Ok(()),
+        };
+
+        // Encoded locally, then stored next to the captures
+        let file_name = format!(
+            "chunk_{}_{}_{}.mp4",
+            first.monitor_index,
+            first.timestamp.format("%Y%m%d_%H%M%S"),
+            first.id
+        );
+        let output = std::env::temp_dir().join(&file_name);
+
+        let images: Vec<String> = frames.iter().map(|f| f.file_path.clone()).collect();
+        let fps = self.config.fps.max(1);
+        let encoded = encode_chunk(
+            &self.config.ffmpeg_path,
+            storage,
+            &images,
+            &output,
+            fps,
+            self.config.crf,
+        )
+        .await;
+        let stored = match encoded {
+            Ok(()) => {
+                storage
+                    .put_file(&format!("{}/{}", ARCHIVE_DIR, file_name), &output)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        let _ = tokio::fs::remove_file(&output).await;
+        let file_path = stored?;
+
+        let chunk = NewVideoChunk {
+            device_name: first.device_name.clone(),
+            file_path: file_path.clone(),
+            start_time: first.timestamp,
+            end_time: last.timestamp,
+            duration_ms: frames.len() as i64 * 1000 / fps as i64,
+            width: first.width,
+            height: first.height,
+            fps: fps as i32,
+        };
+        let ids: Vec<i64> = frames.iter().map(|f| f.id).collect();
+        if let Err(e) = db.insert_compacted_chunk(chunk, &ids).await {
+            let _ = storage.delete(&file_path).await;
+            return Err(e.into());
+        }
+
+        for image in images {
+            if let Err(e) = storage.delete(&image).await {
+                debug!("{:#}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl JobHandler for CompactionJob {
+    fn job_type(&self) -> &'static str {
+        COMPACTION_JOB
+    }
+
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a> {
+        Box::pin(async move {
+            let db = &ctx.state.db;
+            let storage = ctx.state.capture_storage();
+
+            let empty_chunks = db.delete_empty_video_chunks().await?;
+            for path in &empty_chunks {
+                if let Err(e) = storage.delete(path).await {
+                    debug!("{:#}", e);
+                }
+            }
+
+            let before = Utc::now() - chrono::Duration::days(self.config.after_days as i64);
+            let mut compacted = 0;
+            let mut chunks = 0;
+            // Frames of failed chunks stay first in the order, so they are
+            // read again and skipped
+            let mut skipped = 0;
+
+            loop {
+                if ctx.is_cancelled().await {
+                    break;
+                }
+
+                let frames = db
+                    .get_frames_to_compact(before, skipped as i64 + COMPACTION_BATCH)
+                    .await?;
+                if frames.len() <= skipped {
+                    break;
+                }
+
+                for group in plan_chunks(&frames[skipped..], self.config.max_chunk_frames) {
+                    match self.compact(db, &storage, group).await {
+                        Ok(()) => {
+                            compacted += group.len();
+                            chunks += 1;
+                        }
+                        Err(e) => {
+                            warn!(
+                                "Failed to compact {} frames from frame {}: {:#}",
+                                group.len(),
+                                group[0].id,
+                                e
+                            );
+                            skipped += group.len();
+                        }
+                    }
+                }
+            }
+
+            if compacted > 0 {
+                info!(
+                    "Compacted {} frames into {} video chunks",
+                    compacted, chunks
+                );
+            }
+
+            Ok(Some(json!({
+                "frames_compacted": compacted,
+                "chunks_created": chunks,
+                "frames_skipped": skipped,
+                "chunks_deleted": empty_chunks.len(),
+            })))
+        })
+    }
+}
+
+/// Split frames, ordered by device, monitor and time, into runs that can
+/// share a chunk: same device, monitor, size and image format, at most
+/// `max_frames` long
+fn plan_chunks(frames: &[FrameRecord], max_frames: usize) -> Vec<&[FrameRecord]> {
+    let max_frames = max_frames.max(1);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    for i in 1..=frames.len() {
+        let split = i == frames.len() || i - start >= max_frames || {
+            let (a, b) = (&frames[start], &frames[i]);
+            a.device_name != b.device_name
+                || a.monitor_index != b.monitor_index
+                || a.width != b.width
+                || a.height != b.height
+                || extension(&a.file_path) != extension(&b.file_path)
+        };
+        if split {
+            chunks.push(&frames[start..i]);
+            start = i;
+        }
+    }
+
+    chunks
+}
+
+fn extension(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+}
+
+/// Start the periodic compaction worker as a background task
+pub fn spawn_compaction_worker(
+    state: Arc<AppState>,
+    config: CompactionConfig,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        info!(
+            "Starting compaction worker for frames older than {} days",
+            config.after_days
+        );
+
+        let mut tick = interval(Duration::from_secs(config.check_interval_secs));
+        loop {
+            tick.tick().await;
+
+            if let Err(e) = ensure_compaction_job(&state.db).await {
+                error!("Failed to queue compaction job: {}", e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: i64, monitor_index: i32, width: i32, file_path: &str) -> FrameRecord {
+        FrameRecord {
+            id,
+            chunk_id: None,
+            timestamp: Utc::now(),
+            monitor_index,
+            device_name: format!("monitor-{}", monitor_index),
+            file_path: file_path.to_string(),
+            active_window: None,
+            active_process: None,
+            browser_url: None,
+            width,
+            height: 1080,
+            offset_index: 0,
+            focused: None,
+            pinned: false,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_plan_chunks() {
+        let frames = vec![
+            frame(1, 0, 1920, "a.jpg"),
+            frame(2, 0, 1920, "b.JPG"),
+            frame(3, 0, 1920, "c.jpg"),
+            frame(4, 0, 1920, "d.png"),
+            frame(5, 0, 1280, "e.png"),
+            frame(6, 1, 1280, "f.png"),
+        ];
+
+        let ids: Vec<Vec<i64>> = plan_chunks(&frames, 2)
+            .iter()
+            .map(|chunk| chunk.iter().map(|f| f.id).collect())
+            .collect();
+        assert_eq!(ids, vec![vec![1, 2], vec![3], vec![4], vec![5], vec![6]]);
+        assert!(plan_chunks(&[], 10).is_empty());
+    }
+}
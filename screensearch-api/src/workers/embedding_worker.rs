@@ -1,13 +1,31 @@
 //! Background embedding worker
 //!
-//! Processes frames without embeddings in the background.
+//! Processes frames without embeddings in the background. Work goes through
+//! the persisted embedding queue, which also backs `/embeddings/backfill`.
+//! The queue is drained by an `embeddings` job on the job queue; the periodic
+//! worker only queues new frames and makes sure such a job exists.
 
-use screensearch_db::DatabaseManager;
+use super::job_queue::{enqueue_job, JobContext, JobFuture, JobHandler};
+use chrono::Utc;
+use screensearch_capture::CaptureControl;
+use screensearch_db::{DatabaseManager, EmbeddingOutcome};
 use screensearch_embeddings::{EmbeddingEngine, TextChunker};
+use serde_json::json;
 use std::sync::Arc;
 use tokio::time::{interval, Duration};
 use tracing::{debug, error, info, warn};
 
+/// Attempts before a frame that keeps failing is marked failed
+const MAX_EMBEDDING_ATTEMPTS: i64 = 3;
+/// Claimed frames not finished within this time are claimed again
+const STALE_CLAIM_MINUTES: i64 = 10;
+/// OCR lines less likely right than this are left out of embeddings, so
+/// misread text does not pull frames into unrelated results
+const MIN_EMBED_CONFIDENCE: f32 = 0.5;
+
+/// Job type that drains the embedding queue
+pub const EMBEDDINGS_JOB: &str = "embeddings";
+
 /// Configuration for the background embedding worker
 #[derive(Debug, Clone)]
 pub struct EmbeddingWorkerConfig {
@@ -35,6 +53,8 @@ pub struct EmbeddingWorker {
     engine: Arc<EmbeddingEngine>,
     chunker: TextChunker,
     config: EmbeddingWorkerConfig,
+    /// Capture controls whose power saving pauses the worker
+    capture: Option<Arc<CaptureControl>>,
 }
 
 impl EmbeddingWorker {
@@ -49,105 +69,165 @@ impl EmbeddingWorker {
             engine,
             chunker: TextChunker::default(),
             config,
+            capture: None,
         }
     }
 
-    /// Process a batch of frames without embeddings
+    /// Pause while `capture` saves power, e.g. on battery
+    pub fn with_capture_control(mut self, capture: Arc<CaptureControl>) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Queue newly captured frames and process a batch from the queue
     pub async fn process_batch(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-        // Get frames without embeddings
-        let frames = self
+        self.db
+            .enqueue_new_embedding_frames(self.config.batch_size)
+            .await?;
+
+        self.process_queued_batch().await
+    }
+
+    /// Process a batch of frames from the embedding queue
+    ///
+    /// Frames are claimed before processing, so a backfill task and the
+    /// periodic worker can drain the queue at the same time. Returns the number
+    /// of frames taken from the queue, whatever their outcome.
+    #[tracing::instrument(skip_all)]
+    pub async fn process_queued_batch(
+        &self,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let stale_before = Utc::now() - chrono::Duration::minutes(STALE_CLAIM_MINUTES);
+        let frame_ids = self
             .db
-            .get_frames_without_embeddings(self.config.batch_size)
+            .claim_embedding_frames(self.config.batch_size, stale_before)
             .await?;
 
-        if frames.is_empty() {
+        if frame_ids.is_empty() {
             debug!("No frames to process");
             return Ok(0);
         }
 
-        info!("Processing {} frames for embeddings", frames.len());
+        info!("Processing {} frames for embeddings", frame_ids.len());
+        let claimed = frame_ids.len();
         let mut processed = 0;
 
-        for frame in frames {
-            // Get OCR text for the frame
-            let ocr_texts = self.db.get_ocr_text_for_frame(frame.id).await?;
+        for frame_id in frame_ids {
+            let outcome = match self.embed_frame(frame_id).await {
+                Ok(true) => EmbeddingOutcome::Completed,
+                Ok(false) => {
+                    debug!("Frame {} has no OCR text", frame_id);
+                    EmbeddingOutcome::Skipped
+                }
+                Err(e) => {
+                    warn!("Failed to embed frame {}: {}", frame_id, e);
+                    EmbeddingOutcome::Failed(e.to_string())
+                }
+            };
 
-            if ocr_texts.is_empty() {
-                // No OCR text, still mark as processed by inserting empty embedding
-                debug!("Frame {} has no OCR text", frame.id);
-                continue;
-            }
+            self.db
+                .record_embedding_outcome(frame_id, &outcome, MAX_EMBEDDING_ATTEMPTS)
+                .await?;
 
-            // Combine OCR text and chunk it
-            let combined_text: String = ocr_texts
-                .iter()
-                .map(|o| o.text.as_str())
-                .collect::<Vec<_>>()
-                .join(" ");
-
-            let chunks = self.chunker.chunk_text(&combined_text);
-
-            // Generate embeddings for each chunk
-            // Start a transaction for this frame's embeddings
-            // This ensures we don't have partial embeddings if something fails
-            let mut tx = self.db.pool().begin().await.map_err(|e| {
-                Box::new(std::io::Error::other(
-                    format!("Failed to start transaction: {}", e),
-                )) as Box<dyn std::error::Error + Send + Sync>
-            })?;
+            if outcome == EmbeddingOutcome::Completed {
+                processed += 1;
 
-            // Generate embeddings for each chunk
-            for (chunk_index, chunk_text) in chunks.iter().enumerate() {
-                // Generate embedding
-                let embedding = self.engine.embed(chunk_text)?;
-
-                // Convert Vec<f32> to Vec<u8> (little-endian bytes) for BLOB storage
-                let embedding_bytes: Vec<u8> = embedding
-                    .iter()
-                    .flat_map(|f| f.to_le_bytes())
-                    .collect();
-
-                // Insert into DB using the transaction
-                sqlx::query(
-                    r#"
-                    INSERT INTO embeddings (frame_id, chunk_text, chunk_index, embedding, embedding_dim)
-                    VALUES (?, ?, ?, ?, ?)
-                    "#
-                )
-                .bind(frame.id)
-                .bind(chunk_text)
-                .bind(chunk_index as i32)
-                .bind(embedding_bytes)
-                .bind(384) // Dimension
-                .execute(&mut *tx)
-                .await
-                .map_err(|e| {
-                   Box::new(std::io::Error::other(
-                       format!("Failed to insert embedding: {}", e),
-                   )) as Box<dyn std::error::Error + Send + Sync>
-                })?;
+                // Update last processed frame ID
+                self.db
+                    .set_metadata("embeddings_last_processed_frame_id", &frame_id.to_string())
+                    .await?;
             }
-            
-            // Commit transaction
-            tx.commit().await.map_err(|e| {
-                Box::new(std::io::Error::other(
-                    format!("Failed to commit transaction: {}", e),
-                )) as Box<dyn std::error::Error + Send + Sync>
+        }
+
+        info!("Processed {} of {} frames", processed, claimed);
+        Ok(claimed)
+    }
+
+    /// Generate and store embeddings for one frame
+    ///
+    /// Returns `false` if the frame has no OCR text to embed.
+    #[tracing::instrument(skip(self))]
+    async fn embed_frame(
+        &self,
+        frame_id: i64,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        // Get OCR text for the frame
+        let mut ocr_texts = self.db.get_ocr_text_for_frame(frame_id).await?;
+        ocr_texts.retain(|o| o.confidence >= MIN_EMBED_CONFIDENCE);
+
+        if ocr_texts.is_empty() {
+            return Ok(false);
+        }
+
+        // Combine OCR text and chunk it
+        let combined_text: String = ocr_texts
+            .iter()
+            .map(|o| o.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let chunks = self.chunker.chunk_text(&combined_text);
+
+        // Start a transaction for this frame's embeddings
+        // This ensures we don't have partial embeddings if something fails
+        let mut tx = self.db.pool().begin().await.map_err(|e| {
+            Box::new(std::io::Error::other(format!(
+                "Failed to start transaction: {}",
+                e
+            ))) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+
+        // Replace embeddings left by an earlier, interrupted attempt
+        sqlx::query("DELETE FROM embeddings WHERE frame_id = ?")
+            .bind(frame_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to clear embeddings: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
             })?;
 
-            processed += 1;
+        // Generate embeddings for each chunk
+        for (chunk_index, chunk_text) in chunks.iter().enumerate() {
+            // Generate embedding
+            let embedding = self.engine.embed(chunk_text)?;
 
-            // Update last processed frame ID
-            self.db
-                .set_metadata(
-                    "embeddings_last_processed_frame_id",
-                    &frame.id.to_string(),
-                )
-                .await?;
+            // Convert Vec<f32> to Vec<u8> (little-endian bytes) for BLOB storage
+            let embedding_bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+
+            // Insert into DB using the transaction
+            sqlx::query(
+                r#"
+                INSERT INTO embeddings (frame_id, chunk_text, chunk_index, embedding, embedding_dim)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(frame_id)
+            .bind(chunk_text)
+            .bind(chunk_index as i32)
+            .bind(embedding_bytes)
+            .bind(384) // Dimension
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                Box::new(std::io::Error::other(format!(
+                    "Failed to insert embedding: {}",
+                    e
+                ))) as Box<dyn std::error::Error + Send + Sync>
+            })?;
         }
 
-        info!("Processed {} frames", processed);
-        Ok(processed)
+        // Commit transaction
+        tx.commit().await.map_err(|e| {
+            Box::new(std::io::Error::other(format!(
+                "Failed to commit transaction: {}",
+                e
+            ))) as Box<dyn std::error::Error + Send + Sync>
+        })?;
+
+        Ok(true)
     }
 
     /// Run the worker continuously
@@ -184,13 +264,18 @@ impl EmbeddingWorker {
                 // Skips processing this tick if disabled dynamically
                 continue;
             }
+            if self
+                .capture
+                .as_ref()
+                .is_some_and(|capture| capture.power().workers_paused())
+            {
+                debug!("Saving power, not embedding new frames");
+                continue;
+            }
 
-            match self.process_batch().await {
-                Ok(count) => {
-                    if count > 0 {
-                        info!("Embedding worker processed {} frames", count);
-                    }
-                }
+            match self.schedule().await {
+                Ok(Some(id)) => debug!("Embedding job {} is draining the queue", id),
+                Ok(None) => {}
                 Err(e) => {
                     error!("Embedding worker error: {}", e);
                 }
@@ -198,6 +283,25 @@ impl EmbeddingWorker {
         }
     }
 
+    /// Queue newly captured frames and start a job to embed them if needed
+    async fn schedule(&self) -> Result<Option<i64>, Box<dyn std::error::Error + Send + Sync>> {
+        let queued = self
+            .db
+            .enqueue_new_embedding_frames(self.config.batch_size)
+            .await?;
+        if queued > 0 {
+            info!("Queued {} new frames for embedding", queued);
+        }
+
+        let stats = self.db.get_embedding_queue_stats(Utc::now()).await?;
+        if stats.pending == 0 {
+            return Ok(None);
+        }
+
+        let id = ensure_embedding_job(&self.db, self.config.batch_size).await?;
+        Ok(Some(id))
+    }
+
     /// Run a single batch (for manual triggering)
     pub async fn run_once(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
         self.process_batch().await
@@ -208,10 +312,107 @@ impl EmbeddingWorker {
 pub fn spawn_embedding_worker(
     db: Arc<DatabaseManager>,
     engine: Arc<EmbeddingEngine>,
+    capture: Arc<CaptureControl>,
     config: EmbeddingWorkerConfig,
 ) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
-        let worker = EmbeddingWorker::new(db, engine, config);
+        let worker = EmbeddingWorker::new(db, engine, config).with_capture_control(capture);
         worker.run().await;
     })
 }
+
+/// Embed captured frames until none are left without embeddings
+///
+/// Run at shutdown so the last frames are searchable semantically on the
+/// next start; callers bound it with a timeout, since frames interrupted
+/// mid-batch are claimed again later. Returns the number of frames taken
+/// from the queue.
+pub async fn flush_embeddings(
+    db: Arc<DatabaseManager>,
+    engine: Arc<EmbeddingEngine>,
+    batch_size: i64,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let worker = EmbeddingWorker::new(
+        db,
+        engine,
+        EmbeddingWorkerConfig {
+            batch_size,
+            enabled: true,
+            ..Default::default()
+        },
+    );
+
+    let mut total = 0;
+    loop {
+        match worker.process_batch().await? {
+            0 => return Ok(total),
+            count => total += count,
+        }
+    }
+}
+
+/// Get the active `embeddings` job, queuing one if none is queued or running
+pub async fn ensure_embedding_job(
+    db: &DatabaseManager,
+    batch_size: i64,
+) -> screensearch_db::Result<i64> {
+    if let Some(job) = db.find_active_job(EMBEDDINGS_JOB).await? {
+        return Ok(job.id);
+    }
+
+    enqueue_job(db, EMBEDDINGS_JOB, &json!({ "batch_size": batch_size })).await
+}
+
+/// Job handler that drains the embedding queue
+///
+/// Payload: `{"batch_size": 50}`. Result: `{"frames": <frames processed>}`.
+pub struct EmbeddingJob;
+
+impl JobHandler for EmbeddingJob {
+    fn job_type(&self) -> &'static str {
+        EMBEDDINGS_JOB
+    }
+
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a> {
+        Box::pin(async move {
+            let batch_size = ctx
+                .payload
+                .get("batch_size")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(EmbeddingWorkerConfig::default().batch_size)
+                .clamp(1, 500);
+
+            let engine = ctx
+                .state
+                .get_embedding_engine()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load embedding engine: {}", e))?;
+
+            let worker = EmbeddingWorker::new(
+                Arc::clone(&ctx.state.db),
+                engine,
+                EmbeddingWorkerConfig {
+                    batch_size,
+                    enabled: true,
+                    ..Default::default()
+                },
+            );
+
+            let mut total = 0;
+            while !ctx.is_cancelled().await {
+                // The worker starts another job once power saving ends
+                if ctx.state.capture.power().workers_paused() {
+                    info!("Saving power, pausing embedding generation");
+                    break;
+                }
+                match worker.process_queued_batch().await {
+                    Ok(0) => break,
+                    Ok(count) => total += count,
+                    Err(e) => return Err(anyhow::anyhow!("{}", e)),
+                }
+            }
+
+            Ok(Some(json!({ "frames": total })))
+        })
+    }
+}
@@ -0,0 +1,95 @@
+//! Screenshot folder import worker
+//!
+//! Imports the images of a folder as frames (see [`crate::image_import`]) in
+//! an `image_import` job, queued by `POST /admin/import-images`. Images that
+//! fail to import are logged and counted; the job goes on with the rest.
+
+use super::job_queue::{enqueue_job, JobContext, JobFuture, JobHandler};
+use crate::image_import::{find_images, import_image, ImportOutcome};
+use screensearch_capture::OcrEngine;
+use screensearch_db::DatabaseManager;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Job type that imports a folder of screenshots
+pub const IMAGE_IMPORT_JOB: &str = "image_import";
+
+/// Queue an `image_import` job for the images in `dir`
+pub async fn enqueue_image_import(
+    db: &DatabaseManager,
+    dir: &Path,
+    recursive: bool,
+) -> screensearch_db::Result<i64> {
+    let payload = json!({
+        "path": dir.to_string_lossy(),
+        "recursive": recursive,
+    });
+    enqueue_job(db, IMAGE_IMPORT_JOB, &payload).await
+}
+
+/// Job handler that imports the images of a folder
+///
+/// Payload: `{"path": <folder>, "recursive": <bool>}`.
+/// Result: `{"images_found": <count>, "imported": <count>, "skipped":
+/// <count>, "failed": <count>}`; skipped images were imported before.
+pub struct ImageImportJob;
+
+impl JobHandler for ImageImportJob {
+    fn job_type(&self) -> &'static str {
+        IMAGE_IMPORT_JOB
+    }
+
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a> {
+        Box::pin(async move {
+            let dir = ctx
+                .payload
+                .get("path")
+                .and_then(|v| v.as_str())
+                .map(PathBuf::from)
+                .ok_or_else(|| anyhow::anyhow!("Missing path in job payload"))?;
+            let recursive = ctx
+                .payload
+                .get("recursive")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true);
+
+            let search_dir = dir.clone();
+            let images =
+                tokio::task::spawn_blocking(move || find_images(&search_dir, recursive)).await??;
+            info!("Importing {} images from {}", images.len(), dir.display());
+
+            let engine = OcrEngine::new().await?;
+            let storage = ctx.state.capture_storage();
+            let (mut imported, mut skipped, mut failed) = (0, 0, 0);
+            for path in &images {
+                if ctx.is_cancelled().await {
+                    break;
+                }
+
+                match import_image(&ctx.state.db, &storage, &engine, path).await {
+                    Ok(ImportOutcome::Imported(_)) => imported += 1,
+                    Ok(ImportOutcome::Skipped) => skipped += 1,
+                    Err(e) => {
+                        warn!("Failed to import {}: {:#}", path.display(), e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            info!(
+                "Imported {} images from {} ({} already imported, {} failed)",
+                imported,
+                dir.display(),
+                skipped,
+                failed
+            );
+            Ok(Some(json!({
+                "images_found": images.len(),
+                "imported": imported,
+                "skipped": skipped,
+                "failed": failed,
+            })))
+        })
+    }
+}
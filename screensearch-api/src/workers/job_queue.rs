@@ -0,0 +1,267 @@
+//! Persistent background job queue
+//!
+//! Jobs are rows in the `jobs` table. Workers register a [`JobHandler`] for a
+//! job type, and the queue loop claims runnable jobs of registered types and
+//! runs them, retrying failures with a delay. Jobs survive restarts: anything
+//! left running by a previous process is queued again at startup.
+
+use crate::state::AppState;
+use chrono::Utc;
+use screensearch_db::{DatabaseManager, NewJob};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info, info_span, warn, Instrument};
+
+/// Attempts before a failing job is marked failed
+pub const DEFAULT_MAX_ATTEMPTS: i64 = 3;
+
+/// Future returned by [`JobHandler::run`]
+pub type JobFuture<'a> = Pin<Box<dyn Future<Output = anyhow::Result<Option<Value>>> + Send + 'a>>;
+
+/// Executes jobs of one type
+pub trait JobHandler: Send + Sync {
+    /// Job type this handler executes
+    fn job_type(&self) -> &'static str;
+
+    /// Run a job
+    ///
+    /// Returns an optional JSON result stored with the completed job. Long
+    /// running handlers should check [`JobContext::is_cancelled`] between steps
+    /// and return early once the job has been cancelled.
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a>;
+}
+
+/// Context passed to a running job
+pub struct JobContext {
+    /// Job ID
+    pub id: i64,
+    /// Decoded job payload (`Null` if the stored payload is not valid JSON)
+    pub payload: Value,
+    /// Shared application state
+    pub state: Arc<AppState>,
+}
+
+impl JobContext {
+    /// Check whether the job has been cancelled through the API
+    pub async fn is_cancelled(&self) -> bool {
+        match self.state.db.get_job(self.id).await {
+            Ok(Some(job)) => job.state == "cancelled",
+            Ok(None) => true,
+            Err(e) => {
+                warn!("Failed to check job {} state: {}", self.id, e);
+                false
+            }
+        }
+    }
+}
+
+/// Configuration for the job queue
+#[derive(Debug, Clone)]
+pub struct JobQueueConfig {
+    /// Interval between checks for runnable jobs (seconds)
+    pub poll_interval_secs: u64,
+    /// Maximum number of jobs running at once
+    pub max_concurrent: usize,
+    /// Delay before a failed job is retried (seconds)
+    pub retry_delay_secs: i64,
+}
+
+impl Default for JobQueueConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 2,
+            max_concurrent: 2,
+            retry_delay_secs: 30,
+        }
+    }
+}
+
+/// Queue an instance of a job type
+pub async fn enqueue_job(
+    db: &DatabaseManager,
+    job_type: &str,
+    payload: &Value,
+) -> screensearch_db::Result<i64> {
+    let id = db
+        .insert_job(NewJob {
+            job_type: job_type.to_string(),
+            payload: payload.to_string(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        })
+        .await?;
+
+    debug!("Queued {} job {}", job_type, id);
+    Ok(id)
+}
+
+/// Registry of job handlers and the loop that runs queued jobs
+pub struct JobQueue {
+    handlers: RwLock<HashMap<&'static str, Arc<dyn JobHandler>>>,
+    config: JobQueueConfig,
+}
+
+impl JobQueue {
+    /// Create a job queue with no registered handlers
+    pub fn new(config: JobQueueConfig) -> Self {
+        Self {
+            handlers: RwLock::new(HashMap::new()),
+            config,
+        }
+    }
+
+    /// Register the handler for a job type, replacing any previous one
+    ///
+    /// Jobs of unregistered types stay queued until a handler is registered.
+    pub fn register(&self, handler: Arc<dyn JobHandler>) {
+        let job_type = handler.job_type();
+        info!("Registered job handler: {}", job_type);
+        self.handlers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(job_type, handler);
+    }
+
+    /// Job types with a registered handler
+    pub fn job_types(&self) -> Vec<&'static str> {
+        let mut types: Vec<_> = self
+            .handlers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .copied()
+            .collect();
+        types.sort_unstable();
+        types
+    }
+
+    fn handler(&self, job_type: &str) -> Option<Arc<dyn JobHandler>> {
+        self.handlers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(job_type)
+            .cloned()
+    }
+
+    /// Run the queue continuously
+    pub async fn run(self: Arc<Self>, state: Arc<AppState>) {
+        match state.db.requeue_running_jobs().await {
+            Ok(0) => {}
+            Ok(count) => info!("Requeued {} jobs interrupted by shutdown", count),
+            Err(e) => error!("Failed to requeue interrupted jobs: {}", e),
+        }
+
+        info!(
+            "Starting job queue ({} concurrent jobs)",
+            self.config.max_concurrent
+        );
+
+        let permits = Arc::new(Semaphore::new(self.config.max_concurrent.max(1)));
+        let mut tick = interval(Duration::from_secs(self.config.poll_interval_secs));
+
+        loop {
+            tick.tick().await;
+
+            // Start as many runnable jobs as there are free slots
+            while let Ok(permit) = Arc::clone(&permits).try_acquire_owned() {
+                let job_types = self.job_types();
+                let job = match state.db.claim_next_job(&job_types).await {
+                    Ok(Some(job)) => job,
+                    Ok(None) => break,
+                    Err(e) => {
+                        error!("Failed to claim job: {}", e);
+                        break;
+                    }
+                };
+
+                let Some(handler) = self.handler(&job.job_type) else {
+                    // Only registered types are claimed, so this is unreachable in practice
+                    warn!("No handler for {} job {}", job.job_type, job.id);
+                    let error = format!("No handler registered for {}", job.job_type);
+                    if let Err(e) = state.db.fail_job(job.id, &error, Utc::now()).await {
+                        error!("Failed to release job {}: {}", job.id, e);
+                    }
+                    continue;
+                };
+
+                let ctx = JobContext {
+                    id: job.id,
+                    payload: serde_json::from_str(&job.payload).unwrap_or(Value::Null),
+                    state: Arc::clone(&state),
+                };
+                info!(
+                    "Running {} job {} (attempt {} of {})",
+                    job.job_type, job.id, job.attempts, job.max_attempts
+                );
+
+                let span = info_span!("job", id = job.id, job_type = %job.job_type);
+                tokio::spawn(
+                    execute(handler, ctx, self.config.retry_delay_secs, permit).instrument(span),
+                );
+            }
+        }
+    }
+}
+
+/// Run one claimed job and record its outcome
+async fn execute(
+    handler: Arc<dyn JobHandler>,
+    ctx: JobContext,
+    retry_delay_secs: i64,
+    _permit: OwnedSemaphorePermit,
+) {
+    let db = Arc::clone(&ctx.state.db);
+
+    let recorded = match handler.run(&ctx).await {
+        Ok(result) => {
+            info!("{} job {} finished", handler.job_type(), ctx.id);
+            let result = result.map(|v| v.to_string());
+            db.complete_job(ctx.id, result.as_deref()).await
+        }
+        Err(e) => {
+            warn!("{} job {} failed: {}", handler.job_type(), ctx.id, e);
+            let retry_after = Utc::now() + chrono::Duration::seconds(retry_delay_secs);
+            db.fail_job(ctx.id, &e.to_string(), retry_after).await
+        }
+    };
+
+    if let Err(e) = recorded {
+        error!("Failed to record outcome of job {}: {}", ctx.id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoopJob(&'static str);
+
+    impl JobHandler for NoopJob {
+        fn job_type(&self) -> &'static str {
+            self.0
+        }
+
+        fn run<'a>(&'a self, _ctx: &'a JobContext) -> JobFuture<'a> {
+            Box::pin(async { Ok(None) })
+        }
+    }
+
+    #[test]
+    fn test_register_job_types() {
+        let queue = JobQueue::new(JobQueueConfig::default());
+        assert!(queue.job_types().is_empty());
+
+        queue.register(Arc::new(NoopJob("report")));
+        queue.register(Arc::new(NoopJob("embeddings")));
+        // Registering a type again replaces its handler
+        queue.register(Arc::new(NoopJob("report")));
+
+        assert_eq!(queue.job_types(), vec!["embeddings", "report"]);
+        assert!(queue.handler("report").is_some());
+        assert!(queue.handler("unknown").is_none());
+    }
+}
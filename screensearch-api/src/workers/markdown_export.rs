@@ -0,0 +1,399 @@
+//! Markdown daily notes export worker
+//!
+//! Writes one Markdown note per day (see [`crate::daily_notes`]) into a
+//! folder of a notes vault, with its screenshots as JPEG thumbnails in an
+//! `attachments` folder next to the notes. Days are exported by a
+//! `markdown_export` job, queued by `POST /export/markdown` or, when the
+//! schedule is enabled, every day at the configured local time. A scheduled
+//! run also exports the days since the previous one, so days missed while
+//! the app was closed are caught up.
+
+use super::job_queue::{enqueue_job, JobContext, JobFuture, JobHandler};
+use crate::daily_notes::{self, DailyNote, Screenshot};
+use crate::export::make_thumbnail;
+use crate::state::AppState;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use screensearch_db::{FrameFilter, Pagination};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+/// Job type that exports daily notes
+pub const MARKDOWN_EXPORT_JOB: &str = "markdown_export";
+
+/// Days exported by one job at most
+pub const MAX_EXPORT_DAYS: i64 = 366;
+
+/// Metadata key holding the date of the last scheduled export
+const LAST_RUN_KEY: &str = "markdown_export_last_run";
+
+/// Days caught up by a scheduled export at most
+const MAX_CATCH_UP_DAYS: i64 = 7;
+
+/// Frames of a day read to find its sessions
+const MAX_DAY_FRAMES: i64 = 50_000;
+
+/// Folder of the screenshots, next to the notes
+const ATTACHMENTS_DIR: &str = "attachments";
+
+/// Configuration for the daily notes export
+#[derive(Debug, Clone)]
+pub struct MarkdownExportConfig {
+    /// Whether a note is exported every day
+    pub enabled: bool,
+    /// Vault (or any folder) the notes are written to; empty = no export
+    pub vault_dir: PathBuf,
+    /// Folder of the notes inside the vault
+    pub folder: String,
+    /// Local time of the daily export
+    pub export_at: NaiveTime,
+    /// Screenshots embedded per note
+    pub screenshots: usize,
+    /// Interval between schedule checks (seconds)
+    pub check_interval_secs: u64,
+}
+
+impl Default for MarkdownExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            vault_dir: PathBuf::new(),
+            folder: "ScreenSearch".to_string(),
+            export_at: NaiveTime::from_hms_opt(23, 55, 0).unwrap_or(NaiveTime::MIN),
+            screenshots: 6,
+            check_interval_secs: 60,
+        }
+    }
+}
+
+impl MarkdownExportConfig {
+    /// Folder the notes are written to
+    pub fn notes_dir(&self) -> PathBuf {
+        self.vault_dir.join(&self.folder)
+    }
+}
+
+/// Queue a `markdown_export` job for the days from `from` to `to`
+pub async fn enqueue_markdown_export(
+    db: &screensearch_db::DatabaseManager,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> screensearch_db::Result<i64> {
+    let payload = json!({
+        "from": from.format("%Y-%m-%d").to_string(),
+        "to": to.format("%Y-%m-%d").to_string(),
+    });
+    enqueue_job(db, MARKDOWN_EXPORT_JOB, &payload).await
+}
+
+/// Job handler that writes the daily notes of a range of days
+///
+/// Payload: `{"from": "YYYY-MM-DD", "to": "YYYY-MM-DD"}` (local dates).
+/// Result: `{"notes_written": <count>, "days_without_frames": <count>,
+/// "notes_dir": <path>}`. Days without frames get no note.
+pub struct MarkdownExportJob {
+    config: MarkdownExportConfig,
+}
+
+impl MarkdownExportJob {
+    /// Create a daily notes export job handler
+    pub fn new(config: MarkdownExportConfig) -> Self {
+        Self { config }
+    }
+
+    /// Write the note of a day; false if nothing was captured that day
+    async fn export_day(&self, state: &AppState, date: NaiveDate) -> anyhow::Result<bool> {
+        let start = local_midnight(date)?;
+        let end = local_midnight(date + Duration::days(1))?;
+        // Frames at midnight belong to the next day
+        let last = end - Duration::milliseconds(1);
+
+        let filter = FrameFilter {
+            start_time: Some(start),
+            end_time: Some(last),
+            app_name: None,
+            device_name: None,
+            tag_ids: None,
+            monitor_index: None,
+            exclude_apps: None,
+            exclude_tags: None,
+        };
+        let pagination = Pagination {
+            limit: MAX_DAY_FRAMES,
+            offset: 0,
+        };
+        let mut frames = state
+            .db
+            .get_frames_in_range(start, last, filter, pagination)
+            .await?;
+        if frames.is_empty() {
+            return Ok(false);
+        }
+        frames.reverse();
+
+        let frame_count = state.db.count_frames_in_range(start, last).await?;
+        let summaries = state
+            .db
+            .list_activity_summaries(
+                Some(start),
+                Some(end),
+                Pagination {
+                    limit: 48,
+                    offset: 0,
+                },
+            )
+            .await?;
+        let report = state.db.find_report("daily", start, end).await?;
+        let sessions = daily_notes::sessions(&frames);
+
+        let notes_dir = self.config.notes_dir();
+        let attachments = notes_dir.join(ATTACHMENTS_DIR);
+        tokio::fs::create_dir_all(&attachments).await?;
+
+        let prefix = format!("{}_", date.format("%Y-%m-%d"));
+        let mut screenshots = Vec::new();
+        for session in daily_notes::key_sessions(&sessions, self.config.screenshots) {
+            let Some(frame) = frames.iter().find(|frame| frame.id == session.frame_id) else {
+                continue;
+            };
+            let file_name = format!("{}{}.jpg", prefix, frame.id);
+            if let Err(e) = write_screenshot(state, frame, &attachments.join(&file_name)).await {
+                warn!("Failed to export screenshot of frame {}: {:#}", frame.id, e);
+                continue;
+            }
+            screenshots.push(Screenshot {
+                timestamp: frame.timestamp,
+                app: session.app.clone(),
+                link: format!("{}/{}", ATTACHMENTS_DIR, file_name),
+            });
+        }
+        remove_stale_screenshots(&attachments, &prefix, &screenshots).await;
+
+        let note = DailyNote {
+            date,
+            frame_count,
+            summaries,
+            sessions,
+            screenshots,
+            report: report.map(|report| report.content),
+        };
+        let generated = daily_notes::render_note(&note, &Local);
+
+        let path = notes_dir.join(format!("{}.md", date.format("%Y-%m-%d")));
+        let existing = match tokio::fs::read_to_string(&path).await {
+            Ok(existing) => Some(existing),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let content = daily_notes::merge_note(existing.as_deref(), &generated);
+
+        // Replace the note at once, so the notes app never reads half of it
+        let temp = path.with_extension("md.tmp");
+        tokio::fs::write(&temp, content).await?;
+        tokio::fs::rename(&temp, &path).await?;
+
+        debug!("Exported daily note {}", path.display());
+        Ok(true)
+    }
+}
+
+impl JobHandler for MarkdownExportJob {
+    fn job_type(&self) -> &'static str {
+        MARKDOWN_EXPORT_JOB
+    }
+
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a> {
+        Box::pin(async move {
+            let date = |key: &str| {
+                ctx.payload
+                    .get(key)
+                    .and_then(|v| v.as_str())
+                    .and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid {} date in job payload", key))
+            };
+            let from = date("from")?;
+            let to = date("to")?;
+
+            let mut written = 0;
+            let mut without_frames = 0;
+            for day in from.iter_days().take_while(|day| *day <= to) {
+                if ctx.is_cancelled().await {
+                    break;
+                }
+
+                if self.export_day(&ctx.state, day).await? {
+                    written += 1;
+                } else {
+                    without_frames += 1;
+                }
+            }
+
+            if written > 0 {
+                info!(
+                    "Exported {} daily notes to {}",
+                    written,
+                    self.config.notes_dir().display()
+                );
+            }
+
+            Ok(Some(json!({
+                "notes_written": written,
+                "days_without_frames": without_frames,
+                "notes_dir": self.config.notes_dir().to_string_lossy(),
+            })))
+        })
+    }
+}
+
+/// Start of a local day in UTC
+fn local_midnight(date: NaiveDate) -> anyhow::Result<DateTime<Utc>> {
+    date.and_time(NaiveTime::MIN)
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| anyhow::anyhow!("No local midnight on {}", date))
+}
+
+/// Write a thumbnail of a frame's image to `path`
+async fn write_screenshot(
+    state: &AppState,
+    frame: &screensearch_db::FrameRecord,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let data = crate::video_archive::read_frame_image(
+        &state.db,
+        &state.capture_storage(),
+        state.ffmpeg_path(),
+        frame,
+    )
+    .await?;
+    let thumbnail = tokio::task::spawn_blocking(move || make_thumbnail(&data)).await??;
+    tokio::fs::write(path, thumbnail.jpeg).await?;
+    Ok(())
+}
+
+/// Delete screenshots of a day exported earlier that the note no longer shows
+async fn remove_stale_screenshots(dir: &Path, prefix: &str, keep: &[Screenshot]) {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let link = format!("{}/{}", ATTACHMENTS_DIR, name);
+        let kept = keep.iter().any(|shot| shot.link == link);
+        if name.starts_with(prefix) && !kept {
+            if let Err(e) = tokio::fs::remove_file(entry.path()).await {
+                debug!("Failed to remove {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+}
+
+/// Days a scheduled export covers, if one is due
+///
+/// An export is due once the export time has passed and none ran today. It
+/// covers the days since the last run, including that day, whose end was
+/// not captured yet when it ran.
+fn due_range(
+    now: NaiveDateTime,
+    at: NaiveTime,
+    last_run: Option<NaiveDate>,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let today = now.date();
+    if now.time() < at || last_run == Some(today) {
+        return None;
+    }
+
+    let earliest = today - Duration::days(MAX_CATCH_UP_DAYS - 1);
+    let from = last_run.map_or(today, |last| last.max(earliest).min(today));
+    Some((from, today))
+}
+
+/// Queue an export job if the daily export is due
+async fn export_if_due(state: &AppState, at: NaiveTime) {
+    let last_run = match state.db.get_metadata(LAST_RUN_KEY).await {
+        Ok(value) => value.and_then(|v| NaiveDate::parse_from_str(&v, "%Y-%m-%d").ok()),
+        Err(e) => {
+            warn!("Failed to read last daily notes export: {}", e);
+            return;
+        }
+    };
+
+    let Some((from, to)) = due_range(Local::now().naive_local(), at, last_run) else {
+        return;
+    };
+
+    // Record the run before queuing so the job is queued once per day
+    let today = to.format("%Y-%m-%d").to_string();
+    if let Err(e) = state.db.set_metadata(LAST_RUN_KEY, &today).await {
+        error!("Failed to record daily notes export: {}", e);
+        return;
+    }
+
+    match enqueue_markdown_export(&state.db, from, to).await {
+        Ok(id) => info!("Queued daily notes export job {} ({} to {})", id, from, to),
+        Err(e) => error!("Failed to queue daily notes export: {}", e),
+    }
+}
+
+/// Start the daily notes export schedule as a background task
+pub fn spawn_markdown_export_scheduler(
+    state: Arc<AppState>,
+    config: MarkdownExportConfig,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        info!(
+            "Exporting daily notes to {} at {}",
+            config.notes_dir().display(),
+            config.export_at.format("%H:%M")
+        );
+
+        let mut tick = interval(std::time::Duration::from_secs(config.check_interval_secs));
+        loop {
+            tick.tick().await;
+            export_if_due(&state, config.export_at).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_due_range() {
+        let time = NaiveTime::from_hms_opt(23, 55, 0).unwrap();
+
+        assert_eq!(due_range(at("2025-12-10", "23:50"), time, None), None);
+        assert_eq!(
+            due_range(at("2025-12-10", "23:55"), time, None),
+            Some((date("2025-12-10"), date("2025-12-10")))
+        );
+        assert_eq!(
+            due_range(at("2025-12-10", "23:59"), time, Some(date("2025-12-10"))),
+            None
+        );
+
+        // Yesterday is exported again to add its last minutes
+        assert_eq!(
+            due_range(at("2025-12-10", "23:56"), time, Some(date("2025-12-09"))),
+            Some((date("2025-12-09"), date("2025-12-10")))
+        );
+
+        // Long breaks are caught up for a week at most
+        assert_eq!(
+            due_range(at("2025-12-10", "23:56"), time, Some(date("2025-11-01"))),
+            Some((date("2025-12-04"), date("2025-12-10")))
+        );
+    }
+}
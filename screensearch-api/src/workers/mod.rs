@@ -1,5 +1,37 @@
 //! Background workers module
 
+pub mod archival;
+pub mod compaction;
 pub mod embedding_worker;
+pub mod image_import;
+pub mod job_queue;
+pub mod markdown_export;
+pub mod redaction;
+pub mod report_scheduler;
+pub mod retention;
+pub mod summarizer;
 
-pub use embedding_worker::{spawn_embedding_worker, EmbeddingWorker, EmbeddingWorkerConfig};
+pub use archival::{
+    ensure_archival_job, spawn_archival_worker, ArchivalConfig, ArchivalJob, ARCHIVAL_JOB,
+};
+pub use compaction::{
+    ensure_compaction_job, spawn_compaction_worker, CompactionConfig, CompactionJob, COMPACTION_JOB,
+};
+pub use embedding_worker::{
+    ensure_embedding_job, flush_embeddings, spawn_embedding_worker, EmbeddingJob, EmbeddingWorker,
+    EmbeddingWorkerConfig, EMBEDDINGS_JOB,
+};
+pub use image_import::{enqueue_image_import, ImageImportJob, IMAGE_IMPORT_JOB};
+pub use job_queue::{enqueue_job, JobContext, JobFuture, JobHandler, JobQueue, JobQueueConfig};
+pub use markdown_export::{
+    enqueue_markdown_export, spawn_markdown_export_scheduler, MarkdownExportConfig,
+    MarkdownExportJob, MARKDOWN_EXPORT_JOB,
+};
+pub use redaction::{enqueue_redaction, RedactionJob, REDACTION_JOB};
+pub use report_scheduler::{
+    spawn_report_scheduler, ReportJob, ReportScheduler, ReportSchedulerConfig, REPORT_JOB,
+};
+pub use retention::{ensure_retention_job, spawn_retention_worker, RetentionJob, RETENTION_JOB};
+pub use summarizer::{
+    ensure_summary_job, spawn_summarizer, Summarizer, SummarizerConfig, SummaryJob, SUMMARY_JOB,
+};
@@ -0,0 +1,325 @@
+//! Retroactive redaction worker
+//!
+//! Applies a redaction rule (see [`crate::redaction`]) to the frames stored
+//! before it, in a `redaction` job queued when the rule is created or through
+//! `POST /privacy/redactions/:id/apply`. Matching OCR rows are masked in
+//! place, the full-text index follows through triggers, and the frames are
+//! queued to be embedded again from the masked text. Window titles and the
+//! text derived from captures (alert hits, documents, activity summaries and
+//! AI prompts and answers, see [`RedactableText`]) are masked as well.
+//!
+//! With `blur_images`, the image of each affected frame is replaced by a
+//! blurred copy. Frames stored as single images lose the original; frames in
+//! a video chunk or archive leave it, which keeps the original image until
+//! it is deleted.
+
+use super::job_queue::{enqueue_job, JobContext, JobFuture, JobHandler};
+use crate::capture_store::CaptureStorage;
+use crate::cold_archive::is_frame_archive;
+use crate::redaction::{blur, mask, match_ranges, RedactionMatcher};
+use crate::video_archive::read_frame_image;
+use anyhow::Context;
+use screensearch_db::{DatabaseManager, RedactableText};
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::io::Cursor;
+use tracing::{info, warn};
+
+/// Job type that applies a redaction rule to stored frames
+pub const REDACTION_JOB: &str = "redaction";
+
+/// Rows scanned per batch
+const SCAN_BATCH: i64 = 1000;
+
+/// Queue a `redaction` job applying a rule to stored frames
+pub async fn enqueue_redaction(db: &DatabaseManager, rule_id: i64) -> screensearch_db::Result<i64> {
+    enqueue_job(db, REDACTION_JOB, &json!({ "rule_id": rule_id })).await
+}
+
+/// Job handler that applies a redaction rule to stored frames
+///
+/// Payload: `{"rule_id": <id>}`.
+/// Result: `{"rows_redacted": <count>, "other_rows_redacted": <count>,
+/// "frames": <count>, "images_blurred": <count>, "failed": <count>}`;
+/// rows counts OCR rows, other rows window titles and derived text, and
+/// failed counts images that could not be blurred.
+pub struct RedactionJob;
+
+impl JobHandler for RedactionJob {
+    fn job_type(&self) -> &'static str {
+        REDACTION_JOB
+    }
+
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a> {
+        Box::pin(async move {
+            let rule_id = ctx
+                .payload
+                .get("rule_id")
+                .and_then(|v| v.as_i64())
+                .ok_or_else(|| anyhow::anyhow!("Missing rule_id in job payload"))?;
+            let db = &ctx.state.db;
+            let rule = db
+                .get_redaction_rule(rule_id)
+                .await?
+                .with_context(|| format!("Redaction rule {} not found", rule_id))?;
+            let matcher = RedactionMatcher::new(&rule)?;
+            anyhow::ensure!(
+                !matcher.matches_empty(),
+                "Redaction rule {} matches empty text",
+                rule_id
+            );
+
+            let redacted = redact_stored_text(db, &matcher, || ctx.is_cancelled()).await?;
+            let frame_ids: Vec<i64> = redacted.frames.into_iter().collect();
+            if !frame_ids.is_empty() {
+                // The embedding worker picks the pending frames up
+                db.reset_frame_embeddings(&frame_ids).await?;
+            }
+
+            let (mut images_blurred, mut failed) = (0, 0);
+            if rule.blur_images {
+                let storage = ctx.state.capture_storage();
+                for &frame_id in &frame_ids {
+                    if ctx.is_cancelled().await {
+                        break;
+                    }
+
+                    match blur_frame_image(ctx, &storage, frame_id).await {
+                        Ok(true) => images_blurred += 1,
+                        Ok(false) => {}
+                        Err(e) => {
+                            warn!("Failed to blur the image of frame {}: {:#}", frame_id, e);
+                            failed += 1;
+                        }
+                    }
+                }
+            }
+
+            info!(
+                "Redaction rule {} masked {} text rows and {} other rows in {} frames ({} images blurred, {} failed)",
+                rule_id,
+                redacted.ocr_rows,
+                redacted.other_rows,
+                frame_ids.len(),
+                images_blurred,
+                failed
+            );
+            Ok(Some(json!({
+                "rows_redacted": redacted.ocr_rows,
+                "other_rows_redacted": redacted.other_rows,
+                "frames": frame_ids.len(),
+                "images_blurred": images_blurred,
+                "failed": failed,
+            })))
+        })
+    }
+}
+
+/// Rows masked by [`redact_stored_text`]
+#[derive(Debug, Default)]
+struct RedactedText {
+    /// OCR text rows
+    ocr_rows: u64,
+    /// Window titles and rows of derived text
+    other_rows: u64,
+    /// Frames whose OCR text or window title was masked
+    frames: BTreeSet<i64>,
+}
+
+/// Mask the matches of a rule in stored OCR text, window titles and text
+/// derived from captures
+///
+/// `cancelled` is checked between batches; a cancelled scan keeps what it
+/// masked so far.
+async fn redact_stored_text<F, Fut>(
+    db: &DatabaseManager,
+    matcher: &RedactionMatcher,
+    cancelled: F,
+) -> anyhow::Result<RedactedText>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let mut redacted = RedactedText::default();
+
+    let mut after_id = 0;
+    loop {
+        if cancelled().await {
+            return Ok(redacted);
+        }
+
+        let rows = db.get_ocr_text_after_id(after_id, SCAN_BATCH).await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after_id = last.id;
+
+        for row in &rows {
+            let ranges = match_ranges([matcher], &row.text);
+            if ranges.is_empty() {
+                continue;
+            }
+            db.update_ocr_text(row.id, &mask(&row.text, &ranges))
+                .await?;
+            redacted.ocr_rows += 1;
+            redacted.frames.insert(row.frame_id);
+        }
+    }
+
+    for kind in RedactableText::ALL {
+        let mut after_id = 0;
+        loop {
+            if cancelled().await {
+                return Ok(redacted);
+            }
+
+            let rows = db
+                .get_redactable_text_after_id(kind, after_id, SCAN_BATCH)
+                .await?;
+            let Some(&(last_id, _)) = rows.last() else {
+                break;
+            };
+            after_id = last_id;
+
+            for (id, text) in &rows {
+                let ranges = match_ranges([matcher], text);
+                if ranges.is_empty() {
+                    continue;
+                }
+                db.update_redactable_text(kind, *id, &mask(text, &ranges))
+                    .await?;
+                redacted.other_rows += 1;
+                if kind == RedactableText::WindowTitle {
+                    redacted.frames.insert(*id);
+                }
+            }
+        }
+    }
+
+    Ok(redacted)
+}
+
+/// Replace the image of a frame with a blurred copy
+///
+/// Returns false for frames deleted meanwhile or stored without an image.
+async fn blur_frame_image(
+    ctx: &JobContext,
+    storage: &CaptureStorage,
+    frame_id: i64,
+) -> anyhow::Result<bool> {
+    let db = &ctx.state.db;
+    let Some(frame) = db.get_frame(frame_id).await? else {
+        return Ok(false);
+    };
+    if frame.file_path.is_empty() {
+        return Ok(false);
+    }
+
+    // Only a single image file belongs to the frame alone
+    let single_file = frame.chunk_id.is_none() && !is_frame_archive(&frame.file_path);
+    let data = read_frame_image(db, storage, ctx.state.ffmpeg_path(), &frame).await?;
+    let blurred = tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<u8>> {
+        let image = image::load_from_memory(&data)?.to_rgba8();
+        let mut png = Cursor::new(Vec::new());
+        blur(&image).write_to(&mut png, image::ImageOutputFormat::Png)?;
+        Ok(png.into_inner())
+    })
+    .await??;
+
+    let file_path = storage
+        .put(&format!("redacted_{}.png", frame_id), blurred)
+        .await
+        .context("Failed to store blurred image")?;
+    db.replace_frame_image(frame_id, &file_path).await?;
+
+    if single_file && frame.file_path != file_path {
+        if let Err(e) = storage.delete(&frame.file_path).await {
+            warn!(
+                "Failed to delete the original image of frame {}: {:#}",
+                frame_id, e
+            );
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use screensearch_db::{NewAlert, NewAlertHit, NewFrame, NewOcrText, Pagination};
+
+    #[tokio::test]
+    async fn test_redact_stored_text() {
+        let path =
+            std::env::temp_dir().join(format!("screensearch-redaction-{}.db", std::process::id()));
+        let db = DatabaseManager::new(path.to_string_lossy()).await.unwrap();
+
+        let frame_id = db
+            .insert_frame(NewFrame {
+                chunk_id: None,
+                timestamp: chrono::Utc::now(),
+                monitor_index: 0,
+                device_name: "monitor-0".to_string(),
+                file_path: String::new(),
+                active_window: Some("Vault - hunter2".to_string()),
+                active_process: Some("chrome.exe".to_string()),
+                browser_url: None,
+                width: 1,
+                height: 1,
+                offset_index: 0,
+                focused: None,
+            })
+            .await
+            .unwrap();
+        db.insert_ocr_text(NewOcrText {
+            frame_id,
+            text: "Password: HUNTER2".to_string(),
+            text_json: None,
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+            confidence: 0.9,
+        })
+        .await
+        .unwrap();
+        let alert_id = db
+            .insert_alert(NewAlert {
+                pattern: "password".to_string(),
+                is_regex: false,
+                app_filter: None,
+            })
+            .await
+            .unwrap();
+        db.insert_alert_hit(NewAlertHit {
+            alert_id,
+            frame_id,
+            matched_text: "Password: HUNTER2".to_string(),
+            context: "Password: HUNTER2".to_string(),
+            app_name: Some("chrome.exe".to_string()),
+        })
+        .await
+        .unwrap();
+
+        let matcher = RedactionMatcher::compile(1, "hunter2", false, false).unwrap();
+        let redacted = redact_stored_text(&db, &matcher, || async { false })
+            .await
+            .unwrap();
+        assert_eq!(redacted.ocr_rows, 1);
+        assert_eq!(redacted.other_rows, 3);
+        assert_eq!(redacted.frames.into_iter().collect::<Vec<_>>(), [frame_id]);
+
+        let frame = db.get_frame(frame_id).await.unwrap().unwrap();
+        assert_eq!(frame.active_window.as_deref(), Some("Vault - [redacted]"));
+        let hits = db
+            .list_alert_hits(Some(alert_id), Pagination::default())
+            .await
+            .unwrap();
+        assert_eq!(hits[0].matched_text, "Password: [redacted]");
+        assert_eq!(hits[0].context, "Password: [redacted]");
+
+        db.close().await;
+        let _ = std::fs::remove_file(&path);
+    }
+}
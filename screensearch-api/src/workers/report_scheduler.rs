@@ -0,0 +1,331 @@
+//! Scheduled report worker
+//!
+//! Generates daily and weekly AI reports at configured local times and stores
+//! them in the database. The scheduler queues a `report` job when a run is
+//! due; [`ReportJob`] generates the report on the job queue.
+
+use super::job_queue::{enqueue_job, JobContext, JobFuture, JobHandler};
+use crate::handlers::ai::{run_report, AiReportRequest};
+use crate::state::AppState;
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use screensearch_db::NewReport;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+/// Metadata key holding the date of the last daily report run
+const LAST_DAILY_KEY: &str = "reports_last_daily_run";
+/// Metadata key holding the date of the last weekly report run
+const LAST_WEEKLY_KEY: &str = "reports_last_weekly_run";
+
+/// Job type that generates a scheduled report
+pub const REPORT_JOB: &str = "report";
+
+/// Configuration for the scheduled report worker
+#[derive(Debug, Clone)]
+pub struct ReportSchedulerConfig {
+    /// Whether the scheduler is enabled
+    pub enabled: bool,
+    /// OpenAI-compatible provider URL (e.g. "http://localhost:11434/v1")
+    pub provider_url: String,
+    /// Optional API key for the provider
+    pub api_key: Option<String>,
+    /// Model used for generation
+    pub model: String,
+    /// Local time to generate the daily report (None = no daily reports)
+    pub daily_at: Option<NaiveTime>,
+    /// Day of week to generate the weekly report
+    pub weekly_on: Weekday,
+    /// Local time to generate the weekly report (None = no weekly reports)
+    pub weekly_at: Option<NaiveTime>,
+    /// Interval between schedule checks (seconds)
+    pub check_interval_secs: u64,
+}
+
+impl Default for ReportSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider_url: "http://localhost:11434/v1".to_string(),
+            api_key: None,
+            model: "llama3".to_string(),
+            daily_at: NaiveTime::from_hms_opt(18, 0, 0),
+            weekly_on: Weekday::Fri,
+            weekly_at: NaiveTime::from_hms_opt(18, 0, 0),
+            check_interval_secs: 60,
+        }
+    }
+}
+
+/// Report period produced by the scheduler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportPeriod {
+    Daily,
+    Weekly,
+}
+
+impl ReportPeriod {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => "daily",
+            ReportPeriod::Weekly => "weekly",
+        }
+    }
+
+    fn duration(self) -> Duration {
+        match self {
+            ReportPeriod::Daily => Duration::days(1),
+            ReportPeriod::Weekly => Duration::days(7),
+        }
+    }
+
+    fn metadata_key(self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => LAST_DAILY_KEY,
+            ReportPeriod::Weekly => LAST_WEEKLY_KEY,
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "daily" => Some(ReportPeriod::Daily),
+            "weekly" => Some(ReportPeriod::Weekly),
+            _ => None,
+        }
+    }
+}
+
+/// Check whether a scheduled run is due
+///
+/// A run is due once the scheduled time has passed on a matching day and no
+/// run has been recorded for that date yet. This also catches up on a missed
+/// run if the app was started after the scheduled time.
+fn is_due(
+    now: NaiveDateTime,
+    at: NaiveTime,
+    weekday: Option<Weekday>,
+    last_run: Option<NaiveDate>,
+) -> bool {
+    if let Some(day) = weekday {
+        if now.weekday() != day {
+            return false;
+        }
+    }
+
+    now.time() >= at && last_run != Some(now.date())
+}
+
+/// Background worker that generates reports on a schedule
+pub struct ReportScheduler {
+    state: Arc<AppState>,
+    config: ReportSchedulerConfig,
+}
+
+impl ReportScheduler {
+    /// Create a new report scheduler
+    pub fn new(state: Arc<AppState>, config: ReportSchedulerConfig) -> Self {
+        Self { state, config }
+    }
+
+    /// Run the scheduler continuously
+    pub async fn run(&self) {
+        if !self.config.enabled {
+            warn!("Report scheduler is disabled");
+            return;
+        }
+
+        info!(
+            "Starting report scheduler (daily: {:?}, weekly: {:?} {:?})",
+            self.config.daily_at, self.config.weekly_on, self.config.weekly_at
+        );
+
+        let mut tick = interval(std::time::Duration::from_secs(
+            self.config.check_interval_secs,
+        ));
+
+        loop {
+            tick.tick().await;
+
+            let now = Local::now().naive_local();
+
+            if let Some(at) = self.config.daily_at {
+                self.run_if_due(ReportPeriod::Daily, now, at, None).await;
+            }
+            if let Some(at) = self.config.weekly_at {
+                self.run_if_due(ReportPeriod::Weekly, now, at, Some(self.config.weekly_on))
+                    .await;
+            }
+        }
+    }
+
+    /// Queue a report job if its schedule is due
+    async fn run_if_due(
+        &self,
+        period: ReportPeriod,
+        now: NaiveDateTime,
+        at: NaiveTime,
+        weekday: Option<Weekday>,
+    ) {
+        let last_run = match self.state.db.get_metadata(period.metadata_key()).await {
+            Ok(value) => value.and_then(|v| NaiveDate::parse_from_str(&v, "%Y-%m-%d").ok()),
+            Err(e) => {
+                warn!("Failed to read last {} report run: {}", period.as_str(), e);
+                return;
+            }
+        };
+
+        if !is_due(now, at, weekday, last_run) {
+            return;
+        }
+
+        // Record the run before queuing so the job is queued once per scheduled run;
+        // the job queue takes care of retries
+        let today = now.date().format("%Y-%m-%d").to_string();
+        if let Err(e) = self
+            .state
+            .db
+            .set_metadata(period.metadata_key(), &today)
+            .await
+        {
+            error!("Failed to record {} report run: {}", period.as_str(), e);
+            return;
+        }
+
+        let payload = json!({ "period": period.as_str() });
+        match enqueue_job(&self.state.db, REPORT_JOB, &payload).await {
+            Ok(id) => info!("Queued scheduled {} report job {}", period.as_str(), id),
+            Err(e) => error!("Failed to queue {} report job: {}", period.as_str(), e),
+        }
+    }
+}
+
+/// Job handler that generates and stores a report
+///
+/// Payload: `{"period": "daily" | "weekly"}`. Result: `{"report_id": <id>}`.
+pub struct ReportJob {
+    config: ReportSchedulerConfig,
+}
+
+impl ReportJob {
+    /// Create a report job handler using the scheduler's provider settings
+    pub fn new(config: ReportSchedulerConfig) -> Self {
+        Self { config }
+    }
+
+    /// Generate a report covering the period ending now and store it
+    async fn generate(&self, state: &Arc<AppState>, period: ReportPeriod) -> anyhow::Result<i64> {
+        let end_time = Utc::now();
+        let start_time = end_time - period.duration();
+
+        debug!(
+            "Generating {} report for {} to {}",
+            period.as_str(),
+            start_time,
+            end_time
+        );
+
+        let request = AiReportRequest {
+            provider_url: self.config.provider_url.clone(),
+            api_key: self.config.api_key.clone(),
+            model: self.config.model.clone(),
+            start_time: Some(start_time),
+            end_time: Some(end_time),
+            prompt: None,
+            template_id: None,
+        };
+
+        let response = run_report(state, request)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let id = state
+            .db
+            .insert_report(NewReport {
+                period: period.as_str().to_string(),
+                content: response.report,
+                model: response.model_used,
+                context_source: Some(response.context_source),
+                start_time,
+                end_time,
+            })
+            .await?;
+
+        Ok(id)
+    }
+}
+
+impl JobHandler for ReportJob {
+    fn job_type(&self) -> &'static str {
+        REPORT_JOB
+    }
+
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a> {
+        Box::pin(async move {
+            let period = ctx
+                .payload
+                .get("period")
+                .and_then(|v| v.as_str())
+                .and_then(ReportPeriod::parse)
+                .ok_or_else(|| anyhow::anyhow!("Invalid report period in job payload"))?;
+
+            let id = self.generate(&ctx.state, period).await?;
+            info!("Generated scheduled {} report {}", period.as_str(), id);
+
+            Ok(Some(json!({ "report_id": id })))
+        })
+    }
+}
+
+/// Start the report scheduler as a background task
+pub fn spawn_report_scheduler(
+    state: Arc<AppState>,
+    config: ReportSchedulerConfig,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let scheduler = ReportScheduler::new(state, config);
+        scheduler.run().await;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_daily_due_after_time() {
+        let six = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        assert!(!is_due(at("2025-12-10", "17:59"), six, None, None));
+        assert!(is_due(at("2025-12-10", "18:00"), six, None, None));
+        assert!(is_due(at("2025-12-10", "23:00"), six, None, None));
+    }
+
+    #[test]
+    fn test_not_due_twice_same_day() {
+        let six = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 12, 10);
+        assert!(!is_due(at("2025-12-10", "19:00"), six, None, today));
+        assert!(is_due(at("2025-12-11", "19:00"), six, None, today));
+    }
+
+    #[test]
+    fn test_weekly_only_on_weekday() {
+        let six = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        // 2025-12-12 is a Friday
+        let fri = Some(Weekday::Fri);
+        assert!(is_due(at("2025-12-12", "18:30"), six, fri, None));
+        assert!(!is_due(at("2025-12-11", "18:30"), six, fri, None));
+    }
+
+    #[test]
+    fn test_period_roundtrip() {
+        for period in [ReportPeriod::Daily, ReportPeriod::Weekly] {
+            assert_eq!(ReportPeriod::parse(period.as_str()), Some(period));
+        }
+        assert_eq!(ReportPeriod::parse("monthly"), None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Data retention worker
+//!
+//! Deletes frames older than the `retention_days` setting and automation audit
+//! entries older than `audit_retention_days`. Recorded AI interactions and
+//! activity summaries quote screen content, so they follow `retention_days`
+//! like the frames they were built from. Cleanup runs as a
+//! `retention` job on the job queue, queued periodically and whenever the
+//! setting changes; the job reads the current setting when it runs.
+//!
+//! Two tags override `retention_days` for the frames carrying them:
+//! `keep-forever` frames are never deleted, and `ephemeral` frames are deleted
+//! 24 hours after capture. A frame with both is kept. Pinned frames are
+//! kept whatever their tags.
+
+use super::job_queue::{enqueue_job, JobContext, JobFuture, JobHandler};
+use crate::state::AppState;
+use chrono::Utc;
+use screensearch_db::DatabaseManager;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::time::{interval, Duration};
+use tracing::{error, info};
+
+/// Job type that deletes frames and audit entries past their retention period
+pub const RETENTION_JOB: &str = "retention";
+
+/// Interval between scheduled retention runs (seconds)
+const RETENTION_INTERVAL_SECS: u64 = 60 * 60;
+
+/// Days of metrics history kept; metrics hold no screen content
+const METRICS_RETENTION_DAYS: i32 = 180;
+
+/// Hours frames tagged `ephemeral` are kept
+const EPHEMERAL_RETENTION_HOURS: i64 = 24;
+
+/// Get the active `retention` job, queuing one if none is queued or running
+pub async fn ensure_retention_job(db: &DatabaseManager) -> screensearch_db::Result<i64> {
+    if let Some(job) = db.find_active_job(RETENTION_JOB).await? {
+        return Ok(job.id);
+    }
+
+    enqueue_job(db, RETENTION_JOB, &json!({})).await
+}
+
+/// Job handler that applies the retention setting
+///
+/// Result: `{"frames_deleted": <count>, "retention_days": <days>,
+/// "ephemeral_frames_deleted": <count>, "ai_interactions_deleted": <count>, "summaries_deleted": <count>,
+/// "searches_deleted": <count>, "audit_entries_deleted": <count>,
+/// "audit_retention_days": <days>, "metric_values_deleted": <count>}`.
+/// Metrics history is kept for 180 days.
+pub struct RetentionJob;
+
+impl JobHandler for RetentionJob {
+    fn job_type(&self) -> &'static str {
+        RETENTION_JOB
+    }
+
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a> {
+        Box::pin(async move {
+            let settings = ctx.state.db.get_settings().await?;
+            let retention_days = settings.retention_days.max(1);
+
+            let deleted = ctx
+                .state
+                .db
+                .cleanup_old_data(retention_days.min(i32::MAX as i64) as i32)
+                .await?;
+            let ephemeral_deleted = ctx
+                .state
+                .db
+                .delete_ephemeral_frames(
+                    Utc::now() - chrono::Duration::hours(EPHEMERAL_RETENTION_HOURS),
+                )
+                .await?;
+            let ai_deleted = ctx
+                .state
+                .db
+                .cleanup_old_ai_interactions(retention_days.min(i32::MAX as i64) as i32)
+                .await?;
+            let summaries_deleted = ctx
+                .state
+                .db
+                .cleanup_old_activity_summaries(retention_days.min(i32::MAX as i64) as i32)
+                .await?;
+            let searches_deleted = ctx
+                .state
+                .db
+                .cleanup_old_search_history(retention_days.min(i32::MAX as i64) as i32)
+                .await?;
+
+            let audit_retention_days = settings.audit_retention_days.max(1);
+            let audit_deleted = ctx
+                .state
+                .db
+                .cleanup_old_audit_entries(audit_retention_days.min(i32::MAX as i64) as i32)
+                .await?;
+
+            let metrics_deleted = ctx
+                .state
+                .db
+                .cleanup_old_metrics(METRICS_RETENTION_DAYS)
+                .await?;
+
+            Ok(Some(json!({
+                "frames_deleted": deleted,
+                "retention_days": retention_days,
+                "ephemeral_frames_deleted": ephemeral_deleted,
+                "ai_interactions_deleted": ai_deleted,
+                "summaries_deleted": summaries_deleted,
+                "searches_deleted": searches_deleted,
+                "audit_entries_deleted": audit_deleted,
+                "audit_retention_days": audit_retention_days,
+                "metric_values_deleted": metrics_deleted,
+            })))
+        })
+    }
+}
+
+/// Start the periodic retention worker as a background task
+pub fn spawn_retention_worker(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        info!(
+            "Starting retention worker with {}s interval",
+            RETENTION_INTERVAL_SECS
+        );
+
+        let mut tick = interval(Duration::from_secs(RETENTION_INTERVAL_SECS));
+        loop {
+            tick.tick().await;
+
+            if let Err(e) = ensure_retention_job(&state.db).await {
+                error!("Failed to queue retention job: {}", e);
+            }
+        }
+    })
+}
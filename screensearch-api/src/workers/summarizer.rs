@@ -0,0 +1,448 @@
+//! Hourly activity summary worker
+//!
+//! Summarizes each finished hour with captured frames into the
+//! `activity_summaries` table. The configured AI provider writes the summary
+//! from the apps, windows and a sample of the screen text of the hour;
+//! without a provider, or when it fails, an extractive summary of the apps
+//! and windows used is stored instead. Reports over long periods are built
+//! from these summaries rather than from raw OCR text. Summarizing runs as a
+//! `summary` job on the job queue, queued every check interval.
+
+use super::job_queue::{enqueue_job, JobContext, JobFuture, JobHandler};
+use crate::handlers::ai::{
+    complete_chat, model_used, record_interaction, OpenAIMessage, OpenAIUsage,
+};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use screensearch_db::{
+    ContextChunk, DatabaseManager, FrameFilter, FrameRecord, NewActivitySummary, NewAiInteraction,
+    Pagination,
+};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+/// Job type that summarizes finished hours
+pub const SUMMARY_JOB: &str = "summary";
+
+/// Frames of an hour read to summarize it
+const MAX_HOUR_FRAMES: i64 = 1000;
+/// Frames whose screen text is sent to the model
+const SAMPLED_FRAMES: usize = 12;
+/// Characters of screen text sent per sampled frame
+const MAX_EXCERPT_CHARS: usize = 400;
+/// Apps and windows named in a summary
+const TOP_APPS: usize = 3;
+const TOP_WINDOWS: usize = 5;
+
+const SUMMARY_PROMPT: &str = "You summarize one hour of a user's computer activity for their own records. \
+In 2-4 sentences, say what the user worked on, naming projects, documents, people and topics visible in the screen text. \
+Use only the information given. Do not add headings, lists or advice.";
+
+/// Configuration for the activity summary worker
+#[derive(Debug, Clone)]
+pub struct SummarizerConfig {
+    /// Whether the worker is enabled
+    pub enabled: bool,
+    /// OpenAI-compatible provider URL or "builtin" (None = extractive
+    /// summaries only)
+    pub provider_url: Option<String>,
+    /// Optional API key for the provider
+    pub api_key: Option<String>,
+    /// Model used for summaries
+    pub model: String,
+    /// Finished hours before now that are summarized if they are missing
+    pub lookback_hours: u32,
+    /// Interval between checks for unsummarized hours (seconds)
+    pub check_interval_secs: u64,
+}
+
+impl Default for SummarizerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            provider_url: None,
+            api_key: None,
+            model: "llama3".to_string(),
+            lookback_hours: 24,
+            check_interval_secs: 300,
+        }
+    }
+}
+
+/// Get the active `summary` job, queuing one if none is queued or running
+pub async fn ensure_summary_job(db: &DatabaseManager) -> screensearch_db::Result<i64> {
+    if let Some(job) = db.find_active_job(SUMMARY_JOB).await? {
+        return Ok(job.id);
+    }
+
+    enqueue_job(db, SUMMARY_JOB, &json!({})).await
+}
+
+/// Job handler that summarizes the missing hours of the lookback window
+///
+/// Result: `{"hours_summarized": <count>}`.
+pub struct SummaryJob {
+    config: SummarizerConfig,
+}
+
+impl SummaryJob {
+    /// Create a summary job handler
+    pub fn new(config: SummarizerConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl JobHandler for SummaryJob {
+    fn job_type(&self) -> &'static str {
+        SUMMARY_JOB
+    }
+
+    fn run<'a>(&'a self, ctx: &'a JobContext) -> JobFuture<'a> {
+        Box::pin(async move {
+            let summarizer = Summarizer::new(Arc::clone(&ctx.state), self.config.clone());
+            let count = summarizer.summarize_missing_hours().await?;
+            if count > 0 {
+                info!("Summarized {} hours of activity", count);
+            }
+            Ok(Some(json!({ "hours_summarized": count })))
+        })
+    }
+}
+
+/// Summarizes finished hours
+pub struct Summarizer {
+    state: Arc<AppState>,
+    config: SummarizerConfig,
+}
+
+impl Summarizer {
+    /// Create a new summarizer
+    pub fn new(state: Arc<AppState>, config: SummarizerConfig) -> Self {
+        Self { state, config }
+    }
+
+    /// Summarize the finished hours of the lookback window that have frames
+    /// but no summary, returning how many were summarized
+    pub async fn summarize_missing_hours(&self) -> anyhow::Result<usize> {
+        let current_hour = Utc::now().duration_trunc(Duration::hours(1))?;
+        let first_hour = current_hour - Duration::hours(self.config.lookback_hours as i64);
+
+        let summarized: HashSet<DateTime<Utc>> = self
+            .state
+            .db
+            .get_summarized_periods(first_hour, current_hour)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut count = 0;
+        let mut hour = first_hour;
+        while hour < current_hour {
+            if !summarized.contains(&hour) && self.summarize_hour(hour).await? {
+                count += 1;
+            }
+            hour += Duration::hours(1);
+        }
+
+        Ok(count)
+    }
+
+    /// Summarize the hour starting at `start`; false if it has no frames
+    async fn summarize_hour(&self, start: DateTime<Utc>) -> anyhow::Result<bool> {
+        let end = start + Duration::hours(1);
+        // Frames on the hour belong to the next one
+        let last = end - Duration::milliseconds(1);
+
+        let filter = FrameFilter {
+            start_time: Some(start),
+            end_time: Some(last),
+            app_name: None,
+            device_name: None,
+            tag_ids: None,
+            monitor_index: None,
+            exclude_apps: None,
+            exclude_tags: None,
+        };
+        let pagination = Pagination {
+            limit: MAX_HOUR_FRAMES,
+            offset: 0,
+        };
+        let mut frames = self
+            .state
+            .db
+            .get_frames_in_range(start, last, filter, pagination)
+            .await?;
+        if frames.is_empty() {
+            return Ok(false);
+        }
+        frames.reverse();
+        let frame_count = self.state.db.count_frames_in_range(start, last).await?;
+
+        debug!("Summarizing {} frames from {}", frame_count, start);
+        let extractive = extractive_summary(&frames);
+        let (summary, method, model) = match &self.config.provider_url {
+            Some(provider_url) => match self
+                .ai_summary(provider_url, start, end, &frames, &extractive)
+                .await
+            {
+                Ok(summary) if !summary.trim().is_empty() => (
+                    summary.trim().to_string(),
+                    "llm",
+                    Some(model_used(&self.state, provider_url, &self.config.model)),
+                ),
+                Ok(_) => {
+                    warn!(
+                        "AI summary of {} was empty, storing an extractive one",
+                        start
+                    );
+                    (extractive, "extractive", None)
+                }
+                Err(e) => {
+                    warn!(
+                        "AI summary of {} failed, storing an extractive one: {}",
+                        start, e
+                    );
+                    (extractive, "extractive", None)
+                }
+            },
+            None => (extractive, "extractive", None),
+        };
+
+        self.state
+            .db
+            .upsert_activity_summary(NewActivitySummary {
+                period_start: start,
+                period_end: end,
+                summary,
+                method: method.to_string(),
+                model,
+                frame_count,
+                frame_id: frames[0].id,
+            })
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Ask the AI provider to summarize the hour from its apps, windows and
+    /// a sample of its screen text
+    async fn ai_summary(
+        &self,
+        provider_url: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        frames: &[FrameRecord],
+        extractive: &str,
+    ) -> crate::Result<String> {
+        let mut excerpts = Vec::new();
+        let mut context_chunks = Vec::new();
+        for frame in sample_evenly(frames, SAMPLED_FRAMES) {
+            let text = self
+                .state
+                .db
+                .get_ocr_text_for_frame(frame.id)
+                .await?
+                .iter()
+                .flat_map(|region| region.text.split_whitespace())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if text.is_empty() {
+                continue;
+            }
+            let text: String = text.chars().take(MAX_EXCERPT_CHARS).collect();
+            excerpts.push(format!(
+                "[{}] {} - {}: {}",
+                frame.timestamp.format("%H:%M"),
+                frame.active_process.as_deref().unwrap_or("Unknown"),
+                frame.active_window.as_deref().unwrap_or_default(),
+                text
+            ));
+            context_chunks.push(ContextChunk {
+                frame_id: frame.id,
+                chunk_index: None,
+            });
+        }
+
+        let prompt = format!(
+            "Activity from {} to {} (UTC)\n\n{}\n\nScreen text samples:\n{}",
+            start.format("%Y-%m-%d %H:%M"),
+            end.format("%H:%M"),
+            extractive,
+            excerpts.join("\n")
+        );
+        let messages = vec![
+            OpenAIMessage {
+                role: "system".to_string(),
+                content: SUMMARY_PROMPT.to_string(),
+            },
+            OpenAIMessage {
+                role: "user".to_string(),
+                content: prompt.clone(),
+            },
+        ];
+
+        let started = Instant::now();
+        let outcome = complete_chat(
+            &self.state,
+            provider_url,
+            &self.config.api_key,
+            &self.config.model,
+            messages,
+            Some(0.3),
+            Some(300),
+        )
+        .await;
+        let usage = outcome
+            .as_ref()
+            .ok()
+            .and_then(|(_, usage)| usage.as_ref())
+            .map(OpenAIUsage::columns)
+            .unwrap_or_default();
+
+        record_interaction(
+            &self.state,
+            NewAiInteraction {
+                kind: "summary".to_string(),
+                provider_url: provider_url.to_string(),
+                model: model_used(&self.state, provider_url, &self.config.model),
+                system_prompt: Some(SUMMARY_PROMPT.to_string()),
+                prompt,
+                context_chunks,
+                response: outcome.as_ref().ok().map(|(summary, _)| summary.clone()),
+                error: outcome.as_ref().err().map(ToString::to_string),
+                latency_ms: started.elapsed().as_millis() as i64,
+                prompt_tokens: usage.0,
+                completion_tokens: usage.1,
+                total_tokens: usage.2,
+            },
+        )
+        .await;
+
+        outcome.map(|(summary, _)| summary)
+    }
+}
+
+/// Summary naming the most captured apps and windows of `frames`
+fn extractive_summary(frames: &[FrameRecord]) -> String {
+    let ranked = |key: fn(&FrameRecord) -> Option<&str>, limit: usize| {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for frame in frames {
+            if let Some(value) = key(frame).filter(|value| !value.is_empty()) {
+                *counts.entry(value).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts
+            .into_iter()
+            .take(limit)
+            .map(|(value, count)| format!("{} ({} frames)", value, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut parts = Vec::new();
+    let apps = ranked(|f| f.active_process.as_deref(), TOP_APPS);
+    if !apps.is_empty() {
+        parts.push(format!("Apps: {}.", apps));
+    }
+    let windows = ranked(|f| f.active_window.as_deref(), TOP_WINDOWS);
+    if !windows.is_empty() {
+        parts.push(format!("Windows: {}.", windows));
+    }
+    if parts.is_empty() {
+        return format!("{} frames without app or window information.", frames.len());
+    }
+    parts.join(" ")
+}
+
+/// Up to `count` frames spread evenly over `frames`
+fn sample_evenly(frames: &[FrameRecord], count: usize) -> Vec<&FrameRecord> {
+    if frames.len() <= count {
+        return frames.iter().collect();
+    }
+    (0..count)
+        .map(|i| &frames[i * frames.len() / count])
+        .collect()
+}
+
+/// Start the periodic activity summary worker as a background task
+pub fn spawn_summarizer(
+    state: Arc<AppState>,
+    config: SummarizerConfig,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        info!(
+            "Starting activity summary worker ({} summaries, {}h lookback)",
+            if config.provider_url.is_some() {
+                "AI"
+            } else {
+                "extractive"
+            },
+            config.lookback_hours
+        );
+
+        let mut tick = interval(std::time::Duration::from_secs(config.check_interval_secs));
+        loop {
+            tick.tick().await;
+
+            if let Err(e) = ensure_summary_job(&state.db).await {
+                error!("Failed to queue summary job: {}", e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: i64, app: &str, window: &str) -> FrameRecord {
+        FrameRecord {
+            id,
+            chunk_id: None,
+            timestamp: Utc::now(),
+            monitor_index: 0,
+            device_name: String::new(),
+            file_path: String::new(),
+            active_window: Some(window.to_string()),
+            active_process: Some(app.to_string()),
+            browser_url: None,
+            width: 0,
+            height: 0,
+            offset_index: 0,
+            focused: None,
+            pinned: false,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_extractive_summary() {
+        let frames = vec![
+            frame(1, "Code.exe", "main.rs - screensearch"),
+            frame(2, "Code.exe", "main.rs - screensearch"),
+            frame(3, "chrome.exe", "Pull request #12"),
+            frame(4, "Code.exe", "lib.rs - screensearch"),
+        ];
+
+        assert_eq!(
+            extractive_summary(&frames),
+            "Apps: Code.exe (3 frames), chrome.exe (1 frames). \
+             Windows: main.rs - screensearch (2 frames), Pull request #12 (1 frames), \
+             lib.rs - screensearch (1 frames)."
+        );
+    }
+
+    #[test]
+    fn test_sample_evenly() {
+        let frames: Vec<FrameRecord> = (0..10).map(|i| frame(i, "a", "b")).collect();
+        let ids: Vec<i64> = sample_evenly(&frames, 4).iter().map(|f| f.id).collect();
+        assert_eq!(ids, vec![0, 2, 5, 7]);
+        assert_eq!(sample_evenly(&frames, 20).len(), 10);
+    }
+}
@@ -11,6 +11,8 @@ async fn test_server_initialization() {
         host: "127.0.0.1".to_string(),
         port: 3132,                            // Use different port for testing
         database_path: ":memory:".to_string(), // In-memory database
+        tls: None,
+        local_socket: None,
     };
 
     // Server should initialize without errors
@@ -1,6 +1,6 @@
 //! Basic usage example for screen-automation
 
-use screen_automation::*;
+use screensearch_automation::*;
 
 #[tokio::main]
 async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
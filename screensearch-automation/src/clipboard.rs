@@ -0,0 +1,199 @@
+//! Clipboard access
+//!
+//! Reads and writes Unicode text on the system clipboard, so large text can be
+//! pasted instead of typed keystroke by keystroke.
+
+use crate::errors::AutomationError;
+
+/// Clipboard for reading and writing text
+pub struct Clipboard {}
+
+impl Clipboard {
+    /// Create a new clipboard handle
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Get the clipboard text
+    ///
+    /// Returns `None` if the clipboard holds no text (e.g. it is empty or
+    /// contains only an image).
+    pub fn get_text(&self) -> Result<Option<String>, AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            unsafe { windows_impl::get_text() }
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            Err(AutomationError::UnsupportedOperation(
+                "Clipboard only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    /// Replace the clipboard contents with text
+    pub fn set_text(&self, text: &str) -> Result<(), AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            unsafe { windows_impl::set_text(text) }
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = text;
+            Err(AutomationError::UnsupportedOperation(
+                "Clipboard only supported on Windows".to_string(),
+            ))
+        }
+    }
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Encode text as a nul-terminated UTF-16 buffer
+#[cfg_attr(not(any(target_os = "windows", test)), allow(dead_code))]
+fn encode_text(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Decode a UTF-16 buffer up to its first nul
+#[cfg_attr(not(any(target_os = "windows", test)), allow(dead_code))]
+fn decode_text(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
+
+// Windows-specific implementations
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use super::*;
+    use std::ffi::c_void;
+    use std::time::Duration;
+    use windows::Win32::Foundation::{HANDLE, HGLOBAL, HWND};
+    use windows::Win32::System::DataExchange::{
+        CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable,
+        OpenClipboard, SetClipboardData,
+    };
+    use windows::Win32::System::Memory::{
+        GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE,
+    };
+
+    /// Standard clipboard format for UTF-16 text
+    const CF_UNICODETEXT: u32 = 13;
+    /// Attempts to open the clipboard while another application holds it
+    const OPEN_ATTEMPTS: u32 = 10;
+    const OPEN_RETRY_DELAY: Duration = Duration::from_millis(20);
+
+    /// Open clipboard, closed again on drop
+    struct OpenClipboardGuard;
+
+    impl OpenClipboardGuard {
+        unsafe fn open() -> Result<Self, AutomationError> {
+            for _ in 0..OPEN_ATTEMPTS {
+                if OpenClipboard(HWND(0)).is_ok() {
+                    return Ok(Self);
+                }
+                std::thread::sleep(OPEN_RETRY_DELAY);
+            }
+            Err(AutomationError::platform(
+                "Clipboard is in use by another application",
+            ))
+        }
+    }
+
+    impl Drop for OpenClipboardGuard {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = CloseClipboard();
+            }
+        }
+    }
+
+    pub(super) unsafe fn get_text() -> Result<Option<String>, AutomationError> {
+        if IsClipboardFormatAvailable(CF_UNICODETEXT).is_err() {
+            return Ok(None);
+        }
+
+        let _guard = OpenClipboardGuard::open()?;
+        let handle = GetClipboardData(CF_UNICODETEXT).map_err(AutomationError::platform)?;
+        let memory = HGLOBAL(handle.0 as *mut c_void);
+
+        let data = GlobalLock(memory) as *const u16;
+        if data.is_null() {
+            return Err(AutomationError::platform("Failed to lock clipboard data"));
+        }
+
+        // Bound the read by the allocation size rather than trusting the terminator
+        let len = GlobalSize(memory) / std::mem::size_of::<u16>();
+        let text = decode_text(std::slice::from_raw_parts(data, len));
+        // GlobalUnlock reports an error once the lock count reaches zero
+        let _ = GlobalUnlock(memory);
+
+        Ok(Some(text))
+    }
+
+    pub(super) unsafe fn set_text(text: &str) -> Result<(), AutomationError> {
+        let buffer = encode_text(text);
+
+        let _guard = OpenClipboardGuard::open()?;
+        EmptyClipboard().map_err(AutomationError::platform)?;
+
+        let memory = GlobalAlloc(GMEM_MOVEABLE, std::mem::size_of_val(buffer.as_slice()))
+            .map_err(AutomationError::platform)?;
+
+        let data = GlobalLock(memory) as *mut u16;
+        if data.is_null() {
+            let _ = GlobalFree(memory);
+            return Err(AutomationError::platform("Failed to lock clipboard data"));
+        }
+        std::ptr::copy_nonoverlapping(buffer.as_ptr(), data, buffer.len());
+        let _ = GlobalUnlock(memory);
+
+        // On success the clipboard owns the memory
+        if let Err(e) = SetClipboardData(CF_UNICODETEXT, HANDLE(memory.0 as isize)) {
+            let _ = GlobalFree(memory);
+            return Err(AutomationError::platform(e));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_text() {
+        let text = "Grüße, 世界 🎉\r\nline two";
+        let buffer = encode_text(text);
+        assert_eq!(buffer.last(), Some(&0));
+        assert_eq!(decode_text(&buffer), text);
+    }
+
+    #[test]
+    fn test_decode_stops_at_nul() {
+        let buffer: Vec<u16> = "abc\0def".encode_utf16().collect();
+        assert_eq!(decode_text(&buffer), "abc");
+
+        // Missing terminator reads the whole buffer
+        let buffer: Vec<u16> = "abc".encode_utf16().collect();
+        assert_eq!(decode_text(&buffer), "abc");
+    }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_clipboard_roundtrip() {
+        let clipboard = Clipboard::new();
+        clipboard.set_text("screensearch clipboard test").unwrap();
+        assert_eq!(
+            clipboard.get_text().unwrap().as_deref(),
+            Some("screensearch clipboard test")
+        );
+    }
+}
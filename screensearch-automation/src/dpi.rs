@@ -0,0 +1,221 @@
+//! DPI-aware coordinate handling
+//!
+//! Screen coordinates in this crate are physical pixels, which is what
+//! UIAutomation reports element bounds in. On mixed-DPI multi-monitor setups
+//! a process that is not per-monitor DPI aware sees scaled (logical) cursor
+//! coordinates instead, so clicks computed from element bounds miss.
+//!
+//! [`enable_dpi_awareness`] makes the process per-monitor aware, which keeps
+//! every API in physical pixels. When that is not possible (the host process
+//! already chose another mode), input coordinates are converted before they
+//! reach the cursor APIs.
+//!
+//! "Logical" coordinates are device-independent pixels (96 DPI) anchored at
+//! the top-left corner of the monitor containing the point, e.g. as reported
+//! by a web view or a scaled screenshot.
+
+/// DPI of a monitor at 100% scaling
+pub const BASE_DPI: u32 = 96;
+
+/// Make the process per-monitor DPI aware
+///
+/// Returns whether the process is per-monitor aware afterwards. Windows only
+/// allows the mode to be set once per process, typically before any window
+/// is created; a mode chosen earlier by the host is kept.
+pub fn enable_dpi_awareness() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::HiDpi::{
+            SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+        };
+
+        if is_per_monitor_aware() {
+            return true;
+        }
+        if let Err(e) =
+            unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) }
+        {
+            tracing::warn!(
+                "Could not enable per-monitor DPI awareness, converting input coordinates: {}",
+                e
+            );
+        }
+        is_per_monitor_aware()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        true
+    }
+}
+
+/// Whether the calling thread works in physical pixels on every monitor
+pub fn is_per_monitor_aware() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::HiDpi::{
+            GetAwarenessFromDpiAwarenessContext, GetThreadDpiAwarenessContext,
+            DPI_AWARENESS_PER_MONITOR_AWARE,
+        };
+
+        let awareness =
+            unsafe { GetAwarenessFromDpiAwarenessContext(GetThreadDpiAwarenessContext()) };
+        awareness == DPI_AWARENESS_PER_MONITOR_AWARE
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        true
+    }
+}
+
+/// Scale factor (1.0 = 100%) of the monitor containing a physical point
+///
+/// Points outside every monitor use the nearest one.
+pub fn scale_factor_at(x: i32, y: i32) -> f64 {
+    monitor_at(x, y).map(|monitor| monitor.scale).unwrap_or(1.0)
+}
+
+/// Convert a physical point to logical coordinates of its monitor
+pub fn physical_to_logical(x: i32, y: i32) -> (i32, i32) {
+    match monitor_at(x, y) {
+        Some(monitor) => scale_about((x, y), monitor.origin, 1.0 / monitor.scale),
+        None => (x, y),
+    }
+}
+
+/// Convert a logical point of a monitor to physical coordinates
+pub fn logical_to_physical(x: i32, y: i32) -> (i32, i32) {
+    // A monitor's logical extent lies within its physical one, starting at
+    // the same origin, so the logical point itself finds the monitor
+    match monitor_at(x, y) {
+        Some(monitor) => scale_about((x, y), monitor.origin, monitor.scale),
+        None => (x, y),
+    }
+}
+
+/// Convert physical pixels to the coordinates the cursor APIs expect
+#[cfg(any(not(target_os = "linux"), test))]
+pub(crate) fn physical_to_input(x: i32, y: i32) -> (i32, i32) {
+    if is_per_monitor_aware() {
+        return (x, y);
+    }
+    let scale = system_scale();
+    (
+        (x as f64 / scale).round() as i32,
+        (y as f64 / scale).round() as i32,
+    )
+}
+
+/// Convert cursor API coordinates back to physical pixels
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn input_to_physical(x: i32, y: i32) -> (i32, i32) {
+    if is_per_monitor_aware() {
+        return (x, y);
+    }
+    let scale = system_scale();
+    (
+        (x as f64 * scale).round() as i32,
+        (y as f64 * scale).round() as i32,
+    )
+}
+
+/// Scale `point` by `factor` around `origin`
+fn scale_about(point: (i32, i32), origin: (i32, i32), factor: f64) -> (i32, i32) {
+    (
+        origin.0 + ((point.0 - origin.0) as f64 * factor).round() as i32,
+        origin.1 + ((point.1 - origin.1) as f64 * factor).round() as i32,
+    )
+}
+
+/// Monitor containing a point
+#[derive(Debug, Clone, Copy)]
+struct MonitorScale {
+    /// Top-left corner in physical pixels
+    origin: (i32, i32),
+    /// Scale factor (1.0 = 100%)
+    scale: f64,
+}
+
+fn monitor_at(x: i32, y: i32) -> Option<MonitorScale> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::POINT;
+        use windows::Win32::Graphics::Gdi::{
+            GetMonitorInfoW, MonitorFromPoint, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+        };
+        use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+
+        let monitor = unsafe { MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONEAREST) };
+        if monitor.is_invalid() {
+            return None;
+        }
+
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !unsafe { GetMonitorInfoW(monitor, &mut info) }.as_bool() {
+            return None;
+        }
+
+        let (mut dpi_x, mut dpi_y) = (0u32, 0u32);
+        unsafe { GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) }.ok()?;
+
+        Some(MonitorScale {
+            origin: (info.rcMonitor.left, info.rcMonitor.top),
+            scale: dpi_x.max(1) as f64 / BASE_DPI as f64,
+        })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (x, y);
+        None
+    }
+}
+
+/// Scale factor of the system DPI, used by processes that are not per-monitor aware
+fn system_scale() -> f64 {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::HiDpi::GetDpiForSystem;
+
+        unsafe { GetDpiForSystem() }.max(1) as f64 / BASE_DPI as f64
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_about_origin() {
+        // 150% monitor to the right of a 1920px primary
+        let origin = (1920, 0);
+        assert_eq!(scale_about((1920, 0), origin, 1.5), (1920, 0));
+        assert_eq!(scale_about((2020, 100), origin, 1.5), (2070, 150));
+        assert_eq!(scale_about((2070, 150), origin, 1.0 / 1.5), (2020, 100));
+    }
+
+    #[test]
+    fn test_scale_about_negative_coordinates() {
+        // Monitor left of the primary
+        let origin = (-2560, -200);
+        assert_eq!(scale_about((-2460, -100), origin, 2.0), (-2360, 0));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_conversions_are_identity_without_monitors() {
+        assert_eq!(physical_to_logical(100, 200), (100, 200));
+        assert_eq!(logical_to_physical(100, 200), (100, 200));
+        assert_eq!(physical_to_input(100, 200), (100, 200));
+        assert_eq!(scale_factor_at(0, 0), 1.0);
+    }
+}
@@ -1,14 +1,17 @@
 //! UI element wrapper providing safe interaction with Windows UI elements
 
+use crate::dpi;
 use crate::errors::AutomationError;
-use crate::selector::Selector;
+use crate::input::input_point;
+use crate::selector::{parse_path, PathStep, Selector};
+use crate::types::{ClickResult, ElementNode, SelectOption, TableData, UIElementAttributes};
 use std::collections::HashMap;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use uiautomation::controls::ControlType;
 use uiautomation::core::UICondition;
-use uiautomation::types::{Point, ScrollAmount, TreeScope, UIProperty};
+use uiautomation::types::{ScrollAmount, TreeScope, UIProperty};
 use uiautomation::variants::Variant;
 use uiautomation::{filters::*, inputs::*, patterns, UIAutomation};
 
@@ -33,32 +36,6 @@ pub(crate) struct ThreadSafeElement(pub Arc<uiautomation::UIElement>);
 unsafe impl Send for ThreadSafeElement {}
 unsafe impl Sync for ThreadSafeElement {}
 
-/// Result of a click operation
-#[derive(Debug, Clone)]
-pub struct ClickResult {
-    /// Method used to perform the click
-    pub method: String,
-    /// Coordinates where the click occurred
-    pub coordinates: Option<(f64, f64)>,
-    /// Additional details about the operation
-    pub details: String,
-}
-
-/// Attributes of a UI element
-#[derive(Debug, Clone)]
-pub struct UIElementAttributes {
-    /// Element role (control type)
-    pub role: String,
-    /// Element label
-    pub label: Option<String>,
-    /// Element value
-    pub value: Option<String>,
-    /// Element description
-    pub description: Option<String>,
-    /// Additional properties
-    pub properties: HashMap<String, Option<serde_json::Value>>,
-}
-
 /// Safe wrapper around a Windows UI element
 #[derive(Clone)]
 pub struct UIElement {
@@ -66,6 +43,16 @@ pub struct UIElement {
     automation: ThreadSafeAutomation,
 }
 
+/// Text of a grid cell: its value, falling back to its name
+fn cell_text(cell: &uiautomation::UIElement) -> String {
+    cell.get_property_value(UIProperty::ValueValue)
+        .and_then(|value| value.get_string())
+        .ok()
+        .filter(|value| !value.is_empty())
+        .or_else(|| cell.get_name().ok())
+        .unwrap_or_default()
+}
+
 impl fmt::Debug for UIElement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("UIElement")
@@ -104,6 +91,14 @@ impl UIElement {
         self.element.0.get_name().ok()
     }
 
+    /// Check whether this element still exists in the UI
+    ///
+    /// Elements of closed windows or removed controls can no longer be
+    /// queried; their runtime ID lookup fails.
+    pub fn is_alive(&self) -> bool {
+        self.element.0.get_runtime_id().is_ok()
+    }
+
     /// Get comprehensive attributes of this element
     pub fn attributes(&self) -> UIElementAttributes {
         let mut properties = HashMap::new();
@@ -149,6 +144,8 @@ impl UIElement {
     }
 
     /// Get the bounding rectangle of this element (x, y, width, height)
+    ///
+    /// Coordinates are physical screen pixels.
     pub fn bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError> {
         let rect = self
             .element
@@ -164,6 +161,44 @@ impl UIElement {
         ))
     }
 
+    /// Scale factor (1.0 = 100%) of the monitor showing this element
+    pub fn scale_factor(&self) -> Result<f64, AutomationError> {
+        let (left, top, width, height) = self.bounds()?;
+        Ok(dpi::scale_factor_at(
+            (left + width / 2.0) as i32,
+            (top + height / 2.0) as i32,
+        ))
+    }
+
+    /// Get the bounding rectangle in logical pixels of its monitor
+    ///
+    /// [`bounds`](Self::bounds) is in physical pixels; see [`crate::dpi`].
+    pub fn logical_bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError> {
+        let (left, top, width, height) = self.bounds()?;
+        let scale = dpi::scale_factor_at(left as i32, top as i32);
+        let (x, y) = dpi::physical_to_logical(left as i32, top as i32);
+        Ok((x as f64, y as f64, width / scale, height / scale))
+    }
+
+    /// Frame this element on screen for `duration`
+    ///
+    /// Returns once the frame is shown; see [`crate::highlight_rect`].
+    pub fn highlight(
+        &self,
+        color: (u8, u8, u8),
+        duration: std::time::Duration,
+    ) -> Result<(), AutomationError> {
+        let (left, top, width, height) = self.bounds()?;
+        crate::highlight::highlight_rect(
+            left as i32,
+            top as i32,
+            width as i32,
+            height as i32,
+            color,
+            duration,
+        )
+    }
+
     /// Get child elements
     pub fn children(&self) -> Result<Vec<UIElement>, AutomationError> {
         let children = self
@@ -186,6 +221,85 @@ impl UIElement {
         }
     }
 
+    /// Snapshot this element and its subtree
+    ///
+    /// `depth` is the number of child levels to include; 0 returns the
+    /// element alone. Children that disappear while the tree is walked are
+    /// skipped.
+    pub fn to_tree(&self, depth: usize) -> Result<ElementNode, AutomationError> {
+        let mut node = self.to_node();
+        if depth == 0 {
+            return Ok(node);
+        }
+
+        let condition = self
+            .automation
+            .0
+            .create_true_condition()
+            .map_err(AutomationError::platform)?;
+        let children = self
+            .element
+            .0
+            .find_all(TreeScope::Children, &condition)
+            .unwrap_or_default();
+
+        for child in children {
+            let child = UIElement::new(child, &self.automation);
+            if let Ok(child_node) = child.to_tree(depth - 1) {
+                node.children.push(child_node);
+            }
+        }
+
+        Ok(node)
+    }
+
+    /// Snapshot this element without its children
+    fn to_node(&self) -> ElementNode {
+        ElementNode {
+            role: self.role(),
+            name: self.name().filter(|name| !name.is_empty()),
+            automation_id: self.id().filter(|id| !id.is_empty()),
+            bounds: self.bounds().ok(),
+            patterns: self.supported_patterns(),
+            is_enabled: self.is_enabled().unwrap_or(false),
+            is_visible: self.is_visible().unwrap_or(false),
+            children: Vec::new(),
+        }
+    }
+
+    /// Names of the control patterns this element supports
+    pub fn supported_patterns(&self) -> Vec<String> {
+        let element = &self.element.0;
+        let mut supported = Vec::new();
+
+        macro_rules! check_patterns {
+            ($($name:literal => $pattern:ident),* $(,)?) => {
+                $(
+                    if element.get_pattern::<patterns::$pattern>().is_ok() {
+                        supported.push($name.to_string());
+                    }
+                )*
+            };
+        }
+
+        check_patterns!(
+            "Invoke" => UIInvokePattern,
+            "Value" => UIValuePattern,
+            "Toggle" => UITogglePattern,
+            "ExpandCollapse" => UIExpandCollapsePattern,
+            "SelectionItem" => UISelectionItemPattern,
+            "Selection" => UISelectionPattern,
+            "Scroll" => UIScrollPattern,
+            "RangeValue" => UIRangeValuePattern,
+            "Grid" => UIGridPattern,
+            "Table" => UITablePattern,
+            "Text" => UITextPattern,
+            "Window" => UIWindowPattern,
+        );
+
+        supported
+    }
+
     /// Click on this element
     ///
     /// Attempts multiple strategies: direct click, clickable point, and center of bounds
@@ -207,7 +321,9 @@ impl UIElement {
         // Strategy 2: Use clickable point
         if let Ok(Some(point)) = self.element.0.get_clickable_point() {
             let mouse = Mouse::default();
-            mouse.click(point).map_err(AutomationError::platform)?;
+            mouse
+                .click(input_point(point.get_x(), point.get_y()))
+                .map_err(AutomationError::platform)?;
 
             return Ok(ClickResult {
                 method: "ClickablePoint".to_string(),
@@ -221,7 +337,7 @@ impl UIElement {
             let center_x = rect.get_left() + rect.get_width() / 2;
             let center_y = rect.get_top() + rect.get_height() / 2;
 
-            let point = Point::new(center_x, center_y);
+            let point = input_point(center_x, center_y);
             let mouse = Mouse::default();
 
             mouse.click(point).map_err(AutomationError::platform)?;
@@ -251,7 +367,7 @@ impl UIElement {
 
         let mouse = Mouse::default();
         mouse
-            .double_click(point)
+            .double_click(input_point(point.get_x(), point.get_y()))
             .map_err(AutomationError::platform)?;
 
         Ok(ClickResult {
@@ -274,7 +390,7 @@ impl UIElement {
 
         let mouse = Mouse::default();
         mouse
-            .right_click(point)
+            .right_click(input_point(point.get_x(), point.get_y()))
             .map_err(AutomationError::platform)?;
 
         Ok(())
@@ -290,10 +406,19 @@ impl UIElement {
 
     /// Type text into this element
     ///
-    /// The element should be focused before typing
+    /// The element should be focused before typing. Text is typed literally,
+    /// see [`InputSimulator::type_text`](crate::InputSimulator::type_text).
     pub fn type_text(&self, text: &str) -> Result<(), AutomationError> {
-        let keyboard = Keyboard::default();
-        keyboard.send_text(text).map_err(AutomationError::platform)
+        #[cfg(target_os = "windows")]
+        {
+            crate::input::send_unicode_text(text)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let keyboard = Keyboard::default();
+            keyboard.send_text(text).map_err(AutomationError::platform)
+        }
     }
 
     /// Press a key or key combination
@@ -371,6 +496,144 @@ impl UIElement {
             .map_err(AutomationError::platform)
     }
 
+    /// Extract the cells of a data grid or table
+    ///
+    /// Reads cells through the Grid pattern and column headers through the
+    /// Table pattern when the element supports it. At most `max_rows` rows
+    /// are read; virtualized grids may report empty text for rows scrolled
+    /// out of view.
+    pub fn extract_table(&self, max_rows: usize) -> Result<TableData, AutomationError> {
+        let grid = self
+            .element
+            .0
+            .get_pattern::<patterns::UIGridPattern>()
+            .map_err(|e| {
+                AutomationError::UnsupportedOperation(format!(
+                    "Element does not support grid pattern: {}",
+                    e
+                ))
+            })?;
+
+        let row_count = grid
+            .get_row_count()
+            .map_err(AutomationError::platform)?
+            .max(0) as usize;
+        let column_count = grid
+            .get_column_count()
+            .map_err(AutomationError::platform)?
+            .max(0) as usize;
+
+        let headers = self
+            .element
+            .0
+            .get_pattern::<patterns::UITablePattern>()
+            .and_then(|table| table.get_column_headers())
+            .map(|headers| {
+                headers
+                    .iter()
+                    .map(|header| header.get_name().unwrap_or_default())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut rows = Vec::with_capacity(row_count.min(max_rows));
+        for row in 0..row_count.min(max_rows) {
+            let cells = (0..column_count)
+                .map(|column| {
+                    grid.get_item(row as i32, column as i32)
+                        .map(|cell| cell_text(&cell))
+                        .unwrap_or_default()
+                })
+                .collect();
+            rows.push(cells);
+        }
+
+        Ok(TableData {
+            headers,
+            rows,
+            row_count,
+            column_count,
+        })
+    }
+
+    /// Select an option of a combo box or list
+    ///
+    /// Expands the element if it supports ExpandCollapse, so drop-down items
+    /// are materialized, then scrolls the option into view and selects it
+    /// through the SelectionItem pattern. A drop-down is collapsed again
+    /// afterwards. Returns the name of the selected option.
+    pub fn select_option(&self, option: &SelectOption) -> Result<String, AutomationError> {
+        let expand_collapse = self
+            .element
+            .0
+            .get_pattern::<patterns::UIExpandCollapsePattern>()
+            .ok();
+        if let Some(pattern) = &expand_collapse {
+            if pattern.expand().is_ok() {
+                // Give the drop-down time to populate its items
+                std::thread::sleep(std::time::Duration::from_millis(150));
+            }
+        }
+
+        let condition = self
+            .automation
+            .0
+            .create_property_condition(
+                UIProperty::IsSelectionItemPatternAvailable,
+                Variant::from(true),
+                None,
+            )
+            .map_err(AutomationError::platform)?;
+        let items = self
+            .element
+            .0
+            .find_all(TreeScope::Descendants, &condition)
+            .unwrap_or_default();
+        let names: Vec<String> = items
+            .iter()
+            .map(|item| item.get_name().unwrap_or_default())
+            .collect();
+
+        let position = match option {
+            SelectOption::Index(index) => (*index < items.len()).then_some(*index),
+            SelectOption::Text(text) => {
+                let text = text.to_lowercase();
+                names
+                    .iter()
+                    .position(|name| name.to_lowercase() == text)
+                    .or_else(|| {
+                        names
+                            .iter()
+                            .position(|name| name.to_lowercase().contains(&text))
+                    })
+            }
+        };
+        let Some(position) = position else {
+            if let Some(pattern) = &expand_collapse {
+                let _ = pattern.collapse();
+            }
+            return Err(AutomationError::ElementNotFound(format!(
+                "No option matching {} among {} options",
+                option,
+                items.len()
+            )));
+        };
+
+        let item = &items[position];
+        if let Ok(scroll_item) = item.get_pattern::<patterns::UIScrollItemPattern>() {
+            let _ = scroll_item.scroll_into_view();
+        }
+        item.get_pattern::<patterns::UISelectionItemPattern>()
+            .and_then(|selection_item| selection_item.select())
+            .map_err(AutomationError::platform)?;
+
+        if let Some(pattern) = &expand_collapse {
+            let _ = pattern.collapse();
+        }
+
+        Ok(names[position].clone())
+    }
+
     /// Check if element is enabled
     pub fn is_enabled(&self) -> Result<bool, AutomationError> {
         self.element
@@ -490,13 +753,17 @@ impl UIElement {
     pub fn find_elements(&self, selector: &Selector) -> Result<Vec<UIElement>, AutomationError> {
         // Use specialized methods that don't need conditions for most selectors
         match selector {
-            Selector::Text(_) | Selector::Name(_) | Selector::Role { .. } | Selector::Id(_) => {
+            Selector::Text(_) | Selector::Name(_) => {
                 // These use matchers, fall back to finding first and wrapping in Vec
                 match self.find_element(selector) {
                     Ok(elem) => Ok(vec![elem]),
                     Err(_) => Ok(vec![]),
                 }
             }
+            Selector::Role { role, name } => self.find_all_by_role(role, name.as_deref()),
+            Selector::Path(path) => self.find_by_path(path),
+            Selector::Chain(selectors) => self.find_by_chain(selectors),
+            Selector::Nth(_) => Err(nth_outside_chain()),
             _ => {
                 // For other selector types, use conditions
                 match self.selector_to_condition(selector) {
@@ -527,6 +794,17 @@ impl UIElement {
             Selector::Name(name) => self.find_by_name(name),
             Selector::Role { role, name } => self.find_by_role(role, name.as_deref()),
             Selector::Id(id) => self.find_by_id(id),
+            Selector::Path(path) => self
+                .find_by_path(path)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| AutomationError::element_not_found(selector)),
+            Selector::Chain(selectors) => self
+                .find_by_chain(selectors)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| AutomationError::element_not_found(selector)),
+            Selector::Nth(_) => Err(nth_outside_chain()),
             _ => match self.selector_to_condition(selector) {
                 Ok(condition) => {
                     let element = self
@@ -613,6 +891,39 @@ impl UIElement {
         Ok(UIElement::new(element, &self.automation))
     }
 
+    /// Helper: Find all elements of a role, optionally containing a name
+    fn find_all_by_role(
+        &self,
+        role: &str,
+        name: Option<&str>,
+    ) -> Result<Vec<UIElement>, AutomationError> {
+        let control_type = map_role_to_control_type(role);
+        let condition = self
+            .automation
+            .0
+            .create_property_condition(
+                UIProperty::ControlType,
+                Variant::from(control_type as i32),
+                None,
+            )
+            .map_err(AutomationError::platform)?;
+
+        let elements = self
+            .element
+            .0
+            .find_all(TreeScope::Subtree, &condition)
+            .map_err(|e| AutomationError::element_not_found(format!("role '{}': {}", role, e)))?;
+
+        Ok(elements
+            .into_iter()
+            .map(|element| UIElement::new(element, &self.automation))
+            .filter(|element| match name {
+                Some(name) => element.name().is_some_and(|n| n.contains(name)),
+                None => true,
+            })
+            .collect())
+    }
+
     /// Helper: Find by automation ID
     fn find_by_id(&self, id: &str) -> Result<UIElement, AutomationError> {
         let condition = self
@@ -630,6 +941,107 @@ impl UIElement {
         Ok(UIElement::new(element, &self.automation))
     }
 
+    /// Helper: Resolve a path expression by walking the tree one step at a time
+    ///
+    /// Each step is matched against the children (or, for `//`, all
+    /// descendants) of the elements matched by the previous step, so an early
+    /// `window[...]` step keeps later steps from matching in other windows.
+    fn find_by_path(&self, path: &str) -> Result<Vec<UIElement>, AutomationError> {
+        let steps = parse_path(path)?;
+        let condition = self
+            .automation
+            .0
+            .create_true_condition()
+            .map_err(AutomationError::platform)?;
+
+        let mut context = vec![self.clone()];
+        for step in &steps {
+            let scope = if step.descendant {
+                TreeScope::Descendants
+            } else {
+                TreeScope::Children
+            };
+
+            let mut matched = Vec::new();
+            for parent in &context {
+                // Elements that vanish mid-walk simply have no children
+                let candidates = parent
+                    .element
+                    .0
+                    .find_all(scope, &condition)
+                    .unwrap_or_default();
+                let mut matching = candidates
+                    .into_iter()
+                    .map(|element| UIElement::new(element, &self.automation))
+                    .filter(|element| element.matches_step(step));
+
+                match step.index {
+                    Some(index) => matched.extend(matching.nth(index - 1)),
+                    None => matched.extend(matching),
+                }
+            }
+
+            if matched.is_empty() {
+                return Ok(matched);
+            }
+            context = matched;
+        }
+
+        Ok(context)
+    }
+
+    /// Helper: Resolve a chain, searching within the matches of each previous selector
+    ///
+    /// A [`Selector::Nth`] link keeps only that element of the matches so far.
+    fn find_by_chain(&self, selectors: &[Selector]) -> Result<Vec<UIElement>, AutomationError> {
+        let mut context = vec![self.clone()];
+
+        for (position, selector) in selectors.iter().enumerate() {
+            context = match selector {
+                Selector::Nth(_) if position == 0 => return Err(nth_outside_chain()),
+                Selector::Nth(index) => context.into_iter().nth(*index).into_iter().collect(),
+                _ => {
+                    let mut matched = Vec::new();
+                    for parent in &context {
+                        matched.extend(parent.find_elements(selector)?);
+                    }
+                    matched
+                }
+            };
+
+            if context.is_empty() {
+                break;
+            }
+        }
+
+        Ok(context)
+    }
+
+    /// Check whether this element satisfies a path step's role and predicates
+    fn matches_step(&self, step: &PathStep) -> bool {
+        if let Some(role) = &step.role {
+            let expected = map_role_to_control_type(role) as i32;
+            match self.element.0.get_control_type() {
+                Ok(control_type) if control_type as i32 == expected => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(name) = &step.name {
+            if !self.name().is_some_and(|n| name.matches(&n)) {
+                return false;
+            }
+        }
+
+        if let Some(id) = &step.id {
+            if self.id().as_deref() != Some(id.as_str()) {
+                return false;
+            }
+        }
+
+        true
+    }
+
     /// Convert selector to UIAutomation condition (not used for most selectors)
     fn selector_to_condition(&self, selector: &Selector) -> Result<UICondition, AutomationError> {
         match selector {
@@ -659,7 +1071,6 @@ impl UIElement {
             ))),
         }
     }
-
 }
 
 impl PartialEq for UIElement {
@@ -682,6 +1093,11 @@ impl Hash for UIElement {
     }
 }
 
+/// Error for an `nth` selector that does not follow another selector
+fn nth_outside_chain() -> AutomationError {
+    AutomationError::InvalidArgument("nth must follow another selector in a chain".to_string())
+}
+
 /// Map generic role names to Windows ControlType
 fn map_role_to_control_type(role: &str) -> ControlType {
     match role.to_lowercase().as_str() {
@@ -1,17 +1,69 @@
 //! Automation engine - core interface for UI automation
 
+use crate::clipboard::Clipboard;
 use crate::element::{ThreadSafeAutomation, UIElement};
 use crate::errors::AutomationError;
-use crate::input::InputSimulator;
+use crate::events::{UiEvent, UiEventKind, UiEventSubscription};
+use crate::input::{InputSimulator, MouseButton};
+use crate::macros::MacroEvent;
 use crate::selector::Selector;
 use crate::window::WindowManager;
 use std::sync::Arc;
 use std::time::Duration;
+#[cfg(not(target_os = "linux"))]
 use uiautomation::controls::ControlType;
+#[cfg(not(target_os = "linux"))]
 use uiautomation::types::{TreeScope, UIProperty};
+#[cfg(not(target_os = "linux"))]
 use uiautomation::variants::Variant;
+#[cfg(not(target_os = "linux"))]
 use uiautomation::UIAutomation;
 
+/// Longest pause between two replayed macro events, in milliseconds
+const MAX_REPLAY_GAP_MS: u64 = 5_000;
+/// How long replay looks for a recorded click target before using coordinates
+const REPLAY_ELEMENT_TIMEOUT_MS: u64 = 2_000;
+
+/// Condition an element must satisfy for [`AutomationEngine::wait_for_element`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WaitCondition {
+    /// Element exists in the UI tree
+    Exists,
+    /// Element exists and is not offscreen
+    Visible,
+    /// Element exists and is enabled
+    Enabled,
+    /// Element (or its descendants) contains the given text
+    TextContains(String),
+}
+
+impl WaitCondition {
+    /// Parse a condition from its name ("exists", "visible", "enabled", "text-contains")
+    ///
+    /// `text` is required for "text-contains" and ignored otherwise.
+    pub fn from_name(name: &str, text: Option<&str>) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "exists" => Some(WaitCondition::Exists),
+            "visible" => Some(WaitCondition::Visible),
+            "enabled" => Some(WaitCondition::Enabled),
+            "text-contains" | "text_contains" => {
+                text.map(|t| WaitCondition::TextContains(t.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Check whether the element currently satisfies this condition
+    pub fn is_met(&self, element: &UIElement) -> Result<bool, AutomationError> {
+        match self {
+            WaitCondition::Exists => Ok(true),
+            WaitCondition::Visible => element.is_visible(),
+            WaitCondition::Enabled => element.is_enabled(),
+            WaitCondition::TextContains(text) => Ok(element.text(5)?.contains(text.as_str())),
+        }
+    }
+}
+
 /// Main automation engine for Windows UI automation
 ///
 /// This is the primary entry point for all automation operations.
@@ -20,6 +72,7 @@ pub struct AutomationEngine {
     automation: ThreadSafeAutomation,
     window_manager: WindowManager,
     input_simulator: InputSimulator,
+    clipboard: Clipboard,
 }
 
 impl AutomationEngine {
@@ -28,43 +81,126 @@ impl AutomationEngine {
     /// # Errors
     ///
     /// Returns an error if the UIAutomation COM interface cannot be initialized.
+    /// On Linux the AT-SPI bus is connected on first use, so bus errors are
+    /// reported by the first element lookup instead.
     #[allow(clippy::arc_with_non_send_sync)]
     pub fn new() -> Result<Self, AutomationError> {
-        let automation = UIAutomation::new().map_err(|e| {
-            AutomationError::platform(format!("Failed to initialize UIAutomation: {}", e))
-        })?;
+        // Element bounds are physical pixels; keep cursor coordinates in the same space
+        crate::dpi::enable_dpi_awareness();
+
+        #[cfg(not(target_os = "linux"))]
+        let automation = {
+            let automation = UIAutomation::new().map_err(|e| {
+                AutomationError::platform(format!("Failed to initialize UIAutomation: {}", e))
+            })?;
+            ThreadSafeAutomation(Arc::new(automation))
+        };
+
+        #[cfg(target_os = "linux")]
+        let automation = ThreadSafeAutomation(Arc::new(crate::linux::atspi::Bus::new()));
 
-        let automation = ThreadSafeAutomation(Arc::new(automation));
         let window_manager = WindowManager::new();
         let input_simulator = InputSimulator::new();
+        let clipboard = Clipboard::new();
 
         Ok(Self {
             automation,
             window_manager,
             input_simulator,
+            clipboard,
         })
     }
 
+    /// Subscribe to UIAutomation events across the desktop
+    ///
+    /// `callback` is invoked for every event of the given kinds until the
+    /// returned subscription is stopped or dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use screensearch_automation::{AutomationEngine, UiEventKind};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let engine = AutomationEngine::new()?;
+    /// let subscription = engine.subscribe_events(&[UiEventKind::WindowOpened], |event| {
+    ///     println!("Window opened: {:?}", event.name);
+    /// })?;
+    /// // ...
+    /// subscription.stop();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subscribe_events<F>(
+        &self,
+        kinds: &[UiEventKind],
+        callback: F,
+    ) -> Result<UiEventSubscription, AutomationError>
+    where
+        F: Fn(UiEvent) + Send + Sync + 'static,
+    {
+        UiEventSubscription::start(kinds, Box::new(callback))
+    }
+
     /// Get the root UI element (desktop)
     pub fn root(&self) -> Result<UIElement, AutomationError> {
-        let root = self
-            .automation
-            .0
-            .get_root_element()
-            .map_err(AutomationError::platform)?;
+        #[cfg(target_os = "linux")]
+        {
+            crate::linux::root(&self.automation)
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let root = self
+                .automation
+                .0
+                .get_root_element()
+                .map_err(AutomationError::platform)?;
 
-        Ok(UIElement::new(root, &self.automation))
+            Ok(UIElement::new(root, &self.automation))
+        }
     }
 
     /// Get the currently focused element
     pub fn focused_element(&self) -> Result<UIElement, AutomationError> {
-        let element = self
-            .automation
-            .0
-            .get_focused_element()
-            .map_err(AutomationError::platform)?;
+        #[cfg(target_os = "linux")]
+        {
+            crate::linux::focused_element(&self.automation)
+        }
 
-        Ok(UIElement::new(element, &self.automation))
+        #[cfg(not(target_os = "linux"))]
+        {
+            let element = self
+                .automation
+                .0
+                .get_focused_element()
+                .map_err(AutomationError::platform)?;
+
+            Ok(UIElement::new(element, &self.automation))
+        }
+    }
+
+    /// Get the UI element of the foreground window
+    pub fn active_window_element(&self) -> Result<UIElement, AutomationError> {
+        #[cfg(target_os = "linux")]
+        {
+            crate::linux::active_window(&self.automation)
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let window = self
+                .windows()
+                .get_active()?
+                .ok_or_else(|| AutomationError::InvalidState("No active window".to_string()))?;
+
+            let element = self
+                .automation
+                .0
+                .element_from_handle(uiautomation::types::Handle::from(window.handle as isize))
+                .map_err(AutomationError::platform)?;
+
+            Ok(UIElement::new(element, &self.automation))
+        }
     }
 
     /// Find the first element matching a selector
@@ -72,7 +208,7 @@ impl AutomationEngine {
     /// # Example
     ///
     /// ```no_run
-    /// # use screen_automation::{AutomationEngine, Selector};
+    /// # use screensearch_automation::{AutomationEngine, Selector};
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let engine = AutomationEngine::new()?;
     /// let button = engine.find_element(&Selector::role("button").with_name("OK")).await?;
@@ -123,63 +259,81 @@ impl AutomationEngine {
 
     /// Get all application windows
     pub fn applications(&self) -> Result<Vec<UIElement>, AutomationError> {
-        let root = self
-            .automation
-            .0
-            .get_root_element()
-            .map_err(AutomationError::platform)?;
-
-        let condition = self
-            .automation
-            .0
-            .create_property_condition(
-                UIProperty::ControlType,
-                Variant::from(ControlType::Window as i32),
-                None,
-            )
-            .map_err(AutomationError::platform)?;
-
-        let elements = root
-            .find_all(TreeScope::Children, &condition)
-            .map_err(AutomationError::platform)?;
+        #[cfg(target_os = "linux")]
+        {
+            crate::linux::top_level_windows(&self.automation)
+        }
 
-        Ok(elements
-            .into_iter()
-            .map(|elem| UIElement::new(elem, &self.automation))
-            .collect())
+        #[cfg(not(target_os = "linux"))]
+        {
+            let root = self
+                .automation
+                .0
+                .get_root_element()
+                .map_err(AutomationError::platform)?;
+
+            let condition = self
+                .automation
+                .0
+                .create_property_condition(
+                    UIProperty::ControlType,
+                    Variant::from(ControlType::Window as i32),
+                    None,
+                )
+                .map_err(AutomationError::platform)?;
+
+            let elements = root
+                .find_all(TreeScope::Children, &condition)
+                .map_err(AutomationError::platform)?;
+
+            Ok(elements
+                .into_iter()
+                .map(|elem| UIElement::new(elem, &self.automation))
+                .collect())
+        }
     }
 
     /// Find application by name
     ///
-    /// Searches for a window with a title containing the given name
+    /// Searches for a window with a title containing the given name, then
+    /// for a window of a process (on Linux, an application) with that name
     pub async fn application(&self, name: &str) -> Result<UIElement, AutomationError> {
-        let root = self
-            .automation
-            .0
-            .get_root_element()
-            .map_err(AutomationError::platform)?;
+        #[cfg(target_os = "linux")]
+        {
+            crate::linux::application_window(&self.automation, name)
+        }
 
-        // Try to find by matcher first
-        let matcher = self
-            .automation
-            .0
-            .create_matcher()
-            .control_type(ControlType::Window)
-            .contains_name(name)
-            .from_ref(&root)
-            .depth(7)
-            .timeout(5000);
-
-        match matcher.find_first() {
-            Ok(element) => Ok(UIElement::new(element, &self.automation)),
-            Err(_) => {
-                // Fallback: try to find by process name
-                self.find_by_process_name(name).await
+        #[cfg(not(target_os = "linux"))]
+        {
+            let root = self
+                .automation
+                .0
+                .get_root_element()
+                .map_err(AutomationError::platform)?;
+
+            // Try to find by matcher first
+            let matcher = self
+                .automation
+                .0
+                .create_matcher()
+                .control_type(ControlType::Window)
+                .contains_name(name)
+                .from_ref(&root)
+                .depth(7)
+                .timeout(5000);
+
+            match matcher.find_first() {
+                Ok(element) => Ok(UIElement::new(element, &self.automation)),
+                Err(_) => {
+                    // Fallback: try to find by process name
+                    self.find_by_process_name(name).await
+                }
             }
         }
     }
 
     /// Helper to find window by process name
+    #[cfg(not(target_os = "linux"))]
     async fn find_by_process_name(&self, process_name: &str) -> Result<UIElement, AutomationError> {
         // Get PID from process name
         let pid = get_pid_by_name(process_name).ok_or_else(|| {
@@ -215,7 +369,8 @@ impl AutomationEngine {
 
     /// Open an application
     ///
-    /// Uses PowerShell's `start` command to launch the application
+    /// Uses PowerShell's `start` command to launch the application; on Linux
+    /// the name is run as a command, or as a desktop entry via `gtk-launch`
     ///
     /// Note: This method does not wait for or return the application window.
     /// Use `application()` separately if you need to interact with the window.
@@ -223,26 +378,32 @@ impl AutomationEngine {
         let app_name_for_closure = app_name.to_string();
 
         // Run blocking Command in thread pool and wait for app to start
-        tokio::task::spawn_blocking(move || {
-            let status = std::process::Command::new("powershell")
-                .args([
-                    "-NoProfile",
-                    "-WindowStyle",
-                    "hidden",
-                    "-Command",
-                    "start",
-                    &app_name_for_closure,
-                ])
-                .status()
-                .map_err(|e| {
-                    AutomationError::platform(format!("Failed to execute command: {}", e))
-                })?;
-
-            if !status.success() {
-                return Err(AutomationError::platform(format!(
-                    "Failed to open application: {}",
-                    app_name_for_closure
-                )));
+        tokio::task::spawn_blocking(move || -> Result<(), AutomationError> {
+            #[cfg(target_os = "linux")]
+            crate::linux::open_application(&app_name_for_closure)?;
+
+            #[cfg(not(target_os = "linux"))]
+            {
+                let status = std::process::Command::new("powershell")
+                    .args([
+                        "-NoProfile",
+                        "-WindowStyle",
+                        "hidden",
+                        "-Command",
+                        "start",
+                        &app_name_for_closure,
+                    ])
+                    .status()
+                    .map_err(|e| {
+                        AutomationError::platform(format!("Failed to execute command: {}", e))
+                    })?;
+
+                if !status.success() {
+                    return Err(AutomationError::platform(format!(
+                        "Failed to open application: {}",
+                        app_name_for_closure
+                    )));
+                }
             }
 
             // Wait for application to start
@@ -264,21 +425,27 @@ impl AutomationEngine {
         let browser_for_closure = browser.unwrap_or("").to_string();
 
         // Run blocking Command in thread pool
-        tokio::task::spawn_blocking(move || {
-            let mut cmd = std::process::Command::new("powershell");
-            cmd.args(["-NoProfile", "-WindowStyle", "hidden", "-Command", "start"]);
+        tokio::task::spawn_blocking(move || -> Result<(), AutomationError> {
+            #[cfg(target_os = "linux")]
+            crate::linux::open_url(&url_for_closure, Some(browser_for_closure.as_str()))?;
 
-            if !browser_for_closure.is_empty() {
-                cmd.arg(&browser_for_closure);
-            }
-            cmd.arg(&url_for_closure);
+            #[cfg(not(target_os = "linux"))]
+            {
+                let mut cmd = std::process::Command::new("powershell");
+                cmd.args(["-NoProfile", "-WindowStyle", "hidden", "-Command", "start"]);
 
-            let status = cmd.status().map_err(|e| {
-                AutomationError::platform(format!("Failed to execute command: {}", e))
-            })?;
+                if !browser_for_closure.is_empty() {
+                    cmd.arg(&browser_for_closure);
+                }
+                cmd.arg(&url_for_closure);
 
-            if !status.success() {
-                return Err(AutomationError::platform("Failed to open URL".to_string()));
+                let status = cmd.status().map_err(|e| {
+                    AutomationError::platform(format!("Failed to execute command: {}", e))
+                })?;
+
+                if !status.success() {
+                    return Err(AutomationError::platform("Failed to open URL".to_string()));
+                }
             }
 
             // Wait for browser to start
@@ -301,12 +468,17 @@ impl AutomationEngine {
         &self.input_simulator
     }
 
+    /// Get clipboard for reading and writing text
+    pub fn clipboard(&self) -> &Clipboard {
+        &self.clipboard
+    }
+
     /// Wait for a condition to be true
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use screen_automation::{AutomationEngine, Selector};
+    /// # use screensearch_automation::{AutomationEngine, Selector};
     /// # use std::time::Duration;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let engine = AutomationEngine::new()?;
@@ -352,6 +524,153 @@ impl AutomationEngine {
         }
     }
 
+    /// Wait until an element matching the selector satisfies a condition
+    ///
+    /// Polls the UI tree via [`wait_for`](Self::wait_for) and returns the
+    /// matching element, or a timeout error if the condition is not met in time.
+    pub async fn wait_for_element(
+        &self,
+        selector: &Selector,
+        condition: &WaitCondition,
+        timeout: Duration,
+    ) -> Result<UIElement, AutomationError> {
+        let root = self.root()?;
+        let mut matched = None;
+
+        self.wait_for(timeout, || match root.find_element(selector) {
+            Ok(element) => {
+                let met = condition.is_met(&element)?;
+                if met {
+                    matched = Some(element);
+                }
+                Ok(met)
+            }
+            Err(e) if e.is_not_found() => Ok(false),
+            Err(e) => Err(e),
+        })
+        .await?;
+
+        matched.ok_or_else(|| AutomationError::Internal("Matched element was lost".to_string()))
+    }
+
+    /// Replay recorded macro events
+    ///
+    /// Waits between events according to their recorded offsets divided by
+    /// `speed` (pauses are capped at 5 seconds). A click on a named element is
+    /// moved to that element's center if the element has moved away from the
+    /// recorded coordinates. Returns the number of events replayed.
+    pub async fn replay_macro(
+        &self,
+        events: &[MacroEvent],
+        speed: f64,
+    ) -> Result<usize, AutomationError> {
+        let speed = if speed.is_finite() && speed > 0.0 {
+            speed
+        } else {
+            1.0
+        };
+
+        let mut previous_offset = 0;
+        for (index, event) in events.iter().enumerate() {
+            let gap = event
+                .offset_ms()
+                .saturating_sub(previous_offset)
+                .min(MAX_REPLAY_GAP_MS);
+            previous_offset = event.offset_ms();
+            tokio::time::sleep(Duration::from_millis((gap as f64 / speed) as u64)).await;
+
+            self.replay_event(event).await.map_err(|e| {
+                AutomationError::Internal(format!("Macro event {} failed: {}", index + 1, e))
+            })?;
+        }
+
+        Ok(events.len())
+    }
+
+    /// Replay a single macro event
+    async fn replay_event(&self, event: &MacroEvent) -> Result<(), AutomationError> {
+        match event {
+            MacroEvent::Click {
+                x,
+                y,
+                button,
+                element_name,
+                ..
+            } => {
+                let button = match button.as_str() {
+                    "right" => MouseButton::Right,
+                    "middle" => MouseButton::Middle,
+                    _ => MouseButton::Left,
+                };
+                let (x, y) = match element_name {
+                    Some(name) => self.resolve_click_target(name, (*x, *y)).await,
+                    None => (*x, *y),
+                };
+                self.click(x, y, button)
+            }
+            MacroEvent::Keys { keys, .. } => self.input().send_keys(keys),
+            MacroEvent::Text { text, .. } => self.input().type_text(text),
+            MacroEvent::WindowSwitch {
+                title,
+                process_name,
+                ..
+            } => {
+                let window = self
+                    .window_manager
+                    .find_by_title(title)?
+                    .into_iter()
+                    .find(|w| &w.title == title)
+                    .or_else(|| {
+                        self.window_manager
+                            .find_by_process(process_name)
+                            .ok()?
+                            .into_iter()
+                            .next()
+                    });
+
+                match window {
+                    Some(window) => self.window_manager.focus_window(window.handle),
+                    None => {
+                        tracing::warn!("Macro replay: window '{}' not found, skipping", title);
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pick the point to click for a recorded click on a named element
+    ///
+    /// Keeps the recorded point if the element is still under it (so clicks
+    /// inside large elements stay where they were), otherwise uses the center
+    /// of the element's current position.
+    async fn resolve_click_target(&self, name: &str, recorded: (i32, i32)) -> (i32, i32) {
+        let timeout = Duration::from_millis(REPLAY_ELEMENT_TIMEOUT_MS);
+        let bounds = self
+            .find_element_with_timeout(&Selector::name(name), timeout)
+            .await
+            .and_then(|element| element.bounds());
+
+        match bounds {
+            Ok((left, top, width, height)) => {
+                let (x, y) = (recorded.0 as f64, recorded.1 as f64);
+                if x >= left && x < left + width && y >= top && y < top + height {
+                    recorded
+                } else {
+                    ((left + width / 2.0) as i32, (top + height / 2.0) as i32)
+                }
+            }
+            Err(e) => {
+                tracing::debug!(
+                    "Macro replay: element '{}' not found ({}), using recorded coordinates",
+                    name,
+                    e
+                );
+                recorded
+            }
+        }
+    }
+
     // Convenience methods for screen-api compatibility
 
     /// Click at coordinates with specified button
@@ -361,13 +680,39 @@ impl AutomationEngine {
         self.input().click_at(x, y, button)
     }
 
+    /// Move the mouse smoothly to coordinates over `duration_ms`
+    ///
+    /// Wrapper around `input().move_to()`; 0 jumps straight there
+    pub fn move_mouse(&self, x: i32, y: i32, duration_ms: u64) -> Result<(), AutomationError> {
+        self.input()
+            .move_to(x, y, Duration::from_millis(duration_ms))
+    }
+
+    /// Drag between two screen points with the given button held
+    ///
+    /// Wrapper around `input().drag()` spreading `steps` intermediate moves
+    /// evenly over `duration_ms`
+    pub fn drag(
+        &self,
+        from: (i32, i32),
+        to: (i32, i32),
+        button: crate::MouseButton,
+        steps: u32,
+        duration_ms: u64,
+    ) -> Result<(), AutomationError> {
+        let steps = steps.max(1);
+        let step_delay = Duration::from_millis(duration_ms / steps as u64);
+        self.input().drag(from, to, button, steps, step_delay)
+    }
+
     /// Type text with optional delay between characters
     ///
     /// Wrapper around `input().type_text()` with optional character delay
     pub fn type_text(&self, text: &str, delay_ms: Option<u64>) -> Result<(), AutomationError> {
         if let Some(delay) = delay_ms {
-            // Type with delay between chars
-            for ch in text.chars() {
+            // Type with delay between chars; CRLF is a single line break
+            let normalized = text.replace("\r\n", "\n");
+            for ch in normalized.chars() {
                 self.input().type_text(&ch.to_string())?;
                 std::thread::sleep(std::time::Duration::from_millis(delay));
             }
@@ -451,6 +796,7 @@ impl Default for AutomationEngine {
 }
 
 /// Get process ID by process name
+#[cfg(not(target_os = "linux"))]
 fn get_pid_by_name(name: &str) -> Option<i32> {
     let command = format!(
         "Get-Process | Where-Object {{ $_.MainWindowTitle -ne '' -and $_.Name -like '*{}*' }} | ForEach-Object {{ $_.Id }}",
@@ -480,6 +826,20 @@ mod tests {
         assert!(engine.is_ok());
     }
 
+    #[test]
+    fn test_wait_condition_from_name() {
+        assert_eq!(
+            WaitCondition::from_name("Visible", None),
+            Some(WaitCondition::Visible)
+        );
+        assert_eq!(
+            WaitCondition::from_name("text-contains", Some("Done")),
+            Some(WaitCondition::TextContains("Done".to_string()))
+        );
+        assert_eq!(WaitCondition::from_name("text-contains", None), None);
+        assert_eq!(WaitCondition::from_name("focused", None), None);
+    }
+
     #[tokio::test]
     async fn test_get_root() {
         let engine = AutomationEngine::new().unwrap();
@@ -0,0 +1,366 @@
+//! UIAutomation event subscriptions
+//!
+//! Delivers focus changes, windows opening and closing, and UI structure
+//! changes to a callback as [`UiEvent`]s, so callers can react to a dialog
+//! appearing instead of polling the tree.
+//!
+//! Handlers are registered on a dedicated thread with its own UIAutomation
+//! instance and removed when the [`UiEventSubscription`] is stopped or dropped.
+
+use crate::errors::AutomationError;
+use serde::Serialize;
+use std::fmt;
+
+/// Kind of UIAutomation event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UiEventKind {
+    /// Keyboard focus moved to another element
+    FocusChanged,
+    /// A window was opened
+    WindowOpened,
+    /// A window was closed
+    WindowClosed,
+    /// Elements were added, removed or reordered
+    StructureChanged,
+}
+
+impl UiEventKind {
+    /// Every event kind
+    pub const ALL: [UiEventKind; 4] = [
+        UiEventKind::FocusChanged,
+        UiEventKind::WindowOpened,
+        UiEventKind::WindowClosed,
+        UiEventKind::StructureChanged,
+    ];
+
+    /// Parse a kind from its name ("focus_changed", "window_opened",
+    /// "window_closed", "structure_changed"); dashes are accepted too
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace('-', "_").as_str() {
+            "focus_changed" | "focus" => Some(UiEventKind::FocusChanged),
+            "window_opened" => Some(UiEventKind::WindowOpened),
+            "window_closed" => Some(UiEventKind::WindowClosed),
+            "structure_changed" | "structure" => Some(UiEventKind::StructureChanged),
+            _ => None,
+        }
+    }
+
+    /// Name used in serialized events
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UiEventKind::FocusChanged => "focus_changed",
+            UiEventKind::WindowOpened => "window_opened",
+            UiEventKind::WindowClosed => "window_closed",
+            UiEventKind::StructureChanged => "structure_changed",
+        }
+    }
+}
+
+impl fmt::Display for UiEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A UIAutomation event and the element that raised it
+///
+/// Element properties are missing when the element is already gone, which
+/// is common for `window_closed`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UiEvent {
+    /// Event kind
+    pub kind: UiEventKind,
+    /// Element role (control type)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Element name, if not empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Automation ID, if not empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub automation_id: Option<String>,
+    /// ID of the process owning the element
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_id: Option<u32>,
+    /// Structure change type (e.g. "ChildAdded") for `structure_changed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<String>,
+}
+
+/// Callback receiving subscribed events
+///
+/// Called on UIAutomation threads; it should return quickly.
+pub type UiEventCallback = dyn Fn(UiEvent) + Send + Sync + 'static;
+
+/// Active UIAutomation event subscription
+///
+/// Created with [`AutomationEngine::subscribe_events`]. Event handlers are
+/// removed on [`UiEventSubscription::stop`] or when dropped.
+///
+/// [`AutomationEngine::subscribe_events`]: crate::AutomationEngine::subscribe_events
+pub struct UiEventSubscription {
+    kinds: Vec<UiEventKind>,
+    #[cfg(target_os = "windows")]
+    stop_tx: Option<std::sync::mpsc::Sender<()>>,
+    #[cfg(target_os = "windows")]
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl UiEventSubscription {
+    /// Register handlers for `kinds` and deliver events to `callback`
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if `kinds` is empty, or a platform error if
+    /// a handler cannot be registered.
+    pub fn start(
+        kinds: &[UiEventKind],
+        callback: Box<UiEventCallback>,
+    ) -> Result<Self, AutomationError> {
+        let mut kinds = kinds.to_vec();
+        kinds.sort_by_key(|kind| kind.as_str());
+        kinds.dedup();
+        if kinds.is_empty() {
+            return Err(AutomationError::InvalidArgument(
+                "At least one event kind is required".to_string(),
+            ));
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let (stop_tx, stop_rx) = std::sync::mpsc::channel();
+            let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+            let thread_kinds = kinds.clone();
+            let thread = std::thread::spawn(move || {
+                handlers::run(&thread_kinds, callback.into(), ready_tx, stop_rx)
+            });
+
+            match ready_rx.recv() {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    let _ = thread.join();
+                    return Err(e);
+                }
+                Err(_) => {
+                    let _ = thread.join();
+                    return Err(AutomationError::Internal(
+                        "UI event thread exited during startup".to_string(),
+                    ));
+                }
+            }
+
+            tracing::info!("Subscribed to UI events: {:?}", kinds);
+
+            Ok(Self {
+                kinds,
+                stop_tx: Some(stop_tx),
+                thread: Some(thread),
+            })
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = callback;
+            Err(AutomationError::UnsupportedOperation(
+                "UI event subscriptions are only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    /// Subscribed event kinds
+    pub fn kinds(&self) -> &[UiEventKind] {
+        &self.kinds
+    }
+
+    /// Remove the event handlers
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        #[cfg(target_os = "windows")]
+        {
+            // Dropping the sender unblocks the event thread
+            self.stop_tx.take();
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+                tracing::info!("Unsubscribed from UI events: {:?}", self.kinds);
+            }
+        }
+    }
+}
+
+impl Drop for UiEventSubscription {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod handlers {
+    use super::*;
+    use std::sync::mpsc::{Receiver, Sender};
+    use std::sync::Arc;
+    use uiautomation::events::{
+        CustomEventHandlerFn, CustomFocusChangedEventHandlerFn,
+        CustomStructureChangedEventHandlerFn, UIEventHandler, UIFocusChangedEventHandler,
+        UIStructureChangeEventHandler,
+    };
+    use uiautomation::types::{TreeScope, UIEventType};
+    use uiautomation::{UIAutomation, UIElement};
+
+    /// Register the handlers, report readiness, then block until stopped
+    pub(super) fn run(
+        kinds: &[UiEventKind],
+        callback: Arc<UiEventCallback>,
+        ready: Sender<Result<(), AutomationError>>,
+        stop: Receiver<()>,
+    ) {
+        let automation = match register(kinds, callback) {
+            Ok(automation) => automation,
+            Err(e) => {
+                let _ = ready.send(Err(e));
+                return;
+            }
+        };
+        let _ = ready.send(Ok(()));
+
+        // Returns once the subscription drops its sender
+        let _ = stop.recv();
+
+        if let Err(e) = automation.remove_all_event_handlers() {
+            tracing::warn!("Failed to remove UI event handlers: {}", e);
+        }
+    }
+
+    fn register(
+        kinds: &[UiEventKind],
+        callback: Arc<UiEventCallback>,
+    ) -> Result<UIAutomation, AutomationError> {
+        let automation = UIAutomation::new().map_err(AutomationError::platform)?;
+        let root = automation
+            .get_root_element()
+            .map_err(AutomationError::platform)?;
+
+        for &kind in kinds {
+            let callback = Arc::clone(&callback);
+            let result = match kind {
+                UiEventKind::FocusChanged => {
+                    let handler: Box<CustomFocusChangedEventHandlerFn> = Box::new(move |sender| {
+                        callback(event_from(kind, sender, None));
+                        Ok(())
+                    });
+                    automation.add_focus_changed_event_handler(
+                        None,
+                        &UIFocusChangedEventHandler::from(handler),
+                    )
+                }
+                UiEventKind::WindowOpened | UiEventKind::WindowClosed => {
+                    let event_type = if kind == UiEventKind::WindowOpened {
+                        UIEventType::Window_WindowOpened
+                    } else {
+                        UIEventType::Window_WindowClosed
+                    };
+                    let handler: Box<CustomEventHandlerFn> = Box::new(move |sender, _| {
+                        callback(event_from(kind, sender, None));
+                        Ok(())
+                    });
+                    automation.add_automation_event_handler(
+                        event_type,
+                        &root,
+                        TreeScope::Subtree,
+                        None,
+                        &UIEventHandler::from(handler),
+                    )
+                }
+                UiEventKind::StructureChanged => {
+                    let handler: Box<CustomStructureChangedEventHandlerFn> =
+                        Box::new(move |sender, change, _| {
+                            callback(event_from(kind, sender, Some(format!("{:?}", change))));
+                            Ok(())
+                        });
+                    automation.add_structure_changed_event_handler(
+                        &root,
+                        TreeScope::Subtree,
+                        None,
+                        &UIStructureChangeEventHandler::from(handler),
+                    )
+                }
+            };
+
+            if let Err(e) = result {
+                let _ = automation.remove_all_event_handlers();
+                return Err(AutomationError::platform(format!(
+                    "Failed to subscribe to {} events: {}",
+                    kind, e
+                )));
+            }
+        }
+
+        Ok(automation)
+    }
+
+    fn event_from(kind: UiEventKind, sender: &UIElement, change: Option<String>) -> UiEvent {
+        UiEvent {
+            kind,
+            role: sender
+                .get_control_type()
+                .ok()
+                .map(|control_type| format!("{:?}", control_type)),
+            name: sender.get_name().ok().filter(|name| !name.is_empty()),
+            automation_id: sender.get_automation_id().ok().filter(|id| !id.is_empty()),
+            process_id: sender.get_process_id().ok().map(|pid| pid as u32),
+            change,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_from_name() {
+        assert_eq!(
+            UiEventKind::from_name("focus_changed"),
+            Some(UiEventKind::FocusChanged)
+        );
+        assert_eq!(
+            UiEventKind::from_name(" Window-Opened "),
+            Some(UiEventKind::WindowOpened)
+        );
+        assert_eq!(
+            UiEventKind::from_name("structure"),
+            Some(UiEventKind::StructureChanged)
+        );
+        assert_eq!(UiEventKind::from_name("mouse_moved"), None);
+
+        for kind in UiEventKind::ALL {
+            assert_eq!(UiEventKind::from_name(kind.as_str()), Some(kind));
+        }
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let event = UiEvent {
+            kind: UiEventKind::WindowOpened,
+            role: Some("Window".to_string()),
+            name: Some("Save As".to_string()),
+            automation_id: None,
+            process_id: Some(4242),
+            change: None,
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["kind"], "window_opened");
+        assert_eq!(json["name"], "Save As");
+        assert!(json.get("automation_id").is_none());
+    }
+
+    #[test]
+    fn test_start_requires_kinds() {
+        let result = UiEventSubscription::start(&[], Box::new(|_| {}));
+        assert!(matches!(result, Err(AutomationError::InvalidArgument(_))));
+    }
+}
@@ -0,0 +1,249 @@
+//! Highlight overlay for debugging
+//!
+//! Draws a temporary colored frame around a screen rectangle in a topmost,
+//! click-through layered window, to show which element a selector resolved
+//! to or where a click will land.
+
+use crate::errors::AutomationError;
+use std::time::Duration;
+
+/// Frame color used when none is given (red)
+pub const DEFAULT_HIGHLIGHT_COLOR: (u8, u8, u8) = (255, 0, 0);
+/// Width of the highlight frame in pixels
+const FRAME_WIDTH: i32 = 3;
+
+/// Parse a `#RRGGBB` (or `RRGGBB`) color
+pub fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Frame a screen rectangle for `duration`
+///
+/// Coordinates are physical pixels. Returns once the overlay is shown; it
+/// closes by itself on a background thread.
+pub fn highlight_rect(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    color: (u8, u8, u8),
+    duration: Duration,
+) -> Result<(), AutomationError> {
+    if width <= 0 || height <= 0 {
+        return Err(AutomationError::InvalidArgument(format!(
+            "Cannot highlight an empty rectangle ({}x{})",
+            width, height
+        )));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            overlay::run(
+                (x - FRAME_WIDTH, y - FRAME_WIDTH),
+                (width + 2 * FRAME_WIDTH, height + 2 * FRAME_WIDTH),
+                color,
+                duration,
+                ready_tx,
+            )
+        });
+
+        ready_rx.recv().map_err(|_| {
+            AutomationError::Internal("Highlight thread exited during startup".to_string())
+        })?
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (x, y, color, duration, FRAME_WIDTH);
+        Err(AutomationError::UnsupportedOperation(
+            "Highlighting is only supported on Windows".to_string(),
+        ))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod overlay {
+    use super::*;
+    use std::sync::mpsc::Sender;
+    use windows::core::w;
+    use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, RECT, WPARAM};
+    use windows::Win32::Graphics::Gdi::{
+        BeginPaint, CreateSolidBrush, DeleteObject, EndPaint, FillRect, PAINTSTRUCT,
+    };
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetClientRect,
+        GetMessageW, GetWindowLongPtrW, PostQuitMessage, RegisterClassW,
+        SetLayeredWindowAttributes, SetTimer, SetWindowLongPtrW, ShowWindow, TranslateMessage,
+        GWLP_USERDATA, LWA_COLORKEY, MSG, SW_SHOWNOACTIVATE, WM_DESTROY, WM_PAINT, WM_TIMER,
+        WNDCLASSW, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST,
+        WS_EX_TRANSPARENT, WS_POPUP,
+    };
+
+    /// Transparent interior color; frames never use it
+    const KEY_COLOR: (u8, u8, u8) = (255, 0, 255);
+
+    fn colorref((r, g, b): (u8, u8, u8)) -> COLORREF {
+        COLORREF(r as u32 | (g as u32) << 8 | (b as u32) << 16)
+    }
+
+    /// Create the overlay, report the outcome, then pump messages until it closes
+    pub(super) fn run(
+        (x, y): (i32, i32),
+        (width, height): (i32, i32),
+        color: (u8, u8, u8),
+        duration: Duration,
+        ready: Sender<Result<(), AutomationError>>,
+    ) {
+        let color = if color == KEY_COLOR {
+            (254, 0, 255)
+        } else {
+            color
+        };
+
+        let hwnd = match create_window(x, y, width, height, color) {
+            Ok(hwnd) => hwnd,
+            Err(e) => {
+                let _ = ready.send(Err(e));
+                return;
+            }
+        };
+        let _ = ready.send(Ok(()));
+
+        unsafe {
+            let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+            SetTimer(hwnd, 1, duration.as_millis().max(1) as u32, None);
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+    }
+
+    fn create_window(
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        color: (u8, u8, u8),
+    ) -> Result<HWND, AutomationError> {
+        unsafe {
+            let instance = GetModuleHandleW(None).map_err(AutomationError::platform)?;
+            let class_name = w!("ScreenSearchHighlight");
+
+            // Fails harmlessly when an earlier highlight registered the class
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                hInstance: instance.into(),
+                lpszClassName: class_name,
+                ..Default::default()
+            };
+            RegisterClassW(&class);
+
+            let hwnd = CreateWindowExW(
+                WS_EX_LAYERED
+                    | WS_EX_TOPMOST
+                    | WS_EX_TRANSPARENT
+                    | WS_EX_TOOLWINDOW
+                    | WS_EX_NOACTIVATE,
+                class_name,
+                w!(""),
+                WS_POPUP,
+                x,
+                y,
+                width,
+                height,
+                None,
+                None,
+                instance,
+                None,
+            );
+            if hwnd.0 == 0 {
+                return Err(AutomationError::platform(
+                    "Failed to create highlight window",
+                ));
+            }
+
+            SetWindowLongPtrW(hwnd, GWLP_USERDATA, colorref(color).0 as isize);
+            if let Err(e) = SetLayeredWindowAttributes(hwnd, colorref(KEY_COLOR), 0, LWA_COLORKEY) {
+                let _ = DestroyWindow(hwnd);
+                return Err(AutomationError::platform(e));
+            }
+
+            Ok(hwnd)
+        }
+    }
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        match message {
+            WM_PAINT => {
+                let mut paint = PAINTSTRUCT::default();
+                let hdc = BeginPaint(hwnd, &mut paint);
+
+                let mut rect = RECT::default();
+                let _ = GetClientRect(hwnd, &mut rect);
+
+                let frame =
+                    CreateSolidBrush(COLORREF(GetWindowLongPtrW(hwnd, GWLP_USERDATA) as u32));
+                FillRect(hdc, &rect, frame);
+                let interior = RECT {
+                    left: rect.left + FRAME_WIDTH,
+                    top: rect.top + FRAME_WIDTH,
+                    right: rect.right - FRAME_WIDTH,
+                    bottom: rect.bottom - FRAME_WIDTH,
+                };
+                let key = CreateSolidBrush(colorref(KEY_COLOR));
+                FillRect(hdc, &interior, key);
+
+                let _ = DeleteObject(frame);
+                let _ = DeleteObject(key);
+                let _ = EndPaint(hwnd, &paint);
+                LRESULT(0)
+            }
+            WM_TIMER => {
+                let _ = DestroyWindow(hwnd);
+                LRESULT(0)
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, message, wparam, lparam),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#FF8800"), Some((255, 136, 0)));
+        assert_eq!(parse_color("00ff7f"), Some((0, 255, 127)));
+        assert_eq!(parse_color("#F80"), None);
+        assert_eq!(parse_color("#GG0000"), None);
+        assert_eq!(parse_color("red"), None);
+    }
+
+    #[test]
+    fn test_empty_rect_is_rejected() {
+        let result = highlight_rect(0, 0, 0, 10, DEFAULT_HIGHLIGHT_COLOR, Duration::ZERO);
+        assert!(matches!(result, Err(AutomationError::InvalidArgument(_))));
+    }
+}
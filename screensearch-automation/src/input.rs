@@ -1,7 +1,14 @@
 //! Input simulation for mouse and keyboard
 
+#[cfg(not(target_os = "linux"))]
+use crate::dpi;
 use crate::errors::AutomationError;
+#[cfg(target_os = "linux")]
+use crate::linux::xdo;
+use std::time::Duration;
+#[cfg(not(target_os = "linux"))]
 use uiautomation::inputs::{Keyboard, Mouse};
+#[cfg(not(target_os = "linux"))]
 use uiautomation::types::Point;
 
 /// Mouse button types
@@ -96,9 +103,12 @@ impl KeyCode {
 
 /// Input simulator for direct mouse and keyboard control
 ///
-/// Provides low-level input simulation capabilities independent of UI elements
+/// Provides low-level input simulation capabilities independent of UI elements.
+/// On Linux input is synthesized with `xdotool`.
 pub struct InputSimulator {
+    #[cfg(not(target_os = "linux"))]
     mouse: Mouse,
+    #[cfg(not(target_os = "linux"))]
     keyboard: Keyboard,
 }
 
@@ -106,17 +116,22 @@ impl InputSimulator {
     /// Create a new input simulator
     pub fn new() -> Self {
         Self {
+            #[cfg(not(target_os = "linux"))]
             mouse: Mouse::default(),
+            #[cfg(not(target_os = "linux"))]
             keyboard: Keyboard::default(),
         }
     }
 
     /// Click at specific screen coordinates
     ///
+    /// Coordinates are physical pixels, as reported by element bounds; see
+    /// [`crate::dpi`] to convert logical ones.
+    ///
     /// # Example
     ///
     /// ```no_run
-    /// # use screen_automation::{AutomationEngine, MouseButton};
+    /// # use screensearch_automation::{AutomationEngine, MouseButton};
     /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let engine = AutomationEngine::new()?;
     /// engine.input().click_at(100, 200, MouseButton::Left)?;
@@ -124,46 +139,194 @@ impl InputSimulator {
     /// # }
     /// ```
     pub fn click_at(&self, x: i32, y: i32, button: MouseButton) -> Result<(), AutomationError> {
-        let point = Point::new(x, y);
-
-        match button {
-            MouseButton::Left => self.mouse.click(point),
-            MouseButton::Right => self.mouse.right_click(point),
-            MouseButton::Middle => {
-                return Err(AutomationError::UnsupportedOperation(
-                    "Middle mouse button not supported by uiautomation crate".to_string(),
-                ))
+        #[cfg(target_os = "linux")]
+        {
+            xdo::click(x, y, button, 1)
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let point = input_point(x, y);
+
+            match button {
+                MouseButton::Left => self.mouse.click(point),
+                MouseButton::Right => self.mouse.right_click(point),
+                MouseButton::Middle => {
+                    return Err(AutomationError::UnsupportedOperation(
+                        "Middle mouse button not supported by uiautomation crate".to_string(),
+                    ))
+                }
             }
+            .map_err(AutomationError::platform)
         }
-        .map_err(AutomationError::platform)
     }
 
     /// Double-click at specific screen coordinates
     pub fn double_click_at(&self, x: i32, y: i32) -> Result<(), AutomationError> {
-        let point = Point::new(x, y);
-        self.mouse
-            .double_click(point)
-            .map_err(AutomationError::platform)
+        #[cfg(target_os = "linux")]
+        {
+            xdo::click(x, y, MouseButton::Left, 2)
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let point = input_point(x, y);
+            self.mouse
+                .double_click(point)
+                .map_err(AutomationError::platform)
+        }
+    }
+
+    /// Move the mouse to specific coordinates over `duration`
+    ///
+    /// A zero duration jumps straight to the target. Otherwise the cursor
+    /// glides from its current position along an eased path, one move about
+    /// every 10ms, since some applications ignore a cursor that teleports.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use screensearch_automation::AutomationEngine;
+    /// # use std::time::Duration;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let engine = AutomationEngine::new()?;
+    /// engine.input().move_to(640, 360, Duration::from_millis(250))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn move_to(&self, x: i32, y: i32, duration: Duration) -> Result<(), AutomationError> {
+        if duration.is_zero() {
+            #[cfg(target_os = "linux")]
+            return xdo::move_along(&[(x, y)], Duration::ZERO);
+
+            #[cfg(not(target_os = "linux"))]
+            {
+                let point = input_point(x, y);
+                return self.mouse.move_to(point).map_err(AutomationError::platform);
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let from = xdo::cursor_pos()?;
+            let steps = (duration.as_millis() / MOVE_STEP_INTERVAL.as_millis())
+                .clamp(1, MAX_MOVE_STEPS as u128) as u32;
+            xdo::move_along(&drag_path(from, (x, y), steps), duration / steps)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let from = cursor_pos()?;
+            let steps = (duration.as_millis() / MOVE_STEP_INTERVAL.as_millis())
+                .clamp(1, MAX_MOVE_STEPS as u128) as u32;
+            let step_delay = duration / steps;
+
+            drag_path(from, (x, y), steps)
+                .into_iter()
+                .try_for_each(|point| {
+                    std::thread::sleep(step_delay);
+                    set_cursor_pos(point)
+                })
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            let _ = (x, y);
+            Err(AutomationError::UnsupportedOperation(
+                "Smooth mouse movement is only supported on Windows and Linux".to_string(),
+            ))
+        }
     }
 
-    /// Move mouse to specific coordinates
-    pub fn move_to(&self, x: i32, y: i32) -> Result<(), AutomationError> {
-        let point = Point::new(x, y);
-        self.mouse.move_to(point).map_err(AutomationError::platform)
+    /// Drag from one point to another with a mouse button held down
+    ///
+    /// The cursor moves through `steps` eased intermediate positions, waiting
+    /// `step_delay` between each, so applications see a real drag gesture
+    /// rather than a jump. The button is always released, even if a move fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use screensearch_automation::{AutomationEngine, MouseButton};
+    /// # use std::time::Duration;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let engine = AutomationEngine::new()?;
+    /// engine
+    ///     .input()
+    ///     .drag((100, 200), (400, 200), MouseButton::Left, 20, Duration::from_millis(10))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn drag(
+        &self,
+        from: (i32, i32),
+        to: (i32, i32),
+        button: MouseButton,
+        steps: u32,
+        step_delay: Duration,
+    ) -> Result<(), AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            set_cursor_pos(from)?;
+            std::thread::sleep(step_delay);
+            send_mouse_button(button, true)?;
+
+            let moved = drag_path(from, to, steps)
+                .into_iter()
+                .try_for_each(|point| {
+                    std::thread::sleep(step_delay);
+                    set_cursor_pos(point)
+                });
+
+            std::thread::sleep(step_delay);
+            let released = send_mouse_button(button, false);
+            moved.and(released)
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            xdo::drag(from, &drag_path(from, to, steps), button, step_delay)
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            let _ = (from, to, button, steps, step_delay);
+            Err(AutomationError::UnsupportedOperation(
+                "Drag is only supported on Windows and Linux".to_string(),
+            ))
+        }
     }
 
     /// Type text using keyboard
     ///
-    /// This sends text character by character
+    /// On Windows the text is injected as raw Unicode key events, so SendKeys
+    /// metacharacters (`{`, `^`, `%`, `+`, `~`) and characters missing from
+    /// the active keyboard layout are typed literally. Use [`send_keys`] for
+    /// shortcuts and named keys.
+    ///
+    /// [`send_keys`]: InputSimulator::send_keys
     pub fn type_text(&self, text: &str) -> Result<(), AutomationError> {
-        self.keyboard
-            .send_text(text)
-            .map_err(AutomationError::platform)
+        #[cfg(target_os = "windows")]
+        {
+            send_unicode_text(text)
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            xdo::type_text(text)
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            self.keyboard
+                .send_text(text)
+                .map_err(AutomationError::platform)
+        }
     }
 
     /// Send key combination
     ///
-    /// Uses Windows SendKeys notation:
+    /// Uses Windows SendKeys notation (translated to X keysyms on Linux):
     /// - `^` = Ctrl
     /// - `%` = Alt
     /// - `+` = Shift
@@ -172,7 +335,7 @@ impl InputSimulator {
     /// # Example
     ///
     /// ```no_run
-    /// # use screen_automation::AutomationEngine;
+    /// # use screensearch_automation::AutomationEngine;
     /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let engine = AutomationEngine::new()?;
     ///
@@ -188,9 +351,17 @@ impl InputSimulator {
     /// # }
     /// ```
     pub fn send_keys(&self, keys: &str) -> Result<(), AutomationError> {
-        self.keyboard
-            .send_keys(keys)
-            .map_err(AutomationError::platform)
+        #[cfg(target_os = "linux")]
+        {
+            xdo::send_keys(keys)
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            self.keyboard
+                .send_keys(keys)
+                .map_err(AutomationError::platform)
+        }
     }
 
     /// Press a single key
@@ -198,9 +369,7 @@ impl InputSimulator {
     /// Common keys: "ENTER", "ESC", "TAB", "BACKSPACE", "DELETE", "F1"-"F12", etc.
     pub fn press_key(&self, key: &str) -> Result<(), AutomationError> {
         let key_str = format!("{{{}}}", key);
-        self.keyboard
-            .send_keys(&key_str)
-            .map_err(AutomationError::platform)
+        self.send_keys(&key_str)
     }
 
     /// Press Enter key
@@ -294,6 +463,158 @@ impl InputSimulator {
     }
 }
 
+/// Target interval between cursor moves of a smooth movement
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+const MOVE_STEP_INTERVAL: Duration = Duration::from_millis(10);
+/// Maximum number of cursor moves of a smooth movement
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+const MAX_MOVE_STEPS: u32 = 1000;
+
+/// Compute the eased intermediate cursor positions for a drag or smooth move
+///
+/// Returns `steps` points (at least one) ending exactly at `to`; `from` itself
+/// is not included. Uses smoothstep easing so the gesture accelerates and
+/// decelerates like a human drag.
+pub(crate) fn drag_path(from: (i32, i32), to: (i32, i32), steps: u32) -> Vec<(i32, i32)> {
+    let steps = steps.max(1);
+    let (dx, dy) = ((to.0 - from.0) as f64, (to.1 - from.1) as f64);
+
+    (1..=steps)
+        .map(|i| {
+            let t = i as f64 / steps as f64;
+            let eased = t * t * (3.0 - 2.0 * t);
+            (
+                from.0 + (dx * eased).round() as i32,
+                from.1 + (dy * eased).round() as i32,
+            )
+        })
+        .collect()
+}
+
+/// Cursor API point for physical screen coordinates
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn input_point(x: i32, y: i32) -> Point {
+    let (x, y) = dpi::physical_to_input(x, y);
+    Point::new(x, y)
+}
+
+#[cfg(target_os = "windows")]
+fn set_cursor_pos((x, y): (i32, i32)) -> Result<(), AutomationError> {
+    use windows::Win32::UI::WindowsAndMessaging::SetCursorPos;
+
+    let (x, y) = dpi::physical_to_input(x, y);
+    unsafe { SetCursorPos(x, y) }.map_err(AutomationError::platform)
+}
+
+#[cfg(target_os = "windows")]
+fn cursor_pos() -> Result<(i32, i32), AutomationError> {
+    use windows::Win32::Foundation::POINT;
+    use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+    let mut point = POINT::default();
+    unsafe { GetCursorPos(&mut point) }.map_err(AutomationError::platform)?;
+    Ok(dpi::input_to_physical(point.x, point.y))
+}
+
+/// UTF-16 code units to inject for `text`
+///
+/// Line breaks are normalized to a single carriage return, which is what the
+/// Enter key produces; a lone `\n` is ignored by many edit controls.
+#[cfg(any(target_os = "windows", test))]
+fn unicode_units(text: &str) -> Vec<u16> {
+    text.replace("\r\n", "\r")
+        .replace('\n', "\r")
+        .encode_utf16()
+        .collect()
+}
+
+/// Type text as `KEYEVENTF_UNICODE` key events, independent of keyboard layout
+#[cfg(target_os = "windows")]
+pub(crate) fn send_unicode_text(text: &str) -> Result<(), AutomationError> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
+        KEYEVENTF_UNICODE, VIRTUAL_KEY,
+    };
+
+    let key_event = |unit: u16, flags: KEYBD_EVENT_FLAGS| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(0),
+                wScan: unit,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+
+    let inputs: Vec<INPUT> = unicode_units(text)
+        .into_iter()
+        .flat_map(|unit| {
+            [
+                key_event(unit, KEYEVENTF_UNICODE),
+                key_event(unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP),
+            ]
+        })
+        .collect();
+    if inputs.is_empty() {
+        return Ok(());
+    }
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize == inputs.len() {
+        Ok(())
+    } else {
+        Err(AutomationError::platform(format!(
+            "SendInput injected {} of {} key events",
+            sent,
+            inputs.len()
+        )))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn send_mouse_button(button: MouseButton, down: bool) -> Result<(), AutomationError> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_MOUSE, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
+        MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP,
+        MOUSEINPUT,
+    };
+
+    let flags = match (button, down) {
+        (MouseButton::Left, true) => MOUSEEVENTF_LEFTDOWN,
+        (MouseButton::Left, false) => MOUSEEVENTF_LEFTUP,
+        (MouseButton::Right, true) => MOUSEEVENTF_RIGHTDOWN,
+        (MouseButton::Right, false) => MOUSEEVENTF_RIGHTUP,
+        (MouseButton::Middle, true) => MOUSEEVENTF_MIDDLEDOWN,
+        (MouseButton::Middle, false) => MOUSEEVENTF_MIDDLEUP,
+    };
+
+    let input = INPUT {
+        r#type: INPUT_MOUSE,
+        Anonymous: INPUT_0 {
+            mi: MOUSEINPUT {
+                dx: 0,
+                dy: 0,
+                mouseData: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+
+    let sent = unsafe { SendInput(&[input], std::mem::size_of::<INPUT>() as i32) };
+    if sent == 1 {
+        Ok(())
+    } else {
+        Err(AutomationError::platform(
+            "SendInput failed for mouse button",
+        ))
+    }
+}
+
 impl Default for InputSimulator {
     fn default() -> Self {
         Self::new()
@@ -307,8 +628,10 @@ mod tests {
     #[test]
     fn test_input_simulator_creation() {
         let simulator = InputSimulator::new();
-        // Just verify it can be created
+        // Just verify it can be created; the Linux simulator holds no state
+        #[cfg(not(target_os = "linux"))]
         assert!(std::mem::size_of_val(&simulator) > 0);
+        let _ = simulator;
     }
 
     #[test]
@@ -317,6 +640,50 @@ mod tests {
         assert_ne!(MouseButton::Left, MouseButton::Right);
     }
 
+    #[test]
+    fn test_drag_path_ends_at_target() {
+        let path = drag_path((100, 100), (300, 50), 10);
+        assert_eq!(path.len(), 10);
+        assert_eq!(path.last(), Some(&(300, 50)));
+
+        // Eased movement: the first step is shorter than the middle one
+        let first = path[0].0 - 100;
+        let middle = path[5].0 - path[4].0;
+        assert!(first < middle);
+    }
+
+    #[test]
+    fn test_drag_path_zero_steps() {
+        assert_eq!(drag_path((0, 0), (10, 10), 0), vec![(10, 10)]);
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    #[test]
+    fn test_smooth_move_unsupported() {
+        let simulator = InputSimulator::new();
+        let result = simulator.move_to(10, 10, Duration::from_millis(50));
+        assert!(matches!(
+            result,
+            Err(AutomationError::UnsupportedOperation(_))
+        ));
+    }
+
+    #[test]
+    fn test_unicode_units() {
+        assert_eq!(
+            unicode_units("{^%+}"),
+            "{^%+}".encode_utf16().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            unicode_units("a\r\nb\nc"),
+            "a\rb\rc".encode_utf16().collect::<Vec<_>>()
+        );
+
+        // Characters outside the BMP become surrogate pairs
+        assert_eq!(unicode_units("日本"), vec![0x65E5, 0x672C]);
+        assert_eq!(unicode_units("😀"), vec![0xD83D, 0xDE00]);
+    }
+
     #[test]
     fn test_key_modifier_types() {
         assert_eq!(KeyModifier::Ctrl, KeyModifier::Ctrl);
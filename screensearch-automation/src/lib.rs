@@ -3,18 +3,24 @@
 //! Provides a comprehensive Windows UI automation interface using the UIAutomation API.
 //! Inspired by Playwright's web automation model for desktop applications.
 //!
+//! On Linux the same interface is backed by the AT-SPI accessibility bus, with
+//! input simulated through `xdotool` (X11 and XWayland windows).
+//!
 //! # Architecture
 //!
 //! - `AutomationEngine`: Core engine wrapping Windows UIAutomation API
 //! - `Selector`: Playwright-inspired selector system for locating elements
 //! - `UIElement`: Safe wrapper around Windows UI elements
 //! - `Input`: Low-level mouse and keyboard simulation
-//! - `WindowManager`: Window enumeration and management
+//! - `WindowManager`: Window enumeration and management, including virtual desktops
+//! - `Clipboard`: Clipboard text access
+//! - `MacroRecorder`: Records user input into replayable macros
+//! - `UiEventSubscription`: Delivers UIAutomation events (focus, windows, structure)
 //!
 //! # Example
 //!
 //! ```no_run
-//! use screen_automation::{AutomationEngine, Selector};
+//! use screensearch_automation::{AutomationEngine, Selector};
 //!
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 //! let engine = AutomationEngine::new()?;
@@ -30,18 +36,37 @@
 //! # }
 //! ```
 
+mod clipboard;
+pub mod dpi;
+#[cfg(not(target_os = "linux"))]
 mod element;
 mod engine;
 mod errors;
+mod events;
+mod highlight;
 mod input;
+#[cfg(target_os = "linux")]
+mod linux;
+mod macros;
 mod selector;
+mod types;
+mod virtual_desktop;
 mod window;
 
-pub use element::{ClickResult, UIElement, UIElementAttributes};
-pub use engine::AutomationEngine;
+#[cfg(target_os = "linux")]
+use linux::element;
+
+pub use clipboard::Clipboard;
+pub use element::UIElement;
+pub use engine::{AutomationEngine, WaitCondition};
 pub use errors::AutomationError;
+pub use events::{UiEvent, UiEventCallback, UiEventKind, UiEventSubscription};
+pub use highlight::{highlight_rect, parse_color, DEFAULT_HIGHLIGHT_COLOR};
 pub use input::{InputSimulator, KeyCode, KeyModifier, MouseButton, ScrollDirection};
-pub use selector::{Selector, SelectorBuilder};
+pub use macros::{MacroEvent, MacroRecorder, RecordedMacro};
+pub use selector::{parse_path, NameMatch, PathStep, Selector, SelectorBuilder};
+pub use types::{ClickResult, ElementNode, SelectOption, TableData, UIElementAttributes};
+pub use virtual_desktop::VirtualDesktop;
 pub use window::{WindowInfo, WindowManager};
 
 /// Result type for automation operations
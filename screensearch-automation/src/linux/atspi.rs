@@ -0,0 +1,541 @@
+//! AT-SPI accessibility bus access
+//!
+//! Calls the `org.a11y.atspi.*` D-Bus interfaces directly through zbus's
+//! blocking connection, since [`UIElement`](crate::UIElement) methods are
+//! synchronous. Objects are addressed by their bus name and object path.
+
+use crate::errors::AutomationError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use zbus::blocking::{Connection, ConnectionBuilder};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Type, Value};
+
+type Result<T> = std::result::Result<T, AutomationError>;
+
+const REGISTRY: &str = "org.a11y.atspi.Registry";
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const NULL_PATH: &str = "/org/a11y/atspi/null";
+const PROPERTIES: &str = "org.freedesktop.DBus.Properties";
+
+pub(crate) const ACCESSIBLE: &str = "org.a11y.atspi.Accessible";
+pub(crate) const ACTION: &str = "org.a11y.atspi.Action";
+pub(crate) const COMPONENT: &str = "org.a11y.atspi.Component";
+pub(crate) const EDITABLE_TEXT: &str = "org.a11y.atspi.EditableText";
+pub(crate) const SELECTION: &str = "org.a11y.atspi.Selection";
+pub(crate) const TABLE: &str = "org.a11y.atspi.Table";
+pub(crate) const TEXT: &str = "org.a11y.atspi.Text";
+pub(crate) const VALUE: &str = "org.a11y.atspi.Value";
+
+/// `ATSPI_RELATION_LABELLED_BY`
+const RELATION_LABELLED_BY: u32 = 2;
+/// `ATSPI_COORD_TYPE_SCREEN`
+const COORD_TYPE_SCREEN: u32 = 0;
+
+/// Lazily connected accessibility bus
+///
+/// Connecting needs a running session bus, which headless processes (and
+/// tests) do not have, so the connection is made on first use and retried
+/// on the next call if it fails.
+pub(crate) struct Bus {
+    connection: Mutex<Option<Connection>>,
+}
+
+impl Bus {
+    pub(crate) fn new() -> Self {
+        Self {
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// Connection to the accessibility bus
+    pub(crate) fn connection(&self) -> Result<Connection> {
+        let mut connection = self.connection.lock().map_err(|_| {
+            AutomationError::Internal("AT-SPI connection lock poisoned".to_string())
+        })?;
+        if let Some(connection) = connection.as_ref() {
+            return Ok(connection.clone());
+        }
+
+        let connected = connect()?;
+        *connection = Some(connected.clone());
+        Ok(connected)
+    }
+}
+
+/// Connect to the accessibility bus
+///
+/// The bus is separate from the session bus, which publishes its address.
+/// Toolkits only export their widget trees once accessibility is enabled, so
+/// that is switched on as well.
+fn connect() -> Result<Connection> {
+    let address = match std::env::var("AT_SPI_BUS_ADDRESS") {
+        Ok(address) if !address.is_empty() => address,
+        _ => {
+            let session = Connection::session().map_err(|e| {
+                AutomationError::platform(format!("Cannot connect to the session bus: {}", e))
+            })?;
+
+            let enable = session.call_method(
+                Some("org.a11y.Bus"),
+                "/org/a11y/bus",
+                Some(PROPERTIES),
+                "Set",
+                &("org.a11y.Status", "IsEnabled", Value::from(true)),
+            );
+            if let Err(e) = enable {
+                tracing::debug!("Could not enable toolkit accessibility: {}", e);
+            }
+
+            session
+                .call_method(
+                    Some("org.a11y.Bus"),
+                    "/org/a11y/bus",
+                    Some("org.a11y.Bus"),
+                    "GetAddress",
+                    &(),
+                )
+                .and_then(|reply| reply.body().deserialize::<String>())
+                .map_err(|e| {
+                    AutomationError::platform(format!(
+                        "AT-SPI bus unavailable (is at-spi2-core running?): {}",
+                        e
+                    ))
+                })?
+        }
+    };
+
+    ConnectionBuilder::address(address.as_str())
+        .and_then(|builder| builder.build())
+        .map_err(|e| AutomationError::platform(format!("Cannot connect to the AT-SPI bus: {}", e)))
+}
+
+/// An object on the accessibility bus
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Accessible {
+    bus_name: String,
+    path: String,
+}
+
+/// Wire form of an object reference, `(so)`
+type ObjectRef = (String, OwnedObjectPath);
+
+impl From<ObjectRef> for Accessible {
+    fn from((bus_name, path): ObjectRef) -> Self {
+        Self {
+            bus_name,
+            path: path.as_str().to_string(),
+        }
+    }
+}
+
+impl Accessible {
+    /// The desktop, whose children are the accessible applications
+    pub(crate) fn root() -> Self {
+        Self {
+            bus_name: REGISTRY.to_string(),
+            path: ROOT_PATH.to_string(),
+        }
+    }
+
+    /// Whether this is the desktop object
+    ///
+    /// Applications use the same path for their own root object, so the
+    /// registry's bus name tells them apart.
+    pub(crate) fn is_root(&self) -> bool {
+        self.bus_name == REGISTRY && self.path == ROOT_PATH
+    }
+
+    /// Whether this reference points at no object
+    pub(crate) fn is_null(&self) -> bool {
+        self.path == NULL_PATH || self.bus_name.is_empty()
+    }
+
+    /// Unique bus name of the owning application
+    #[cfg(test)]
+    pub(crate) fn bus_name(&self) -> &str {
+        &self.bus_name
+    }
+
+    fn call<B, R>(&self, bus: &Connection, interface: &str, method: &str, body: &B) -> Result<R>
+    where
+        B: Serialize + Type,
+        R: DeserializeOwned + Type,
+    {
+        bus.call_method(
+            Some(self.bus_name.as_str()),
+            self.path.as_str(),
+            Some(interface),
+            method,
+            body,
+        )
+        .and_then(|reply| reply.body().deserialize::<R>())
+        .map_err(|e| AutomationError::platform(format!("{}.{}: {}", interface, method, e)))
+    }
+
+    fn property_value(&self, bus: &Connection, interface: &str, name: &str) -> Result<OwnedValue> {
+        self.call(bus, PROPERTIES, "Get", &(interface, name))
+    }
+
+    fn property<T>(&self, bus: &Connection, interface: &str, name: &str) -> Result<T>
+    where
+        T: TryFrom<OwnedValue>,
+        T::Error: std::fmt::Display,
+    {
+        let value = self.property_value(bus, interface, name)?;
+        T::try_from(value).map_err(|e| {
+            AutomationError::platform(format!("{}.{} has unexpected type: {}", interface, name, e))
+        })
+    }
+
+    pub(crate) fn name(&self, bus: &Connection) -> Result<String> {
+        self.property(bus, ACCESSIBLE, "Name")
+    }
+
+    pub(crate) fn description(&self, bus: &Connection) -> Result<String> {
+        self.property(bus, ACCESSIBLE, "Description")
+    }
+
+    /// Toolkit-assigned identifier (AT-SPI 2.34+), empty when unset
+    pub(crate) fn accessible_id(&self, bus: &Connection) -> Result<String> {
+        self.property(bus, ACCESSIBLE, "AccessibleId")
+    }
+
+    /// Role name, e.g. "push button"
+    pub(crate) fn role_name(&self, bus: &Connection) -> Result<String> {
+        self.call(bus, ACCESSIBLE, "GetRoleName", &())
+    }
+
+    pub(crate) fn state(&self, bus: &Connection) -> Result<StateSet> {
+        let words: Vec<u32> = self.call(bus, ACCESSIBLE, "GetState", &())?;
+        Ok(StateSet::from_words(&words))
+    }
+
+    /// Names of the interfaces the object implements, e.g. "org.a11y.atspi.Text"
+    pub(crate) fn interfaces(&self, bus: &Connection) -> Result<Vec<String>> {
+        let names: Vec<String> = self.call(bus, ACCESSIBLE, "GetInterfaces", &())?;
+        Ok(names
+            .into_iter()
+            .map(|name| {
+                if name.contains('.') {
+                    name
+                } else {
+                    format!("org.a11y.atspi.{}", name)
+                }
+            })
+            .collect())
+    }
+
+    /// Toolkit attributes, e.g. `id` for web content
+    pub(crate) fn attributes(&self, bus: &Connection) -> Result<HashMap<String, String>> {
+        self.call(bus, ACCESSIBLE, "GetAttributes", &())
+    }
+
+    pub(crate) fn children(&self, bus: &Connection) -> Result<Vec<Accessible>> {
+        let children: Vec<ObjectRef> = self.call(bus, ACCESSIBLE, "GetChildren", &())?;
+        Ok(children
+            .into_iter()
+            .map(Accessible::from)
+            .filter(|child| !child.is_null())
+            .collect())
+    }
+
+    pub(crate) fn parent(&self, bus: &Connection) -> Result<Option<Accessible>> {
+        let value = self.property_value(bus, ACCESSIBLE, "Parent")?;
+        Ok(object_ref(&value).filter(|parent| !parent.is_null()))
+    }
+
+    /// Name of the first object this one is labelled by
+    pub(crate) fn label(&self, bus: &Connection) -> Result<Option<String>> {
+        let relations: Vec<(u32, Vec<ObjectRef>)> =
+            self.call(bus, ACCESSIBLE, "GetRelationSet", &())?;
+        let label = relations
+            .into_iter()
+            .find(|(relation, _)| *relation == RELATION_LABELLED_BY)
+            .and_then(|(_, targets)| targets.into_iter().next())
+            .map(Accessible::from);
+
+        match label {
+            Some(label) => label.name(bus).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Process ID of the owning application
+    pub(crate) fn process_id(&self, bus: &Connection) -> Result<u32> {
+        bus.call_method(
+            Some("org.freedesktop.DBus"),
+            "/org/freedesktop/DBus",
+            Some("org.freedesktop.DBus"),
+            "GetConnectionUnixProcessID",
+            &(self.bus_name.as_str(),),
+        )
+        .and_then(|reply| reply.body().deserialize::<u32>())
+        .map_err(AutomationError::platform)
+    }
+
+    /// Screen extents (x, y, width, height)
+    pub(crate) fn extents(&self, bus: &Connection) -> Result<(i32, i32, i32, i32)> {
+        self.call(bus, COMPONENT, "GetExtents", &(COORD_TYPE_SCREEN,))
+    }
+
+    pub(crate) fn grab_focus(&self, bus: &Connection) -> Result<bool> {
+        self.call(bus, COMPONENT, "GrabFocus", &())
+    }
+
+    /// Names of the object's actions, in index order
+    pub(crate) fn actions(&self, bus: &Connection) -> Result<Vec<String>> {
+        let count: i32 = self.property(bus, ACTION, "NActions")?;
+        (0..count)
+            .map(|index| self.call(bus, ACTION, "GetName", &(index,)))
+            .collect()
+    }
+
+    pub(crate) fn do_action(&self, bus: &Connection, index: i32) -> Result<bool> {
+        self.call(bus, ACTION, "DoAction", &(index,))
+    }
+
+    /// Full text content
+    pub(crate) fn text(&self, bus: &Connection) -> Result<String> {
+        self.call(bus, TEXT, "GetText", &(0i32, -1i32))
+    }
+
+    pub(crate) fn set_text(&self, bus: &Connection, text: &str) -> Result<bool> {
+        self.call(bus, EDITABLE_TEXT, "SetTextContents", &(text,))
+    }
+
+    pub(crate) fn current_value(&self, bus: &Connection) -> Result<f64> {
+        self.property(bus, VALUE, "CurrentValue")
+    }
+
+    pub(crate) fn set_current_value(&self, bus: &Connection, value: f64) -> Result<()> {
+        self.call(
+            bus,
+            PROPERTIES,
+            "Set",
+            &(VALUE, "CurrentValue", Value::from(value)),
+        )
+    }
+
+    pub(crate) fn select_child(&self, bus: &Connection, index: i32) -> Result<bool> {
+        self.call(bus, SELECTION, "SelectChild", &(index,))
+    }
+
+    pub(crate) fn index_in_parent(&self, bus: &Connection) -> Result<i32> {
+        self.call(bus, ACCESSIBLE, "GetIndexInParent", &())
+    }
+
+    /// Row and column counts of a table
+    pub(crate) fn table_size(&self, bus: &Connection) -> Result<(i32, i32)> {
+        Ok((
+            self.property(bus, TABLE, "NRows")?,
+            self.property(bus, TABLE, "NColumns")?,
+        ))
+    }
+
+    pub(crate) fn table_cell(&self, bus: &Connection, row: i32, column: i32) -> Result<Accessible> {
+        let cell: ObjectRef = self.call(bus, TABLE, "GetAccessibleAt", &(row, column))?;
+        Ok(Accessible::from(cell))
+    }
+
+    pub(crate) fn column_header(
+        &self,
+        bus: &Connection,
+        column: i32,
+    ) -> Result<Option<Accessible>> {
+        let header: ObjectRef = self.call(bus, TABLE, "GetColumnHeader", &(column,))?;
+        Ok(Some(Accessible::from(header)).filter(|header| !header.is_null()))
+    }
+}
+
+/// Read an object reference out of a `(so)` property value
+fn object_ref(value: &Value<'_>) -> Option<Accessible> {
+    let value = match value {
+        Value::Value(inner) => inner.as_ref(),
+        value => value,
+    };
+    let Value::Structure(structure) = value else {
+        return None;
+    };
+
+    match structure.fields() {
+        [Value::Str(bus_name), Value::ObjectPath(path)] => Some(Accessible {
+            bus_name: bus_name.as_str().to_string(),
+            path: path.as_str().to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// AT-SPI object states used by the backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum State {
+    Active = 1,
+    Defunct = 6,
+    Enabled = 8,
+    Focused = 12,
+    ManagesDescendants = 16,
+    Sensitive = 24,
+    Showing = 25,
+    Visible = 30,
+}
+
+/// Set of AT-SPI states, sent as two 32-bit words
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct StateSet(u64);
+
+impl StateSet {
+    pub(crate) fn from_words(words: &[u32]) -> Self {
+        let low = words.first().copied().unwrap_or(0) as u64;
+        let high = words.get(1).copied().unwrap_or(0) as u64;
+        Self(low | high << 32)
+    }
+
+    pub(crate) fn contains(self, state: State) -> bool {
+        self.0 & (1 << state as u64) != 0
+    }
+}
+
+/// Control type name (as on Windows) for an AT-SPI role name
+///
+/// Lets selectors and API responses use the same role names on both
+/// platforms; roles without a counterpart map to "Custom".
+pub(crate) fn control_type_name(role_name: &str) -> &'static str {
+    match role_name {
+        "push button" | "toggle button" | "button" => "Button",
+        "check box" => "CheckBox",
+        "radio button" => "RadioButton",
+        "combo box" => "ComboBox",
+        "entry" | "password text" | "text" | "editbar" => "Edit",
+        "label" | "static" | "paragraph" | "heading" | "caption" => "Text",
+        "frame" | "window" | "dialog" | "alert" | "file chooser" | "color chooser" => "Window",
+        "menu" | "popup menu" => "Menu",
+        "menu bar" => "MenuBar",
+        "menu item" | "check menu item" | "radio menu item" | "tearoff menu item" => "MenuItem",
+        "list" | "list box" => "List",
+        "list item" => "ListItem",
+        "tree" => "Tree",
+        "tree item" => "TreeItem",
+        "tree table" => "DataGrid",
+        "table" => "Table",
+        "table cell" => "DataItem",
+        "table column header" | "column header" | "table row header" | "row header" => "HeaderItem",
+        "header" => "Header",
+        "page tab list" => "Tab",
+        "page tab" => "TabItem",
+        "tool bar" => "ToolBar",
+        "status bar" => "StatusBar",
+        "tool tip" => "ToolTip",
+        "image" | "icon" => "Image",
+        "link" => "Hyperlink",
+        "progress bar" | "level bar" => "ProgressBar",
+        "scroll bar" => "ScrollBar",
+        "slider" => "Slider",
+        "spin button" => "Spinner",
+        "separator" => "Separator",
+        "title bar" => "TitleBar",
+        "document frame"
+        | "document web"
+        | "document text"
+        | "document spreadsheet"
+        | "document presentation"
+        | "document email" => "Document",
+        "grouping" | "section" | "form" | "landmark" | "block quote" | "article" => "Group",
+        "application" | "desktop frame" | "panel" | "filler" | "scroll pane" | "viewport"
+        | "split pane" | "layered pane" | "root pane" | "glass pane" | "internal frame" => "Pane",
+        _ => "Custom",
+    }
+}
+
+/// Control type name for a selector role ("button", "edit", ...)
+///
+/// Mirrors the Windows backend's role mapping.
+pub(crate) fn selector_control_type(role: &str) -> &'static str {
+    match role.to_lowercase().as_str() {
+        "window" | "dialog" => "Window",
+        "button" => "Button",
+        "checkbox" => "CheckBox",
+        "menu" => "Menu",
+        "menuitem" => "MenuItem",
+        "text" => "Text",
+        "edit" | "input" | "textfield" => "Edit",
+        "tree" => "Tree",
+        "treeitem" => "TreeItem",
+        "datagrid" => "DataGrid",
+        "list" => "List",
+        "listitem" => "ListItem",
+        "combobox" => "ComboBox",
+        "tab" => "Tab",
+        "tabitem" => "TabItem",
+        "toolbar" => "ToolBar",
+        "image" => "Image",
+        "hyperlink" => "Hyperlink",
+        "progressbar" => "ProgressBar",
+        "radiobutton" => "RadioButton",
+        "scrollbar" => "ScrollBar",
+        "slider" => "Slider",
+        "spinner" => "Spinner",
+        "statusbar" => "StatusBar",
+        "tooltip" => "ToolTip",
+        "group" => "Group",
+        "document" => "Document",
+        "pane" => "Pane",
+        "header" => "Header",
+        "headeritem" => "HeaderItem",
+        "table" => "Table",
+        "titlebar" => "TitleBar",
+        "separator" => "Separator",
+        _ => "Custom",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::zvariant::ObjectPath;
+
+    #[test]
+    fn test_state_set_words() {
+        let states = StateSet::from_words(&[1 << 8 | 1 << 25 | 1 << 30, 1]);
+        assert!(states.contains(State::Enabled));
+        assert!(states.contains(State::Showing));
+        assert!(states.contains(State::Visible));
+        assert!(!states.contains(State::Focused));
+        assert_eq!(StateSet::from_words(&[]), StateSet::default());
+    }
+
+    #[test]
+    fn test_control_type_names() {
+        assert_eq!(control_type_name("push button"), "Button");
+        assert_eq!(control_type_name("password text"), "Edit");
+        assert_eq!(control_type_name("frame"), "Window");
+        assert_eq!(control_type_name("page tab"), "TabItem");
+        assert_eq!(control_type_name("unknown role"), "Custom");
+    }
+
+    #[test]
+    fn test_selector_roles_match_atspi_roles() {
+        assert_eq!(
+            selector_control_type("Button"),
+            control_type_name("push button")
+        );
+        assert_eq!(selector_control_type("input"), control_type_name("entry"));
+        assert_eq!(selector_control_type("dialog"), control_type_name("dialog"));
+        assert_eq!(
+            selector_control_type("listitem"),
+            control_type_name("list item")
+        );
+    }
+
+    #[test]
+    fn test_object_ref_from_value() {
+        let path = ObjectPath::try_from("/org/a11y/atspi/accessible/7").unwrap();
+        let value = Value::from((":1.42", path));
+
+        let object = object_ref(&value).unwrap();
+        assert_eq!(object.bus_name(), ":1.42");
+        assert!(!object.is_null());
+        assert!(object_ref(&Value::from("not a reference")).is_none());
+    }
+}
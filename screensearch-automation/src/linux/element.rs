@@ -0,0 +1,898 @@
+//! UI element wrapper over the AT-SPI accessibility tree
+//!
+//! Mirrors the Windows `UIElement` API. Roles are reported with the Windows
+//! control type names so selectors work unchanged; interactions use AT-SPI
+//! actions where the toolkit exposes them and fall back to simulated input.
+
+use super::atspi::{self, Accessible, Bus, State};
+use super::xdo;
+use crate::dpi;
+use crate::errors::AutomationError;
+use crate::input::MouseButton;
+use crate::selector::{parse_path, PathStep, Selector};
+use crate::types::{ClickResult, ElementNode, SelectOption, TableData, UIElementAttributes};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use zbus::blocking::Connection;
+
+/// Shared accessibility bus connection
+#[derive(Clone)]
+pub(crate) struct ThreadSafeAutomation(pub Arc<Bus>);
+
+/// Depth searched by name and text selectors, as on Windows
+const NAME_SEARCH_DEPTH: usize = 10;
+/// Upper bound on elements visited by a single search
+const MAX_SEARCH_NODES: usize = 5000;
+/// Action names that activate an element, in order of preference
+const CLICK_ACTIONS: &[&str] = &["click", "press", "activate", "jump", "open"];
+
+/// Safe wrapper around an AT-SPI accessible object
+#[derive(Clone)]
+pub struct UIElement {
+    element: Accessible,
+    automation: ThreadSafeAutomation,
+}
+
+impl fmt::Debug for UIElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UIElement")
+            .field("id", &self.id())
+            .field("role", &self.role())
+            .finish()
+    }
+}
+
+impl UIElement {
+    /// Create a new UIElement from an AT-SPI object reference
+    pub(crate) fn new(element: Accessible, automation: &ThreadSafeAutomation) -> Self {
+        Self {
+            element,
+            automation: automation.clone(),
+        }
+    }
+
+    fn bus(&self) -> Result<Connection, AutomationError> {
+        self.automation.0.connection()
+    }
+
+    /// Whether the element currently has an AT-SPI state
+    pub(crate) fn has_state(&self, state: State) -> bool {
+        self.bus()
+            .and_then(|bus| self.element.state(&bus))
+            .is_ok_and(|states| states.contains(state))
+    }
+
+    /// ID of the process owning this element
+    pub(crate) fn process_id(&self) -> Result<u32, AutomationError> {
+        self.element.process_id(&self.bus()?)
+    }
+
+    /// Get the automation ID of this element
+    ///
+    /// Uses the toolkit's accessible ID, falling back to an `id` attribute
+    /// (set by browsers for web content).
+    pub fn id(&self) -> Option<String> {
+        let bus = self.bus().ok()?;
+        self.element
+            .accessible_id(&bus)
+            .ok()
+            .filter(|id| !id.is_empty())
+            .or_else(|| {
+                self.element
+                    .attributes(&bus)
+                    .ok()
+                    .and_then(|mut attributes| attributes.remove("id"))
+            })
+    }
+
+    /// Get the control type (role) of this element
+    pub fn role(&self) -> String {
+        self.bus()
+            .and_then(|bus| self.element.role_name(&bus))
+            .map(|role| atspi::control_type_name(&role).to_string())
+            .unwrap_or_else(|_| "Unknown".to_string())
+    }
+
+    /// Get the name of this element
+    pub fn name(&self) -> Option<String> {
+        self.bus().and_then(|bus| self.element.name(&bus)).ok()
+    }
+
+    /// Check whether this element still exists in the UI
+    ///
+    /// Objects of closed applications stop answering, and removed widgets
+    /// report the defunct state.
+    pub fn is_alive(&self) -> bool {
+        self.bus()
+            .and_then(|bus| self.element.state(&bus))
+            .is_ok_and(|states| !states.contains(State::Defunct))
+    }
+
+    /// Get comprehensive attributes of this element
+    pub fn attributes(&self) -> UIElementAttributes {
+        let bus = self.bus().ok();
+        let bus = bus.as_ref();
+        let mut properties = HashMap::new();
+
+        let mut insert = |key: &str, value: Option<String>| {
+            properties.insert(key.to_string(), value.map(serde_json::Value::String));
+        };
+        insert("Name", bus.and_then(|bus| self.element.name(bus).ok()));
+        insert(
+            "Description",
+            bus.and_then(|bus| self.element.description(bus).ok()),
+        );
+        insert(
+            "RoleName",
+            bus.and_then(|bus| self.element.role_name(bus).ok()),
+        );
+        insert("AccessibleId", self.id());
+        insert("Value", self.value());
+
+        if let Some(attributes) = bus.and_then(|bus| self.element.attributes(bus).ok()) {
+            for (key, value) in attributes {
+                properties
+                    .entry(key)
+                    .or_insert(Some(serde_json::Value::String(value)));
+            }
+        }
+
+        UIElementAttributes {
+            role: self.role(),
+            label: bus.and_then(|bus| self.element.label(bus).ok().flatten()),
+            value: self.value(),
+            description: bus
+                .and_then(|bus| self.element.description(bus).ok())
+                .filter(|description| !description.is_empty()),
+            properties,
+        }
+    }
+
+    /// Text of an editable element, or the current value of a range
+    fn value(&self) -> Option<String> {
+        let bus = self.bus().ok()?;
+        let interfaces = self.element.interfaces(&bus).ok()?;
+
+        if interfaces.iter().any(|i| i == atspi::EDITABLE_TEXT) {
+            return self.element.text(&bus).ok();
+        }
+        if interfaces.iter().any(|i| i == atspi::VALUE) {
+            return self
+                .element
+                .current_value(&bus)
+                .ok()
+                .map(|value| value.to_string());
+        }
+        None
+    }
+
+    /// Get the bounding rectangle of this element (x, y, width, height)
+    ///
+    /// Coordinates are physical screen pixels.
+    pub fn bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError> {
+        let (x, y, width, height) = self.element.extents(&self.bus()?)?;
+        Ok((x as f64, y as f64, width as f64, height as f64))
+    }
+
+    /// Scale factor (1.0 = 100%) of the monitor showing this element
+    pub fn scale_factor(&self) -> Result<f64, AutomationError> {
+        let (left, top, width, height) = self.bounds()?;
+        Ok(dpi::scale_factor_at(
+            (left + width / 2.0) as i32,
+            (top + height / 2.0) as i32,
+        ))
+    }
+
+    /// Get the bounding rectangle in logical pixels of its monitor
+    ///
+    /// [`bounds`](Self::bounds) is in physical pixels; see [`crate::dpi`].
+    pub fn logical_bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError> {
+        let (left, top, width, height) = self.bounds()?;
+        let scale = dpi::scale_factor_at(left as i32, top as i32);
+        let (x, y) = dpi::physical_to_logical(left as i32, top as i32);
+        Ok((x as f64, y as f64, width / scale, height / scale))
+    }
+
+    /// Frame this element on screen for `duration`
+    ///
+    /// Returns once the frame is shown; see [`crate::highlight_rect`].
+    pub fn highlight(
+        &self,
+        color: (u8, u8, u8),
+        duration: std::time::Duration,
+    ) -> Result<(), AutomationError> {
+        let (left, top, width, height) = self.bounds()?;
+        crate::highlight::highlight_rect(
+            left as i32,
+            top as i32,
+            width as i32,
+            height as i32,
+            color,
+            duration,
+        )
+    }
+
+    /// Center of the bounding rectangle, failing for elements without extents
+    fn center(&self) -> Result<(i32, i32), AutomationError> {
+        let (left, top, width, height) = self.bounds()?;
+        if width <= 0.0 || height <= 0.0 {
+            return Err(AutomationError::InvalidState(
+                "Element has no on-screen area".to_string(),
+            ));
+        }
+        Ok(((left + width / 2.0) as i32, (top + height / 2.0) as i32))
+    }
+
+    /// Get child elements
+    pub fn children(&self) -> Result<Vec<UIElement>, AutomationError> {
+        let children = self.element.children(&self.bus()?)?;
+        Ok(children
+            .into_iter()
+            .map(|child| UIElement::new(child, &self.automation))
+            .collect())
+    }
+
+    /// Get parent element
+    pub fn parent(&self) -> Result<Option<UIElement>, AutomationError> {
+        if self.element.is_root() {
+            return Ok(None);
+        }
+        Ok(self
+            .element
+            .parent(&self.bus()?)
+            .ok()
+            .flatten()
+            .map(|parent| UIElement::new(parent, &self.automation)))
+    }
+
+    /// Snapshot this element and its subtree
+    ///
+    /// `depth` is the number of child levels to include; 0 returns the
+    /// element alone. Children that disappear while the tree is walked are
+    /// skipped.
+    pub fn to_tree(&self, depth: usize) -> Result<ElementNode, AutomationError> {
+        let mut node = self.to_node();
+        if depth == 0 {
+            return Ok(node);
+        }
+
+        for child in self.children().unwrap_or_default() {
+            if let Ok(child_node) = child.to_tree(depth - 1) {
+                node.children.push(child_node);
+            }
+        }
+
+        Ok(node)
+    }
+
+    /// Snapshot this element without its children
+    fn to_node(&self) -> ElementNode {
+        ElementNode {
+            role: self.role(),
+            name: self.name().filter(|name| !name.is_empty()),
+            automation_id: self.id().filter(|id| !id.is_empty()),
+            bounds: self.bounds().ok(),
+            patterns: self.supported_patterns(),
+            is_enabled: self.is_enabled().unwrap_or(false),
+            is_visible: self.is_visible().unwrap_or(false),
+            children: Vec::new(),
+        }
+    }
+
+    /// Names of the control patterns this element supports
+    ///
+    /// Derived from the AT-SPI interfaces and actions, using the Windows
+    /// pattern names.
+    pub fn supported_patterns(&self) -> Vec<String> {
+        let Ok(bus) = self.bus() else {
+            return Vec::new();
+        };
+        let interfaces = self.element.interfaces(&bus).unwrap_or_default();
+        let actions = self.actions(&bus);
+        let has = |interface: &str| interfaces.iter().any(|i| i == interface);
+        let has_action = |names: &[&str]| actions.iter().any(|a| names.contains(&a.as_str()));
+
+        let mut supported = Vec::new();
+        let mut push = |name: &str, condition: bool| {
+            if condition {
+                supported.push(name.to_string());
+            }
+        };
+        push("Invoke", has_action(CLICK_ACTIONS));
+        push("Value", has(atspi::EDITABLE_TEXT));
+        push("Toggle", has_action(&["toggle"]));
+        push(
+            "ExpandCollapse",
+            has_action(&["expand or contract", "expand", "collapse"]),
+        );
+        push("Selection", has(atspi::SELECTION));
+        push("RangeValue", has(atspi::VALUE));
+        push("Table", has(atspi::TABLE));
+        push("Grid", has(atspi::TABLE));
+        push("Text", has(atspi::TEXT));
+
+        supported
+    }
+
+    /// Lowercased action names, empty when the element has no actions
+    fn actions(&self, bus: &Connection) -> Vec<String> {
+        self.element
+            .actions(bus)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|action| action.to_lowercase())
+            .collect()
+    }
+
+    /// Run the first available action among `names`
+    ///
+    /// Returns the name of the action performed, or `None` if the element
+    /// has none of them.
+    fn do_named_action(&self, names: &[&str]) -> Result<Option<String>, AutomationError> {
+        let bus = self.bus()?;
+        let actions = self.actions(&bus);
+
+        for name in names {
+            if let Some(index) = actions.iter().position(|action| action == name) {
+                if self.element.do_action(&bus, index as i32)? {
+                    return Ok(Some(name.to_string()));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Click on this element
+    ///
+    /// Attempts multiple strategies: the element's activation action, then
+    /// a mouse click at the center of its bounds
+    pub fn click(&self) -> Result<ClickResult, AutomationError> {
+        // Try to focus first
+        let _ = self.focus();
+
+        tracing::debug!("Attempting to click element: {:?}", self);
+
+        // Strategy 1: AT-SPI action
+        if let Ok(Some(action)) = self.do_named_action(CLICK_ACTIONS) {
+            return Ok(ClickResult {
+                method: "Action".to_string(),
+                coordinates: None,
+                details: format!("Clicked using AT-SPI '{}' action", action),
+            });
+        }
+
+        // Strategy 2: Use center of bounding rectangle
+        if let Ok((x, y)) = self.center() {
+            xdo::click(x, y, MouseButton::Left, 1)?;
+
+            return Ok(ClickResult {
+                method: "BoundsCenter".to_string(),
+                coordinates: Some((x as f64, y as f64)),
+                details: "Clicked at center of bounding rectangle".to_string(),
+            });
+        }
+
+        Err(AutomationError::InvalidState(
+            "Cannot determine click location for element".to_string(),
+        ))
+    }
+
+    /// Double-click on this element
+    pub fn double_click(&self) -> Result<ClickResult, AutomationError> {
+        let _ = self.focus();
+
+        let (x, y) = self.center()?;
+        xdo::click(x, y, MouseButton::Left, 2)?;
+
+        Ok(ClickResult {
+            method: "DoubleClick".to_string(),
+            coordinates: Some((x as f64, y as f64)),
+            details: "Double-clicked using mouse".to_string(),
+        })
+    }
+
+    /// Right-click on this element
+    pub fn right_click(&self) -> Result<(), AutomationError> {
+        let _ = self.focus();
+
+        let (x, y) = self.center()?;
+        xdo::click(x, y, MouseButton::Right, 1)
+    }
+
+    /// Focus this element
+    pub fn focus(&self) -> Result<(), AutomationError> {
+        if self.element.grab_focus(&self.bus()?)? {
+            Ok(())
+        } else {
+            Err(AutomationError::InvalidState(
+                "Element did not accept focus".to_string(),
+            ))
+        }
+    }
+
+    /// Type text into this element
+    ///
+    /// The element should be focused before typing. Text is typed literally,
+    /// see [`InputSimulator::type_text`](crate::InputSimulator::type_text).
+    pub fn type_text(&self, text: &str) -> Result<(), AutomationError> {
+        xdo::type_text(text)
+    }
+
+    /// Press a key or key combination
+    ///
+    /// Supports SendKeys notation (e.g., "^c" for Ctrl+C)
+    pub fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        xdo::send_keys(key)
+    }
+
+    /// Get text content from this element and its descendants
+    ///
+    /// `max_depth` controls how deep to search in the element tree
+    pub fn text(&self, max_depth: usize) -> Result<String, AutomationError> {
+        let mut all_texts = Vec::new();
+        self.extract_text(&mut all_texts, 0, max_depth)?;
+        Ok(all_texts.join(" "))
+    }
+
+    /// Recursive helper for text extraction
+    fn extract_text(
+        &self,
+        texts: &mut Vec<String>,
+        current_depth: usize,
+        max_depth: usize,
+    ) -> Result<(), AutomationError> {
+        if current_depth > max_depth {
+            return Ok(());
+        }
+
+        if let Some(name) = self.name().filter(|name| !name.is_empty()) {
+            texts.push(name);
+        }
+
+        if let Some(value) = self.value().filter(|value| !value.is_empty()) {
+            texts.push(value);
+        }
+
+        if let Ok(children) = self.children() {
+            for child in children {
+                child.extract_text(texts, current_depth + 1, max_depth)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the value of this element
+    ///
+    /// Replaces the text of editable elements; range elements (sliders,
+    /// spin buttons) take a number.
+    pub fn set_value(&self, value: &str) -> Result<(), AutomationError> {
+        let bus = self.bus()?;
+        let interfaces = self.element.interfaces(&bus)?;
+
+        if interfaces.iter().any(|i| i == atspi::EDITABLE_TEXT) {
+            return if self.element.set_text(&bus, value)? {
+                Ok(())
+            } else {
+                Err(AutomationError::InvalidState(
+                    "Element rejected the new text".to_string(),
+                ))
+            };
+        }
+
+        if interfaces.iter().any(|i| i == atspi::VALUE) {
+            let number: f64 = value.trim().parse().map_err(|_| {
+                AutomationError::InvalidArgument(format!(
+                    "Range element needs a numeric value, got '{}'",
+                    value
+                ))
+            })?;
+            return self.element.set_current_value(&bus, number);
+        }
+
+        Err(AutomationError::UnsupportedOperation(
+            "Element does not support setting a value".to_string(),
+        ))
+    }
+
+    /// Extract the cells of a data grid or table
+    ///
+    /// Reads cells and column headers through the AT-SPI Table interface.
+    /// At most `max_rows` rows are read; virtualized tables may report empty
+    /// text for rows scrolled out of view.
+    pub fn extract_table(&self, max_rows: usize) -> Result<TableData, AutomationError> {
+        let bus = self.bus()?;
+        let (row_count, column_count) = self.element.table_size(&bus).map_err(|e| {
+            AutomationError::UnsupportedOperation(format!(
+                "Element does not support the table interface: {}",
+                e
+            ))
+        })?;
+        let row_count = row_count.max(0) as usize;
+        let column_count = column_count.max(0) as usize;
+
+        let headers = (0..column_count)
+            .map(|column| {
+                self.element
+                    .column_header(&bus, column as i32)
+                    .ok()
+                    .flatten()
+                    .and_then(|header| header.name(&bus).ok())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        let headers = if headers.iter().all(|header| header.is_empty()) {
+            Vec::new()
+        } else {
+            headers
+        };
+
+        let mut rows = Vec::with_capacity(row_count.min(max_rows));
+        for row in 0..row_count.min(max_rows) {
+            let cells = (0..column_count)
+                .map(|column| {
+                    self.element
+                        .table_cell(&bus, row as i32, column as i32)
+                        .map(|cell| UIElement::new(cell, &self.automation).cell_text())
+                        .unwrap_or_default()
+                })
+                .collect();
+            rows.push(cells);
+        }
+
+        Ok(TableData {
+            headers,
+            rows,
+            row_count,
+            column_count,
+        })
+    }
+
+    /// Text of a table cell: its text content, falling back to its name
+    fn cell_text(&self) -> String {
+        self.bus()
+            .and_then(|bus| self.element.text(&bus))
+            .ok()
+            .filter(|text| !text.is_empty())
+            .or_else(|| self.name())
+            .unwrap_or_default()
+    }
+
+    /// Select an option of a combo box or list
+    ///
+    /// Options are the list and menu items below the element; toolkits keep
+    /// a combo box's menu in the tree while it is collapsed. The option is
+    /// selected through its container's Selection interface, or activated
+    /// when the container has none. Returns the name of the selected option.
+    pub fn select_option(&self, option: &SelectOption) -> Result<String, AutomationError> {
+        let items = self.search(Some(4), false, |element| {
+            matches!(element.role().as_str(), "ListItem" | "MenuItem")
+        });
+        let names: Vec<String> = items
+            .iter()
+            .map(|item| item.name().unwrap_or_default())
+            .collect();
+
+        let position = match option {
+            SelectOption::Index(index) => (*index < items.len()).then_some(*index),
+            SelectOption::Text(text) => {
+                let text = text.to_lowercase();
+                names
+                    .iter()
+                    .position(|name| name.to_lowercase() == text)
+                    .or_else(|| {
+                        names
+                            .iter()
+                            .position(|name| name.to_lowercase().contains(&text))
+                    })
+            }
+        };
+        let Some(position) = position else {
+            return Err(AutomationError::ElementNotFound(format!(
+                "No option matching {} among {} options",
+                option,
+                items.len()
+            )));
+        };
+
+        let item = &items[position];
+        let bus = self.bus()?;
+        let selected = match item.parent()? {
+            Some(container) => {
+                let interfaces = container.element.interfaces(&bus).unwrap_or_default();
+                if interfaces.iter().any(|i| i == atspi::SELECTION) {
+                    let index = item.element.index_in_parent(&bus)?;
+                    container.element.select_child(&bus, index)?
+                } else {
+                    false
+                }
+            }
+            None => false,
+        };
+        if !selected && item.do_named_action(CLICK_ACTIONS)?.is_none() {
+            return Err(AutomationError::UnsupportedOperation(format!(
+                "Option '{}' cannot be selected",
+                names[position]
+            )));
+        }
+
+        Ok(names[position].clone())
+    }
+
+    /// Check if element is enabled
+    pub fn is_enabled(&self) -> Result<bool, AutomationError> {
+        let states = self.element.state(&self.bus()?)?;
+        Ok(states.contains(State::Enabled) || states.contains(State::Sensitive))
+    }
+
+    /// Check if element is visible (not offscreen)
+    pub fn is_visible(&self) -> Result<bool, AutomationError> {
+        let states = self.element.state(&self.bus()?)?;
+        Ok(states.contains(State::Showing) && states.contains(State::Visible))
+    }
+
+    /// Scroll the element
+    ///
+    /// Direction: "up", "down", "left", "right". Scrolls with the mouse
+    /// wheel over the element, one wheel click per unit of `amount`.
+    pub fn scroll(&self, direction: &str, amount: f64) -> Result<(), AutomationError> {
+        if !matches!(direction, "up" | "down" | "left" | "right") {
+            return Err(AutomationError::InvalidArgument(format!(
+                "Invalid scroll direction: {}",
+                direction
+            )));
+        }
+
+        let clicks = amount.abs() as u32;
+        if clicks == 0 {
+            return Ok(());
+        }
+
+        let (x, y) = self.center()?;
+        xdo::scroll(x, y, direction, clicks)
+    }
+
+    /// Perform a named action on this element
+    pub fn perform_action(&self, action: &str) -> Result<(), AutomationError> {
+        let names: &[&str] = match action {
+            "focus" => return self.focus(),
+            "click" => return self.click().map(|_| ()),
+            "double_click" => return self.double_click().map(|_| ()),
+            "right_click" => return self.right_click(),
+            "invoke" => CLICK_ACTIONS,
+            "toggle" => &["toggle", "click", "press"],
+            "expand" => &["expand", "expand or contract", "open"],
+            "collapse" => &["collapse", "expand or contract"],
+            _ => {
+                return Err(AutomationError::UnsupportedOperation(format!(
+                    "Action '{}' not supported",
+                    action
+                )))
+            }
+        };
+
+        match self.do_named_action(names)? {
+            Some(_) => Ok(()),
+            None => Err(AutomationError::UnsupportedOperation(format!(
+                "Element has no '{}' action",
+                action
+            ))),
+        }
+    }
+
+    /// Find elements within this element using a selector
+    pub fn find_elements(&self, selector: &Selector) -> Result<Vec<UIElement>, AutomationError> {
+        match selector {
+            Selector::Text(_) | Selector::Name(_) => match self.find_element(selector) {
+                Ok(elem) => Ok(vec![elem]),
+                Err(_) => Ok(vec![]),
+            },
+            Selector::Role { role, name } => {
+                Ok(self.search(None, false, |e| e.matches_role(role, name.as_deref())))
+            }
+            Selector::Id(id) => {
+                Ok(self.search(None, false, |e| e.id().as_deref() == Some(id.as_str())))
+            }
+            Selector::Path(path) => self.find_by_path(path),
+            Selector::Chain(selectors) => self.find_by_chain(selectors),
+            Selector::Nth(_) => Err(nth_outside_chain()),
+            Selector::Attributes(_) => Err(AutomationError::UnsupportedOperation(format!(
+                "Selector type not supported for condition conversion: {:?}",
+                selector
+            ))),
+        }
+    }
+
+    /// Find first element within this element using a selector
+    pub fn find_element(&self, selector: &Selector) -> Result<UIElement, AutomationError> {
+        let found = match selector {
+            Selector::Text(text) => {
+                let text = text.to_lowercase();
+                self.search(Some(NAME_SEARCH_DEPTH), true, |e| {
+                    e.name().is_some_and(|n| n.to_lowercase().contains(&text))
+                })
+            }
+            Selector::Name(name) => self.search(Some(NAME_SEARCH_DEPTH), true, |e| {
+                e.name().is_some_and(|n| n.contains(name.as_str()))
+            }),
+            Selector::Role { role, name } => {
+                self.search(None, true, |e| e.matches_role(role, name.as_deref()))
+            }
+            Selector::Id(id) => self.search(None, true, |e| e.id().as_deref() == Some(id.as_str())),
+            Selector::Nth(_) => return Err(nth_outside_chain()),
+            _ => self.find_elements(selector)?,
+        };
+
+        found
+            .into_iter()
+            .next()
+            .ok_or_else(|| AutomationError::element_not_found(selector))
+    }
+
+    /// Walk the descendants breadth-first, collecting those that match
+    ///
+    /// `max_depth` limits the levels below this element (1 = children).
+    /// Subtrees of elements that manage their descendants (large tables and
+    /// lists) are not entered, and the walk stops after
+    /// [`MAX_SEARCH_NODES`] elements.
+    pub(crate) fn search(
+        &self,
+        max_depth: Option<usize>,
+        first_only: bool,
+        matches: impl Fn(&UIElement) -> bool,
+    ) -> Vec<UIElement> {
+        let mut found = Vec::new();
+        let mut queue: VecDeque<(UIElement, usize)> = self
+            .children()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| (child, 1))
+            .collect();
+        let mut visited = 0;
+
+        while let Some((element, depth)) = queue.pop_front() {
+            visited += 1;
+            if visited > MAX_SEARCH_NODES {
+                tracing::debug!("Search stopped after {} elements", MAX_SEARCH_NODES);
+                break;
+            }
+
+            if matches(&element) {
+                found.push(element.clone());
+                if first_only {
+                    break;
+                }
+            }
+
+            let descend = max_depth.is_none_or(|max_depth| depth < max_depth)
+                && !element.has_state(State::ManagesDescendants);
+            if descend {
+                // Elements that vanish mid-walk simply have no children
+                queue.extend(
+                    element
+                        .children()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|child| (child, depth + 1)),
+                );
+            }
+        }
+
+        found
+    }
+
+    /// Check the role and, if given, that the name contains `name`
+    fn matches_role(&self, role: &str, name: Option<&str>) -> bool {
+        if self.role() != atspi::selector_control_type(role) {
+            return false;
+        }
+        match name {
+            Some(name) => self.name().is_some_and(|n| n.contains(name)),
+            None => true,
+        }
+    }
+
+    /// Helper: Resolve a path expression by walking the tree one step at a time
+    ///
+    /// Each step is matched against the children (or, for `//`, all
+    /// descendants) of the elements matched by the previous step, so an early
+    /// `window[...]` step keeps later steps from matching in other windows.
+    fn find_by_path(&self, path: &str) -> Result<Vec<UIElement>, AutomationError> {
+        let steps = parse_path(path)?;
+
+        let mut context = vec![self.clone()];
+        for step in &steps {
+            let max_depth = if step.descendant { None } else { Some(1) };
+
+            let mut matched = Vec::new();
+            for parent in &context {
+                let mut matching = parent
+                    .search(max_depth, false, |element| element.matches_step(step))
+                    .into_iter();
+
+                match step.index {
+                    Some(index) => matched.extend(matching.nth(index - 1)),
+                    None => matched.extend(matching),
+                }
+            }
+
+            if matched.is_empty() {
+                return Ok(matched);
+            }
+            context = matched;
+        }
+
+        Ok(context)
+    }
+
+    /// Helper: Resolve a chain, searching within the matches of each previous selector
+    ///
+    /// A [`Selector::Nth`] link keeps only that element of the matches so far.
+    fn find_by_chain(&self, selectors: &[Selector]) -> Result<Vec<UIElement>, AutomationError> {
+        let mut context = vec![self.clone()];
+
+        for (position, selector) in selectors.iter().enumerate() {
+            context = match selector {
+                Selector::Nth(_) if position == 0 => return Err(nth_outside_chain()),
+                Selector::Nth(index) => context.into_iter().nth(*index).into_iter().collect(),
+                _ => {
+                    let mut matched = Vec::new();
+                    for parent in &context {
+                        matched.extend(parent.find_elements(selector)?);
+                    }
+                    matched
+                }
+            };
+
+            if context.is_empty() {
+                break;
+            }
+        }
+
+        Ok(context)
+    }
+
+    /// Check whether this element satisfies a path step's role and predicates
+    fn matches_step(&self, step: &PathStep) -> bool {
+        if let Some(role) = &step.role {
+            if self.role() != atspi::selector_control_type(role) {
+                return false;
+            }
+        }
+
+        if let Some(name) = &step.name {
+            if !self.name().is_some_and(|n| name.matches(&n)) {
+                return false;
+            }
+        }
+
+        if let Some(id) = &step.id {
+            if self.id().as_deref() != Some(id.as_str()) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl PartialEq for UIElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.element == other.element
+    }
+}
+
+impl Eq for UIElement {}
+
+impl Hash for UIElement {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.element.hash(state);
+    }
+}
+
+/// Error for an `nth` selector that does not follow another selector
+fn nth_outside_chain() -> AutomationError {
+    AutomationError::InvalidArgument("nth must follow another selector in a chain".to_string())
+}
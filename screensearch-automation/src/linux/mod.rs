@@ -0,0 +1,162 @@
+//! Linux automation backend
+//!
+//! Elements come from the AT-SPI accessibility bus (`atspi`), and input and
+//! window management go through `xdotool` (`xdo`). Both work on X11 and
+//! for XWayland windows; native Wayland clients are visible to AT-SPI but
+//! cannot receive synthesized input or be moved.
+
+pub(crate) mod atspi;
+pub(crate) mod element;
+pub(crate) mod xdo;
+
+use self::atspi::{Accessible, State};
+use self::element::{ThreadSafeAutomation, UIElement};
+use crate::errors::AutomationError;
+use std::process::{Command, Stdio};
+
+/// The desktop element, whose children are the accessible applications
+pub(crate) fn root(automation: &ThreadSafeAutomation) -> Result<UIElement, AutomationError> {
+    // Surface a missing accessibility bus here rather than as empty results
+    let _ = automation.0.connection()?;
+    Ok(UIElement::new(Accessible::root(), automation))
+}
+
+/// Top-level windows of every accessible application
+pub(crate) fn top_level_windows(
+    automation: &ThreadSafeAutomation,
+) -> Result<Vec<UIElement>, AutomationError> {
+    let mut windows = Vec::new();
+    for application in root(automation)?.children()? {
+        // Applications that quit mid-walk have no windows
+        windows.extend(
+            application
+                .children()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|window| window.role() == "Window"),
+        );
+    }
+    Ok(windows)
+}
+
+/// The window with the active state
+pub(crate) fn active_window(
+    automation: &ThreadSafeAutomation,
+) -> Result<UIElement, AutomationError> {
+    top_level_windows(automation)?
+        .into_iter()
+        .find(|window| window.has_state(State::Active))
+        .ok_or_else(|| AutomationError::InvalidState("No active window".to_string()))
+}
+
+/// The focused element of the active window
+pub(crate) fn focused_element(
+    automation: &ThreadSafeAutomation,
+) -> Result<UIElement, AutomationError> {
+    let window = active_window(automation)?;
+    if window.has_state(State::Focused) {
+        return Ok(window);
+    }
+
+    window
+        .search(None, true, |element| element.has_state(State::Focused))
+        .into_iter()
+        .next()
+        .ok_or_else(|| AutomationError::ElementNotFound("No focused element".to_string()))
+}
+
+/// A window whose title contains `name`, or the first window of an
+/// application (or process) named like `name`
+pub(crate) fn application_window(
+    automation: &ThreadSafeAutomation,
+    name: &str,
+) -> Result<UIElement, AutomationError> {
+    let windows = top_level_windows(automation)?;
+    if let Some(window) = windows
+        .iter()
+        .find(|window| window.name().is_some_and(|title| title.contains(name)))
+    {
+        return Ok(window.clone());
+    }
+
+    let wanted = name.to_lowercase();
+    windows
+        .into_iter()
+        .find(|window| {
+            let application = window
+                .parent()
+                .ok()
+                .flatten()
+                .and_then(|application| application.name())
+                .unwrap_or_default();
+            let process = window
+                .process_id()
+                .ok()
+                .and_then(|pid| std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok())
+                .unwrap_or_default();
+
+            application.to_lowercase().contains(&wanted)
+                || process.trim().to_lowercase().contains(&wanted)
+        })
+        .ok_or_else(|| {
+            AutomationError::element_not_found(format!("No application found with name: {}", name))
+        })
+}
+
+/// Launch an application
+///
+/// `app_name` is a command line (program and arguments, split on
+/// whitespace) or, when no such program exists, a desktop entry ID started
+/// with `gtk-launch`.
+pub(crate) fn open_application(app_name: &str) -> Result<(), AutomationError> {
+    let mut parts = app_name.split_whitespace();
+    let program = parts.next().ok_or_else(|| {
+        AutomationError::InvalidArgument("Application name must not be empty".to_string())
+    })?;
+
+    match spawn_detached(Command::new(program).args(parts)) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(AutomationError::platform(format!(
+                "Failed to open application {}: {}",
+                app_name, e
+            )))
+        }
+        Ok(()) => return Ok(()),
+    }
+
+    let status = Command::new("gtk-launch")
+        .arg(app_name)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| AutomationError::platform(format!("Failed to execute command: {}", e)))?;
+    if !status.success() {
+        return Err(AutomationError::platform(format!(
+            "Failed to open application: {}",
+            app_name
+        )));
+    }
+    Ok(())
+}
+
+/// Open a URL with `browser`, or the default handler through `xdg-open`
+pub(crate) fn open_url(url: &str, browser: Option<&str>) -> Result<(), AutomationError> {
+    let program = browser.filter(|b| !b.is_empty()).unwrap_or("xdg-open");
+    spawn_detached(Command::new(program).arg(url)).map_err(|e| {
+        AutomationError::platform(format!("Failed to open URL with {}: {}", program, e))
+    })
+}
+
+/// Start a process without waiting for it, reaping it when it exits
+fn spawn_detached(command: &mut Command) -> std::io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
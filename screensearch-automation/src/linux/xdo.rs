@@ -0,0 +1,562 @@
+//! Input simulation and window control through `xdotool`
+//!
+//! Works on X11 sessions and for XWayland windows; native Wayland windows do
+//! not accept synthetic input from other clients.
+
+use crate::errors::AutomationError;
+use crate::input::MouseButton;
+use crate::window::WindowInfo;
+use std::process::Command;
+use std::time::Duration;
+
+/// Run `xdotool` with `args` and return its standard output
+fn run<I, S>(args: I) -> Result<String, AutomationError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+{
+    let output = Command::new("xdotool").args(args).output().map_err(|e| {
+        AutomationError::platform(format!(
+            "Failed to run xdotool (is it installed and is DISPLAY set?): {}",
+            e
+        ))
+    })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(AutomationError::platform(format!(
+            "xdotool failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// X button number of a mouse button
+fn button_number(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "1",
+        MouseButton::Middle => "2",
+        MouseButton::Right => "3",
+    }
+}
+
+/// Click `count` times at a screen point
+pub(crate) fn click(
+    x: i32,
+    y: i32,
+    button: MouseButton,
+    count: u32,
+) -> Result<(), AutomationError> {
+    run([
+        "mousemove".to_string(),
+        "--sync".to_string(),
+        x.to_string(),
+        y.to_string(),
+        "click".to_string(),
+        "--repeat".to_string(),
+        count.max(1).to_string(),
+        button_number(button).to_string(),
+    ])
+    .map(|_| ())
+}
+
+/// Current cursor position
+pub(crate) fn cursor_pos() -> Result<(i32, i32), AutomationError> {
+    let vars = parse_shell_vars(&run(["getmouselocation", "--shell"])?);
+    match (vars.get("X"), vars.get("Y")) {
+        (Some(x), Some(y)) => Ok((*x, *y)),
+        _ => Err(AutomationError::platform(
+            "xdotool did not report the cursor position",
+        )),
+    }
+}
+
+/// Arguments moving the cursor through `points`, pausing `step_delay` before each
+fn path_args(points: &[(i32, i32)], step_delay: Duration) -> Vec<String> {
+    let mut args = Vec::with_capacity(points.len() * 5);
+    for &(x, y) in points {
+        if !step_delay.is_zero() {
+            args.push("sleep".to_string());
+            args.push(format!("{:.3}", step_delay.as_secs_f64()));
+        }
+        args.push("mousemove".to_string());
+        args.push(x.to_string());
+        args.push(y.to_string());
+    }
+    args
+}
+
+/// Move the cursor through `points` in a single xdotool run
+pub(crate) fn move_along(
+    points: &[(i32, i32)],
+    step_delay: Duration,
+) -> Result<(), AutomationError> {
+    if points.is_empty() {
+        return Ok(());
+    }
+    run(path_args(points, step_delay)).map(|_| ())
+}
+
+/// Press `button` at `from`, move through `points` and release
+pub(crate) fn drag(
+    from: (i32, i32),
+    points: &[(i32, i32)],
+    button: MouseButton,
+    step_delay: Duration,
+) -> Result<(), AutomationError> {
+    let button = button_number(button).to_string();
+    let mut args = vec![
+        "mousemove".to_string(),
+        "--sync".to_string(),
+        from.0.to_string(),
+        from.1.to_string(),
+        "mousedown".to_string(),
+        button.clone(),
+    ];
+    args.extend(path_args(points, step_delay));
+    args.push("mouseup".to_string());
+    args.push(button.clone());
+
+    let result = run(args).map(|_| ());
+    if result.is_err() {
+        // Never leave the button held down
+        let _ = run(["mouseup".to_string(), button]);
+    }
+    result
+}
+
+/// Scroll the wheel `clicks` times at a screen point
+pub(crate) fn scroll(x: i32, y: i32, direction: &str, clicks: u32) -> Result<(), AutomationError> {
+    let button = match direction {
+        "up" => "4",
+        "down" => "5",
+        "left" => "6",
+        "right" => "7",
+        _ => {
+            return Err(AutomationError::InvalidArgument(format!(
+                "Invalid scroll direction: {}",
+                direction
+            )))
+        }
+    };
+    run([
+        "mousemove".to_string(),
+        "--sync".to_string(),
+        x.to_string(),
+        y.to_string(),
+        "click".to_string(),
+        "--repeat".to_string(),
+        clicks.max(1).to_string(),
+        button.to_string(),
+    ])
+    .map(|_| ())
+}
+
+/// Type text literally, independent of keyboard layout
+pub(crate) fn type_text(text: &str) -> Result<(), AutomationError> {
+    if text.is_empty() {
+        return Ok(());
+    }
+    // A lone line feed is what xdotool types as Return
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    run([
+        "type",
+        "--clearmodifiers",
+        "--delay",
+        "0",
+        "--",
+        text.as_str(),
+    ])
+    .map(|_| ())
+}
+
+/// One xdotool action of a translated SendKeys string
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum KeyStep {
+    /// Key combination (e.g. "ctrl+shift+s"), pressed `repeat` times
+    Key { combo: String, repeat: u32 },
+    /// Text typed literally
+    Text(String),
+}
+
+/// Press keys in Windows SendKeys notation (e.g. "^c", "%{TAB}", "{ENTER}")
+pub(crate) fn send_keys(keys: &str) -> Result<(), AutomationError> {
+    for step in translate_send_keys(keys)? {
+        match step {
+            KeyStep::Key { combo, repeat } => {
+                run([
+                    "key".to_string(),
+                    "--clearmodifiers".to_string(),
+                    "--repeat".to_string(),
+                    repeat.to_string(),
+                    combo,
+                ])?;
+            }
+            KeyStep::Text(text) => type_text(&text)?,
+        }
+    }
+    Ok(())
+}
+
+/// X keysym of a SendKeys key name (the part between braces)
+fn keysym(name: &str) -> Option<String> {
+    let upper = name.to_uppercase();
+    let keysym = match upper.as_str() {
+        "ENTER" => "Return",
+        "ESC" | "ESCAPE" => "Escape",
+        "TAB" => "Tab",
+        "BACKSPACE" | "BS" | "BKSP" => "BackSpace",
+        "DELETE" | "DEL" => "Delete",
+        "INSERT" | "INS" => "Insert",
+        "HOME" => "Home",
+        "END" => "End",
+        "PGUP" => "Prior",
+        "PGDN" => "Next",
+        "UP" => "Up",
+        "DOWN" => "Down",
+        "LEFT" => "Left",
+        "RIGHT" => "Right",
+        "SPACE" => "space",
+        "CAPSLOCK" => "Caps_Lock",
+        "NUMLOCK" => "Num_Lock",
+        "SCROLLLOCK" => "Scroll_Lock",
+        "PRTSC" => "Print",
+        "BREAK" => "Break",
+        "HELP" => "Help",
+        "WIN" | "LWIN" => "super",
+        "APPS" => "Menu",
+        _ => {
+            let number = upper.strip_prefix('F')?.parse::<u8>().ok()?;
+            return (1..=24).contains(&number).then(|| format!("F{}", number));
+        }
+    };
+    Some(keysym.to_string())
+}
+
+/// X keysym of a single character pressed together with modifiers
+fn char_keysym(c: char) -> Option<String> {
+    if c.is_ascii_alphanumeric() {
+        return Some(c.to_ascii_lowercase().to_string());
+    }
+    let keysym = match c {
+        ' ' => "space",
+        '.' => "period",
+        ',' => "comma",
+        '/' => "slash",
+        '\\' => "backslash",
+        '-' => "minus",
+        '=' => "equal",
+        ';' => "semicolon",
+        '\'' => "apostrophe",
+        '`' => "grave",
+        '[' => "bracketleft",
+        ']' => "bracketright",
+        '+' => "plus",
+        '^' => "asciicircum",
+        '%' => "percent",
+        '~' => "asciitilde",
+        '(' => "parenleft",
+        ')' => "parenright",
+        '{' => "braceleft",
+        '}' => "braceright",
+        _ => return None,
+    };
+    Some(keysym.to_string())
+}
+
+/// Translate SendKeys notation into xdotool key presses and typed text
+///
+/// `+`, `^` and `%` hold Shift, Ctrl and Alt for the next key, or for a
+/// parenthesized group; `~` is Enter; `{KEY}` names a key and `{KEY n}`
+/// repeats it. Characters without modifiers are typed literally.
+fn translate_send_keys(keys: &str) -> Result<Vec<KeyStep>, AutomationError> {
+    let invalid = |reason: &str| {
+        AutomationError::InvalidArgument(format!("Invalid key sequence '{}': {}", keys, reason))
+    };
+
+    let mut steps = Vec::new();
+    let mut text = String::new();
+    let mut modifiers: Vec<&str> = Vec::new();
+    let mut group: Option<Vec<&str>> = None;
+    let mut chars = keys.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        // A key: its keysym, a literal character, and how often to press it
+        let (key, literal, repeat) = match c {
+            '+' | '^' | '%' => {
+                modifiers.push(match c {
+                    '+' => "shift",
+                    '^' => "ctrl",
+                    _ => "alt",
+                });
+                continue;
+            }
+            '(' if !modifiers.is_empty() => {
+                if group.is_some() {
+                    return Err(invalid("nested groups are not supported"));
+                }
+                group = Some(std::mem::take(&mut modifiers));
+                continue;
+            }
+            ')' if group.is_some() => {
+                group = None;
+                continue;
+            }
+            '~' => ("Return".to_string(), None, 1),
+            '{' => {
+                let mut name = String::new();
+                // "{}}" names the closing brace itself
+                if chars.peek() == Some(&'}') {
+                    name.push(chars.next().unwrap_or('}'));
+                }
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(invalid("unclosed '{'")),
+                    }
+                }
+
+                let (name, repeat) = match name.rsplit_once(' ') {
+                    Some((name, count)) if !name.is_empty() => (
+                        name.to_string(),
+                        count
+                            .parse::<u32>()
+                            .map_err(|_| invalid("bad repeat count"))?,
+                    ),
+                    _ => (name, 1),
+                };
+
+                let mut name_chars = name.chars();
+                match (name_chars.next(), name_chars.next()) {
+                    (Some(c), None) => (
+                        char_keysym(c).unwrap_or_else(|| c.to_string()),
+                        Some(c),
+                        repeat,
+                    ),
+                    _ => (
+                        keysym(&name)
+                            .ok_or_else(|| invalid(&format!("unknown key {{{}}}", name)))?,
+                        None,
+                        repeat,
+                    ),
+                }
+            }
+            c => (char_keysym(c).unwrap_or_else(|| c.to_string()), Some(c), 1),
+        };
+
+        let held: Vec<&str> = group
+            .iter()
+            .flatten()
+            .chain(modifiers.iter())
+            .copied()
+            .collect();
+        modifiers.clear();
+
+        if let (Some(c), true) = (literal, held.is_empty()) {
+            text.extend(std::iter::repeat_n(c, repeat as usize));
+            continue;
+        }
+        if literal.is_some_and(|c| char_keysym(c).is_none()) {
+            return Err(invalid("modifiers can only be combined with ASCII keys"));
+        }
+        if !text.is_empty() {
+            steps.push(KeyStep::Text(std::mem::take(&mut text)));
+        }
+
+        let mut combo = held.join("+");
+        if !combo.is_empty() {
+            combo.push('+');
+        }
+        combo.push_str(&key);
+        steps.push(KeyStep::Key { combo, repeat });
+    }
+
+    if group.is_some() {
+        return Err(invalid("unclosed '('"));
+    }
+    if !text.is_empty() {
+        steps.push(KeyStep::Text(text));
+    }
+    Ok(steps)
+}
+
+/// Parse `KEY=value` lines of `xdotool --shell` output with integer values
+fn parse_shell_vars(output: &str) -> std::collections::HashMap<String, i32> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim().to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// IDs of visible windows that have a title
+pub(crate) fn visible_windows() -> Result<Vec<u64>, AutomationError> {
+    // No match exits with status 1, which means no windows rather than an error
+    let output = match run(["search", "--onlyvisible", "--name", "."]) {
+        Ok(output) => output,
+        Err(_) => return Ok(Vec::new()),
+    };
+    Ok(output
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect())
+}
+
+/// ID of the focused window
+pub(crate) fn active_window() -> Result<Option<u64>, AutomationError> {
+    match run(["getactivewindow"]) {
+        Ok(output) => Ok(output.trim().parse().ok()),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Information about a window
+pub(crate) fn window_info(id: u64) -> Result<WindowInfo, AutomationError> {
+    let id_arg = id.to_string();
+    let geometry = parse_shell_vars(
+        &run(["getwindowgeometry", "--shell", id_arg.as_str()]).map_err(|_| {
+            AutomationError::InvalidArgument(format!("No window with handle {}", id))
+        })?,
+    );
+    let title = run(["getwindowname", id_arg.as_str()])
+        .map(|name| name.trim_end_matches('\n').to_string())
+        .unwrap_or_default();
+    let process_id: u32 = run(["getwindowpid", id_arg.as_str()])
+        .ok()
+        .and_then(|pid| pid.trim().parse().ok())
+        .unwrap_or(0);
+    let process_name = std::fs::read_to_string(format!("/proc/{}/comm", process_id))
+        .map(|name| name.trim().to_string())
+        .unwrap_or_else(|_| "Unknown".to_string());
+
+    Ok(WindowInfo {
+        title,
+        process_name,
+        process_id,
+        x: geometry.get("X").copied().unwrap_or(0),
+        y: geometry.get("Y").copied().unwrap_or(0),
+        width: geometry.get("WIDTH").copied().unwrap_or(0),
+        height: geometry.get("HEIGHT").copied().unwrap_or(0),
+        is_visible: true,
+        // xdotool cannot query window states; minimized windows are not listed
+        is_minimized: false,
+        is_maximized: false,
+        desktop_id: None,
+        on_current_desktop: true,
+        handle: id as usize,
+    })
+}
+
+/// Run a window command (e.g. "windowactivate") on a window
+pub(crate) fn window_command(
+    id: u64,
+    command: &str,
+    extra: &[String],
+) -> Result<(), AutomationError> {
+    let mut args = vec![command.to_string()];
+    if command == "windowactivate" {
+        args.push("--sync".to_string());
+    }
+    args.push(id.to_string());
+    args.extend_from_slice(extra);
+    run(args).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(combo: &str, repeat: u32) -> KeyStep {
+        KeyStep::Key {
+            combo: combo.to_string(),
+            repeat,
+        }
+    }
+
+    #[test]
+    fn test_translate_shortcuts() {
+        assert_eq!(translate_send_keys("^c").unwrap(), vec![key("ctrl+c", 1)]);
+        assert_eq!(
+            translate_send_keys("%{TAB}").unwrap(),
+            vec![key("alt+Tab", 1)]
+        );
+        assert_eq!(
+            translate_send_keys("^+S").unwrap(),
+            vec![key("ctrl+shift+s", 1)]
+        );
+        assert_eq!(translate_send_keys("{F5}").unwrap(), vec![key("F5", 1)]);
+    }
+
+    #[test]
+    fn test_translate_text_and_keys() {
+        assert_eq!(
+            translate_send_keys("Hello world~").unwrap(),
+            vec![KeyStep::Text("Hello world".to_string()), key("Return", 1)]
+        );
+        assert_eq!(
+            translate_send_keys("{LEFT 3}x").unwrap(),
+            vec![key("Left", 3), KeyStep::Text("x".to_string())]
+        );
+        // Braced metacharacters are typed literally
+        assert_eq!(
+            translate_send_keys("{+}{{}{}}").unwrap(),
+            vec![KeyStep::Text("+{}".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_translate_groups() {
+        assert_eq!(
+            translate_send_keys("+(ab)c").unwrap(),
+            vec![
+                key("shift+a", 1),
+                key("shift+b", 1),
+                KeyStep::Text("c".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_translate_errors() {
+        assert!(translate_send_keys("{ENTER").is_err());
+        assert!(translate_send_keys("{NOPE}").is_err());
+        assert!(translate_send_keys("+(ab").is_err());
+        assert!(translate_send_keys("^é").is_err());
+    }
+
+    #[test]
+    fn test_path_args() {
+        let args = path_args(&[(1, 2), (3, 4)], Duration::from_millis(10));
+        assert_eq!(
+            args,
+            [
+                "sleep",
+                "0.010",
+                "mousemove",
+                "1",
+                "2",
+                "sleep",
+                "0.010",
+                "mousemove",
+                "3",
+                "4"
+            ]
+        );
+        assert_eq!(
+            path_args(&[(5, 6)], Duration::ZERO),
+            ["mousemove", "5", "6"]
+        );
+    }
+
+    #[test]
+    fn test_parse_shell_vars() {
+        let vars = parse_shell_vars("WINDOW=1234\nX=-20\nY=40\nWIDTH=800\nSCREEN=0\nbad\n");
+        assert_eq!(vars.get("X"), Some(&-20));
+        assert_eq!(vars.get("WIDTH"), Some(&800));
+        assert_eq!(vars.get("bad"), None);
+    }
+}
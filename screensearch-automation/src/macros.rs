@@ -0,0 +1,702 @@
+//! Macro recording for desktop automation
+//!
+//! Records global mouse clicks (with the element under the cursor), keystrokes
+//! and foreground window switches into a list of [`MacroEvent`]s that can be
+//! stored and replayed with [`AutomationEngine::replay_macro`].
+//!
+//! Recording uses low-level Windows input hooks. Input injected by other
+//! programs (including macro replay itself) is ignored.
+//!
+//! [`AutomationEngine::replay_macro`]: crate::AutomationEngine::replay_macro
+
+use crate::errors::AutomationError;
+#[cfg(target_os = "windows")]
+use crate::input::MouseButton;
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "windows")]
+use std::sync::mpsc;
+#[cfg(target_os = "windows")]
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// A single recorded input event
+///
+/// `offset_ms` is the time since recording started.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MacroEvent {
+    /// Mouse click, with the element under the cursor when it was recorded
+    Click {
+        offset_ms: u64,
+        x: i32,
+        y: i32,
+        button: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        element_name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        element_role: Option<String>,
+    },
+    /// Non-text key press in SendKeys notation (e.g. "{ENTER}", "^c")
+    Keys { offset_ms: u64, keys: String },
+    /// Typed text
+    Text { offset_ms: u64, text: String },
+    /// Foreground window changed
+    WindowSwitch {
+        offset_ms: u64,
+        title: String,
+        process_name: String,
+    },
+}
+
+impl MacroEvent {
+    /// Time since recording started, in milliseconds
+    pub fn offset_ms(&self) -> u64 {
+        match self {
+            MacroEvent::Click { offset_ms, .. }
+            | MacroEvent::Keys { offset_ms, .. }
+            | MacroEvent::Text { offset_ms, .. }
+            | MacroEvent::WindowSwitch { offset_ms, .. } => *offset_ms,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+/// Button name used in recorded click events
+pub(crate) fn button_name(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+    }
+}
+
+#[cfg(target_os = "windows")]
+/// Raw input captured by the hooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RawInput {
+    KeyDown(u32),
+    KeyUp(u32),
+    MouseDown { x: i32, y: i32, button: MouseButton },
+}
+
+#[cfg(target_os = "windows")]
+/// Channel the hook procedures forward raw input to
+///
+/// Hook procedures cannot capture state, so only one recording can be active
+/// at a time.
+static HOOK_SENDER: Mutex<Option<mpsc::Sender<(Instant, RawInput)>>> = Mutex::new(None);
+
+#[cfg(target_os = "windows")]
+fn forward_raw(input: RawInput) {
+    if let Ok(guard) = HOOK_SENDER.lock() {
+        if let Some(tx) = guard.as_ref() {
+            let _ = tx.send((Instant::now(), input));
+        }
+    }
+}
+
+// Virtual-key codes
+#[cfg(any(target_os = "windows", test))]
+const VK_BACK: u32 = 0x08;
+#[cfg(any(target_os = "windows", test))]
+const VK_TAB: u32 = 0x09;
+#[cfg(any(target_os = "windows", test))]
+const VK_RETURN: u32 = 0x0D;
+#[cfg(any(target_os = "windows", test))]
+const VK_SHIFT: u32 = 0x10;
+#[cfg(any(target_os = "windows", test))]
+const VK_CONTROL: u32 = 0x11;
+#[cfg(any(target_os = "windows", test))]
+const VK_MENU: u32 = 0x12;
+#[cfg(any(target_os = "windows", test))]
+const VK_ESCAPE: u32 = 0x1B;
+#[cfg(any(target_os = "windows", test))]
+const VK_SPACE: u32 = 0x20;
+#[cfg(any(target_os = "windows", test))]
+const VK_LWIN: u32 = 0x5B;
+#[cfg(any(target_os = "windows", test))]
+const VK_RWIN: u32 = 0x5C;
+#[cfg(any(target_os = "windows", test))]
+const VK_F1: u32 = 0x70;
+#[cfg(any(target_os = "windows", test))]
+const VK_F12: u32 = 0x7B;
+#[cfg(any(target_os = "windows", test))]
+const VK_LSHIFT: u32 = 0xA0;
+#[cfg(any(target_os = "windows", test))]
+const VK_RSHIFT: u32 = 0xA1;
+#[cfg(any(target_os = "windows", test))]
+const VK_LCONTROL: u32 = 0xA2;
+#[cfg(any(target_os = "windows", test))]
+const VK_RCONTROL: u32 = 0xA3;
+#[cfg(any(target_os = "windows", test))]
+const VK_LMENU: u32 = 0xA4;
+#[cfg(any(target_os = "windows", test))]
+const VK_RMENU: u32 = 0xA5;
+
+#[cfg(any(target_os = "windows", test))]
+/// Modifier keys tracked while recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+    Shift,
+    Ctrl,
+    Alt,
+    Win,
+}
+
+#[cfg(any(target_os = "windows", test))]
+fn modifier_for(vk: u32) -> Option<Modifier> {
+    match vk {
+        VK_SHIFT | VK_LSHIFT | VK_RSHIFT => Some(Modifier::Shift),
+        VK_CONTROL | VK_LCONTROL | VK_RCONTROL => Some(Modifier::Ctrl),
+        VK_MENU | VK_LMENU | VK_RMENU => Some(Modifier::Alt),
+        VK_LWIN | VK_RWIN => Some(Modifier::Win),
+        _ => None,
+    }
+}
+
+#[cfg(any(target_os = "windows", test))]
+/// Punctuation keys as (virtual-key code, character, shifted character)
+const OEM_KEYS: [(u32, char, char); 11] = [
+    (0xBA, ';', ':'),
+    (0xBB, '=', '+'),
+    (0xBC, ',', '<'),
+    (0xBD, '-', '_'),
+    (0xBE, '.', '>'),
+    (0xBF, '/', '?'),
+    (0xC0, '`', '~'),
+    (0xDB, '[', '{'),
+    (0xDC, '\\', '|'),
+    (0xDD, ']', '}'),
+    (0xDE, '\'', '"'),
+];
+
+#[cfg(any(target_os = "windows", test))]
+/// Character produced by a key on a US keyboard layout
+fn printable_char(vk: u32, shift: bool) -> Option<char> {
+    const SHIFTED_DIGITS: [char; 10] = [')', '!', '@', '#', '$', '%', '^', '&', '*', '('];
+
+    match vk {
+        0x41..=0x5A => {
+            let ch = char::from_u32(vk)?;
+            Some(if shift { ch } else { ch.to_ascii_lowercase() })
+        }
+        0x30..=0x39 if shift => Some(SHIFTED_DIGITS[(vk - 0x30) as usize]),
+        0x30..=0x39 => char::from_u32(vk),
+        VK_SPACE => Some(' '),
+        _ => OEM_KEYS
+            .iter()
+            .find(|(code, _, _)| *code == vk)
+            .map(|&(_, ch, shifted)| if shift { shifted } else { ch }),
+    }
+}
+
+#[cfg(any(target_os = "windows", test))]
+/// SendKeys name of a non-printable key
+fn named_key(vk: u32) -> Option<String> {
+    let name = match vk {
+        VK_RETURN => "ENTER",
+        VK_TAB => "TAB",
+        VK_BACK => "BACKSPACE",
+        VK_ESCAPE => "ESC",
+        0x2E => "DELETE",
+        0x2D => "INSERT",
+        0x24 => "HOME",
+        0x23 => "END",
+        0x21 => "PGUP",
+        0x22 => "PGDN",
+        0x25 => "LEFT",
+        0x26 => "UP",
+        0x27 => "RIGHT",
+        0x28 => "DOWN",
+        VK_F1..=VK_F12 => return Some(format!("{{F{}}}", vk - VK_F1 + 1)),
+        _ => return None,
+    };
+    Some(format!("{{{}}}", name))
+}
+
+#[cfg(any(target_os = "windows", test))]
+/// Escape a character for SendKeys notation
+fn sendkeys_char(ch: char) -> String {
+    match ch {
+        '+' | '^' | '%' | '~' | '(' | ')' | '{' | '}' | '[' | ']' => format!("{{{}}}", ch),
+        _ => ch.to_string(),
+    }
+}
+
+#[cfg(any(target_os = "windows", test))]
+/// Turns raw hook input into macro events
+///
+/// Consecutive printable keystrokes are merged into a single `Text` event;
+/// shortcuts and special keys become `Keys` events.
+#[derive(Debug, Default)]
+struct EventBuilder {
+    events: Vec<MacroEvent>,
+    pending_text: String,
+    pending_text_offset: u64,
+    shift: bool,
+    ctrl: bool,
+    alt: bool,
+    win: bool,
+}
+
+#[cfg(any(target_os = "windows", test))]
+impl EventBuilder {
+    fn key_down(&mut self, vk: u32, offset_ms: u64) {
+        if let Some(modifier) = modifier_for(vk) {
+            self.set_modifier(modifier, true);
+            return;
+        }
+
+        // Windows-key shortcuts are OS-level and cannot be expressed in SendKeys
+        if self.win {
+            return;
+        }
+
+        if !self.ctrl && !self.alt {
+            if let Some(ch) = printable_char(vk, self.shift) {
+                if self.pending_text.is_empty() {
+                    self.pending_text_offset = offset_ms;
+                }
+                self.pending_text.push(ch);
+                return;
+            }
+        }
+
+        let key = match named_key(vk) {
+            Some(key) => key,
+            // Shortcut letters/digits use the unshifted character (e.g. Ctrl+Shift+S = "^+s")
+            None => match printable_char(vk, false) {
+                Some(ch) => sendkeys_char(ch),
+                None => return,
+            },
+        };
+
+        let mut keys = String::new();
+        if self.ctrl {
+            keys.push('^');
+        }
+        if self.alt {
+            keys.push('%');
+        }
+        if self.shift {
+            keys.push('+');
+        }
+        keys.push_str(&key);
+
+        self.push(MacroEvent::Keys { offset_ms, keys });
+    }
+
+    fn key_up(&mut self, vk: u32) {
+        if let Some(modifier) = modifier_for(vk) {
+            self.set_modifier(modifier, false);
+        }
+    }
+
+    fn set_modifier(&mut self, modifier: Modifier, down: bool) {
+        match modifier {
+            Modifier::Shift => self.shift = down,
+            Modifier::Ctrl => self.ctrl = down,
+            Modifier::Alt => self.alt = down,
+            Modifier::Win => self.win = down,
+        }
+    }
+
+    /// Append an event, flushing any pending text first
+    fn push(&mut self, event: MacroEvent) {
+        self.flush_text();
+        self.events.push(event);
+    }
+
+    fn flush_text(&mut self) {
+        if !self.pending_text.is_empty() {
+            self.events.push(MacroEvent::Text {
+                offset_ms: self.pending_text_offset,
+                text: std::mem::take(&mut self.pending_text),
+            });
+        }
+    }
+
+    fn finish(mut self) -> Vec<MacroEvent> {
+        self.flush_text();
+        self.events
+    }
+}
+
+/// A finished recording
+#[derive(Debug, Clone)]
+pub struct RecordedMacro {
+    /// Recorded events in order
+    pub events: Vec<MacroEvent>,
+    /// Total recording time in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Active macro recording session
+///
+/// Created with [`MacroRecorder::start`]; call [`MacroRecorder::stop`] to
+/// remove the hooks and collect the recorded events.
+pub struct MacroRecorder {
+    started: Instant,
+    #[cfg(target_os = "windows")]
+    hook_thread_id: u32,
+    #[cfg(target_os = "windows")]
+    hook_thread: std::thread::JoinHandle<()>,
+    #[cfg(target_os = "windows")]
+    worker: std::thread::JoinHandle<Vec<MacroEvent>>,
+}
+
+impl MacroRecorder {
+    /// Install the input hooks and start recording
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidState` if another recording is already running.
+    pub fn start() -> Result<Self, AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            let (tx, rx) = mpsc::channel();
+            {
+                let mut sender = HOOK_SENDER
+                    .lock()
+                    .map_err(|e| AutomationError::Internal(e.to_string()))?;
+                if sender.is_some() {
+                    return Err(AutomationError::InvalidState(
+                        "A macro recording is already in progress".to_string(),
+                    ));
+                }
+                *sender = Some(tx);
+            }
+
+            let started = Instant::now();
+            let (hook_thread_id, hook_thread) = match hooks::spawn_hook_thread() {
+                Ok(spawned) => spawned,
+                Err(e) => {
+                    clear_sender();
+                    return Err(e);
+                }
+            };
+            let worker = std::thread::spawn(move || hooks::process_events(rx, started));
+
+            tracing::info!("Macro recording started");
+
+            Ok(Self {
+                started,
+                hook_thread_id,
+                hook_thread,
+                worker,
+            })
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            Err(AutomationError::UnsupportedOperation(
+                "Macro recording is only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    /// Time since recording started, in milliseconds
+    pub fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    /// Remove the hooks and return the recorded events
+    pub fn stop(self) -> Result<RecordedMacro, AutomationError> {
+        let duration_ms = self.elapsed_ms();
+
+        #[cfg(target_os = "windows")]
+        {
+            hooks::stop_hook_thread(self.hook_thread_id);
+            let _ = self.hook_thread.join();
+
+            // Dropping the sender ends the worker loop
+            clear_sender();
+            let events = self
+                .worker
+                .join()
+                .map_err(|_| AutomationError::Internal("Macro recorder thread panicked".into()))?;
+
+            tracing::info!("Macro recording stopped ({} events)", events.len());
+
+            Ok(RecordedMacro {
+                events,
+                duration_ms,
+            })
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            Ok(RecordedMacro {
+                events: Vec::new(),
+                duration_ms,
+            })
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn clear_sender() {
+    if let Ok(mut sender) = HOOK_SENDER.lock() {
+        *sender = None;
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod hooks {
+    use super::*;
+    use crate::element::{ThreadSafeAutomation, UIElement};
+    use crate::window::WindowManager;
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+    use uiautomation::types::Point;
+    use uiautomation::UIAutomation;
+    use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::Threading::GetCurrentThreadId;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    /// Keyboard hook: forwards key presses not injected by software
+    unsafe extern "system" fn keyboard_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 {
+            let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+            if info.flags.0 & LLKHF_INJECTED.0 == 0 {
+                match wparam.0 as u32 {
+                    WM_KEYDOWN | WM_SYSKEYDOWN => forward_raw(RawInput::KeyDown(info.vkCode)),
+                    WM_KEYUP | WM_SYSKEYUP => forward_raw(RawInput::KeyUp(info.vkCode)),
+                    _ => {}
+                }
+            }
+        }
+        CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+    }
+
+    /// Mouse hook: forwards button presses not injected by software
+    unsafe extern "system" fn mouse_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 {
+            let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+            if info.flags & LLMHF_INJECTED == 0 {
+                let button = match wparam.0 as u32 {
+                    WM_LBUTTONDOWN => Some(MouseButton::Left),
+                    WM_RBUTTONDOWN => Some(MouseButton::Right),
+                    WM_MBUTTONDOWN => Some(MouseButton::Middle),
+                    _ => None,
+                };
+                if let Some(button) = button {
+                    forward_raw(RawInput::MouseDown {
+                        x: info.pt.x,
+                        y: info.pt.y,
+                        button,
+                    });
+                }
+            }
+        }
+        CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+    }
+
+    /// Spawn the thread owning the hooks and its message loop
+    ///
+    /// Low-level hooks are called on the installing thread, which must pump
+    /// messages for them to fire.
+    pub(super) fn spawn_hook_thread() -> Result<(u32, JoinHandle<()>), AutomationError> {
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let handle = std::thread::spawn(move || unsafe {
+            let keyboard =
+                SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook), HINSTANCE::default(), 0);
+            let mouse = SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook), HINSTANCE::default(), 0);
+
+            let (keyboard, mouse) = match (keyboard, mouse) {
+                (Ok(keyboard), Ok(mouse)) => (keyboard, mouse),
+                (keyboard, mouse) => {
+                    if let Ok(hook) = keyboard {
+                        let _ = UnhookWindowsHookEx(hook);
+                    }
+                    if let Ok(hook) = mouse {
+                        let _ = UnhookWindowsHookEx(hook);
+                    }
+                    let _ = ready_tx.send(Err(AutomationError::platform(
+                        "Failed to install input hooks",
+                    )));
+                    return;
+                }
+            };
+
+            let _ = ready_tx.send(Ok(GetCurrentThreadId()));
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+
+            let _ = UnhookWindowsHookEx(keyboard);
+            let _ = UnhookWindowsHookEx(mouse);
+        });
+
+        let thread_id = ready_rx
+            .recv()
+            .map_err(|_| AutomationError::Internal("Hook thread exited".to_string()))??;
+
+        Ok((thread_id, handle))
+    }
+
+    /// Ask the hook thread to leave its message loop
+    pub(super) fn stop_hook_thread(thread_id: u32) {
+        unsafe {
+            let _ = PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+        }
+    }
+
+    /// Convert raw input into macro events until the channel closes
+    ///
+    /// Clicks are resolved to the element under the cursor here rather than in
+    /// the hook, which must return quickly. The foreground window is polled to
+    /// record window switches.
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub(super) fn process_events(
+        rx: mpsc::Receiver<(Instant, RawInput)>,
+        started: Instant,
+    ) -> Vec<MacroEvent> {
+        let automation = UIAutomation::new()
+            .ok()
+            .map(|a| ThreadSafeAutomation(Arc::new(a)));
+        let windows = WindowManager::new();
+        let mut builder = EventBuilder::default();
+        let mut active_window = windows.get_active().ok().flatten().map(|w| w.handle);
+
+        loop {
+            match rx.recv_timeout(Duration::from_millis(100)) {
+                Ok((at, raw)) => {
+                    let offset_ms = at.saturating_duration_since(started).as_millis() as u64;
+                    match raw {
+                        RawInput::KeyDown(vk) => builder.key_down(vk, offset_ms),
+                        RawInput::KeyUp(vk) => builder.key_up(vk),
+                        RawInput::MouseDown { x, y, button } => {
+                            let element = automation.as_ref().and_then(|automation| {
+                                automation
+                                    .0
+                                    .element_from_point(Point::new(x, y))
+                                    .ok()
+                                    .map(|element| UIElement::new(element, automation))
+                            });
+                            builder.push(MacroEvent::Click {
+                                offset_ms,
+                                x,
+                                y,
+                                button: button_name(button).to_string(),
+                                element_name: element
+                                    .as_ref()
+                                    .and_then(|e| e.name())
+                                    .filter(|name| !name.is_empty()),
+                                element_role: element.as_ref().map(|e| e.role()),
+                            });
+                        }
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            if let Ok(Some(window)) = windows.get_active() {
+                if active_window != Some(window.handle) {
+                    active_window = Some(window.handle);
+                    builder.push(MacroEvent::WindowSwitch {
+                        offset_ms: started.elapsed().as_millis() as u64,
+                        title: window.title,
+                        process_name: window.process_name,
+                    });
+                }
+            }
+        }
+
+        builder.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_keys(builder: &mut EventBuilder, vks: &[u32], offset_ms: u64) {
+        for &vk in vks {
+            builder.key_down(vk, offset_ms);
+            builder.key_up(vk);
+        }
+    }
+
+    #[test]
+    fn test_text_is_coalesced() {
+        let mut builder = EventBuilder::default();
+        builder.key_down(VK_SHIFT, 0);
+        type_keys(&mut builder, &[0x48], 10); // H
+        builder.key_up(VK_SHIFT);
+        type_keys(&mut builder, &[0x49, VK_SPACE, 0x31], 20); // i 1
+        builder.key_down(VK_RETURN, 30);
+
+        assert_eq!(
+            builder.finish(),
+            vec![
+                MacroEvent::Text {
+                    offset_ms: 10,
+                    text: "Hi 1".to_string()
+                },
+                MacroEvent::Keys {
+                    offset_ms: 30,
+                    keys: "{ENTER}".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shortcuts_use_sendkeys_notation() {
+        let mut builder = EventBuilder::default();
+        builder.key_down(VK_LCONTROL, 0);
+        builder.key_down(VK_LSHIFT, 0);
+        builder.key_down(0x53, 5); // S
+        builder.key_up(VK_LSHIFT);
+        builder.key_down(0xDB, 6); // [
+        builder.key_up(VK_LCONTROL);
+        builder.key_down(VK_F1 + 4, 7);
+
+        let keys: Vec<String> = builder
+            .finish()
+            .into_iter()
+            .map(|e| match e {
+                MacroEvent::Keys { keys, .. } => keys,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(keys, vec!["^+s", "^{[}", "{F5}"]);
+    }
+
+    #[test]
+    fn test_click_flushes_pending_text() {
+        let mut builder = EventBuilder::default();
+        type_keys(&mut builder, &[0x41], 0);
+        builder.push(MacroEvent::Click {
+            offset_ms: 50,
+            x: 10,
+            y: 20,
+            button: "left".to_string(),
+            element_name: None,
+            element_role: None,
+        });
+
+        let events = builder.finish();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], MacroEvent::Text { .. }));
+        assert_eq!(events[1].offset_ms(), 50);
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let event = MacroEvent::Keys {
+            offset_ms: 120,
+            keys: "^c".to_string(),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"type":"keys","offset_ms":120,"keys":"^c"}"#);
+        assert_eq!(serde_json::from_str::<MacroEvent>(&json).unwrap(), event);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Provides a Playwright-inspired API for building selectors to find UI elements.
 
+use crate::errors::AutomationError;
 use std::collections::BTreeMap;
 use std::fmt;
 
@@ -16,12 +17,17 @@ pub enum Selector {
     Name(String),
     /// Select by visible text content
     Text(String),
-    /// Select by XPath-like path expression
+    /// Select by XPath-like path expression, resolved by walking the tree
+    ///
+    /// e.g. `window[name~="Visual Studio"]/pane/toolbar/button[3]`; see
+    /// [`parse_path`] for the syntax.
     Path(String),
     /// Select by multiple attributes
     Attributes(BTreeMap<String, String>),
     /// Chain multiple selectors (hierarchical search)
     Chain(Vec<Selector>),
+    /// Pick the nth (0-based) element matched by the preceding selectors of a chain
+    Nth(usize),
 }
 
 impl Selector {
@@ -29,7 +35,7 @@ impl Selector {
     ///
     /// # Example
     /// ```
-    /// use screen_automation::Selector;
+    /// use screensearch_automation::Selector;
     ///
     /// let button = Selector::role("button");
     /// let named_button = Selector::role("button").with_name("Submit");
@@ -59,6 +65,13 @@ impl Selector {
     }
 
     /// Create a selector by path expression
+    ///
+    /// # Example
+    /// ```
+    /// use screensearch_automation::Selector;
+    ///
+    /// let save = Selector::path(r#"window[name~="Notepad"]/toolbar/button[name="Save"]"#);
+    /// ```
     pub fn path(path: impl Into<String>) -> Self {
         Selector::Path(path.into())
     }
@@ -70,6 +83,55 @@ impl Selector {
         }
     }
 
+    /// Parse a selector string
+    ///
+    /// Parts separated by `>>` are chained, each searching within the matches
+    /// of the previous one. Each part is `engine=value`:
+    ///
+    /// - `role=button` or `role=button[name='Submit']`
+    /// - `id=btnSubmit` or `#btnSubmit`
+    /// - `name=OK`
+    /// - `text=Log in`
+    /// - `path=window/pane/button[2]`, or any part starting with `/` (see [`parse_path`])
+    /// - `nth=2`: the third element matched by the preceding parts
+    ///
+    /// `engine:value` is accepted as well, so the [`Display`](fmt::Display) form
+    /// of role, ID, name and text selectors parses back. A part without a known
+    /// engine is a name selector, so plain element names keep working.
+    ///
+    /// # Example
+    /// ```
+    /// use screensearch_automation::Selector;
+    ///
+    /// let selector = Selector::parse("role=button[name='Submit'] >> nth=2")?;
+    /// # Ok::<(), screensearch_automation::AutomationError>(())
+    /// ```
+    pub fn parse(input: &str) -> Result<Selector, AutomationError> {
+        let invalid = |reason: String| {
+            AutomationError::InvalidArgument(format!("Invalid selector '{}': {}", input, reason))
+        };
+
+        let mut selectors = Vec::new();
+        for part in split_chain(input) {
+            let part = part.trim();
+            if part.is_empty() {
+                return Err(invalid("empty selector".to_string()));
+            }
+
+            let selector = parse_part(part).map_err(invalid)?;
+            if matches!(selector, Selector::Nth(_)) && selectors.is_empty() {
+                return Err(invalid("nth must follow another selector".to_string()));
+            }
+            selectors.push(selector);
+        }
+
+        if selectors.len() == 1 {
+            Ok(selectors.remove(0))
+        } else {
+            Ok(Selector::Chain(selectors))
+        }
+    }
+
     /// Chain this selector with another (hierarchical search)
     pub fn then(self, next: Selector) -> Self {
         match self {
@@ -103,8 +165,9 @@ impl fmt::Display for Selector {
             }
             Selector::Chain(selectors) => {
                 let parts: Vec<_> = selectors.iter().map(|s| s.to_string()).collect();
-                write!(f, "{}", parts.join(" > "))
+                write!(f, "{}", parts.join(" >> "))
             }
+            Selector::Nth(index) => write!(f, "nth={}", index),
         }
     }
 }
@@ -142,6 +205,114 @@ impl From<String> for Selector {
     }
 }
 
+impl std::str::FromStr for Selector {
+    type Err = AutomationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Selector::parse(s)
+    }
+}
+
+/// Split a selector string on `>>`, ignoring it inside brackets and quoted values
+fn split_chain(input: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut previous = ' ';
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if q == c => quote = None,
+            (Some(_), _) => {}
+            // Quotes only open a value, so apostrophes in plain names are literal
+            (None, '"' | '\'') if depth > 0 || previous == '=' || previous == ':' => {
+                quote = Some(c)
+            }
+            (None, '[') => depth += 1,
+            (None, ']') => depth = depth.saturating_sub(1),
+            (None, '>') if depth == 0 && chars.next_if(|(_, c)| *c == '>').is_some() => {
+                parts.push(&input[start..i]);
+                start = i + 2;
+            }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            previous = c;
+        }
+    }
+
+    parts.push(&input[start..]);
+    parts
+}
+
+/// Parse one `engine=value` part of a selector string
+fn parse_part(part: &str) -> Result<Selector, String> {
+    if let Some(id) = part.strip_prefix('#') {
+        return Ok(Selector::Id(unquote(id.trim()).to_string()));
+    }
+    if part.starts_with('/') {
+        return parse_path_part(part);
+    }
+
+    let Some(separator) = part.find(['=', ':']) else {
+        return Ok(Selector::Name(part.to_string()));
+    };
+    let engine = part[..separator].trim().to_lowercase();
+    let value = part[separator + 1..].trim();
+
+    let known = matches!(
+        engine.as_str(),
+        "role" | "id" | "name" | "text" | "path" | "nth"
+    );
+    if !known {
+        return Ok(Selector::Name(part.to_string()));
+    }
+    if value.is_empty() {
+        return Err(format!("missing value for '{}'", engine));
+    }
+
+    match engine.as_str() {
+        "role" => parse_role(value),
+        "id" => Ok(Selector::Id(unquote(value).to_string())),
+        "name" => Ok(Selector::Name(unquote(value).to_string())),
+        "text" => Ok(Selector::Text(unquote(value).to_string())),
+        "path" => parse_path_part(value),
+        _ => value
+            .parse()
+            .map(Selector::Nth)
+            .map_err(|_| format!("invalid nth '{}': expected a 0-based index", value)),
+    }
+}
+
+/// Parse `button` or `button[name='Submit']` into a role selector
+fn parse_role(value: &str) -> Result<Selector, String> {
+    let mut steps = parse_path(value).map_err(|e| e.to_string())?;
+    let step = match (steps.len(), steps.pop()) {
+        (1, Some(step)) if !step.descendant => step,
+        _ => return Err(format!("invalid role '{}'", value)),
+    };
+    if step.id.is_some() || step.index.is_some() {
+        return Err("role only supports a name predicate".to_string());
+    }
+
+    let role = step
+        .role
+        .ok_or_else(|| "role cannot be a wildcard".to_string())?;
+    let name = step.name.map(|name| match name {
+        NameMatch::Exact(name) | NameMatch::Contains(name) => name,
+    });
+
+    Ok(Selector::Role { role, name })
+}
+
+/// Validate a path expression and wrap it in a path selector
+fn parse_path_part(path: &str) -> Result<Selector, String> {
+    parse_path(path).map_err(|e| e.to_string())?;
+    Ok(Selector::Path(path.to_string()))
+}
+
 /// Builder for role-based selectors
 #[derive(Debug, Clone)]
 pub struct SelectorBuilder {
@@ -198,6 +369,167 @@ impl From<AttributeSelectorBuilder> for Selector {
     }
 }
 
+/// One step of a [`Selector::Path`] expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathStep {
+    /// Match at any depth below the previous step (`//`) instead of direct children only
+    pub descendant: bool,
+    /// Element role, or `None` for the `*` wildcard
+    pub role: Option<String>,
+    /// Name constraint (`[name="..."]` or `[name~="..."]`)
+    pub name: Option<NameMatch>,
+    /// Automation ID constraint (`[id="..."]`)
+    pub id: Option<String>,
+    /// 1-based position among the elements matching the rest of the step (`[3]`)
+    pub index: Option<usize>,
+}
+
+/// Name constraint of a path step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameMatch {
+    /// Name equals the value (`name="..."`)
+    Exact(String),
+    /// Name contains the value, ignoring case (`name~="..."`)
+    Contains(String),
+}
+
+impl NameMatch {
+    /// Check whether an element name satisfies this constraint
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NameMatch::Exact(value) => name == value,
+            NameMatch::Contains(value) => name.to_lowercase().contains(&value.to_lowercase()),
+        }
+    }
+}
+
+/// Parse a path expression into its steps
+///
+/// Steps are separated by `/` (direct children) or `//` (any descendant) and
+/// start from the element the search runs on. Each step is a role, or `*` for
+/// any role, followed by optional predicates:
+///
+/// - `[name="Save"]`: name equals the value
+/// - `[name~="Visual Studio"]`: name contains the value, ignoring case
+/// - `[id="btnSave"]`: automation ID equals the value
+/// - `[3]`: the third element matching the rest of the step under the same parent
+///
+/// Values may be single- or double-quoted, and keys may be prefixed with `@`.
+pub fn parse_path(path: &str) -> Result<Vec<PathStep>, AutomationError> {
+    let invalid = |reason: &str| {
+        AutomationError::InvalidArgument(format!("Invalid path selector '{}': {}", path, reason))
+    };
+
+    let mut chars = path.trim().chars().peekable();
+    let mut steps = Vec::new();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut slashes = 0;
+        while chars.next_if_eq(&'/').is_some() {
+            slashes += 1;
+        }
+        if slashes > 2 {
+            return Err(invalid("too many '/'"));
+        }
+        if slashes == 0 && !steps.is_empty() {
+            return Err(invalid("expected '/' between steps"));
+        }
+
+        let mut role = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '[' && *c != '/') {
+            role.push(c);
+        }
+        let role = role.trim();
+        if role.is_empty() {
+            return Err(invalid("empty step"));
+        }
+
+        let mut step = PathStep {
+            descendant: slashes == 2,
+            role: (role != "*").then(|| role.to_string()),
+            name: None,
+            id: None,
+            index: None,
+        };
+
+        while chars.next_if_eq(&'[').is_some() {
+            let predicate =
+                read_predicate(&mut chars).ok_or_else(|| invalid("unterminated '['"))?;
+            apply_predicate(&mut step, &predicate).map_err(|reason| invalid(&reason))?;
+        }
+
+        steps.push(step);
+
+        if chars.peek().is_none() {
+            return Ok(steps);
+        }
+    }
+}
+
+/// Read a predicate up to its closing `]`, skipping brackets inside quotes
+fn read_predicate(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Option<String> {
+    let mut predicate = String::new();
+    let mut quote = None;
+
+    for c in chars.by_ref() {
+        match (quote, c) {
+            (None, ']') => return Some(predicate),
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            _ => {}
+        }
+        predicate.push(c);
+    }
+
+    None
+}
+
+/// Apply one predicate (the text between `[` and `]`) to a path step
+fn apply_predicate(step: &mut PathStep, predicate: &str) -> Result<(), String> {
+    let predicate = predicate.trim();
+
+    if let Ok(index) = predicate.parse::<usize>() {
+        if index == 0 {
+            return Err("positions start at 1".to_string());
+        }
+        step.index = Some(index);
+        return Ok(());
+    }
+
+    let eq = predicate
+        .find('=')
+        .ok_or_else(|| format!("unsupported predicate '[{}]'", predicate))?;
+    let (key, contains) = match predicate[..eq].strip_suffix('~') {
+        Some(key) => (key, true),
+        None => (&predicate[..eq], false),
+    };
+    let value = unquote(predicate[eq + 1..].trim()).to_string();
+
+    match key.trim().trim_start_matches('@').to_lowercase().as_str() {
+        "name" if contains => step.name = Some(NameMatch::Contains(value)),
+        "name" => step.name = Some(NameMatch::Exact(value)),
+        "id" | "automationid" if !contains => step.id = Some(value),
+        other => return Err(format!("unsupported predicate key '{}'", other)),
+    }
+
+    Ok(())
+}
+
+/// Strip matching single or double quotes around a value
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -264,4 +596,135 @@ mod tests {
 
         assert_eq!(Selector::id("myId").to_string(), "#myId");
     }
+
+    #[test]
+    fn test_parse_path() {
+        let steps = parse_path(r#"window[name~="Visual Studio"]/pane/toolbar/button[3]"#).unwrap();
+
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[0].role.as_deref(), Some("window"));
+        assert_eq!(
+            steps[0].name,
+            Some(NameMatch::Contains("Visual Studio".to_string()))
+        );
+        assert_eq!(steps[1].role.as_deref(), Some("pane"));
+        assert_eq!(steps[3].role.as_deref(), Some("button"));
+        assert_eq!(steps[3].index, Some(3));
+        assert!(steps.iter().all(|step| !step.descendant));
+    }
+
+    #[test]
+    fn test_parse_path_predicates() {
+        let steps = parse_path(r#"//*[@id='main']//button[name="Save / Close"][2]"#).unwrap();
+
+        assert_eq!(steps.len(), 2);
+        assert!(steps[0].descendant);
+        assert_eq!(steps[0].role, None);
+        assert_eq!(steps[0].id.as_deref(), Some("main"));
+        assert!(steps[1].descendant);
+        assert_eq!(
+            steps[1].name,
+            Some(NameMatch::Exact("Save / Close".to_string()))
+        );
+        assert_eq!(steps[1].index, Some(2));
+    }
+
+    #[test]
+    fn test_parse_path_errors() {
+        assert!(parse_path("").is_err());
+        assert!(parse_path("window/").is_err());
+        assert!(parse_path("window///button").is_err());
+        assert!(parse_path("button[0]").is_err());
+        assert!(parse_path("button[name='Save'").is_err());
+        assert!(parse_path("button[enabled=true]").is_err());
+        assert!(parse_path("button[1]pane").is_err());
+    }
+
+    #[test]
+    fn test_name_match() {
+        assert!(NameMatch::Contains("visual studio".to_string()).matches("Microsoft Visual Studio"));
+        assert!(NameMatch::Exact("Save".to_string()).matches("Save"));
+        assert!(!NameMatch::Exact("Save".to_string()).matches("Save As"));
+    }
+
+    #[test]
+    fn test_selector_parse() {
+        assert_eq!(
+            Selector::parse("role=button[name='Submit'] >> nth=2").unwrap(),
+            Selector::Chain(vec![
+                Selector::Role {
+                    role: "button".to_string(),
+                    name: Some("Submit".to_string()),
+                },
+                Selector::Nth(2),
+            ])
+        );
+        assert_eq!(
+            Selector::parse("id=btnSave").unwrap(),
+            Selector::Id("btnSave".to_string())
+        );
+        assert_eq!(
+            Selector::parse("#btnSave").unwrap(),
+            Selector::Id("btnSave".to_string())
+        );
+        assert_eq!(
+            Selector::parse(r#"text="a >> b""#).unwrap(),
+            Selector::Text("a >> b".to_string())
+        );
+        assert_eq!(
+            Selector::parse("//toolbar/button[2]").unwrap(),
+            Selector::Path("//toolbar/button[2]".to_string())
+        );
+        assert_eq!(
+            Selector::parse("role=window >> path=pane/button[name='>>']").unwrap(),
+            Selector::Chain(vec![
+                Selector::Role {
+                    role: "window".to_string(),
+                    name: None,
+                },
+                Selector::Path("pane/button[name='>>']".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_selector_parse_plain_names() {
+        assert_eq!(
+            Selector::parse("Save As").unwrap(),
+            Selector::Name("Save As".to_string())
+        );
+        assert_eq!(
+            Selector::parse("Note: it's saved").unwrap(),
+            Selector::Name("Note: it's saved".to_string())
+        );
+    }
+
+    #[test]
+    fn test_selector_parse_errors() {
+        assert!(Selector::parse("").is_err());
+        assert!(Selector::parse("nth=1").is_err());
+        assert!(Selector::parse("role=button >> ").is_err());
+        assert!(Selector::parse("role=").is_err());
+        assert!(Selector::parse("role=button >> nth=first").is_err());
+        assert!(Selector::parse("role=*").is_err());
+        assert!(Selector::parse("role=button[2]").is_err());
+        assert!(Selector::parse("path=window/").is_err());
+    }
+
+    #[test]
+    fn test_selector_display_round_trip() {
+        let selectors = [
+            Selector::role("button").with_name("Submit").build(),
+            Selector::id("myId"),
+            Selector::text("Log in"),
+            Selector::role("list")
+                .build()
+                .then(Selector::role("listitem").build())
+                .then(Selector::Nth(0)),
+        ];
+
+        for selector in selectors {
+            assert_eq!(Selector::parse(&selector.to_string()).unwrap(), selector);
+        }
+    }
 }
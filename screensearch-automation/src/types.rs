@@ -0,0 +1,90 @@
+//! Element data shared by the platform backends
+//!
+//! The UIAutomation (Windows) and AT-SPI (Linux) implementations of
+//! [`UIElement`](crate::UIElement) report their results in these types.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Result of a click operation
+#[derive(Debug, Clone)]
+pub struct ClickResult {
+    /// Method used to perform the click
+    pub method: String,
+    /// Coordinates where the click occurred
+    pub coordinates: Option<(f64, f64)>,
+    /// Additional details about the operation
+    pub details: String,
+}
+
+/// Attributes of a UI element
+#[derive(Debug, Clone)]
+pub struct UIElementAttributes {
+    /// Element role (control type)
+    pub role: String,
+    /// Element label
+    pub label: Option<String>,
+    /// Element value
+    pub value: Option<String>,
+    /// Element description
+    pub description: Option<String>,
+    /// Additional properties
+    pub properties: HashMap<String, Option<serde_json::Value>>,
+}
+
+/// Serializable snapshot of an element and its subtree
+#[derive(Debug, Clone, Serialize)]
+pub struct ElementNode {
+    /// Element role (control type)
+    pub role: String,
+    /// Element name, if not empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Automation ID, if not empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub automation_id: Option<String>,
+    /// Bounding rectangle (x, y, width, height), if available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<(f64, f64, f64, f64)>,
+    /// Supported control patterns (e.g. "Invoke", "Value")
+    pub patterns: Vec<String>,
+    /// Whether the element is enabled
+    pub is_enabled: bool,
+    /// Whether the element is on screen
+    pub is_visible: bool,
+    /// Child elements, up to the requested depth
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ElementNode>,
+}
+
+/// Contents of a grid or table element
+#[derive(Debug, Clone, Serialize)]
+pub struct TableData {
+    /// Column headers, empty when the element exposes none
+    pub headers: Vec<String>,
+    /// Cell text, row by row
+    pub rows: Vec<Vec<String>>,
+    /// Total number of rows in the element, including rows not extracted
+    pub row_count: usize,
+    /// Number of columns in the element
+    pub column_count: usize,
+}
+
+/// Option to pick in a combo box or list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectOption {
+    /// Option whose name matches, exactly (ignoring case) or else partially
+    Text(String),
+    /// Option at this 0-based position
+    Index(usize),
+}
+
+impl fmt::Display for SelectOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectOption::Text(text) => write!(f, "'{}'", text),
+            SelectOption::Index(index) => write!(f, "#{}", index),
+        }
+    }
+}
@@ -0,0 +1,523 @@
+//! Virtual desktop support
+//!
+//! Windows spreads top-level windows over virtual desktops. Windows on a
+//! desktop other than the current one are still enumerated and found by
+//! UIAutomation, but they are cloaked: clicks land on whatever is shown
+//! instead and `SetForegroundWindow` fails, so automation aimed at them
+//! silently does nothing.
+//!
+//! The desktop a window lives on comes from the documented
+//! `IVirtualDesktopManager` interface. Windows has no public API to list or
+//! switch desktops, so the list is read from Explorer's state in the registry
+//! and switching sends the Ctrl+Win+Left/Right shortcut.
+
+use crate::errors::AutomationError;
+use serde::Serialize;
+
+/// A virtual desktop
+#[derive(Debug, Clone, Serialize)]
+pub struct VirtualDesktop {
+    /// Desktop GUID (e.g. "5C2F2C8A-4D0B-4B7E-9E0B-6C5A2D1F0E3A")
+    pub id: String,
+    /// Position in the task view, starting at 0
+    pub index: usize,
+    /// Name given in the task view, or "Desktop N"
+    pub name: String,
+    /// Whether this desktop is shown
+    pub is_current: bool,
+}
+
+/// Parse a desktop GUID, with or without braces, into its in-memory layout
+fn parse_id(id: &str) -> Option<[u8; 16]> {
+    let id = id.trim().trim_start_matches('{').trim_end_matches('}');
+    let bytes = id.as_bytes();
+    if bytes.len() != 36 || [8, 13, 18, 23].iter().any(|&i| bytes[i] != b'-') {
+        return None;
+    }
+
+    let hex: String = id.chars().filter(|&c| c != '-').collect();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| u32::from_str_radix(&hex[range], 16).ok();
+
+    // GUIDs store their first three fields little-endian
+    let mut guid = [0u8; 16];
+    guid[0..4].copy_from_slice(&field(0..8)?.to_le_bytes());
+    guid[4..6].copy_from_slice(&(field(8..12)? as u16).to_le_bytes());
+    guid[6..8].copy_from_slice(&(field(12..16)? as u16).to_le_bytes());
+    for (i, byte) in guid[8..].iter_mut().enumerate() {
+        *byte = field(16 + 2 * i..18 + 2 * i)? as u8;
+    }
+    Some(guid)
+}
+
+/// Format a GUID from its in-memory layout
+fn format_id(guid: &[u8; 16]) -> String {
+    format!(
+        "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+        u16::from_le_bytes([guid[4], guid[5]]),
+        u16::from_le_bytes([guid[6], guid[7]]),
+        guid[8],
+        guid[9],
+        guid[10],
+        guid[11],
+        guid[12],
+        guid[13],
+        guid[14],
+        guid[15]
+    )
+}
+
+/// Split Explorer's `VirtualDesktopIDs` value into desktop IDs, in task view order
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_id_list(data: &[u8]) -> Vec<String> {
+    data.chunks_exact(16)
+        .map(|chunk| {
+            let mut guid = [0u8; 16];
+            guid.copy_from_slice(chunk);
+            format_id(&guid)
+        })
+        .collect()
+}
+
+/// Find a desktop by ID, ignoring case and braces
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn find<'a>(
+    desktops: &'a [VirtualDesktop],
+    id: &str,
+) -> Result<&'a VirtualDesktop, AutomationError> {
+    let wanted = parse_id(id).ok_or_else(|| {
+        AutomationError::InvalidArgument(format!("Invalid virtual desktop ID '{}'", id))
+    })?;
+    desktops
+        .iter()
+        .find(|desktop| parse_id(&desktop.id) == Some(wanted))
+        .ok_or_else(|| {
+            AutomationError::InvalidArgument(format!("No virtual desktop with ID {}", id))
+        })
+}
+
+/// List virtual desktops in task view order
+pub(crate) fn list() -> Result<Vec<VirtualDesktop>, AutomationError> {
+    #[cfg(target_os = "windows")]
+    {
+        platform::list()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(AutomationError::UnsupportedOperation(
+            "Virtual desktops are only supported on Windows".to_string(),
+        ))
+    }
+}
+
+/// Desktop ID of a window and whether it is on the current desktop
+///
+/// `None` when the shell does not track the window.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn window_desktop(handle: usize) -> Option<(String, bool)> {
+    #[cfg(target_os = "windows")]
+    {
+        platform::window_desktop(windows::Win32::Foundation::HWND(handle as isize))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = handle;
+        None
+    }
+}
+
+/// Show another virtual desktop
+///
+/// Returns once Explorer reports the desktop as current.
+pub(crate) fn switch_to(id: &str) -> Result<(), AutomationError> {
+    #[cfg(target_os = "windows")]
+    {
+        let desktops = list()?;
+        platform::switch_to(&desktops, find(&desktops, id)?)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = id;
+        Err(AutomationError::UnsupportedOperation(
+            "Virtual desktops are only supported on Windows".to_string(),
+        ))
+    }
+}
+
+/// Move a top-level window to another virtual desktop
+///
+/// `handle` must be a valid window handle.
+#[cfg(target_os = "windows")]
+pub(crate) fn move_window(handle: usize, id: &str) -> Result<(), AutomationError> {
+    let desktops = list()?;
+    platform::move_window(handle, &desktops, find(&desktops, id)?)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use windows::core::{GUID, HSTRING, PCWSTR};
+    use windows::Win32::Foundation::{ERROR_SUCCESS, E_ACCESSDENIED, HWND};
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+    use windows::Win32::System::Registry::{
+        RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_BINARY, RRF_RT_REG_SZ,
+    };
+    use windows::Win32::UI::Shell::{IVirtualDesktopManager, VirtualDesktopManager};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, ShowWindow, SW_HIDE, SW_SHOWNA,
+    };
+
+    const DESKTOPS_KEY: &str =
+        "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\VirtualDesktops";
+    /// Pause between desktop shortcuts so Explorer registers each one
+    const SWITCH_INTERVAL: Duration = Duration::from_millis(150);
+    /// How long to wait for a switch to show up in Explorer's state
+    const SWITCH_TIMEOUT: Duration = Duration::from_secs(2);
+
+    thread_local! {
+        static MANAGER: Option<IVirtualDesktopManager> = unsafe {
+            // Already initialized (in either mode) is fine
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            CoCreateInstance(&VirtualDesktopManager, None, CLSCTX_ALL)
+                .map_err(|e| tracing::warn!("Virtual desktop manager unavailable: {}", e))
+                .ok()
+        };
+    }
+
+    fn with_manager<T>(
+        f: impl FnOnce(&IVirtualDesktopManager) -> windows::core::Result<T>,
+    ) -> Result<T, AutomationError> {
+        MANAGER.with(|manager| match manager {
+            Some(manager) => f(manager).map_err(AutomationError::platform),
+            None => Err(AutomationError::platform(
+                "Virtual desktop manager unavailable",
+            )),
+        })
+    }
+
+    fn guid_bytes(guid: &GUID) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        bytes[0..4].copy_from_slice(&guid.data1.to_le_bytes());
+        bytes[4..6].copy_from_slice(&guid.data2.to_le_bytes());
+        bytes[6..8].copy_from_slice(&guid.data3.to_le_bytes());
+        bytes[8..].copy_from_slice(&guid.data4);
+        bytes
+    }
+
+    fn guid_from_id(id: &str) -> Option<GUID> {
+        let bytes = parse_id(id)?;
+        Some(GUID::from_values(
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u16::from_le_bytes([bytes[4], bytes[5]]),
+            u16::from_le_bytes([bytes[6], bytes[7]]),
+            bytes[8..].try_into().ok()?,
+        ))
+    }
+
+    pub(super) fn window_desktop(hwnd: HWND) -> Option<(String, bool)> {
+        let guid = with_manager(|manager| unsafe { manager.GetWindowDesktopId(hwnd) }).ok()?;
+        if guid == GUID::zeroed() {
+            return None;
+        }
+        let is_current =
+            with_manager(|manager| unsafe { manager.IsWindowOnCurrentVirtualDesktop(hwnd) })
+                .map(|current| current.as_bool())
+                .unwrap_or(true);
+        Some((format_id(&guid_bytes(&guid)), is_current))
+    }
+
+    /// Read a registry value under `HKEY_CURRENT_USER`
+    fn read_value(subkey: &str, value: &str, binary: bool) -> Option<Vec<u8>> {
+        let subkey = HSTRING::from(subkey);
+        let value = HSTRING::from(value);
+        let flags = if binary {
+            RRF_RT_REG_BINARY
+        } else {
+            RRF_RT_REG_SZ
+        };
+
+        unsafe {
+            let mut size = 0u32;
+            let status = RegGetValueW(
+                HKEY_CURRENT_USER,
+                PCWSTR(subkey.as_ptr()),
+                PCWSTR(value.as_ptr()),
+                flags,
+                None,
+                None,
+                Some(&mut size),
+            );
+            if status != ERROR_SUCCESS || size == 0 {
+                return None;
+            }
+
+            let mut data = vec![0u8; size as usize];
+            let status = RegGetValueW(
+                HKEY_CURRENT_USER,
+                PCWSTR(subkey.as_ptr()),
+                PCWSTR(value.as_ptr()),
+                flags,
+                None,
+                Some(data.as_mut_ptr().cast()),
+                Some(&mut size),
+            );
+            if status != ERROR_SUCCESS {
+                return None;
+            }
+            data.truncate(size as usize);
+            Some(data)
+        }
+    }
+
+    fn desktop_name(id: &str) -> Option<String> {
+        let data = read_value(
+            &format!("{}\\Desktops\\{{{}}}", DESKTOPS_KEY, id),
+            "Name",
+            false,
+        )?;
+        let units: Vec<u16> = data
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+        let name = String::from_utf16_lossy(&units);
+        (!name.is_empty()).then_some(name)
+    }
+
+    /// ID of the shown desktop
+    ///
+    /// Explorer only records it once a second desktop has been created, so
+    /// the foreground window's desktop is used otherwise.
+    fn current_id() -> Option<String> {
+        if let Some(data) = read_value(DESKTOPS_KEY, "CurrentVirtualDesktop", true) {
+            if let Some(id) = parse_id_list(&data).into_iter().next() {
+                return Some(id);
+            }
+        }
+        let foreground = unsafe { GetForegroundWindow() };
+        window_desktop(foreground)
+            .filter(|(_, is_current)| *is_current)
+            .map(|(id, _)| id)
+    }
+
+    pub(super) fn list() -> Result<Vec<VirtualDesktop>, AutomationError> {
+        let current = current_id();
+        let mut ids = read_value(DESKTOPS_KEY, "VirtualDesktopIDs", true)
+            .map(|data| parse_id_list(&data))
+            .unwrap_or_default();
+        if ids.is_empty() {
+            // Only one desktop exists
+            ids.extend(current.clone());
+        }
+        if ids.is_empty() {
+            return Err(AutomationError::platform(
+                "Could not determine the virtual desktops",
+            ));
+        }
+
+        Ok(ids
+            .into_iter()
+            .enumerate()
+            .map(|(index, id)| VirtualDesktop {
+                name: desktop_name(&id).unwrap_or_else(|| format!("Desktop {}", index + 1)),
+                is_current: current.as_deref() == Some(id.as_str()),
+                index,
+                id,
+            })
+            .collect())
+    }
+
+    pub(super) fn switch_to(
+        desktops: &[VirtualDesktop],
+        target: &VirtualDesktop,
+    ) -> Result<(), AutomationError> {
+        let current = desktops
+            .iter()
+            .find(|desktop| desktop.is_current)
+            .ok_or_else(|| {
+                AutomationError::InvalidState("Could not determine the current desktop".to_string())
+            })?;
+        if current.index == target.index {
+            return Ok(());
+        }
+
+        let right = target.index > current.index;
+        for _ in 0..current.index.abs_diff(target.index) {
+            send_switch_shortcut(right)?;
+            std::thread::sleep(SWITCH_INTERVAL);
+        }
+
+        let deadline = Instant::now() + SWITCH_TIMEOUT;
+        loop {
+            if current_id().as_deref() == Some(target.id.as_str()) {
+                tracing::info!("Switched to virtual desktop {}", target.name);
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(AutomationError::timeout(
+                    format!("Switching to virtual desktop {}", target.name),
+                    SWITCH_TIMEOUT.as_millis() as u64,
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    pub(super) fn move_window(
+        handle: usize,
+        desktops: &[VirtualDesktop],
+        target: &VirtualDesktop,
+    ) -> Result<(), AutomationError> {
+        let hwnd = HWND(handle as isize);
+        let guid = guid_from_id(&target.id).ok_or_else(|| {
+            AutomationError::Internal(format!("Unparseable desktop ID {}", target.id))
+        })?;
+
+        let moved = MANAGER.with(|manager| {
+            manager
+                .as_ref()
+                .map(|manager| unsafe { manager.MoveWindowToDesktop(hwnd, &guid) })
+        });
+        match moved {
+            Some(Ok(())) => return Ok(()),
+            Some(Err(e)) if e.code() != E_ACCESSDENIED => {
+                return Err(AutomationError::platform(e));
+            }
+            _ => {}
+        }
+
+        // The shell only lets a process move its own windows. A window shown
+        // again after being hidden joins the current desktop, so hide it,
+        // show it on the target desktop and switch back.
+        tracing::debug!(
+            "Moving window {} by re-showing it on {}",
+            handle,
+            target.name
+        );
+        let original = desktops.iter().find(|desktop| desktop.is_current);
+        unsafe {
+            let _ = ShowWindow(hwnd, SW_HIDE);
+        }
+        let switched = switch_to(desktops, target);
+        unsafe {
+            let _ = ShowWindow(hwnd, SW_SHOWNA);
+        }
+        switched?;
+        if let Some(original) = original {
+            switch_to(&list()?, original)?;
+        }
+
+        match window_desktop(hwnd) {
+            Some((id, _)) if id == target.id => Ok(()),
+            _ => Err(AutomationError::platform(format!(
+                "Window {} could not be moved to virtual desktop {}",
+                handle, target.name
+            ))),
+        }
+    }
+
+    /// Press Ctrl+Win+Right (or Left) to show the adjacent desktop
+    fn send_switch_shortcut(right: bool) -> Result<(), AutomationError> {
+        use windows::Win32::UI::Input::KeyboardAndMouse::{
+            SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+            KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, VIRTUAL_KEY, VK_LCONTROL, VK_LEFT, VK_LWIN,
+            VK_RIGHT,
+        };
+
+        let key_event = |key: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS| INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: key,
+                    wScan: 0,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+        let arrow = if right { VK_RIGHT } else { VK_LEFT };
+        let inputs = [
+            key_event(VK_LCONTROL, KEYBD_EVENT_FLAGS(0)),
+            key_event(VK_LWIN, KEYEVENTF_EXTENDEDKEY),
+            key_event(arrow, KEYEVENTF_EXTENDEDKEY),
+            key_event(arrow, KEYEVENTF_EXTENDEDKEY | KEYEVENTF_KEYUP),
+            key_event(VK_LWIN, KEYEVENTF_EXTENDEDKEY | KEYEVENTF_KEYUP),
+            key_event(VK_LCONTROL, KEYEVENTF_KEYUP),
+        ];
+
+        let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+        if sent as usize == inputs.len() {
+            Ok(())
+        } else {
+            Err(AutomationError::platform(
+                "SendInput failed for the desktop switch shortcut",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "5C2F2C8A-4D0B-4B7E-9E0B-6C5A2D1F0E3A";
+
+    #[test]
+    fn test_id_round_trip() {
+        let guid = parse_id(ID).unwrap();
+        // First field is stored little-endian
+        assert_eq!(&guid[..4], &[0x8A, 0x2C, 0x2F, 0x5C]);
+        assert_eq!(format_id(&guid), ID);
+
+        assert_eq!(parse_id(&format!("{{{}}}", ID.to_lowercase())), Some(guid));
+        assert_eq!(parse_id("5C2F2C8A4D0B4B7E9E0B6C5A2D1F0E3A"), None);
+        assert_eq!(parse_id("not-a-guid"), None);
+    }
+
+    #[test]
+    fn test_parse_id_list() {
+        let mut data = parse_id(ID).unwrap().to_vec();
+        data.extend([0u8; 16]);
+        // Trailing partial entries are ignored
+        data.extend([1u8; 3]);
+
+        let ids = parse_id_list(&data);
+        assert_eq!(
+            ids,
+            vec![
+                ID.to_string(),
+                "00000000-0000-0000-0000-000000000000".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_desktop() {
+        let desktops = vec![VirtualDesktop {
+            id: ID.to_string(),
+            index: 0,
+            name: "Desktop 1".to_string(),
+            is_current: true,
+        }];
+
+        assert_eq!(find(&desktops, &ID.to_lowercase()).unwrap().index, 0);
+        assert!(matches!(
+            find(&desktops, "00000000-0000-0000-0000-000000000000"),
+            Err(AutomationError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            find(&desktops, "desktop 1"),
+            Err(AutomationError::InvalidArgument(_))
+        ));
+    }
+}
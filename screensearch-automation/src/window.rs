@@ -1,6 +1,9 @@
 //! Window management utilities
 
 use crate::errors::AutomationError;
+#[cfg(target_os = "linux")]
+use crate::linux::xdo;
+use crate::virtual_desktop::{self, VirtualDesktop};
 use serde::{Deserialize, Serialize};
 
 /// Information about a window
@@ -10,6 +13,8 @@ pub struct WindowInfo {
     pub title: String,
     /// Process name
     pub process_name: String,
+    /// Process ID
+    pub process_id: u32,
     /// X coordinate
     pub x: i32,
     /// Y coordinate
@@ -22,11 +27,26 @@ pub struct WindowInfo {
     pub is_visible: bool,
     /// Is minimized
     pub is_minimized: bool,
+    /// Is maximized
+    pub is_maximized: bool,
+    /// ID of the virtual desktop the window is on, if the shell tracks it
+    #[serde(default)]
+    pub desktop_id: Option<String>,
+    /// Whether the window is on the current virtual desktop
+    ///
+    /// Windows on other desktops are hidden, so input aimed at them does not
+    /// reach them until their desktop is shown.
+    #[serde(default = "default_on_current_desktop")]
+    pub on_current_desktop: bool,
     /// Window handle (HWND as usize)
     #[serde(skip)]
     pub handle: usize,
 }
 
+fn default_on_current_desktop() -> bool {
+    true
+}
+
 /// Window manager for enumerating and managing windows
 pub struct WindowManager {}
 
@@ -38,7 +58,8 @@ impl WindowManager {
 
     /// Enumerate all visible windows
     ///
-    /// Returns a list of windows that are visible and not tool windows
+    /// Returns a list of windows that are visible and not tool windows,
+    /// including windows on other virtual desktops
     pub fn enumerate(&self) -> Result<Vec<WindowInfo>, AutomationError> {
         #[cfg(target_os = "windows")]
         {
@@ -61,7 +82,16 @@ impl WindowManager {
             }
         }
 
-        #[cfg(not(target_os = "windows"))]
+        #[cfg(target_os = "linux")]
+        {
+            // Windows that close mid-enumeration are skipped
+            Ok(xdo::visible_windows()?
+                .into_iter()
+                .filter_map(|id| xdo::window_info(id).ok())
+                .collect())
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
         {
             Err(AutomationError::UnsupportedOperation(
                 "Window enumeration only supported on Windows".to_string(),
@@ -85,7 +115,15 @@ impl WindowManager {
             }
         }
 
-        #[cfg(not(target_os = "windows"))]
+        #[cfg(target_os = "linux")]
+        {
+            match xdo::active_window()? {
+                Some(id) => xdo::window_info(id).map(Some),
+                None => Ok(None),
+            }
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
         {
             Err(AutomationError::UnsupportedOperation(
                 "Window operations only supported on Windows".to_string(),
@@ -115,15 +153,46 @@ impl WindowManager {
             .collect())
     }
 
+    /// Get information about a window by handle
+    pub fn get_window(&self, handle: usize) -> Result<WindowInfo, AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            unsafe { get_window_info(valid_hwnd(handle)?) }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            xdo::window_info(handle as u64)
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            Err(AutomationError::UnsupportedOperation(
+                "Window operations only supported on Windows".to_string(),
+            ))
+        }
+    }
+
     /// Focus (activate) a window by handle
+    ///
+    /// Minimized windows are restored first. A window on another virtual
+    /// desktop is brought into view by switching to its desktop.
     pub fn focus_window(&self, handle: usize) -> Result<(), AutomationError> {
         #[cfg(target_os = "windows")]
         {
-            use windows::Win32::Foundation::HWND;
-            use windows::Win32::UI::WindowsAndMessaging::SetForegroundWindow;
+            use windows::Win32::UI::WindowsAndMessaging::{
+                IsIconic, SetForegroundWindow, ShowWindow, SW_RESTORE,
+            };
 
             unsafe {
-                let hwnd = HWND(handle as isize);
+                let hwnd = valid_hwnd(handle)?;
+                if let Some((desktop_id, false)) = virtual_desktop::window_desktop(handle) {
+                    virtual_desktop::switch_to(&desktop_id)?;
+                }
+                if IsIconic(hwnd).as_bool() {
+                    let _ = ShowWindow(hwnd, SW_RESTORE);
+                }
+
                 let result = SetForegroundWindow(hwnd);
                 if result.as_bool() {
                     Ok(())
@@ -133,8 +202,212 @@ impl WindowManager {
             }
         }
 
+        #[cfg(target_os = "linux")]
+        {
+            xdo::window_info(handle as u64)?;
+            xdo::window_command(handle as u64, "windowactivate", &[])
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            Err(AutomationError::UnsupportedOperation(
+                "Window operations only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    /// Move and resize a window
+    ///
+    /// Fields left as `None` keep their current value. A minimized or
+    /// maximized window is restored first so the new bounds take effect.
+    pub fn move_window(
+        &self,
+        handle: usize,
+        x: Option<i32>,
+        y: Option<i32>,
+        width: Option<i32>,
+        height: Option<i32>,
+    ) -> Result<WindowInfo, AutomationError> {
+        if width.is_some_and(|w| w <= 0) || height.is_some_and(|h| h <= 0) {
+            return Err(AutomationError::InvalidArgument(
+                "Window width and height must be positive".to_string(),
+            ));
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::Foundation::HWND;
+            use windows::Win32::UI::WindowsAndMessaging::{
+                IsIconic, IsZoomed, SetWindowPos, ShowWindow, SWP_NOACTIVATE, SWP_NOZORDER,
+                SW_RESTORE,
+            };
+
+            unsafe {
+                let hwnd = valid_hwnd(handle)?;
+                if IsIconic(hwnd).as_bool() || IsZoomed(hwnd).as_bool() {
+                    let _ = ShowWindow(hwnd, SW_RESTORE);
+                }
+
+                let current = get_window_info(hwnd)?;
+                SetWindowPos(
+                    hwnd,
+                    HWND(0),
+                    x.unwrap_or(current.x),
+                    y.unwrap_or(current.y),
+                    width.unwrap_or(current.width),
+                    height.unwrap_or(current.height),
+                    SWP_NOZORDER | SWP_NOACTIVATE,
+                )
+                .map_err(AutomationError::platform)?;
+
+                get_window_info(hwnd)
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let id = handle as u64;
+            let current = xdo::window_info(id)?;
+            let size = [
+                width.unwrap_or(current.width).to_string(),
+                height.unwrap_or(current.height).to_string(),
+            ];
+            let position = [
+                x.unwrap_or(current.x).to_string(),
+                y.unwrap_or(current.y).to_string(),
+            ];
+            xdo::window_command(id, "windowsize", &size)?;
+            xdo::window_command(id, "windowmove", &position)?;
+
+            xdo::window_info(id)
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            Err(AutomationError::UnsupportedOperation(
+                "Window operations only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    /// Minimize a window
+    pub fn minimize_window(&self, handle: usize) -> Result<(), AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            show_window(handle, windows::Win32::UI::WindowsAndMessaging::SW_MINIMIZE)
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            xdo::window_info(handle as u64)?;
+            xdo::window_command(handle as u64, "windowminimize", &[])
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            Err(AutomationError::UnsupportedOperation(
+                "Window operations only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    /// Maximize a window
+    pub fn maximize_window(&self, handle: usize) -> Result<(), AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            show_window(handle, windows::Win32::UI::WindowsAndMessaging::SW_MAXIMIZE)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = handle;
+            Err(AutomationError::UnsupportedOperation(
+                "Window operations only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    /// Restore a minimized or maximized window to its normal size
+    pub fn restore_window(&self, handle: usize) -> Result<(), AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            show_window(handle, windows::Win32::UI::WindowsAndMessaging::SW_RESTORE)
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = handle;
+            Err(AutomationError::UnsupportedOperation(
+                "Window operations only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    /// Ask a window to close
+    ///
+    /// Posts `WM_CLOSE` (on Linux, a window manager close request), so the
+    /// application may still prompt (e.g. to save unsaved changes) or refuse.
+    pub fn close_window(&self, handle: usize) -> Result<(), AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::Foundation::{LPARAM, WPARAM};
+            use windows::Win32::UI::WindowsAndMessaging::{PostMessageW, WM_CLOSE};
+
+            unsafe {
+                let hwnd = valid_hwnd(handle)?;
+                PostMessageW(hwnd, WM_CLOSE, WPARAM(0), LPARAM(0))
+                    .map_err(AutomationError::platform)
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            xdo::window_info(handle as u64)?;
+            xdo::window_command(handle as u64, "windowclose", &[])
+        }
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        {
+            Err(AutomationError::UnsupportedOperation(
+                "Window operations only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    /// List virtual desktops in task view order
+    pub fn virtual_desktops(&self) -> Result<Vec<VirtualDesktop>, AutomationError> {
+        virtual_desktop::list()
+    }
+
+    /// Show another virtual desktop by ID
+    ///
+    /// Returns an `InvalidArgument` error if no desktop has that ID.
+    pub fn switch_virtual_desktop(&self, desktop_id: &str) -> Result<(), AutomationError> {
+        virtual_desktop::switch_to(desktop_id)
+    }
+
+    /// Move a window to another virtual desktop
+    ///
+    /// Windows only lets a process move its own windows directly. Windows of
+    /// other processes are hidden, shown again on the target desktop and the
+    /// original desktop is restored, which briefly switches desktops.
+    pub fn move_window_to_desktop(
+        &self,
+        handle: usize,
+        desktop_id: &str,
+    ) -> Result<WindowInfo, AutomationError> {
+        #[cfg(target_os = "windows")]
+        {
+            unsafe {
+                let hwnd = valid_hwnd(handle)?;
+                virtual_desktop::move_window(handle, desktop_id)?;
+                get_window_info(hwnd)
+            }
+        }
+
         #[cfg(not(target_os = "windows"))]
         {
+            let _ = (handle, desktop_id);
             Err(AutomationError::UnsupportedOperation(
                 "Window operations only supported on Windows".to_string(),
             ))
@@ -225,22 +498,54 @@ mod windows_impl {
             "Unknown".to_string()
         };
 
-        // Check if minimized
+        // Check if minimized or maximized
         let is_minimized = IsIconic(hwnd).as_bool();
+        let is_maximized = IsZoomed(hwnd).as_bool();
+
+        let desktop = virtual_desktop::window_desktop(hwnd.0 as usize);
 
         Ok(WindowInfo {
             title,
             process_name,
+            process_id,
             x: rect.left,
             y: rect.top,
             width: rect.right - rect.left,
             height: rect.bottom - rect.top,
             is_visible: true,
             is_minimized,
+            is_maximized,
+            on_current_desktop: desktop.as_ref().map_or(true, |(_, current)| *current),
+            desktop_id: desktop.map(|(id, _)| id),
             handle: hwnd.0 as usize,
         })
     }
 
+    /// Convert a handle to an HWND, checking that the window still exists
+    pub(super) unsafe fn valid_hwnd(handle: usize) -> Result<HWND, AutomationError> {
+        let hwnd = HWND(handle as isize);
+        if handle == 0 || !IsWindow(hwnd).as_bool() {
+            return Err(AutomationError::InvalidArgument(format!(
+                "No window with handle {}",
+                handle
+            )));
+        }
+        Ok(hwnd)
+    }
+
+    /// Apply a show command (minimize, maximize, restore) to a window
+    pub(super) fn show_window(
+        handle: usize,
+        command: SHOW_WINDOW_CMD,
+    ) -> Result<(), AutomationError> {
+        unsafe {
+            let hwnd = valid_hwnd(handle)?;
+            // The return value is the previous visibility, not success
+            let _ = ShowWindow(hwnd, command);
+        }
+        Ok(())
+    }
+
     /// Get process name from process ID
     unsafe fn get_process_name(process_id: u32) -> Option<String> {
         use windows::core::PWSTR;
@@ -275,8 +580,7 @@ mod tests {
 
     #[test]
     fn test_window_manager_creation() {
-        let manager = WindowManager::new();
-        assert!(std::mem::size_of_val(&manager) >= 0);
+        let _manager = WindowManager::new();
     }
 
     #[test]
@@ -298,4 +602,35 @@ mod tests {
         let result = manager.get_active();
         assert!(result.is_ok());
     }
+
+    #[test]
+    #[cfg(target_os = "windows")]
+    fn test_invalid_window_handle() {
+        let manager = WindowManager::new();
+        assert!(matches!(
+            manager.get_window(0),
+            Err(AutomationError::InvalidArgument(_))
+        ));
+        assert!(manager.minimize_window(0).is_err());
+    }
+
+    #[test]
+    fn test_move_window_rejects_empty_size() {
+        let manager = WindowManager::new();
+        assert!(matches!(
+            manager.move_window(1, None, None, Some(0), Some(100)),
+            Err(AutomationError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_virtual_desktops_unsupported() {
+        let manager = WindowManager::new();
+        assert!(matches!(
+            manager.virtual_desktops(),
+            Err(AutomationError::UnsupportedOperation(_))
+        ));
+        assert!(manager.switch_virtual_desktop("desktop").is_err());
+    }
 }
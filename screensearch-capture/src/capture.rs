@@ -3,7 +3,9 @@
 //! This module provides the core screen capture functionality using the modern
 //! Windows Graphics Capture API for hardware-accelerated, efficient screen recording.
 
-use crate::{CaptureError, CapturedFrame, FrameDiffer, MonitorInfo, Result, WindowContext};
+use crate::{
+    CaptureControl, CaptureError, CapturedFrame, FrameDiffer, MonitorInfo, Result, WindowContext,
+};
 use crossbeam::queue::ArrayQueue;
 use image::RgbaImage;
 use screenshots::Screen;
@@ -11,9 +13,12 @@ use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+/// Longest a capture loop sleeps before re-checking its controls
+const CONTROL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Configuration for screen capture
 #[derive(Debug, Clone)]
 pub struct CaptureConfig {
@@ -135,6 +140,54 @@ impl ScreenCapture {
             .map_err(|e| CaptureError::ScreenCaptureError(format!("Task join error: {}", e)))?
     }
 
+    /// Capture a screen region from the monitor that contains it
+    ///
+    /// The region is given in virtual-screen coordinates. The monitor containing
+    /// the region's center is captured fresh and the image is cropped to the
+    /// region, clipped to the monitor bounds.
+    pub async fn capture_region(x: i32, y: i32, width: u32, height: u32) -> Result<RgbaImage> {
+        tokio::task::spawn_blocking(move || {
+            let center_x = x + (width / 2) as i32;
+            let center_y = y + (height / 2) as i32;
+
+            let monitor = MonitorInfo::enumerate()?
+                .into_iter()
+                .find(|m| {
+                    center_x >= m.x
+                        && center_y >= m.y
+                        && center_x < m.x + m.width as i32
+                        && center_y < m.y + m.height as i32
+                })
+                .ok_or_else(|| {
+                    CaptureError::ScreenCaptureError(format!(
+                        "No monitor contains point ({}, {})",
+                        center_x, center_y
+                    ))
+                })?;
+
+            let frame = Self::capture_single_frame(monitor.clone())?;
+            let (crop_x, crop_y, crop_width, crop_height) = crop_rect(
+                (monitor.x, monitor.y),
+                (monitor.width, monitor.height),
+                frame.image.dimensions(),
+                (x, y, width, height),
+            )
+            .ok_or_else(|| {
+                CaptureError::ImageProcessingError(format!(
+                    "Region {}x{} at ({}, {}) is outside monitor {}",
+                    width, height, x, y, monitor.index
+                ))
+            })?;
+
+            Ok(
+                image::imageops::crop_imm(&frame.image, crop_x, crop_y, crop_width, crop_height)
+                    .to_image(),
+            )
+        })
+        .await
+        .map_err(|e| CaptureError::ScreenCaptureError(format!("Task join error: {}", e)))?
+    }
+
     /// Stop the capture loop
     pub async fn stop(&mut self) -> Result<()> {
         tracing::info!("Stopping screen capture");
@@ -261,9 +314,61 @@ impl ScreenCapture {
     }
 }
 
+/// Map a virtual-screen region onto a monitor capture
+///
+/// Returns the pixel rectangle `(x, y, width, height)` inside the captured
+/// image, scaling for captures whose resolution differs from the monitor's
+/// reported size. Returns `None` if the region does not overlap the monitor.
+fn crop_rect(
+    monitor_origin: (i32, i32),
+    monitor_size: (u32, u32),
+    image_size: (u32, u32),
+    region: (i32, i32, u32, u32),
+) -> Option<(u32, u32, u32, u32)> {
+    let (region_x, region_y, region_width, region_height) = region;
+
+    // Clip the region to the monitor, in monitor-local coordinates
+    let left = (region_x - monitor_origin.0).max(0) as i64;
+    let top = (region_y - monitor_origin.1).max(0) as i64;
+    let right =
+        ((region_x - monitor_origin.0) as i64 + region_width as i64).min(monitor_size.0 as i64);
+    let bottom =
+        ((region_y - monitor_origin.1) as i64 + region_height as i64).min(monitor_size.1 as i64);
+
+    if right <= left || bottom <= top || image_size.0 == 0 || image_size.1 == 0 {
+        return None;
+    }
+
+    let scale_x = image_size.0 as f64 / monitor_size.0 as f64;
+    let scale_y = image_size.1 as f64 / monitor_size.1 as f64;
+
+    let x = ((left as f64 * scale_x).round() as u32).min(image_size.0.saturating_sub(1));
+    let y = ((top as f64 * scale_y).round() as u32).min(image_size.1.saturating_sub(1));
+    let width = (((right - left) as f64 * scale_x).round() as u32).clamp(1, image_size.0 - x);
+    let height = (((bottom - top) as f64 * scale_y).round() as u32).clamp(1, image_size.1 - y);
+
+    Some((x, y, width, height))
+}
+
+/// Sleep until the next capture is due
+///
+/// Wakes periodically so a shortened interval or a stop request takes effect
+/// without waiting out a long interval.
+fn wait_for_next_capture(started: Instant, control: &CaptureControl, running: &AtomicBool) {
+    while running.load(Ordering::SeqCst) {
+        let interval = Duration::from_millis(control.effective_interval_ms());
+        let elapsed = started.elapsed();
+        if elapsed >= interval {
+            break;
+        }
+        std::thread::sleep((interval - elapsed).min(CONTROL_POLL_INTERVAL));
+    }
+}
+
 /// Lower-level capture engine for more control
 pub struct CaptureEngine {
     config: CaptureConfig,
+    control: Arc<CaptureControl>,
     frame_queue: Arc<ArrayQueue<CapturedFrame>>,
     running: Arc<AtomicBool>,
 }
@@ -271,14 +376,29 @@ pub struct CaptureEngine {
 impl CaptureEngine {
     /// Create a new capture engine
     pub fn new(config: CaptureConfig) -> Result<Self> {
+        let control = Arc::new(CaptureControl::from_config(&config));
+        Self::with_control(config, control)
+    }
+
+    /// Create a capture engine driven by shared runtime controls
+    ///
+    /// The interval, pause state, monitor selection and excluded apps are read
+    /// from `control` while capturing; the matching `config` fields are ignored.
+    pub fn with_control(config: CaptureConfig, control: Arc<CaptureControl>) -> Result<Self> {
         let queue_size = config.max_frames_buffer;
         Ok(Self {
             config,
+            control,
             frame_queue: Arc::new(ArrayQueue::new(queue_size)),
             running: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    /// Runtime controls of this engine
+    pub fn control(&self) -> Arc<CaptureControl> {
+        Arc::clone(&self.control)
+    }
+
     /// Start capturing frames (non-blocking)
     pub fn start(&mut self) -> Result<()> {
         if self.running.swap(true, Ordering::SeqCst) {
@@ -289,26 +409,26 @@ impl CaptureEngine {
 
         tracing::info!("Starting capture engine");
 
-        // Enumerate monitors
-        let monitors = if self.config.monitor_indices.is_empty() {
-            MonitorInfo::enumerate()?
-        } else {
-            self.config
-                .monitor_indices
-                .iter()
-                .map(|&idx| MonitorInfo::by_index(idx))
-                .collect::<Result<Vec<_>>>()?
-        };
+        // Every monitor gets a loop so the monitor selection can change at
+        // runtime; loops for unselected monitors stay idle
+        let monitors = MonitorInfo::enumerate()?;
 
-        tracing::info!("Capture engine will capture {} monitor(s)", monitors.len());
+        tracing::info!(
+            "Capture engine found {} monitor(s), capturing {:?} (empty = all)",
+            monitors.len(),
+            self.control.monitor_indices()
+        );
 
         // Spawn capture thread for each monitor
         for monitor in monitors {
             let config = self.config.clone();
+            let control = self.control.clone();
             let running = self.running.clone();
             let queue = self.frame_queue.clone();
 
-            std::thread::spawn(move || Self::capture_loop(monitor, config, running, queue));
+            std::thread::spawn(move || {
+                Self::capture_loop(monitor, config, control, running, queue)
+            });
         }
 
         Ok(())
@@ -318,6 +438,7 @@ impl CaptureEngine {
     fn capture_loop(
         monitor: MonitorInfo,
         config: CaptureConfig,
+        control: Arc<CaptureControl>,
         running: Arc<AtomicBool>,
         queue: Arc<ArrayQueue<CapturedFrame>>,
     ) {
@@ -334,12 +455,23 @@ impl CaptureEngine {
             None
         };
 
-        let interval = Duration::from_millis(config.interval_ms);
-
         while running.load(Ordering::SeqCst) {
-            let capture_start = std::time::Instant::now();
+            let capture_start = Instant::now();
+
+            if control.is_paused()
+                || !control.is_scheduled()
+                || control.memory_budget().is_suspended()
+                || control.disk_space().is_suspended()
+                || !control.captures_monitor(monitor.index)
+            {
+                wait_for_next_capture(capture_start, &control, &running);
+                continue;
+            }
 
             match Self::capture_single_frame(monitor.clone()) {
+                Ok(frame) if control.is_excluded(frame.active_process.as_deref()) => {
+                    tracing::trace!("Frame skipped (excluded app {:?})", frame.active_process);
+                }
                 Ok(frame) => {
                     let should_process = if let Some(ref mut differ) = differ {
                         differ.has_changed(&frame.image)
@@ -371,10 +503,7 @@ impl CaptureEngine {
                 }
             }
 
-            let elapsed = capture_start.elapsed();
-            if elapsed < interval {
-                std::thread::sleep(interval - elapsed);
-            }
+            wait_for_next_capture(capture_start, &control, &running);
         }
 
         tracing::info!("Capture loop stopped for monitor {}", monitor.index);
@@ -460,6 +589,26 @@ mod tests {
         assert_eq!(config.max_frames_buffer, 30);
     }
 
+    #[test]
+    fn test_crop_rect_inside_monitor() {
+        // Secondary monitor to the right of a 1920px primary
+        let rect = crop_rect((1920, 0), (2560, 1440), (2560, 1440), (2020, 50, 300, 40));
+        assert_eq!(rect, Some((100, 50, 300, 40)));
+    }
+
+    #[test]
+    fn test_crop_rect_clips_and_scales() {
+        // Region hanging off the left edge, capture at 2x the reported size
+        let rect = crop_rect((0, 0), (1920, 1080), (3840, 2160), (-50, 10, 150, 20));
+        assert_eq!(rect, Some((0, 20, 200, 40)));
+
+        // Region entirely outside the monitor
+        assert_eq!(
+            crop_rect((0, 0), (1920, 1080), (1920, 1080), (2000, 0, 10, 10)),
+            None
+        );
+    }
+
     #[test]
     fn test_screen_capture_new() {
         let config = CaptureConfig::default();
@@ -0,0 +1,298 @@
+//! Runtime capture controls
+//!
+//! Settings the running capture loops re-read on every iteration, so changes
+//! made through the settings API apply without restarting the capture engine.
+
+use crate::{
+    CaptureConfig, CaptureSchedule, DiskSpace, FrameQueue, MemoryBudget, MonitorBacklog,
+    PowerSaving,
+};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Capture settings shared between the capture loops and the API
+#[derive(Debug)]
+pub struct CaptureControl {
+    /// Capture interval in milliseconds
+    interval_ms: AtomicU64,
+
+    /// Whether capture is paused
+    paused: AtomicBool,
+
+    /// Monitor indices to capture (empty = all monitors)
+    monitor_indices: RwLock<Vec<usize>>,
+
+    /// Lowercased process names whose frames are dropped
+    excluded_apps: RwLock<Vec<String>>,
+
+    /// Lowercased process names excluded in the config file, kept when the
+    /// settings list changes
+    config_excluded_apps: RwLock<Vec<String>>,
+
+    /// Limit on frames queued between capture and storage
+    memory_budget: MemoryBudget,
+
+    /// Free space of the volumes captures are stored on
+    disk_space: DiskSpace,
+
+    /// Power source, which throttles capture on battery
+    power: PowerSaving,
+
+    /// Frames waiting for OCR, once the pipeline is running
+    frame_queue: OnceLock<Arc<FrameQueue>>,
+
+    /// Hours of the week capture runs (empty = always)
+    schedule: RwLock<CaptureSchedule>,
+
+    /// Whether the schedule kept capture off at the last check
+    outside_schedule: AtomicBool,
+}
+
+impl CaptureControl {
+    /// Create controls from the static capture configuration
+    pub fn from_config(config: &CaptureConfig) -> Self {
+        Self {
+            interval_ms: AtomicU64::new(config.interval_ms),
+            paused: AtomicBool::new(false),
+            monitor_indices: RwLock::new(config.monitor_indices.clone()),
+            excluded_apps: RwLock::new(Vec::new()),
+            config_excluded_apps: RwLock::new(Vec::new()),
+            memory_budget: MemoryBudget::default(),
+            disk_space: DiskSpace::default(),
+            power: PowerSaving::default(),
+            frame_queue: OnceLock::new(),
+            schedule: RwLock::new(CaptureSchedule::default()),
+            outside_schedule: AtomicBool::new(false),
+        }
+    }
+
+    /// Current capture interval in milliseconds
+    pub fn interval_ms(&self) -> u64 {
+        self.interval_ms.load(Ordering::SeqCst)
+    }
+
+    /// Capture interval to wait, stretched while saving power
+    pub fn effective_interval_ms(&self) -> u64 {
+        self.power.capture_interval_ms(self.interval_ms())
+    }
+
+    /// Set the capture interval in milliseconds
+    pub fn set_interval_ms(&self, interval_ms: u64) {
+        self.interval_ms.store(interval_ms.max(1), Ordering::SeqCst);
+    }
+
+    /// Whether capture is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Pause or resume capture
+    pub fn set_paused(&self, paused: bool) {
+        if self.paused.swap(paused, Ordering::SeqCst) != paused {
+            tracing::info!("Capture {}", if paused { "paused" } else { "resumed" });
+        }
+    }
+
+    /// Set the hours of the week capture runs (empty = always)
+    pub fn set_schedule(&self, schedule: CaptureSchedule) {
+        *self.schedule.write().unwrap_or_else(|e| e.into_inner()) = schedule;
+    }
+
+    /// Whether the schedule lets capture run now
+    pub fn is_scheduled(&self) -> bool {
+        let scheduled = self
+            .schedule
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_active_now();
+        if self.outside_schedule.swap(!scheduled, Ordering::SeqCst) == scheduled {
+            tracing::info!(
+                "Capture {} by schedule",
+                if scheduled { "resumed" } else { "paused" }
+            );
+        }
+        scheduled
+    }
+
+    /// Memory budget of the capture pipeline, which suspends capture while
+    /// the frame backlog drains
+    pub fn memory_budget(&self) -> &MemoryBudget {
+        &self.memory_budget
+    }
+
+    /// Free space of the capture volumes, which suspends capture while the
+    /// disk is almost full
+    pub fn disk_space(&self) -> &DiskSpace {
+        &self.disk_space
+    }
+
+    /// Power source, which stretches the capture interval and pauses
+    /// background work on battery
+    pub fn power(&self) -> &PowerSaving {
+        &self.power
+    }
+
+    /// Publish the queue of frames waiting for OCR
+    pub fn set_frame_queue(&self, queue: Arc<FrameQueue>) {
+        let _ = self.frame_queue.set(queue);
+    }
+
+    /// Frames waiting for OCR per monitor (empty until capture runs)
+    pub fn frame_backlog(&self) -> Vec<MonitorBacklog> {
+        self.frame_queue
+            .get()
+            .map(|queue| queue.backlog())
+            .unwrap_or_default()
+    }
+
+    /// Monitor indices to capture (empty = all monitors)
+    pub fn monitor_indices(&self) -> Vec<usize> {
+        self.monitor_indices
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Set the monitor indices to capture (empty = all monitors)
+    pub fn set_monitor_indices(&self, indices: Vec<usize>) {
+        *self
+            .monitor_indices
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = indices;
+    }
+
+    /// Whether the monitor with the given index should be captured
+    pub fn captures_monitor(&self, index: usize) -> bool {
+        let indices = self
+            .monitor_indices
+            .read()
+            .unwrap_or_else(|e| e.into_inner());
+        indices.is_empty() || indices.contains(&index)
+    }
+
+    /// Applications whose frames are dropped
+    pub fn excluded_apps(&self) -> Vec<String> {
+        self.excluded_apps
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Set the applications whose frames are dropped
+    ///
+    /// Names are process names such as `"KeePass.exe"` or `"keepass"`; the
+    /// comparison ignores case and the `.exe` extension.
+    pub fn set_excluded_apps(&self, apps: Vec<String>) {
+        *self
+            .excluded_apps
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = normalize_apps(apps);
+    }
+
+    /// Set the applications excluded by the config file
+    ///
+    /// These are dropped in addition to [`Self::excluded_apps`], which the
+    /// settings API replaces.
+    pub fn set_config_excluded_apps(&self, apps: Vec<String>) {
+        *self
+            .config_excluded_apps
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = normalize_apps(apps);
+    }
+
+    /// Whether frames of the given foreground process should be dropped
+    pub fn is_excluded(&self, process_name: Option<&str>) -> bool {
+        let Some(process_name) = process_name else {
+            return false;
+        };
+
+        let process_name = normalize_app(process_name);
+        [&self.excluded_apps, &self.config_excluded_apps]
+            .iter()
+            .any(|apps| {
+                apps.read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .contains(&process_name)
+            })
+    }
+}
+
+impl Default for CaptureControl {
+    fn default() -> Self {
+        Self::from_config(&CaptureConfig::default())
+    }
+}
+
+/// Normalize application names, dropping empty ones
+fn normalize_apps(apps: Vec<String>) -> Vec<String> {
+    apps.into_iter()
+        .map(|app| normalize_app(&app))
+        .filter(|app| !app.is_empty())
+        .collect()
+}
+
+/// Lowercase an application name and strip a trailing `.exe`
+fn normalize_app(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    match name.strip_suffix(".exe") {
+        Some(stem) => stem.to_string(),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_config() {
+        let config = CaptureConfig {
+            interval_ms: 5000,
+            monitor_indices: vec![1],
+            ..Default::default()
+        };
+        let control = CaptureControl::from_config(&config);
+
+        assert_eq!(control.interval_ms(), 5000);
+        assert!(!control.is_paused());
+        assert!(control.captures_monitor(1));
+        assert!(!control.captures_monitor(0));
+    }
+
+    #[test]
+    fn test_empty_monitor_list_captures_all() {
+        let control = CaptureControl::default();
+        control.set_monitor_indices(vec![0]);
+        assert!(!control.captures_monitor(2));
+
+        control.set_monitor_indices(Vec::new());
+        assert!(control.captures_monitor(0));
+        assert!(control.captures_monitor(2));
+    }
+
+    #[test]
+    fn test_excluded_apps_match_process_name() {
+        let control = CaptureControl::default();
+        control.set_excluded_apps(vec!["KeePass.exe".to_string(), "1password".to_string()]);
+
+        assert!(control.is_excluded(Some("keepass.exe")));
+        assert!(control.is_excluded(Some("KEEPASS")));
+        assert!(control.is_excluded(Some("1Password.exe")));
+        assert!(!control.is_excluded(Some("notepad.exe")));
+        assert!(!control.is_excluded(None));
+    }
+
+    #[test]
+    fn test_config_excluded_apps_survive_settings_changes() {
+        let control = CaptureControl::default();
+        control.set_config_excluded_apps(vec!["Bitwarden".to_string()]);
+        control.set_excluded_apps(vec!["KeePass".to_string()]);
+        assert!(control.is_excluded(Some("bitwarden.exe")));
+        assert!(control.is_excluded(Some("keepass.exe")));
+
+        control.set_excluded_apps(Vec::new());
+        assert!(control.is_excluded(Some("bitwarden.exe")));
+        assert!(!control.is_excluded(Some("keepass.exe")));
+        assert!(control.excluded_apps().is_empty());
+    }
+}
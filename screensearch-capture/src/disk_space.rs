@@ -0,0 +1,164 @@
+//! Free disk space guard
+//!
+//! Captures fill the disk a few hundred megabytes a day, so the volumes
+//! holding the database and the frame images are watched. [`DiskSpace`]
+//! tracks the free space reported for them and decides what to do:
+//!
+//! 1. below the cleanup threshold, the oldest frames are deleted ahead of
+//!    the retention period until the free space is back above it, and
+//! 2. below the pause threshold, capture is suspended until the free space
+//!    has recovered to twice that (at most the cleanup threshold).
+
+use crate::stats::DiskSpaceStats;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+const MB: u64 = 1024 * 1024;
+
+/// Free space of the capture volumes, checked against the
+/// `storage.cleanup_below_mb` and `storage.pause_below_mb` thresholds
+#[derive(Debug, Default)]
+pub struct DiskSpace {
+    /// Free space below which old frames are deleted, in bytes (0 = never)
+    cleanup_below: AtomicU64,
+
+    /// Free space below which capture is suspended, in bytes (0 = never)
+    pause_below: AtomicU64,
+
+    /// Last measured free space, in bytes
+    free_bytes: AtomicU64,
+
+    /// Size of the volume the free space was measured on, in bytes
+    total_bytes: AtomicU64,
+
+    /// Whether capture is suspended until space is freed
+    suspended: AtomicBool,
+
+    /// Cleanups run since startup
+    cleanups: AtomicU64,
+
+    /// Frames deleted by cleanups since startup
+    frames_deleted: AtomicU64,
+}
+
+impl DiskSpace {
+    /// Set the thresholds in megabytes (0 = disabled)
+    pub fn set_thresholds_mb(&self, cleanup_below_mb: u64, pause_below_mb: u64) {
+        self.cleanup_below
+            .store(cleanup_below_mb.saturating_mul(MB), Ordering::Relaxed);
+        self.pause_below
+            .store(pause_below_mb.saturating_mul(MB), Ordering::Relaxed);
+    }
+
+    /// Record a measurement of `free_bytes` out of `total_bytes`
+    ///
+    /// Returns whether old frames should be deleted to free space.
+    pub fn update(&self, free_bytes: u64, total_bytes: u64) -> bool {
+        self.free_bytes.store(free_bytes, Ordering::Relaxed);
+        self.total_bytes.store(total_bytes, Ordering::Relaxed);
+
+        let cleanup_below = self.cleanup_below.load(Ordering::Relaxed);
+        let pause_below = self.pause_below.load(Ordering::Relaxed);
+        let free_mb = free_bytes / MB;
+
+        if pause_below == 0 {
+            self.suspended.store(false, Ordering::Relaxed);
+        } else if self.suspended.load(Ordering::Relaxed) {
+            let resume_at = if cleanup_below > pause_below {
+                (pause_below * 2).min(cleanup_below)
+            } else {
+                pause_below * 2
+            };
+            if free_bytes >= resume_at {
+                tracing::info!(free_mb, "Disk space recovered, resuming capture");
+                self.suspended.store(false, Ordering::Relaxed);
+            }
+        } else if free_bytes < pause_below {
+            tracing::error!(
+                free_mb,
+                pause_below_mb = pause_below / MB,
+                "Disk almost full, suspending capture until space is freed"
+            );
+            self.suspended.store(true, Ordering::Relaxed);
+        }
+
+        cleanup_below > 0 && free_bytes < cleanup_below
+    }
+
+    /// Free space to reach with a cleanup, in bytes
+    pub fn cleanup_target(&self) -> u64 {
+        self.cleanup_below.load(Ordering::Relaxed)
+    }
+
+    /// Count a cleanup that deleted `frames` frames
+    pub fn record_cleanup(&self, frames: u64) {
+        self.cleanups.fetch_add(1, Ordering::Relaxed);
+        self.frames_deleted.fetch_add(frames, Ordering::Relaxed);
+    }
+
+    /// Whether capture is suspended until space is freed
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.load(Ordering::Relaxed)
+    }
+
+    /// Whether a threshold is set
+    pub fn is_enabled(&self) -> bool {
+        self.cleanup_below.load(Ordering::Relaxed) > 0
+            || self.pause_below.load(Ordering::Relaxed) > 0
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> DiskSpaceStats {
+        DiskSpaceStats {
+            free_mb: self.free_bytes.load(Ordering::Relaxed) / MB,
+            total_mb: self.total_bytes.load(Ordering::Relaxed) / MB,
+            cleanup_below_mb: self.cleanup_below.load(Ordering::Relaxed) / MB,
+            pause_below_mb: self.pause_below.load(Ordering::Relaxed) / MB,
+            capture_suspended: self.suspended.load(Ordering::Relaxed),
+            cleanups: self.cleanups.load(Ordering::Relaxed),
+            frames_deleted: self.frames_deleted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1024 * MB;
+
+    #[test]
+    fn test_disabled_never_acts() {
+        let disk = DiskSpace::default();
+        assert!(!disk.update(0, 100 * GB));
+        assert!(!disk.is_suspended());
+    }
+
+    #[test]
+    fn test_cleanup_below_threshold() {
+        let disk = DiskSpace::default();
+        disk.set_thresholds_mb(5 * 1024, 1024);
+
+        assert!(!disk.update(6 * GB, 100 * GB));
+        assert!(disk.update(4 * GB, 100 * GB));
+        assert!(!disk.is_suspended());
+        assert_eq!(disk.cleanup_target(), 5 * GB);
+        assert_eq!(disk.stats().free_mb, 4 * 1024);
+    }
+
+    #[test]
+    fn test_suspends_until_space_recovers() {
+        let disk = DiskSpace::default();
+        disk.set_thresholds_mb(5 * 1024, 1024);
+
+        assert!(disk.update(GB / 2, 100 * GB));
+        assert!(disk.is_suspended());
+
+        // Stays suspended just above the pause threshold
+        disk.update(GB + GB / 2, 100 * GB);
+        assert!(disk.is_suspended());
+
+        disk.update(2 * GB, 100 * GB);
+        assert!(!disk.is_suspended());
+        assert_eq!(disk.stats().pause_below_mb, 1024);
+    }
+}
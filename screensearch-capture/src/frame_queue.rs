@@ -0,0 +1,367 @@
+//! Fair frame queue between capture and OCR
+//!
+//! Frames wait for OCR in one sub-queue per monitor, and OCR takes them from
+//! the monitors in turn, so a busy monitor producing a frame at every capture
+//! cannot starve quieter ones behind its backlog. When the queue is full, the
+//! oldest frame of the monitor with the longest backlog makes room.
+//!
+//! Like a channel, the queue closes once every [`FrameSender`] is dropped:
+//! [`FrameReceiver::recv`] hands out the frames left, then returns `None`.
+
+use crate::stats::MonitorBacklog;
+use crate::CapturedFrame;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+#[derive(Debug, Default)]
+struct MonitorQueue {
+    frames: VecDeque<CapturedFrame>,
+    processed: u64,
+    dropped: u64,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    monitors: BTreeMap<usize, MonitorQueue>,
+    /// Frames queued over all monitors
+    len: usize,
+    /// Monitor the last frame was taken from
+    last_served: Option<usize>,
+    senders: usize,
+    receiver_dropped: bool,
+}
+
+impl QueueState {
+    fn push(&mut self, frame: CapturedFrame) {
+        self.monitors
+            .entry(frame.monitor_index)
+            .or_default()
+            .frames
+            .push_back(frame);
+        self.len += 1;
+    }
+
+    /// Drop the oldest frame of the monitor with the longest backlog
+    fn evict(&mut self) -> Option<CapturedFrame> {
+        let queue = self
+            .monitors
+            .values_mut()
+            .filter(|queue| !queue.frames.is_empty())
+            .max_by_key(|queue| queue.frames.len())?;
+        let frame = queue.frames.pop_front()?;
+        queue.dropped += 1;
+        self.len -= 1;
+        Some(frame)
+    }
+
+    /// Take the oldest frame of the next monitor after the last one served
+    fn pop(&mut self) -> Option<CapturedFrame> {
+        let waiting = || {
+            self.monitors
+                .iter()
+                .filter(|(_, queue)| !queue.frames.is_empty())
+                .map(|(index, _)| *index)
+        };
+        let next = waiting()
+            .find(|index| self.last_served.is_none_or(|last| *index > last))
+            .or_else(|| waiting().next())?;
+
+        let queue = self.monitors.get_mut(&next)?;
+        let frame = queue.frames.pop_front()?;
+        queue.processed += 1;
+        self.len -= 1;
+        self.last_served = Some(next);
+        Some(frame)
+    }
+}
+
+/// Per-monitor frame queue shared by its senders and receiver
+#[derive(Debug)]
+pub struct FrameQueue {
+    state: Mutex<QueueState>,
+    capacity: usize,
+    /// Signalled when a frame is queued or the last sender is dropped
+    frame_queued: Notify,
+    /// Signalled when a frame is taken or the receiver is dropped
+    frame_taken: Notify,
+}
+
+impl FrameQueue {
+    /// Frames the queue holds at most, over all monitors
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Frames waiting for OCR, over all monitors
+    pub fn len(&self) -> usize {
+        self.lock().len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Counters of each monitor that queued a frame, by monitor index
+    pub fn backlog(&self) -> Vec<MonitorBacklog> {
+        self.lock()
+            .monitors
+            .iter()
+            .map(|(index, queue)| MonitorBacklog {
+                monitor_index: *index,
+                queued: queue.frames.len(),
+                processed: queue.processed,
+                dropped: queue.dropped,
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Create a frame queue holding up to `capacity` frames
+pub fn frame_queue(capacity: usize) -> (FrameSender, FrameReceiver) {
+    let queue = Arc::new(FrameQueue {
+        state: Mutex::new(QueueState {
+            senders: 1,
+            ..Default::default()
+        }),
+        capacity: capacity.max(1),
+        frame_queued: Notify::new(),
+        frame_taken: Notify::new(),
+    });
+
+    (
+        FrameSender {
+            queue: Arc::clone(&queue),
+        },
+        FrameReceiver { queue },
+    )
+}
+
+/// Queues frames for OCR
+#[derive(Debug)]
+pub struct FrameSender {
+    queue: Arc<FrameQueue>,
+}
+
+impl FrameSender {
+    /// Queue a frame without waiting
+    ///
+    /// When the queue is full, the oldest frame of the monitor with the
+    /// longest backlog is dropped and returned. Fails with the frame when the
+    /// receiver is gone.
+    pub fn push(&self, frame: CapturedFrame) -> Result<Option<CapturedFrame>, CapturedFrame> {
+        let dropped = {
+            let mut state = self.queue.lock();
+            if state.receiver_dropped {
+                return Err(frame);
+            }
+            let dropped = if state.len >= self.queue.capacity {
+                state.evict()
+            } else {
+                None
+            };
+            state.push(frame);
+            dropped
+        };
+
+        self.queue.frame_queued.notify_one();
+        Ok(dropped)
+    }
+
+    /// Queue a frame, waiting for room when the queue is full
+    ///
+    /// Fails with the frame when the receiver is gone.
+    pub async fn send(&self, frame: CapturedFrame) -> Result<(), CapturedFrame> {
+        loop {
+            // Created before checking, so a frame taken meanwhile wakes it
+            let taken = self.queue.frame_taken.notified();
+            {
+                let mut state = self.queue.lock();
+                if state.receiver_dropped {
+                    return Err(frame);
+                }
+                if state.len < self.queue.capacity {
+                    state.push(frame);
+                    break;
+                }
+            }
+            taken.await;
+        }
+
+        self.queue.frame_queued.notify_one();
+        Ok(())
+    }
+
+    /// [`Self::send`] from outside the async runtime
+    pub fn blocking_send(&self, frame: CapturedFrame) -> Result<(), CapturedFrame> {
+        futures::executor::block_on(self.send(frame))
+    }
+
+    /// The queue, to read its length and backlog without keeping it open
+    pub fn queue(&self) -> Arc<FrameQueue> {
+        Arc::clone(&self.queue)
+    }
+}
+
+impl Clone for FrameSender {
+    fn clone(&self) -> Self {
+        self.queue.lock().senders += 1;
+        Self {
+            queue: Arc::clone(&self.queue),
+        }
+    }
+}
+
+impl Drop for FrameSender {
+    fn drop(&mut self) {
+        let closed = {
+            let mut state = self.queue.lock();
+            state.senders -= 1;
+            state.senders == 0
+        };
+        if closed {
+            self.queue.frame_queued.notify_one();
+        }
+    }
+}
+
+/// Takes queued frames for OCR, in turn from each monitor
+#[derive(Debug)]
+pub struct FrameReceiver {
+    queue: Arc<FrameQueue>,
+}
+
+impl FrameReceiver {
+    /// The next frame, or `None` once the queue is empty and closed
+    pub async fn recv(&mut self) -> Option<CapturedFrame> {
+        loop {
+            let queued = self.queue.frame_queued.notified();
+            {
+                let mut state = self.queue.lock();
+                if let Some(frame) = state.pop() {
+                    drop(state);
+                    self.queue.frame_taken.notify_waiters();
+                    return Some(frame);
+                }
+                if state.senders == 0 {
+                    return None;
+                }
+            }
+            queued.await;
+        }
+    }
+
+    /// The queue, to read its length and backlog
+    pub fn queue(&self) -> Arc<FrameQueue> {
+        Arc::clone(&self.queue)
+    }
+}
+
+impl Drop for FrameReceiver {
+    fn drop(&mut self) {
+        self.queue.lock().receiver_dropped = true;
+        self.queue.frame_taken.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn frame(monitor_index: usize, id: u8) -> CapturedFrame {
+        CapturedFrame {
+            timestamp: chrono::Utc::now(),
+            monitor_index,
+            image: image::RgbaImage::from_pixel(1, 1, image::Rgba([id, 0, 0, 255])),
+            active_window: None,
+            active_process: None,
+        }
+    }
+
+    fn id(frame: &CapturedFrame) -> (usize, u8) {
+        (frame.monitor_index, frame.image.get_pixel(0, 0).0[0])
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_across_monitors() {
+        let (tx, mut rx) = frame_queue(100);
+        // A busy monitor queues its frames before the quiet ones
+        for i in 0..4 {
+            tx.push(frame(0, i)).unwrap();
+        }
+        tx.push(frame(2, 0)).unwrap();
+        tx.push(frame(1, 0)).unwrap();
+        tx.push(frame(1, 1)).unwrap();
+        drop(tx);
+
+        let mut order = Vec::new();
+        while let Some(frame) = rx.recv().await {
+            order.push(id(&frame));
+        }
+        assert_eq!(
+            order,
+            [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (0, 2), (0, 3)]
+        );
+
+        let backlog = rx.queue().backlog();
+        assert_eq!(backlog.len(), 3);
+        assert_eq!(backlog[0].processed, 4);
+        assert!(backlog.iter().all(|monitor| monitor.queued == 0));
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_from_longest_backlog() {
+        let (tx, mut rx) = frame_queue(3);
+        tx.push(frame(0, 0)).unwrap();
+        tx.push(frame(0, 1)).unwrap();
+        tx.push(frame(1, 0)).unwrap();
+
+        // Monitor 1 gets in at the expense of monitor 0's oldest frame
+        let dropped = tx.push(frame(1, 1)).unwrap();
+        assert_eq!(dropped.as_ref().map(id), Some((0, 0)));
+        assert_eq!(rx.queue().len(), 3);
+
+        let backlog = tx.queue().backlog();
+        assert_eq!((backlog[0].queued, backlog[0].dropped), (1, 1));
+        assert_eq!((backlog[1].queued, backlog[1].dropped), (2, 0));
+
+        assert_eq!(rx.recv().await.as_ref().map(id), Some((0, 1)));
+        assert_eq!(rx.recv().await.as_ref().map(id), Some((1, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_send_waits_for_room() {
+        let (tx, mut rx) = frame_queue(1);
+        tx.send(frame(0, 0)).await.unwrap();
+
+        let sender = tx.clone();
+        let send = tokio::spawn(async move { sender.send(frame(1, 0)).await.is_ok() });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!send.is_finished());
+
+        assert_eq!(rx.recv().await.as_ref().map(id), Some((0, 0)));
+        assert!(send.await.unwrap());
+        assert_eq!(rx.recv().await.as_ref().map(id), Some((1, 0)));
+
+        // A clone keeps the queue open until it is dropped too
+        let recv = tokio::spawn(async move { rx.recv().await.is_none() });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!recv.is_finished());
+        drop(tx);
+        assert!(recv.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_receiver_dropped() {
+        let (tx, rx) = frame_queue(1);
+        tx.push(frame(0, 0)).unwrap();
+        drop(rx);
+        assert!(tx.push(frame(0, 1)).is_err());
+        assert!(tx.send(frame(0, 2)).await.is_err());
+    }
+}
@@ -26,26 +26,77 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! # Cargo features
+//!
+//! - `capture` (default): everything above. Without it only the [`stats`]
+//!   reported by the health endpoint are built.
 
+#[cfg(feature = "capture")]
 use thiserror::Error;
 
+#[cfg(feature = "capture")]
 pub mod capture;
+#[cfg(feature = "capture")]
+pub mod control;
+#[cfg(feature = "capture")]
+pub mod disk_space;
+#[cfg(feature = "capture")]
 pub mod frame_diff;
+#[cfg(feature = "capture")]
+pub mod frame_queue;
+#[cfg(feature = "capture")]
+pub mod memory_budget;
+#[cfg(feature = "capture")]
 pub mod monitor;
+#[cfg(feature = "capture")]
 pub mod ocr;
+#[cfg(feature = "capture")]
+pub mod ocr_language;
+#[cfg(feature = "capture")]
 pub mod ocr_processor;
+#[cfg(feature = "capture")]
+pub mod power;
+#[cfg(feature = "capture")]
+pub mod schedule;
+#[cfg(feature = "capture")]
+pub mod sensitive;
+pub mod stats;
+#[cfg(feature = "capture")]
 pub mod window_context;
 
+#[cfg(feature = "capture")]
 pub use capture::{CaptureConfig, CaptureEngine, ScreenCapture};
+#[cfg(feature = "capture")]
+pub use control::CaptureControl;
+#[cfg(feature = "capture")]
+pub use disk_space::DiskSpace;
+#[cfg(feature = "capture")]
 pub use frame_diff::FrameDiffer;
+#[cfg(feature = "capture")]
+pub use frame_queue::{frame_queue, FrameQueue, FrameReceiver, FrameSender};
+#[cfg(feature = "capture")]
+pub use memory_budget::MemoryBudget;
+#[cfg(feature = "capture")]
 pub use monitor::MonitorInfo;
+#[cfg(feature = "capture")]
 pub use ocr::{OcrEngine, OcrResult, TextRegion};
+#[cfg(feature = "capture")]
 pub use ocr_processor::{
     OcrMetrics, OcrProcessor, OcrProcessorBuilder, OcrProcessorConfig, ProcessedFrame,
 };
+#[cfg(feature = "capture")]
+pub use power::PowerSaving;
+#[cfg(feature = "capture")]
+pub use schedule::{CaptureSchedule, ScheduleRule};
+#[cfg(feature = "capture")]
+pub use sensitive::{SensitiveAction, SensitiveCategory, SensitiveContentFilter};
+pub use stats::{DiskSpaceStats, MemoryBudgetStats, MonitorBacklog, PowerStats};
+#[cfg(feature = "capture")]
 pub use window_context::WindowContext;
 
 /// Errors that can occur during screen capture operations
+#[cfg(feature = "capture")]
 #[derive(Error, Debug)]
 pub enum CaptureError {
     #[error("Failed to initialize capture: {0}")]
@@ -71,9 +122,11 @@ pub enum CaptureError {
 }
 
 /// Result type alias for capture operations
+#[cfg(feature = "capture")]
 pub type Result<T> = std::result::Result<T, CaptureError>;
 
 /// Represents a captured frame with metadata
+#[cfg(feature = "capture")]
 #[derive(Debug, Clone)]
 pub struct CapturedFrame {
     /// Timestamp when the frame was captured
@@ -105,7 +158,7 @@ pub struct OcrTextResult {
     pub confidence: f32,
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "capture"))]
 mod tests {
     use super::*;
 
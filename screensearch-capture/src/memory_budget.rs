@@ -0,0 +1,232 @@
+//! Memory budget for frames in the pipeline
+//!
+//! Frames are held as raw RGBA images (about 8 MB each at 1080p) from capture
+//! until they are stored, so a stalled OCR stage lets the queues between the
+//! stages grow to gigabytes. [`MemoryBudget`] estimates what queued frames
+//! and embedding batches hold and, once that reaches the limit:
+//!
+//! 1. shrinks the frame queue to what fits by dropping new frames, and
+//! 2. suspends capture when frames keep being dropped for [`SUSPEND_AFTER`],
+//!    resuming once the backlog has drained to half the limit.
+
+use crate::stats::MemoryBudgetStats;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long frames must keep being dropped before capture is suspended
+pub const SUSPEND_AFTER: Duration = Duration::from_secs(30);
+
+const MB: u64 = 1024 * 1024;
+
+/// Approximate memory of the queued capture pipeline, checked against
+/// `performance.max_memory_mb`
+#[derive(Debug, Default)]
+pub struct MemoryBudget {
+    /// Limit in bytes (0 = unlimited)
+    limit_bytes: AtomicU64,
+
+    /// Held outside the frame queues, e.g. by embedding batches
+    reserved_bytes: AtomicU64,
+
+    /// Size of the most recent frame, used for frames already queued
+    frame_bytes: AtomicU64,
+
+    /// Last usage estimate
+    usage_bytes: AtomicU64,
+
+    /// Frames dropped since startup
+    dropped_frames: AtomicU64,
+
+    /// Times the limit was reached since startup
+    activations: AtomicU64,
+
+    /// Whether a frame was dropped since the last [`Self::update`]
+    dropping: AtomicBool,
+
+    /// Whether capture is suspended until the backlog drains
+    suspended: AtomicBool,
+
+    /// When frames started being dropped
+    limited_since: Mutex<Option<Instant>>,
+}
+
+impl MemoryBudget {
+    /// Create a budget of `limit_mb` megabytes (0 = unlimited)
+    pub fn new(limit_mb: u64) -> Self {
+        let budget = Self::default();
+        budget.set_limit_mb(limit_mb);
+        budget
+    }
+
+    /// Change the limit (0 = unlimited)
+    pub fn set_limit_mb(&self, limit_mb: u64) {
+        self.limit_bytes
+            .store(limit_mb.saturating_mul(MB), Ordering::Relaxed);
+    }
+
+    /// Whether a limit is set
+    pub fn is_enabled(&self) -> bool {
+        self.limit_bytes.load(Ordering::Relaxed) > 0
+    }
+
+    /// Set memory held outside the frame queues, counted against the limit
+    pub fn set_reserved_bytes(&self, bytes: u64) {
+        self.reserved_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    /// Whether a new frame of `frame_bytes` fits next to `queued_frames`
+    /// frames already in the pipeline
+    ///
+    /// Counts the frame as dropped when it does not.
+    pub fn admit(&self, queued_frames: usize, frame_bytes: u64) -> bool {
+        self.frame_bytes.store(frame_bytes, Ordering::Relaxed);
+        let limit = self.limit_bytes.load(Ordering::Relaxed);
+        if limit == 0 || self.estimate(queued_frames) + frame_bytes <= limit {
+            return true;
+        }
+
+        self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+        self.dropping.store(true, Ordering::Relaxed);
+        false
+    }
+
+    /// Re-estimate usage with `queued_frames` in the pipeline
+    ///
+    /// Returns whether capture should be suspended.
+    pub fn update(&self, queued_frames: usize, now: Instant) -> bool {
+        let usage = self.estimate(queued_frames);
+        self.usage_bytes.store(usage, Ordering::Relaxed);
+
+        let limit = self.limit_bytes.load(Ordering::Relaxed);
+        let dropping = self.dropping.swap(false, Ordering::Relaxed);
+        let mut limited_since = self.limited_since.lock().unwrap_or_else(|e| e.into_inner());
+
+        if limit == 0 {
+            *limited_since = None;
+            self.suspended.store(false, Ordering::Relaxed);
+            return false;
+        }
+
+        let mut suspended = self.suspended.load(Ordering::Relaxed);
+        if suspended {
+            if usage <= limit / 2 {
+                tracing::info!(
+                    usage_mb = usage / MB,
+                    "Frame backlog drained, resuming capture"
+                );
+                suspended = false;
+                *limited_since = None;
+            }
+        } else if dropping {
+            let since = *limited_since.get_or_insert_with(|| {
+                let activations = self.activations.fetch_add(1, Ordering::Relaxed) + 1;
+                tracing::warn!(
+                    limit_mb = limit / MB,
+                    usage_mb = usage / MB,
+                    activations,
+                    "Memory budget reached, dropping new frames until OCR catches up"
+                );
+                now
+            });
+            if now.duration_since(since) >= SUSPEND_AFTER {
+                tracing::warn!(
+                    limit_mb = limit / MB,
+                    usage_mb = usage / MB,
+                    dropped_frames = self.dropped_frames.load(Ordering::Relaxed),
+                    "Memory budget exceeded for {}s, suspending capture",
+                    SUSPEND_AFTER.as_secs()
+                );
+                suspended = true;
+            }
+        } else if limited_since.take().is_some() {
+            tracing::info!(usage_mb = usage / MB, "Back under the memory budget");
+        }
+
+        self.suspended.store(suspended, Ordering::Relaxed);
+        suspended
+    }
+
+    /// Whether capture is suspended until the backlog drains
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.load(Ordering::Relaxed)
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> MemoryBudgetStats {
+        MemoryBudgetStats {
+            limit_mb: self.limit_bytes.load(Ordering::Relaxed) / MB,
+            usage_mb: self.usage_bytes.load(Ordering::Relaxed) / MB,
+            limiting: self
+                .limited_since
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .is_some(),
+            capture_suspended: self.suspended.load(Ordering::Relaxed),
+            dropped_frames: self.dropped_frames.load(Ordering::Relaxed),
+            activations: self.activations.load(Ordering::Relaxed),
+        }
+    }
+
+    fn estimate(&self, queued_frames: usize) -> u64 {
+        self.reserved_bytes.load(Ordering::Relaxed)
+            + queued_frames as u64 * self.frame_bytes.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: u64 = 8 * MB;
+
+    #[test]
+    fn test_unlimited_admits_everything() {
+        let budget = MemoryBudget::new(0);
+        assert!(budget.admit(10_000, FRAME));
+        assert!(!budget.update(10_000, Instant::now()));
+        assert_eq!(budget.stats().dropped_frames, 0);
+    }
+
+    #[test]
+    fn test_drops_frames_over_budget() {
+        let budget = MemoryBudget::new(100);
+        budget.set_reserved_bytes(20 * MB);
+
+        // 20 MB reserved + 9 queued frames + this one = 100 MB
+        assert!(budget.admit(9, FRAME));
+        assert!(!budget.admit(10, FRAME));
+
+        let now = Instant::now();
+        assert!(!budget.update(10, now));
+        let stats = budget.stats();
+        assert!(stats.limiting);
+        assert_eq!(stats.dropped_frames, 1);
+        assert_eq!(stats.activations, 1);
+        assert_eq!(stats.usage_mb, 100);
+
+        // Recovers once frames fit again
+        assert!(budget.admit(2, FRAME));
+        assert!(!budget.update(2, now));
+        assert!(!budget.stats().limiting);
+    }
+
+    #[test]
+    fn test_suspends_until_drained() {
+        let budget = MemoryBudget::new(80);
+        let start = Instant::now();
+
+        assert!(!budget.admit(10, FRAME));
+        assert!(!budget.update(10, start));
+
+        assert!(!budget.admit(10, FRAME));
+        assert!(budget.update(10, start + SUSPEND_AFTER));
+        assert!(budget.stats().capture_suspended);
+
+        // Stays suspended until the backlog is at half the limit
+        assert!(budget.update(6, start + SUSPEND_AFTER * 2));
+        assert!(!budget.update(5, start + SUSPEND_AFTER * 2));
+        assert!(!budget.stats().limiting);
+        assert_eq!(budget.stats().activations, 1);
+    }
+}
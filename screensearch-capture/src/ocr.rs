@@ -8,7 +8,8 @@
 //!
 //! - Async processing with tokio
 //! - Bounding box extraction for text regions
-//! - Confidence scoring (Windows OCR provides line-level confidence)
+//! - Confidence estimated from the text, as Windows OCR reports none (see
+//!   [`estimate_confidence`])
 //! - Memory-efficient image conversion
 //! - Support for multiple languages via Windows language packs
 //!
@@ -68,8 +69,12 @@ pub struct TextRegion {
     pub height: u32,
 
     /// Confidence score (0.0 - 1.0)
-    /// Windows OCR provides line-level confidence
+    /// Estimated with [`estimate_confidence`]
     pub confidence: f32,
+
+    /// Word-level regions of a line, in reading order (empty for word regions)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<TextRegion>,
 }
 
 impl TextRegion {
@@ -82,9 +87,16 @@ impl TextRegion {
             width,
             height,
             confidence,
+            words: Vec::new(),
         }
     }
 
+    /// Attach the word-level regions of a line
+    pub fn with_words(mut self, words: Vec<TextRegion>) -> Self {
+        self.words = words;
+        self
+    }
+
     /// Get the area of this region in pixels
     pub fn area(&self) -> u64 {
         self.width as u64 * self.height as u64
@@ -118,6 +130,10 @@ pub struct OcrResult {
 
     /// Image dimensions (width, height)
     pub image_dimensions: (u32, u32),
+
+    /// BCP-47 tag of the language the text was read in, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
 }
 
 impl OcrResult {
@@ -138,6 +154,7 @@ impl OcrResult {
             full_text,
             processing_time_ms,
             image_dimensions,
+            language: None,
         }
     }
 
@@ -148,6 +165,7 @@ impl OcrResult {
             full_text: String::new(),
             processing_time_ms: 0,
             image_dimensions,
+            language: None,
         }
     }
 
@@ -176,15 +194,166 @@ impl OcrResult {
     }
 }
 
+/// Common words, sorted, for the dictionary hit rate of [`estimate_confidence`]
+const COMMON_WORDS: &[&str] = &[
+    "a", "about", "above", "account", "add", "after", "all", "also", "am", "an", "and", "any",
+    "app", "are", "as", "at", "back", "be", "been", "before", "but", "by", "call", "can", "cancel",
+    "change", "chat", "check", "clear", "click", "close", "code", "copy", "could", "create",
+    "data", "date", "day", "delete", "do", "does", "done", "download", "edit", "email", "end",
+    "error", "file", "files", "find", "first", "for", "from", "get", "go", "has", "have", "help",
+    "here", "home", "how", "if", "in", "info", "into", "is", "it", "item", "items", "its", "just",
+    "last", "like", "link", "list", "load", "log", "login", "make", "may", "menu", "message",
+    "more", "most", "my", "name", "new", "next", "no", "not", "now", "of", "off", "ok", "on",
+    "one", "only", "open", "or", "other", "our", "out", "over", "page", "password", "please", "pm",
+    "post", "print", "profile", "project", "read", "recent", "reply", "report", "run", "save",
+    "search", "see", "select", "send", "set", "settings", "share", "show", "sign", "so", "some",
+    "start", "status", "stop", "submit", "support", "system", "team", "than", "that", "the",
+    "their", "them", "then", "there", "these", "they", "this", "time", "to", "today", "tools",
+    "up", "update", "upload", "use", "user", "view", "was", "we", "web", "week", "what", "when",
+    "where", "which", "who", "will", "window", "with", "work", "would", "year", "yes", "you",
+    "your",
+];
+
+/// Punctuation of ordinary text and code, besides letters and digits
+const COMMON_PUNCTUATION: &str = ".,:;!?'\"()[]{}<>-_/&@#%$*+=‘’“”–—…•·";
+
+/// Letters confused with each other, and with digits, on bad recognition
+const LOOKALIKES: &str = "Il1|";
+
+/// Estimate how likely recognized text is right, from 0.0 to 1.0
+///
+/// Windows OCR reports no confidence, so it is guessed from the text and its
+/// box: how many words are common words or look like words, how much of the
+/// text is letters, digits and ordinary punctuation, whether it has garbage
+/// such as long runs of one symbol, and whether the box fits the text.
+pub fn estimate_confidence(text: &str, width: u32, height: u32) -> f32 {
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if chars.is_empty() {
+        return 0.0;
+    }
+
+    let ordinary = chars
+        .iter()
+        .filter(|c| c.is_alphanumeric() || COMMON_PUNCTUATION.contains(**c))
+        .count();
+    let class_score = ordinary as f32 / chars.len() as f32;
+
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let word_score = if words.is_empty() {
+        0.0
+    } else {
+        words.iter().map(|word| word_score(word)).sum::<f32>() / words.len() as f32
+    };
+
+    let mut confidence =
+        0.3 * class_score + 0.5 * word_score + 0.2 * box_score(chars.len(), width, height);
+    // Long runs of one symbol are borders, rules and noise
+    if chars
+        .windows(4)
+        .any(|run| !run[0].is_alphanumeric() && run.iter().all(|c| *c == run[0]))
+    {
+        confidence *= 0.5;
+    }
+    confidence.clamp(0.0, 1.0)
+}
+
+/// How much a word looks like a real one, from 0.0 to 1.0
+fn word_score(word: &str) -> f32 {
+    let lower = word.to_lowercase();
+    if COMMON_WORDS.binary_search(&lower.as_str()).is_ok() {
+        return 1.0;
+    }
+    // Numbers, dates, times and versions such as 3.14, 12:30 or 2024-01-05
+    if word
+        .chars()
+        .all(|c| c.is_ascii_digit() || ".,:/-".contains(c))
+    {
+        return 0.9;
+    }
+    // Scripts past Latin, such as Cyrillic or CJK, are not judged further
+    if word.chars().any(|c| c.is_alphabetic() && c as u32 > 0x024F) {
+        return 0.8;
+    }
+
+    let letters = word.chars().filter(|c| c.is_alphabetic()).count();
+    let digits = word.chars().filter(|c| c.is_ascii_digit()).count();
+    let mut score = 0.8;
+    // Digits within a word, as in "c0mput3r"
+    if letters > 0 && digits > 0 {
+        score -= 0.2;
+    }
+
+    // Words have vowels, and rarely six consonants in a row
+    let is_vowel = |c: char| "aeiouyàáâäãåèéêëìíîïòóôöõùúûüý".contains(c);
+    let mut run = 0;
+    let mut longest_run = 0;
+    for c in lower.chars() {
+        run = if c.is_alphabetic() && !is_vowel(c) {
+            run + 1
+        } else {
+            0
+        };
+        longest_run = longest_run.max(run);
+    }
+    if letters >= 4 && !lower.chars().any(is_vowel) {
+        score -= 0.4;
+    }
+    if longest_run >= 6 {
+        score -= 0.3;
+    }
+
+    // Case flipping back and forth, as in "lIlIl"; camelCase flips a few times
+    let flips = word
+        .chars()
+        .zip(word.chars().skip(1))
+        .filter(|(a, b)| a.is_lowercase() && b.is_uppercase())
+        .count();
+    if flips > 2 {
+        score -= 0.3;
+    }
+    let lookalikes = word.chars().filter(|c| LOOKALIKES.contains(*c)).count();
+    if lookalikes >= 3 && lookalikes * 2 >= word.chars().count() {
+        score -= 0.4;
+    }
+    f32::max(score, 0.0)
+}
+
+/// How well a `width` x `height` box fits a line of `chars` characters, from
+/// 0.0 to 1.0
+fn box_score(chars: usize, width: u32, height: u32) -> f32 {
+    if width == 0 || height == 0 {
+        return 0.5;
+    }
+    // Smaller text is rarely read right
+    if height < 6 {
+        return 0.3;
+    }
+    // Characters are about a third to once as wide as the line is high
+    let char_width = width as f32 / chars as f32 / height as f32;
+    if (0.2..=1.5).contains(&char_width) {
+        1.0
+    } else if (0.1..=3.0).contains(&char_width) {
+        0.6
+    } else {
+        0.2
+    }
+}
+
 /// Windows OCR engine wrapper
 ///
 /// This struct manages the Windows.Media.Ocr OCR engine and provides
-/// async methods for processing images. It automatically selects the
-/// appropriate language based on system configuration.
+/// async methods for processing images. It reads the language it was created
+/// with, or the user's profile languages by default.
 pub struct OcrEngine {
     /// Windows OCR engine instance (not directly used, created per-task to avoid Send/Sync issues)
     #[allow(dead_code)]
     engine: WindowsOcrEngine,
+    /// Language to read, `None` for the user's profile languages
+    language: Option<String>,
 }
 
 impl OcrEngine {
@@ -201,15 +370,13 @@ impl OcrEngine {
     pub async fn new() -> Result<Self> {
         tracing::debug!("Initializing Windows OCR engine");
 
-        let engine = WindowsOcrEngine::TryCreateFromUserProfileLanguages().map_err(|e| {
-            CaptureError::OcrError(format!(
-                "Failed to create OCR engine from user languages: {}",
-                e
-            ))
-        })?;
+        let engine = Self::create_engine(None)?;
 
         tracing::info!("OCR engine initialized successfully");
-        Ok(Self { engine })
+        Ok(Self {
+            engine,
+            language: None,
+        })
     }
 
     /// Create OCR engine with specific language
@@ -222,22 +389,45 @@ impl OcrEngine {
     ///
     /// Returns error if the specified language pack is not available
     pub async fn new_with_language(language_tag: &str) -> Result<Self> {
+        tracing::debug!("Initializing OCR engine with language: {}", language_tag);
+
+        let engine = Self::create_engine(Some(language_tag))?;
+
+        tracing::info!("OCR engine initialized for language: {}", language_tag);
+        Ok(Self {
+            engine,
+            language: Some(language_tag.to_string()),
+        })
+    }
+
+    /// Language the engine reads, `None` for the user's profile languages
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Create a Windows OCR engine for a language, or for the user's profile
+    /// languages
+    fn create_engine(language_tag: Option<&str>) -> Result<WindowsOcrEngine> {
         use windows::Globalization::Language;
 
-        tracing::debug!("Initializing OCR engine with language: {}", language_tag);
+        let Some(language_tag) = language_tag else {
+            return WindowsOcrEngine::TryCreateFromUserProfileLanguages().map_err(|e| {
+                CaptureError::OcrError(format!(
+                    "Failed to create OCR engine from user languages: {}",
+                    e
+                ))
+            });
+        };
 
         let language = Language::CreateLanguage(&language_tag.into())
             .map_err(|e| CaptureError::OcrError(format!("Invalid language tag: {}", e)))?;
 
-        let engine = WindowsOcrEngine::TryCreateFromLanguage(&language).map_err(|e| {
+        WindowsOcrEngine::TryCreateFromLanguage(&language).map_err(|e| {
             CaptureError::OcrError(format!(
                 "Failed to create OCR engine for language {}: {}",
                 language_tag, e
             ))
-        })?;
-
-        tracing::info!("OCR engine initialized for language: {}", language_tag);
-        Ok(Self { engine })
+        })
     }
 
     /// Get available OCR languages on the system
@@ -289,6 +479,19 @@ impl OcrEngine {
     /// - Image encoding fails
     /// - Windows OCR API fails
     pub async fn process_image(&self, image: &RgbaImage) -> Result<OcrResult> {
+        self.process_image_in(image, self.language.as_deref()).await
+    }
+
+    /// Process an image in a given language, `None` for the user's profile
+    /// languages
+    ///
+    /// Like [`process_image`](Self::process_image), for callers choosing the
+    /// language per image. The result records the language read.
+    pub async fn process_image_in(
+        &self,
+        image: &RgbaImage,
+        language_tag: Option<&str>,
+    ) -> Result<OcrResult> {
         let start_time = std::time::Instant::now();
         let (width, height) = image.dimensions();
 
@@ -310,18 +513,19 @@ impl OcrEngine {
 
         // Perform OCR synchronously to avoid Send/Sync issues with Windows types
         // Windows COM objects are not Send/Sync safe, so we create a new engine in the blocking task
-        let regions = tokio::task::spawn_blocking({
+        let language_tag = language_tag.map(str::to_string);
+        let (regions, language) = tokio::task::spawn_blocking({
             let buffer = buffer.clone();
-            move || -> Result<Vec<TextRegion>> {
+            move || -> Result<(Vec<TextRegion>, Option<String>)> {
                 // Create a new engine in the blocking thread
-                let engine =
-                    WindowsOcrEngine::TryCreateFromUserProfileLanguages().map_err(|e| {
-                        CaptureError::OcrError(format!(
-                            "Failed to create OCR engine in worker thread: {}",
-                            e
-                        ))
-                    })?;
-                Self::process_image_sync(engine, &buffer)
+                let engine = Self::create_engine(language_tag.as_deref())?;
+                let language = engine
+                    .RecognizerLanguage()
+                    .and_then(|language| language.LanguageTag())
+                    .map(|tag| tag.to_string())
+                    .ok()
+                    .or(language_tag);
+                Ok((Self::process_image_sync(engine, &buffer)?, language))
             }
         })
         .await
@@ -336,7 +540,9 @@ impl OcrEngine {
             regions.iter().map(|r| r.text.len()).sum::<usize>()
         );
 
-        Ok(OcrResult::new(regions, (width, height), processing_time_ms))
+        let mut result = OcrResult::new(regions, (width, height), processing_time_ms);
+        result.language = language;
+        Ok(result)
     }
 
     /// Process image synchronously (called from spawn_blocking)
@@ -429,6 +635,7 @@ impl OcrEngine {
                 let mut min_y = f32::MAX;
                 let mut max_x = f32::MIN;
                 let mut max_y = f32::MIN;
+                let mut word_regions = Vec::new();
 
                 for j in 0..words.Size().unwrap_or(0) {
                     if let Ok(word) = words.GetAt(j) {
@@ -437,25 +644,43 @@ impl OcrEngine {
                             min_y = min_y.min(bounds.Y);
                             max_x = max_x.max(bounds.X + bounds.Width);
                             max_y = max_y.max(bounds.Y + bounds.Height);
+
+                            // Keep word boxes so callers can locate text within a line
+                            if let Ok(word_text) = word.Text() {
+                                let word_text = word_text.to_string();
+                                let confidence = estimate_confidence(
+                                    &word_text,
+                                    bounds.Width as u32,
+                                    bounds.Height as u32,
+                                );
+                                word_regions.push(TextRegion::new(
+                                    word_text,
+                                    bounds.X as u32,
+                                    bounds.Y as u32,
+                                    bounds.Width as u32,
+                                    bounds.Height as u32,
+                                    confidence,
+                                ));
+                            }
                         }
                     }
                 }
 
-                // Windows OCR doesn't provide explicit confidence scores
-                // We use 1.0 for successfully recognized text
-                // In production, you could implement heuristics based on word count,
-                // character types, etc. to estimate confidence
-                let confidence = 1.0;
-
                 if min_x < f32::MAX && !text.is_empty() {
-                    regions.push(TextRegion::new(
-                        text,
-                        min_x as u32,
-                        min_y as u32,
-                        (max_x - min_x) as u32,
-                        (max_y - min_y) as u32,
-                        confidence,
-                    ));
+                    // Windows OCR reports no confidence of its own
+                    let (width, height) = ((max_x - min_x) as u32, (max_y - min_y) as u32);
+                    let confidence = estimate_confidence(&text, width, height);
+                    regions.push(
+                        TextRegion::new(
+                            text,
+                            min_x as u32,
+                            min_y as u32,
+                            width,
+                            height,
+                            confidence,
+                        )
+                        .with_words(word_regions),
+                    );
                 }
             }
         }
@@ -563,6 +788,38 @@ mod tests {
         assert_eq!(sorted[2].text, "Low");
     }
 
+    #[test]
+    fn test_common_words_sorted() {
+        assert!(COMMON_WORDS.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_estimate_confidence() {
+        // Ordinary text, code, numbers and other scripts pass the default 0.7
+        for text in [
+            "Save changes to the document",
+            "let mut total = price * 2;",
+            "12:30 PM",
+            "https://example.com/path",
+            "Настройки системы",
+        ] {
+            let confidence = estimate_confidence(text, text.len() as u32 * 8, 16);
+            assert!(confidence >= 0.8, "{}: {}", text, confidence);
+        }
+
+        // Garbage does not
+        for text in ["|||| ~~~~", "lIlIlI", "xkcdqwrt zzvbn", "------------"] {
+            let confidence = estimate_confidence(text, text.len() as u32 * 8, 16);
+            assert!(confidence < 0.7, "{}: {}", text, confidence);
+        }
+        assert_eq!(estimate_confidence("  ", 20, 16), 0.0);
+
+        // Tiny text and boxes that do not fit the text count against it
+        let fitting = estimate_confidence("Save changes", 96, 16);
+        assert!(estimate_confidence("Save changes", 96, 4) < fitting);
+        assert!(estimate_confidence("Save changes", 2000, 16) < fitting);
+    }
+
     #[tokio::test]
     async fn test_ocr_engine_creation() {
         // This test may fail in CI/headless environments without Windows language packs
@@ -0,0 +1,289 @@
+//! Per-frame OCR language selection
+//!
+//! Windows OCR reads one language per engine, and the user-profile default
+//! butchers screens in another script. With several `[ocr] languages`
+//! configured, the OCR processor picks one per frame: the language last
+//! detected for the frame's app, or, when the app is new or its text no
+//! longer reads well, the language whose quick pass over a downscaled copy of
+//! the frame reads best.
+//!
+//! A pass reads well when its text looks like words (see
+//! [`estimate_confidence`](crate::ocr::estimate_confidence)) written in the
+//! script of the language it was read in. An engine reading a language in the
+//! wrong script produces look-alike letters of its own script or garbage, both
+//! of which score low.
+
+use crate::OcrResult;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Read score at which the language remembered for an app is kept
+pub const GOOD_READ_SCORE: f32 = 0.5;
+
+/// Apps whose language is remembered; the map is cleared when full
+const MAX_REMEMBERED_APPS: usize = 256;
+
+/// Writing systems told apart by the pre-detector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Hangul,
+    Kana,
+    Han,
+}
+
+/// Script of a letter, `None` for digits, punctuation and unknown scripts
+pub fn script_of(c: char) -> Option<Script> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    match c as u32 {
+        0x0041..=0x024F | 0x1E00..=0x1EFF => Some(Script::Latin),
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Some(Script::Greek),
+        0x0400..=0x052F => Some(Script::Cyrillic),
+        0x0590..=0x05FF => Some(Script::Hebrew),
+        0x0600..=0x06FF | 0x0750..=0x077F => Some(Script::Arabic),
+        0x0900..=0x097F => Some(Script::Devanagari),
+        0x0E00..=0x0E7F => Some(Script::Thai),
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Some(Script::Hangul),
+        0x3040..=0x30FF => Some(Script::Kana),
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF => Some(Script::Han),
+        _ => None,
+    }
+}
+
+/// Scripts a language is written in, from its BCP-47 tag
+///
+/// Screens in any language show Latin words (names, code, URLs), so they
+/// count for every language.
+pub fn language_scripts(tag: &str) -> &'static [Script] {
+    let lower = tag.to_ascii_lowercase();
+    if lower.split('-').any(|subtag| subtag == "latn") {
+        return &[Script::Latin];
+    }
+    if lower.split('-').any(|subtag| subtag == "cyrl") {
+        return &[Script::Cyrillic, Script::Latin];
+    }
+
+    match lower.split('-').next().unwrap_or_default() {
+        "ru" | "uk" | "be" | "bg" | "sr" | "mk" | "kk" => &[Script::Cyrillic, Script::Latin],
+        "el" => &[Script::Greek, Script::Latin],
+        "he" => &[Script::Hebrew, Script::Latin],
+        "ar" | "fa" | "ur" => &[Script::Arabic, Script::Latin],
+        "hi" | "mr" | "ne" => &[Script::Devanagari, Script::Latin],
+        "th" => &[Script::Thai, Script::Latin],
+        "zh" => &[Script::Han, Script::Latin],
+        "ja" => &[Script::Kana, Script::Han, Script::Latin],
+        "ko" => &[Script::Hangul, Script::Han, Script::Latin],
+        _ => &[Script::Latin],
+    }
+}
+
+/// How well a pass in `language` read, from 0.0 to 1.0
+///
+/// The confidence of the lines, weighted by length, times the share of the
+/// letters written in a script of the language. Latin letters only count when
+/// the language has no script of its own or no letter of that script was
+/// read, so a Cyrillic pass over an English screen does not win on the
+/// Latin words every language accepts.
+pub fn read_score(result: &OcrResult, language: &str) -> f32 {
+    let chars: usize = result.regions.iter().map(|r| r.text.chars().count()).sum();
+    if chars == 0 {
+        return 0.0;
+    }
+    let confidence = result
+        .regions
+        .iter()
+        .map(|r| r.confidence * r.text.chars().count() as f32)
+        .sum::<f32>()
+        / chars as f32;
+
+    let scripts = language_scripts(language);
+    let letters: Vec<Script> = result.full_text.chars().filter_map(script_of).collect();
+    if letters.is_empty() {
+        return confidence;
+    }
+    let own = letters
+        .iter()
+        .filter(|s| **s != Script::Latin && scripts.contains(s))
+        .count();
+    let latin = letters.iter().filter(|s| **s == Script::Latin).count();
+    let in_script = if scripts == [Script::Latin] || own == 0 {
+        own + latin
+    } else {
+        // Latin words on a screen in another script are fine, but should not
+        // carry the pass on their own
+        own + latin / 2
+    };
+
+    confidence * in_script as f32 / letters.len() as f32
+}
+
+/// The language of the best-reading pass, if any pass read text
+///
+/// Passes are `(language, read score)` in configured order; ties go to the
+/// language configured first.
+pub fn best_language<'a>(passes: impl IntoIterator<Item = (&'a str, f32)>) -> Option<&'a str> {
+    let mut best: Option<(&str, f32)> = None;
+    for (language, score) in passes {
+        if score > best.map_or(0.0, |(_, best_score)| best_score) {
+            best = Some((language, score));
+        }
+    }
+    best.map(|(language, _)| language)
+}
+
+/// Match a configured language to an installed OCR language
+///
+/// Tags compare case-insensitively, and a bare language ("de") matches the
+/// first installed region of it ("de-DE").
+pub fn resolve_language(tag: &str, available: &[String]) -> Option<String> {
+    available
+        .iter()
+        .find(|a| a.eq_ignore_ascii_case(tag))
+        .or_else(|| {
+            available.iter().find(|a| {
+                a.split('-')
+                    .next()
+                    .is_some_and(|primary| primary.eq_ignore_ascii_case(tag))
+            })
+        })
+        .cloned()
+}
+
+/// Languages configured for OCR and the last one detected per app
+#[derive(Debug)]
+pub struct LanguageSelector {
+    /// Installed language tags, the first being the default
+    languages: Vec<String>,
+    /// Index into `languages` last detected for each app
+    by_app: Mutex<HashMap<String, usize>>,
+}
+
+impl LanguageSelector {
+    /// Create a selector for installed language tags, the first the default
+    pub fn new(languages: Vec<String>) -> Self {
+        Self {
+            languages,
+            by_app: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Configured languages, the default first
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    /// Language used when nothing tells the languages apart
+    pub fn default_language(&self) -> Option<&str> {
+        self.languages.first().map(String::as_str)
+    }
+
+    /// Language last detected for an app
+    pub fn remembered(&self, app: Option<&str>) -> Option<&str> {
+        let by_app = self.by_app.lock().unwrap_or_else(|e| e.into_inner());
+        let index = *by_app.get(app?)?;
+        self.languages.get(index).map(String::as_str)
+    }
+
+    /// Remember the language detected for an app
+    pub fn remember(&self, app: Option<&str>, language: &str) {
+        let (Some(app), Some(index)) = (app, self.languages.iter().position(|l| l == language))
+        else {
+            return;
+        };
+
+        let mut by_app = self.by_app.lock().unwrap_or_else(|e| e.into_inner());
+        if by_app.len() >= MAX_REMEMBERED_APPS && !by_app.contains_key(app) {
+            by_app.clear();
+        }
+        by_app.insert(app.to_string(), index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextRegion;
+
+    fn result(lines: &[(&str, f32)]) -> OcrResult {
+        let regions = lines
+            .iter()
+            .map(|(text, confidence)| TextRegion::new(text.to_string(), 0, 0, 100, 20, *confidence))
+            .collect();
+        OcrResult::new(regions, (100, 100), 0)
+    }
+
+    #[test]
+    fn test_language_scripts() {
+        assert_eq!(language_scripts("en-US"), [Script::Latin]);
+        assert_eq!(language_scripts("ru-RU")[0], Script::Cyrillic);
+        assert_eq!(language_scripts("sr-Latn-RS"), [Script::Latin]);
+        assert_eq!(language_scripts("ja")[0], Script::Kana);
+        assert_eq!(script_of('Ж'), Some(Script::Cyrillic));
+        assert_eq!(script_of('é'), Some(Script::Latin));
+        assert_eq!(script_of('7'), None);
+    }
+
+    #[test]
+    fn test_read_score_prefers_matching_script() {
+        let russian = result(&[("Сохранить изменения", 0.8)]);
+        assert!(read_score(&russian, "ru-RU") > 0.7);
+        assert_eq!(read_score(&russian, "en-US"), 0.0);
+
+        // A Cyrillic engine reading the same Latin words ties, and ties go to
+        // the language configured first
+        let english = result(&[("Save changes", 0.8)]);
+        assert_eq!(read_score(&english, "en-US"), read_score(&english, "ru-RU"));
+
+        // Mixed screens: the Cyrillic pass is judged on its Cyrillic letters
+        let mixed = result(&[("Файл File", 0.8)]);
+        assert!(read_score(&mixed, "ru-RU") > read_score(&mixed, "en-US"));
+
+        assert_eq!(read_score(&result(&[]), "en-US"), 0.0);
+    }
+
+    #[test]
+    fn test_resolve_language() {
+        let available = vec!["en-US".to_string(), "de-DE".to_string()];
+        assert_eq!(
+            resolve_language("EN-us", &available).as_deref(),
+            Some("en-US")
+        );
+        assert_eq!(resolve_language("de", &available).as_deref(), Some("de-DE"));
+        assert_eq!(resolve_language("ru", &available), None);
+    }
+
+    #[test]
+    fn test_selector_remembers_per_app() {
+        let selector = LanguageSelector::new(vec!["en-US".to_string(), "ru-RU".to_string()]);
+        assert_eq!(selector.default_language(), Some("en-US"));
+        assert_eq!(selector.remembered(Some("telegram.exe")), None);
+
+        selector.remember(Some("telegram.exe"), "ru-RU");
+        selector.remember(Some("code.exe"), "fr-FR");
+        selector.remember(None, "ru-RU");
+        assert_eq!(selector.remembered(Some("telegram.exe")), Some("ru-RU"));
+        assert_eq!(selector.remembered(Some("code.exe")), None);
+        assert_eq!(selector.remembered(None), None);
+    }
+
+    #[test]
+    fn test_best_language() {
+        assert_eq!(
+            best_language([("en-US", 0.4), ("ru-RU", 0.7)]),
+            Some("ru-RU")
+        );
+        assert_eq!(
+            best_language([("en-US", 0.5), ("ru-RU", 0.5)]),
+            Some("en-US")
+        );
+        assert_eq!(best_language([("en-US", 0.0), ("ru-RU", 0.0)]), None);
+    }
+}
@@ -34,7 +34,11 @@
 //! }
 //! ```
 
-use crate::{CaptureError, CapturedFrame, OcrEngine, OcrResult, Result};
+use crate::ocr_language::{
+    best_language, read_score, resolve_language, LanguageSelector, GOOD_READ_SCORE,
+};
+use crate::{CaptureError, CapturedFrame, FrameReceiver, OcrEngine, OcrResult, Result};
+use image::imageops::{self, FilterType};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -68,6 +72,12 @@ pub struct OcrProcessorConfig {
 
     /// Metrics reporting interval (seconds)
     pub metrics_interval_secs: u64,
+
+    /// BCP-47 tags of the languages to read, the first being the default
+    ///
+    /// Empty reads the user's profile languages. With several, the language
+    /// is picked per frame (see [`crate::ocr_language`]).
+    pub languages: Vec<String>,
 }
 
 impl Default for OcrProcessorConfig {
@@ -81,10 +91,15 @@ impl Default for OcrProcessorConfig {
             channel_buffer_size: 100,
             enable_metrics: true,
             metrics_interval_secs: 60,
+            languages: Vec::new(),
         }
     }
 }
 
+/// Widest image a language detection pass reads; larger frames are scaled
+/// down, which reads much faster and still shows the script
+const DETECTION_PASS_MAX_WIDTH: u32 = 1280;
+
 /// Performance metrics for OCR processing
 #[derive(Debug, Clone, Default)]
 pub struct OcrMetrics {
@@ -195,6 +210,8 @@ pub struct ProcessedFrame {
 pub struct OcrProcessor {
     config: OcrProcessorConfig,
     ocr_engine: OcrEngine,
+    /// Per-frame language selection, when several languages are installed
+    languages: Option<LanguageSelector>,
     running: Arc<AtomicBool>,
     metrics: OcrMetrics,
 }
@@ -202,23 +219,66 @@ pub struct OcrProcessor {
 impl OcrProcessor {
     /// Create a new OCR processor
     ///
+    /// Configured languages without an installed language pack are skipped
+    /// with a warning.
+    ///
     /// # Errors
     ///
     /// Returns error if OCR engine initialization fails
     pub async fn new(config: OcrProcessorConfig) -> Result<Self> {
         tracing::info!("Initializing OCR processor with config: {:?}", config);
 
-        let ocr_engine = OcrEngine::new().await?;
+        let languages = Self::installed_languages(&config.languages);
+        let ocr_engine = match languages.first() {
+            Some(language) => OcrEngine::new_with_language(language).await?,
+            None => OcrEngine::new().await?,
+        };
+        let languages = (languages.len() > 1).then(|| {
+            tracing::info!("Selecting the OCR language per frame among {:?}", languages);
+            LanguageSelector::new(languages)
+        });
         let metrics = OcrMetrics::new();
 
         Ok(Self {
             config,
             ocr_engine,
+            languages,
             running: Arc::new(AtomicBool::new(false)),
             metrics,
         })
     }
 
+    /// The configured languages that can be read, as installed tags
+    fn installed_languages(configured: &[String]) -> Vec<String> {
+        if configured.is_empty() {
+            return Vec::new();
+        }
+        let available = match OcrEngine::available_languages() {
+            Ok(available) => available,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to list OCR languages, reading the user's languages: {}",
+                    e
+                );
+                return Vec::new();
+            }
+        };
+
+        let mut languages = Vec::new();
+        for tag in configured {
+            match resolve_language(tag, &available) {
+                Some(language) if !languages.contains(&language) => languages.push(language),
+                Some(_) => {}
+                None => tracing::warn!(
+                    "OCR language {} is not installed (installed: {:?})",
+                    tag,
+                    available
+                ),
+            }
+        }
+        languages
+    }
+
     /// Get current metrics
     pub fn metrics(&self) -> &OcrMetrics {
         &self.metrics
@@ -248,7 +308,13 @@ impl OcrProcessor {
         tracing::debug!("Processing frame from {}", frame_timestamp);
 
         // Attempt OCR with retry logic
-        let ocr_result = self.process_with_retry(&frame.image).await?;
+        let ocr_result = match &self.languages {
+            Some(selector) => self.process_multilingual(selector, &frame).await?,
+            None => {
+                self.process_with_retry(&frame.image, self.ocr_engine.language())
+                    .await?
+            }
+        };
 
         // Check if frame should be stored
         let should_store = if ocr_result.regions.is_empty() {
@@ -281,15 +347,16 @@ impl OcrProcessor {
         }))
     }
 
-    /// Start processing frames from a channel
+    /// Start processing frames from a frame queue
     ///
     /// This spawns worker tasks that continuously consume frames from the
-    /// provided channel and process them with OCR. Results are sent to
-    /// the output channel for database storage.
+    /// provided queue, taking them from each monitor in turn, and process
+    /// them with OCR. Results are sent to the output channel for database
+    /// storage.
     ///
     /// # Arguments
     ///
-    /// * `input_rx` - Receiver for captured frames
+    /// * `input_rx` - Receiver of the frame queue (see [`crate::frame_queue`])
     /// * `output_tx` - Sender for processed frames
     ///
     /// # Returns
@@ -297,7 +364,7 @@ impl OcrProcessor {
     /// A join handle for the processing task
     pub fn start_processing(
         self: Arc<Self>,
-        mut input_rx: mpsc::Receiver<CapturedFrame>,
+        mut input_rx: FrameReceiver,
         output_tx: mpsc::Sender<ProcessedFrame>,
     ) -> tokio::task::JoinHandle<()> {
         self.running.store(true, Ordering::SeqCst);
@@ -341,7 +408,7 @@ impl OcrProcessor {
                         }
                     }
                     None => {
-                        tracing::info!("Input channel closed, stopping processor");
+                        tracing::info!("Frame queue closed, stopping processor");
                         break;
                     }
                 }
@@ -363,12 +430,98 @@ impl OcrProcessor {
         self.running.store(false, Ordering::SeqCst);
     }
 
+    /// Read a frame in the language of its app, detecting it if needed
+    ///
+    /// The language last detected for the frame's app is tried first. When
+    /// there is none, or the frame no longer reads well in it, a detection
+    /// pass over a downscaled copy in each language picks the language the
+    /// frame is read in.
+    async fn process_multilingual(
+        &self,
+        selector: &LanguageSelector,
+        frame: &CapturedFrame,
+    ) -> Result<OcrResult> {
+        let app = frame.active_process.as_deref();
+        let mut remembered_result = None;
+        if let Some(language) = selector.remembered(app) {
+            let result = self
+                .process_with_retry(&frame.image, Some(language))
+                .await?;
+            if read_score(&result, language) >= GOOD_READ_SCORE {
+                return Ok(result);
+            }
+            remembered_result = Some(result);
+        }
+
+        let Some(language) = self.detect_language(selector, &frame.image).await else {
+            // No text in any language; nothing to remember
+            return match remembered_result {
+                Some(result) => Ok(result),
+                None => {
+                    self.process_with_retry(&frame.image, selector.default_language())
+                        .await
+                }
+            };
+        };
+        tracing::debug!("Detected OCR language {} for {:?}", language, app);
+        selector.remember(app, language);
+
+        match remembered_result {
+            Some(result) if result.language.as_deref() == Some(language) => Ok(result),
+            _ => self.process_with_retry(&frame.image, Some(language)).await,
+        }
+    }
+
+    /// Language whose pass over a downscaled copy of an image reads best
+    async fn detect_language<'a>(
+        &self,
+        selector: &'a LanguageSelector,
+        image: &image::RgbaImage,
+    ) -> Option<&'a str> {
+        let (width, height) = image.dimensions();
+        let small;
+        let image = if width > DETECTION_PASS_MAX_WIDTH {
+            let scaled_height =
+                (height as u64 * DETECTION_PASS_MAX_WIDTH as u64 / width as u64).max(1) as u32;
+            small = imageops::resize(
+                image,
+                DETECTION_PASS_MAX_WIDTH,
+                scaled_height,
+                FilterType::Triangle,
+            );
+            &small
+        } else {
+            image
+        };
+
+        let mut passes = Vec::with_capacity(selector.languages().len());
+        for language in selector.languages() {
+            let score = match self
+                .ocr_engine
+                .process_image_in(image, Some(language))
+                .await
+            {
+                Ok(result) => read_score(&result, language),
+                Err(e) => {
+                    tracing::debug!("Detection pass in {} failed: {}", language, e);
+                    0.0
+                }
+            };
+            passes.push((language.as_str(), score));
+        }
+        best_language(passes)
+    }
+
     /// Process image with retry logic
-    async fn process_with_retry(&self, image: &image::RgbaImage) -> Result<OcrResult> {
+    async fn process_with_retry(
+        &self,
+        image: &image::RgbaImage,
+        language: Option<&str>,
+    ) -> Result<OcrResult> {
         let mut last_error = None;
 
         for attempt in 0..=self.config.max_retries {
-            match self.ocr_engine.process_image(image).await {
+            match self.ocr_engine.process_image_in(image, language).await {
                 Ok(result) => return Ok(result),
                 Err(e) => {
                     last_error = Some(e);
@@ -433,6 +586,12 @@ impl OcrProcessorBuilder {
         self
     }
 
+    /// Set the languages to read, the first being the default
+    pub fn languages(mut self, languages: Vec<String>) -> Self {
+        self.config.languages = languages;
+        self
+    }
+
     /// Build the OcrProcessor
     pub async fn build(self) -> Result<OcrProcessor> {
         OcrProcessor::new(self.config).await
@@ -457,6 +616,7 @@ mod tests {
         assert_eq!(config.worker_threads, 2);
         assert_eq!(config.max_retries, 3);
         assert!(!config.store_empty_frames);
+        assert!(config.languages.is_empty());
     }
 
     #[test]
@@ -570,7 +730,7 @@ mod tests {
     #[tokio::test]
     async fn test_processor_channels() {
         if let Ok(processor) = OcrProcessor::new(OcrProcessorConfig::default()).await {
-            let (input_tx, input_rx) = mpsc::channel(10);
+            let (input_tx, input_rx) = crate::frame_queue(10);
             let (output_tx, _output_rx) = mpsc::channel(10);
 
             let processor = Arc::new(processor);
@@ -586,7 +746,7 @@ mod tests {
             };
 
             input_tx.send(frame).await.ok();
-            drop(input_tx); // Close channel to stop processor
+            drop(input_tx); // Close queue to stop processor
 
             // Wait a moment for processing
             tokio::time::sleep(Duration::from_millis(100)).await;
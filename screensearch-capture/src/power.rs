@@ -0,0 +1,127 @@
+//! Power-aware throttling
+//!
+//! On laptops a recorder should not drain the battery. [`PowerSaving`]
+//! tracks whether the machine runs on battery or in power saver mode, as
+//! reported by the power monitor of the application, and then:
+//!
+//! 1. stretches the capture interval to `performance.battery_interval_ms`
+//!    when that is longer than the configured one, and
+//! 2. pauses background work such as embedding generation when
+//!    `performance.battery_pause_workers` is set.
+//!
+//! Both end as soon as the machine is plugged in again.
+
+use crate::stats::PowerStats;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Power source and throttling settings of the capture pipeline
+#[derive(Debug, Default)]
+pub struct PowerSaving {
+    /// Capture interval while saving power, in milliseconds (0 = unchanged)
+    interval_ms: AtomicU64,
+
+    /// Whether background workers pause while saving power
+    pause_workers: AtomicBool,
+
+    /// Whether the machine runs on battery
+    on_battery: AtomicBool,
+
+    /// Whether the system power saver (battery saver) is on
+    power_saver: AtomicBool,
+}
+
+impl PowerSaving {
+    /// Set the capture interval while saving power (0 = unchanged) and
+    /// whether background workers pause
+    pub fn set_settings(&self, interval_ms: u64, pause_workers: bool) {
+        self.interval_ms.store(interval_ms, Ordering::Relaxed);
+        self.pause_workers.store(pause_workers, Ordering::Relaxed);
+    }
+
+    /// Record the power source and power saver state
+    pub fn update(&self, on_battery: bool, power_saver: bool) {
+        let was_saving = self.is_saving();
+        self.on_battery.store(on_battery, Ordering::Relaxed);
+        self.power_saver.store(power_saver, Ordering::Relaxed);
+
+        match (was_saving, self.is_saving()) {
+            (false, true) => tracing::info!(
+                on_battery,
+                power_saver,
+                "Saving power: throttling capture and background work"
+            ),
+            (true, false) => tracing::info!("No longer saving power, throttling ended"),
+            _ => {}
+        }
+    }
+
+    /// Whether the machine runs on battery or in power saver mode
+    pub fn is_saving(&self) -> bool {
+        self.on_battery.load(Ordering::Relaxed) || self.power_saver.load(Ordering::Relaxed)
+    }
+
+    /// Capture interval to use instead of `interval_ms`
+    pub fn capture_interval_ms(&self, interval_ms: u64) -> u64 {
+        if self.is_saving() {
+            interval_ms.max(self.interval_ms.load(Ordering::Relaxed))
+        } else {
+            interval_ms
+        }
+    }
+
+    /// Whether background workers should pause
+    pub fn workers_paused(&self) -> bool {
+        self.is_saving() && self.pause_workers.load(Ordering::Relaxed)
+    }
+
+    /// Whether saving power changes anything
+    pub fn is_enabled(&self) -> bool {
+        self.interval_ms.load(Ordering::Relaxed) > 0 || self.pause_workers.load(Ordering::Relaxed)
+    }
+
+    /// Current state
+    pub fn stats(&self) -> PowerStats {
+        PowerStats {
+            on_battery: self.on_battery.load(Ordering::Relaxed),
+            power_saver: self.power_saver.load(Ordering::Relaxed),
+            throttled: self.is_saving(),
+            battery_interval_ms: self.interval_ms.load(Ordering::Relaxed),
+            workers_paused: self.workers_paused(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttles_only_when_saving_power() {
+        let power = PowerSaving::default();
+        power.set_settings(10_000, true);
+
+        assert_eq!(power.capture_interval_ms(3000), 3000);
+        assert!(!power.workers_paused());
+
+        power.update(true, false);
+        assert_eq!(power.capture_interval_ms(3000), 10_000);
+        // A longer configured interval is kept
+        assert_eq!(power.capture_interval_ms(20_000), 20_000);
+        assert!(power.workers_paused());
+
+        power.update(false, true);
+        assert!(power.stats().throttled);
+
+        power.update(false, false);
+        assert_eq!(power.capture_interval_ms(3000), 3000);
+        assert!(!power.workers_paused());
+    }
+
+    #[test]
+    fn test_disabled_settings_change_nothing() {
+        let power = PowerSaving::default();
+        power.update(true, true);
+        assert_eq!(power.capture_interval_ms(3000), 3000);
+        assert!(!power.workers_paused());
+    }
+}
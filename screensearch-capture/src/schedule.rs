@@ -0,0 +1,219 @@
+//! Capture schedules
+//!
+//! Rules limiting capture to certain hours of the week, such as working
+//! hours. Each rule names days and a local time range:
+//!
+//! - `"Mon-Fri 09:00-18:00"` - a range of days
+//! - `"Mon,Wed,Fri 13:00-17:30"` - a list of days or ranges
+//! - `"weekdays 08:00-12:00"`, `"weekends 10:00-14:00"`, `"daily 07:00-23:00"`
+//! - `"09:00-18:00"` - every day
+//! - `"Fri 22:00-02:00"` - a range ending before it starts runs past
+//!   midnight into the next day
+//!
+//! Capture runs while any rule matches. An empty schedule captures at all
+//! times.
+
+use chrono::{Datelike, NaiveDateTime, Timelike};
+
+const DAY_NAMES: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// One rule of a capture schedule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleRule {
+    /// Days the time range starts on, Monday first
+    days: [bool; 7],
+
+    /// Start of the time range in minutes after midnight
+    start: u32,
+
+    /// End of the time range in minutes after midnight (exclusive)
+    end: u32,
+}
+
+impl ScheduleRule {
+    /// Parse a rule such as `"Mon-Fri 09:00-18:00"`
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        let (days, times) = match rule.rsplit_once(char::is_whitespace) {
+            Some((days, times)) => (parse_days(days.trim())?, times),
+            None => ([true; 7], rule),
+        };
+
+        let (start, end) = times
+            .split_once('-')
+            .ok_or_else(|| format!("Expected a time range like 09:00-18:00, got '{}'", times))?;
+        let (start, end) = (parse_time(start)?, parse_time(end)?);
+        if start == end {
+            return Err(format!("Time range '{}' is empty", times));
+        }
+
+        Ok(Self { days, start, end })
+    }
+
+    /// Whether the rule covers the local time `at`
+    fn matches(&self, at: NaiveDateTime) -> bool {
+        let day = at.weekday().num_days_from_monday() as usize;
+        let minute = at.hour() * 60 + at.minute();
+
+        if self.start < self.end {
+            self.days[day] && (self.start..self.end).contains(&minute)
+        } else {
+            // Past midnight: the evening part of the start day, or the
+            // morning part of the day after it
+            let previous_day = (day + 6) % 7;
+            (self.days[day] && minute >= self.start)
+                || (self.days[previous_day] && minute < self.end)
+        }
+    }
+}
+
+/// Rules limiting capture to certain hours of the week
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptureSchedule {
+    rules: Vec<ScheduleRule>,
+}
+
+impl CaptureSchedule {
+    /// Parse a schedule from its rules
+    ///
+    /// Returns the first invalid rule's error, naming the rule.
+    pub fn parse<S: AsRef<str>>(rules: &[S]) -> Result<Self, String> {
+        rules
+            .iter()
+            .map(|rule| {
+                ScheduleRule::parse(rule.as_ref())
+                    .map_err(|e| format!("Invalid schedule rule '{}': {}", rule.as_ref(), e))
+            })
+            .collect::<Result<_, _>>()
+            .map(|rules| Self { rules })
+    }
+
+    /// Whether the schedule has no rules, capturing at all times
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether capture runs at the local time `at`
+    pub fn is_active(&self, at: NaiveDateTime) -> bool {
+        self.is_empty() || self.rules.iter().any(|rule| rule.matches(at))
+    }
+
+    /// Whether capture runs now
+    pub fn is_active_now(&self) -> bool {
+        self.is_empty() || self.is_active(chrono::Local::now().naive_local())
+    }
+}
+
+/// Parse days such as `"Mon-Fri"`, `"Sat,Sun"` or `"weekdays"`
+fn parse_days(days: &str) -> Result<[bool; 7], String> {
+    let mut set = [false; 7];
+    for part in days.to_lowercase().split(',') {
+        let part = part.trim();
+        let range = match part {
+            "daily" => 0..=6,
+            "weekdays" => 0..=4,
+            "weekends" => 5..=6,
+            _ => match part.split_once('-') {
+                Some((first, last)) => parse_day(first)?..=parse_day(last)?,
+                None => parse_day(part)?..=parse_day(part)?,
+            },
+        };
+        let (first, last) = range.into_inner();
+        if first > last {
+            return Err(format!("Day range '{}' runs backwards", part));
+        }
+        set[first..=last].iter_mut().for_each(|day| *day = true);
+    }
+    Ok(set)
+}
+
+/// Index of a day name or its abbreviation of at least three letters,
+/// Monday being 0
+fn parse_day(day: &str) -> Result<usize, String> {
+    let day = day.trim();
+    DAY_NAMES
+        .iter()
+        .position(|name| day.len() >= 3 && name.starts_with(day))
+        .ok_or_else(|| format!("Unknown day '{}', expected Mon to Sun", day))
+}
+
+/// Minutes after midnight of a time such as `"09:30"`, up to `"24:00"`
+fn parse_time(time: &str) -> Result<u32, String> {
+    let invalid = || format!("Invalid time '{}', expected HH:MM", time);
+    let (hours, minutes) = time.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if minutes >= 60 || hours * 60 + minutes > MINUTES_PER_DAY {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    /// 2025-12-01 is a Monday
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 12, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_working_hours() {
+        let schedule = CaptureSchedule::parse(&["Mon-Fri 09:00-18:00"]).unwrap();
+
+        assert!(schedule.is_active(at(1, 9, 0)));
+        assert!(schedule.is_active(at(5, 17, 59)));
+        assert!(!schedule.is_active(at(1, 18, 0)));
+        assert!(!schedule.is_active(at(1, 8, 59)));
+        assert!(!schedule.is_active(at(6, 12, 0)));
+        assert!(CaptureSchedule::default().is_active(at(6, 12, 0)));
+    }
+
+    #[test]
+    fn test_rules_past_midnight_and_day_lists() {
+        let schedule = CaptureSchedule::parse(&["Fri 22:00-02:00", "sat,SUN 10:00-24:00"]).unwrap();
+
+        assert!(schedule.is_active(at(5, 23, 0)));
+        assert!(schedule.is_active(at(6, 1, 59)));
+        assert!(!schedule.is_active(at(6, 2, 0)));
+        assert!(schedule.is_active(at(7, 23, 59)));
+        assert!(!schedule.is_active(at(1, 1, 0)));
+
+        let every_day = CaptureSchedule::parse(&["08:00-20:00"]).unwrap();
+        assert!(every_day.is_active(at(7, 8, 0)));
+        assert_eq!(
+            CaptureSchedule::parse(&["weekdays 09:00-18:00"]).unwrap(),
+            CaptureSchedule::parse(&["Monday-Friday 09:00-18:00"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        for rule in [
+            "Mon-Fri",
+            "Mon-Fri 9-18",
+            "Fri-Mon 09:00-18:00",
+            "Someday 09:00-18:00",
+            "Mon 09:00-09:00",
+            "Mon 25:00-26:00",
+            "Mon 09:60-10:00",
+        ] {
+            assert!(CaptureSchedule::parse(&[rule]).is_err(), "{}", rule);
+        }
+    }
+}
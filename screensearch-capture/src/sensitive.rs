@@ -0,0 +1,396 @@
+//! Sensitive content detection
+//!
+//! Heuristics that recognise banking pages, password prompts, explicit
+//! content and payment card numbers in a processed frame, so they can be
+//! dropped or blurred before the frame is written to disk. This covers
+//! sensitive content inside applications that cannot be excluded as a whole,
+//! such as the browser.
+//!
+//! Detection looks at the window title and the OCR text. A single phrase in
+//! the title is enough; the OCR text needs two distinct phrases of the same
+//! category, since a lone "password" or "account number" is common in
+//! ordinary documents. Payment cards are 13-19 digit numbers that pass the
+//! Luhn checksum.
+
+use crate::ProcessedFrame;
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+/// Distinct OCR phrases of one category needed to flag a frame
+const MIN_TEXT_MATCHES: usize = 2;
+
+/// Pixel size of the blocks a blurred frame is reduced to
+const BLUR_BLOCK_SIZE: u32 = 24;
+
+/// What to do with frames showing sensitive content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SensitiveAction {
+    /// Store frames unchanged
+    #[default]
+    Off,
+
+    /// Discard the frame and its text
+    Drop,
+
+    /// Store a blurred image without its text
+    Blur,
+}
+
+/// Kind of sensitive content found in a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensitiveCategory {
+    /// Credit or debit card number
+    PaymentCard,
+
+    /// Login or password prompt
+    PasswordPrompt,
+
+    /// Online banking page
+    Banking,
+
+    /// Adult content
+    Explicit,
+}
+
+impl SensitiveCategory {
+    /// Name used in logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PaymentCard => "payment_card",
+            Self::PasswordPrompt => "password_prompt",
+            Self::Banking => "banking",
+            Self::Explicit => "explicit",
+        }
+    }
+}
+
+/// Phrases that identify one category of sensitive content
+struct Rule {
+    category: SensitiveCategory,
+    /// Phrases flagging the frame when they appear in the window title
+    title: &'static [&'static str],
+    /// Phrases flagging the frame when enough of them appear in the text
+    text: &'static [&'static str],
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        category: SensitiveCategory::PasswordPrompt,
+        title: &["sign in", "log in", "password"],
+        text: &[
+            "password",
+            "passcode",
+            "sign in",
+            "log in",
+            "verification code",
+            "one-time code",
+            "two-factor",
+            "security code",
+        ],
+    },
+    Rule {
+        category: SensitiveCategory::Banking,
+        title: &["online banking", "bank account", "account balance"],
+        text: &[
+            "online banking",
+            "account balance",
+            "available balance",
+            "account number",
+            "routing number",
+            "sort code",
+            "iban",
+            "wire transfer",
+            "transfer funds",
+            "bank statement",
+        ],
+    },
+    Rule {
+        category: SensitiveCategory::Explicit,
+        title: &[
+            "porn", "xxx", "nsfw", "onlyfans", "pornhub", "xvideos", "xhamster",
+        ],
+        text: &[
+            "porn",
+            "xxx",
+            "nsfw",
+            "explicit content",
+            "adults only",
+            "18+",
+            "age verification",
+        ],
+    },
+];
+
+/// Drops or blurs frames showing sensitive content
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SensitiveContentFilter {
+    action: SensitiveAction,
+}
+
+impl SensitiveContentFilter {
+    /// Create a filter applying `action` to sensitive frames
+    pub fn new(action: SensitiveAction) -> Self {
+        Self { action }
+    }
+
+    /// Action applied to sensitive frames
+    pub fn action(&self) -> SensitiveAction {
+        self.action
+    }
+
+    /// Apply the configured action to a frame
+    ///
+    /// Returns `None` for dropped frames. Blurred frames keep their metadata
+    /// but lose their OCR text, so the content stays out of search.
+    pub fn filter(&self, mut processed: ProcessedFrame) -> Option<ProcessedFrame> {
+        if self.action == SensitiveAction::Off {
+            return Some(processed);
+        }
+
+        let Some(category) = detect(
+            processed.frame.active_window.as_deref(),
+            &processed.ocr_result.full_text,
+        ) else {
+            return Some(processed);
+        };
+
+        match self.action {
+            SensitiveAction::Off => Some(processed),
+            SensitiveAction::Drop => {
+                tracing::debug!(
+                    "Dropping frame from monitor {} with {} content",
+                    processed.frame.monitor_index,
+                    category.as_str()
+                );
+                None
+            }
+            SensitiveAction::Blur => {
+                tracing::debug!(
+                    "Blurring frame from monitor {} with {} content",
+                    processed.frame.monitor_index,
+                    category.as_str()
+                );
+                processed.frame.image = blur(&processed.frame.image);
+                processed.ocr_result.regions.clear();
+                processed.ocr_result.full_text.clear();
+                Some(processed)
+            }
+        }
+    }
+}
+
+/// Detect sensitive content from a window title and the frame's OCR text
+pub fn detect(window_title: Option<&str>, text: &str) -> Option<SensitiveCategory> {
+    if contains_card_number(text) {
+        return Some(SensitiveCategory::PaymentCard);
+    }
+
+    let title = window_title.unwrap_or_default().to_lowercase();
+    let text = text.to_lowercase();
+
+    RULES
+        .iter()
+        .find(|rule| {
+            rule.title
+                .iter()
+                .any(|phrase| contains_phrase(&title, phrase))
+                || rule
+                    .text
+                    .iter()
+                    .filter(|phrase| contains_phrase(&text, phrase))
+                    .count()
+                    >= MIN_TEXT_MATCHES
+        })
+        .map(|rule| rule.category)
+}
+
+/// Whether `haystack` contains `phrase` as whole words
+fn contains_phrase(haystack: &str, phrase: &str) -> bool {
+    haystack.match_indices(phrase).any(|(start, _)| {
+        let end = start + phrase.len();
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[end..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Whether the text contains a Luhn-valid card number
+///
+/// Digits may be grouped with single spaces or dashes the way cards print
+/// them (groups of four, or 4-6-5), so unrelated numbers such as dates and
+/// phone numbers next to each other are not read as one.
+fn contains_card_number(text: &str) -> bool {
+    let mut digits = Vec::new();
+    let mut groups = vec![0usize];
+
+    for c in text.chars().chain(std::iter::once('\n')) {
+        if let Some(digit) = c.to_digit(10) {
+            digits.push(digit);
+            *groups.last_mut().unwrap() += 1;
+        } else if (c == ' ' || c == '-') && groups.last().is_some_and(|&len| len > 0) {
+            groups.push(0);
+        } else {
+            if groups.last() == Some(&0) {
+                groups.pop();
+            }
+            if is_card_grouping(&groups) && is_card_number(&digits) {
+                return true;
+            }
+            digits.clear();
+            groups = vec![0];
+        }
+    }
+    false
+}
+
+/// Whether digit group lengths match how card numbers are printed
+fn is_card_grouping(groups: &[usize]) -> bool {
+    match groups {
+        [_] | [4, 6, 5] => true,
+        [leading @ .., last] => leading.iter().all(|&len| len == 4) && (1..=4).contains(last),
+        [] => false,
+    }
+}
+
+/// Whether the digits form a card number with a valid Luhn checksum
+fn is_card_number(digits: &[u32]) -> bool {
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Pixelate an image beyond legibility
+fn blur(image: &RgbaImage) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let small = imageops::resize(
+        image,
+        (width / BLUR_BLOCK_SIZE).max(1),
+        (height / BLUR_BLOCK_SIZE).max(1),
+        FilterType::Triangle,
+    );
+    imageops::resize(&small, width, height, FilterType::Nearest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CapturedFrame, OcrResult, TextRegion};
+
+    fn processed_frame(title: &str, text: &str) -> ProcessedFrame {
+        let region = TextRegion::new(text.to_string(), 0, 0, 100, 20, 0.9);
+        ProcessedFrame {
+            frame: CapturedFrame {
+                timestamp: chrono::Utc::now(),
+                monitor_index: 0,
+                image: RgbaImage::from_fn(96, 48, |x, _| {
+                    image::Rgba([if x % 2 == 0 { 255 } else { 0 }, 0, 0, 255])
+                }),
+                active_window: Some(title.to_string()),
+                active_process: Some("chrome.exe".to_string()),
+            },
+            ocr_result: OcrResult::new(vec![region], (96, 48), 10),
+            frame_id: None,
+        }
+    }
+
+    #[test]
+    fn test_detects_title_phrases() {
+        assert_eq!(
+            detect(Some("Sign in - Google Accounts - Chrome"), ""),
+            Some(SensitiveCategory::PasswordPrompt)
+        );
+        assert_eq!(
+            detect(Some("Online Banking | Example Bank"), ""),
+            Some(SensitiveCategory::Banking)
+        );
+        assert_eq!(detect(Some("Rust documentation"), ""), None);
+    }
+
+    #[test]
+    fn test_text_needs_two_phrases() {
+        assert_eq!(detect(None, "Reset your password in settings"), None);
+        assert_eq!(
+            detect(None, "Username Password Sign in Forgot?"),
+            Some(SensitiveCategory::PasswordPrompt)
+        );
+        assert_eq!(
+            detect(None, "Available balance $1,024.00 IBAN DE89 3704"),
+            Some(SensitiveCategory::Banking)
+        );
+    }
+
+    #[test]
+    fn test_phrases_match_whole_words() {
+        assert!(contains_phrase("log in to continue", "log in"));
+        assert!(!contains_phrase("catalog index", "log in"));
+        assert!(!contains_phrase("caribbean", "iban"));
+        assert!(contains_phrase("rated 18+ only", "18+"));
+    }
+
+    #[test]
+    fn test_detects_card_numbers() {
+        assert!(contains_card_number("Card 4111 1111 1111 1111 exp 12/29"));
+        assert!(contains_card_number("5500-0000-0000-0004"));
+        assert!(!contains_card_number("Order 4111 1111 1111 1112"));
+        assert!(!contains_card_number("Call 555 1234"));
+        assert!(contains_card_number("Amex 3782 822463 10005"));
+        // Loose groups are not read as one number
+        assert!(!contains_card_number("2024 10 16 4111 111 111"));
+        assert_eq!(
+            detect(None, "4111111111111111"),
+            Some(SensitiveCategory::PaymentCard)
+        );
+    }
+
+    #[test]
+    fn test_filter_actions() {
+        let sensitive = || processed_frame("Online Banking", "Account number 12");
+        let harmless = || processed_frame("Editor", "fn main() {}");
+
+        let off = SensitiveContentFilter::new(SensitiveAction::Off);
+        assert!(off.filter(sensitive()).is_some());
+
+        let drop = SensitiveContentFilter::new(SensitiveAction::Drop);
+        assert!(drop.filter(sensitive()).is_none());
+        assert!(drop.filter(harmless()).is_some());
+
+        let blur = SensitiveContentFilter::new(SensitiveAction::Blur);
+        let original = sensitive();
+        let blurred = blur.filter(sensitive()).unwrap();
+        assert!(blurred.ocr_result.regions.is_empty());
+        assert!(blurred.ocr_result.full_text.is_empty());
+        assert_eq!(
+            blurred.frame.image.dimensions(),
+            original.frame.image.dimensions()
+        );
+        assert_ne!(blurred.frame.image, original.frame.image);
+    }
+
+    #[test]
+    fn test_action_deserializes_lowercase() {
+        let action: SensitiveAction = serde_json::from_str("\"blur\"").unwrap();
+        assert_eq!(action, SensitiveAction::Blur);
+        assert_eq!(SensitiveAction::default(), SensitiveAction::Off);
+    }
+}
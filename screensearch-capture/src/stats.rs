@@ -0,0 +1,52 @@
+//! Capture pipeline counters
+//!
+//! Reported by the health endpoint, and built without the `capture` feature
+//! so API clients can read them.
+
+use serde::{Deserialize, Serialize};
+
+/// Memory budget counters reported by the health endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryBudgetStats {
+    pub limit_mb: u64,
+    pub usage_mb: u64,
+    pub limiting: bool,
+    pub capture_suspended: bool,
+    pub dropped_frames: u64,
+    pub activations: u64,
+}
+
+/// Disk space counters reported by the health endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskSpaceStats {
+    pub free_mb: u64,
+    pub total_mb: u64,
+    pub cleanup_below_mb: u64,
+    pub pause_below_mb: u64,
+    pub capture_suspended: bool,
+    pub cleanups: u64,
+    pub frames_deleted: u64,
+}
+
+/// Power state reported by the health endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerStats {
+    pub on_battery: bool,
+    pub power_saver: bool,
+    /// Whether capture and background work are throttled
+    pub throttled: bool,
+    pub battery_interval_ms: u64,
+    pub workers_paused: bool,
+}
+
+/// Sub-queue counters of one monitor, reported by the health endpoint
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonitorBacklog {
+    pub monitor_index: usize,
+    /// Frames waiting for OCR
+    pub queued: usize,
+    /// Frames handed to OCR since startup
+    pub processed: u64,
+    /// Frames dropped from a full queue since startup
+    pub dropped: u64,
+}
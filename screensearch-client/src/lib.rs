@@ -0,0 +1,444 @@
+//! Typed client for the ScreenSearch REST API
+//!
+//! Wraps the search, frame, tag, automation and AI endpoints of a running
+//! server. Requests and responses are the types the server itself uses
+//! (from [`screensearch_api::models`]), so they stay in step with the API
+//! without hand-written URLs or JSON. The server crates are built without
+//! their `server` and `database` features, so the client does not pull in
+//! the server, SQLite or capture code.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use screensearch_client::{Client, SearchQuery};
+//!
+//! #[tokio::main]
+//! async fn main() -> screensearch_client::Result<()> {
+//!     let client = Client::local();
+//!
+//!     let query = SearchQuery {
+//!         q: "invoice".to_string(),
+//!         limit: Some(10),
+//!         ..Default::default()
+//!     };
+//!     let page = client.search(&query).await?;
+//!     for result in page.data {
+//!         println!("{} {:?}", result.frame.timestamp, result.frame.active_window);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use reqwest::{RequestBuilder, Response, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use thiserror::Error;
+
+pub use screensearch_api::citations::ReportSource;
+pub use screensearch_api::models::{
+    AddTagToFrameRequest, AiChatRequest, AiChatResponse, AiReportRequest, AiReportResponse,
+    AskRequest, AskResponse, AutomationResponse, ClickRequest, ClickTextRequest, ClickTextResponse,
+    CreateTagRequest, ElementInfo, FindElementsRequest, FrameContextQuery, FrameContextResponse,
+    FrameQuery, FrameResponse, GetTextRequest, GetTextResponse, HealthResponse, KeyPressRequest,
+    KeywordSearchQuery, ListElementsRequest, OpenAIMessage, OpenAppRequest, OpenUrlRequest,
+    Paginated, PaginatedFramesResponse, PaginationInfo, ScrollRequest, SearchMode, SearchQuery,
+    SearchResponse, TagResponse, TypeRequest,
+};
+pub use screensearch_db::{OcrTextRecord, SearchResult};
+
+/// Address the server listens on by default
+pub const DEFAULT_BASE_URL: &str = "http://127.0.0.1:3131";
+
+/// Client error types
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Invalid base URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("API error ({status}): {message}")]
+    Api { status: u16, message: String },
+}
+
+/// Result type for client operations
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// Error body returned by the server
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// Client for one ScreenSearch server
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Client {
+    /// Client for the server at `base_url`, e.g. `http://127.0.0.1:3131`
+    pub fn new(base_url: &str) -> Result<Self> {
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    /// Client for the server on this machine, at [`DEFAULT_BASE_URL`]
+    pub fn local() -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    /// Client sending its requests with `http`, e.g. to set timeouts or
+    /// trust the server's self-signed certificate
+    pub fn with_http_client(base_url: &str, http: reqwest::Client) -> Result<Self> {
+        let url = Url::parse(base_url).map_err(|e| ClientError::InvalidUrl(e.to_string()))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ClientError::InvalidUrl(format!(
+                "'{}' is not an http or https URL",
+                base_url
+            )));
+        }
+
+        Ok(Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Address of the server
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // ===== System =====
+
+    /// GET /api/health - Server status and database counts
+    pub async fn health(&self) -> Result<HealthResponse> {
+        self.send(self.http.get(self.url("/health"))).await
+    }
+
+    // ===== Search =====
+
+    /// GET /api/search - Full-text search
+    pub async fn search(&self, query: &SearchQuery) -> Result<SearchResponse> {
+        self.send(self.http.get(self.url("/search")).query(query))
+            .await
+    }
+
+    /// GET /api/search/keywords - OCR text containing any of the keywords
+    pub async fn search_keywords(&self, query: &KeywordSearchQuery) -> Result<Vec<OcrTextRecord>> {
+        self.send(self.http.get(self.url("/search/keywords")).query(query))
+            .await
+    }
+
+    // ===== Frames =====
+
+    /// GET /api/frames - Frames matching the filters, newest first
+    pub async fn frames(&self, query: &FrameQuery) -> Result<PaginatedFramesResponse> {
+        self.send(self.http.get(self.url("/frames")).query(query))
+            .await
+    }
+
+    /// GET /api/frames/:id - One frame
+    pub async fn frame(&self, id: i64) -> Result<FrameResponse> {
+        self.send(self.http.get(self.url(&format!("/frames/{}", id))))
+            .await
+    }
+
+    /// GET /api/frames/:id/image - Image file of a frame
+    pub async fn frame_image(&self, id: i64) -> Result<Vec<u8>> {
+        let response = self
+            .http
+            .get(self.url(&format!("/frames/{}/image", id)))
+            .send()
+            .await?;
+        let bytes = check(response).await?.bytes().await?;
+        Ok(bytes.to_vec())
+    }
+
+    /// GET /api/frames/:id/context - Frames captured around a frame
+    pub async fn frame_context(
+        &self,
+        id: i64,
+        query: &FrameContextQuery,
+    ) -> Result<FrameContextResponse> {
+        let url = self.url(&format!("/frames/{}/context", id));
+        self.send(self.http.get(url).query(query)).await
+    }
+
+    // ===== Tags =====
+
+    /// GET /api/tags - First page of tags (100), by name
+    pub async fn tags(&self) -> Result<Paginated<TagResponse>> {
+        self.send(self.http.get(self.url("/tags"))).await
+    }
+
+    /// POST /api/tags - Create a tag
+    pub async fn create_tag(&self, request: &CreateTagRequest) -> Result<TagResponse> {
+        self.send(self.http.post(self.url("/tags")).json(request))
+            .await
+    }
+
+    /// PUT /api/tags/:id - Rename or recolor a tag
+    pub async fn update_tag(&self, id: i64, request: &CreateTagRequest) -> Result<TagResponse> {
+        let url = self.url(&format!("/tags/{}", id));
+        self.send(self.http.put(url).json(request)).await
+    }
+
+    /// DELETE /api/tags/:id - Delete a tag
+    pub async fn delete_tag(&self, id: i64) -> Result<()> {
+        let url = self.url(&format!("/tags/{}", id));
+        self.execute(self.http.delete(url)).await
+    }
+
+    /// GET /api/frames/:id/tags - Tags of a frame
+    pub async fn frame_tags(&self, frame_id: i64) -> Result<Vec<TagResponse>> {
+        let url = self.url(&format!("/frames/{}/tags", frame_id));
+        self.send(self.http.get(url)).await
+    }
+
+    /// POST /api/frames/:id/tags - Tag a frame
+    pub async fn add_tag_to_frame(&self, frame_id: i64, tag_id: i64) -> Result<()> {
+        let url = self.url(&format!("/frames/{}/tags", frame_id));
+        let request = AddTagToFrameRequest { tag_id };
+        self.execute(self.http.post(url).json(&request)).await
+    }
+
+    /// DELETE /api/frames/:id/tags/:tag_id - Untag a frame
+    pub async fn remove_tag_from_frame(&self, frame_id: i64, tag_id: i64) -> Result<()> {
+        let url = self.url(&format!("/frames/{}/tags/{}", frame_id, tag_id));
+        self.execute(self.http.delete(url)).await
+    }
+
+    /// POST /api/frames/:id/pin - Exempt a frame from all cleanup
+    pub async fn pin_frame(&self, frame_id: i64) -> Result<()> {
+        let url = self.url(&format!("/frames/{}/pin", frame_id));
+        self.execute(self.http.post(url)).await
+    }
+
+    /// DELETE /api/frames/:id/pin - Unpin a frame
+    pub async fn unpin_frame(&self, frame_id: i64) -> Result<()> {
+        let url = self.url(&format!("/frames/{}/pin", frame_id));
+        self.execute(self.http.delete(url)).await
+    }
+
+    // ===== Automation =====
+
+    /// POST /api/automation/find-elements - UI elements matching a selector
+    pub async fn find_elements(&self, request: &FindElementsRequest) -> Result<Vec<ElementInfo>> {
+        self.post("/automation/find-elements", request).await
+    }
+
+    /// POST /api/automation/click - Click at coordinates or on an element
+    pub async fn click(&self, request: &ClickRequest) -> Result<AutomationResponse> {
+        self.post("/automation/click", request).await
+    }
+
+    /// POST /api/automation/click-text - Click visible text
+    pub async fn click_text(&self, request: &ClickTextRequest) -> Result<ClickTextResponse> {
+        self.post("/automation/click-text", request).await
+    }
+
+    /// POST /api/automation/type - Type text into the focused element
+    pub async fn type_text(&self, request: &TypeRequest) -> Result<AutomationResponse> {
+        self.post("/automation/type", request).await
+    }
+
+    /// POST /api/automation/scroll - Scroll
+    pub async fn scroll(&self, request: &ScrollRequest) -> Result<AutomationResponse> {
+        self.post("/automation/scroll", request).await
+    }
+
+    /// POST /api/automation/press-key - Press a key with modifiers
+    pub async fn press_key(&self, request: &KeyPressRequest) -> Result<AutomationResponse> {
+        self.post("/automation/press-key", request).await
+    }
+
+    /// POST /api/automation/get-text - Text of a UI element
+    pub async fn get_text(&self, request: &GetTextRequest) -> Result<GetTextResponse> {
+        self.post("/automation/get-text", request).await
+    }
+
+    /// POST /api/automation/list-elements - Interactive elements
+    pub async fn list_elements(&self, request: &ListElementsRequest) -> Result<Vec<ElementInfo>> {
+        self.post("/automation/list-elements", request).await
+    }
+
+    /// POST /api/automation/open-app - Launch an application
+    pub async fn open_app(&self, request: &OpenAppRequest) -> Result<AutomationResponse> {
+        self.post("/automation/open-app", request).await
+    }
+
+    /// POST /api/automation/open-url - Open a URL in the browser
+    pub async fn open_url(&self, request: &OpenUrlRequest) -> Result<AutomationResponse> {
+        self.post("/automation/open-url", request).await
+    }
+
+    // ===== AI =====
+
+    /// POST /api/ask - Answer a question from captured screen content
+    pub async fn ask(&self, request: &AskRequest) -> Result<AskResponse> {
+        self.post("/ask", request).await
+    }
+
+    /// POST /api/ai/chat - Chat with a model
+    pub async fn chat(&self, request: &AiChatRequest) -> Result<AiChatResponse> {
+        self.post("/ai/chat", request).await
+    }
+
+    /// POST /api/ai/generate - Generate an activity report
+    pub async fn generate_report(&self, request: &AiReportRequest) -> Result<AiReportResponse> {
+        self.post("/ai/generate", request).await
+    }
+
+    // ===== Requests =====
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api{}", self.base_url, path)
+    }
+
+    async fn post<B, T>(&self, path: &str, body: &B) -> Result<T>
+    where
+        B: serde::Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.send(self.http.post(self.url(path)).json(body)).await
+    }
+
+    /// Send a request and decode its JSON response
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let response = check(request.send().await?).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Send a request whose response body is of no interest
+    async fn execute(&self, request: RequestBuilder) -> Result<()> {
+        check(request.send().await?).await?;
+        Ok(())
+    }
+}
+
+/// The response if successful, else its error as [`ClientError::Api`]
+async fn check(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<ErrorBody>(&body)
+        .map(|error| error.error)
+        .unwrap_or_else(|_| body.trim().to_string());
+    Err(ClientError::Api {
+        status: status.as_u16(),
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, Query};
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+
+    /// Serve `router` on a free port and return a client for it
+    async fn serve(router: Router) -> Client {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Client::new(&format!("http://{}/", addr)).unwrap()
+    }
+
+    #[test]
+    fn test_base_url() {
+        let client = Client::new("http://localhost:3131/").unwrap();
+        assert_eq!(client.base_url(), "http://localhost:3131");
+        assert_eq!(client.url("/tags"), "http://localhost:3131/api/tags");
+        assert_eq!(Client::local().base_url(), DEFAULT_BASE_URL);
+
+        assert!(matches!(
+            Client::new("localhost:3131"),
+            Err(ClientError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            Client::new("ftp://localhost"),
+            Err(ClientError::InvalidUrl(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_typed_requests() {
+        let router = Router::new()
+            .route(
+                "/api/search",
+                get(|Query(params): Query<HashMap<String, String>>| async move {
+                    // Unset filters are left out of the query string
+                    assert_eq!(params.len(), 5, "{:?}", params);
+                    assert_eq!(params["q"], "invoice");
+                    assert_eq!(params["mode"], "fts");
+                    assert_eq!(params["limit"], "5");
+                    assert_eq!(params["fuzzy"], "true");
+                    Json(serde_json::json!({
+                        "data": [],
+                        "pagination": {"limit": 5, "offset": 0, "total": 0, "has_more": false}
+                    }))
+                }),
+            )
+            .route(
+                "/api/tags",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "data": [
+                            {"id": 1, "name": "work", "color": "#3B82F6", "created_at": "2025-12-10T09:00:00Z"}
+                        ],
+                        "pagination": {"limit": 100, "offset": 0, "total": 1, "has_more": false}
+                    }))
+                }),
+            )
+            .route(
+                "/api/frames/:id",
+                get(|Path(id): Path<i64>| async move {
+                    (
+                        StatusCode::NOT_FOUND,
+                        Json(serde_json::json!({
+                            "error": format!("Frame {} not found", id),
+                            "status": 404
+                        })),
+                    )
+                }),
+            );
+        let client = serve(router).await;
+
+        let query = SearchQuery {
+            q: "invoice".to_string(),
+            limit: Some(5),
+            fuzzy: true,
+            ..Default::default()
+        };
+        let page = client.search(&query).await.unwrap();
+        assert!(page.data.is_empty());
+        assert_eq!(page.pagination.total, Some(0));
+        assert!(page.facets.is_none());
+
+        let tags = client.tags().await.unwrap().data;
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "work");
+        assert_eq!(tags[0].color.as_deref(), Some("#3B82F6"));
+
+        match client.frame(42).await {
+            Err(ClientError::Api { status, message }) => {
+                assert_eq!(status, 404);
+                assert_eq!(message, "Frame 42 not found");
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
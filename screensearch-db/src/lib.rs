@@ -49,23 +49,43 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! # Cargo features
+//!
+//! - `database` (default): the SQLite access above. Without it only the
+//!   models are built, for crates that exchange them with the API.
 
 use thiserror::Error;
 
+#[cfg(feature = "database")]
 pub mod db;
+#[cfg(feature = "database")]
 pub mod migrations;
 pub mod models;
+#[cfg(feature = "database")]
 pub mod queries;
+#[cfg(feature = "database")]
 pub mod vector_search;
 
+#[cfg(feature = "database")]
 pub use db::DatabaseManager;
 pub use models::{
-    EmbeddingRecord, EmbeddingStatus, FrameFilter, FrameRecord, FrameTagRecord, FrameWithTags,
-    FtsOcrResult, HybridResult, NewEmbedding, NewFrame, NewOcrText, NewTag, NewVideoChunk,
-    OcrTextRecord, Pagination, SearchResult, SemanticResult, SettingsRecord, TagRecord,
+    ActivitySummaryRecord, AiInteractionRecord, AiUsageRecord, AlertHitRecord, AlertRecord,
+    ArchivedFrameRecord, AuditEntryRecord, ContextChunk, DocumentRecord, EmbeddingIndexStats,
+    EmbeddingOutcome, EmbeddingQueueStats, EmbeddingRecord, EmbeddingStatus, FacetCount,
+    FrameArchiveRecord, FrameFilter, FrameRecord, FrameTagRecord, FrameWithTags, FtsOcrResult,
+    HybridResult, JobRecord, MacroRecord, MatchHighlight, MatchOffset, MetricSampleRecord,
+    NewActivitySummary, NewAiInteraction, NewAlert, NewAlertHit, NewArchivedFrame, NewAuditEntry,
+    NewDocument, NewEmbedding, NewFrame, NewFrameArchive, NewJob, NewMacro, NewMetricSample,
+    NewOcrText, NewRedactionRule, NewReport, NewReportTemplate, NewSearchHistory, NewSyncedFrame,
+    NewTag, NewVideoChunk, OcrTextRecord, Pagination, PurgeFilter, PurgeOutcome, PurgeReportRecord,
+    RedactableText, RedactionRuleRecord, ReportRecord, ReportTemplateRecord, SearchFacet,
+    SearchFacets, SearchHistoryRecord, SearchHistoryStats, SearchQueryCount, SearchRanking,
+    SearchResult, SemanticResult, SettingsRecord, SyncOrigin, SyncPeerRecord, TagRecord,
     UpdateSettings, VideoChunkRecord,
 };
-pub use queries::DatabaseStatistics;
+#[cfg(feature = "database")]
+pub use queries::{snippet_html, DatabaseStatistics};
 
 /// Database-related errors
 #[derive(Error, Debug)]
@@ -85,6 +105,7 @@ pub enum DatabaseError {
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
 
+    #[cfg(feature = "database")]
     #[error("SQLx error: {0}")]
     SqlxError(#[from] sqlx::Error),
 
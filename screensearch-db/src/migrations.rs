@@ -32,6 +32,28 @@ pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<()> {
     apply_migration(pool, "002_settings_table", MIGRATION_002_SETTINGS).await?;
     apply_migration(pool, "003_embeddings_table", MIGRATION_003_EMBEDDINGS).await?;
     apply_migration(pool, "004_add_embedding_column", MIGRATION_004_ADD_EMBEDDING_COLUMN).await?;
+    apply_migration(pool, "005_reports_table", MIGRATION_005_REPORTS).await?;
+    apply_migration(pool, "006_report_templates", MIGRATION_006_REPORT_TEMPLATES).await?;
+    apply_migration(pool, "007_macros_table", MIGRATION_007_MACROS).await?;
+    apply_migration(pool, "008_embedding_queue", MIGRATION_008_EMBEDDING_QUEUE).await?;
+    apply_migration(pool, "009_jobs_table", MIGRATION_009_JOBS).await?;
+    apply_migration(pool, "010_alerts", MIGRATION_010_ALERTS).await?;
+    apply_migration(pool, "011_automation_audit", MIGRATION_011_AUTOMATION_AUDIT).await?;
+    apply_migration(pool, "012_documents", MIGRATION_012_DOCUMENTS).await?;
+    apply_migration(pool, "013_setup_completed", MIGRATION_013_SETUP_COMPLETED).await?;
+    apply_migration(pool, "014_capture_schedule", MIGRATION_014_CAPTURE_SCHEDULE).await?;
+    apply_migration(pool, "015_ai_interactions", MIGRATION_015_AI_INTERACTIONS).await?;
+    apply_migration(pool, "016_activity_summaries", MIGRATION_016_ACTIVITY_SUMMARIES).await?;
+    apply_migration(pool, "017_ocr_text_vocab", MIGRATION_017_OCR_TEXT_VOCAB).await?;
+    apply_migration(pool, "018_search_ranking", MIGRATION_018_SEARCH_RANKING).await?;
+    apply_migration(pool, "019_search_history", MIGRATION_019_SEARCH_HISTORY).await?;
+    apply_migration(pool, "020_ocr_terms", MIGRATION_020_OCR_TERMS).await?;
+    apply_migration(pool, "021_frame_archives", MIGRATION_021_FRAME_ARCHIVES).await?;
+    apply_migration(pool, "022_metrics_history", MIGRATION_022_METRICS_HISTORY).await?;
+    apply_migration(pool, "023_sync", MIGRATION_023_SYNC).await?;
+    apply_migration(pool, "024_redaction_rules", MIGRATION_024_REDACTION_RULES).await?;
+    apply_migration(pool, "025_purge_reports", MIGRATION_025_PURGE_REPORTS).await?;
+    apply_migration(pool, "026_frame_pins", MIGRATION_026_FRAME_PINS).await?;
 
     tracing::info!("All migrations completed successfully");
     Ok(())
@@ -263,3 +285,416 @@ ALTER TABLE embeddings ADD COLUMN embedding BLOB;
 -- Clear existing data to force re-processing with actual vectors
 DELETE FROM embeddings;
 "#;
+
+/// Migration 005 - Reports table for generated AI reports
+const MIGRATION_005_REPORTS: &str = r#"
+-- Reports table: stores AI-generated activity reports
+CREATE TABLE IF NOT EXISTS reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    period TEXT NOT NULL,               -- 'daily' or 'weekly'
+    content TEXT NOT NULL,              -- Markdown report body
+    model TEXT NOT NULL,
+    context_source TEXT,
+    start_time DATETIME NOT NULL,
+    end_time DATETIME NOT NULL,
+    generated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_reports_generated_at ON reports(generated_at DESC);
+CREATE INDEX IF NOT EXISTS idx_reports_period ON reports(period);
+"#;
+
+/// Migration 006 - User-managed report templates with built-in examples
+const MIGRATION_006_REPORT_TEMPLATES: &str = r#"
+-- Report templates: system prompts with {start}, {end}, {top_apps}, {frame_count} variables
+CREATE TABLE IF NOT EXISTS report_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    system_prompt TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO report_templates (name, description, system_prompt) VALUES (
+    'standup',
+    'Short daily standup summary',
+    'You are preparing a daily standup update for the user based on their screen activity from {start} to {end}.
+Most used applications: {top_apps}.
+
+Write three short Markdown sections, written in the first person and in a casual tone:
+## Yesterday
+## Today
+## Blockers
+
+Use at most 3 bullets per section. Mention concrete tasks, tickets, files or documents visible in the OCR text. Do not pad with generic statements.'
+);
+
+INSERT OR IGNORE INTO report_templates (name, description, system_prompt) VALUES (
+    'client-billing',
+    'Formal billable work log for clients',
+    'You are preparing a formal billable work summary for a client covering {start} to {end}.
+Captured frames: {frame_count}. Most used applications: {top_apps}.
+
+Group the work into billable line items in a Markdown table with columns: Date, Task, Description, Estimated Hours.
+Estimate hours from the timestamps of related activity. Use a professional, neutral tone and exclude personal or non-work activity.
+End with a short total and a one-sentence summary of deliverables.'
+);
+"#;
+
+/// Migration 007 - Recorded automation macros
+const MIGRATION_007_MACROS: &str = r#"
+-- Macros table: recorded input events replayed via the automation engine
+CREATE TABLE IF NOT EXISTS macros (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    description TEXT,
+    events TEXT NOT NULL,               -- JSON array of macro events
+    event_count INTEGER NOT NULL DEFAULT 0,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_macros_created_at ON macros(created_at DESC);
+"#;
+
+/// Migration 008 - Per-frame embedding queue for backfills and progress tracking
+const MIGRATION_008_EMBEDDING_QUEUE: &str = r#"
+-- Embedding queue: one row per frame scheduled for (or processed by) the embedding worker
+CREATE TABLE IF NOT EXISTS embedding_queue (
+    frame_id INTEGER PRIMARY KEY,
+    status TEXT NOT NULL DEFAULT 'pending', -- 'pending', 'processing', 'completed', 'skipped' or 'failed'
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    updated_at DATETIME NOT NULL,
+    FOREIGN KEY (frame_id) REFERENCES frames(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_embedding_queue_status ON embedding_queue(status, frame_id);
+CREATE INDEX IF NOT EXISTS idx_embedding_queue_updated_at ON embedding_queue(updated_at);
+"#;
+
+/// Migration 009 - Persistent background job queue
+const MIGRATION_009_JOBS: &str = r#"
+-- Jobs table: units of background work executed by registered job handlers
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job_type TEXT NOT NULL,
+    payload TEXT NOT NULL DEFAULT '{}',     -- JSON input for the handler
+    state TEXT NOT NULL DEFAULT 'queued',   -- 'queued', 'running', 'completed', 'failed' or 'cancelled'
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 3,
+    last_error TEXT,
+    result TEXT,                            -- JSON summary written by the handler
+    run_after DATETIME NOT NULL,            -- Earliest time the job may (re)start
+    created_at DATETIME NOT NULL,
+    started_at DATETIME,
+    finished_at DATETIME
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_state_run_after ON jobs(state, run_after);
+CREATE INDEX IF NOT EXISTS idx_jobs_type_state ON jobs(job_type, state);
+"#;
+
+/// Migration 010 - Keyword watch alerts and their hits
+const MIGRATION_010_ALERTS: &str = r#"
+-- Alerts table: keywords or regexes matched against OCR text as frames are processed
+CREATE TABLE IF NOT EXISTS alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    is_regex INTEGER NOT NULL DEFAULT 0,    -- SQLite boolean (0/1)
+    app_filter TEXT,                        -- Only match frames of this application
+    created_at DATETIME NOT NULL
+);
+
+-- Alert hits table: one row per alert matching a frame
+CREATE TABLE IF NOT EXISTS alert_hits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    alert_id INTEGER NOT NULL,
+    frame_id INTEGER NOT NULL,
+    matched_text TEXT NOT NULL,             -- Text matched by the pattern
+    context TEXT NOT NULL,                  -- OCR text region containing the match
+    app_name TEXT,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (alert_id) REFERENCES alerts(id) ON DELETE CASCADE,
+    FOREIGN KEY (frame_id) REFERENCES frames(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_hits_created_at ON alert_hits(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_alert_hits_alert_id ON alert_hits(alert_id, created_at DESC);
+"#;
+
+/// Automation audit migration - append-only log of automation actions
+const MIGRATION_011_AUTOMATION_AUDIT: &str = r#"
+-- Automation audit table: one row per automation call, never updated
+CREATE TABLE IF NOT EXISTS automation_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    endpoint TEXT NOT NULL,                 -- e.g. 'POST /api/automation/click' or 'agent:click'
+    parameters TEXT,                        -- Request body or query string
+    element TEXT,                           -- JSON of the resolved UI element
+    success INTEGER NOT NULL,               -- SQLite boolean (0/1)
+    error TEXT,
+    caller TEXT,                            -- X-Caller or User-Agent header, or 'agent'
+    duration_ms INTEGER NOT NULL,
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_automation_audit_created_at ON automation_audit(created_at DESC);
+
+-- Entries may only be removed by retention, never rewritten
+CREATE TRIGGER IF NOT EXISTS automation_audit_append_only
+BEFORE UPDATE ON automation_audit
+BEGIN
+    SELECT RAISE(ABORT, 'automation_audit is append-only');
+END;
+
+-- Days to keep audit entries (separate from frame retention)
+ALTER TABLE settings ADD COLUMN audit_retention_days INTEGER NOT NULL DEFAULT 90;
+"#;
+
+/// Migration 012 - Receipts and invoices extracted from frames
+const MIGRATION_012_DOCUMENTS: &str = r#"
+-- Documents table: structured fields of receipts and invoices seen on screen
+CREATE TABLE IF NOT EXISTS documents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    frame_id INTEGER NOT NULL,
+    doc_type TEXT NOT NULL,                 -- 'receipt' or 'invoice'
+    vendor TEXT,
+    document_date TEXT,                     -- Date printed on the document (YYYY-MM-DD)
+    total REAL,
+    currency TEXT,                          -- ISO 4217 code, e.g. 'USD'
+    line_items TEXT NOT NULL,               -- JSON array of {description, quantity, amount}
+    text TEXT NOT NULL,                     -- OCR text the fields were extracted from
+    app_name TEXT,
+    captured_at DATETIME NOT NULL,          -- Timestamp of the frame
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (frame_id) REFERENCES frames(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_documents_captured_at ON documents(captured_at DESC);
+CREATE INDEX IF NOT EXISTS idx_documents_vendor ON documents(vendor);
+"#;
+
+/// Migration 013 - Skip first-run setup for databases created before it
+const MIGRATION_013_SETUP_COMPLETED: &str = r#"
+-- Databases that already hold frames were set up by hand
+INSERT OR IGNORE INTO metadata (key, value)
+SELECT 'setup_completed', 'true' WHERE EXISTS (SELECT 1 FROM frames);
+"#;
+
+/// Migration 014 - Hours of the week capture runs
+const MIGRATION_014_CAPTURE_SCHEDULE: &str = r#"
+-- JSON array of rules such as "Mon-Fri 09:00-18:00" (empty = always capture)
+ALTER TABLE settings ADD COLUMN capture_schedule TEXT NOT NULL DEFAULT '[]';
+"#;
+
+/// Migration 015 - History of requests sent to AI providers
+const MIGRATION_015_AI_INTERACTIONS: &str = r#"
+-- AI interactions: what was sent to which model and what came back
+CREATE TABLE IF NOT EXISTS ai_interactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,                     -- 'report' or 'agent'
+    provider_url TEXT NOT NULL,
+    model TEXT NOT NULL,
+    system_prompt TEXT,
+    prompt TEXT NOT NULL,                   -- User message, including the retrieved context
+    context_chunks TEXT NOT NULL,           -- JSON array of {frame_id, chunk_index} sent as context
+    response TEXT,
+    error TEXT,
+    latency_ms INTEGER NOT NULL,
+    prompt_tokens INTEGER,                  -- As reported by the provider, when it does
+    completion_tokens INTEGER,
+    total_tokens INTEGER,
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_interactions_created_at ON ai_interactions(created_at DESC);
+"#;
+
+/// Migration 016 - Hourly summaries of activity
+const MIGRATION_016_ACTIVITY_SUMMARIES: &str = r#"
+-- Activity summaries: what happened in each hour, written by an LLM or extracted
+CREATE TABLE IF NOT EXISTS activity_summaries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    period_start DATETIME NOT NULL UNIQUE,  -- Start of the hour (UTC)
+    period_end DATETIME NOT NULL,
+    summary TEXT NOT NULL,
+    method TEXT NOT NULL,                   -- 'llm' or 'extractive'
+    model TEXT,                             -- Model that wrote an 'llm' summary
+    frame_count INTEGER NOT NULL,
+    frame_id INTEGER NOT NULL,              -- First frame of the hour, cited in reports
+    created_at DATETIME NOT NULL
+);
+"#;
+
+/// Migration 017 - Indexed terms for fuzzy search
+const MIGRATION_017_OCR_TEXT_VOCAB: &str = r#"
+-- Read-only view of the terms in the full-text index and how many rows contain each
+CREATE VIRTUAL TABLE IF NOT EXISTS ocr_text_vocab USING fts5vocab(ocr_text_fts, row);
+"#;
+
+/// Migration 018 - Search ranking weights
+const MIGRATION_018_SEARCH_RANKING: &str = r#"
+-- JSON object of text, window title and recency weights (empty = defaults)
+ALTER TABLE settings ADD COLUMN search_ranking TEXT NOT NULL DEFAULT '{}';
+"#;
+
+/// Migration 019 - History of executed searches
+const MIGRATION_019_SEARCH_HISTORY: &str = r#"
+-- Search history: what was searched for and what came of it
+CREATE TABLE IF NOT EXISTS search_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    query TEXT NOT NULL,
+    mode TEXT NOT NULL,                     -- 'fts', 'fuzzy' or 'regex'
+    result_count INTEGER NOT NULL,
+    clicked_frame_id INTEGER,               -- Result opened afterwards, when the UI reports it
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_history_created_at ON search_history(created_at DESC);
+"#;
+
+/// Migration 020 - Frequency of the words in OCR text, for search suggestions
+const MIGRATION_020_OCR_TERMS: &str = r#"
+-- Index of the unstemmed words of OCR text; detail=none keeps only which rows hold them
+CREATE VIRTUAL TABLE IF NOT EXISTS ocr_terms USING fts5(
+    text,
+    content='ocr_text',
+    content_rowid='id',
+    tokenize = 'unicode61 remove_diacritics 0',
+    detail = none
+);
+
+-- Read-only view of each word and how many rows contain it
+CREATE VIRTUAL TABLE IF NOT EXISTS ocr_terms_vocab USING fts5vocab(ocr_terms, row);
+
+CREATE TRIGGER IF NOT EXISTS ocr_terms_ai AFTER INSERT ON ocr_text BEGIN
+    INSERT INTO ocr_terms(rowid, text) VALUES (new.id, new.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ocr_terms_ad AFTER DELETE ON ocr_text BEGIN
+    INSERT INTO ocr_terms(ocr_terms, rowid, text) VALUES ('delete', old.id, old.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ocr_terms_au AFTER UPDATE OF text ON ocr_text BEGIN
+    INSERT INTO ocr_terms(ocr_terms, rowid, text) VALUES ('delete', old.id, old.text);
+    INSERT INTO ocr_terms(rowid, text) VALUES (new.id, new.text);
+END;
+
+-- Index the text captured so far
+INSERT INTO ocr_terms(ocr_terms) VALUES ('rebuild');
+"#;
+
+/// Migration 021 - Cold-tier archives of old frame images
+const MIGRATION_021_FRAME_ARCHIVES: &str = r#"
+-- Archives: the images of a day's old frames in one tar.zst file
+CREATE TABLE IF NOT EXISTS frame_archives (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    day TEXT NOT NULL,                      -- Capture date (UTC), YYYY-MM-DD
+    file_path TEXT NOT NULL,
+    frame_count INTEGER NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_frame_archives_day ON frame_archives(day);
+
+-- Where the image of each archived frame is in its archive
+CREATE TABLE IF NOT EXISTS archived_frames (
+    frame_id INTEGER PRIMARY KEY,
+    archive_id INTEGER NOT NULL,
+    entry_offset INTEGER NOT NULL,          -- Byte offset of the image's zstd frame
+    entry_length INTEGER NOT NULL,          -- Compressed length in bytes
+    FOREIGN KEY (frame_id) REFERENCES frames(id) ON DELETE CASCADE,
+    FOREIGN KEY (archive_id) REFERENCES frame_archives(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_archived_frames_archive ON archived_frames(archive_id);
+"#;
+
+/// Migration 022 - History of OCR and capture pipeline metrics
+const MIGRATION_022_METRICS_HISTORY: &str = r#"
+-- Periodic metric snapshots, so performance can be compared across restarts
+CREATE TABLE IF NOT EXISTS metrics_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    metric TEXT NOT NULL,                   -- e.g. 'ocr_latency', 'capture_backlog'
+    value REAL NOT NULL,
+    version TEXT NOT NULL,                  -- App version that recorded it
+    recorded_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_metrics_history_metric_time ON metrics_history(metric, recorded_at);
+CREATE INDEX IF NOT EXISTS idx_metrics_history_recorded_at ON metrics_history(recorded_at);
+"#;
+
+/// Migration 023 - Delta sync between machines
+const MIGRATION_023_SYNC: &str = r#"
+-- Frames received from other machines, by their ID on the machine that
+-- captured them, so they are stored once however often they are sent
+CREATE TABLE IF NOT EXISTS sync_frames (
+    frame_id INTEGER PRIMARY KEY,
+    origin_machine TEXT NOT NULL,
+    origin_frame_id INTEGER NOT NULL,
+    FOREIGN KEY (frame_id) REFERENCES frames(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sync_frames_origin ON sync_frames(origin_machine, origin_frame_id);
+
+-- Machines changes were received from, and how far
+CREATE TABLE IF NOT EXISTS sync_peers (
+    machine_id TEXT PRIMARY KEY,
+    cursor INTEGER NOT NULL,                -- Last frame ID received, on the peer
+    frames_received INTEGER NOT NULL DEFAULT 0,
+    last_sync_at DATETIME NOT NULL
+);
+"#;
+
+/// Migration 024 - User-defined text redaction rules
+const MIGRATION_024_REDACTION_RULES: &str = r#"
+-- Terms or regexes masked in OCR text, at capture time and in stored frames
+CREATE TABLE IF NOT EXISTS redaction_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    is_regex INTEGER NOT NULL DEFAULT 0,    -- SQLite boolean (0/1)
+    blur_images INTEGER NOT NULL DEFAULT 0, -- Also blur images of matching frames
+    created_at DATETIME NOT NULL
+);
+
+-- Masked text must leave the search index. With external content a plain
+-- DELETE looks the row up again, finding the new text (or none), so the old
+-- words stayed indexed; remove them with the text they were indexed from
+DROP TRIGGER IF EXISTS ocr_text_ad;
+CREATE TRIGGER ocr_text_ad AFTER DELETE ON ocr_text BEGIN
+    INSERT INTO ocr_text_fts(ocr_text_fts, rowid, text) VALUES ('delete', old.id, old.text);
+END;
+
+DROP TRIGGER IF EXISTS ocr_text_au;
+CREATE TRIGGER ocr_text_au AFTER UPDATE OF text ON ocr_text BEGIN
+    INSERT INTO ocr_text_fts(ocr_text_fts, rowid, text) VALUES ('delete', old.id, old.text);
+    INSERT INTO ocr_text_fts(rowid, text) VALUES (new.id, new.text);
+END;
+
+-- Drop words left behind by earlier updates and deletes
+INSERT INTO ocr_text_fts(ocr_text_fts) VALUES ('rebuild');
+"#;
+
+/// Migration 025 - Signed reports of purged frames
+const MIGRATION_025_PURGE_REPORTS: &str = r#"
+-- What each purge deleted, kept after the frames are gone
+CREATE TABLE IF NOT EXISTS purge_reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    report TEXT NOT NULL,                   -- JSON, as signed
+    signature TEXT NOT NULL,                -- HMAC-SHA256 of report (hex)
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_purge_reports_created_at ON purge_reports(created_at DESC);
+"#;
+
+/// Migration 026 - Pinned frames, exempt from cleanup
+const MIGRATION_026_FRAME_PINS: &str = r#"
+-- Pinned frames survive retention, compaction, archiving and purges
+ALTER TABLE frames ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_frames_pinned ON frames(pinned) WHERE pinned = 1;
+"#;
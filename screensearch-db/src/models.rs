@@ -3,11 +3,12 @@
 //! Defines Rust structs that map to database tables with proper serialization
 //! and deserialization support.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Video chunk record - stores video file segments
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct VideoChunkRecord {
     pub id: i64,
     pub device_name: String,
@@ -21,8 +22,36 @@ pub struct VideoChunkRecord {
     pub created_at: DateTime<Utc>,
 }
 
+/// Cold-tier archive record - images of a day's old frames
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct FrameArchiveRecord {
+    pub id: i64,
+    /// Capture date (UTC) of the archived frames
+    pub day: NaiveDate,
+    pub file_path: String,
+    pub frame_count: i64,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Location of an archived frame's image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct ArchivedFrameRecord {
+    pub frame_id: i64,
+    pub archive_id: i64,
+    /// File path of the archive
+    pub file_path: String,
+    /// Byte offset of the zstd frame holding the image's tar entry
+    pub entry_offset: i64,
+    /// Compressed length of that zstd frame in bytes
+    pub entry_length: i64,
+}
+
 /// Frame record - metadata for captured screenshot
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct FrameRecord {
     pub id: i64,
     pub chunk_id: Option<i64>,
@@ -37,11 +66,15 @@ pub struct FrameRecord {
     pub height: i32,
     pub offset_index: i32,
     pub focused: Option<bool>,
+    /// Exempt from retention, compaction, archiving and purges
+    #[serde(default)]
+    pub pinned: bool,
     pub created_at: DateTime<Utc>,
 }
 
 /// OCR text record with precise bounding box coordinates
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct OcrTextRecord {
     pub id: i64,
     pub frame_id: i64,
@@ -56,7 +89,8 @@ pub struct OcrTextRecord {
 }
 
 /// Tag record - user-defined category/annotation
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct TagRecord {
     pub id: i64,
     pub tag_name: String,
@@ -65,8 +99,17 @@ pub struct TagRecord {
     pub created_at: DateTime<Utc>,
 }
 
+impl TagRecord {
+    /// Tag exempting frames from retention and low-disk cleanup
+    pub const KEEP_FOREVER: &'static str = "keep-forever";
+
+    /// Tag deleting frames a day after capture, whatever the retention period
+    pub const EPHEMERAL: &'static str = "ephemeral";
+}
+
 /// Frame tag record - junction table entry
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct FrameTagRecord {
     pub id: i64,
     pub frame_id: i64,
@@ -75,7 +118,8 @@ pub struct FrameTagRecord {
 }
 
 /// Settings record - application configuration (singleton)
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct SettingsRecord {
     pub id: i64,
     pub capture_interval: i64,
@@ -83,9 +127,258 @@ pub struct SettingsRecord {
     pub excluded_apps: String, // JSON array
     pub is_paused: i64,        // SQLite boolean (0/1)
     pub retention_days: i64,
+    pub audit_retention_days: i64,
+    pub capture_schedule: String, // JSON array of rules like "Mon-Fri 09:00-18:00"
+    pub search_ranking: String,   // JSON object, see SearchRanking
     pub updated_at: DateTime<Utc>,
 }
 
+/// Report record - AI-generated activity report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct ReportRecord {
+    pub id: i64,
+    pub period: String, // "daily" or "weekly"
+    pub content: String,
+    pub model: String,
+    pub context_source: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Report template record - user-managed system prompt for report generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct ReportTemplateRecord {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub system_prompt: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Macro record - recorded input events for automation replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct MacroRecord {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub events: String, // JSON array
+    pub event_count: i64,
+    pub duration_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Alert record - keyword watch evaluated against OCR text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct AlertRecord {
+    pub id: i64,
+    pub pattern: String,
+    pub is_regex: bool,
+    pub app_filter: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Alert hit record - an alert matching a captured frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct AlertHitRecord {
+    pub id: i64,
+    pub alert_id: i64,
+    pub frame_id: i64,
+    pub matched_text: String,
+    pub context: String,
+    pub app_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Redaction rule record - a term or regex masked in OCR text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct RedactionRuleRecord {
+    pub id: i64,
+    pub pattern: String,
+    pub is_regex: bool,
+    pub blur_images: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Automation audit record - one automation call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct AuditEntryRecord {
+    pub id: i64,
+    pub endpoint: String,
+    pub parameters: Option<String>,
+    pub element: Option<String>, // JSON
+    pub success: bool,
+    pub error: Option<String>,
+    pub caller: Option<String>,
+    pub duration_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Document record - receipt or invoice extracted from a frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct DocumentRecord {
+    pub id: i64,
+    pub frame_id: i64,
+    pub doc_type: String, // "receipt" or "invoice"
+    pub vendor: Option<String>,
+    pub document_date: Option<NaiveDate>,
+    pub total: Option<f64>,
+    pub currency: Option<String>,
+    pub line_items: String, // JSON array
+    pub text: String,
+    pub app_name: Option<String>,
+    pub captured_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// AI interaction record - one request to an AI provider and its outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct AiInteractionRecord {
+    pub id: i64,
+    pub kind: String, // "report", "chat", "ask", "summary" or "agent"
+    pub provider_url: String,
+    pub model: String,
+    pub system_prompt: Option<String>,
+    pub prompt: String,
+    pub context_chunks: String, // JSON array of ContextChunk
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: i64,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub total_tokens: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Screen content sent to an AI provider as context
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextChunk {
+    pub frame_id: i64,
+    /// Embedding chunk or OCR region of the frame, when only part of it was sent
+    pub chunk_index: Option<i32>,
+}
+
+/// Token usage of one model over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct AiUsageRecord {
+    pub model: String,
+    pub interactions: i64,
+    pub failed: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub total_tokens: i64,
+}
+
+/// Search history record - one executed search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct SearchHistoryRecord {
+    pub id: i64,
+    pub query: String,
+    pub mode: String, // "fts", "fuzzy" or "regex"
+    pub result_count: i64,
+    pub clicked_frame_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Metrics history record - one metric value of a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct MetricSampleRecord {
+    pub id: i64,
+    pub metric: String,
+    pub value: f64,
+    /// App version that recorded the value
+    pub version: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Machine that captured a frame received by sync, and the frame's ID there
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncOrigin {
+    pub machine_id: String,
+    pub frame_id: i64,
+}
+
+/// Sync peer record - a machine changes were received from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct SyncPeerRecord {
+    pub machine_id: String,
+    /// Last frame ID received, on the peer
+    pub cursor: i64,
+    pub frames_received: i64,
+    pub last_sync_at: DateTime<Utc>,
+}
+
+/// How often a query was searched for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct SearchQueryCount {
+    pub query: String,
+    pub searches: i64,
+    /// Searches after which a result was opened
+    pub clicks: i64,
+    pub last_searched_at: DateTime<Utc>,
+}
+
+/// Aggregate statistics of the search history over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHistoryStats {
+    pub total_searches: i64,
+    pub unique_queries: i64,
+    pub zero_result_searches: i64,
+    pub clicked_searches: i64,
+    /// Most searched queries, most frequent first
+    pub top_queries: Vec<SearchQueryCount>,
+    /// Queries that found nothing, most frequent first
+    pub zero_result_queries: Vec<SearchQueryCount>,
+}
+
+/// Activity summary record - what happened in one hour
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct ActivitySummaryRecord {
+    pub id: i64,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub summary: String,
+    pub method: String, // "llm" or "extractive"
+    pub model: Option<String>,
+    pub frame_count: i64,
+    pub frame_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Job record - a unit of background work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct JobRecord {
+    pub id: i64,
+    pub job_type: String,
+    pub payload: String, // JSON
+    pub state: String,   // 'queued', 'running', 'completed', 'failed' or 'cancelled'
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub last_error: Option<String>,
+    pub result: Option<String>, // JSON
+    pub run_after: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
 /// Search result combining frame and OCR data with relevance score
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -93,6 +386,88 @@ pub struct SearchResult {
     pub ocr_matches: Vec<OcrTextRecord>,
     pub relevance_score: f32,
     pub tags: Vec<String>,
+    /// Highlighted snippets explaining why each OCR match was returned
+    #[serde(default)]
+    pub highlights: Vec<MatchHighlight>,
+    /// Near-identical results collapsed into this one
+    #[serde(default)]
+    pub duplicates_count: usize,
+}
+
+/// Highlighted FTS5 snippet for a single matching OCR text record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchHighlight {
+    /// ID of the OCR text record this highlight belongs to
+    pub ocr_text_id: i64,
+    /// Short HTML-escaped excerpt around the match with terms wrapped in `<mark>` tags
+    pub snippet: String,
+    /// Character offsets of every match within the full OCR text
+    pub offsets: Vec<MatchOffset>,
+}
+
+/// Character range of a match within OCR text (end exclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchOffset {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Dimension search results can be counted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchFacet {
+    /// Application (`active_process`)
+    App,
+    /// Capture day (UTC, `YYYY-MM-DD`)
+    Day,
+    /// Tag name
+    Tag,
+    /// Monitor index
+    Monitor,
+}
+
+impl SearchFacet {
+    /// All facets
+    pub const ALL: [SearchFacet; 4] = [
+        SearchFacet::App,
+        SearchFacet::Day,
+        SearchFacet::Tag,
+        SearchFacet::Monitor,
+    ];
+
+    /// Parse a facet name (`app`, `day`, `tag` or `monitor`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "app" => Some(SearchFacet::App),
+            "day" => Some(SearchFacet::Day),
+            "tag" => Some(SearchFacet::Tag),
+            "monitor" => Some(SearchFacet::Monitor),
+            _ => None,
+        }
+    }
+}
+
+/// Number of matching frames with one facet value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct FacetCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Facet counts over the full set of frames matching a search
+///
+/// Only requested facets are set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchFacets {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app: Option<Vec<FacetCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<Vec<FacetCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Vec<FacetCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<Vec<FacetCount>>,
 }
 
 /// Frame with associated tags
@@ -103,7 +478,8 @@ pub struct FrameWithTags {
 }
 
 /// OCR content from FTS5 search
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct FtsOcrResult {
     pub id: i64,
     pub frame_id: i64,
@@ -126,6 +502,22 @@ pub struct NewVideoChunk {
     pub fps: i32,
 }
 
+/// New cold-tier archive input
+#[derive(Debug, Clone)]
+pub struct NewFrameArchive {
+    pub day: NaiveDate,
+    pub file_path: String,
+    pub size_bytes: i64,
+}
+
+/// Position of a frame's image in a new archive
+#[derive(Debug, Clone, Copy)]
+pub struct NewArchivedFrame {
+    pub frame_id: i64,
+    pub entry_offset: i64,
+    pub entry_length: i64,
+}
+
 /// New frame input
 #[derive(Debug, Clone)]
 pub struct NewFrame {
@@ -164,6 +556,252 @@ pub struct NewTag {
     pub color: Option<String>,
 }
 
+/// New report input
+#[derive(Debug, Clone)]
+pub struct NewReport {
+    pub period: String,
+    pub content: String,
+    pub model: String,
+    pub context_source: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+/// New or updated report template input
+#[derive(Debug, Clone)]
+pub struct NewReportTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub system_prompt: String,
+}
+
+/// New macro input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewMacro {
+    pub name: String,
+    pub description: Option<String>,
+    pub events: String, // JSON array
+    pub event_count: i64,
+    pub duration_ms: i64,
+}
+
+/// New alert input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAlert {
+    pub pattern: String,
+    pub is_regex: bool,
+    pub app_filter: Option<String>,
+}
+
+/// New redaction rule input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewRedactionRule {
+    pub pattern: String,
+    pub is_regex: bool,
+    pub blur_images: bool,
+}
+
+/// Stored text, besides OCR text, that repeats what was on screen
+///
+/// Redaction rules applied to stored frames mask these columns too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactableText {
+    /// Window title of a frame (`frames.active_window`); rows are frames
+    WindowTitle,
+    /// Text matched by an alert (`alert_hits.matched_text`)
+    AlertMatch,
+    /// OCR text around an alert match (`alert_hits.context`)
+    AlertContext,
+    /// OCR text of a receipt or invoice (`documents.text`)
+    DocumentText,
+    /// Hourly activity summary (`activity_summaries.summary`)
+    Summary,
+    /// Prompt sent to an LLM, with its context (`ai_interactions.prompt`)
+    AiPrompt,
+    /// Answer of an LLM (`ai_interactions.response`)
+    AiResponse,
+}
+
+impl RedactableText {
+    /// All redactable columns
+    pub const ALL: [RedactableText; 7] = [
+        RedactableText::WindowTitle,
+        RedactableText::AlertMatch,
+        RedactableText::AlertContext,
+        RedactableText::DocumentText,
+        RedactableText::Summary,
+        RedactableText::AiPrompt,
+        RedactableText::AiResponse,
+    ];
+
+    /// Table and column holding the text
+    #[cfg(feature = "database")]
+    pub(crate) fn column(self) -> (&'static str, &'static str) {
+        match self {
+            RedactableText::WindowTitle => ("frames", "active_window"),
+            RedactableText::AlertMatch => ("alert_hits", "matched_text"),
+            RedactableText::AlertContext => ("alert_hits", "context"),
+            RedactableText::DocumentText => ("documents", "text"),
+            RedactableText::Summary => ("activity_summaries", "summary"),
+            RedactableText::AiPrompt => ("ai_interactions", "prompt"),
+            RedactableText::AiResponse => ("ai_interactions", "response"),
+        }
+    }
+}
+
+/// Frames to purge; a frame must match every criterion given
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PurgeFilter {
+    /// Text in the frame's OCR text or window title (case-insensitive)
+    pub keyword: Option<String>,
+    /// Part of the process name (case-insensitive)
+    pub app: Option<String>,
+    /// Part of the browser URL (case-insensitive)
+    pub url: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl PurgeFilter {
+    /// Whether no criterion is given, which would match every frame
+    pub fn is_empty(&self) -> bool {
+        self.keyword.is_none()
+            && self.app.is_none()
+            && self.url.is_none()
+            && self.start_time.is_none()
+            && self.end_time.is_none()
+    }
+}
+
+/// What purging frames deleted from the database
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PurgeOutcome {
+    /// Frames deleted, in ID order
+    pub frame_ids: Vec<i64>,
+    pub ocr_rows: u64,
+    pub embeddings: u64,
+    /// Activity summaries covering the frames
+    pub summaries: u64,
+    /// AI interactions that sent the frames as context
+    pub ai_interactions: u64,
+    /// Image, video chunk and archive files left without frames, to delete
+    pub files: Vec<String>,
+    /// Video chunk and archive files that still hold images of other frames
+    pub retained_files: Vec<String>,
+}
+
+/// Signed report of a purge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
+pub struct PurgeReportRecord {
+    pub id: i64,
+    /// Report as signed (JSON)
+    pub report: String,
+    /// HMAC-SHA256 of `report` (hex)
+    pub signature: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// New alert hit input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAlertHit {
+    pub alert_id: i64,
+    pub frame_id: i64,
+    pub matched_text: String,
+    pub context: String,
+    pub app_name: Option<String>,
+}
+
+/// New automation audit entry input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAuditEntry {
+    pub endpoint: String,
+    pub parameters: Option<String>,
+    pub element: Option<String>, // JSON
+    pub success: bool,
+    pub error: Option<String>,
+    pub caller: Option<String>,
+    pub duration_ms: i64,
+}
+
+/// New document input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewDocument {
+    pub frame_id: i64,
+    pub doc_type: String,
+    pub vendor: Option<String>,
+    pub document_date: Option<NaiveDate>,
+    pub total: Option<f64>,
+    pub currency: Option<String>,
+    pub line_items: String, // JSON array
+    pub text: String,
+    pub app_name: Option<String>,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// New AI interaction input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAiInteraction {
+    pub kind: String,
+    pub provider_url: String,
+    pub model: String,
+    pub system_prompt: Option<String>,
+    pub prompt: String,
+    pub context_chunks: Vec<ContextChunk>,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: i64,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub total_tokens: Option<i64>,
+}
+
+/// New search history input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewSearchHistory {
+    pub query: String,
+    pub mode: String,
+    pub result_count: i64,
+}
+
+/// New metric value of a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewMetricSample {
+    pub metric: String,
+    pub value: f64,
+}
+
+/// Frame received from another machine, with its text and tags
+#[derive(Debug, Clone)]
+pub struct NewSyncedFrame {
+    pub frame: NewFrame,
+    /// OCR text; `frame_id` is set on insertion
+    pub ocr: Vec<NewOcrText>,
+    /// Tag names, created when missing
+    pub tags: Vec<String>,
+    pub origin: SyncOrigin,
+}
+
+/// New activity summary input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewActivitySummary {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub summary: String,
+    pub method: String,
+    pub model: Option<String>,
+    pub frame_count: i64,
+    pub frame_id: i64,
+}
+
+/// New job input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewJob {
+    pub job_type: String,
+    pub payload: String, // JSON
+    pub max_attempts: i64,
+}
+
 /// Update settings input
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateSettings {
@@ -172,6 +810,15 @@ pub struct UpdateSettings {
     pub excluded_apps: String, // JSON array
     pub is_paused: i64,        // SQLite boolean (0/1)
     pub retention_days: i64,
+    /// Days to keep automation audit entries (`None` keeps the current value)
+    #[serde(default)]
+    pub audit_retention_days: Option<i64>,
+    /// JSON array of capture schedule rules (`None` keeps the current value)
+    #[serde(default)]
+    pub capture_schedule: Option<String>,
+    /// JSON object of search ranking weights (`None` keeps the current value)
+    #[serde(default)]
+    pub search_ranking: Option<String>,
 }
 
 /// Frame filter parameters for queries
@@ -183,6 +830,37 @@ pub struct FrameFilter {
     pub device_name: Option<String>,
     pub tag_ids: Option<Vec<i64>>,
     pub monitor_index: Option<i32>,
+    /// Leave out frames of these applications (case-insensitive)
+    pub exclude_apps: Option<Vec<String>>,
+    /// Leave out frames with any of these tags (case-insensitive)
+    pub exclude_tags: Option<Vec<String>>,
+}
+
+/// How full-text search results are ranked
+///
+/// A match scores its BM25 relevance times `text_weight + title_weight * s`,
+/// where `s` is the share of query words found in the window title of its
+/// frame. With a `recency_weight` above zero, newer frames score up to
+/// `1 + recency_weight` times more. The boost halves with every
+/// `recency_half_life_hours` of age, and shrinks linearly in between.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchRanking {
+    pub text_weight: f64,
+    pub title_weight: f64,
+    pub recency_weight: f64,
+    pub recency_half_life_hours: f64,
+}
+
+impl Default for SearchRanking {
+    fn default() -> Self {
+        Self {
+            text_weight: 1.0,
+            title_weight: 0.0,
+            recency_weight: 0.0,
+            recency_half_life_hours: 24.0,
+        }
+    }
 }
 
 /// Pagination parameters
@@ -212,7 +890,8 @@ impl Default for Pagination {
 // ============================================================
 
 /// Embedding record - stored embedding for a text chunk
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "database", derive(sqlx::FromRow))]
 pub struct EmbeddingRecord {
     pub id: i64,
     pub frame_id: i64,
@@ -261,3 +940,48 @@ pub struct EmbeddingStatus {
     pub last_processed_frame_id: i64,
 }
 
+/// Embedding queue counts by status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddingQueueStats {
+    pub pending: i64,
+    pub processing: i64,
+    pub completed: i64,
+    pub skipped: i64,
+    pub failed: i64,
+    /// Frames that reached a final status since the requested time
+    pub finished_since: i64,
+}
+
+impl EmbeddingQueueStats {
+    /// Total number of queued frames
+    pub fn total(&self) -> i64 {
+        self.pending + self.processing + self.completed + self.skipped + self.failed
+    }
+}
+
+/// Size and growth of the stored embeddings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddingIndexStats {
+    /// Embedded text chunks
+    pub chunks: i64,
+    /// Bytes of stored vectors
+    pub vector_bytes: i64,
+    /// Bytes of stored chunk text
+    pub text_bytes: i64,
+    /// Chunks embedded since the requested time
+    pub chunks_since: i64,
+    /// When the newest chunk was embedded
+    pub last_embedded_at: Option<DateTime<Utc>>,
+}
+
+/// Result of generating embeddings for one queued frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmbeddingOutcome {
+    /// Embeddings were stored
+    Completed,
+    /// The frame has no OCR text to embed
+    Skipped,
+    /// Embedding failed with the given error
+    Failed(String),
+}
+
@@ -49,6 +49,248 @@ impl DatabaseManager {
         Ok(chunk)
     }
 
+    /// Get up to `limit` unpinned frames captured before `before` that are
+    /// still stored as individual images
+    ///
+    /// Ordered by device, monitor and capture time, so frames that can share
+    /// a video chunk are adjacent.
+    pub async fn get_frames_to_compact(
+        &self,
+        before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<FrameRecord>> {
+        let frames = sqlx::query_as::<_, FrameRecord>(
+            r#"
+            SELECT id, chunk_id, timestamp, monitor_index, device_name, file_path,
+                   active_window, active_process, browser_url, width, height,
+                   offset_index, focused, pinned, created_at
+            FROM frames
+            WHERE chunk_id IS NULL AND timestamp < ? AND pinned = 0
+              AND NOT EXISTS (SELECT 1 FROM archived_frames a WHERE a.frame_id = frames.id)
+            ORDER BY device_name, monitor_index, timestamp, id
+            LIMIT ?
+            "#,
+        )
+        .bind(before)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(frames)
+    }
+
+    /// Store a video chunk encoded from `frame_ids`, in order
+    ///
+    /// Each frame is pointed at the chunk file, with its position in the
+    /// chunk as `offset_index`. Frames already in a chunk are left alone.
+    /// Returns the ID of the new chunk.
+    pub async fn insert_compacted_chunk(
+        &self,
+        chunk: NewVideoChunk,
+        frame_ids: &[i64],
+    ) -> Result<i64> {
+        let mut tx = self.pool().begin().await?;
+
+        let chunk_id = sqlx::query(
+            r#"
+            INSERT INTO video_chunks (device_name, file_path, start_time, end_time, duration_ms, width, height, fps)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&chunk.device_name)
+        .bind(&chunk.file_path)
+        .bind(chunk.start_time)
+        .bind(chunk.end_time)
+        .bind(chunk.duration_ms)
+        .bind(chunk.width)
+        .bind(chunk.height)
+        .bind(chunk.fps)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        for (offset, frame_id) in frame_ids.iter().enumerate() {
+            sqlx::query(
+                "UPDATE frames SET chunk_id = ?, offset_index = ?, file_path = ? \
+                 WHERE id = ? AND chunk_id IS NULL",
+            )
+            .bind(chunk_id)
+            .bind(offset as i32)
+            .bind(&chunk.file_path)
+            .bind(frame_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(chunk_id)
+    }
+
+    /// Delete video chunks no frame refers to anymore
+    ///
+    /// Returns the file paths of the deleted chunks, so the files can be
+    /// removed as well.
+    pub async fn delete_empty_video_chunks(&self) -> Result<Vec<String>> {
+        let paths = sqlx::query_scalar::<_, String>(
+            r#"
+            DELETE FROM video_chunks
+            WHERE NOT EXISTS (SELECT 1 FROM frames WHERE frames.chunk_id = video_chunks.id)
+            RETURNING file_path
+            "#,
+        )
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(paths)
+    }
+
+    // ===== Frame Archive Operations =====
+
+    /// Get up to `limit` unpinned frames captured before `before` that are
+    /// still stored as individual images, oldest first
+    pub async fn get_frames_to_archive(
+        &self,
+        before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<FrameRecord>> {
+        let frames = sqlx::query_as::<_, FrameRecord>(
+            r#"
+            SELECT id, chunk_id, timestamp, monitor_index, device_name, file_path,
+                   active_window, active_process, browser_url, width, height,
+                   offset_index, focused, pinned, created_at
+            FROM frames
+            WHERE chunk_id IS NULL AND timestamp < ? AND pinned = 0
+              AND NOT EXISTS (SELECT 1 FROM archived_frames a WHERE a.frame_id = frames.id)
+            ORDER BY timestamp, id
+            LIMIT ?
+            "#,
+        )
+        .bind(before)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(frames)
+    }
+
+    /// Store an archive holding the images of `frames`
+    ///
+    /// Each frame is pointed at the archive file. Frames deleted or
+    /// compacted in the meantime are left out. Returns the ID of the new
+    /// archive.
+    pub async fn insert_frame_archive(
+        &self,
+        archive: NewFrameArchive,
+        frames: &[NewArchivedFrame],
+    ) -> Result<i64> {
+        let mut tx = self.pool().begin().await?;
+
+        let archive_id = sqlx::query(
+            r#"
+            INSERT INTO frame_archives (day, file_path, frame_count, size_bytes, created_at)
+            VALUES (?, ?, 0, ?, ?)
+            "#,
+        )
+        .bind(archive.day)
+        .bind(&archive.file_path)
+        .bind(archive.size_bytes)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        let mut frame_count = 0;
+        for frame in frames {
+            let updated = sqlx::query(
+                "UPDATE frames SET file_path = ? WHERE id = ? AND chunk_id IS NULL \
+                 AND NOT EXISTS (SELECT 1 FROM archived_frames WHERE frame_id = ?)",
+            )
+            .bind(&archive.file_path)
+            .bind(frame.frame_id)
+            .bind(frame.frame_id)
+            .execute(&mut *tx)
+            .await?;
+            if updated.rows_affected() == 0 {
+                continue;
+            }
+
+            sqlx::query(
+                "INSERT INTO archived_frames (frame_id, archive_id, entry_offset, entry_length) \
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(frame.frame_id)
+            .bind(archive_id)
+            .bind(frame.entry_offset)
+            .bind(frame.entry_length)
+            .execute(&mut *tx)
+            .await?;
+            frame_count += 1;
+        }
+
+        sqlx::query("UPDATE frame_archives SET frame_count = ? WHERE id = ?")
+            .bind(frame_count)
+            .bind(archive_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(archive_id)
+    }
+
+    /// Get where the image of an archived frame is
+    ///
+    /// Returns `None` for frames that are not archived.
+    pub async fn get_archived_frame(&self, frame_id: i64) -> Result<Option<ArchivedFrameRecord>> {
+        let entry = sqlx::query_as::<_, ArchivedFrameRecord>(
+            r#"
+            SELECT a.frame_id, a.archive_id, fa.file_path, a.entry_offset, a.entry_length
+            FROM archived_frames a
+            JOIN frame_archives fa ON fa.id = a.archive_id
+            WHERE a.frame_id = ?
+            "#,
+        )
+        .bind(frame_id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(entry)
+    }
+
+    /// List archives, most recent day first
+    pub async fn list_frame_archives(&self) -> Result<Vec<FrameArchiveRecord>> {
+        let archives = sqlx::query_as::<_, FrameArchiveRecord>(
+            r#"
+            SELECT id, day, file_path, frame_count, size_bytes, created_at
+            FROM frame_archives
+            ORDER BY day DESC, id DESC
+            "#,
+        )
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(archives)
+    }
+
+    /// Delete archives no frame refers to anymore
+    ///
+    /// Returns the file paths of the deleted archives, so the files can be
+    /// removed as well.
+    pub async fn delete_empty_frame_archives(&self) -> Result<Vec<String>> {
+        let paths = sqlx::query_scalar::<_, String>(
+            r#"
+            DELETE FROM frame_archives
+            WHERE NOT EXISTS (
+                SELECT 1 FROM archived_frames WHERE archived_frames.archive_id = frame_archives.id
+            )
+            RETURNING file_path
+            "#,
+        )
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(paths)
+    }
+
     // ===== Frame Operations =====
 
     /// Insert a new frame record
@@ -87,7 +329,7 @@ impl DatabaseManager {
             r#"
             SELECT id, chunk_id, timestamp, monitor_index, device_name, file_path,
                    active_window, active_process, browser_url, width, height,
-                   offset_index, focused, created_at
+                   offset_index, focused, pinned, created_at
             FROM frames
             WHERE id = ?
             "#,
@@ -99,7 +341,34 @@ impl DatabaseManager {
         Ok(frame)
     }
 
+    /// ID of the frame from `device_name` at `timestamp` with the window
+    /// title `active_window`, if one was stored
+    ///
+    /// Used to skip images that were imported before.
+    pub async fn find_frame_id(
+        &self,
+        device_name: &str,
+        timestamp: DateTime<Utc>,
+        active_window: &str,
+    ) -> Result<Option<i64>> {
+        let id = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT id FROM frames
+            WHERE device_name = ? AND timestamp = ? AND active_window = ?
+            LIMIT 1
+            "#,
+        )
+        .bind(device_name)
+        .bind(timestamp)
+        .bind(active_window)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(id)
+    }
+
     /// Get frames within a time range with optional filters
+    #[tracing::instrument(skip(self, filter, pagination), fields(limit = pagination.limit))]
     pub async fn get_frames_in_range(
         &self,
         start: DateTime<Utc>,
@@ -111,7 +380,7 @@ impl DatabaseManager {
             r#"
             SELECT id, chunk_id, timestamp, monitor_index, device_name, file_path,
                    active_window, active_process, browser_url, width, height,
-                   offset_index, focused, created_at
+                   offset_index, focused, pinned, created_at
             FROM frames
             WHERE timestamp >= ? AND timestamp <= ?
             "#,
@@ -126,6 +395,7 @@ impl DatabaseManager {
         if let Some(_monitor) = filter.monitor_index {
             query.push_str(" AND monitor_index = ?");
         }
+        let excluded = push_exclusions(&mut query, &filter, "frames");
 
         query.push_str(" ORDER BY timestamp DESC LIMIT ? OFFSET ?");
 
@@ -142,6 +412,9 @@ impl DatabaseManager {
         if let Some(monitor) = filter.monitor_index {
             query_builder = query_builder.bind(monitor);
         }
+        for value in excluded {
+            query_builder = query_builder.bind(value);
+        }
 
         let frames = query_builder
             .bind(pagination.limit)
@@ -152,7 +425,143 @@ impl DatabaseManager {
         Ok(frames)
     }
 
+    /// Count the frames [`get_frames_in_range`](Self::get_frames_in_range)
+    /// pages through
+    pub async fn count_frames_with_filter(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        filter: &FrameFilter,
+    ) -> Result<i64> {
+        let mut query =
+            String::from("SELECT COUNT(*) FROM frames WHERE timestamp >= ? AND timestamp <= ?");
+        if filter.app_name.is_some() {
+            query.push_str(" AND active_process = ?");
+        }
+        if filter.device_name.is_some() {
+            query.push_str(" AND device_name = ?");
+        }
+        if filter.monitor_index.is_some() {
+            query.push_str(" AND monitor_index = ?");
+        }
+        let excluded = push_exclusions(&mut query, filter, "frames");
+
+        let mut query_builder = sqlx::query_scalar::<_, i64>(&query).bind(start).bind(end);
+        if let Some(app) = &filter.app_name {
+            query_builder = query_builder.bind(app);
+        }
+        if let Some(device) = &filter.device_name {
+            query_builder = query_builder.bind(device);
+        }
+        if let Some(monitor) = filter.monitor_index {
+            query_builder = query_builder.bind(monitor);
+        }
+        for value in excluded {
+            query_builder = query_builder.bind(value);
+        }
+
+        let count = query_builder.fetch_one(self.pool()).await?;
+        Ok(count)
+    }
+
+    /// Get frames after a cursor in capture order, for sequential playback
+    ///
+    /// Returns up to `limit` frames ordered by `(timestamp, id)` that come after
+    /// `(after_time, after_id)` and were captured no later than `end`. Pass the
+    /// last returned frame's timestamp and ID to fetch the next page.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_frames_after(
+        &self,
+        after_time: DateTime<Utc>,
+        after_id: i64,
+        end: DateTime<Utc>,
+        monitor_index: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<FrameRecord>> {
+        let frames = sqlx::query_as::<_, FrameRecord>(
+            r#"
+            SELECT id, chunk_id, timestamp, monitor_index, device_name, file_path,
+                   active_window, active_process, browser_url, width, height,
+                   offset_index, focused, pinned, created_at
+            FROM frames
+            WHERE (timestamp > ? OR (timestamp = ? AND id > ?))
+              AND timestamp <= ?
+              AND (? IS NULL OR monitor_index = ?)
+            ORDER BY timestamp ASC, id ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(after_time)
+        .bind(after_time)
+        .bind(after_id)
+        .bind(end)
+        .bind(monitor_index)
+        .bind(monitor_index)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(frames)
+    }
+
+    /// Get frames captured immediately before and after a frame on the same monitor
+    ///
+    /// Returns `(before, after)`, both ordered by timestamp ascending. Neither list
+    /// includes the frame itself.
+    pub async fn get_surrounding_frames(
+        &self,
+        frame: &FrameRecord,
+        before: i64,
+        after: i64,
+    ) -> Result<(Vec<FrameRecord>, Vec<FrameRecord>)> {
+        let mut frames_before = sqlx::query_as::<_, FrameRecord>(
+            r#"
+            SELECT id, chunk_id, timestamp, monitor_index, device_name, file_path,
+                   active_window, active_process, browser_url, width, height,
+                   offset_index, focused, pinned, created_at
+            FROM frames
+            WHERE monitor_index = ? AND id != ?
+              AND (timestamp < ? OR (timestamp = ? AND id < ?))
+            ORDER BY timestamp DESC, id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(frame.monitor_index)
+        .bind(frame.id)
+        .bind(frame.timestamp)
+        .bind(frame.timestamp)
+        .bind(frame.id)
+        .bind(before)
+        .fetch_all(self.pool())
+        .await?;
+        frames_before.reverse();
+
+        let frames_after = sqlx::query_as::<_, FrameRecord>(
+            r#"
+            SELECT id, chunk_id, timestamp, monitor_index, device_name, file_path,
+                   active_window, active_process, browser_url, width, height,
+                   offset_index, focused, pinned, created_at
+            FROM frames
+            WHERE monitor_index = ? AND id != ?
+              AND (timestamp > ? OR (timestamp = ? AND id > ?))
+            ORDER BY timestamp ASC, id ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(frame.monitor_index)
+        .bind(frame.id)
+        .bind(frame.timestamp)
+        .bind(frame.timestamp)
+        .bind(frame.id)
+        .bind(after)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok((frames_before, frames_after))
+    }
+
     /// Get frames with tags
+    #[tracing::instrument(skip_all, fields(frames = frame_ids.len()))]
     pub async fn get_frames_with_tags(&self, frame_ids: Vec<i64>) -> Result<Vec<FrameWithTags>> {
         if frame_ids.is_empty() {
             return Ok(Vec::new());
@@ -170,16 +579,107 @@ impl DatabaseManager {
         Ok(results)
     }
 
-    /// Delete frames older than specified timestamp
-    pub async fn delete_old_frames(&self, before: DateTime<Utc>) -> Result<u64> {
-        let result = sqlx::query("DELETE FROM frames WHERE timestamp < ?")
-            .bind(before)
+    /// Pin or unpin a frame
+    ///
+    /// Pinned frames are exempt from retention, compaction, archiving and
+    /// purges. Returns the number of frames updated (0 if not found).
+    pub async fn set_frame_pinned(&self, frame_id: i64, pinned: bool) -> Result<u64> {
+        let result = sqlx::query("UPDATE frames SET pinned = ? WHERE id = ?")
+            .bind(pinned)
+            .bind(frame_id)
             .execute(self.pool())
             .await?;
 
         Ok(result.rows_affected())
     }
 
+    /// Delete frames older than specified timestamp
+    ///
+    /// Pinned frames and frames tagged [`TagRecord::KEEP_FOREVER`] are kept.
+    pub async fn delete_old_frames(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM frames WHERE timestamp < ? AND pinned = 0 AND id NOT IN ({})",
+            TAGGED_FRAMES
+        ))
+        .bind(before)
+        .bind(TagRecord::KEEP_FOREVER)
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete frames tagged [`TagRecord::EPHEMERAL`] captured before `before`
+    ///
+    /// Pinned frames and frames also tagged [`TagRecord::KEEP_FOREVER`] are
+    /// kept.
+    pub async fn delete_ephemeral_frames(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(&format!(
+            r#"
+            DELETE FROM frames
+            WHERE timestamp < ? AND pinned = 0 AND id IN ({}) AND id NOT IN ({})
+            "#,
+            TAGGED_FRAMES, TAGGED_FRAMES
+        ))
+        .bind(before)
+        .bind(TagRecord::EPHEMERAL)
+        .bind(TagRecord::KEEP_FOREVER)
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete up to `limit` of the oldest frames captured before `before`
+    ///
+    /// Pinned frames and frames tagged [`TagRecord::KEEP_FOREVER`] are kept.
+    /// Returns the number of frames deleted, and the image paths of the
+    /// deleted frames and of video chunks and archives left without frames,
+    /// so the files can be removed as well.
+    pub async fn delete_oldest_frames(
+        &self,
+        before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<(u64, Vec<String>)> {
+        let deleted = sqlx::query_as::<_, (Option<i64>, bool, String)>(&format!(
+            r#"
+            DELETE FROM frames
+            WHERE id IN (
+                SELECT id FROM frames
+                WHERE timestamp < ? AND pinned = 0 AND id NOT IN ({})
+                ORDER BY timestamp ASC LIMIT ?
+            )
+            RETURNING chunk_id,
+                      EXISTS (SELECT 1 FROM frame_archives fa WHERE fa.file_path = frames.file_path),
+                      file_path
+            "#,
+            TAGGED_FRAMES
+        ))
+        .bind(before)
+        .bind(TagRecord::KEEP_FOREVER)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        // Compacted and archived frames share their chunk's or archive's file
+        let count = deleted.len() as u64;
+        let compacted = deleted.iter().any(|(chunk_id, _, _)| chunk_id.is_some());
+        let archived = deleted.iter().any(|(_, archived, _)| *archived);
+        let mut paths: Vec<String> = deleted
+            .into_iter()
+            .filter(|(chunk_id, archived, _)| chunk_id.is_none() && !archived)
+            .map(|(_, _, path)| path)
+            .collect();
+        if compacted {
+            paths.extend(self.delete_empty_video_chunks().await?);
+        }
+        if archived {
+            paths.extend(self.delete_empty_frame_archives().await?);
+        }
+
+        Ok((count, paths))
+    }
+
     /// Get frame count within a time range
     pub async fn count_frames_in_range(
         &self,
@@ -197,6 +697,38 @@ impl DatabaseManager {
         Ok(count)
     }
 
+    /// Image file paths of all frames
+    pub async fn get_frame_file_paths(&self) -> Result<Vec<String>> {
+        let paths = sqlx::query_scalar::<_, String>("SELECT file_path FROM frames")
+            .fetch_all(self.pool())
+            .await?;
+
+        Ok(paths)
+    }
+
+    /// Replace the prefix `from` of stored file paths with `to`
+    ///
+    /// Used when the data directory moves. Covers frame images, video
+    /// chunks and archives; returns the number of rows rewritten.
+    pub async fn rewrite_file_paths(&self, from: &str, to: &str) -> Result<u64> {
+        let mut rewritten = 0;
+        for table in ["frames", "video_chunks", "frame_archives"] {
+            let result = sqlx::query(&format!(
+                "UPDATE {table} SET file_path = ? || substr(file_path, length(?) + 1) \
+                 WHERE substr(file_path, 1, length(?)) = ?"
+            ))
+            .bind(to)
+            .bind(from)
+            .bind(from)
+            .bind(from)
+            .execute(self.pool())
+            .await?;
+            rewritten += result.rows_affected();
+        }
+
+        Ok(rewritten)
+    }
+
     // ===== OCR Text Operations =====
 
     /// Insert OCR text result for a frame
@@ -238,9 +770,43 @@ impl DatabaseManager {
         Ok(ocr_texts)
     }
 
-    /// Get OCR text by ID
-    pub async fn get_ocr_text(&self, id: i64) -> Result<Option<OcrTextRecord>> {
-        let ocr = sqlx::query_as::<_, OcrTextRecord>(
+    /// Get OCR text of several frames, by frame ID
+    ///
+    /// Frames without OCR text are left out of the map.
+    pub async fn get_ocr_text_for_frames(
+        &self,
+        frame_ids: &[i64],
+    ) -> Result<std::collections::HashMap<i64, Vec<OcrTextRecord>>> {
+        let mut result: std::collections::HashMap<i64, Vec<OcrTextRecord>> =
+            std::collections::HashMap::new();
+        if frame_ids.is_empty() {
+            return Ok(result);
+        }
+
+        let placeholders = frame_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query_str = format!(
+            r#"
+            SELECT id, frame_id, text, text_json, x, y, width, height, confidence, created_at
+            FROM ocr_text
+            WHERE frame_id IN ({})
+            ORDER BY frame_id, y ASC, x ASC
+            "#,
+            placeholders
+        );
+        let mut query = sqlx::query_as::<_, OcrTextRecord>(&query_str);
+        for &id in frame_ids {
+            query = query.bind(id);
+        }
+
+        for record in query.fetch_all(self.pool()).await? {
+            result.entry(record.frame_id).or_default().push(record);
+        }
+        Ok(result)
+    }
+
+    /// Get OCR text by ID
+    pub async fn get_ocr_text(&self, id: i64) -> Result<Option<OcrTextRecord>> {
+        let ocr = sqlx::query_as::<_, OcrTextRecord>(
             r#"
             SELECT id, frame_id, text, text_json, x, y, width, height, confidence, created_at
             FROM ocr_text
@@ -260,25 +826,224 @@ impl DatabaseManager {
     ///
     /// Uses FTS5 virtual table for efficient full-text search with Porter stemming
     /// and BM25 relevance ranking.
+    #[tracing::instrument(skip(self, filter, pagination), fields(limit = pagination.limit))]
     pub async fn search_ocr_text(
         &self,
         query: &str,
         filter: FrameFilter,
         pagination: Pagination,
     ) -> Result<Vec<SearchResult>> {
-        // Escape the query for FTS5 - wrap in double quotes to treat as literal phrase
-        // This prevents numbers and special chars from being misinterpreted
-        let escaped_query = format!("\"{}\"", query.replace("\"", "\"\""));
-        
-        let mut sql = String::from(
+        self.search_ocr_text_ranked(query, filter, pagination, &SearchRanking::default())
+            .await
+    }
+
+    /// Search OCR text, ranking matches with the given weights
+    #[tracing::instrument(skip(self, filter, pagination, ranking), fields(limit = pagination.limit))]
+    pub async fn search_ocr_text_ranked(
+        &self,
+        query: &str,
+        filter: FrameFilter,
+        pagination: Pagination,
+        ranking: &SearchRanking,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_ocr_match(
+            &phrase_query(query),
+            &query_words(query),
+            ranking,
+            filter,
+            pagination,
+        )
+        .await
+    }
+
+    /// Search OCR text, tolerating typos and OCR errors
+    ///
+    /// Query words that are not in the index are replaced by the indexed terms
+    /// closest to them (see [`fuzzy_match_expression`](Self::fuzzy_match_expression)),
+    /// so "kuberntes" still finds "kubernetes". Unlike
+    /// [`search_ocr_text`](Self::search_ocr_text), the words do not need to
+    /// appear as a phrase.
+    #[tracing::instrument(skip(self, filter, pagination), fields(limit = pagination.limit))]
+    pub async fn search_ocr_text_fuzzy(
+        &self,
+        query: &str,
+        filter: FrameFilter,
+        pagination: Pagination,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_ocr_text_fuzzy_ranked(query, filter, pagination, &SearchRanking::default())
+            .await
+    }
+
+    /// Search OCR text tolerating typos, ranking matches with the given weights
+    #[tracing::instrument(skip(self, filter, pagination, ranking), fields(limit = pagination.limit))]
+    pub async fn search_ocr_text_fuzzy_ranked(
+        &self,
+        query: &str,
+        filter: FrameFilter,
+        pagination: Pagination,
+        ranking: &SearchRanking,
+    ) -> Result<Vec<SearchResult>> {
+        let expression = self.fuzzy_match_expression(query).await?;
+        self.search_ocr_match(
+            &expression,
+            &query_words(query),
+            ranking,
+            filter,
+            pagination,
+        )
+        .await
+    }
+
+    /// Build an FTS5 expression matching `query` with typos corrected
+    ///
+    /// Each word of the query must match. A word of at least
+    /// `FUZZY_MIN_WORD_LEN` characters that is not in the index also matches
+    /// the most frequent indexed terms within edit distance 1 (2 for words of
+    /// 8 or more characters). Indexed terms are Porter stems, so up to three
+    /// trailing characters of the word are ignored when comparing them.
+    pub async fn fuzzy_match_expression(&self, query: &str) -> Result<String> {
+        let words = query_words(query);
+        if words.is_empty() {
+            return Ok(phrase_query(query));
+        }
+
+        let mut groups = Vec::with_capacity(words.len());
+        for word in &words {
+            let exact = phrase_query(word);
+            let length = word.chars().count();
+            if length < FUZZY_MIN_WORD_LEN || self.fts_matches_any(&exact).await? {
+                groups.push(exact);
+                continue;
+            }
+
+            let max_distance = if length >= 8 { 2 } else { 1 };
+            let first = word.chars().next().unwrap_or_default();
+            let next = char::from_u32(first as u32 + 1).unwrap_or(char::MAX);
+            let candidates: Vec<(String, i64)> = sqlx::query_as(
+                r#"
+                SELECT term, doc FROM ocr_text_vocab
+                WHERE term >= ? AND term < ? AND length(term) BETWEEN ? AND ?
+                "#,
+            )
+            .bind(first.to_string())
+            .bind(next.to_string())
+            .bind((length.saturating_sub(FUZZY_MAX_SUFFIX + max_distance)) as i64)
+            .bind((length + max_distance) as i64)
+            .fetch_all(self.pool())
+            .await?;
+
+            let mut corrections: Vec<(usize, i64, String)> = candidates
+                .into_iter()
+                .filter_map(|(term, docs)| {
+                    let distance = stem_distance(word, &term);
+                    (distance <= max_distance).then_some((distance, docs, term))
+                })
+                .collect();
+            corrections.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+            corrections.truncate(FUZZY_MAX_CORRECTIONS);
+            tracing::debug!(
+                "Fuzzy search corrected '{}' to {:?}",
+                word,
+                corrections.iter().map(|c| &c.2).collect::<Vec<_>>()
+            );
+
+            let mut alternatives = vec![exact];
+            for (_, _, stem) in &corrections {
+                if let Some(expression) = self.stem_expression(stem).await? {
+                    alternatives.push(expression);
+                }
+            }
+            groups.push(format!("({})", alternatives.join(" OR ")));
+        }
+
+        Ok(groups.join(" AND "))
+    }
+
+    /// FTS5 expression matching an indexed Porter stem
+    ///
+    /// Query terms are stemmed again, which changes some stems (e.g. "deploy"
+    /// becomes "deploi"), so those are matched by their longest prefix that
+    /// still finds them.
+    async fn stem_expression(&self, stem: &str) -> Result<Option<String>> {
+        let exact = phrase_query(stem);
+        if self.fts_matches_any(&exact).await? {
+            return Ok(Some(exact));
+        }
+
+        let chars: Vec<char> = stem.chars().collect();
+        for len in (FUZZY_MIN_WORD_LEN - 1..=chars.len()).rev() {
+            let prefix: String = chars[..len].iter().collect();
+            let expression = format!("{}*", phrase_query(&prefix));
+            if self.fts_matches_any(&expression).await? {
+                return Ok(Some(expression));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether an FTS5 expression matches any OCR text
+    async fn fts_matches_any(&self, expression: &str) -> Result<bool> {
+        let found = sqlx::query_scalar::<_, i64>(
+            "SELECT rowid FROM ocr_text_fts WHERE ocr_text_fts MATCH ? LIMIT 1",
+        )
+        .bind(expression)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(found.is_some())
+    }
+
+    /// Search OCR text matching an FTS5 expression
+    ///
+    /// `words` are looked for in window titles when `ranking` weights them.
+    /// Pages hold frames, ranked by their best-scoring match, each with all
+    /// of its matching OCR text.
+    async fn search_ocr_match(
+        &self,
+        expression: &str,
+        words: &[String],
+        ranking: &SearchRanking,
+        filter: FrameFilter,
+        pagination: Pagination,
+    ) -> Result<Vec<SearchResult>> {
+        // Share of the query words found in the window title
+        let title_words = if ranking.title_weight != 0.0 {
+            words
+        } else {
+            &[]
+        };
+        let title_share = if title_words.is_empty() {
+            "0.0".to_string()
+        } else {
+            let hits = vec!["(COALESCE(f.active_window, '') LIKE ?)"; title_words.len()];
+            format!("({}) / {}.0", hits.join(" + "), title_words.len())
+        };
+        // Boost that halves with every half-life of age, linearly in between.
+        // SQLite is built without math functions, so whole half-lives are a
+        // bit shift; past 62 of them the boost is as good as gone.
+        let recency = if ranking.recency_weight != 0.0 {
+            let age = "(MAX(0.0, (julianday('now') - julianday(f.timestamp)) * 24.0) / ?)";
+            format!(
+                "(1.0 + ? * (1.0 - ({age} - CAST({age} AS INTEGER)) / 2.0) \
+                 / (1 << MIN(CAST({age} AS INTEGER), 62)))"
+            )
+        } else {
+            "1.0".to_string()
+        };
+
+        let mut sql = format!(
             r#"
+            WITH matches AS MATERIALIZED (
             SELECT
                 f.id, f.chunk_id, f.timestamp, f.monitor_index, f.device_name,
                 f.file_path, f.active_window, f.active_process, f.browser_url,
-                f.width, f.height, f.offset_index, f.focused, f.created_at,
-                o.id, o.frame_id, o.text, o.text_json, o.x, o.y, o.width, o.height,
-                o.confidence, o.created_at,
-                ocr_text_fts.rank
+                f.width, f.height, f.offset_index, f.focused, f.pinned, f.created_at,
+                o.id AS ocr_id, o.frame_id, o.text, o.text_json, o.x, o.y,
+                o.width AS ocr_width, o.height AS ocr_height, o.confidence,
+                o.created_at AS ocr_created_at,
+                -bm25(ocr_text_fts) * (? + ? * {title_share}) * {recency} AS score,
+                snippet(ocr_text_fts, 0, char(2), char(3), '...', 16) AS snippet,
+                highlight(ocr_text_fts, 0, char(2), char(3)) AS highlighted
             FROM ocr_text_fts
             JOIN ocr_text o ON ocr_text_fts.rowid = o.id
             JOIN frames f ON o.frame_id = f.id
@@ -287,26 +1052,52 @@ impl DatabaseManager {
         );
 
         // Add optional filters
-        if let Some(start) = filter.start_time {
-            let _ = (start,); // Use filter to avoid unused variable warnings
+        if filter.start_time.is_some() {
             sql.push_str(" AND f.timestamp >= ?");
         }
-        if let Some(end) = filter.end_time {
-            let _ = (end,);
+        if filter.end_time.is_some() {
             sql.push_str(" AND f.timestamp <= ?");
         }
-        if let Some(app) = &filter.app_name {
-            let _ = app;
+        if filter.app_name.is_some() {
             sql.push_str(" AND f.active_process = ?");
         }
-        if let Some(device) = &filter.device_name {
-            let _ = device;
+        if filter.device_name.is_some() {
             sql.push_str(" AND f.device_name = ?");
         }
+        let excluded = push_exclusions(&mut sql, &filter, "f");
 
-        sql.push_str(" ORDER BY ocr_text_fts.rank ASC LIMIT ? OFFSET ?");
+        // Page through frames rather than OCR rows, so a page holds `limit`
+        // frames and totals count frames
+        sql.push_str(
+            r#"
+            ),
+            page AS (
+                SELECT id AS page_id, MAX(score) AS frame_score
+                FROM matches
+                GROUP BY id
+                ORDER BY frame_score DESC, id DESC
+                LIMIT ? OFFSET ?
+            )
+            SELECT matches.* FROM matches
+            JOIN page ON page.page_id = matches.id
+            ORDER BY page.frame_score DESC, matches.id DESC, matches.score DESC
+            "#,
+        );
 
-        let mut query_builder = sqlx::query(&sql).bind(&escaped_query);
+        let mut query_builder = sqlx::query(&sql)
+            .bind(ranking.text_weight)
+            .bind(ranking.title_weight);
+        for word in title_words {
+            query_builder = query_builder.bind(format!("%{}%", word));
+        }
+        if ranking.recency_weight != 0.0 {
+            query_builder = query_builder
+                .bind(ranking.recency_weight)
+                .bind(ranking.recency_half_life_hours)
+                .bind(ranking.recency_half_life_hours)
+                .bind(ranking.recency_half_life_hours);
+        }
+        query_builder = query_builder.bind(expression);
 
         if let Some(start) = filter.start_time {
             query_builder = query_builder.bind(start);
@@ -320,6 +1111,9 @@ impl DatabaseManager {
         if let Some(device) = &filter.device_name {
             query_builder = query_builder.bind(device);
         }
+        for value in excluded {
+            query_builder = query_builder.bind(value);
+        }
 
         let rows = query_builder
             .bind(pagination.limit)
@@ -331,58 +1125,238 @@ impl DatabaseManager {
             std::collections::HashMap::new();
 
         for row in rows {
-            let frame = FrameRecord {
-                id: row.get("id"),
-                chunk_id: row.get("chunk_id"),
-                timestamp: row.get("timestamp"),
-                monitor_index: row.get("monitor_index"),
-                device_name: row.get("device_name"),
-                file_path: row.get("file_path"),
-                active_window: row.get("active_window"),
-                active_process: row.get("active_process"),
-                browser_url: row.get("browser_url"),
-                width: row.get("width"),
-                height: row.get("height"),
-                offset_index: row.get("offset_index"),
-                focused: row.get("focused"),
-                created_at: row.get::<DateTime<Utc>, _>("created_at"),
-            };
+            let (frame, ocr) = frame_and_ocr_from_row(&row);
 
-            let ocr = OcrTextRecord {
-                id: row.get::<i64, _>("id"),
-                frame_id: row.get("frame_id"),
-                text: row.get("text"),
-                text_json: row.get("text_json"),
-                x: row.get::<i32, _>("x"),
-                y: row.get::<i32, _>("y"),
-                width: row.get::<i32, _>("width"),
-                height: row.get::<i32, _>("height"),
-                confidence: row.get("confidence"),
-                created_at: row.get::<DateTime<Utc>, _>("created_at"),
+            let snippet: String = row.get("snippet");
+            let highlighted: String = row.get("highlighted");
+            let highlight = MatchHighlight {
+                ocr_text_id: ocr.id,
+                snippet: snippet_html(&snippet),
+                offsets: highlight_offsets(&highlighted),
             };
 
-            let rank: f32 = row.get("rank");
-            let relevance_score = -rank; // BM25 rank is negative, invert for score
+            let relevance_score = row.get::<f64, _>("score") as f32;
 
-            results
-                .entry(frame.id)
-                .or_insert_with(|| SearchResult {
-                    frame: frame.clone(),
-                    ocr_matches: Vec::new(),
-                    relevance_score,
-                    tags: Vec::new(),
-                })
-                .ocr_matches
-                .push(ocr);
+            let entry = results.entry(frame.id).or_insert_with(|| SearchResult {
+                frame: frame.clone(),
+                ocr_matches: Vec::new(),
+                relevance_score,
+                tags: Vec::new(),
+                highlights: Vec::new(),
+                duplicates_count: 0,
+            });
+            entry.ocr_matches.push(ocr);
+            entry.highlights.push(highlight);
         }
 
         let mut search_results: Vec<SearchResult> = results.into_values().collect();
-        search_results.sort_by(|a, b| b.relevance_score.partial_cmp(&a.relevance_score).unwrap());
+        search_results.sort_by(|a, b| {
+            b.relevance_score
+                .partial_cmp(&a.relevance_score)
+                .unwrap()
+                .then(b.frame.id.cmp(&a.frame.id))
+        });
 
         Ok(search_results)
     }
 
+    /// Count the frames matching a full-text search
+    ///
+    /// Uses the same query and filters as [`search_ocr_text`](Self::search_ocr_text),
+    /// whose pages hold one result per frame.
+    #[tracing::instrument(skip(self, filter))]
+    pub async fn count_search_results(&self, query: &str, filter: &FrameFilter) -> Result<i64> {
+        self.count_match(&phrase_query(query), filter).await
+    }
+
+    /// Count the frames matching a fuzzy search
+    ///
+    /// The fuzzy counterpart of [`count_search_results`](Self::count_search_results).
+    #[tracing::instrument(skip(self, filter))]
+    pub async fn count_search_results_fuzzy(
+        &self,
+        query: &str,
+        filter: &FrameFilter,
+    ) -> Result<i64> {
+        let expression = self.fuzzy_match_expression(query).await?;
+        self.count_match(&expression, filter).await
+    }
+
+    /// Count the frames with OCR text matching an FTS5 expression
+    async fn count_match(&self, expression: &str, filter: &FrameFilter) -> Result<i64> {
+        let mut sql = String::from(
+            r#"
+            SELECT COUNT(DISTINCT f.id)
+            FROM ocr_text_fts
+            JOIN ocr_text o ON ocr_text_fts.rowid = o.id
+            JOIN frames f ON o.frame_id = f.id
+            WHERE ocr_text_fts MATCH ?
+            "#,
+        );
+        if filter.start_time.is_some() {
+            sql.push_str(" AND f.timestamp >= ?");
+        }
+        if filter.end_time.is_some() {
+            sql.push_str(" AND f.timestamp <= ?");
+        }
+        if filter.app_name.is_some() {
+            sql.push_str(" AND f.active_process = ?");
+        }
+        if filter.device_name.is_some() {
+            sql.push_str(" AND f.device_name = ?");
+        }
+        let excluded = push_exclusions(&mut sql, filter, "f");
+
+        let mut query_builder = sqlx::query_scalar::<_, i64>(&sql).bind(expression);
+        if let Some(start) = filter.start_time {
+            query_builder = query_builder.bind(start);
+        }
+        if let Some(end) = filter.end_time {
+            query_builder = query_builder.bind(end);
+        }
+        if let Some(app) = &filter.app_name {
+            query_builder = query_builder.bind(app);
+        }
+        if let Some(device) = &filter.device_name {
+            query_builder = query_builder.bind(device);
+        }
+        for value in excluded {
+            query_builder = query_builder.bind(value);
+        }
+
+        let count = query_builder.fetch_one(self.pool()).await?;
+        Ok(count)
+    }
+
+    /// Count the frames matching a full-text search by each requested facet
+    ///
+    /// Uses the same query and filters as [`search_ocr_text`](Self::search_ocr_text)
+    /// but counts every matching frame, not just one page of results. App, tag
+    /// and monitor values are ordered by count, days chronologically; each
+    /// facet returns at most `max_values` values.
+    #[tracing::instrument(skip(self, filter))]
+    pub async fn search_facets(
+        &self,
+        query: &str,
+        filter: &FrameFilter,
+        facets: &[SearchFacet],
+        max_values: i64,
+    ) -> Result<SearchFacets> {
+        self.facets_for_match(&phrase_query(query), filter, facets, max_values)
+            .await
+    }
+
+    /// Count the frames matching a fuzzy search by each requested facet
+    ///
+    /// The fuzzy counterpart of [`search_facets`](Self::search_facets), using
+    /// the same matching as [`search_ocr_text_fuzzy`](Self::search_ocr_text_fuzzy).
+    #[tracing::instrument(skip(self, filter))]
+    pub async fn search_facets_fuzzy(
+        &self,
+        query: &str,
+        filter: &FrameFilter,
+        facets: &[SearchFacet],
+        max_values: i64,
+    ) -> Result<SearchFacets> {
+        let expression = self.fuzzy_match_expression(query).await?;
+        self.facets_for_match(&expression, filter, facets, max_values)
+            .await
+    }
+
+    /// Count the frames matching an FTS5 expression by each requested facet
+    async fn facets_for_match(
+        &self,
+        expression: &str,
+        filter: &FrameFilter,
+        facets: &[SearchFacet],
+        max_values: i64,
+    ) -> Result<SearchFacets> {
+        let mut matched = String::from(
+            r#"
+            WITH matched AS (
+                SELECT DISTINCT f.id, f.timestamp, f.active_process, f.monitor_index
+                FROM ocr_text_fts
+                JOIN ocr_text o ON ocr_text_fts.rowid = o.id
+                JOIN frames f ON o.frame_id = f.id
+                WHERE ocr_text_fts MATCH ?
+            "#,
+        );
+        if filter.start_time.is_some() {
+            matched.push_str(" AND f.timestamp >= ?");
+        }
+        if filter.end_time.is_some() {
+            matched.push_str(" AND f.timestamp <= ?");
+        }
+        if filter.app_name.is_some() {
+            matched.push_str(" AND f.active_process = ?");
+        }
+        if filter.device_name.is_some() {
+            matched.push_str(" AND f.device_name = ?");
+        }
+        let excluded = push_exclusions(&mut matched, filter, "f");
+        matched.push_str(")\n");
+
+        let mut result = SearchFacets::default();
+
+        for facet in facets {
+            let select = match facet {
+                SearchFacet::App => {
+                    "SELECT COALESCE(active_process, 'unknown') AS value, COUNT(*) AS count
+                     FROM matched GROUP BY value ORDER BY count DESC, value ASC"
+                }
+                SearchFacet::Day => {
+                    "SELECT substr(timestamp, 1, 10) AS value, COUNT(*) AS count
+                     FROM matched GROUP BY value ORDER BY value ASC"
+                }
+                SearchFacet::Tag => {
+                    "SELECT t.tag_name AS value, COUNT(*) AS count
+                     FROM matched m
+                     JOIN frame_tags ft ON ft.frame_id = m.id
+                     JOIN tags t ON t.id = ft.tag_id
+                     GROUP BY t.id ORDER BY count DESC, value ASC"
+                }
+                SearchFacet::Monitor => {
+                    "SELECT CAST(monitor_index AS TEXT) AS value, COUNT(*) AS count
+                     FROM matched GROUP BY monitor_index ORDER BY count DESC, monitor_index ASC"
+                }
+            };
+            let sql = format!("{}{} LIMIT ?", matched, select);
+
+            let mut query_builder = sqlx::query_as::<_, FacetCount>(&sql).bind(expression);
+            if let Some(start) = filter.start_time {
+                query_builder = query_builder.bind(start);
+            }
+            if let Some(end) = filter.end_time {
+                query_builder = query_builder.bind(end);
+            }
+            if let Some(app) = &filter.app_name {
+                query_builder = query_builder.bind(app);
+            }
+            if let Some(device) = &filter.device_name {
+                query_builder = query_builder.bind(device);
+            }
+            for value in &excluded {
+                query_builder = query_builder.bind(value);
+            }
+
+            let counts = query_builder
+                .bind(max_values)
+                .fetch_all(self.pool())
+                .await?;
+
+            match facet {
+                SearchFacet::App => result.app = Some(counts),
+                SearchFacet::Day => result.day = Some(counts),
+                SearchFacet::Tag => result.tag = Some(counts),
+                SearchFacet::Monitor => result.monitor = Some(counts),
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Search OCR text by exact keywords
+    #[tracing::instrument(skip(self, pagination), fields(limit = pagination.limit))]
     pub async fn search_ocr_keywords(
         &self,
         keywords: Vec<String>,
@@ -424,6 +1398,75 @@ impl DatabaseManager {
         Ok(results)
     }
 
+    /// Scan OCR text records with their frames, newest first
+    ///
+    /// For searches FTS5 cannot answer, such as regular expressions, which
+    /// are matched by the caller. Returns up to `limit` records older than
+    /// `before_id` (the last ID of the previous page) that pass `filter`.
+    #[tracing::instrument(skip(self, filter))]
+    pub async fn scan_ocr_text(
+        &self,
+        filter: &FrameFilter,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<(FrameRecord, OcrTextRecord)>> {
+        let mut sql = String::from(
+            r#"
+            SELECT
+                f.id, f.chunk_id, f.timestamp, f.monitor_index, f.device_name,
+                f.file_path, f.active_window, f.active_process, f.browser_url,
+                f.width, f.height, f.offset_index, f.focused, f.pinned, f.created_at,
+                o.id AS ocr_id, o.frame_id, o.text, o.text_json, o.x, o.y,
+                o.width AS ocr_width, o.height AS ocr_height, o.confidence,
+                o.created_at AS ocr_created_at
+            FROM ocr_text o
+            JOIN frames f ON o.frame_id = f.id
+            WHERE 1=1
+            "#,
+        );
+        if before_id.is_some() {
+            sql.push_str(" AND o.id < ?");
+        }
+        if filter.start_time.is_some() {
+            sql.push_str(" AND f.timestamp >= ?");
+        }
+        if filter.end_time.is_some() {
+            sql.push_str(" AND f.timestamp <= ?");
+        }
+        if filter.app_name.is_some() {
+            sql.push_str(" AND f.active_process = ?");
+        }
+        if filter.device_name.is_some() {
+            sql.push_str(" AND f.device_name = ?");
+        }
+        let excluded = push_exclusions(&mut sql, filter, "f");
+        sql.push_str(" ORDER BY o.id DESC LIMIT ?");
+
+        let mut query_builder = sqlx::query(&sql);
+        if let Some(before_id) = before_id {
+            query_builder = query_builder.bind(before_id);
+        }
+        if let Some(start) = filter.start_time {
+            query_builder = query_builder.bind(start);
+        }
+        if let Some(end) = filter.end_time {
+            query_builder = query_builder.bind(end);
+        }
+        if let Some(app) = &filter.app_name {
+            query_builder = query_builder.bind(app);
+        }
+        if let Some(device) = &filter.device_name {
+            query_builder = query_builder.bind(device);
+        }
+        for value in excluded {
+            query_builder = query_builder.bind(value);
+        }
+
+        let rows = query_builder.bind(limit).fetch_all(self.pool()).await?;
+
+        Ok(rows.iter().map(frame_and_ocr_from_row).collect())
+    }
+
     // ===== Tag Operations =====
 
     /// Create a new tag
@@ -467,6 +1510,15 @@ impl DatabaseManager {
         Ok(tag)
     }
 
+    /// Number of tags
+    pub async fn count_tags(&self) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM tags")
+            .fetch_one(self.pool())
+            .await?;
+
+        Ok(count)
+    }
+
     /// List all tags
     pub async fn list_tags(&self, pagination: Pagination) -> Result<Vec<TagRecord>> {
         let tags = sqlx::query_as::<_, TagRecord>(
@@ -587,6 +1639,31 @@ impl DatabaseManager {
         Ok(result)
     }
 
+    /// Capture times and tag names of the tagged frames in a time range,
+    /// in time order
+    pub async fn get_frame_tags_in_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, String)>> {
+        let tags = sqlx::query_as::<_, (DateTime<Utc>, String)>(
+            r#"
+            SELECT f.timestamp, t.tag_name
+            FROM frame_tags ft
+            JOIN frames f ON f.id = ft.frame_id
+            JOIN tags t ON t.id = ft.tag_id
+            WHERE f.timestamp >= ? AND f.timestamp <= ?
+            ORDER BY f.timestamp ASC, t.tag_name ASC
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(tags)
+    }
+
     /// Get frames by tag
     pub async fn get_frames_by_tag(
         &self,
@@ -597,7 +1674,7 @@ impl DatabaseManager {
             r#"
             SELECT f.id, f.chunk_id, f.timestamp, f.monitor_index, f.device_name,
                    f.file_path, f.active_window, f.active_process, f.browser_url,
-                   f.width, f.height, f.offset_index, f.focused, f.created_at
+                   f.width, f.height, f.offset_index, f.focused, f.pinned, f.created_at
             FROM frames f
             JOIN frame_tags ft ON f.id = ft.frame_id
             WHERE ft.tag_id = ?
@@ -650,7 +1727,8 @@ impl DatabaseManager {
         let settings = sqlx::query_as::<_, SettingsRecord>(
             r#"
             SELECT id, capture_interval, monitors, excluded_apps, is_paused,
-                   retention_days, updated_at
+                   retention_days, audit_retention_days, capture_schedule, search_ranking,
+                   updated_at
             FROM settings
             WHERE id = 1
             "#,
@@ -671,6 +1749,9 @@ impl DatabaseManager {
                 excluded_apps = ?,
                 is_paused = ?,
                 retention_days = ?,
+                audit_retention_days = COALESCE(?, audit_retention_days),
+                capture_schedule = COALESCE(?, capture_schedule),
+                search_ranking = COALESCE(?, search_ranking),
                 updated_at = CURRENT_TIMESTAMP
             WHERE id = 1
             "#,
@@ -680,6 +1761,9 @@ impl DatabaseManager {
         .bind(settings.excluded_apps)
         .bind(settings.is_paused)
         .bind(settings.retention_days)
+        .bind(settings.audit_retention_days)
+        .bind(settings.capture_schedule)
+        .bind(settings.search_ranking)
         .execute(self.pool())
         .await?;
 
@@ -687,205 +1771,3103 @@ impl DatabaseManager {
         self.get_settings().await
     }
 
-    // ===== Statistics and Metadata Operations =====
+    /// Set whether capture is paused, leaving the other settings unchanged
+    pub async fn set_capture_paused(&self, paused: bool) -> Result<()> {
+        sqlx::query(
+            "UPDATE settings SET is_paused = ?, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+        )
+        .bind(paused as i64)
+        .execute(self.pool())
+        .await?;
 
-    /// Get database statistics
-    pub async fn get_statistics(&self) -> Result<DatabaseStatistics> {
-        let frame_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM frames")
+        Ok(())
+    }
+
+    // ===== Report Operations =====
+
+    /// Store a generated report
+    pub async fn insert_report(&self, report: NewReport) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO reports (period, content, model, context_source, start_time, end_time)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(report.period)
+        .bind(report.content)
+        .bind(report.model)
+        .bind(report.context_source)
+        .bind(report.start_time)
+        .bind(report.end_time)
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Get a report by ID
+    pub async fn get_report(&self, id: i64) -> Result<Option<ReportRecord>> {
+        let report = sqlx::query_as::<_, ReportRecord>(
+            r#"
+            SELECT id, period, content, model, context_source, start_time, end_time, generated_at
+            FROM reports
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(report)
+    }
+
+    /// List reports, newest first, optionally filtered by period
+    pub async fn list_reports(
+        &self,
+        period: Option<&str>,
+        pagination: Pagination,
+    ) -> Result<Vec<ReportRecord>> {
+        let mut query = String::from(
+            r#"
+            SELECT id, period, content, model, context_source, start_time, end_time, generated_at
+            FROM reports
+            "#,
+        );
+
+        if period.is_some() {
+            query.push_str(" WHERE period = ?");
+        }
+
+        query.push_str(" ORDER BY generated_at DESC, id DESC LIMIT ? OFFSET ?");
+
+        let mut query_builder = sqlx::query_as::<_, ReportRecord>(&query);
+
+        if let Some(period) = period {
+            query_builder = query_builder.bind(period);
+        }
+
+        let reports = query_builder
+            .bind(pagination.limit)
+            .bind(pagination.offset)
+            .fetch_all(self.pool())
+            .await?;
+
+        Ok(reports)
+    }
+
+    /// Count the reports [`list_reports`](Self::list_reports) pages through
+    pub async fn count_reports(&self, period: Option<&str>) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM reports WHERE ? IS NULL OR period = ?",
+        )
+        .bind(period)
+        .bind(period)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Get the latest report of a period ending between `start` and `end`
+    pub async fn find_report(
+        &self,
+        period: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Option<ReportRecord>> {
+        let report = sqlx::query_as::<_, ReportRecord>(
+            r#"
+            SELECT id, period, content, model, context_source, start_time, end_time, generated_at
+            FROM reports
+            WHERE period = ? AND end_time >= ? AND end_time <= ?
+            ORDER BY end_time DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(period)
+        .bind(start)
+        .bind(end)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(report)
+    }
+
+    /// Delete a report
+    pub async fn delete_report(&self, id: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM reports WHERE id = ?")
+            .bind(id)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // ===== Report Template Operations =====
+
+    /// Create a report template
+    pub async fn create_report_template(&self, template: NewReportTemplate) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO report_templates (name, description, system_prompt)
+            VALUES (?, ?, ?)
+            "#,
+        )
+        .bind(template.name)
+        .bind(template.description)
+        .bind(template.system_prompt)
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Get a report template by ID
+    pub async fn get_report_template(&self, id: i64) -> Result<Option<ReportTemplateRecord>> {
+        let template = sqlx::query_as::<_, ReportTemplateRecord>(
+            r#"
+            SELECT id, name, description, system_prompt, created_at, updated_at
+            FROM report_templates
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(template)
+    }
+
+    /// Get a report template by name
+    pub async fn get_report_template_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<ReportTemplateRecord>> {
+        let template = sqlx::query_as::<_, ReportTemplateRecord>(
+            r#"
+            SELECT id, name, description, system_prompt, created_at, updated_at
+            FROM report_templates
+            WHERE name = ?
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(template)
+    }
+
+    /// List report templates ordered by name
+    pub async fn list_report_templates(
+        &self,
+        pagination: Pagination,
+    ) -> Result<Vec<ReportTemplateRecord>> {
+        let templates = sqlx::query_as::<_, ReportTemplateRecord>(
+            r#"
+            SELECT id, name, description, system_prompt, created_at, updated_at
+            FROM report_templates
+            ORDER BY name ASC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(templates)
+    }
+
+    /// Number of report templates
+    pub async fn count_report_templates(&self) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM report_templates")
+            .fetch_one(self.pool())
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Update a report template
+    pub async fn update_report_template(
+        &self,
+        id: i64,
+        template: NewReportTemplate,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE report_templates
+            SET name = ?, description = ?, system_prompt = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(template.name)
+        .bind(template.description)
+        .bind(template.system_prompt)
+        .bind(id)
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete a report template
+    pub async fn delete_report_template(&self, id: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM report_templates WHERE id = ?")
+            .bind(id)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Get the most captured applications in a time range with their frame counts
+    pub async fn get_top_apps(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<(String, i64)>> {
+        let apps = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT active_process, COUNT(*) AS frame_count
+            FROM frames
+            WHERE timestamp >= ? AND timestamp <= ? AND active_process IS NOT NULL
+            GROUP BY active_process
+            ORDER BY frame_count DESC, active_process ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(apps)
+    }
+
+    // ===== Macro Operations =====
+
+    /// Store a recorded macro
+    pub async fn insert_macro(&self, macro_record: NewMacro) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO macros (name, description, events, event_count, duration_ms)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(macro_record.name)
+        .bind(macro_record.description)
+        .bind(macro_record.events)
+        .bind(macro_record.event_count)
+        .bind(macro_record.duration_ms)
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Get a macro by ID
+    pub async fn get_macro(&self, id: i64) -> Result<Option<MacroRecord>> {
+        let macro_record = sqlx::query_as::<_, MacroRecord>(
+            r#"
+            SELECT id, name, description, events, event_count, duration_ms, created_at
+            FROM macros
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(macro_record)
+    }
+
+    /// List macros, newest first
+    pub async fn list_macros(&self, pagination: Pagination) -> Result<Vec<MacroRecord>> {
+        let macros = sqlx::query_as::<_, MacroRecord>(
+            r#"
+            SELECT id, name, description, events, event_count, duration_ms, created_at
+            FROM macros
+            ORDER BY created_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(macros)
+    }
+
+    /// Number of macros
+    pub async fn count_macros(&self) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM macros")
+            .fetch_one(self.pool())
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Delete a macro
+    pub async fn delete_macro(&self, id: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM macros WHERE id = ?")
+            .bind(id)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // ===== Alert Operations =====
+
+    /// Store a keyword watch alert
+    pub async fn insert_alert(&self, alert: NewAlert) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO alerts (pattern, is_regex, app_filter, created_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(alert.pattern)
+        .bind(alert.is_regex)
+        .bind(alert.app_filter)
+        .bind(Utc::now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Get an alert by ID
+    pub async fn get_alert(&self, id: i64) -> Result<Option<AlertRecord>> {
+        let alert = sqlx::query_as::<_, AlertRecord>(
+            r#"
+            SELECT id, pattern, is_regex, app_filter, created_at
+            FROM alerts
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(alert)
+    }
+
+    /// List all alerts, oldest first
+    pub async fn list_alerts(&self) -> Result<Vec<AlertRecord>> {
+        let alerts = sqlx::query_as::<_, AlertRecord>(
+            r#"
+            SELECT id, pattern, is_regex, app_filter, created_at
+            FROM alerts
+            ORDER BY id ASC
+            "#,
+        )
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(alerts)
+    }
+
+    /// Delete an alert and its hits
+    pub async fn delete_alert(&self, id: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM alerts WHERE id = ?")
+            .bind(id)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Record an alert matching a frame
+    pub async fn insert_alert_hit(&self, hit: NewAlertHit) -> Result<AlertHitRecord> {
+        let created_at = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO alert_hits (alert_id, frame_id, matched_text, context, app_name, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(hit.alert_id)
+        .bind(hit.frame_id)
+        .bind(&hit.matched_text)
+        .bind(&hit.context)
+        .bind(&hit.app_name)
+        .bind(created_at)
+        .execute(self.pool())
+        .await?;
+
+        Ok(AlertHitRecord {
+            id: result.last_insert_rowid(),
+            alert_id: hit.alert_id,
+            frame_id: hit.frame_id,
+            matched_text: hit.matched_text,
+            context: hit.context,
+            app_name: hit.app_name,
+            created_at,
+        })
+    }
+
+    /// List alert hits, newest first, optionally limited to one alert
+    pub async fn list_alert_hits(
+        &self,
+        alert_id: Option<i64>,
+        pagination: Pagination,
+    ) -> Result<Vec<AlertHitRecord>> {
+        let hits = sqlx::query_as::<_, AlertHitRecord>(
+            r#"
+            SELECT id, alert_id, frame_id, matched_text, context, app_name, created_at
+            FROM alert_hits
+            WHERE ? IS NULL OR alert_id = ?
+            ORDER BY created_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(alert_id)
+        .bind(alert_id)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(hits)
+    }
+
+    /// Count the alert hits [`list_alert_hits`](Self::list_alert_hits) pages through
+    pub async fn count_alert_hits(&self, alert_id: Option<i64>) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM alert_hits WHERE ? IS NULL OR alert_id = ?",
+        )
+        .bind(alert_id)
+        .bind(alert_id)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    // ===== Redaction Operations =====
+
+    /// Store a redaction rule
+    pub async fn insert_redaction_rule(&self, rule: NewRedactionRule) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO redaction_rules (pattern, is_regex, blur_images, created_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(rule.pattern)
+        .bind(rule.is_regex)
+        .bind(rule.blur_images)
+        .bind(Utc::now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Get a redaction rule by ID
+    pub async fn get_redaction_rule(&self, id: i64) -> Result<Option<RedactionRuleRecord>> {
+        let rule = sqlx::query_as::<_, RedactionRuleRecord>(
+            r#"
+            SELECT id, pattern, is_regex, blur_images, created_at
+            FROM redaction_rules
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(rule)
+    }
+
+    /// List all redaction rules, oldest first
+    pub async fn list_redaction_rules(&self) -> Result<Vec<RedactionRuleRecord>> {
+        let rules = sqlx::query_as::<_, RedactionRuleRecord>(
+            r#"
+            SELECT id, pattern, is_regex, blur_images, created_at
+            FROM redaction_rules
+            ORDER BY id ASC
+            "#,
+        )
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(rules)
+    }
+
+    /// List one page of redaction rules, oldest first
+    pub async fn list_redaction_rules_page(
+        &self,
+        pagination: Pagination,
+    ) -> Result<Vec<RedactionRuleRecord>> {
+        let rules = sqlx::query_as::<_, RedactionRuleRecord>(
+            r#"
+            SELECT id, pattern, is_regex, blur_images, created_at
+            FROM redaction_rules
+            ORDER BY id ASC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(rules)
+    }
+
+    /// Number of redaction rules
+    pub async fn count_redaction_rules(&self) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM redaction_rules")
+            .fetch_one(self.pool())
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Delete a redaction rule
+    ///
+    /// Text the rule masked stays masked.
+    pub async fn delete_redaction_rule(&self, id: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM redaction_rules WHERE id = ?")
+            .bind(id)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Get OCR text rows with an ID above `after_id`, in ID order
+    ///
+    /// Pass the last ID of a batch to get the next, to go through all
+    /// stored text.
+    pub async fn get_ocr_text_after_id(
+        &self,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<OcrTextRecord>> {
+        let rows = sqlx::query_as::<_, OcrTextRecord>(
+            r#"
+            SELECT id, frame_id, text, text_json, x, y, width, height, confidence, created_at
+            FROM ocr_text
+            WHERE id > ?
+            ORDER BY id ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Replace the text of an OCR row
+    ///
+    /// The full-text index and search terms follow through triggers.
+    pub async fn update_ocr_text(&self, id: i64, text: &str) -> Result<u64> {
+        let result = sqlx::query("UPDATE ocr_text SET text = ? WHERE id = ?")
+            .bind(text)
+            .bind(id)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Get rows of a redactable text column with an ID above `after_id`, in
+    /// ID order, as `(id, text)`
+    ///
+    /// Rows without text are skipped. Pass the last ID of a batch to get the
+    /// next.
+    pub async fn get_redactable_text_after_id(
+        &self,
+        kind: RedactableText,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<(i64, String)>> {
+        let (table, column) = kind.column();
+        let sql = format!(
+            "SELECT id, {column} FROM {table} WHERE id > ? AND {column} IS NOT NULL \
+             ORDER BY id ASC LIMIT ?"
+        );
+        let rows = sqlx::query_as::<_, (i64, String)>(&sql)
+            .bind(after_id)
+            .bind(limit)
+            .fetch_all(self.pool())
+            .await?;
+
+        Ok(rows)
+    }
+
+    /// Replace the text of a row of a redactable text column
+    pub async fn update_redactable_text(
+        &self,
+        kind: RedactableText,
+        id: i64,
+        text: &str,
+    ) -> Result<u64> {
+        let (table, column) = kind.column();
+        let sql = format!("UPDATE {table} SET {column} = ? WHERE id = ?");
+        let result = sqlx::query(&sql)
+            .bind(text)
+            .bind(id)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete the embeddings of frames and queue them to be embedded again
+    ///
+    /// Used after their OCR text changed. Returns the number of frames queued.
+    pub async fn reset_frame_embeddings(&self, frame_ids: &[i64]) -> Result<u64> {
+        let mut tx = self.pool().begin().await?;
+        let now = Utc::now();
+
+        let mut queued = 0;
+        for frame_id in frame_ids {
+            sqlx::query("DELETE FROM embeddings WHERE frame_id = ?")
+                .bind(frame_id)
+                .execute(&mut *tx)
+                .await?;
+            queued += sqlx::query(
+                r#"
+                INSERT INTO embedding_queue (frame_id, status, attempts, last_error, updated_at)
+                SELECT id, 'pending', 0, NULL, ? FROM frames WHERE id = ?
+                ON CONFLICT(frame_id) DO UPDATE SET
+                    status = 'pending', attempts = 0, last_error = NULL, updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(now)
+            .bind(frame_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(queued)
+    }
+
+    /// Point a frame at a new image file, such as a blurred copy
+    ///
+    /// The frame leaves its video chunk or archive, which keep the old
+    /// image until they are deleted.
+    pub async fn replace_frame_image(&self, frame_id: i64, file_path: &str) -> Result<u64> {
+        let mut tx = self.pool().begin().await?;
+
+        sqlx::query(
+            "UPDATE frame_archives SET frame_count = frame_count - 1 \
+             WHERE id = (SELECT archive_id FROM archived_frames WHERE frame_id = ?)",
+        )
+        .bind(frame_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM archived_frames WHERE frame_id = ?")
+            .bind(frame_id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query(
+            "UPDATE frames SET file_path = ?, chunk_id = NULL, offset_index = 0 WHERE id = ?",
+        )
+        .bind(file_path)
+        .bind(frame_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    // ===== Purge Operations =====
+
+    /// IDs of the unpinned frames matching a purge filter, in ID order
+    pub async fn find_purge_frames(&self, filter: &PurgeFilter) -> Result<Vec<i64>> {
+        self.find_filtered_frames(filter, false).await
+    }
+
+    /// IDs of the pinned frames matching a purge filter, which a purge keeps
+    pub async fn find_pinned_purge_frames(&self, filter: &PurgeFilter) -> Result<Vec<i64>> {
+        self.find_filtered_frames(filter, true).await
+    }
+
+    async fn find_filtered_frames(&self, filter: &PurgeFilter, pinned: bool) -> Result<Vec<i64>> {
+        let mut sql = String::from("SELECT f.id FROM frames f WHERE f.pinned = ?");
+        let mut patterns = Vec::new();
+        if let Some(keyword) = &filter.keyword {
+            sql.push_str(
+                " AND (f.active_window LIKE ? ESCAPE '\\' OR EXISTS (\
+                 SELECT 1 FROM ocr_text o WHERE o.frame_id = f.id AND o.text LIKE ? ESCAPE '\\'))",
+            );
+            let pattern = format!("%{}%", escape_like(keyword));
+            patterns.push(pattern.clone());
+            patterns.push(pattern);
+        }
+        if let Some(app) = &filter.app {
+            sql.push_str(" AND f.active_process LIKE ? ESCAPE '\\'");
+            patterns.push(format!("%{}%", escape_like(app)));
+        }
+        if let Some(url) = &filter.url {
+            sql.push_str(" AND f.browser_url LIKE ? ESCAPE '\\'");
+            patterns.push(format!("%{}%", escape_like(url)));
+        }
+        if filter.start_time.is_some() {
+            sql.push_str(" AND f.timestamp >= ?");
+        }
+        if filter.end_time.is_some() {
+            sql.push_str(" AND f.timestamp <= ?");
+        }
+        sql.push_str(" ORDER BY f.id ASC");
+
+        let mut query = sqlx::query_scalar::<_, i64>(&sql).bind(pinned);
+        for pattern in patterns {
+            query = query.bind(pattern);
+        }
+        if let Some(start) = filter.start_time {
+            query = query.bind(start);
+        }
+        if let Some(end) = filter.end_time {
+            query = query.bind(end);
+        }
+
+        Ok(query.fetch_all(self.pool()).await?)
+    }
+
+    /// Delete frames with their OCR text, embeddings, tags and other rows in
+    /// one transaction
+    ///
+    /// Activity summaries of the hours the frames were captured in and AI
+    /// interactions that sent them as context are deleted with them.
+    ///
+    /// Returns what was deleted, with the files to remove: the frames' own
+    /// image files, and video chunks and archives left without frames.
+    /// Chunks and archives still holding other frames keep the images of
+    /// the deleted ones; they are returned as retained.
+    pub async fn purge_frames(&self, frame_ids: &[i64]) -> Result<PurgeOutcome> {
+        let mut outcome = PurgeOutcome::default();
+        let mut tx = self.pool().begin().await?;
+
+        let mut chunk_ids = std::collections::BTreeSet::new();
+        let mut archive_ids = std::collections::BTreeSet::new();
+        for batch in frame_ids.chunks(PURGE_BATCH) {
+            let placeholders = batch.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+
+            let counts_sql = format!(
+                "SELECT (SELECT COUNT(*) FROM ocr_text WHERE frame_id IN ({0})), \
+                        (SELECT COUNT(*) FROM embeddings WHERE frame_id IN ({0}))",
+                placeholders
+            );
+            let mut counts = sqlx::query_as::<_, (i64, i64)>(&counts_sql);
+            for &id in batch.iter().chain(batch) {
+                counts = counts.bind(id);
+            }
+            let (ocr_rows, embeddings) = counts.fetch_one(&mut *tx).await?;
+            outcome.ocr_rows += ocr_rows as u64;
+            outcome.embeddings += embeddings as u64;
+
+            // Archive entries go with the frames, so look them up first
+            let archives_sql = format!(
+                "SELECT DISTINCT archive_id FROM archived_frames WHERE frame_id IN ({})",
+                placeholders
+            );
+            let mut archives = sqlx::query_scalar::<_, i64>(&archives_sql);
+            for &id in batch {
+                archives = archives.bind(id);
+            }
+            let batch_archives = archives.fetch_all(&mut *tx).await?;
+
+            // Summaries of the hours the frames were captured in, and AI
+            // interactions that sent them as context, repeat their text
+            let summaries_sql = format!(
+                r#"
+                DELETE FROM activity_summaries
+                WHERE EXISTS (
+                    SELECT 1 FROM frames f
+                    WHERE f.id IN ({})
+                    AND (f.id = activity_summaries.frame_id
+                        OR (f.timestamp >= activity_summaries.period_start
+                            AND f.timestamp < activity_summaries.period_end))
+                )
+                "#,
+                placeholders
+            );
+            let mut summaries = sqlx::query(&summaries_sql);
+            for &id in batch {
+                summaries = summaries.bind(id);
+            }
+            outcome.summaries += summaries.execute(&mut *tx).await?.rows_affected();
+
+            let interactions_sql = format!(
+                r#"
+                DELETE FROM ai_interactions
+                WHERE EXISTS (
+                    SELECT 1 FROM json_each(ai_interactions.context_chunks)
+                    WHERE json_extract(json_each.value, '$.frame_id') IN ({})
+                )
+                "#,
+                placeholders
+            );
+            let mut interactions = sqlx::query(&interactions_sql);
+            for &id in batch {
+                interactions = interactions.bind(id);
+            }
+            outcome.ai_interactions += interactions.execute(&mut *tx).await?.rows_affected();
+
+            let delete_sql = format!(
+                "DELETE FROM frames WHERE id IN ({}) RETURNING id, chunk_id, file_path",
+                placeholders
+            );
+            let mut delete = sqlx::query_as::<_, (i64, Option<i64>, String)>(&delete_sql);
+            for &id in batch {
+                delete = delete.bind(id);
+            }
+            let deleted = delete.fetch_all(&mut *tx).await?;
+
+            let archive_paths = if batch_archives.is_empty() {
+                Vec::new()
+            } else {
+                let sql = format!(
+                    "SELECT file_path FROM frame_archives WHERE id IN ({})",
+                    batch_archives
+                        .iter()
+                        .map(|_| "?")
+                        .collect::<Vec<_>>()
+                        .join(",")
+                );
+                let mut query = sqlx::query_scalar::<_, String>(&sql);
+                for &id in &batch_archives {
+                    query = query.bind(id);
+                }
+                query.fetch_all(&mut *tx).await?
+            };
+            archive_ids.extend(batch_archives);
+
+            for (id, chunk_id, file_path) in deleted {
+                outcome.frame_ids.push(id);
+                match chunk_id {
+                    Some(chunk_id) => {
+                        chunk_ids.insert(chunk_id);
+                    }
+                    // Compacted and archived frames share their chunk's or
+                    // archive's file
+                    None if !archive_paths.contains(&file_path) => outcome.files.push(file_path),
+                    None => {}
+                }
+            }
+        }
+
+        for chunk_id in chunk_ids {
+            let emptied = sqlx::query_scalar::<_, String>(
+                r#"
+                DELETE FROM video_chunks
+                WHERE id = ? AND NOT EXISTS (SELECT 1 FROM frames WHERE frames.chunk_id = video_chunks.id)
+                RETURNING file_path
+                "#,
+            )
+            .bind(chunk_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            match emptied {
+                Some(path) => outcome.files.push(path),
+                None => outcome.retained_files.extend(
+                    sqlx::query_scalar::<_, String>(
+                        "SELECT file_path FROM video_chunks WHERE id = ?",
+                    )
+                    .bind(chunk_id)
+                    .fetch_optional(&mut *tx)
+                    .await?,
+                ),
+            }
+        }
+        for archive_id in archive_ids {
+            let emptied = sqlx::query_scalar::<_, String>(
+                r#"
+                DELETE FROM frame_archives
+                WHERE id = ? AND NOT EXISTS (
+                    SELECT 1 FROM archived_frames WHERE archived_frames.archive_id = frame_archives.id
+                )
+                RETURNING file_path
+                "#,
+            )
+            .bind(archive_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            match emptied {
+                Some(path) => outcome.files.push(path),
+                None => outcome.retained_files.extend(
+                    sqlx::query_scalar::<_, String>(
+                        "SELECT file_path FROM frame_archives WHERE id = ?",
+                    )
+                    .bind(archive_id)
+                    .fetch_optional(&mut *tx)
+                    .await?,
+                ),
+            }
+        }
+
+        tx.commit().await?;
+        outcome.frame_ids.sort_unstable();
+        Ok(outcome)
+    }
+
+    /// Store the signed report of a purge
+    pub async fn insert_purge_report(&self, report: &str, signature: &str) -> Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO purge_reports (report, signature, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(report)
+        .bind(signature)
+        .bind(Utc::now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// List purge reports, newest first
+    pub async fn list_purge_reports(
+        &self,
+        pagination: Pagination,
+    ) -> Result<Vec<PurgeReportRecord>> {
+        let reports = sqlx::query_as::<_, PurgeReportRecord>(
+            r#"
+            SELECT id, report, signature, created_at
+            FROM purge_reports
+            ORDER BY created_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(reports)
+    }
+
+    /// Number of purge reports
+    pub async fn count_purge_reports(&self) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM purge_reports")
+            .fetch_one(self.pool())
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Key purge reports are signed with, created on first use
+    ///
+    /// A random 256-bit key (hex), kept in the database so reports can be
+    /// verified later on this installation.
+    pub async fn purge_signing_key(&self) -> Result<String> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO metadata (key, value) VALUES ('purge_signing_key', lower(hex(randomblob(32))))",
+        )
+        .execute(self.pool())
+        .await?;
+
+        let key = sqlx::query_scalar::<_, String>(
+            "SELECT value FROM metadata WHERE key = 'purge_signing_key'",
+        )
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(key)
+    }
+
+    // ===== Document Operations =====
+
+    /// Store a receipt or invoice extracted from a frame
+    pub async fn insert_document(&self, document: NewDocument) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO documents (frame_id, doc_type, vendor, document_date, total, currency,
+                                   line_items, text, app_name, captured_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(document.frame_id)
+        .bind(document.doc_type)
+        .bind(document.vendor)
+        .bind(document.document_date)
+        .bind(document.total)
+        .bind(document.currency)
+        .bind(document.line_items)
+        .bind(document.text)
+        .bind(document.app_name)
+        .bind(document.captured_at)
+        .bind(Utc::now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Get a document by ID
+    pub async fn get_document(&self, id: i64) -> Result<Option<DocumentRecord>> {
+        let document = sqlx::query_as::<_, DocumentRecord>(
+            r#"
+            SELECT id, frame_id, doc_type, vendor, document_date, total, currency,
+                   line_items, text, app_name, captured_at, created_at
+            FROM documents
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(document)
+    }
+
+    /// Find a stored document with the same type, vendor, date and total
+    ///
+    /// Documents without a printed date only match those captured at or
+    /// after `undated_since`, so separate undated receipts with equal totals
+    /// are kept apart.
+    pub async fn find_matching_document(
+        &self,
+        document: &NewDocument,
+        undated_since: DateTime<Utc>,
+    ) -> Result<Option<DocumentRecord>> {
+        let found = sqlx::query_as::<_, DocumentRecord>(
+            r#"
+            SELECT id, frame_id, doc_type, vendor, document_date, total, currency,
+                   line_items, text, app_name, captured_at, created_at
+            FROM documents
+            WHERE doc_type = ?
+              AND vendor IS ?
+              AND document_date IS ?
+              AND total IS ?
+              AND (document_date IS NOT NULL OR captured_at >= ?)
+            ORDER BY id ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(&document.doc_type)
+        .bind(&document.vendor)
+        .bind(document.document_date)
+        .bind(document.total)
+        .bind(undated_since)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(found)
+    }
+
+    /// List documents, most recently captured first
+    ///
+    /// `query` matches any part of the vendor, line items or document text.
+    pub async fn list_documents(
+        &self,
+        query: Option<&str>,
+        doc_type: Option<&str>,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        pagination: Pagination,
+    ) -> Result<Vec<DocumentRecord>> {
+        let documents = sqlx::query_as::<_, DocumentRecord>(
+            r#"
+            SELECT id, frame_id, doc_type, vendor, document_date, total, currency,
+                   line_items, text, app_name, captured_at, created_at
+            FROM documents
+            WHERE (? IS NULL OR vendor LIKE '%' || ? || '%'
+                             OR line_items LIKE '%' || ? || '%'
+                             OR text LIKE '%' || ? || '%')
+              AND (? IS NULL OR doc_type = ?)
+              AND (? IS NULL OR captured_at >= ?)
+              AND (? IS NULL OR captured_at <= ?)
+            ORDER BY captured_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(query)
+        .bind(query)
+        .bind(query)
+        .bind(query)
+        .bind(doc_type)
+        .bind(doc_type)
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(documents)
+    }
+
+    /// Count the documents [`list_documents`](Self::list_documents) pages through
+    pub async fn count_documents(
+        &self,
+        query: Option<&str>,
+        doc_type: Option<&str>,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM documents
+            WHERE (? IS NULL OR vendor LIKE '%' || ? || '%'
+                             OR line_items LIKE '%' || ? || '%'
+                             OR text LIKE '%' || ? || '%')
+              AND (? IS NULL OR doc_type = ?)
+              AND (? IS NULL OR captured_at >= ?)
+              AND (? IS NULL OR captured_at <= ?)
+            "#,
+        )
+        .bind(query)
+        .bind(query)
+        .bind(query)
+        .bind(query)
+        .bind(doc_type)
+        .bind(doc_type)
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    // ===== Automation Audit Operations =====
+
+    /// Append an automation audit entry
+    pub async fn insert_audit_entry(&self, entry: NewAuditEntry) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO automation_audit (endpoint, parameters, element, success, error, caller, duration_ms, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&entry.endpoint)
+        .bind(&entry.parameters)
+        .bind(&entry.element)
+        .bind(entry.success)
+        .bind(&entry.error)
+        .bind(&entry.caller)
+        .bind(entry.duration_ms)
+        .bind(Utc::now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// List automation audit entries, newest first
+    ///
+    /// `start`/`end` bound the entry time; `endpoint` and `caller` match substrings.
+    pub async fn list_audit_entries(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        endpoint: Option<&str>,
+        caller: Option<&str>,
+        pagination: Pagination,
+    ) -> Result<Vec<AuditEntryRecord>> {
+        let entries = sqlx::query_as::<_, AuditEntryRecord>(
+            r#"
+            SELECT id, endpoint, parameters, element, success, error, caller, duration_ms, created_at
+            FROM automation_audit
+            WHERE (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+              AND (? IS NULL OR endpoint LIKE '%' || ? || '%')
+              AND (? IS NULL OR caller LIKE '%' || ? || '%')
+            ORDER BY created_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .bind(endpoint)
+        .bind(endpoint)
+        .bind(caller)
+        .bind(caller)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Count the entries [`list_audit_entries`](Self::list_audit_entries) pages through
+    pub async fn count_audit_entries(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        endpoint: Option<&str>,
+        caller: Option<&str>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM automation_audit
+            WHERE (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+              AND (? IS NULL OR endpoint LIKE '%' || ? || '%')
+              AND (? IS NULL OR caller LIKE '%' || ? || '%')
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .bind(endpoint)
+        .bind(endpoint)
+        .bind(caller)
+        .bind(caller)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Delete automation audit entries older than `days_to_keep` days
+    pub async fn cleanup_old_audit_entries(&self, days_to_keep: i32) -> Result<u64> {
+        let cutoff_date = Utc::now() - chrono::Duration::days(days_to_keep as i64);
+
+        let result = sqlx::query("DELETE FROM automation_audit WHERE created_at < ?")
+            .bind(cutoff_date)
+            .execute(self.pool())
+            .await?;
+
+        tracing::info!(
+            "Cleaned up {} old automation audit entries (older than {} days)",
+            result.rows_affected(),
+            days_to_keep
+        );
+
+        Ok(result.rows_affected())
+    }
+
+    // ===== AI Interaction Operations =====
+
+    /// Record a request to an AI provider
+    pub async fn insert_ai_interaction(&self, interaction: NewAiInteraction) -> Result<i64> {
+        let context_chunks = serde_json::to_string(&interaction.context_chunks)
+            .map_err(|e| crate::DatabaseError::InvalidParameter(e.to_string()))?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO ai_interactions (
+                kind, provider_url, model, system_prompt, prompt, context_chunks, response, error,
+                latency_ms, prompt_tokens, completion_tokens, total_tokens, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&interaction.kind)
+        .bind(&interaction.provider_url)
+        .bind(&interaction.model)
+        .bind(&interaction.system_prompt)
+        .bind(&interaction.prompt)
+        .bind(context_chunks)
+        .bind(&interaction.response)
+        .bind(&interaction.error)
+        .bind(interaction.latency_ms)
+        .bind(interaction.prompt_tokens)
+        .bind(interaction.completion_tokens)
+        .bind(interaction.total_tokens)
+        .bind(Utc::now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Get an AI interaction by ID
+    pub async fn get_ai_interaction(&self, id: i64) -> Result<Option<AiInteractionRecord>> {
+        let interaction = sqlx::query_as::<_, AiInteractionRecord>(
+            r#"
+            SELECT id, kind, provider_url, model, system_prompt, prompt, context_chunks, response,
+                   error, latency_ms, prompt_tokens, completion_tokens, total_tokens, created_at
+            FROM ai_interactions
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(interaction)
+    }
+
+    /// List AI interactions, newest first
+    ///
+    /// `start`/`end` bound the interaction time; `kind` and `model` must match exactly.
+    pub async fn list_ai_interactions(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        kind: Option<&str>,
+        model: Option<&str>,
+        pagination: Pagination,
+    ) -> Result<Vec<AiInteractionRecord>> {
+        let interactions = sqlx::query_as::<_, AiInteractionRecord>(
+            r#"
+            SELECT id, kind, provider_url, model, system_prompt, prompt, context_chunks, response,
+                   error, latency_ms, prompt_tokens, completion_tokens, total_tokens, created_at
+            FROM ai_interactions
+            WHERE (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+              AND (? IS NULL OR kind = ?)
+              AND (? IS NULL OR model = ?)
+            ORDER BY created_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .bind(kind)
+        .bind(kind)
+        .bind(model)
+        .bind(model)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(interactions)
+    }
+
+    /// Count the interactions [`list_ai_interactions`](Self::list_ai_interactions)
+    /// pages through
+    pub async fn count_ai_interactions(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        kind: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM ai_interactions
+            WHERE (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+              AND (? IS NULL OR kind = ?)
+              AND (? IS NULL OR model = ?)
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .bind(kind)
+        .bind(kind)
+        .bind(model)
+        .bind(model)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Token usage per model of the AI interactions between `start` and `end`
+    pub async fn get_ai_usage(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        pagination: Pagination,
+    ) -> Result<Vec<AiUsageRecord>> {
+        let usage = sqlx::query_as::<_, AiUsageRecord>(
+            r#"
+            SELECT model,
+                   COUNT(*) AS interactions,
+                   COUNT(error) AS failed,
+                   COALESCE(SUM(prompt_tokens), 0) AS prompt_tokens,
+                   COALESCE(SUM(completion_tokens), 0) AS completion_tokens,
+                   COALESCE(SUM(total_tokens), 0) AS total_tokens
+            FROM ai_interactions
+            WHERE (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+            GROUP BY model
+            ORDER BY total_tokens DESC, model
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(usage)
+    }
+
+    /// Number of models [`get_ai_usage`](Self::get_ai_usage) reports usage of
+    pub async fn count_ai_usage_models(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(DISTINCT model)
+            FROM ai_interactions
+            WHERE (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Delete AI interactions older than `days_to_keep` days
+    pub async fn cleanup_old_ai_interactions(&self, days_to_keep: i32) -> Result<u64> {
+        let cutoff_date = Utc::now() - chrono::Duration::days(days_to_keep as i64);
+
+        let result = sqlx::query("DELETE FROM ai_interactions WHERE created_at < ?")
+            .bind(cutoff_date)
+            .execute(self.pool())
+            .await?;
+
+        tracing::info!(
+            "Cleaned up {} old AI interactions (older than {} days)",
+            result.rows_affected(),
+            days_to_keep
+        );
+
+        Ok(result.rows_affected())
+    }
+
+    // ===== Search History Operations =====
+
+    /// Record an executed search
+    pub async fn insert_search_history(&self, search: NewSearchHistory) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO search_history (query, mode, result_count, created_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(&search.query)
+        .bind(&search.mode)
+        .bind(search.result_count)
+        .bind(Utc::now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Record the result opened after a search
+    ///
+    /// Returns false if the search is not in the history.
+    pub async fn record_search_click(&self, id: i64, frame_id: i64) -> Result<bool> {
+        let result = sqlx::query("UPDATE search_history SET clicked_frame_id = ? WHERE id = ?")
+            .bind(frame_id)
+            .bind(id)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List recorded searches, newest first
+    ///
+    /// `start`/`end` bound the search time; `contains` matches part of the
+    /// query, ignoring case.
+    pub async fn list_search_history(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        contains: Option<&str>,
+        pagination: Pagination,
+    ) -> Result<Vec<SearchHistoryRecord>> {
+        let searches = sqlx::query_as::<_, SearchHistoryRecord>(
+            r#"
+            SELECT id, query, mode, result_count, clicked_frame_id, created_at
+            FROM search_history
+            WHERE (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+              AND (? IS NULL OR instr(lower(query), lower(?)) > 0)
+            ORDER BY created_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .bind(contains)
+        .bind(contains)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(searches)
+    }
+
+    /// Count the searches [`list_search_history`](Self::list_search_history) pages through
+    pub async fn count_search_history(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        contains: Option<&str>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM search_history
+            WHERE (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+              AND (? IS NULL OR instr(lower(query), lower(?)) > 0)
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .bind(contains)
+        .bind(contains)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Aggregate statistics of the searches between `start` and `end`
+    ///
+    /// Queries are counted together regardless of case. At most `limit`
+    /// queries are listed in each ranking.
+    pub async fn get_search_history_stats(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<SearchHistoryStats> {
+        let (total_searches, unique_queries, zero_result_searches, clicked_searches): (
+            i64,
+            i64,
+            i64,
+            i64,
+        ) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*),
+                   COUNT(DISTINCT lower(query)),
+                   COALESCE(SUM(result_count = 0), 0),
+                   COUNT(clicked_frame_id)
+            FROM search_history
+            WHERE (? IS NULL OR created_at >= ?)
+              AND (? IS NULL OR created_at <= ?)
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .fetch_one(self.pool())
+        .await?;
+
+        let ranking = |zero_results: bool| {
+            sqlx::query_as::<_, SearchQueryCount>(
+                r#"
+                SELECT MAX(query) AS query,
+                       COUNT(*) AS searches,
+                       COUNT(clicked_frame_id) AS clicks,
+                       MAX(created_at) AS last_searched_at
+                FROM search_history
+                WHERE (? IS NULL OR created_at >= ?)
+                  AND (? IS NULL OR created_at <= ?)
+                  AND (NOT ? OR result_count = 0)
+                GROUP BY lower(query)
+                ORDER BY searches DESC, last_searched_at DESC
+                LIMIT ?
+                "#,
+            )
+            .bind(start)
+            .bind(start)
+            .bind(end)
+            .bind(end)
+            .bind(zero_results)
+            .bind(limit)
+            .fetch_all(self.pool())
+        };
+        let top_queries = ranking(false).await?;
+        let zero_result_queries = ranking(true).await?;
+
+        Ok(SearchHistoryStats {
+            total_searches,
+            unique_queries,
+            zero_result_searches,
+            clicked_searches,
+            top_queries,
+            zero_result_queries,
+        })
+    }
+
+    /// Delete the searches recorded before `before`, or all of them
+    pub async fn clear_search_history(&self, before: Option<DateTime<Utc>>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM search_history WHERE ? IS NULL OR created_at < ?")
+            .bind(before)
+            .bind(before)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete searches older than `days_to_keep` days
+    pub async fn cleanup_old_search_history(&self, days_to_keep: i32) -> Result<u64> {
+        let cutoff_date = Utc::now() - chrono::Duration::days(days_to_keep as i64);
+        let deleted = self.clear_search_history(Some(cutoff_date)).await?;
+
+        tracing::info!(
+            "Cleaned up {} old searches (older than {} days)",
+            deleted,
+            days_to_keep
+        );
+
+        Ok(deleted)
+    }
+
+    // ===== Metrics History Operations =====
+
+    /// Record the values of a metrics snapshot taken at `recorded_at`
+    pub async fn insert_metric_samples(
+        &self,
+        version: &str,
+        recorded_at: DateTime<Utc>,
+        samples: &[NewMetricSample],
+    ) -> Result<u64> {
+        let mut tx = self.pool().begin().await?;
+
+        for sample in samples {
+            sqlx::query(
+                "INSERT INTO metrics_history (metric, value, version, recorded_at) \
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(&sample.metric)
+            .bind(sample.value)
+            .bind(version)
+            .bind(recorded_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(samples.len() as u64)
+    }
+
+    /// Get the recorded values of `metric` between `start` and `end`, oldest
+    /// first
+    pub async fn get_metric_history(
+        &self,
+        metric: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<MetricSampleRecord>> {
+        let samples = sqlx::query_as::<_, MetricSampleRecord>(
+            r#"
+            SELECT id, metric, value, version, recorded_at
+            FROM metrics_history
+            WHERE metric = ? AND recorded_at >= ? AND recorded_at <= ?
+            ORDER BY recorded_at ASC, id ASC
+            "#,
+        )
+        .bind(metric)
+        .bind(start)
+        .bind(end)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(samples)
+    }
+
+    /// Delete metric values older than `days_to_keep` days
+    pub async fn cleanup_old_metrics(&self, days_to_keep: i32) -> Result<u64> {
+        let cutoff_date = Utc::now() - chrono::Duration::days(days_to_keep as i64);
+        let result = sqlx::query("DELETE FROM metrics_history WHERE recorded_at < ?")
+            .bind(cutoff_date)
+            .execute(self.pool())
+            .await?;
+
+        tracing::info!(
+            "Cleaned up {} old metric values (older than {} days)",
+            result.rows_affected(),
+            days_to_keep
+        );
+
+        Ok(result.rows_affected())
+    }
+
+    // ===== Sync Operations =====
+
+    /// ID of this database among synced machines, created on first use
+    pub async fn machine_id(&self) -> Result<String> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO metadata (key, value) VALUES ('machine_id', lower(hex(randomblob(16))))",
+        )
+        .execute(self.pool())
+        .await?;
+
+        let id =
+            sqlx::query_scalar::<_, String>("SELECT value FROM metadata WHERE key = 'machine_id'")
+                .fetch_one(self.pool())
+                .await?;
+
+        Ok(id)
+    }
+
+    /// Frames with an ID above `after_id`, lowest ID first
+    pub async fn get_frames_after_id(&self, after_id: i64, limit: i64) -> Result<Vec<FrameRecord>> {
+        let frames = sqlx::query_as::<_, FrameRecord>(
+            r#"
+            SELECT id, chunk_id, timestamp, monitor_index, device_name, file_path,
+                   active_window, active_process, browser_url, width, height,
+                   offset_index, focused, pinned, created_at
+            FROM frames
+            WHERE id > ?
+            ORDER BY id ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(frames)
+    }
+
+    /// Origins of the frames among `frame_ids` that were received by sync
+    pub async fn get_frame_origins(
+        &self,
+        frame_ids: &[i64],
+    ) -> Result<std::collections::HashMap<i64, SyncOrigin>> {
+        let mut origins = std::collections::HashMap::new();
+        if frame_ids.is_empty() {
+            return Ok(origins);
+        }
+
+        let placeholders = vec!["?"; frame_ids.len()].join(", ");
+        let query = format!(
+            "SELECT frame_id, origin_machine, origin_frame_id FROM sync_frames WHERE frame_id IN ({})",
+            placeholders
+        );
+        let mut q = sqlx::query(&query);
+        for id in frame_ids {
+            q = q.bind(id);
+        }
+        for row in q.fetch_all(self.pool()).await? {
+            origins.insert(
+                row.get::<i64, _>("frame_id"),
+                SyncOrigin {
+                    machine_id: row.get("origin_machine"),
+                    frame_id: row.get("origin_frame_id"),
+                },
+            );
+        }
+
+        Ok(origins)
+    }
+
+    /// Store a frame received from another machine with its text and tags
+    ///
+    /// Returns the new frame's ID, or `None` if the frame was received before.
+    pub async fn insert_synced_frame(&self, synced: NewSyncedFrame) -> Result<Option<i64>> {
+        let mut tx = self.pool().begin().await?;
+
+        let existing = sqlx::query_scalar::<_, i64>(
+            "SELECT frame_id FROM sync_frames WHERE origin_machine = ? AND origin_frame_id = ?",
+        )
+        .bind(&synced.origin.machine_id)
+        .bind(synced.origin.frame_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if existing.is_some() {
+            return Ok(None);
+        }
+
+        let frame = synced.frame;
+        let frame_id = sqlx::query(
+            r#"
+            INSERT INTO frames (
+                chunk_id, timestamp, monitor_index, device_name, file_path,
+                active_window, active_process, browser_url, width, height,
+                offset_index, focused
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(frame.chunk_id)
+        .bind(frame.timestamp)
+        .bind(frame.monitor_index)
+        .bind(frame.device_name)
+        .bind(frame.file_path)
+        .bind(frame.active_window)
+        .bind(frame.active_process)
+        .bind(frame.browser_url)
+        .bind(frame.width)
+        .bind(frame.height)
+        .bind(frame.offset_index)
+        .bind(frame.focused)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        for ocr in synced.ocr {
+            sqlx::query(
+                r#"
+                INSERT INTO ocr_text (frame_id, text, text_json, x, y, width, height, confidence)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(frame_id)
+            .bind(ocr.text)
+            .bind(ocr.text_json)
+            .bind(ocr.x)
+            .bind(ocr.y)
+            .bind(ocr.width)
+            .bind(ocr.height)
+            .bind(ocr.confidence)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for tag in &synced.tags {
+            sqlx::query("INSERT OR IGNORE INTO tags (tag_name) VALUES (?)")
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT OR IGNORE INTO frame_tags (frame_id, tag_id) \
+                 SELECT ?, id FROM tags WHERE tag_name = ?",
+            )
+            .bind(frame_id)
+            .bind(tag)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            "INSERT INTO sync_frames (frame_id, origin_machine, origin_frame_id) VALUES (?, ?, ?)",
+        )
+        .bind(frame_id)
+        .bind(&synced.origin.machine_id)
+        .bind(synced.origin.frame_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(frame_id))
+    }
+
+    /// Record that frames up to `cursor` were received from `machine_id`
+    ///
+    /// The cursor never moves back, so applying an older batch again is
+    /// harmless.
+    pub async fn record_sync(&self, machine_id: &str, cursor: i64, frames: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sync_peers (machine_id, cursor, frames_received, last_sync_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(machine_id) DO UPDATE SET
+                cursor = MAX(cursor, excluded.cursor),
+                frames_received = frames_received + excluded.frames_received,
+                last_sync_at = excluded.last_sync_at
+            "#,
+        )
+        .bind(machine_id)
+        .bind(cursor)
+        .bind(frames)
+        .bind(Utc::now())
+        .execute(self.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Machines changes were received from, most recent first
+    pub async fn list_sync_peers(&self) -> Result<Vec<SyncPeerRecord>> {
+        let peers = sqlx::query_as::<_, SyncPeerRecord>(
+            r#"
+            SELECT machine_id, cursor, frames_received, last_sync_at
+            FROM sync_peers
+            ORDER BY last_sync_at DESC
+            "#,
+        )
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(peers)
+    }
+
+    // ===== Search Suggestion Operations =====
+
+    /// Words of OCR text that start with `prefix`, with the number of OCR
+    /// text records containing each, most frequent first
+    ///
+    /// Words are lowercase. Only words found in at least `min_count` records
+    /// are returned, which leaves out most OCR misreadings.
+    pub async fn suggest_terms(
+        &self,
+        prefix: &str,
+        min_count: i64,
+        limit: i64,
+    ) -> Result<Vec<(String, i64)>> {
+        let prefix = prefix.to_lowercase();
+        let terms = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT term, doc
+            FROM ocr_terms_vocab
+            WHERE term >= ? AND term < ? AND term != ? AND doc >= ?
+            ORDER BY doc DESC, term ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(&prefix)
+        .bind(format!("{}{}", prefix, char::MAX))
+        .bind(&prefix)
+        .bind(min_count)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(terms)
+    }
+
+    /// Past queries that start with `prefix` and found something, with how
+    /// often each was searched for, most frequent first
+    ///
+    /// Queries are matched and counted together regardless of case.
+    pub async fn suggest_past_queries(
+        &self,
+        prefix: &str,
+        limit: i64,
+    ) -> Result<Vec<(String, i64)>> {
+        let queries = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT MAX(query), COUNT(*) AS searches
+            FROM search_history
+            WHERE query LIKE ? ESCAPE '\' AND result_count > 0
+            GROUP BY lower(query)
+            ORDER BY searches DESC, MAX(created_at) DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(format!("{}%", escape_like(prefix)))
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(queries)
+    }
+
+    /// Applications whose name starts with `prefix`, with their frame
+    /// counts, most captured first
+    pub async fn suggest_apps(&self, prefix: &str, limit: i64) -> Result<Vec<(String, i64)>> {
+        // Walk the distinct names through the index instead of every frame
+        let apps = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            WITH RECURSIVE apps(name) AS (
+                SELECT MIN(active_process) FROM frames
+                UNION ALL
+                SELECT (SELECT MIN(active_process) FROM frames WHERE active_process > name)
+                FROM apps
+                WHERE name IS NOT NULL
+            )
+            SELECT name, (SELECT COUNT(*) FROM frames WHERE active_process = name) AS frame_count
+            FROM apps
+            WHERE name LIKE ? ESCAPE '\'
+            ORDER BY frame_count DESC, name ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(format!("{}%", escape_like(prefix)))
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(apps)
+    }
+
+    /// Tags whose name starts with `prefix`, with the number of frames
+    /// carrying each, most used first
+    pub async fn suggest_tags(&self, prefix: &str, limit: i64) -> Result<Vec<(String, i64)>> {
+        let tags = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT t.tag_name, COUNT(ft.frame_id) AS frame_count
+            FROM tags t
+            LEFT JOIN frame_tags ft ON ft.tag_id = t.id
+            WHERE t.tag_name LIKE ? ESCAPE '\'
+            GROUP BY t.id
+            ORDER BY frame_count DESC, t.tag_name ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(format!("{}%", escape_like(prefix)))
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(tags)
+    }
+
+    // ===== Activity Summary Operations =====
+
+    /// Store the summary of an hour, replacing an earlier one
+    pub async fn upsert_activity_summary(&self, summary: NewActivitySummary) -> Result<i64> {
+        let id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO activity_summaries (
+                period_start, period_end, summary, method, model, frame_count, frame_id, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(period_start) DO UPDATE SET
+                period_end = excluded.period_end,
+                summary = excluded.summary,
+                method = excluded.method,
+                model = excluded.model,
+                frame_count = excluded.frame_count,
+                frame_id = excluded.frame_id,
+                created_at = excluded.created_at
+            RETURNING id
+            "#,
+        )
+        .bind(summary.period_start)
+        .bind(summary.period_end)
+        .bind(&summary.summary)
+        .bind(&summary.method)
+        .bind(&summary.model)
+        .bind(summary.frame_count)
+        .bind(summary.frame_id)
+        .bind(Utc::now())
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(id)
+    }
+
+    /// List activity summaries in time order
+    ///
+    /// `start`/`end` bound the summarized periods, which must lie within them.
+    pub async fn list_activity_summaries(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        pagination: Pagination,
+    ) -> Result<Vec<ActivitySummaryRecord>> {
+        let summaries = sqlx::query_as::<_, ActivitySummaryRecord>(
+            r#"
+            SELECT id, period_start, period_end, summary, method, model, frame_count, frame_id,
+                   created_at
+            FROM activity_summaries
+            WHERE (? IS NULL OR period_start >= ?)
+              AND (? IS NULL OR period_end <= ?)
+            ORDER BY period_start ASC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(summaries)
+    }
+
+    /// Count the summaries [`list_activity_summaries`](Self::list_activity_summaries)
+    /// pages through
+    pub async fn count_activity_summaries(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM activity_summaries
+            WHERE (? IS NULL OR period_start >= ?)
+              AND (? IS NULL OR period_end <= ?)
+            "#,
+        )
+        .bind(start)
+        .bind(start)
+        .bind(end)
+        .bind(end)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Start times of the summarized periods starting between `start` and `end`
+    pub async fn get_summarized_periods(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DateTime<Utc>>> {
+        let periods = sqlx::query_scalar::<_, DateTime<Utc>>(
+            r#"
+            SELECT period_start
+            FROM activity_summaries
+            WHERE period_start >= ? AND period_start <= ?
+            ORDER BY period_start ASC
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(periods)
+    }
+
+    /// Number of clock hours between `start` and `end` with captured frames
+    pub async fn count_active_hours(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(DISTINCT strftime('%Y-%m-%d %H', timestamp))
+            FROM frames
+            WHERE timestamp >= ? AND timestamp <= ?
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Delete activity summaries of periods older than `days_to_keep` days
+    pub async fn cleanup_old_activity_summaries(&self, days_to_keep: i32) -> Result<u64> {
+        let cutoff_date = Utc::now() - chrono::Duration::days(days_to_keep as i64);
+
+        let result = sqlx::query("DELETE FROM activity_summaries WHERE period_end < ?")
+            .bind(cutoff_date)
+            .execute(self.pool())
+            .await?;
+
+        tracing::info!(
+            "Cleaned up {} old activity summaries (older than {} days)",
+            result.rows_affected(),
+            days_to_keep
+        );
+
+        Ok(result.rows_affected())
+    }
+
+    // ===== Statistics and Metadata Operations =====
+
+    /// Get database statistics
+    pub async fn get_statistics(&self) -> Result<DatabaseStatistics> {
+        let frame_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM frames")
+            .fetch_one(self.pool())
+            .await?;
+
+        let ocr_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM ocr_text")
+            .fetch_one(self.pool())
+            .await?;
+
+        let tag_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM tags")
+            .fetch_one(self.pool())
+            .await?;
+
+        let oldest_frame =
+            sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT MIN(timestamp) FROM frames")
+                .fetch_one(self.pool())
+                .await?;
+
+        let newest_frame =
+            sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT MAX(timestamp) FROM frames")
+                .fetch_one(self.pool())
+                .await?;
+
+        Ok(DatabaseStatistics {
+            frame_count,
+            ocr_count,
+            tag_count,
+            oldest_frame,
+            newest_frame,
+        })
+    }
+
+    /// Store metadata value
+    pub async fn set_metadata(&self, key: &str, value: &str) -> Result<()> {
+        sqlx::query("INSERT OR REPLACE INTO metadata (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value)
+            .execute(self.pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get metadata value
+    pub async fn get_metadata(&self, key: &str) -> Result<Option<String>> {
+        let value = sqlx::query_scalar::<_, String>("SELECT value FROM metadata WHERE key = ?")
+            .bind(key)
+            .fetch_optional(self.pool())
+            .await?;
+
+        Ok(value)
+    }
+
+    /// Clean up old data
+    #[tracing::instrument(skip(self))]
+    pub async fn cleanup_old_data(&self, days_to_keep: i32) -> Result<u64> {
+        let cutoff_date = Utc::now() - chrono::Duration::days(days_to_keep as i64);
+
+        let deleted = self.delete_old_frames(cutoff_date).await?;
+
+        tracing::info!(
+            "Cleaned up {} old frames (older than {} days)",
+            deleted,
+            days_to_keep
+        );
+
+        Ok(deleted)
+    }
+
+    /// Rebuild the database file to reclaim space left by deleted rows
+    ///
+    /// Checkpoints the write-ahead log afterwards so the WAL file is truncated
+    /// too. Blocks writers until it finishes, which can take a while on large
+    /// databases.
+    #[tracing::instrument(skip(self))]
+    pub async fn vacuum(&self) -> Result<()> {
+        sqlx::query("VACUUM").execute(self.pool()).await?;
+        self.checkpoint().await?;
+
+        tracing::info!("Vacuumed database");
+        Ok(())
+    }
+
+    /// Write a consistent copy of the database to `path`
+    ///
+    /// Uses `VACUUM INTO`, so the copy is compacted and readers and writers
+    /// can keep using the database meanwhile. Fails when `path` exists.
+    #[tracing::instrument(skip(self))]
+    pub async fn copy_to(&self, path: &str) -> Result<()> {
+        sqlx::query("VACUUM INTO ?")
+            .bind(path)
+            .execute(self.pool())
+            .await?;
+
+        tracing::info!("Copied database to {}", path);
+        Ok(())
+    }
+
+    /// Run SQLite's integrity check
+    ///
+    /// Returns the problems found, or nothing when the database is intact.
+    /// Reads the whole database, so it is only run after an unclean shutdown.
+    #[tracing::instrument(skip(self))]
+    pub async fn integrity_check(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query_scalar::<_, String>("PRAGMA integrity_check")
+            .fetch_all(self.pool())
+            .await?;
+
+        Ok(rows.into_iter().filter(|row| row != "ok").collect())
+    }
+
+    /// Copy the write-ahead log into the database file and truncate it
+    ///
+    /// Run at shutdown so the database file is complete on its own, e.g. for
+    /// backups taken while the application is stopped.
+    #[tracing::instrument(skip(self))]
+    pub async fn checkpoint(&self) -> Result<()> {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(self.pool())
+            .await?;
+        Ok(())
+    }
+
+    // ===== Embedding Operations (RAG) =====
+
+    /// Insert an embedding record (stores metadata and vector blob)
+    pub async fn insert_embedding(&self, embedding: NewEmbedding) -> Result<i64> {
+        // Convert f32 vector to bytes (BLOB)
+        let embedding_blob: Vec<u8> = embedding
+            .embedding
+            .iter()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO embeddings (frame_id, chunk_text, chunk_index, embedding_dim, embedding)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(embedding.frame_id)
+        .bind(&embedding.chunk_text)
+        .bind(embedding.chunk_index)
+        .bind(embedding.embedding.len() as i32)
+        .bind(embedding_blob)
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Get embeddings for a frame
+    pub async fn get_embeddings_for_frame(&self, frame_id: i64) -> Result<Vec<EmbeddingRecord>> {
+        let embeddings = sqlx::query_as::<_, EmbeddingRecord>(
+            r#"
+            SELECT id, frame_id, chunk_text, chunk_index, embedding_dim, created_at
+            FROM embeddings
+            WHERE frame_id = ?
+            ORDER BY chunk_index ASC
+            "#,
+        )
+        .bind(frame_id)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(embeddings)
+    }
+
+    /// Get frames that don't have embeddings yet (for background processing)
+    pub async fn get_frames_without_embeddings(&self, limit: i64) -> Result<Vec<FrameRecord>> {
+        let frames = sqlx::query_as::<_, FrameRecord>(
+            r#"
+            SELECT f.id, f.chunk_id, f.timestamp, f.monitor_index, f.device_name,
+                   f.file_path, f.active_window, f.active_process, f.browser_url,
+                   f.width, f.height, f.offset_index, f.focused, f.pinned, f.created_at
+            FROM frames f
+            LEFT JOIN embeddings e ON f.id = e.frame_id
+            WHERE e.id IS NULL
+            ORDER BY f.id ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(frames)
+    }
+
+    /// Get embedding status statistics
+    pub async fn get_embedding_status(&self) -> Result<EmbeddingStatus> {
+        let total_frames = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM frames")
+            .fetch_one(self.pool())
+            .await?;
+
+        let frames_with_embeddings =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(DISTINCT frame_id) FROM embeddings")
+                .fetch_one(self.pool())
+                .await?;
+
+        let enabled = self
+            .get_metadata("embeddings_enabled")
+            .await?
+            .map(|v| v == "true")
+            .unwrap_or(false);
+
+        let model = self
+            .get_metadata("embeddings_model")
+            .await?
+            .unwrap_or_else(|| "all-MiniLM-L6-v2".to_string());
+
+        let last_processed_frame_id = self
+            .get_metadata("embeddings_last_processed_frame_id")
+            .await?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        let coverage_percent = if total_frames > 0 {
+            (frames_with_embeddings as f32 / total_frames as f32) * 100.0
+        } else {
+            0.0
+        };
+
+        Ok(EmbeddingStatus {
+            enabled,
+            model,
+            total_frames,
+            frames_with_embeddings,
+            coverage_percent,
+            last_processed_frame_id,
+        })
+    }
+
+    /// Delete embeddings for a frame
+    pub async fn delete_embeddings_for_frame(&self, frame_id: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM embeddings WHERE frame_id = ?")
+            .bind(frame_id)
+            .execute(self.pool())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Get total embedding count
+    pub async fn count_embeddings(&self) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM embeddings")
             .fetch_one(self.pool())
             .await?;
 
-        let ocr_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM ocr_text")
-            .fetch_one(self.pool())
-            .await?;
+        Ok(count)
+    }
+
+    /// Get the size of the stored embeddings
+    ///
+    /// `chunks_since` counts chunks embedded after `since`, for throughput
+    /// estimates. `created_at` is stored in SQLite's own format, so `since` is
+    /// converted to it before comparing.
+    pub async fn get_embedding_index_stats(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<EmbeddingIndexStats> {
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS chunks,
+                   COALESCE(SUM(LENGTH(embedding)), 0) AS vector_bytes,
+                   COALESCE(SUM(LENGTH(CAST(chunk_text AS BLOB))), 0) AS text_bytes,
+                   COUNT(CASE WHEN created_at >= datetime(?) THEN 1 END) AS chunks_since,
+                   MAX(created_at) AS last_embedded_at
+            FROM embeddings
+            "#,
+        )
+        .bind(since)
+        .fetch_one(self.pool())
+        .await?;
+
+        Ok(EmbeddingIndexStats {
+            chunks: row.get("chunks"),
+            vector_bytes: row.get("vector_bytes"),
+            text_bytes: row.get("text_bytes"),
+            chunks_since: row.get("chunks_since"),
+            last_embedded_at: row.get("last_embedded_at"),
+        })
+    }
+
+    // ===== Embedding Queue Operations =====
+
+    /// Queue frames without embeddings for a backfill
+    ///
+    /// Frames in the optional time range that have no embeddings are added as
+    /// pending. Frames that previously failed (or completed but have since lost
+    /// their embeddings) are queued again; pending, processing and skipped
+    /// frames are left alone. Returns the number of frames queued.
+    pub async fn enqueue_embedding_backfill(
+        &self,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO embedding_queue (frame_id, status, attempts, last_error, updated_at)
+            SELECT f.id, 'pending', 0, NULL, ?
+            FROM frames f
+            WHERE NOT EXISTS (SELECT 1 FROM embeddings e WHERE e.frame_id = f.id)
+              AND (? IS NULL OR f.timestamp >= ?)
+              AND (? IS NULL OR f.timestamp <= ?)
+            ON CONFLICT(frame_id) DO UPDATE SET
+                status = 'pending', attempts = 0, last_error = NULL, updated_at = excluded.updated_at
+            WHERE embedding_queue.status IN ('failed', 'completed')
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(start_time)
+        .bind(start_time)
+        .bind(end_time)
+        .bind(end_time)
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Queue the oldest frames that have neither embeddings nor a queue entry
+    ///
+    /// Used by the background worker to pick up newly captured frames.
+    pub async fn enqueue_new_embedding_frames(&self, limit: i64) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO embedding_queue (frame_id, status, attempts, last_error, updated_at)
+            SELECT f.id, 'pending', 0, NULL, ?
+            FROM frames f
+            WHERE NOT EXISTS (SELECT 1 FROM embeddings e WHERE e.frame_id = f.id)
+              AND NOT EXISTS (SELECT 1 FROM embedding_queue q WHERE q.frame_id = f.id)
+            ORDER BY f.id ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(limit)
+        .execute(self.pool())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Claim up to `limit` pending frames for processing
+    ///
+    /// Claimed frames move to `processing` in a single statement, so concurrent
+    /// workers never receive the same frame. Frames stuck in `processing` since
+    /// before `stale_before` (e.g. after a crash) are claimed again.
+    pub async fn claim_embedding_frames(
+        &self,
+        limit: i64,
+        stale_before: DateTime<Utc>,
+    ) -> Result<Vec<i64>> {
+        let mut frame_ids = sqlx::query_scalar::<_, i64>(
+            r#"
+            UPDATE embedding_queue
+            SET status = 'processing', updated_at = ?
+            WHERE frame_id IN (
+                SELECT frame_id FROM embedding_queue
+                WHERE status = 'pending' OR (status = 'processing' AND updated_at < ?)
+                ORDER BY frame_id ASC
+                LIMIT ?
+            )
+            RETURNING frame_id
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(stale_before)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await?;
+
+        // RETURNING does not guarantee row order
+        frame_ids.sort_unstable();
+        Ok(frame_ids)
+    }
+
+    /// Record the outcome of processing a claimed frame
+    ///
+    /// A failed frame goes back to `pending` until it has been attempted
+    /// `max_attempts` times, after which it stays `failed`.
+    pub async fn record_embedding_outcome(
+        &self,
+        frame_id: i64,
+        outcome: &EmbeddingOutcome,
+        max_attempts: i64,
+    ) -> Result<()> {
+        let (status, error) = match outcome {
+            EmbeddingOutcome::Completed => ("completed", None),
+            EmbeddingOutcome::Skipped => ("skipped", None),
+            EmbeddingOutcome::Failed(e) => ("failed", Some(e.as_str())),
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE embedding_queue
+            SET attempts = attempts + 1,
+                status = CASE
+                    WHEN ?1 = 'failed' AND attempts + 1 < ?2 THEN 'pending'
+                    ELSE ?1
+                END,
+                last_error = ?3,
+                updated_at = ?4
+            WHERE frame_id = ?5
+            "#,
+        )
+        .bind(status)
+        .bind(max_attempts)
+        .bind(error)
+        .bind(Utc::now())
+        .bind(frame_id)
+        .execute(self.pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Count queued frames by status
+    ///
+    /// `finished_since` counts frames that reached completed, skipped or failed
+    /// after `since`, for throughput estimates.
+    pub async fn get_embedding_queue_stats(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<EmbeddingQueueStats> {
+        let rows =
+            sqlx::query("SELECT status, COUNT(*) AS count FROM embedding_queue GROUP BY status")
+                .fetch_all(self.pool())
+                .await?;
+
+        let mut stats = EmbeddingQueueStats::default();
+        for row in rows {
+            let count: i64 = row.get("count");
+            match row.get::<String, _>("status").as_str() {
+                "pending" => stats.pending = count,
+                "processing" => stats.processing = count,
+                "completed" => stats.completed = count,
+                "skipped" => stats.skipped = count,
+                "failed" => stats.failed = count,
+                _ => {}
+            }
+        }
+
+        stats.finished_since = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM embedding_queue
+            WHERE status IN ('completed', 'skipped', 'failed') AND updated_at >= ?
+            "#,
+        )
+        .bind(since)
+        .fetch_one(self.pool())
+        .await?;
 
-        let tag_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM tags")
-            .fetch_one(self.pool())
-            .await?;
+        Ok(stats)
+    }
 
-        let oldest_frame =
-            sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT MIN(timestamp) FROM frames")
-                .fetch_one(self.pool())
-                .await?;
+    // ===== Job Queue Operations =====
 
-        let newest_frame =
-            sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT MAX(timestamp) FROM frames")
-                .fetch_one(self.pool())
-                .await?;
+    /// Queue a new job, runnable immediately
+    pub async fn insert_job(&self, job: NewJob) -> Result<i64> {
+        let now = Utc::now();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO jobs (job_type, payload, state, max_attempts, run_after, created_at)
+            VALUES (?, ?, 'queued', ?, ?, ?)
+            "#,
+        )
+        .bind(&job.job_type)
+        .bind(&job.payload)
+        .bind(job.max_attempts)
+        .bind(now)
+        .bind(now)
+        .execute(self.pool())
+        .await?;
 
-        Ok(DatabaseStatistics {
-            frame_count,
-            ocr_count,
-            tag_count,
-            oldest_frame,
-            newest_frame,
-        })
+        Ok(result.last_insert_rowid())
     }
 
-    /// Store metadata value
-    pub async fn set_metadata(&self, key: &str, value: &str) -> Result<()> {
-        sqlx::query("INSERT OR REPLACE INTO metadata (key, value) VALUES (?, ?)")
-            .bind(key)
-            .bind(value)
-            .execute(self.pool())
-            .await?;
+    /// Get a job by ID
+    pub async fn get_job(&self, id: i64) -> Result<Option<JobRecord>> {
+        let job = sqlx::query_as::<_, JobRecord>(
+            r#"
+            SELECT id, job_type, payload, state, attempts, max_attempts, last_error, result,
+                   run_after, created_at, started_at, finished_at
+            FROM jobs
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool())
+        .await?;
 
-        Ok(())
+        Ok(job)
     }
 
-    /// Get metadata value
-    pub async fn get_metadata(&self, key: &str) -> Result<Option<String>> {
-        let value = sqlx::query_scalar::<_, String>("SELECT value FROM metadata WHERE key = ?")
-            .bind(key)
-            .fetch_optional(self.pool())
-            .await?;
+    /// List jobs, newest first, optionally filtered by state and type
+    pub async fn list_jobs(
+        &self,
+        state: Option<&str>,
+        job_type: Option<&str>,
+        pagination: Pagination,
+    ) -> Result<Vec<JobRecord>> {
+        let jobs = sqlx::query_as::<_, JobRecord>(
+            r#"
+            SELECT id, job_type, payload, state, attempts, max_attempts, last_error, result,
+                   run_after, created_at, started_at, finished_at
+            FROM jobs
+            WHERE (? IS NULL OR state = ?)
+              AND (? IS NULL OR job_type = ?)
+            ORDER BY id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(state)
+        .bind(state)
+        .bind(job_type)
+        .bind(job_type)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
+        .fetch_all(self.pool())
+        .await?;
 
-        Ok(value)
+        Ok(jobs)
     }
 
-    /// Clean up old data
-    pub async fn cleanup_old_data(&self, days_to_keep: i32) -> Result<u64> {
-        let cutoff_date = Utc::now() - chrono::Duration::days(days_to_keep as i64);
+    /// Count the jobs [`list_jobs`](Self::list_jobs) pages through
+    pub async fn count_jobs(&self, state: Option<&str>, job_type: Option<&str>) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM jobs
+            WHERE (? IS NULL OR state = ?)
+              AND (? IS NULL OR job_type = ?)
+            "#,
+        )
+        .bind(state)
+        .bind(state)
+        .bind(job_type)
+        .bind(job_type)
+        .fetch_one(self.pool())
+        .await?;
 
-        let deleted = self.delete_old_frames(cutoff_date).await?;
+        Ok(count)
+    }
 
-        tracing::info!(
-            "Cleaned up {} old frames (older than {} days)",
-            deleted,
-            days_to_keep
-        );
+    /// Get the oldest queued or running job of the given type, if any
+    pub async fn find_active_job(&self, job_type: &str) -> Result<Option<JobRecord>> {
+        let job = sqlx::query_as::<_, JobRecord>(
+            r#"
+            SELECT id, job_type, payload, state, attempts, max_attempts, last_error, result,
+                   run_after, created_at, started_at, finished_at
+            FROM jobs
+            WHERE job_type = ? AND state IN ('queued', 'running')
+            ORDER BY id ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(job_type)
+        .fetch_optional(self.pool())
+        .await?;
 
-        Ok(deleted)
+        Ok(job)
     }
 
-    // ===== Embedding Operations (RAG) =====
+    /// Claim the oldest runnable job of one of the given types
+    ///
+    /// The job moves to `running` in a single statement, so concurrent
+    /// workers never receive the same job.
+    pub async fn claim_next_job(&self, job_types: &[&str]) -> Result<Option<JobRecord>> {
+        if job_types.is_empty() {
+            return Ok(None);
+        }
 
-    /// Insert an embedding record (stores metadata and vector blob)
-    pub async fn insert_embedding(&self, embedding: NewEmbedding) -> Result<i64> {
-        // Convert f32 vector to bytes (BLOB)
-        let embedding_blob: Vec<u8> = embedding.embedding
-            .iter()
-            .flat_map(|f| f.to_le_bytes())
-            .collect();
+        let placeholders = vec!["?"; job_types.len()].join(", ");
+        let sql = format!(
+            r#"
+            UPDATE jobs
+            SET state = 'running', attempts = attempts + 1, started_at = ?, finished_at = NULL
+            WHERE id = (
+                SELECT id FROM jobs
+                WHERE state = 'queued' AND run_after <= ? AND job_type IN ({})
+                ORDER BY run_after ASC, id ASC
+                LIMIT 1
+            )
+            RETURNING id, job_type, payload, state, attempts, max_attempts, last_error, result,
+                      run_after, created_at, started_at, finished_at
+            "#,
+            placeholders
+        );
 
-        let result = sqlx::query(
+        let now = Utc::now();
+        let mut query = sqlx::query_as::<_, JobRecord>(&sql).bind(now).bind(now);
+        for job_type in job_types {
+            query = query.bind(*job_type);
+        }
+
+        let job = query.fetch_optional(self.pool()).await?;
+        Ok(job)
+    }
+
+    /// Mark a running job as completed with an optional JSON result
+    ///
+    /// Jobs cancelled while running are left cancelled.
+    pub async fn complete_job(&self, id: i64, result: Option<&str>) -> Result<()> {
+        sqlx::query(
             r#"
-            INSERT INTO embeddings (frame_id, chunk_text, chunk_index, embedding_dim, embedding)
-            VALUES (?, ?, ?, ?, ?)
+            UPDATE jobs
+            SET state = 'completed', result = ?, last_error = NULL, finished_at = ?
+            WHERE id = ? AND state = 'running'
             "#,
         )
-        .bind(embedding.frame_id)
-        .bind(&embedding.chunk_text)
-        .bind(embedding.chunk_index)
-        .bind(embedding.embedding.len() as i32)
-        .bind(embedding_blob)
+        .bind(result)
+        .bind(Utc::now())
+        .bind(id)
         .execute(self.pool())
         .await?;
 
-        Ok(result.last_insert_rowid())
+        Ok(())
     }
 
-    /// Get embeddings for a frame
-    pub async fn get_embeddings_for_frame(&self, frame_id: i64) -> Result<Vec<EmbeddingRecord>> {
-        let embeddings = sqlx::query_as::<_, EmbeddingRecord>(
+    /// Record a failed attempt of a running job
+    ///
+    /// The job is queued again to run after `retry_after` until it has been
+    /// attempted `max_attempts` times, after which it stays `failed`.
+    pub async fn fail_job(&self, id: i64, error: &str, retry_after: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
             r#"
-            SELECT id, frame_id, chunk_text, chunk_index, embedding_dim, created_at
-            FROM embeddings
-            WHERE frame_id = ?
-            ORDER BY chunk_index ASC
+            UPDATE jobs
+            SET state = CASE WHEN attempts < max_attempts THEN 'queued' ELSE 'failed' END,
+                run_after = CASE WHEN attempts < max_attempts THEN ?1 ELSE run_after END,
+                finished_at = CASE WHEN attempts < max_attempts THEN NULL ELSE ?2 END,
+                last_error = ?3
+            WHERE id = ?4 AND state = 'running'
             "#,
         )
-        .bind(frame_id)
-        .fetch_all(self.pool())
+        .bind(retry_after)
+        .bind(Utc::now())
+        .bind(error)
+        .bind(id)
+        .execute(self.pool())
         .await?;
 
-        Ok(embeddings)
+        Ok(())
     }
 
-    /// Get frames that don't have embeddings yet (for background processing)
-    pub async fn get_frames_without_embeddings(&self, limit: i64) -> Result<Vec<FrameRecord>> {
-        let frames = sqlx::query_as::<_, FrameRecord>(
+    /// Cancel a queued or running job
+    ///
+    /// Returns false if the job does not exist or has already finished.
+    /// Running jobs observe the cancellation the next time they check.
+    pub async fn cancel_job(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query(
             r#"
-            SELECT f.id, f.chunk_id, f.timestamp, f.monitor_index, f.device_name,
-                   f.file_path, f.active_window, f.active_process, f.browser_url,
-                   f.width, f.height, f.offset_index, f.focused, f.created_at
-            FROM frames f
-            LEFT JOIN embeddings e ON f.id = e.frame_id
-            WHERE e.id IS NULL
-            ORDER BY f.id ASC
-            LIMIT ?
+            UPDATE jobs
+            SET state = 'cancelled', finished_at = ?
+            WHERE id = ? AND state IN ('queued', 'running')
             "#,
         )
-        .bind(limit)
-        .fetch_all(self.pool())
+        .bind(Utc::now())
+        .bind(id)
+        .execute(self.pool())
         .await?;
 
-        Ok(frames)
+        Ok(result.rows_affected() > 0)
     }
 
-    /// Get embedding status statistics
-    pub async fn get_embedding_status(&self) -> Result<EmbeddingStatus> {
-        let total_frames = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM frames")
-            .fetch_one(self.pool())
-            .await?;
-
-        let frames_with_embeddings = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(DISTINCT frame_id) FROM embeddings"
+    /// Return jobs left `running` by a previous process to the queue
+    ///
+    /// Call once at startup, before any job worker runs.
+    pub async fn requeue_running_jobs(&self) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET state = 'queued', run_after = ?, started_at = NULL
+            WHERE state = 'running'
+            "#,
         )
-        .fetch_one(self.pool())
+        .bind(Utc::now())
+        .execute(self.pool())
         .await?;
 
-        let enabled = self
-            .get_metadata("embeddings_enabled")
-            .await?
-            .map(|v| v == "true")
-            .unwrap_or(false);
+        Ok(result.rows_affected())
+    }
+}
 
-        let model = self
-            .get_metadata("embeddings_model")
-            .await?
-            .unwrap_or_else(|| "all-MiniLM-L6-v2".to_string());
+/// Read a frame and one of its OCR text records from a joined row
+///
+/// The OCR columns that clash with frame columns are aliased `ocr_id`,
+/// `ocr_width`, `ocr_height` and `ocr_created_at`.
+fn frame_and_ocr_from_row(row: &sqlx::sqlite::SqliteRow) -> (FrameRecord, OcrTextRecord) {
+    let frame = FrameRecord {
+        id: row.get("id"),
+        chunk_id: row.get("chunk_id"),
+        timestamp: row.get("timestamp"),
+        monitor_index: row.get("monitor_index"),
+        device_name: row.get("device_name"),
+        file_path: row.get("file_path"),
+        active_window: row.get("active_window"),
+        active_process: row.get("active_process"),
+        browser_url: row.get("browser_url"),
+        width: row.get("width"),
+        height: row.get("height"),
+        offset_index: row.get("offset_index"),
+        focused: row.get("focused"),
+        pinned: row.get("pinned"),
+        created_at: row.get::<DateTime<Utc>, _>("created_at"),
+    };
 
-        let last_processed_frame_id = self
-            .get_metadata("embeddings_last_processed_frame_id")
-            .await?
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(0);
+    let ocr = OcrTextRecord {
+        id: row.get::<i64, _>("ocr_id"),
+        frame_id: row.get("frame_id"),
+        text: row.get("text"),
+        text_json: row.get("text_json"),
+        x: row.get::<i32, _>("x"),
+        y: row.get::<i32, _>("y"),
+        width: row.get::<i32, _>("ocr_width"),
+        height: row.get::<i32, _>("ocr_height"),
+        confidence: row.get("confidence"),
+        created_at: row.get::<DateTime<Utc>, _>("ocr_created_at"),
+    };
 
-        let coverage_percent = if total_frames > 0 {
-            (frames_with_embeddings as f32 / total_frames as f32) * 100.0
-        } else {
-            0.0
-        };
+    (frame, ocr)
+}
 
-        Ok(EmbeddingStatus {
-            enabled,
-            model,
-            total_frames,
-            frames_with_embeddings,
-            coverage_percent,
-            last_processed_frame_id,
-        })
+/// Append the exclusion filters of `filter` to a query over frames
+///
+/// `frames` is the name or alias of the frames table in the query. Returns
+/// the values to bind, in order, after those of the clauses before.
+fn push_exclusions(sql: &mut String, filter: &FrameFilter, frames: &str) -> Vec<String> {
+    let mut values = Vec::new();
+
+    if let Some(apps) = filter.exclude_apps.as_ref().filter(|a| !a.is_empty()) {
+        sql.push_str(&format!(
+            " AND LOWER(COALESCE({}.active_process, '')) NOT IN ({})",
+            frames,
+            vec!["?"; apps.len()].join(", ")
+        ));
+        values.extend(apps.iter().map(|app| app.to_lowercase()));
+    }
+    if let Some(tags) = filter.exclude_tags.as_ref().filter(|t| !t.is_empty()) {
+        sql.push_str(&format!(
+            " AND NOT EXISTS (SELECT 1 FROM frame_tags xft JOIN tags xt ON xt.id = xft.tag_id \
+             WHERE xft.frame_id = {}.id AND LOWER(xt.tag_name) IN ({}))",
+            frames,
+            vec!["?"; tags.len()].join(", ")
+        ));
+        values.extend(tags.iter().map(|tag| tag.to_lowercase()));
     }
 
-    /// Delete embeddings for a frame
-    pub async fn delete_embeddings_for_frame(&self, frame_id: i64) -> Result<u64> {
-        let result = sqlx::query("DELETE FROM embeddings WHERE frame_id = ?")
-            .bind(frame_id)
-            .execute(self.pool())
-            .await?;
+    values
+}
 
-        Ok(result.rows_affected())
+/// Frames deleted per statement by a purge, within SQLite's parameter limit
+const PURGE_BATCH: usize = 500;
+
+/// IDs of the frames carrying the tag bound to `?` (case-insensitive)
+const TAGGED_FRAMES: &str = "SELECT ft.frame_id FROM frame_tags ft \
+     JOIN tags t ON t.id = ft.tag_id WHERE t.tag_name = ? COLLATE NOCASE";
+
+/// Shortest query word fuzzy search corrects
+const FUZZY_MIN_WORD_LEN: usize = 4;
+/// Trailing characters of a query word ignored when comparing it to a stem
+const FUZZY_MAX_SUFFIX: usize = 3;
+/// Indexed terms a misspelled query word is expanded to
+const FUZZY_MAX_CORRECTIONS: usize = 5;
+
+/// Quote `text` as an FTS5 phrase, so that numbers and special characters
+/// are matched literally
+fn phrase_query(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Lowercased words of a search query, without LIKE or FTS5 special characters
+fn query_words(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Escape the wildcards of a LIKE pattern, using `\` as the escape character
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Edit distance between a query word and an indexed Porter stem
+///
+/// Up to `FUZZY_MAX_SUFFIX` trailing characters of the word are free to
+/// drop, as the stemmer removes suffixes such as "es" or "ing".
+fn stem_distance(word: &str, stem: &str) -> usize {
+    let word: Vec<char> = word.chars().collect();
+    let stem: Vec<char> = stem.chars().collect();
+    let shortest = word.len().saturating_sub(FUZZY_MAX_SUFFIX).max(1);
+
+    (shortest..=word.len())
+        .map(|len| edit_distance(&word[..len], &stem))
+        .min()
+        .unwrap_or(usize::MAX)
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and transpositions of adjacent characters
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
     }
 
-    /// Get total embedding count
-    pub async fn count_embeddings(&self) -> Result<i64> {
-        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM embeddings")
-            .fetch_one(self.pool())
-            .await?;
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
 
-        Ok(count)
+    rows[a.len()][b.len()]
+}
+
+/// Marker inserted by FTS5 `highlight()` before each matched term
+const HIGHLIGHT_OPEN: char = '\u{2}';
+/// Marker inserted by FTS5 `highlight()` after each matched term
+const HIGHLIGHT_CLOSE: char = '\u{3}';
+
+/// Extract character offsets of matches from `highlight()` output
+///
+/// The markers are control characters that never appear in OCR text, so the
+/// offsets map directly onto the original (unmarked) text.
+fn highlight_offsets(highlighted: &str) -> Vec<MatchOffset> {
+    let mut offsets = Vec::new();
+    let mut position = 0;
+    let mut start = None;
+
+    for ch in highlighted.chars() {
+        match ch {
+            HIGHLIGHT_OPEN => start = Some(position),
+            HIGHLIGHT_CLOSE => {
+                if let Some(s) = start.take() {
+                    offsets.push(MatchOffset {
+                        start: s,
+                        end: position,
+                    });
+                }
+            }
+            _ => position += 1,
+        }
+    }
+
+    offsets
+}
+
+/// Render an excerpt marked like `snippet()` output as HTML with matches
+/// wrapped in `<mark>` tags
+///
+/// OCR text is escaped first, so markup captured from the screen is shown as
+/// text rather than interpreted by the browser.
+pub fn snippet_html(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    for ch in snippet.chars() {
+        match ch {
+            HIGHLIGHT_OPEN => html.push_str("<mark>"),
+            HIGHLIGHT_CLOSE => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            _ => html.push(ch),
+        }
     }
+    html
 }
 
 /// Database statistics
@@ -897,3 +4879,56 @@ pub struct DatabaseStatistics {
     pub oldest_frame: Option<DateTime<Utc>>,
     pub newest_frame: Option<DateTime<Utc>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_offsets() {
+        let offsets = highlight_offsets("the \u{2}database\u{3} and \u{2}SQL\u{3}");
+        assert_eq!(
+            offsets,
+            vec![
+                MatchOffset { start: 4, end: 12 },
+                MatchOffset { start: 17, end: 20 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_offsets_counts_characters() {
+        let offsets = highlight_offsets("café \u{2}menu\u{3}");
+        assert_eq!(offsets, vec![MatchOffset { start: 5, end: 9 }]);
+    }
+
+    #[test]
+    fn test_highlight_offsets_no_matches() {
+        assert!(highlight_offsets("plain text").is_empty());
+    }
+
+    #[test]
+    fn test_snippet_html_escapes_text() {
+        assert_eq!(
+            snippet_html("...<img src=x onerror=\"alert('\u{2}hi\u{3}')\"> & more"),
+            "...&lt;img src=x onerror=&quot;alert(&#39;<mark>hi</mark>&#39;)&quot;&gt; &amp; more"
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars("form"), &chars("from")), 1);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+    }
+
+    #[test]
+    fn test_stem_distance() {
+        // Stems of "kubernetes" and "deployment"
+        assert_eq!(stem_distance("kuberntes", "kubernet"), 1);
+        assert_eq!(stem_distance("kubernetes", "kubernet"), 0);
+        assert_eq!(stem_distance("deploymnet", "deploy"), 1);
+        assert!(stem_distance("database", "kubernet") > 2);
+    }
+}
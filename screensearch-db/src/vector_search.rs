@@ -115,6 +115,33 @@ impl DatabaseManager {
         Ok(index)
     }
 
+    /// Stored embedding of the first text chunk of a frame
+    ///
+    /// `None` until the frame has been embedded, or if the stored vector is
+    /// malformed.
+    pub async fn get_frame_embedding(&self, frame_id: i64) -> Result<Option<Vec<f32>>> {
+        let row = sqlx::query_as::<_, (Vec<u8>, i32)>(
+            r#"
+            SELECT embedding, embedding_dim
+            FROM embeddings
+            WHERE frame_id = ?
+            ORDER BY chunk_index ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(frame_id)
+        .fetch_optional(self.pool())
+        .await?;
+
+        Ok(row.and_then(|(blob, dim)| {
+            (blob.len() == dim as usize * 4).then(|| {
+                blob.chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect()
+            })
+        }))
+    }
+
     /// Perform semantic search using in-memory vector similarity
     ///
     /// Fetches all embeddings, computes similarity in Rust, and returns top results.
@@ -123,6 +150,7 @@ impl DatabaseManager {
     ///
     /// Fetches all embeddings within the time range, computes similarity in Rust, and returns top results.
     /// This avoids dependency on sqlite-vec extension availability.
+    #[tracing::instrument(skip(self, query_embedding))]
     pub async fn semantic_search(
         &self,
         query_embedding: Vec<f32>,
@@ -236,6 +264,7 @@ impl DatabaseManager {
     }
 
     /// Hybrid search combining FTS5 and vector similarity
+    #[tracing::instrument(skip(self, query_embedding))]
     pub async fn hybrid_search(
         &self,
         query: &str,
@@ -4,7 +4,13 @@
 //! full-text search, tag management, and filtering.
 
 use chrono::{Duration, Utc};
-use screen_db::{DatabaseManager, FrameFilter, NewFrame, NewOcrText, NewTag, Pagination};
+use screensearch_db::{
+    ContextChunk, DatabaseManager, EmbeddingOutcome, FacetCount, FrameFilter, NewActivitySummary,
+    NewAiInteraction, NewAlert, NewAlertHit, NewArchivedFrame, NewAuditEntry, NewDocument,
+    NewEmbedding, NewFrame, NewFrameArchive, NewJob, NewMacro, NewMetricSample, NewOcrText,
+    NewRedactionRule, NewReport, NewReportTemplate, NewSearchHistory, NewSyncedFrame, NewTag,
+    NewVideoChunk, Pagination, PurgeFilter, SearchFacet, SearchRanking, SyncOrigin, UpdateSettings,
+};
 use tempfile::NamedTempFile;
 
 /// Create a temporary database for testing
@@ -88,6 +94,22 @@ async fn test_frame_insertion() {
     assert_eq!(retrieved_frame.id, frame_id);
     assert_eq!(retrieved_frame.active_process, Some("chrome".to_string()));
 
+    // Looked up by device, time and window
+    assert_eq!(
+        db.find_frame_id("test-device", now, "Google")
+            .await
+            .unwrap(),
+        Some(frame_id)
+    );
+    assert_eq!(
+        db.find_frame_id("import", now, "Google").await.unwrap(),
+        None
+    );
+    assert_eq!(
+        db.find_frame_id("test-device", now, "Other").await.unwrap(),
+        None
+    );
+
     db.close().await;
 }
 
@@ -120,6 +142,18 @@ async fn test_ocr_text_insertion() {
     assert_eq!(retrieved[0].text, "Hello World");
     assert_eq!(retrieved[0].confidence, 0.95);
 
+    // Several frames at once; frames without text are left out
+    let empty_id = db
+        .insert_frame(create_test_frame(now, "chrome", "Blank"))
+        .await
+        .unwrap();
+    let by_frame = db
+        .get_ocr_text_for_frames(&[frame_id, empty_id])
+        .await
+        .unwrap();
+    assert_eq!(by_frame.len(), 1);
+    assert_eq!(by_frame[&frame_id][0].id, ocr_id);
+
     db.close().await;
 }
 
@@ -168,6 +202,345 @@ async fn test_fts5_search() {
     db.close().await;
 }
 
+#[tokio::test]
+async fn test_search_highlights() {
+    let (db, _path) = create_test_db().await;
+
+    let frame = create_test_frame(Utc::now(), "chrome", "Search");
+    let frame_id = db
+        .insert_frame(frame)
+        .await
+        .expect("Failed to insert frame");
+
+    let ocr = create_test_ocr(frame_id, "the database query language");
+    let ocr_id = db.insert_ocr_text(ocr).await.expect("Failed to insert OCR");
+
+    let results = db
+        .search_ocr_text("database", FrameFilter::default(), Pagination::default())
+        .await
+        .expect("Failed to search");
+
+    assert_eq!(results.len(), 1);
+    let highlights = &results[0].highlights;
+    assert_eq!(highlights.len(), 1);
+    assert_eq!(highlights[0].ocr_text_id, ocr_id);
+    assert!(highlights[0].snippet.contains("<mark>database</mark>"));
+    assert_eq!(highlights[0].offsets.len(), 1);
+    assert_eq!(highlights[0].offsets[0].start, 4);
+    assert_eq!(highlights[0].offsets[0].end, 12);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_fuzzy_search() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let texts = [
+        "kubernetes deployment rolled out",
+        "kubernetes pod restarted",
+        "database backup finished",
+    ];
+    for (i, text) in texts.iter().enumerate() {
+        let frame = create_test_frame(now + Duration::seconds(i as i64), "chrome", "Logs");
+        let frame_id = db
+            .insert_frame(frame)
+            .await
+            .expect("Failed to insert frame");
+        db.insert_ocr_text(create_test_ocr(frame_id, text))
+            .await
+            .expect("Failed to insert OCR");
+    }
+
+    // Exact search misses the typo
+    let results = db
+        .search_ocr_text("kuberntes", FrameFilter::default(), Pagination::default())
+        .await
+        .expect("Failed to search");
+    assert!(results.is_empty());
+
+    let results = db
+        .search_ocr_text_fuzzy("kuberntes", FrameFilter::default(), Pagination::default())
+        .await
+        .expect("Failed to search");
+    assert_eq!(results.len(), 2);
+
+    // Every word must match, correctly spelled or not
+    let results = db
+        .search_ocr_text_fuzzy(
+            "kuberntes deploymnet",
+            FrameFilter::default(),
+            Pagination::default(),
+        )
+        .await
+        .expect("Failed to search");
+    assert_eq!(results.len(), 1);
+    assert!(results[0].ocr_matches[0].text.contains("deployment"));
+
+    let facets = db
+        .search_facets_fuzzy(
+            "kuberntes",
+            &FrameFilter::default(),
+            &[SearchFacet::App],
+            10,
+        )
+        .await
+        .expect("Failed to count facets");
+    assert_eq!(facets.app.unwrap()[0].count, 2);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_search_ranking() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let frames = [
+        (
+            now - Duration::days(8),
+            "Inbox",
+            "quarterly report quarterly report",
+        ),
+        (
+            now,
+            "Quarterly planning",
+            "quarterly report draft for review",
+        ),
+        (now, "Terminal", "cargo build finished"),
+        (now, "Terminal", "git push origin main"),
+        (now, "Music", "now playing"),
+        (now, "Music", "next track queued"),
+    ];
+    let mut frame_ids = Vec::new();
+    for (timestamp, window, text) in frames {
+        let frame_id = db
+            .insert_frame(create_test_frame(timestamp, "app", window))
+            .await
+            .unwrap();
+        db.insert_ocr_text(create_test_ocr(frame_id, text))
+            .await
+            .unwrap();
+        frame_ids.push(frame_id);
+    }
+
+    let search = |ranking: SearchRanking| {
+        let db = &db;
+        async move {
+            db.search_ocr_text_ranked(
+                "quarterly report",
+                FrameFilter::default(),
+                Pagination::default(),
+                &ranking,
+            )
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.frame.id)
+            .collect::<Vec<_>>()
+        }
+    };
+
+    // Plain BM25 prefers the older frame that repeats the words
+    let default_order = search(SearchRanking::default()).await;
+    assert_eq!(default_order, vec![frame_ids[0], frame_ids[1]]);
+
+    let recent = search(SearchRanking {
+        recency_weight: 10.0,
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(recent, vec![frame_ids[1], frame_ids[0]]);
+
+    let titled = search(SearchRanking {
+        title_weight: 10.0,
+        ..Default::default()
+    })
+    .await;
+    assert_eq!(titled, vec![frame_ids[1], frame_ids[0]]);
+
+    // Weights are kept in settings
+    let settings = db.get_settings().await.unwrap();
+    assert_eq!(settings.search_ranking, "{}");
+    let ranking = r#"{"recency_weight":2.0}"#;
+    let updated = db
+        .update_settings(UpdateSettings {
+            capture_interval: settings.capture_interval,
+            monitors: settings.monitors,
+            excluded_apps: settings.excluded_apps,
+            is_paused: settings.is_paused,
+            retention_days: settings.retention_days,
+            audit_retention_days: None,
+            capture_schedule: None,
+            search_ranking: Some(ranking.to_string()),
+        })
+        .await
+        .unwrap();
+    assert_eq!(updated.search_ranking, ranking);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_search_recency_halves_every_half_life() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    for hours in [0, 24, 36, 48] {
+        let frame_id = db
+            .insert_frame(create_test_frame(
+                now - Duration::hours(hours),
+                "app",
+                "Notes",
+            ))
+            .await
+            .unwrap();
+        db.insert_ocr_text(create_test_ocr(frame_id, "standup notes"))
+            .await
+            .unwrap();
+    }
+
+    let ranking = SearchRanking {
+        recency_weight: 1.0,
+        recency_half_life_hours: 24.0,
+        ..Default::default()
+    };
+    let results = db
+        .search_ocr_text_ranked(
+            "standup",
+            FrameFilter::default(),
+            Pagination::default(),
+            &ranking,
+        )
+        .await
+        .unwrap();
+    let scores: Vec<f32> = results.iter().map(|r| r.relevance_score).collect();
+    assert_eq!(scores.len(), 4);
+
+    // The same text scores 1 + decay times its BM25 relevance
+    let base = scores[0] / 2.0;
+    for (score, boost) in scores.iter().zip([2.0, 1.5, 1.375, 1.25]) {
+        assert!((score / base - boost).abs() < 1e-3, "{:?}", scores);
+    }
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_scan_ocr_text() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let mut ocr_ids = Vec::new();
+    for (i, app) in ["chrome", "code", "chrome"].iter().enumerate() {
+        let frame = create_test_frame(now + Duration::seconds(i as i64), app, "Window");
+        let frame_id = db.insert_frame(frame).await.unwrap();
+        let ocr_id = db
+            .insert_ocr_text(create_test_ocr(frame_id, &format!("ticket ABC-{}", i)))
+            .await
+            .unwrap();
+        ocr_ids.push(ocr_id);
+    }
+
+    let filter = FrameFilter {
+        app_name: Some("chrome".to_string()),
+        ..Default::default()
+    };
+    let page = db.scan_ocr_text(&filter, None, 1).await.unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].1.id, ocr_ids[2]);
+    assert_eq!(page[0].0.active_process.as_deref(), Some("chrome"));
+
+    let page = db
+        .scan_ocr_text(&filter, Some(page[0].1.id), 10)
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].1.id, ocr_ids[0]);
+    assert_eq!(page[0].1.text, "ticket ABC-0");
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_search_facets() {
+    let (db, _path) = create_test_db().await;
+
+    let day1 = Utc::now() - Duration::days(2);
+    let day2 = Utc::now() - Duration::days(1);
+    let mut frame_ids = Vec::new();
+    for (timestamp, app, text) in [
+        (day1, "chrome", "invoice 42"),
+        (day1, "chrome", "invoice 43"),
+        (day2, "code", "invoice parser"),
+        (day2, "code", "unrelated text"),
+    ] {
+        let frame_id = db
+            .insert_frame(create_test_frame(timestamp, app, "Window"))
+            .await
+            .unwrap();
+        // Two matching regions in one frame still count the frame once
+        db.insert_ocr_text(create_test_ocr(frame_id, text))
+            .await
+            .unwrap();
+        db.insert_ocr_text(create_test_ocr(frame_id, text))
+            .await
+            .unwrap();
+        frame_ids.push(frame_id);
+    }
+
+    let tag_id = db
+        .create_tag(NewTag {
+            tag_name: "billing".to_string(),
+            description: None,
+            color: None,
+        })
+        .await
+        .unwrap();
+    db.add_tag_to_frame(frame_ids[0], tag_id).await.unwrap();
+
+    let facets = db
+        .search_facets("invoice", &FrameFilter::default(), &SearchFacet::ALL, 100)
+        .await
+        .unwrap();
+
+    let counts = |values: Option<Vec<FacetCount>>| {
+        values
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.value, c.count))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        counts(facets.app),
+        vec![("chrome".to_string(), 2), ("code".to_string(), 1)]
+    );
+    assert_eq!(
+        counts(facets.day),
+        vec![
+            (day1.format("%Y-%m-%d").to_string(), 2),
+            (day2.format("%Y-%m-%d").to_string(), 1)
+        ]
+    );
+    assert_eq!(counts(facets.tag), vec![("billing".to_string(), 1)]);
+    assert_eq!(counts(facets.monitor), vec![("0".to_string(), 3)]);
+
+    // Filters apply and unrequested facets are left out
+    let filter = FrameFilter {
+        app_name: Some("code".to_string()),
+        ..Default::default()
+    };
+    let facets = db
+        .search_facets("invoice", &filter, &[SearchFacet::App], 100)
+        .await
+        .unwrap();
+    assert_eq!(counts(facets.app), vec![("code".to_string(), 1)]);
+    assert!(facets.day.is_none());
+
+    db.close().await;
+}
+
 #[tokio::test]
 async fn test_frame_filtering_by_time() {
     let (db, _path) = create_test_db().await;
@@ -218,10 +591,12 @@ async fn test_frame_filtering_by_app() {
 
     // Query frames from chrome only
     let wide_range = (now - Duration::days(1), now + Duration::days(1));
-    let mut filter = FrameFilter::default();
-    filter.start_time = Some(wide_range.0);
-    filter.end_time = Some(wide_range.1);
-    filter.app_name = Some("chrome".to_string());
+    let filter = FrameFilter {
+        start_time: Some(wide_range.0),
+        end_time: Some(wide_range.1),
+        app_name: Some("chrome".to_string()),
+        ..Default::default()
+    };
 
     let frames = db
         .get_frames_in_range(wide_range.0, wide_range.1, filter, Pagination::default())
@@ -234,6 +609,145 @@ async fn test_frame_filtering_by_app() {
     db.close().await;
 }
 
+#[tokio::test]
+async fn test_frame_exclusion_filters() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let mut frame_ids = Vec::new();
+    for app in ["chrome", "Slack.exe", "notepad"] {
+        let frame_id = db
+            .insert_frame(create_test_frame(now, app, "Window"))
+            .await
+            .unwrap();
+        db.insert_ocr_text(create_test_ocr(frame_id, "quarterly budget"))
+            .await
+            .unwrap();
+        frame_ids.push(frame_id);
+    }
+    let tag_id = db
+        .create_tag(NewTag {
+            tag_name: "Personal".to_string(),
+            description: None,
+            color: None,
+        })
+        .await
+        .unwrap();
+    db.add_tag_to_frame(frame_ids[2], tag_id).await.unwrap();
+
+    let filter = FrameFilter {
+        exclude_apps: Some(vec!["slack.exe".to_string()]),
+        exclude_tags: Some(vec!["personal".to_string()]),
+        ..Default::default()
+    };
+
+    let frames = db
+        .get_frames_in_range(
+            now - Duration::days(1),
+            now + Duration::days(1),
+            filter.clone(),
+            Pagination::default(),
+        )
+        .await
+        .expect("Failed to get filtered frames");
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].id, frame_ids[0]);
+
+    let results = db
+        .search_ocr_text("budget", filter.clone(), Pagination::default())
+        .await
+        .expect("Failed to search");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].frame.id, frame_ids[0]);
+
+    let facets = db
+        .search_facets("budget", &filter, &[SearchFacet::App], 100)
+        .await
+        .expect("Failed to count facets");
+    assert_eq!(facets.app.unwrap().len(), 1);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_search_pages_and_counts() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let mut frame_ids = Vec::new();
+    for i in 0..5 {
+        let frame_id = db
+            .insert_frame(create_test_frame(
+                now + Duration::seconds(i),
+                "chrome",
+                "Window",
+            ))
+            .await
+            .unwrap();
+        // Several matching lines per frame still make one result
+        for line in ["release notes", "release checklist"] {
+            db.insert_ocr_text(create_test_ocr(frame_id, line))
+                .await
+                .unwrap();
+        }
+        frame_ids.push(frame_id);
+    }
+    db.insert_frame(create_test_frame(now, "slack", "Window"))
+        .await
+        .unwrap();
+
+    let filter = FrameFilter::default();
+    let mut seen = Vec::new();
+    for offset in [0, 2, 4] {
+        let page = db
+            .search_ocr_text("release", filter.clone(), Pagination { limit: 2, offset })
+            .await
+            .expect("Failed to search");
+        assert_eq!(page.len(), if offset == 4 { 1 } else { 2 });
+        assert!(page.iter().all(|result| result.ocr_matches.len() == 2));
+        seen.extend(page.iter().map(|result| result.frame.id));
+    }
+    seen.sort();
+    assert_eq!(seen, frame_ids);
+
+    assert_eq!(
+        db.count_search_results("release", &filter).await.unwrap(),
+        5
+    );
+    assert_eq!(
+        db.count_search_results_fuzzy("relaese", &filter)
+            .await
+            .unwrap(),
+        5
+    );
+    let excluded = FrameFilter {
+        exclude_apps: Some(vec!["chrome".to_string()]),
+        ..Default::default()
+    };
+    assert_eq!(
+        db.count_search_results("release", &excluded).await.unwrap(),
+        0
+    );
+
+    let range = (now - Duration::hours(1), now + Duration::hours(1));
+    assert_eq!(
+        db.count_frames_with_filter(range.0, range.1, &filter)
+            .await
+            .unwrap(),
+        6
+    );
+    assert_eq!(
+        db.count_frames_with_filter(range.0, range.1, &excluded)
+            .await
+            .unwrap(),
+        1
+    );
+
+    assert_eq!(db.count_tags().await.unwrap(), 0);
+
+    db.close().await;
+}
+
 #[tokio::test]
 async fn test_tag_creation_and_assignment() {
     let (db, _path) = create_test_db().await;
@@ -319,6 +833,23 @@ async fn test_multiple_tags_per_frame() {
     assert!(tag_names.contains(&"Important".to_string()));
     assert!(tag_names.contains(&"Review".to_string()));
 
+    let in_range = db
+        .get_frame_tags_in_range(now - Duration::minutes(1), now + Duration::minutes(1))
+        .await
+        .expect("Failed to get tags in range");
+    assert_eq!(
+        in_range
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Important", "Review"]
+    );
+    assert!(db
+        .get_frame_tags_in_range(now + Duration::minutes(1), now + Duration::minutes(2))
+        .await
+        .unwrap()
+        .is_empty());
+
     db.close().await;
 }
 
@@ -424,6 +955,94 @@ async fn test_database_statistics() {
     db.close().await;
 }
 
+#[tokio::test]
+async fn test_vacuum() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    for i in 0..10 {
+        let frame = create_test_frame(now - Duration::days(60 + i), "chrome", "Browser");
+        let frame_id = db.insert_frame(frame).await.unwrap();
+        db.insert_ocr_text(create_test_ocr(frame_id, "Old text"))
+            .await
+            .unwrap();
+    }
+    let recent = create_test_frame(now, "notepad", "Editor");
+    db.insert_frame(recent).await.unwrap();
+
+    db.cleanup_old_data(30).await.unwrap();
+    db.vacuum().await.expect("Failed to vacuum database");
+
+    let stats = db.get_statistics().await.unwrap();
+    assert_eq!(stats.frame_count, 1);
+    assert_eq!(stats.ocr_count, 0);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_checkpoint() {
+    let (db, path) = create_test_db().await;
+
+    let frame_id = db
+        .insert_frame(create_test_frame(Utc::now(), "notepad", "Editor"))
+        .await
+        .unwrap();
+    db.insert_ocr_text(create_test_ocr(frame_id, "Checkpointed text"))
+        .await
+        .unwrap();
+
+    db.checkpoint()
+        .await
+        .expect("Failed to checkpoint database");
+
+    let wal = std::fs::metadata(format!("{}-wal", path)).map_or(0, |m| m.len());
+    assert_eq!(wal, 0);
+    assert_eq!(db.get_statistics().await.unwrap().frame_count, 1);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_integrity_check_and_frame_paths() {
+    let (db, _path) = create_test_db().await;
+
+    let mut frame = create_test_frame(Utc::now(), "notepad", "Editor");
+    frame.file_path = "captures/frame_0_20260101_120000_000.jpg".to_string();
+    db.insert_frame(frame).await.unwrap();
+
+    assert!(db.integrity_check().await.unwrap().is_empty());
+    assert_eq!(
+        db.get_frame_file_paths().await.unwrap(),
+        vec!["captures/frame_0_20260101_120000_000.jpg".to_string()]
+    );
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_copy_to() {
+    let (db, path) = create_test_db().await;
+    db.insert_frame(create_test_frame(Utc::now(), "notepad", "Editor"))
+        .await
+        .unwrap();
+    // A fresh database has not been through first-run setup
+    assert_eq!(db.get_metadata("setup_completed").await.unwrap(), None);
+
+    let copy_path = format!("{}-copy", path);
+    db.copy_to(&copy_path)
+        .await
+        .expect("Failed to copy database");
+    assert!(db.copy_to(&copy_path).await.is_err());
+
+    let copy = DatabaseManager::new(&copy_path).await.unwrap();
+    assert_eq!(copy.get_statistics().await.unwrap().frame_count, 1);
+
+    copy.close().await;
+    db.close().await;
+    let _ = std::fs::remove_file(&copy_path);
+}
+
 #[tokio::test]
 async fn test_metadata_storage() {
     let (db, _path) = create_test_db().await;
@@ -474,51 +1093,1816 @@ async fn test_delete_old_frames() {
 }
 
 #[tokio::test]
-async fn test_pagination() {
+async fn test_tag_retention_overrides() {
     let (db, _path) = create_test_db().await;
 
     let now = Utc::now();
+    let mut frame_ids = Vec::new();
+    for hours in [48, 48, 48, 2] {
+        let frame = create_test_frame(now - Duration::hours(hours), "chrome", "Tagged");
+        frame_ids.push(db.insert_frame(frame).await.unwrap());
+    }
 
-    // Insert multiple frames
-    for i in 0..25 {
-        let frame = create_test_frame(now + Duration::seconds(i as i64), "chrome", "Page");
-        db.insert_frame(frame).await.unwrap();
+    let mut tag_ids = Vec::new();
+    for name in ["Keep-Forever", "ephemeral"] {
+        let tag = NewTag {
+            tag_name: name.to_string(),
+            description: None,
+            color: None,
+        };
+        tag_ids.push(db.create_tag(tag).await.unwrap());
     }
+    let (keep, ephemeral) = (tag_ids[0], tag_ids[1]);
+    db.add_tag_to_frame(frame_ids[0], keep).await.unwrap();
+    db.add_tag_to_frame(frame_ids[0], ephemeral).await.unwrap();
+    db.add_tag_to_frame(frame_ids[1], ephemeral).await.unwrap();
+    db.add_tag_to_frame(frame_ids[3], ephemeral).await.unwrap();
 
-    // Get first page (10 items)
-    let page1 = db
-        .get_frames_in_range(
-            now - Duration::minutes(1),
-            now + Duration::minutes(1),
-            FrameFilter::default(),
-            Pagination {
-                limit: 10,
-                offset: 0,
-            },
-        )
+    // Only the old ephemeral frame without keep-forever goes
+    let deleted = db
+        .delete_ephemeral_frames(now - Duration::hours(24))
         .await
         .unwrap();
+    assert_eq!(deleted, 1);
+    assert!(db.get_frame(frame_ids[1]).await.unwrap().is_none());
 
-    assert_eq!(page1.len(), 10);
-
-    // Get second page
-    let page2 = db
-        .get_frames_in_range(
-            now - Duration::minutes(1),
-            now + Duration::minutes(1),
-            FrameFilter::default(),
-            Pagination {
-                limit: 10,
-                offset: 10,
-            },
-        )
+    // Retention and low-disk cleanup skip keep-forever frames
+    let deleted = db
+        .delete_old_frames(now - Duration::hours(1))
+        .await
+        .unwrap();
+    assert_eq!(deleted, 2);
+    let (count, _) = db
+        .delete_oldest_frames(now - Duration::hours(1), 10)
         .await
         .unwrap();
+    assert_eq!(count, 0);
+    assert!(db.get_frame(frame_ids[0]).await.unwrap().is_some());
 
-    assert_eq!(page2.len(), 10);
+    db.close().await;
+}
 
-    // Frames should be different
-    assert_ne!(page1[0].id, page2[0].id);
+#[tokio::test]
+async fn test_pinned_frames_skip_cleanup() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let mut frame_ids = Vec::new();
+    for window in ["Contract", "Inbox"] {
+        let frame = create_test_frame(now - Duration::days(10), "acrobat", window);
+        let id = db.insert_frame(frame).await.unwrap();
+        db.insert_ocr_text(create_test_ocr(id, "signed contract"))
+            .await
+            .unwrap();
+        frame_ids.push(id);
+    }
+    let (pinned, other) = (frame_ids[0], frame_ids[1]);
+    let tag = NewTag {
+        tag_name: "ephemeral".to_string(),
+        description: None,
+        color: None,
+    };
+    let tag_id = db.create_tag(tag).await.unwrap();
+    db.add_tag_to_frame(pinned, tag_id).await.unwrap();
+
+    assert!(!db.get_frame(pinned).await.unwrap().unwrap().pinned);
+    assert_eq!(db.set_frame_pinned(pinned, true).await.unwrap(), 1);
+    assert_eq!(db.set_frame_pinned(9999, true).await.unwrap(), 0);
+    assert!(db.get_frame(pinned).await.unwrap().unwrap().pinned);
+
+    // Search results carry the flag
+    let results = db
+        .search_ocr_text("contract", FrameFilter::default(), Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results
+        .iter()
+        .all(|r| r.frame.pinned == (r.frame.id == pinned)));
+
+    // Compaction and archiving leave the pinned frame's image alone
+    let cutoff = now - Duration::days(1);
+    let to_compact = db.get_frames_to_compact(cutoff, 10).await.unwrap();
+    assert_eq!(to_compact.iter().map(|f| f.id).collect::<Vec<_>>(), [other]);
+    let to_archive = db.get_frames_to_archive(cutoff, 10).await.unwrap();
+    assert_eq!(to_archive.iter().map(|f| f.id).collect::<Vec<_>>(), [other]);
+
+    // Purges find only the unpinned frame
+    let filter = PurgeFilter {
+        keyword: Some("contract".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(db.find_purge_frames(&filter).await.unwrap(), [other]);
+    assert_eq!(
+        db.find_pinned_purge_frames(&filter).await.unwrap(),
+        [pinned]
+    );
+
+    // Retention, ephemeral tags and low-disk cleanup keep it
+    assert_eq!(db.delete_ephemeral_frames(cutoff).await.unwrap(), 0);
+    let (count, _) = db.delete_oldest_frames(cutoff, 10).await.unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(db.delete_old_frames(now).await.unwrap(), 0);
+    assert!(db.get_frame(pinned).await.unwrap().is_some());
+
+    // Unpinned, it is cleaned up like any other frame
+    db.set_frame_pinned(pinned, false).await.unwrap();
+    assert_eq!(db.delete_old_frames(now).await.unwrap(), 1);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_delete_oldest_frames() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    for days in [1, 3, 2, 5] {
+        let mut frame = create_test_frame(now - Duration::days(days), "chrome", "Page");
+        frame.file_path = format!("captures/{}.jpg", days);
+        db.insert_frame(frame).await.unwrap();
+    }
+
+    // Oldest first, never frames from after the cutoff
+    let (count, mut deleted) = db
+        .delete_oldest_frames(now - Duration::hours(36), 2)
+        .await
+        .unwrap();
+    deleted.sort();
+    assert_eq!(count, 2);
+    assert_eq!(deleted, vec!["captures/3.jpg", "captures/5.jpg"]);
+
+    let (count, deleted) = db
+        .delete_oldest_frames(now - Duration::hours(36), 10)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(deleted, vec!["captures/2.jpg"]);
+
+    let stats = db.get_statistics().await.unwrap();
+    assert_eq!(stats.frame_count, 1);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_compact_frames() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let mut ids = Vec::new();
+    for (days, monitor) in [(4, 1), (3, 0), (5, 0), (0, 0)] {
+        let mut frame = create_test_frame(now - Duration::days(days), "chrome", "Page");
+        frame.monitor_index = monitor;
+        frame.file_path = format!("captures/{}.jpg", days);
+        ids.push(db.insert_frame(frame).await.unwrap());
+    }
+
+    // Only frames before the cutoff, grouped by monitor, oldest first
+    let frames = db
+        .get_frames_to_compact(now - Duration::days(1), 10)
+        .await
+        .unwrap();
+    let found: Vec<i64> = frames.iter().map(|f| f.id).collect();
+    assert_eq!(found, vec![ids[2], ids[1], ids[0]]);
+
+    let chunk = NewVideoChunk {
+        device_name: "test-device".to_string(),
+        file_path: "captures/chunk.mp4".to_string(),
+        start_time: frames[0].timestamp,
+        end_time: frames[1].timestamp,
+        duration_ms: 1000,
+        width: 1920,
+        height: 1080,
+        fps: 2,
+    };
+    let chunk_id = db
+        .insert_compacted_chunk(chunk, &[ids[2], ids[1]])
+        .await
+        .unwrap();
+
+    let frame = db.get_frame(ids[1]).await.unwrap().unwrap();
+    assert_eq!(frame.chunk_id, Some(chunk_id));
+    assert_eq!(frame.offset_index, 1);
+    assert_eq!(frame.file_path, "captures/chunk.mp4");
+    assert_eq!(db.get_video_chunk(chunk_id).await.unwrap().unwrap().fps, 2);
+
+    let frames = db
+        .get_frames_to_compact(now - Duration::days(1), 10)
+        .await
+        .unwrap();
+    assert_eq!(frames.len(), 1);
+    assert!(db.delete_empty_video_chunks().await.unwrap().is_empty());
+
+    // The chunk file is only deleted with the last of its frames
+    let (count, deleted) = db
+        .delete_oldest_frames(now - Duration::hours(108), 10)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert!(deleted.is_empty());
+
+    let (count, mut deleted) = db
+        .delete_oldest_frames(now - Duration::days(1), 10)
+        .await
+        .unwrap();
+    deleted.sort();
+    assert_eq!(count, 2);
+    assert_eq!(deleted, vec!["captures/4.jpg", "captures/chunk.mp4"]);
+    assert!(db.get_video_chunk(chunk_id).await.unwrap().is_none());
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_archive_frames() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let mut ids = Vec::new();
+    for days in [4, 5, 0] {
+        let mut frame = create_test_frame(now - Duration::days(days), "chrome", "Page");
+        frame.file_path = format!("captures/{}.jpg", days);
+        ids.push(db.insert_frame(frame).await.unwrap());
+    }
+
+    // Only loose images before the cutoff, oldest first
+    let frames = db
+        .get_frames_to_archive(now - Duration::days(1), 10)
+        .await
+        .unwrap();
+    let found: Vec<i64> = frames.iter().map(|f| f.id).collect();
+    assert_eq!(found, vec![ids[1], ids[0]]);
+
+    let archive = NewFrameArchive {
+        day: frames[0].timestamp.date_naive(),
+        file_path: "captures/cold/archive.tar.zst".to_string(),
+        size_bytes: 2048,
+    };
+    let entries = [
+        NewArchivedFrame {
+            frame_id: ids[1],
+            entry_offset: 0,
+            entry_length: 1000,
+        },
+        NewArchivedFrame {
+            frame_id: ids[0],
+            entry_offset: 1000,
+            entry_length: 1048,
+        },
+    ];
+    let archive_id = db.insert_frame_archive(archive, &entries).await.unwrap();
+
+    let frame = db.get_frame(ids[0]).await.unwrap().unwrap();
+    assert_eq!(frame.file_path, "captures/cold/archive.tar.zst");
+    let entry = db.get_archived_frame(ids[0]).await.unwrap().unwrap();
+    assert_eq!(entry.archive_id, archive_id);
+    assert_eq!(entry.file_path, "captures/cold/archive.tar.zst");
+    assert_eq!((entry.entry_offset, entry.entry_length), (1000, 1048));
+    assert!(db.get_archived_frame(ids[2]).await.unwrap().is_none());
+
+    let archives = db.list_frame_archives().await.unwrap();
+    assert_eq!(archives.len(), 1);
+    assert_eq!(archives[0].frame_count, 2);
+
+    // Archived frames are neither archived again nor compacted
+    let cutoff = now - Duration::days(1);
+    assert!(db
+        .get_frames_to_archive(cutoff, 10)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(db
+        .get_frames_to_compact(cutoff, 10)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // The archive file is only deleted with the last of its frames
+    let (count, deleted) = db
+        .delete_oldest_frames(now - Duration::hours(108), 10)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert!(deleted.is_empty());
+
+    let (count, deleted) = db.delete_oldest_frames(cutoff, 10).await.unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(deleted, vec!["captures/cold/archive.tar.zst"]);
+    assert!(db.list_frame_archives().await.unwrap().is_empty());
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_pagination() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+
+    // Insert multiple frames
+    for i in 0..25 {
+        let frame = create_test_frame(now + Duration::seconds(i as i64), "chrome", "Page");
+        db.insert_frame(frame).await.unwrap();
+    }
+
+    // Get first page (10 items)
+    let page1 = db
+        .get_frames_in_range(
+            now - Duration::minutes(1),
+            now + Duration::minutes(1),
+            FrameFilter::default(),
+            Pagination {
+                limit: 10,
+                offset: 0,
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(page1.len(), 10);
+
+    // Get second page
+    let page2 = db
+        .get_frames_in_range(
+            now - Duration::minutes(1),
+            now + Duration::minutes(1),
+            FrameFilter::default(),
+            Pagination {
+                limit: 10,
+                offset: 10,
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(page2.len(), 10);
+
+    // Frames should be different
+    assert_ne!(page1[0].id, page2[0].id);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_surrounding_frames() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let frame = create_test_frame(now + Duration::seconds(i), "app", "window");
+        ids.push(db.insert_frame(frame).await.unwrap());
+    }
+
+    // Frame on another monitor should be excluded
+    let mut other = create_test_frame(now + Duration::seconds(4), "app", "window");
+    other.monitor_index = 1;
+    db.insert_frame(other).await.unwrap();
+
+    let center = db.get_frame(ids[5]).await.unwrap().unwrap();
+    let (before, after) = db.get_surrounding_frames(&center, 3, 10).await.unwrap();
+
+    assert_eq!(
+        before.iter().map(|f| f.id).collect::<Vec<_>>(),
+        vec![ids[2], ids[3], ids[4]]
+    );
+    assert_eq!(
+        after.iter().map(|f| f.id).collect::<Vec<_>>(),
+        vec![ids[6], ids[7], ids[8], ids[9]]
+    );
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_report_storage() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    for period in ["daily", "weekly", "daily"] {
+        db.insert_report(NewReport {
+            period: period.to_string(),
+            content: format!("# {} report", period),
+            model: "llama3".to_string(),
+            context_source: None,
+            start_time: now - Duration::days(1),
+            end_time: now,
+        })
+        .await
+        .expect("Failed to insert report");
+    }
+
+    let all = db
+        .list_reports(None, Pagination::default())
+        .await
+        .expect("Failed to list reports");
+    assert_eq!(all.len(), 3);
+
+    let daily = db
+        .list_reports(Some("daily"), Pagination::default())
+        .await
+        .expect("Failed to list daily reports");
+    assert_eq!(daily.len(), 2);
+    assert!(daily.iter().all(|r| r.period == "daily"));
+    assert_eq!(db.count_reports(None).await.unwrap(), 3);
+    assert_eq!(db.count_reports(Some("daily")).await.unwrap(), 2);
+
+    let report = db.get_report(daily[0].id).await.unwrap().unwrap();
+    assert_eq!(report.content, "# daily report");
+
+    let found = db
+        .find_report("weekly", now - Duration::hours(1), now + Duration::hours(1))
+        .await
+        .expect("Failed to find report")
+        .expect("Weekly report not found");
+    assert_eq!(found.period, "weekly");
+    assert!(db
+        .find_report("daily", now - Duration::days(2), now - Duration::days(1))
+        .await
+        .unwrap()
+        .is_none());
+
+    assert_eq!(db.delete_report(report.id).await.unwrap(), 1);
+    assert!(db.get_report(report.id).await.unwrap().is_none());
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_macro_storage() {
+    let (db, _path) = create_test_db().await;
+
+    let id = db
+        .insert_macro(NewMacro {
+            name: "open-settings".to_string(),
+            description: Some("Open the settings page".to_string()),
+            events: r#"[{"type":"text","offset_ms":0,"text":"hello"}]"#.to_string(),
+            event_count: 1,
+            duration_ms: 1200,
+        })
+        .await
+        .expect("Failed to insert macro");
+
+    let macros = db
+        .list_macros(Pagination::default())
+        .await
+        .expect("Failed to list macros");
+    assert_eq!(macros.len(), 1);
+    assert_eq!(macros[0].name, "open-settings");
+    assert_eq!(db.count_macros().await.unwrap(), 1);
+
+    let stored = db.get_macro(id).await.unwrap().unwrap();
+    assert_eq!(stored.event_count, 1);
+    assert_eq!(stored.duration_ms, 1200);
+    assert!(stored.events.contains("hello"));
+
+    assert_eq!(db.delete_macro(id).await.unwrap(), 1);
+    assert!(db.get_macro(id).await.unwrap().is_none());
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_alert_hits() {
+    let (db, _path) = create_test_db().await;
+
+    let frame_id = db
+        .insert_frame(create_test_frame(Utc::now(), "java.exe", "Console"))
+        .await
+        .unwrap();
+
+    let keyword = db
+        .insert_alert(NewAlert {
+            pattern: "OutOfMemoryError".to_string(),
+            is_regex: false,
+            app_filter: None,
+        })
+        .await
+        .expect("Failed to insert alert");
+    let regex = db
+        .insert_alert(NewAlert {
+            pattern: r"ERR-\d+".to_string(),
+            is_regex: true,
+            app_filter: Some("java".to_string()),
+        })
+        .await
+        .expect("Failed to insert alert");
+
+    let alerts = db.list_alerts().await.unwrap();
+    assert_eq!(alerts.len(), 2);
+    assert!(!alerts[0].is_regex);
+    assert!(alerts[1].is_regex);
+    assert_eq!(alerts[1].app_filter.as_deref(), Some("java"));
+
+    for alert_id in [keyword, regex] {
+        db.insert_alert_hit(NewAlertHit {
+            alert_id,
+            frame_id,
+            matched_text: "match".to_string(),
+            context: "java.lang.OutOfMemoryError ERR-42".to_string(),
+            app_name: Some("java.exe".to_string()),
+        })
+        .await
+        .expect("Failed to insert alert hit");
+    }
+
+    let hits = db
+        .list_alert_hits(None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 2);
+
+    let hits = db
+        .list_alert_hits(Some(regex), Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].frame_id, frame_id);
+    assert_eq!(db.count_alert_hits(None).await.unwrap(), 2);
+    assert_eq!(db.count_alert_hits(Some(regex)).await.unwrap(), 1);
+
+    // Deleting an alert removes its hits
+    assert_eq!(db.delete_alert(regex).await.unwrap(), 1);
+    assert!(db.get_alert(regex).await.unwrap().is_none());
+    let hits = db
+        .list_alert_hits(None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].alert_id, keyword);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_automation_audit() {
+    let (db, _path) = create_test_db().await;
+
+    for (endpoint, caller, success) in [
+        ("POST /api/automation/click", "my-agent", true),
+        ("POST /api/automation/type", "my-agent", false),
+        ("agent:open_app", "agent", true),
+    ] {
+        db.insert_audit_entry(NewAuditEntry {
+            endpoint: endpoint.to_string(),
+            parameters: Some("{}".to_string()),
+            element: None,
+            success,
+            error: (!success).then(|| "failed".to_string()),
+            caller: Some(caller.to_string()),
+            duration_ms: 5,
+        })
+        .await
+        .unwrap();
+    }
+
+    let all = db
+        .list_audit_entries(None, None, None, None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[0].endpoint, "agent:open_app");
+    assert_eq!(all[1].error.as_deref(), Some("failed"));
+    assert!(!all[1].success);
+
+    let clicks = db
+        .list_audit_entries(
+            None,
+            None,
+            Some("click"),
+            Some("my-agent"),
+            Pagination::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(clicks.len(), 1);
+    assert_eq!(
+        db.count_audit_entries(None, None, None, None)
+            .await
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        db.count_audit_entries(None, None, Some("click"), Some("my-agent"))
+            .await
+            .unwrap(),
+        1
+    );
+
+    // Entries cannot be rewritten
+    let update = sqlx::query("UPDATE automation_audit SET success = 1")
+        .execute(db.pool())
+        .await;
+    assert!(update.is_err());
+
+    assert_eq!(db.get_settings().await.unwrap().audit_retention_days, 90);
+    assert_eq!(db.cleanup_old_audit_entries(0).await.unwrap(), 3);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_set_capture_paused() {
+    let (db, _path) = create_test_db().await;
+
+    let before = db.get_settings().await.unwrap();
+    db.set_capture_paused(true).await.unwrap();
+
+    let paused = db.get_settings().await.unwrap();
+    assert_eq!(paused.is_paused, 1);
+    assert_eq!(paused.capture_interval, before.capture_interval);
+    assert_eq!(paused.excluded_apps, before.excluded_apps);
+
+    db.set_capture_paused(false).await.unwrap();
+    assert_eq!(db.get_settings().await.unwrap().is_paused, 0);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_rewrite_file_paths() {
+    let (db, _path) = create_test_db().await;
+
+    for file_path in ["/data/ss/captures/a.jpg", "/data/ss2/captures/b.jpg"] {
+        let mut frame = create_test_frame(Utc::now(), "App", "Window");
+        frame.file_path = file_path.to_string();
+        db.insert_frame(frame).await.unwrap();
+    }
+
+    // Only whole directories match
+    let rewritten = db
+        .rewrite_file_paths("/data/ss/", "/mnt/ss/")
+        .await
+        .unwrap();
+    assert_eq!(rewritten, 1);
+
+    let mut paths = db.get_frame_file_paths().await.unwrap();
+    paths.sort();
+    assert_eq!(
+        paths,
+        ["/data/ss2/captures/b.jpg", "/mnt/ss/captures/a.jpg"]
+    );
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_capture_schedule_setting() {
+    let (db, _path) = create_test_db().await;
+
+    let settings = db.get_settings().await.unwrap();
+    assert_eq!(settings.capture_schedule, "[]");
+
+    let update = |capture_schedule: Option<&str>| UpdateSettings {
+        capture_interval: settings.capture_interval,
+        monitors: settings.monitors.clone(),
+        excluded_apps: settings.excluded_apps.clone(),
+        is_paused: settings.is_paused,
+        retention_days: settings.retention_days,
+        audit_retention_days: None,
+        capture_schedule: capture_schedule.map(str::to_string),
+        search_ranking: None,
+    };
+    let schedule = r#"["Mon-Fri 09:00-18:00"]"#;
+    let updated = db.update_settings(update(Some(schedule))).await.unwrap();
+    assert_eq!(updated.capture_schedule, schedule);
+
+    // Clients that do not know the schedule leave it alone
+    let updated = db.update_settings(update(None)).await.unwrap();
+    assert_eq!(updated.capture_schedule, schedule);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_search_history() {
+    let (db, _path) = create_test_db().await;
+
+    let search = |query: &str, mode: &str, result_count: i64| NewSearchHistory {
+        query: query.to_string(),
+        mode: mode.to_string(),
+        result_count,
+    };
+    let first = db
+        .insert_search_history(search("Invoice", "fts", 12))
+        .await
+        .unwrap();
+    db.insert_search_history(search("kuberntes", "fuzzy", 0))
+        .await
+        .unwrap();
+    let last = db
+        .insert_search_history(search("invoice", "fts", 10))
+        .await
+        .unwrap();
+
+    assert!(db.record_search_click(first, 42).await.unwrap());
+    assert!(!db.record_search_click(9999, 42).await.unwrap());
+
+    let history = db
+        .list_search_history(None, None, None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0].id, last);
+    assert_eq!(history[2].clicked_frame_id, Some(42));
+
+    let matching = db
+        .list_search_history(None, None, Some("VOICE"), Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(matching.len(), 2);
+    assert_eq!(db.count_search_history(None, None, None).await.unwrap(), 3);
+    assert_eq!(
+        db.count_search_history(None, None, Some("VOICE"))
+            .await
+            .unwrap(),
+        2
+    );
+
+    let stats = db.get_search_history_stats(None, None, 10).await.unwrap();
+    assert_eq!(stats.total_searches, 3);
+    assert_eq!(stats.unique_queries, 2);
+    assert_eq!(stats.zero_result_searches, 1);
+    assert_eq!(stats.clicked_searches, 1);
+    assert_eq!(stats.top_queries[0].query.to_lowercase(), "invoice");
+    assert_eq!(stats.top_queries[0].searches, 2);
+    assert_eq!(stats.top_queries[0].clicks, 1);
+    assert_eq!(stats.zero_result_queries.len(), 1);
+    assert_eq!(stats.zero_result_queries[0].query, "kuberntes");
+
+    assert_eq!(db.clear_search_history(None).await.unwrap(), 3);
+    let stats = db.get_search_history_stats(None, None, 10).await.unwrap();
+    assert_eq!(stats.total_searches, 0);
+    assert!(stats.top_queries.is_empty());
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_metrics_history() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let snapshot = |latency: f64| {
+        vec![
+            NewMetricSample {
+                metric: "ocr_latency".to_string(),
+                value: latency,
+            },
+            NewMetricSample {
+                metric: "capture_backlog".to_string(),
+                value: 2.0,
+            },
+        ]
+    };
+    db.insert_metric_samples("0.1.0", now - Duration::days(200), &snapshot(90.0))
+        .await
+        .unwrap();
+    db.insert_metric_samples("0.1.0", now - Duration::hours(2), &snapshot(120.0))
+        .await
+        .unwrap();
+    db.insert_metric_samples("0.2.0", now - Duration::hours(1), &snapshot(80.0))
+        .await
+        .unwrap();
+
+    // One metric in the range, oldest first
+    let history = db
+        .get_metric_history("ocr_latency", now - Duration::days(1), now)
+        .await
+        .unwrap();
+    let values: Vec<(f64, &str)> = history
+        .iter()
+        .map(|s| (s.value, s.version.as_str()))
+        .collect();
+    assert_eq!(values, vec![(120.0, "0.1.0"), (80.0, "0.2.0")]);
+
+    assert_eq!(db.cleanup_old_metrics(180).await.unwrap(), 2);
+    let history = db
+        .get_metric_history("capture_backlog", now - Duration::days(365), now)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_search_suggestions() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let frames = [
+        (now - Duration::days(3), "chrome.exe", "Deployment failed"),
+        (now, "chrome.exe", "deployment rolled back"),
+        (now, "code.exe", "deploy script and depot"),
+        (now, "Designer.exe", "unrelated text"),
+    ];
+    let mut frame_ids = Vec::new();
+    for (timestamp, app, text) in frames {
+        let frame_id = db
+            .insert_frame(create_test_frame(timestamp, app, "Window"))
+            .await
+            .unwrap();
+        db.insert_ocr_text(create_test_ocr(frame_id, text))
+            .await
+            .unwrap();
+        frame_ids.push(frame_id);
+    }
+
+    let terms = db.suggest_terms("Dep", 1, 10).await.unwrap();
+    assert_eq!(
+        terms,
+        vec![
+            ("deployment".to_string(), 2),
+            ("deploy".to_string(), 1),
+            ("depot".to_string(), 1),
+        ]
+    );
+    assert_eq!(db.suggest_terms("dep", 2, 10).await.unwrap().len(), 1);
+
+    // Term counts follow deleted text
+    db.delete_old_frames(now - Duration::days(1)).await.unwrap();
+    let terms = db.suggest_terms("deployment", 1, 10).await.unwrap();
+    assert!(terms.is_empty());
+    let terms = db.suggest_terms("deploym", 1, 10).await.unwrap();
+    assert_eq!(terms, vec![("deployment".to_string(), 1)]);
+
+    let apps = db.suggest_apps("c", 10).await.unwrap();
+    assert_eq!(
+        apps,
+        vec![("chrome.exe".to_string(), 1), ("code.exe".to_string(), 1)]
+    );
+    assert_eq!(db.suggest_apps("DES", 10).await.unwrap().len(), 1);
+    assert!(db.suggest_apps("%", 10).await.unwrap().is_empty());
+
+    let tag_id = db
+        .create_tag(NewTag {
+            tag_name: "deploys".to_string(),
+            description: None,
+            color: None,
+        })
+        .await
+        .unwrap();
+    db.add_tag_to_frame(frame_ids[2], tag_id).await.unwrap();
+    let tags = db.suggest_tags("dep", 10).await.unwrap();
+    assert_eq!(tags, vec![("deploys".to_string(), 1)]);
+
+    for (query, result_count) in [
+        ("Deploy pipeline", 4),
+        ("deploy pipeline", 2),
+        ("deplyo", 0),
+    ] {
+        db.insert_search_history(NewSearchHistory {
+            query: query.to_string(),
+            mode: "fts".to_string(),
+            result_count,
+        })
+        .await
+        .unwrap();
+    }
+    let queries = db.suggest_past_queries("dep", 10).await.unwrap();
+    assert_eq!(queries.len(), 1);
+    assert_eq!(queries[0].0.to_lowercase(), "deploy pipeline");
+    assert_eq!(queries[0].1, 2);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_ai_interactions() {
+    let (db, _path) = create_test_db().await;
+
+    let interaction =
+        |kind: &str, model: &str, tokens: Option<i64>, error: Option<&str>| NewAiInteraction {
+            kind: kind.to_string(),
+            provider_url: "http://localhost:11434/v1".to_string(),
+            model: model.to_string(),
+            system_prompt: Some("You are a productivity analyst".to_string()),
+            prompt: "Summarize my day".to_string(),
+            context_chunks: vec![ContextChunk {
+                frame_id: 7,
+                chunk_index: Some(2),
+            }],
+            response: error.is_none().then(|| "You wrote code".to_string()),
+            error: error.map(str::to_string),
+            latency_ms: 1200,
+            prompt_tokens: tokens.map(|t| t - 10),
+            completion_tokens: tokens.map(|_| 10),
+            total_tokens: tokens,
+        };
+    let report = db
+        .insert_ai_interaction(interaction("report", "llama3", Some(500), None))
+        .await
+        .unwrap();
+    db.insert_ai_interaction(interaction("agent", "llama3", None, Some("HTTP 500")))
+        .await
+        .unwrap();
+    db.insert_ai_interaction(interaction("report", "gpt-4o", Some(900), None))
+        .await
+        .unwrap();
+
+    let stored = db.get_ai_interaction(report).await.unwrap().unwrap();
+    assert_eq!(stored.context_chunks, r#"[{"frame_id":7,"chunk_index":2}]"#);
+    assert_eq!(stored.total_tokens, Some(500));
+
+    let all = || Pagination {
+        limit: 10,
+        offset: 0,
+    };
+    let reports = db
+        .list_ai_interactions(None, None, Some("report"), None, all())
+        .await
+        .unwrap();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].model, "gpt-4o");
+    let llama = db
+        .list_ai_interactions(None, None, None, Some("llama3"), all())
+        .await
+        .unwrap();
+    assert_eq!(llama.len(), 2);
+    assert_eq!(
+        db.count_ai_interactions(None, None, Some("report"), None)
+            .await
+            .unwrap(),
+        2
+    );
+
+    let usage = db.get_ai_usage(None, None, all()).await.unwrap();
+    assert_eq!(usage.len(), 2);
+    assert_eq!(
+        (usage[0].model.as_str(), usage[0].total_tokens),
+        ("gpt-4o", 900)
+    );
+    assert_eq!(
+        (
+            usage[1].interactions,
+            usage[1].failed,
+            usage[1].prompt_tokens
+        ),
+        (2, 1, 490)
+    );
+    assert_eq!(db.count_ai_usage_models(None, None).await.unwrap(), 2);
+    let second = Pagination {
+        limit: 1,
+        offset: 1,
+    };
+    let usage = db.get_ai_usage(None, None, second).await.unwrap();
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].model, "llama3");
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_activity_summaries() {
+    let (db, _path) = create_test_db().await;
+
+    let start = Utc::now() - Duration::hours(3);
+    let summary = |hour: i64, text: &str, method: &str| NewActivitySummary {
+        period_start: start + Duration::hours(hour),
+        period_end: start + Duration::hours(hour + 1),
+        summary: text.to_string(),
+        method: method.to_string(),
+        model: (method == "llm").then(|| "llama3".to_string()),
+        frame_count: 12,
+        frame_id: hour + 1,
+    };
+    let first = db
+        .upsert_activity_summary(summary(0, "Code (12 frames)", "extractive"))
+        .await
+        .unwrap();
+    db.upsert_activity_summary(summary(1, "Wrote the parser", "llm"))
+        .await
+        .unwrap();
+
+    // A later summary of the same hour replaces the earlier one
+    let replaced = db
+        .upsert_activity_summary(summary(0, "Reviewed pull requests", "llm"))
+        .await
+        .unwrap();
+    assert_eq!(replaced, first);
+
+    let all = || Pagination {
+        limit: 10,
+        offset: 0,
+    };
+    let summaries = db.list_activity_summaries(None, None, all()).await.unwrap();
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].summary, "Reviewed pull requests");
+    assert_eq!(summaries[0].model.as_deref(), Some("llama3"));
+    assert_eq!(summaries[1].summary, "Wrote the parser");
+
+    // Only periods entirely within the range
+    let within = db
+        .list_activity_summaries(Some(start), Some(start + Duration::minutes(90)), all())
+        .await
+        .unwrap();
+    assert_eq!(within.len(), 1);
+    assert_eq!(db.count_activity_summaries(None, None).await.unwrap(), 2);
+    assert_eq!(
+        db.count_activity_summaries(Some(start), Some(start + Duration::minutes(90)))
+            .await
+            .unwrap(),
+        1
+    );
+
+    let periods = db
+        .get_summarized_periods(start, start + Duration::hours(3))
+        .await
+        .unwrap();
+    assert_eq!(periods, vec![start, start + Duration::hours(1)]);
+
+    for minutes in [5, 20, 130] {
+        let frame = create_test_frame(start + Duration::minutes(minutes), "Code", "main.rs");
+        db.insert_frame(frame).await.unwrap();
+    }
+    let active = db
+        .count_active_hours(start, start + Duration::hours(3))
+        .await
+        .unwrap();
+    assert!((2..=3).contains(&active));
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_frames_after_cursor() {
+    let (db, _path) = create_test_db().await;
+
+    let start = Utc::now() - Duration::hours(1);
+    let mut ids = Vec::new();
+    for minutes in [30, 10, 20, 40] {
+        let frame = create_test_frame(start + Duration::minutes(minutes), "App", "Window");
+        ids.push(db.insert_frame(frame).await.unwrap());
+    }
+    let end = start + Duration::minutes(35);
+
+    // Ascending order, excluding frames after `end`
+    let page = db.get_frames_after(start, 0, end, None, 2).await.unwrap();
+    assert_eq!(
+        page.iter().map(|f| f.id).collect::<Vec<_>>(),
+        vec![ids[1], ids[2]]
+    );
+
+    let last = page.last().unwrap();
+    let page = db
+        .get_frames_after(last.timestamp, last.id, end, None, 2)
+        .await
+        .unwrap();
+    assert_eq!(page.iter().map(|f| f.id).collect::<Vec<_>>(), vec![ids[0]]);
+
+    // Monitor filter
+    let page = db
+        .get_frames_after(start, 0, end, Some(1), 10)
+        .await
+        .unwrap();
+    assert!(page.is_empty());
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_documents() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let frame_id = db
+        .insert_frame(create_test_frame(now, "chrome.exe", "Your receipt"))
+        .await
+        .unwrap();
+
+    let receipt = NewDocument {
+        frame_id,
+        doc_type: "receipt".to_string(),
+        vendor: Some("Blue Bottle Coffee".to_string()),
+        document_date: chrono::NaiveDate::from_ymd_opt(2026, 10, 16),
+        total: Some(9.75),
+        currency: Some("USD".to_string()),
+        line_items: r#"[{"description":"Latte","amount":5.25}]"#.to_string(),
+        text: "Blue Bottle Coffee Receipt Latte 5.25 Total 9.75".to_string(),
+        app_name: Some("chrome.exe".to_string()),
+        captured_at: now,
+    };
+    let receipt_id = db
+        .insert_document(receipt.clone())
+        .await
+        .expect("Failed to insert document");
+    let invoice_id = db
+        .insert_document(NewDocument {
+            doc_type: "invoice".to_string(),
+            vendor: Some("Acme Hosting".to_string()),
+            document_date: None,
+            total: Some(120.0),
+            line_items: "[]".to_string(),
+            text: "Invoice Acme Hosting Amount due 120.00".to_string(),
+            captured_at: now - Duration::hours(2),
+            ..receipt.clone()
+        })
+        .await
+        .unwrap();
+
+    let stored = db.get_document(receipt_id).await.unwrap().unwrap();
+    assert_eq!(stored.vendor.as_deref(), Some("Blue Bottle Coffee"));
+    assert_eq!(stored.document_date, receipt.document_date);
+    assert_eq!(stored.total, Some(9.75));
+
+    // Dated documents match regardless of when they were captured
+    let found = db
+        .find_matching_document(&receipt, now + Duration::hours(1))
+        .await
+        .unwrap();
+    assert_eq!(found.map(|d| d.id), Some(receipt_id));
+
+    // Undated documents only match recent captures
+    let undated = db.get_document(invoice_id).await.unwrap().unwrap();
+    let undated = NewDocument {
+        doc_type: undated.doc_type,
+        vendor: undated.vendor,
+        document_date: None,
+        total: undated.total,
+        ..receipt.clone()
+    };
+    let found = db
+        .find_matching_document(&undated, now - Duration::hours(3))
+        .await
+        .unwrap();
+    assert_eq!(found.map(|d| d.id), Some(invoice_id));
+    let found = db
+        .find_matching_document(&undated, now - Duration::hours(1))
+        .await
+        .unwrap();
+    assert!(found.is_none());
+
+    let all = db
+        .list_documents(None, None, None, None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        all.iter().map(|d| d.id).collect::<Vec<_>>(),
+        vec![receipt_id, invoice_id]
+    );
+
+    let latte = db
+        .list_documents(Some("latte"), None, None, None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(latte.len(), 1);
+    assert_eq!(latte[0].id, receipt_id);
+
+    let invoices = db
+        .list_documents(None, Some("invoice"), None, None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(invoices.len(), 1);
+    assert_eq!(invoices[0].id, invoice_id);
+    assert_eq!(
+        db.count_documents(Some("latte"), None, None, None)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(db.count_documents(None, None, None, None).await.unwrap(), 2);
+
+    let recent = db
+        .list_documents(
+            None,
+            None,
+            Some(now - Duration::hours(1)),
+            None,
+            Pagination::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].id, receipt_id);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_embedding_queue() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let old = db
+        .insert_frame(create_test_frame(now - Duration::days(10), "App", "Old"))
+        .await
+        .unwrap();
+    let recent = db
+        .insert_frame(create_test_frame(now - Duration::hours(1), "App", "Recent"))
+        .await
+        .unwrap();
+
+    // Only the frame inside the range is queued, and queuing is idempotent
+    let queued = db
+        .enqueue_embedding_backfill(Some(now - Duration::days(1)), None)
+        .await
+        .unwrap();
+    assert_eq!(queued, 1);
+    assert_eq!(
+        db.enqueue_embedding_backfill(Some(now - Duration::days(1)), None)
+            .await
+            .unwrap(),
+        0
+    );
+
+    // The worker picks up the remaining frame
+    assert_eq!(db.enqueue_new_embedding_frames(10).await.unwrap(), 1);
+
+    let claimed = db
+        .claim_embedding_frames(10, now - Duration::minutes(10))
+        .await
+        .unwrap();
+    assert_eq!(claimed, vec![old, recent]);
+    // Claimed frames are not handed out twice
+    assert!(db
+        .claim_embedding_frames(10, now - Duration::minutes(10))
+        .await
+        .unwrap()
+        .is_empty());
+
+    db.record_embedding_outcome(old, &EmbeddingOutcome::Skipped, 3)
+        .await
+        .unwrap();
+    // A failure below the attempt limit goes back to pending
+    db.record_embedding_outcome(recent, &EmbeddingOutcome::Failed("boom".to_string()), 2)
+        .await
+        .unwrap();
+
+    let stats = db
+        .get_embedding_queue_stats(now - Duration::minutes(10))
+        .await
+        .unwrap();
+    assert_eq!(stats.pending, 1);
+    assert_eq!(stats.skipped, 1);
+    assert_eq!(stats.finished_since, 1);
+
+    let claimed = db
+        .claim_embedding_frames(10, now - Duration::minutes(10))
+        .await
+        .unwrap();
+    assert_eq!(claimed, vec![recent]);
+    db.record_embedding_outcome(recent, &EmbeddingOutcome::Failed("boom".to_string()), 2)
+        .await
+        .unwrap();
+
+    let stats = db
+        .get_embedding_queue_stats(now - Duration::minutes(10))
+        .await
+        .unwrap();
+    assert_eq!(stats.failed, 1);
+    assert_eq!(stats.pending, 0);
+    assert_eq!(stats.total(), 2);
+
+    // A new backfill retries failed frames but not skipped ones
+    assert_eq!(db.enqueue_embedding_backfill(None, None).await.unwrap(), 1);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_embedding_index_stats() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    let stats = db
+        .get_embedding_index_stats(now - Duration::minutes(10))
+        .await
+        .unwrap();
+    assert_eq!(stats.chunks, 0);
+    assert_eq!(stats.vector_bytes, 0);
+    assert!(stats.last_embedded_at.is_none());
+
+    let frame_id = db
+        .insert_frame(create_test_frame(now, "App", "Window"))
+        .await
+        .unwrap();
+    for (chunk_index, text) in ["first chunk", "second"].iter().enumerate() {
+        db.insert_embedding(NewEmbedding {
+            frame_id,
+            chunk_text: text.to_string(),
+            chunk_index: chunk_index as i32,
+            embedding: vec![0.5; 4],
+        })
+        .await
+        .unwrap();
+    }
+
+    let stats = db
+        .get_embedding_index_stats(now - Duration::minutes(10))
+        .await
+        .unwrap();
+    assert_eq!(stats.chunks, 2);
+    assert_eq!(stats.vector_bytes, 32);
+    assert_eq!(stats.text_bytes, 17);
+    assert_eq!(stats.chunks_since, 2);
+    assert!(stats.last_embedded_at.is_some());
+
+    let stats = db
+        .get_embedding_index_stats(now + Duration::minutes(10))
+        .await
+        .unwrap();
+    assert_eq!(stats.chunks_since, 0);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_job_queue() {
+    let (db, _path) = create_test_db().await;
+
+    let new_job = |job_type: &str| NewJob {
+        job_type: job_type.to_string(),
+        payload: "{}".to_string(),
+        max_attempts: 2,
+    };
+    let report = db.insert_job(new_job("report")).await.unwrap();
+    let embeddings = db.insert_job(new_job("embeddings")).await.unwrap();
+    let active = db.find_active_job("report").await.unwrap().unwrap();
+    assert_eq!(active.id, report);
+
+    // Only registered types are claimed
+    let job = db.claim_next_job(&["embeddings"]).await.unwrap().unwrap();
+    assert_eq!(job.id, embeddings);
+    assert_eq!(job.state, "running");
+    assert_eq!(job.attempts, 1);
+    assert!(db.claim_next_job(&["embeddings"]).await.unwrap().is_none());
+
+    db.complete_job(embeddings, Some(r#"{"frames":3}"#))
+        .await
+        .unwrap();
+    let job = db.get_job(embeddings).await.unwrap().unwrap();
+    assert_eq!(job.state, "completed");
+    assert_eq!(job.result.as_deref(), Some(r#"{"frames":3}"#));
+    assert!(db.find_active_job("embeddings").await.unwrap().is_none());
+
+    // A failure below the attempt limit is retried after the delay
+    db.claim_next_job(&["report"]).await.unwrap().unwrap();
+    db.fail_job(report, "boom", Utc::now() + Duration::hours(1))
+        .await
+        .unwrap();
+    let job = db.get_job(report).await.unwrap().unwrap();
+    assert_eq!(job.state, "queued");
+    assert_eq!(job.last_error.as_deref(), Some("boom"));
+    assert!(db.claim_next_job(&["report"]).await.unwrap().is_none());
+
+    // Finished jobs cannot be cancelled
+    assert!(db.cancel_job(report).await.unwrap());
+    assert!(!db.cancel_job(report).await.unwrap());
+    assert!(!db.cancel_job(embeddings).await.unwrap());
+
+    let cancelled = db
+        .list_jobs(Some("cancelled"), None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(cancelled.len(), 1);
+    assert_eq!(cancelled[0].id, report);
+    assert_eq!(db.count_jobs(Some("cancelled"), None).await.unwrap(), 1);
+
+    let all = db
+        .list_jobs(None, None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        all.iter().map(|j| j.id).collect::<Vec<_>>(),
+        vec![embeddings, report]
+    );
+
+    // Jobs interrupted by a restart run again
+    let interrupted = db.insert_job(new_job("report")).await.unwrap();
+    db.claim_next_job(&["report"]).await.unwrap().unwrap();
+    assert_eq!(db.requeue_running_jobs().await.unwrap(), 1);
+    let job = db.claim_next_job(&["report"]).await.unwrap().unwrap();
+    assert_eq!(job.id, interrupted);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_report_templates() {
+    let (db, _path) = create_test_db().await;
+
+    // Built-in templates are seeded by migrations
+    let builtin = db
+        .list_report_templates(Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        db.count_report_templates().await.unwrap(),
+        builtin.len() as i64
+    );
+    assert!(builtin.iter().any(|t| t.name == "standup"));
+    assert!(builtin.iter().any(|t| t.name == "client-billing"));
+
+    let id = db
+        .create_report_template(NewReportTemplate {
+            name: "weekly-review".to_string(),
+            description: None,
+            system_prompt: "Review {start} to {end}".to_string(),
+        })
+        .await
+        .expect("Failed to create template");
+
+    let updated = db
+        .update_report_template(
+            id,
+            NewReportTemplate {
+                name: "weekly-review".to_string(),
+                description: Some("Updated".to_string()),
+                system_prompt: "Review {top_apps}".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated, 1);
+
+    let template = db
+        .get_report_template_by_name("weekly-review")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(template.id, id);
+    assert_eq!(template.system_prompt, "Review {top_apps}");
+
+    assert_eq!(db.delete_report_template(id).await.unwrap(), 1);
+    assert!(db.get_report_template(id).await.unwrap().is_none());
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_top_apps() {
+    let (db, _path) = create_test_db().await;
+
+    let now = Utc::now();
+    for app in ["code", "chrome", "code", "slack", "code", "chrome"] {
+        db.insert_frame(create_test_frame(now, app, "window"))
+            .await
+            .unwrap();
+    }
+
+    let top = db
+        .get_top_apps(now - Duration::minutes(1), now + Duration::minutes(1), 2)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        top,
+        vec![("code".to_string(), 3), ("chrome".to_string(), 2)]
+    );
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_sync_frames() {
+    let (db, _path) = create_test_db().await;
+
+    let machine_id = db.machine_id().await.unwrap();
+    assert_eq!(machine_id.len(), 32);
+    assert_eq!(db.machine_id().await.unwrap(), machine_id);
+
+    let now = Utc::now();
+    let local = db
+        .insert_frame(create_test_frame(now, "code", "main.rs"))
+        .await
+        .unwrap();
+
+    let origin = SyncOrigin {
+        machine_id: "laptop".to_string(),
+        frame_id: 42,
+    };
+    let synced = NewSyncedFrame {
+        frame: create_test_frame(now, "firefox", "Quarterly plan"),
+        ocr: vec![create_test_ocr(0, "roadmap for the quarter")],
+        tags: vec!["planning".to_string(), "planning".to_string()],
+        origin: origin.clone(),
+    };
+    let frame_id = db
+        .insert_synced_frame(synced.clone())
+        .await
+        .unwrap()
+        .expect("first copy is stored");
+    assert!(frame_id > local);
+    // The same frame sent again is skipped
+    assert_eq!(db.insert_synced_frame(synced).await.unwrap(), None);
+
+    let frames = db.get_frames_after_id(0, 10).await.unwrap();
+    assert_eq!(
+        frames.iter().map(|f| f.id).collect::<Vec<_>>(),
+        vec![local, frame_id]
+    );
+    assert_eq!(db.get_frames_after_id(local, 10).await.unwrap().len(), 1);
+
+    let origins = db.get_frame_origins(&[local, frame_id]).await.unwrap();
+    assert_eq!(origins.len(), 1);
+    assert_eq!(origins[&frame_id], origin);
+
+    let ocr = db.get_ocr_text_for_frame(frame_id).await.unwrap();
+    assert_eq!(ocr.len(), 1);
+    assert_eq!(ocr[0].frame_id, frame_id);
+    let tags = db.get_tags_for_frame(frame_id).await.unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].tag_name, "planning");
+
+    // The cursor only moves forward
+    db.record_sync("laptop", 42, 1).await.unwrap();
+    db.record_sync("laptop", 10, 0).await.unwrap();
+    let peers = db.list_sync_peers().await.unwrap();
+    assert_eq!(peers.len(), 1);
+    assert_eq!(peers[0].cursor, 42);
+    assert_eq!(peers[0].frames_received, 1);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_redaction_rules() {
+    let (db, _path) = create_test_db().await;
+
+    let id = db
+        .insert_redaction_rule(NewRedactionRule {
+            pattern: "sk-[a-z0-9]+".to_string(),
+            is_regex: true,
+            blur_images: true,
+        })
+        .await
+        .unwrap();
+    let rule = db.get_redaction_rule(id).await.unwrap().unwrap();
+    assert_eq!(rule.pattern, "sk-[a-z0-9]+");
+    assert!(rule.is_regex && rule.blur_images);
+    assert_eq!(db.list_redaction_rules().await.unwrap().len(), 1);
+    let page = db
+        .list_redaction_rules_page(Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(db.count_redaction_rules().await.unwrap(), 1);
+
+    // Stored text is gone through in ID order
+    let now = Utc::now();
+    let frame_id = db
+        .insert_frame(create_test_frame(now, "terminal", "env"))
+        .await
+        .unwrap();
+    let first = db
+        .insert_ocr_text(create_test_ocr(frame_id, "OPENAI_KEY=sk-abc123"))
+        .await
+        .unwrap();
+    db.insert_ocr_text(create_test_ocr(frame_id, "cargo build"))
+        .await
+        .unwrap();
+    let rows = db.get_ocr_text_after_id(0, 10).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].id, first);
+    assert_eq!(db.get_ocr_text_after_id(first, 10).await.unwrap().len(), 1);
+
+    // Masked text leaves the search index
+    assert_eq!(
+        db.update_ocr_text(first, "OPENAI_KEY=[redacted]")
+            .await
+            .unwrap(),
+        1
+    );
+    let results = db
+        .search_ocr_text("abc123", FrameFilter::default(), Pagination::default())
+        .await
+        .unwrap();
+    assert!(results.is_empty());
+
+    // The frame is embedded again from its new text
+    assert_eq!(
+        db.reset_frame_embeddings(&[frame_id, 999]).await.unwrap(),
+        1
+    );
+    let claimed = db
+        .claim_embedding_frames(10, now - Duration::minutes(10))
+        .await
+        .unwrap();
+    assert_eq!(claimed, vec![frame_id]);
+
+    // A blurred copy takes the frame out of its archive
+    let archive_id = db
+        .insert_frame_archive(
+            NewFrameArchive {
+                day: now.date_naive(),
+                file_path: "captures/cold/archive.tar.zst".to_string(),
+                size_bytes: 2048,
+            },
+            &[NewArchivedFrame {
+                frame_id,
+                entry_offset: 0,
+                entry_length: 2048,
+            }],
+        )
+        .await
+        .unwrap();
+    assert!(db.get_archived_frame(frame_id).await.unwrap().is_some());
+    assert_eq!(
+        db.replace_frame_image(frame_id, "captures/redacted_1.png")
+            .await
+            .unwrap(),
+        1
+    );
+    assert!(db.get_archived_frame(frame_id).await.unwrap().is_none());
+    let frame = db.get_frame(frame_id).await.unwrap().unwrap();
+    assert_eq!(frame.file_path, "captures/redacted_1.png");
+    assert_eq!(frame.chunk_id, None);
+    let archives = db.list_frame_archives().await.unwrap();
+    assert_eq!(archives[0].id, archive_id);
+    assert_eq!(archives[0].frame_count, 0);
+
+    assert_eq!(db.delete_redaction_rule(id).await.unwrap(), 1);
+    assert!(db.list_redaction_rules().await.unwrap().is_empty());
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_purge_frames() {
+    let (db, _path) = create_test_db().await;
+    let now = Utc::now();
+
+    let mut ids = Vec::new();
+    for (i, (app, window, text)) in [
+        ("slack", "Secret plans", "meeting at noon"),
+        ("chrome", "Login", "the password is hunter2"),
+        ("chrome", "Docs", "HUNTER2 again"),
+        ("chrome", "Docs", "other page"),
+        ("code", "main.rs", "fn main()"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut frame = create_test_frame(now - Duration::minutes(10 - i as i64), app, window);
+        frame.file_path = format!("captures/frame_{}.png", i);
+        let id = db.insert_frame(frame).await.unwrap();
+        db.insert_ocr_text(create_test_ocr(id, text)).await.unwrap();
+        ids.push(id);
+    }
+    db.insert_embedding(NewEmbedding {
+        frame_id: ids[1],
+        chunk_text: "the password is hunter2".to_string(),
+        chunk_index: 0,
+        embedding: vec![0.1; 4],
+    })
+    .await
+    .unwrap();
+    let chunk = NewVideoChunk {
+        device_name: "test-device".to_string(),
+        file_path: "captures/chunk.mp4".to_string(),
+        start_time: now - Duration::minutes(8),
+        end_time: now - Duration::minutes(7),
+        duration_ms: 1000,
+        width: 1920,
+        height: 1080,
+        fps: 2,
+    };
+    db.insert_compacted_chunk(chunk, &[ids[2], ids[3]])
+        .await
+        .unwrap();
+
+    // Keywords match OCR text and window titles, ignoring case
+    let keyword = |k: &str| PurgeFilter {
+        keyword: Some(k.to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        db.find_purge_frames(&keyword("hunter2")).await.unwrap(),
+        vec![ids[1], ids[2]]
+    );
+    assert_eq!(
+        db.find_purge_frames(&keyword("secret")).await.unwrap(),
+        vec![ids[0]]
+    );
+    assert!(db
+        .find_purge_frames(&keyword("100%"))
+        .await
+        .unwrap()
+        .is_empty());
+    let filter = PurgeFilter {
+        app: Some("CHROM".to_string()),
+        start_time: Some(now - Duration::minutes(8) - Duration::seconds(30)),
+        ..Default::default()
+    };
+    assert_eq!(
+        db.find_purge_frames(&filter).await.unwrap(),
+        vec![ids[2], ids[3]]
+    );
+    assert!(!filter.is_empty());
+    assert!(PurgeFilter::default().is_empty());
+
+    // The chunk keeps the image of the purged frame while another frame uses it
+    let outcome = db.purge_frames(&[ids[2], ids[1]]).await.unwrap();
+    assert_eq!(outcome.frame_ids, vec![ids[1], ids[2]]);
+    assert_eq!(outcome.ocr_rows, 2);
+    assert_eq!(outcome.embeddings, 1);
+    assert_eq!(outcome.files, vec!["captures/frame_1.png"]);
+    assert_eq!(outcome.retained_files, vec!["captures/chunk.mp4"]);
+    assert!(db.get_frame(ids[1]).await.unwrap().is_none());
+    assert!(db
+        .get_embeddings_for_frame(ids[1])
+        .await
+        .unwrap()
+        .is_empty());
+    let results = db
+        .search_ocr_text("hunter2", FrameFilter::default(), Pagination::default())
+        .await
+        .unwrap();
+    assert!(results.is_empty());
+
+    let outcome = db.purge_frames(&[ids[3]]).await.unwrap();
+    assert_eq!(outcome.files, vec!["captures/chunk.mp4"]);
+    assert!(outcome.retained_files.is_empty());
+
+    // Reports are kept with a stable signing key
+    let key = db.purge_signing_key().await.unwrap();
+    assert_eq!(key.len(), 64);
+    assert_eq!(db.purge_signing_key().await.unwrap(), key);
+    let id = db
+        .insert_purge_report(r#"{"frames_deleted":2}"#, "abc")
+        .await
+        .unwrap();
+    let reports = db.list_purge_reports(Pagination::default()).await.unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].id, id);
+    assert_eq!(reports[0].signature, "abc");
+    assert_eq!(db.count_purge_reports().await.unwrap(), 1);
+
+    db.close().await;
+}
+
+#[tokio::test]
+async fn test_purge_frames_deletes_derived_rows() {
+    let (db, _path) = create_test_db().await;
+    let start = Utc::now() - Duration::hours(4);
+
+    let mut ids = Vec::new();
+    for hour in 0..2 {
+        let timestamp = start + Duration::hours(hour) + Duration::minutes(5);
+        let frame = create_test_frame(timestamp, "chrome", "Login");
+        let id = db.insert_frame(frame).await.unwrap();
+        db.insert_ocr_text(create_test_ocr(id, "the password is hunter2"))
+            .await
+            .unwrap();
+        db.upsert_activity_summary(NewActivitySummary {
+            period_start: start + Duration::hours(hour),
+            period_end: start + Duration::hours(hour + 1),
+            summary: "Logged in with hunter2".to_string(),
+            method: "extractive".to_string(),
+            model: None,
+            frame_count: 1,
+            frame_id: id,
+        })
+        .await
+        .unwrap();
+        ids.push(id);
+    }
+
+    let interaction = |frame_ids: &[i64]| NewAiInteraction {
+        kind: "report".to_string(),
+        provider_url: "http://localhost:11434/v1".to_string(),
+        model: "llama3".to_string(),
+        system_prompt: None,
+        prompt: "Context: the password is hunter2".to_string(),
+        context_chunks: frame_ids
+            .iter()
+            .map(|&frame_id| ContextChunk {
+                frame_id,
+                chunk_index: None,
+            })
+            .collect(),
+        response: Some("Done".to_string()),
+        error: None,
+        latency_ms: 100,
+        prompt_tokens: None,
+        completion_tokens: None,
+        total_tokens: None,
+    };
+    let sent_purged = db
+        .insert_ai_interaction(interaction(&[ids[1], ids[0]]))
+        .await
+        .unwrap();
+    let sent_other = db
+        .insert_ai_interaction(interaction(&[ids[1]]))
+        .await
+        .unwrap();
+    let no_context = db.insert_ai_interaction(interaction(&[])).await.unwrap();
+
+    let outcome = db.purge_frames(&[ids[0]]).await.unwrap();
+    assert_eq!(outcome.summaries, 1);
+    assert_eq!(outcome.ai_interactions, 1);
+
+    let summaries = db
+        .list_activity_summaries(None, None, Pagination::default())
+        .await
+        .unwrap();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].frame_id, ids[1]);
+    assert!(db.get_ai_interaction(sent_purged).await.unwrap().is_none());
+    assert!(db.get_ai_interaction(sent_other).await.unwrap().is_some());
+    assert!(db.get_ai_interaction(no_context).await.unwrap().is_some());
 
     db.close().await;
 }
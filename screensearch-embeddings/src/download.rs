@@ -4,6 +4,7 @@
 
 use crate::{EmbeddingError, Result};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tracing::info;
 
 /// Model file names
@@ -15,8 +16,24 @@ const TOKENIZER_FILE: &str = "tokenizer.json";
 const MODEL_URL: &str = "https://huggingface.co/Xenova/paraphrase-multilingual-MiniLM-L12-v2/resolve/main/onnx/model.onnx";
 const TOKENIZER_URL: &str = "https://huggingface.co/Xenova/paraphrase-multilingual-MiniLM-L12-v2/resolve/main/tokenizer.json";
 
+/// Models directory chosen by the application, see [`set_models_dir`]
+static MODELS_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` as the models directory instead of looking for one
+///
+/// Portable installs keep the model next to the executable even before it
+/// is downloaded. Only the first call has an effect.
+pub fn set_models_dir(dir: PathBuf) {
+    let _ = MODELS_DIR.set(dir);
+}
+
 /// Get the default models directory
 pub fn get_models_dir() -> PathBuf {
+    // 0. Directory set by the application
+    if let Some(dir) = MODELS_DIR.get() {
+        return dir.clone();
+    }
+
     // 1. Check "models" in current working directory (Portable/Bundled)
     let cwd_models = PathBuf::from("models");
     if cwd_models.exists() {
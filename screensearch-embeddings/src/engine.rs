@@ -110,6 +110,7 @@ impl EmbeddingEngine {
     }
 
     /// Generate embeddings for multiple texts (batch processing)
+    #[tracing::instrument(skip_all, fields(texts = texts.len()))]
     pub fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
         if texts.is_empty() {
             return Ok(Vec::new());
@@ -268,6 +269,32 @@ impl EmbeddingEngine {
         embedding
     }
 
+    /// Count the tokens of `text` with the model's tokenizer
+    ///
+    /// Text longer than the tokenizer's truncation length is counted in
+    /// full. Falls back to [`crate::estimate_tokens`] when the tokenizer is
+    /// not loaded.
+    pub fn count_tokens(&self, text: &str) -> usize {
+        let Some(tokenizer) = self.tokenizer.as_ref() else {
+            return crate::estimate_tokens(text);
+        };
+
+        match tokenizer.encode(text, false) {
+            Ok(encoding) => {
+                encoding.len()
+                    + encoding
+                        .get_overflowing()
+                        .iter()
+                        .map(|overflow| overflow.len())
+                        .sum::<usize>()
+            }
+            Err(e) => {
+                warn!("Failed to count tokens, estimating instead: {}", e);
+                crate::estimate_tokens(text)
+            }
+        }
+    }
+
     pub fn config(&self) -> &EmbeddingConfig {
         &self.config
     }
@@ -289,5 +316,6 @@ mod tests {
         let res = engine.embed("test");
         assert!(res.is_ok());
         assert_eq!(res.unwrap().len(), EMBEDDING_DIM);
+        assert_eq!(engine.count_tokens("Hello, world!"), 4);
     }
 }
@@ -35,7 +35,7 @@ mod download;
 
 pub use engine::EmbeddingEngine;
 pub use chunker::TextChunker;
-pub use download::{download_model, get_models_dir, model_exists, needs_download};
+pub use download::{download_model, get_models_dir, model_exists, needs_download, set_models_dir};
 
 /// Embedding dimension for the multilingual MiniLM model
 pub const EMBEDDING_DIM: usize = 384;
@@ -62,6 +62,16 @@ pub enum EmbeddingError {
     IoError(#[from] std::io::Error),
 }
 
+/// Rough token count of `text`, for when no tokenizer is loaded
+///
+/// Counts a token per four ASCII characters and one per other character,
+/// which overestimates rather than underestimates for non-Latin scripts.
+pub fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.chars().filter(char::is_ascii).count();
+    let other = text.chars().count() - ascii;
+    ascii.div_ceil(4) + other
+}
+
 /// Result type alias for embedding operations
 pub type Result<T> = std::result::Result<T, EmbeddingError>;
 
@@ -108,6 +118,13 @@ mod tests {
         assert!(config.normalize);
     }
 
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("Hello, world!"), 4);
+        assert_eq!(estimate_tokens("日本語"), 3);
+    }
+
     #[test]
     fn test_constants() {
         assert_eq!(EMBEDDING_DIM, 384);
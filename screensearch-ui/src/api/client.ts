@@ -105,8 +105,8 @@ class APIClient {
 
   // Tag Endpoints
   async getTags(): Promise<Tag[]> {
-    const { data } = await this.client.get<Tag[]>('/tags');
-    return data;
+    const { data } = await this.client.get<PaginatedResponse<Tag>>('/tags');
+    return data.data;
   }
 
   async createTag(tag: CreateTagRequest): Promise<Tag> {
@@ -171,7 +171,7 @@ export function Timeline() {
       )}
 
       {/* Pagination */}
-      {data && data.pagination.total > limit && (
+      {data && (data.pagination.has_more || page > 0) && (
         <div className="flex items-center justify-center gap-2 pt-6">
           <button
             onClick={() => setPage(Math.max(0, page - 1))}
@@ -183,7 +183,7 @@ export function Timeline() {
 
           <button
             onClick={() => setPage(page + 1)}
-            disabled={(page + 1) * limit >= data.pagination.total}
+            disabled={!data.pagination.has_more}
             className="px-4 py-2 text-sm font-medium text-muted-foreground hover:text-foreground disabled:opacity-50 disabled:cursor-not-allowed transition-colors"
           >
             Next
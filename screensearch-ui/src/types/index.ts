@@ -2,7 +2,9 @@
 export interface PaginationInfo {
   limit: number;
   offset: number;
-  total: number;
+  // Left out when results cannot be counted (regex search, deduplication)
+  total?: number;
+  has_more: boolean;
 }
 
 export interface PaginatedResponse<T> {
@@ -33,6 +35,7 @@ export interface Frame {
   file_path: string;
   app_name: string;
   window_name: string;
+  pinned?: boolean;
   ocr_text: OCRTextContent;
   tags: Tag[];
   thumbnail?: string;
@@ -44,6 +47,7 @@ export interface FrameResponse {
   file_path: string;
   app_name: string;
   window_name: string;
+  pinned?: boolean;
   ocr_text: string;
   tags: Tag[];
   thumbnail?: string;
@@ -153,6 +157,8 @@ export interface Settings {
   excluded_apps: string; // JSON array
   is_paused: number; // 0 or 1
   retention_days: number;
+  audit_retention_days: number;
+  capture_schedule: string; // JSON array of rules like "Mon-Fri 09:00-18:00"
   updated_at: string;
 }
 
@@ -162,6 +168,8 @@ export interface UpdateSettingsRequest {
   excluded_apps: string; // JSON array
   is_paused: number; // 0 or 1
   retention_days: number;
+  audit_retention_days?: number; // omitted = keep current
+  capture_schedule?: string; // JSON array, omitted = keep current
 }
 
 // Legacy type for backwards compatibility
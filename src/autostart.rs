@@ -0,0 +1,185 @@
+//! Start at login
+//!
+//! Registers the binary to run when the user logs in: a value under the
+//! `Run` registry key on Windows (the same one the installer's "Launch on
+//! Windows startup" option writes), or an XDG autostart entry on Linux.
+//! The registered command passes the config file explicitly, since programs
+//! started at login do not run from the application directory.
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Registry key of programs started at login
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// Registry value and desktop entry name
+const APP_NAME: &str = "ScreenSearch";
+
+/// Register `exe` with `args` to start at login
+///
+/// Returns where the registration was written.
+pub fn install(exe: &Path, args: &[String]) -> Result<String> {
+    if cfg!(windows) {
+        let command = windows_command_line(exe, args);
+        reg(&[
+            "add", RUN_KEY, "/v", APP_NAME, "/t", "REG_SZ", "/d", &command, "/f",
+        ])?;
+        Ok(format!(r"{}\{}", RUN_KEY, APP_NAME))
+    } else if cfg!(target_os = "linux") {
+        let path = desktop_entry_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(&path, desktop_entry(exe, args))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path.display().to_string())
+    } else {
+        bail!("Starting at login is only supported on Windows and Linux");
+    }
+}
+
+/// Remove the login registration
+///
+/// Returns whether there was one to remove.
+pub fn uninstall() -> Result<bool> {
+    if cfg!(windows) {
+        if reg(&["query", RUN_KEY, "/v", APP_NAME]).is_err() {
+            return Ok(false);
+        }
+        reg(&["delete", RUN_KEY, "/v", APP_NAME, "/f"])?;
+        Ok(true)
+    } else if cfg!(target_os = "linux") {
+        let path = desktop_entry_path()?;
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("Failed to remove {}", path.display())),
+        }
+    } else {
+        bail!("Starting at login is only supported on Windows and Linux");
+    }
+}
+
+/// Run `reg.exe`, failing on a non-zero exit status
+fn reg(args: &[&str]) -> Result<()> {
+    let output = Command::new("reg")
+        .args(args)
+        .output()
+        .context("Failed to run reg.exe")?;
+    if !output.status.success() {
+        bail!(
+            "reg {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// XDG autostart entry for the current user
+fn desktop_entry_path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir().context("Could not determine the config directory")?;
+    Ok(config_dir.join("autostart").join("screensearch.desktop"))
+}
+
+/// Command line for the `Run` registry value
+fn windows_command_line(exe: &Path, args: &[String]) -> String {
+    std::iter::once(exe.display().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| {
+            if arg.is_empty() || arg.contains([' ', '\t']) {
+                format!("\"{}\"", arg)
+            } else {
+                arg
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Desktop entry starting `exe` with `args`
+fn desktop_entry(exe: &Path, args: &[String]) -> String {
+    let exec = std::iter::once(exe.display().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| desktop_exec_arg(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={}\n\
+         Comment=Capture and index your screen history\n\
+         Exec={}\n\
+         Terminal=false\n\
+         NoDisplay=true\n\
+         X-GNOME-Autostart-enabled=true\n",
+        APP_NAME, exec
+    )
+}
+
+/// Quote an argument for the `Exec` key of a desktop entry
+fn desktop_exec_arg(arg: &str) -> String {
+    const RESERVED: &[char] = &[
+        ' ', '\t', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')', '`',
+    ];
+    if !arg.is_empty() && !arg.contains(RESERVED) {
+        return arg.replace('%', "%%");
+    }
+
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    // Escapes inside quotes are read twice (desktop entry and Exec quoting)
+    quoted.replace('\\', "\\\\").replace('%', "%%")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_command_line_quotes_spaces() {
+        let args = vec![
+            "run".to_string(),
+            "--service".to_string(),
+            "--config".to_string(),
+            r"C:\Program Files\ScreenSearch\config.toml".to_string(),
+        ];
+        assert_eq!(
+            windows_command_line(
+                Path::new(r"C:\Program Files\ScreenSearch\screensearch.exe"),
+                &args
+            ),
+            r#""C:\Program Files\ScreenSearch\screensearch.exe" run --service --config "C:\Program Files\ScreenSearch\config.toml""#
+        );
+    }
+
+    #[test]
+    fn test_desktop_entry_exec_line() {
+        let entry = desktop_entry(
+            Path::new("/opt/screen search/screensearch"),
+            &["run".to_string(), "--service".to_string()],
+        );
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("\nExec=\"/opt/screen search/screensearch\" run --service\n"));
+    }
+
+    #[test]
+    fn test_desktop_exec_arg_escapes() {
+        assert_eq!(
+            desktop_exec_arg("/usr/bin/screensearch"),
+            "/usr/bin/screensearch"
+        );
+        assert_eq!(desktop_exec_arg("100%"), "100%%");
+        assert_eq!(desktop_exec_arg("a$b"), r#""a\\$b""#);
+        assert_eq!(desktop_exec_arg(""), "\"\"");
+    }
+}
@@ -0,0 +1,700 @@
+//! Command-line interface
+//!
+//! Without a subcommand (or with `run`) the binary starts capturing with the
+//! tray icon; `run --service` captures headless for use at login. The other
+//! subcommands work on the database directly, so they can be used from
+//! scripts while the application is running or stopped, except `move-data`,
+//! which needs the application stopped.
+//!
+//! Times accept `now`, `today`, `yesterday`, a relative age such as `30m`,
+//! `2h`, `3d` or `1w`, a date (`2026-10-01`, midnight local time) or an
+//! RFC 3339 timestamp.
+
+use crate::{autostart, single_instance, AppConfig};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
+use screensearch_db::{DatabaseManager, FrameFilter, Pagination, SearchResult};
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use tracing_subscriber::EnvFilter;
+
+/// Frames fetched per page when exporting
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+/// Local screen history with OCR search
+#[derive(Debug, Parser)]
+#[command(name = "screensearch", version)]
+pub struct Cli {
+    /// Configuration file (default: config.toml in the working directory,
+    /// then next to the executable)
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Keep the database, captures, models and logs next to the executable,
+    /// with config.toml read from there too (same as `[storage] portable`)
+    #[arg(long, global = true)]
+    pub portable: bool,
+
+    /// Profile to use, as configured under `[profiles.<name>]` (default: the
+    /// one last switched to)
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Capture the screen with the tray icon (default)
+    Run {
+        /// Run headless without the tray icon, logging only to the log file
+        #[arg(long)]
+        service: bool,
+    },
+
+    /// Search captured text
+    Search(SearchArgs),
+
+    /// Export frames and their text as JSON Lines
+    Export(ExportArgs),
+
+    /// Show database statistics and whether the API is running
+    Status {
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Database maintenance
+    #[command(subcommand)]
+    Db(DbCommand),
+
+    /// Start capturing automatically at login
+    InstallAutostart {
+        /// Start headless without the tray icon (see `run --service`)
+        #[arg(long)]
+        service: bool,
+    },
+
+    /// Stop starting at login
+    UninstallAutostart,
+
+    /// Import a folder of existing screenshots as frames, reading their text
+    ImportImages(ImportImagesArgs),
+
+    /// Move the database, captures and other data to another directory
+    MoveData {
+        /// New data directory, which must not exist yet or be empty
+        new_path: PathBuf,
+
+        /// Delete the old data directory after a successful move
+        #[arg(long)]
+        remove_old: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Text to search for
+    pub query: String,
+
+    /// Only frames captured at or after this time
+    #[arg(long, value_parser = parse_time_arg)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Only frames captured at or before this time
+    #[arg(long, value_parser = parse_time_arg)]
+    pub until: Option<DateTime<Utc>>,
+
+    /// Only frames from this application
+    #[arg(long)]
+    pub app: Option<String>,
+
+    /// Leave out frames from this application (repeatable)
+    #[arg(long = "exclude-app", value_name = "APP")]
+    pub exclude_apps: Vec<String>,
+
+    /// Leave out frames with this tag (repeatable)
+    #[arg(long = "exclude-tag", value_name = "TAG")]
+    pub exclude_tags: Vec<String>,
+
+    /// Also match words with typos or OCR errors
+    #[arg(long)]
+    pub fuzzy: bool,
+
+    /// Maximum number of results
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(i64).range(1..=1000))]
+    pub limit: i64,
+
+    /// Print JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Export frames captured at or after this time
+    #[arg(long, value_parser = parse_time_arg)]
+    pub from: DateTime<Utc>,
+
+    /// Export frames captured at or before this time (default: now)
+    #[arg(long, value_parser = parse_time_arg)]
+    pub to: Option<DateTime<Utc>>,
+
+    /// File to write instead of standard output
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportImagesArgs {
+    /// Folder of the images
+    pub path: PathBuf,
+
+    /// Leave out the images in subfolders
+    #[arg(long)]
+    pub no_recursive: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    /// Reclaim space left by deleted frames
+    Vacuum,
+}
+
+/// Run a subcommand other than `run`
+pub fn execute(command: Command, config: &AppConfig, config_path: &Path) -> Result<()> {
+    // Only warnings on stderr, keeping stdout for results
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        )
+        .init();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to build Tokio runtime")?;
+
+    runtime.block_on(async {
+        match command {
+            Command::Run { .. } => unreachable!("run is handled by main"),
+            Command::Search(args) => search(config, args).await,
+            Command::Export(args) => export(config, args).await,
+            Command::Status { json } => status(config, json).await,
+            Command::Db(DbCommand::Vacuum) => vacuum(config).await,
+            Command::InstallAutostart { service } => {
+                install_autostart(config_path, service, config.is_portable())
+            }
+            Command::UninstallAutostart => uninstall_autostart(),
+            Command::ImportImages(args) => import_images(config, args).await,
+            Command::MoveData {
+                new_path,
+                remove_old,
+            } => move_data(config, config_path, &new_path, remove_old).await,
+        }
+    })
+}
+
+async fn open_database(config: &AppConfig) -> Result<DatabaseManager> {
+    let db_config = config.database_config();
+    DatabaseManager::with_config(db_config.clone())
+        .await
+        .with_context(|| format!("Failed to open database at {}", db_config.path))
+}
+
+async fn search(config: &AppConfig, args: SearchArgs) -> Result<()> {
+    let db = open_database(config).await?;
+    let filter = FrameFilter {
+        start_time: args.since,
+        end_time: args.until,
+        app_name: args.app,
+        exclude_apps: (!args.exclude_apps.is_empty()).then_some(args.exclude_apps),
+        exclude_tags: (!args.exclude_tags.is_empty()).then_some(args.exclude_tags),
+        ..Default::default()
+    };
+    let pagination = Pagination {
+        limit: args.limit,
+        offset: 0,
+    };
+    let results = if args.fuzzy {
+        db.search_ocr_text_fuzzy(&args.query, filter, pagination)
+            .await?
+    } else {
+        db.search_ocr_text(&args.query, filter, pagination).await?
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
+    if results.is_empty() {
+        eprintln!("No matches for \"{}\"", args.query);
+        return Ok(());
+    }
+
+    let highlight = std::io::stdout().is_terminal();
+    for result in &results {
+        print_result(result, highlight);
+    }
+    Ok(())
+}
+
+fn print_result(result: &SearchResult, highlight: bool) {
+    let frame = &result.frame;
+    println!(
+        "{}  {}  {}  (frame {})",
+        frame
+            .timestamp
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S"),
+        frame.active_process.as_deref().unwrap_or("unknown"),
+        frame.active_window.as_deref().unwrap_or_default(),
+        frame.id
+    );
+
+    let snippets: Vec<String> = if result.highlights.is_empty() {
+        result
+            .ocr_matches
+            .iter()
+            .take(1)
+            .map(|ocr| ocr.text.chars().take(120).collect())
+            .collect()
+    } else {
+        result
+            .highlights
+            .iter()
+            .map(|h| format_snippet(&h.snippet, highlight))
+            .collect()
+    };
+    for snippet in snippets {
+        println!("    {}", snippet.replace('\n', " "));
+    }
+}
+
+/// Replace `<mark>` tags with bold text, or drop them when not on a terminal
+fn format_snippet(snippet: &str, highlight: bool) -> String {
+    let (open, close) = if highlight {
+        ("\x1b[1m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+    snippet.replace("<mark>", open).replace("</mark>", close)
+}
+
+async fn export(config: &AppConfig, args: ExportArgs) -> Result<()> {
+    let end = args.to.unwrap_or_else(Utc::now);
+    if args.from > end {
+        bail!("--from must be before --to");
+    }
+
+    let db = open_database(config).await?;
+    let mut writer: BufWriter<Box<dyn Write>> = BufWriter::new(match &args.output {
+        Some(path) => Box::new(
+            std::fs::File::create(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?,
+        ),
+        None => Box::new(std::io::stdout().lock()),
+    });
+
+    let (mut after_time, mut after_id) = (args.from, 0);
+    let mut exported = 0usize;
+    loop {
+        let frames = db
+            .get_frames_after(after_time, after_id, end, None, EXPORT_PAGE_SIZE)
+            .await?;
+        let Some(last) = frames.last() else {
+            break;
+        };
+        (after_time, after_id) = (last.timestamp, last.id);
+
+        for frame in frames {
+            let text: Vec<String> = db
+                .get_ocr_text_for_frame(frame.id)
+                .await?
+                .into_iter()
+                .map(|ocr| ocr.text)
+                .collect();
+            let line = serde_json::json!({
+                "frame_id": frame.id,
+                "timestamp": frame.timestamp,
+                "monitor_index": frame.monitor_index,
+                "app_name": frame.active_process,
+                "window_title": frame.active_window,
+                "browser_url": frame.browser_url,
+                "file_path": frame.file_path,
+                "text": text.join("\n"),
+            });
+            serde_json::to_writer(&mut writer, &line)?;
+            writer.write_all(b"\n")?;
+            exported += 1;
+        }
+    }
+    writer.flush()?;
+
+    if let Some(path) = &args.output {
+        eprintln!("Exported {} frames to {}", exported, path.display());
+    }
+    Ok(())
+}
+
+async fn status(config: &AppConfig, json: bool) -> Result<()> {
+    let db = open_database(config).await?;
+    let db_path = config.database_config().path;
+    let stats = db.get_statistics().await?;
+    let size = database_size(Path::new(&db_path));
+    let api_url = config.api_url();
+    let api_running = api_is_running(config, &api_url).await;
+
+    if json {
+        let status = serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "database_path": db_path,
+            "database_size_bytes": size,
+            "frame_count": stats.frame_count,
+            "ocr_count": stats.ocr_count,
+            "tag_count": stats.tag_count,
+            "oldest_frame": stats.oldest_frame,
+            "newest_frame": stats.newest_frame,
+            "api_url": api_url,
+            "api_running": api_running,
+        });
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    let format_time = |time: Option<DateTime<Utc>>| {
+        time.map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    println!("ScreenSearch v{}", env!("CARGO_PKG_VERSION"));
+    println!(
+        "Database:  {} ({:.1} MB)",
+        db_path,
+        size as f64 / (1024.0 * 1024.0)
+    );
+    println!("Frames:    {}", stats.frame_count);
+    println!("OCR text:  {}", stats.ocr_count);
+    println!("Tags:      {}", stats.tag_count);
+    println!("Oldest:    {}", format_time(stats.oldest_frame));
+    println!("Newest:    {}", format_time(stats.newest_frame));
+    println!(
+        "API:       {} ({})",
+        api_url,
+        if api_running {
+            "running"
+        } else {
+            "not running"
+        }
+    );
+    Ok(())
+}
+
+/// Whether the API of a running instance answers its health check
+async fn api_is_running(config: &AppConfig, api_url: &str) -> bool {
+    if !config.api.local_socket.is_empty() {
+        return false;
+    }
+
+    let client = match reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(2))
+        .danger_accept_invalid_certs(config.api.tls.self_signed)
+        .build()
+    {
+        Ok(client) => client,
+        Err(_) => return false,
+    };
+    client
+        .get(format!("{}/api/health", api_url))
+        .send()
+        .await
+        .is_ok_and(|response| response.status().is_success())
+}
+
+async fn vacuum(config: &AppConfig) -> Result<()> {
+    let db = open_database(config).await?;
+    let db_path = PathBuf::from(config.database_config().path);
+
+    let before = database_size(&db_path);
+    db.vacuum().await.context("Failed to vacuum database")?;
+    db.close().await;
+    let after = database_size(&db_path);
+
+    let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    println!(
+        "Vacuumed {}: {:.1} MB -> {:.1} MB",
+        db_path.display(),
+        mb(before),
+        mb(after)
+    );
+    Ok(())
+}
+
+fn install_autostart(config_path: &Path, service: bool, portable: bool) -> Result<()> {
+    let exe = std::env::current_exe().context("Could not determine the executable path")?;
+    let mut args = vec!["run".to_string()];
+    if service {
+        args.push("--service".to_string());
+    }
+    if portable {
+        args.push("--portable".to_string());
+    }
+    // Programs started at login run from another directory
+    if config_path.exists() {
+        let config_path = std::path::absolute(config_path)
+            .with_context(|| format!("Failed to resolve {}", config_path.display()))?;
+        args.push("--config".to_string());
+        args.push(config_path.display().to_string());
+    }
+
+    let location = autostart::install(&exe, &args)?;
+    println!("ScreenSearch will start at login ({})", location);
+    Ok(())
+}
+
+fn uninstall_autostart() -> Result<()> {
+    if autostart::uninstall()? {
+        println!("ScreenSearch will no longer start at login");
+    } else {
+        println!("ScreenSearch was not set to start at login");
+    }
+    Ok(())
+}
+
+async fn import_images(config: &AppConfig, args: ImportImagesArgs) -> Result<()> {
+    use screensearch_api::capture_store::CaptureStorage;
+    use screensearch_api::image_import::{find_images, import_image, ImportOutcome};
+
+    let images = find_images(&args.path, !args.no_recursive)
+        .with_context(|| format!("Failed to read {}", args.path.display()))?;
+    if images.is_empty() {
+        bail!("No images found in {}", args.path.display());
+    }
+
+    let db = open_database(config).await?;
+    let storage = CaptureStorage::new(&config.capture_storage_config()?)
+        .context("Failed to open capture storage")?;
+    let engine = screensearch_capture::OcrEngine::new()
+        .await
+        .context("Failed to initialize OCR")?;
+
+    let (mut imported, mut skipped, mut failed) = (0, 0, 0);
+    for (i, path) in images.iter().enumerate() {
+        match import_image(&db, &storage, &engine, path).await {
+            Ok(ImportOutcome::Imported(_)) => imported += 1,
+            Ok(ImportOutcome::Skipped) => skipped += 1,
+            Err(e) => {
+                eprintln!("\rFailed to import {}: {:#}", path.display(), e);
+                failed += 1;
+            }
+        }
+        eprint!("\rProcessed {}/{} images", i + 1, images.len());
+    }
+    eprintln!();
+
+    println!(
+        "Imported {} images from {} ({} already imported, {} failed)",
+        imported,
+        args.path.display(),
+        skipped,
+        failed
+    );
+    if imported > 0 {
+        println!("Their embeddings are generated while ScreenSearch runs with embeddings enabled");
+    }
+    Ok(())
+}
+
+async fn move_data(
+    config: &AppConfig,
+    config_path: &Path,
+    new_path: &Path,
+    remove_old: bool,
+) -> Result<()> {
+    use screensearch_api::data_move;
+
+    let from = config
+        .movable_data_dir()
+        .map_err(|reason| anyhow::anyhow!("The data directory cannot be moved: {}", reason))?;
+    let to = std::path::absolute(new_path)
+        .with_context(|| format!("Failed to resolve {}", new_path.display()))?;
+    // Held until the move is done, so the application cannot start meanwhile
+    let Some(_instance) = single_instance::acquire()? else {
+        bail!("ScreenSearch is running: quit it first, or use POST /api/admin/move-data");
+    };
+
+    let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    eprintln!("Moving {} to {}", from.display(), to.display());
+    let report = data_move::move_data(&from, &to, &config.database.path, config_path, |progress| {
+        eprint!(
+            "\rCopied {}/{} files ({:.1} of {:.1} MB)",
+            progress.files_copied,
+            progress.files_total,
+            mb(progress.bytes_copied),
+            mb(progress.bytes_total)
+        );
+    })
+    .await;
+    eprintln!();
+    let report = report?;
+
+    println!(
+        "Moved {} files ({:.1} MB) to {}, rewriting {} paths in {} database(s)",
+        report.files,
+        mb(report.bytes),
+        report.to,
+        report.paths_rewritten,
+        report.databases
+    );
+    if remove_old {
+        data_move::remove_old(&from)?;
+        println!("Deleted {}", from.display());
+    } else {
+        println!("{} was kept and can be deleted", from.display());
+    }
+    Ok(())
+}
+
+/// Size of the database file and its write-ahead log
+fn database_size(path: &Path) -> u64 {
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    [path, Path::new(&wal)]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn parse_time_arg(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_time(value, Local::now())
+}
+
+/// Parse a time argument relative to `now`
+fn parse_time(value: &str, now: DateTime<Local>) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    let midnight = |date: NaiveDate| {
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|time| time.and_local_timezone(Local).earliest())
+            .map(|time| time.with_timezone(&Utc))
+            .ok_or_else(|| format!("No local midnight on {}", date))
+    };
+
+    match value.to_lowercase().as_str() {
+        "now" => return Ok(now.with_timezone(&Utc)),
+        "today" => return midnight(now.date_naive()),
+        "yesterday" => return midnight(now.date_naive() - Duration::days(1)),
+        _ => {}
+    }
+
+    if let Some(unit) = value.chars().last().filter(|c| c.is_ascii_alphabetic()) {
+        if let Ok(amount) = value[..value.len() - 1].parse::<i64>() {
+            let age = match unit {
+                'm' => Duration::minutes(amount),
+                'h' => Duration::hours(amount),
+                'd' => Duration::days(amount),
+                'w' => Duration::weeks(amount),
+                _ => return Err(format!("Unknown time unit '{}' in '{}'", unit, value)),
+            };
+            return Ok((now - age).with_timezone(&Utc));
+        }
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return midnight(date);
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| {
+            format!(
+                "Invalid time '{}': use now, today, yesterday, an age like 2h or 3d, \
+                 a date (YYYY-MM-DD) or an RFC 3339 timestamp",
+                value
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 16, 14, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_named_times() {
+        let now = now();
+        assert_eq!(parse_time("now", now).unwrap(), now.with_timezone(&Utc));
+        assert_eq!(
+            parse_time("Today", now).unwrap(),
+            Local.with_ymd_and_hms(2026, 10, 16, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_time("yesterday", now).unwrap(),
+            Local.with_ymd_and_hms(2026, 10, 15, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_relative_times() {
+        let now = now();
+        assert_eq!(parse_time("30m", now).unwrap(), now - Duration::minutes(30));
+        assert_eq!(parse_time("2h", now).unwrap(), now - Duration::hours(2));
+        assert_eq!(parse_time("3d", now).unwrap(), now - Duration::days(3));
+        assert_eq!(parse_time("1w", now).unwrap(), now - Duration::weeks(1));
+        assert!(parse_time("5y", now).is_err());
+    }
+
+    #[test]
+    fn test_parse_absolute_times() {
+        let now = now();
+        assert_eq!(
+            parse_time("2026-10-01", now).unwrap(),
+            Local.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_time("2026-10-01T08:15:00Z", now).unwrap(),
+            Utc.with_ymd_and_hms(2026, 10, 1, 8, 15, 0).unwrap()
+        );
+        assert!(parse_time("last tuesday", now).is_err());
+    }
+
+    #[test]
+    fn test_format_snippet() {
+        assert_eq!(
+            format_snippet("the <mark>invoice</mark> total", false),
+            "the invoice total"
+        );
+        assert_eq!(format_snippet("<mark>a</mark>", true), "\x1b[1ma\x1b[0m");
+    }
+
+    #[test]
+    fn test_cli_parses_subcommands() {
+        let cli =
+            Cli::try_parse_from(["screensearch", "search", "invoice", "--since", "2d"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Search(SearchArgs { ref query, since: Some(_), limit: 20, .. }))
+                if query == "invoice"
+        ));
+
+        let cli = Cli::try_parse_from(["screensearch", "db", "vacuum"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Db(DbCommand::Vacuum))));
+
+        let cli = Cli::try_parse_from(["screensearch", "run", "--service", "--config", "a.toml"])
+            .unwrap();
+        assert!(matches!(cli.command, Some(Command::Run { service: true })));
+        assert_eq!(cli.config, Some(PathBuf::from("a.toml")));
+
+        assert!(Cli::try_parse_from(["screensearch"])
+            .unwrap()
+            .command
+            .is_none());
+        assert!(Cli::try_parse_from(["screensearch", "export"]).is_err());
+    }
+}
@@ -0,0 +1,332 @@
+//! Config file hot-reload
+//!
+//! Watches `config.toml` and applies edits without restarting capture. The
+//! capture interval and monitors, excluded applications, the sensitive
+//! content filter, the memory budget, battery throttling, the log level and
+//! the storage settings (except the data directory and portable mode) take
+//! effect on the next frame, as do the active profile's privacy settings. Other settings are
+//! read once at startup, so changing them logs a warning that a restart is
+//! needed.
+//!
+//! A file that fails to parse is ignored and the running configuration is
+//! kept, so a half-saved edit never takes the application down.
+
+use crate::AppConfig;
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use screensearch_capture::CaptureControl;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch};
+use tracing::{debug, info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Handle replacing the log level filter of the running subscriber
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Quiet period after the last change before reloading, since editors often
+/// save a file in several writes
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Reloads the config file and applies changes to the running application
+pub struct ConfigReloader {
+    path: PathBuf,
+    config: watch::Sender<AppConfig>,
+    capture: Arc<CaptureControl>,
+    log_filter: LogFilterHandle,
+}
+
+impl ConfigReloader {
+    /// Create a reloader publishing new configurations to `config`
+    pub fn new(
+        path: PathBuf,
+        config: watch::Sender<AppConfig>,
+        capture: Arc<CaptureControl>,
+        log_filter: LogFilterHandle,
+    ) -> Self {
+        Self {
+            path,
+            config,
+            capture,
+            log_filter,
+        }
+    }
+
+    /// Watch the config file until shutdown
+    ///
+    /// Watches the containing directory, since editors commonly save by
+    /// replacing the file, which ends a watch on the file itself.
+    pub fn spawn(self, mut shutdown: broadcast::Receiver<()>) -> Result<()> {
+        let file_name = self
+            .path
+            .file_name()
+            .map(OsString::from)
+            .context("Config path has no file name")?;
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (changed_tx, mut changed_rx) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) if is_config_change(&event, &file_name) => {
+                    let _ = changed_tx.send(());
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Config watcher error: {}", e),
+            })
+            .context("Failed to create config watcher")?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+
+        info!("Watching {} for changes", self.path.display());
+        tokio::spawn(async move {
+            // Dropping the watcher stops it
+            let _watcher = watcher;
+            loop {
+                tokio::select! {
+                    Some(()) = changed_rx.recv() => {
+                        while let Ok(Some(())) =
+                            tokio::time::timeout(DEBOUNCE, changed_rx.recv()).await
+                        {}
+                        self.reload();
+                    }
+                    _ = shutdown.recv() => break,
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Re-read the config file and apply what changed
+    fn reload(&self) {
+        let mut new = match AppConfig::from_file(&self.path) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Keeping the running configuration: {:#}", e);
+                return;
+            }
+        };
+        new.apply_profile();
+
+        let old = self.config.borrow().clone();
+        if new == old {
+            debug!("{} changed without setting changes", self.path.display());
+            return;
+        }
+
+        self.apply(&old, &new);
+        let sections = restart_required(&old, &new);
+        if !sections.is_empty() {
+            warn!(
+                "Changes to [{}] take effect after a restart",
+                sections.join("], [")
+            );
+        }
+        self.config.send_replace(new);
+    }
+
+    /// Apply live settings to the capture pipeline and logging
+    ///
+    /// Storage and sensitive content settings are read from the published
+    /// configuration for every frame, so they need no action here.
+    fn apply(&self, old: &AppConfig, new: &AppConfig) {
+        if new.capture.interval_ms != old.capture.interval_ms {
+            info!("Capture interval set to {}ms", new.capture.interval_ms);
+            self.capture.set_interval_ms(new.capture.interval_ms);
+        }
+        if new.capture.monitor_indices != old.capture.monitor_indices {
+            info!("Capturing monitors {:?}", new.capture.monitor_indices);
+            self.capture
+                .set_monitor_indices(new.capture.monitor_indices.clone());
+        }
+        if new.privacy.excluded_apps != old.privacy.excluded_apps {
+            info!("Excluded apps set to {:?}", new.privacy.excluded_apps);
+            self.capture
+                .set_config_excluded_apps(new.privacy.excluded_apps.clone());
+        }
+        if new.privacy.sensitive_content != old.privacy.sensitive_content {
+            info!(
+                "Sensitive content action set to {:?}",
+                new.privacy.sensitive_content
+            );
+        }
+        if new.storage != old.storage {
+            info!("Storage settings updated: {:?}", new.storage);
+            self.capture
+                .disk_space()
+                .set_thresholds_mb(new.storage.cleanup_below_mb, new.storage.pause_below_mb);
+        }
+        if new.performance.max_memory_mb != old.performance.max_memory_mb {
+            info!("Memory budget set to {} MB", new.performance.max_memory_mb);
+            self.capture
+                .memory_budget()
+                .set_limit_mb(new.performance.max_memory_mb);
+        }
+        if new.performance.battery_interval_ms != old.performance.battery_interval_ms
+            || new.performance.battery_pause_workers != old.performance.battery_pause_workers
+        {
+            info!(
+                "Battery throttling set to {} ms, pause workers: {}",
+                new.performance.battery_interval_ms, new.performance.battery_pause_workers
+            );
+            self.capture.power().set_settings(
+                new.performance.battery_interval_ms,
+                new.performance.battery_pause_workers,
+            );
+        }
+        if new.logging.level != old.logging.level {
+            self.set_log_level(&new.logging.level);
+        }
+    }
+
+    /// Replace the log filter, unless `RUST_LOG` overrides the config file
+    fn set_log_level(&self, level: &str) {
+        if std::env::var_os(EnvFilter::DEFAULT_ENV).is_some() {
+            warn!(
+                "Not changing the log level to '{}': {} is set",
+                level,
+                EnvFilter::DEFAULT_ENV
+            );
+            return;
+        }
+
+        match EnvFilter::try_new(level) {
+            Ok(filter) => match self.log_filter.reload(filter) {
+                Ok(()) => info!("Log level set to '{}'", level),
+                Err(e) => warn!("Failed to change the log level: {}", e),
+            },
+            Err(e) => warn!("Invalid log level '{}': {}", level, e),
+        }
+    }
+}
+
+/// Whether a file system event modified the config file
+fn is_config_change(event: &notify::Event, file_name: &OsString) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Any
+    ) && event
+        .paths
+        .iter()
+        .any(|path| path.file_name() == Some(file_name.as_os_str()))
+}
+
+/// Config sections with changes that only apply after a restart
+fn restart_required(old: &AppConfig, new: &AppConfig) -> Vec<&'static str> {
+    // Copy the live settings over so only the rest is compared
+    let mut rest = new.clone();
+    rest.capture.interval_ms = old.capture.interval_ms;
+    rest.capture.monitor_indices = old.capture.monitor_indices.clone();
+    rest.privacy.excluded_apps = old.privacy.excluded_apps.clone();
+    rest.privacy.sensitive_content = old.privacy.sensitive_content;
+    rest.logging.level = old.logging.level.clone();
+    rest.storage = old.storage.clone();
+    rest.storage.data_dir = new.storage.data_dir.clone();
+    rest.storage.portable = new.storage.portable;
+    rest.storage.backend = new.storage.backend.clone();
+    rest.storage.remote = new.storage.remote.clone();
+    rest.performance.max_memory_mb = old.performance.max_memory_mb;
+    rest.performance.battery_interval_ms = old.performance.battery_interval_ms;
+    rest.performance.battery_pause_workers = old.performance.battery_pause_workers;
+
+    [
+        ("capture", rest.capture != old.capture),
+        ("ocr", rest.ocr != old.ocr),
+        ("api", rest.api != old.api),
+        ("database", rest.database != old.database),
+        ("privacy", rest.privacy != old.privacy),
+        ("performance", rest.performance != old.performance),
+        ("logging", rest.logging != old.logging),
+        ("embeddings", rest.embeddings != old.embeddings),
+        ("reports", rest.reports != old.reports),
+        ("summaries", rest.summaries != old.summaries),
+        ("documents", rest.documents != old.documents),
+        ("compaction", rest.compaction != old.compaction),
+        ("archival", rest.archival != old.archival),
+        (
+            "markdown_export",
+            rest.markdown_export != old.markdown_export,
+        ),
+        ("time_tracking", rest.time_tracking != old.time_tracking),
+        ("storage", rest.storage != old.storage),
+        ("updates", rest.updates != old.updates),
+        ("hotkeys", rest.hotkeys != old.hotkeys),
+        ("profiles", !rest.profiles.keys().eq(old.profiles.keys())),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(section, _)| section)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+    use screensearch_capture::SensitiveAction;
+
+    #[test]
+    fn test_live_settings_need_no_restart() {
+        let mut old = AppConfig::default();
+        old.profiles.insert("work".to_string(), Default::default());
+        let mut new = old.clone();
+        new.capture.interval_ms = 10_000;
+        new.capture.monitor_indices = vec![1];
+        new.privacy.excluded_apps.push("Signal".to_string());
+        new.privacy.sensitive_content = SensitiveAction::Blur;
+        new.logging.level = "debug".to_string();
+        new.storage.jpeg_quality = 60;
+        new.performance.max_memory_mb = 1024;
+        new.performance.battery_interval_ms = 30_000;
+        new.performance.battery_pause_workers = false;
+        new.profiles.get_mut("work").unwrap().excluded_apps = Some(vec!["Slack".to_string()]);
+
+        assert!(restart_required(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn test_other_settings_need_restart() {
+        let old = AppConfig::default();
+        let mut new = old.clone();
+        new.capture.interval_ms = 10_000;
+        new.capture.include_cursor = !old.capture.include_cursor;
+        new.api.port = 4000;
+        new.logging.log_to_file = !old.logging.log_to_file;
+        new.storage.data_dir = "D:\\ScreenSearch".to_string();
+        new.profiles.insert("work".to_string(), Default::default());
+
+        assert_eq!(
+            restart_required(&old, &new),
+            vec!["capture", "api", "logging", "storage", "profiles"]
+        );
+    }
+
+    #[test]
+    fn test_config_change_events() {
+        let file_name = OsString::from("config.toml");
+        let event = |kind, path: &str| notify::Event::new(kind).add_path(PathBuf::from(path));
+
+        assert!(is_config_change(
+            &event(EventKind::Modify(ModifyKind::Any), "./config.toml"),
+            &file_name
+        ));
+        assert!(is_config_change(
+            &event(EventKind::Create(CreateKind::File), "/app/config.toml"),
+            &file_name
+        ));
+        assert!(!is_config_change(
+            &event(EventKind::Modify(ModifyKind::Any), "./screensearch.log"),
+            &file_name
+        ));
+        assert!(!is_config_change(
+            &event(EventKind::Access(AccessKind::Any), "./config.toml"),
+            &file_name
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+//! Disk space monitor
+//!
+//! Checks the free space of the volumes holding the database and the frame
+//! images every [`CHECK_INTERVAL`]. Below `storage.cleanup_below_mb` the
+//! oldest frames and their images are deleted ahead of the retention period;
+//! below `storage.pause_below_mb` capture is suspended until space is freed
+//! (see [`DiskSpace`](screensearch_capture::DiskSpace)), which `/health` and
+//! the tray report. Captures stored in a bucket are not deleted to free space.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use screensearch_api::capture_store::CaptureStorage;
+use screensearch_capture::CaptureControl;
+use screensearch_db::DatabaseManager;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// How often free space is measured
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Frames this recent are never deleted to free space
+const KEEP_RECENT: chrono::Duration = chrono::Duration::hours(24);
+
+/// Frames deleted before free space is measured again
+const CLEANUP_BATCH: i64 = 500;
+
+const MB: u64 = 1024 * 1024;
+
+/// Watch the free space of the volumes holding `paths` until shutdown
+pub async fn run(
+    control: Arc<CaptureControl>,
+    db: Arc<DatabaseManager>,
+    storage: Arc<CaptureStorage>,
+    paths: Vec<PathBuf>,
+    mut shutdown: broadcast::Receiver<()>,
+) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.recv() => break,
+        }
+
+        if !control.disk_space().is_enabled() {
+            continue;
+        }
+        if let Err(e) = check(&control, &db, &storage, &paths).await {
+            warn!("Disk space check failed: {:#}", e);
+        }
+    }
+}
+
+/// Measure free space and delete old frames when it is low
+async fn check(
+    control: &CaptureControl,
+    db: &DatabaseManager,
+    storage: &CaptureStorage,
+    paths: &[PathBuf],
+) -> Result<()> {
+    let disk = control.disk_space();
+    let (mut free, mut total) = lowest_free_space(paths)?;
+    if !disk.update(free, total) {
+        return Ok(());
+    }
+
+    // Captures in a bucket take no local space, so deleting them frees none
+    if storage.is_remote() {
+        return Ok(());
+    }
+
+    let target = disk.cleanup_target();
+    let before = Utc::now() - KEEP_RECENT;
+    let mut deleted = 0;
+    while free < target {
+        let (count, files) = db.delete_oldest_frames(before, CLEANUP_BATCH).await?;
+        if count == 0 {
+            break;
+        }
+        deleted += count;
+
+        for file in files {
+            if let Err(e) = storage.delete(&file).await {
+                debug!("{:#}", e);
+            }
+        }
+        (free, total) = lowest_free_space(paths)?;
+    }
+    disk.update(free, total);
+
+    if deleted == 0 {
+        debug!(
+            free_mb = free / MB,
+            "Low on disk space, but only frames from the last day are left"
+        );
+        return Ok(());
+    }
+    disk.record_cleanup(deleted);
+    if free < target {
+        warn!(
+            free_mb = free / MB,
+            "Low on disk space: deleted {} frames, only frames from the last day are left", deleted
+        );
+    } else {
+        info!(
+            free_mb = free / MB,
+            "Low on disk space: deleted the {} oldest frames", deleted
+        );
+    }
+    Ok(())
+}
+
+/// Free and total bytes of the fullest volume holding one of `paths`
+fn lowest_free_space(paths: &[PathBuf]) -> Result<(u64, u64)> {
+    paths
+        .iter()
+        .map(|path| free_space(existing_ancestor(path)))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .min_by_key(|(free, _)| *free)
+        .context("No paths to check")
+}
+
+/// The path itself or its closest existing parent, since the captures
+/// directory is only created with the first frame
+fn existing_ancestor(path: &Path) -> &Path {
+    path.ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or(Path::new("."))
+}
+
+/// Free bytes available to this user and total bytes of the volume at `path`
+#[cfg(target_os = "windows")]
+fn free_space(path: &Path) -> Result<(u64, u64)> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let path_w = HSTRING::from(path.as_os_str());
+    let (mut free, mut total) = (0u64, 0u64);
+    unsafe {
+        GetDiskFreeSpaceExW(
+            PCWSTR(path_w.as_ptr()),
+            Some(&mut free),
+            Some(&mut total),
+            None,
+        )
+    }
+    .with_context(|| format!("Failed to get the free space of {}", path.display()))?;
+    Ok((free, total))
+}
+
+/// Free bytes available to this user and total bytes of the volume at `path`
+#[cfg(not(target_os = "windows"))]
+fn free_space(path: &Path) -> Result<(u64, u64)> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path_c = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path_c.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("Failed to get the free space of {}", path.display()));
+    }
+
+    let block_size = stat.f_frsize as u64;
+    Ok((
+        stat.f_bavail as u64 * block_size,
+        stat.f_blocks as u64 * block_size,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_space_of_missing_directory() {
+        let missing = std::env::temp_dir()
+            .join("screensearch-missing")
+            .join("captures");
+        let (free, total) = lowest_free_space(&[missing, std::env::temp_dir()]).unwrap();
+        assert!(total > 0);
+        assert!(free <= total);
+    }
+}
@@ -0,0 +1,310 @@
+//! Global hotkeys
+//!
+//! The `[hotkeys]` shortcuts are registered system-wide with `RegisterHotKey`
+//! on a thread of their own, which waits for them in a message loop and
+//! forwards each press as a [`HotkeyAction`]. The application carries the
+//! actions out with the same capture controls as the tray menu and the API.
+//! Other platforms have no global hotkeys.
+
+use anyhow::{bail, Result};
+use std::fmt;
+use std::str::FromStr;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::warn;
+
+/// What a hotkey does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Capture every monitor now and tag the frames
+    CaptureNow,
+    /// Open the web interface
+    OpenSearch,
+    /// Pause or resume capture
+    TogglePause,
+}
+
+/// `RegisterHotKey` modifier flags
+const MOD_ALT: u32 = 0x1;
+const MOD_CONTROL: u32 = 0x2;
+const MOD_SHIFT: u32 = 0x4;
+const MOD_WIN: u32 = 0x8;
+
+/// A key combination such as "Ctrl+Shift+S"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hotkey {
+    /// `MOD_*` flags
+    pub modifiers: u32,
+    /// Windows virtual-key code
+    pub key: u32,
+    /// As written in the config, for log messages
+    name: String,
+}
+
+impl FromStr for Hotkey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = 0;
+        let mut key = None;
+        for part in s.split('+').map(str::trim) {
+            let modifier = match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => MOD_CONTROL,
+                "alt" => MOD_ALT,
+                "shift" => MOD_SHIFT,
+                "win" | "super" | "meta" => MOD_WIN,
+                _ => 0,
+            };
+            if modifier != 0 {
+                modifiers |= modifier;
+            } else if key.is_some() {
+                bail!("Hotkey '{}' has more than one key", s);
+            } else {
+                let Some(code) = virtual_key(part) else {
+                    bail!("Unknown key '{}' in hotkey '{}'", part, s);
+                };
+                key = Some(code);
+            }
+        }
+
+        let Some(key) = key else {
+            bail!("Hotkey '{}' has no key", s);
+        };
+        // A bare key would be taken from every application
+        if modifiers == 0 {
+            bail!("Hotkey '{}' needs Ctrl, Alt, Shift or Win", s);
+        }
+        Ok(Self {
+            modifiers,
+            key,
+            name: s.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Virtual-key code of a key name: a letter, digit, F1 to F24 or a named key
+fn virtual_key(name: &str) -> Option<u32> {
+    let upper = name.to_ascii_uppercase();
+    let mut chars = upper.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        // Letters and digits are their ASCII codes
+        return c.is_ascii_alphanumeric().then_some(c as u32);
+    }
+    if let Some(n) = upper.strip_prefix('F').and_then(|n| n.parse::<u32>().ok()) {
+        return (1..=24).contains(&n).then_some(0x6F + n);
+    }
+
+    Some(match upper.as_str() {
+        "SPACE" => 0x20,
+        "ENTER" | "RETURN" => 0x0D,
+        "TAB" => 0x09,
+        "ESC" | "ESCAPE" => 0x1B,
+        "PAGEUP" => 0x21,
+        "PAGEDOWN" => 0x22,
+        "END" => 0x23,
+        "HOME" => 0x24,
+        "LEFT" => 0x25,
+        "UP" => 0x26,
+        "RIGHT" => 0x27,
+        "DOWN" => 0x28,
+        "PRINTSCREEN" => 0x2C,
+        "INSERT" => 0x2D,
+        "DELETE" | "DEL" => 0x2E,
+        _ => return None,
+    })
+}
+
+/// Parse the configured hotkeys, skipping empty and invalid ones
+pub fn bindings<'a>(
+    keys: impl IntoIterator<Item = (HotkeyAction, &'a str)>,
+) -> Vec<(Hotkey, HotkeyAction)> {
+    keys.into_iter()
+        .filter(|(_, key)| !key.trim().is_empty())
+        .filter_map(|(action, key)| match key.parse() {
+            Ok(hotkey) => Some((hotkey, action)),
+            Err(e) => {
+                warn!("Ignoring hotkey for {:?}: {:#}", action, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Registered hotkeys; unregistered when dropped
+pub struct Hotkeys {
+    _registration: platform::Registration,
+}
+
+/// Register the hotkeys, sending the action of each press to `actions`
+///
+/// Hotkeys another application holds already are skipped with a warning.
+pub fn register(
+    bindings: Vec<(Hotkey, HotkeyAction)>,
+    actions: UnboundedSender<HotkeyAction>,
+) -> Result<Hotkeys> {
+    Ok(Hotkeys {
+        _registration: platform::register(bindings, actions)?,
+    })
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{Hotkey, HotkeyAction};
+    use anyhow::{Context, Result};
+    use tokio::sync::mpsc::UnboundedSender;
+    use tracing::{info, warn};
+    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+    use windows::Win32::System::Threading::GetCurrentThreadId;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS, MOD_NOREPEAT,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetMessageW, PeekMessageW, PostThreadMessageW, MSG, PM_NOREMOVE, WM_HOTKEY, WM_QUIT,
+        WM_USER,
+    };
+
+    /// Thread owning the hotkeys, stopped when dropped
+    pub struct Registration {
+        thread_id: u32,
+        thread: Option<std::thread::JoinHandle<()>>,
+    }
+
+    impl Drop for Registration {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+            }
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    pub fn register(
+        bindings: Vec<(Hotkey, HotkeyAction)>,
+        actions: UnboundedSender<HotkeyAction>,
+    ) -> Result<Registration> {
+        let (id_tx, id_rx) = std::sync::mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("hotkeys".to_string())
+            .spawn(move || {
+                let mut msg = MSG::default();
+                // Create the message queue before the thread can be stopped
+                unsafe {
+                    let _ = PeekMessageW(&mut msg, HWND::default(), WM_USER, WM_USER, PM_NOREMOVE);
+                }
+                let _ = id_tx.send(unsafe { GetCurrentThreadId() });
+
+                // Hotkeys without a window are posted to the thread that
+                // registered them
+                let mut registered = Vec::new();
+                for (id, (hotkey, action)) in (1..).zip(bindings) {
+                    let modifiers = HOT_KEY_MODIFIERS(hotkey.modifiers) | MOD_NOREPEAT;
+                    match unsafe { RegisterHotKey(HWND::default(), id, modifiers, hotkey.key) } {
+                        Ok(()) => {
+                            info!("Registered hotkey {} for {:?}", hotkey, action);
+                            registered.push((id, action));
+                        }
+                        Err(e) => warn!(
+                            "Failed to register hotkey {}, another application may use it: {}",
+                            hotkey, e
+                        ),
+                    }
+                }
+
+                // 0 on WM_QUIT, -1 on error
+                while unsafe { GetMessageW(&mut msg, HWND::default(), 0, 0) }.0 > 0 {
+                    if msg.message != WM_HOTKEY {
+                        continue;
+                    }
+                    let pressed = registered
+                        .iter()
+                        .find(|(id, _)| *id as usize == msg.wParam.0);
+                    if let Some((_, action)) = pressed {
+                        if actions.send(*action).is_err() {
+                            break;
+                        }
+                    }
+                }
+
+                for (id, _) in registered {
+                    unsafe {
+                        let _ = UnregisterHotKey(HWND::default(), id);
+                    }
+                }
+            })
+            .context("Failed to start the hotkey thread")?;
+
+        let thread_id = id_rx.recv().context("The hotkey thread stopped")?;
+        Ok(Registration {
+            thread_id,
+            thread: Some(thread),
+        })
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use super::{Hotkey, HotkeyAction};
+    use anyhow::Result;
+    use tokio::sync::mpsc::UnboundedSender;
+    use tracing::warn;
+
+    pub struct Registration;
+
+    pub fn register(
+        bindings: Vec<(Hotkey, HotkeyAction)>,
+        _actions: UnboundedSender<HotkeyAction>,
+    ) -> Result<Registration> {
+        if !bindings.is_empty() {
+            warn!("Global hotkeys are only supported on Windows");
+        }
+        Ok(Registration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hotkey() {
+        let hotkey: Hotkey = "Ctrl+Shift+S".parse().unwrap();
+        assert_eq!(hotkey.modifiers, MOD_CONTROL | MOD_SHIFT);
+        assert_eq!(hotkey.key, 'S' as u32);
+        assert_eq!(hotkey.to_string(), "Ctrl+Shift+S");
+
+        let hotkey: Hotkey = " alt + win + f12 ".parse().unwrap();
+        assert_eq!(hotkey.modifiers, MOD_ALT | MOD_WIN);
+        assert_eq!(hotkey.key, 0x7B);
+        assert_eq!("Ctrl+7".parse::<Hotkey>().unwrap().key, '7' as u32);
+        assert_eq!("Ctrl+PrintScreen".parse::<Hotkey>().unwrap().key, 0x2C);
+
+        for invalid in [
+            "S",
+            "Ctrl+Shift",
+            "Ctrl+S+D",
+            "Ctrl+F25",
+            "Ctrl+Nope",
+            "Ctrl+",
+        ] {
+            assert!(invalid.parse::<Hotkey>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_bindings_skip_empty_and_invalid() {
+        let bindings = bindings([
+            (HotkeyAction::CaptureNow, "Ctrl+Shift+S"),
+            (HotkeyAction::OpenSearch, ""),
+            (HotkeyAction::TogglePause, "Shift+Nope"),
+        ]);
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].1, HotkeyAction::CaptureNow);
+    }
+}
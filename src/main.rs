@@ -6,42 +6,117 @@
 //! - SQLite database storage
 //! - REST API server on localhost:3131
 //! - Graceful shutdown handling
+//! - Command-line search, export and maintenance (see [`cli`])
+//! - Headless service mode and start at login (see [`autostart`])
+//! - Single running instance (see [`single_instance`])
+//! - Live reload of config.toml edits (see [`config_watcher`])
+//! - Integrity check and capture file recovery after a crash (see [`recovery`])
+//! - Update download, install and rollback (see [`updater`])
+//! - Old frame cleanup and capture pause when the disk fills up (see
+//!   [`disk_monitor`])
+//! - Slower capture and paused embeddings on battery (see [`power_monitor`])
+//! - Global hotkeys to capture, search and pause (see [`hotkeys`])
 
 use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::signal;
 use tokio::sync::broadcast;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 // Import workspace crates
-use screensearch_api::{ApiConfig, ApiServer};
-use screensearch_capture::{CaptureConfig, CaptureEngine, OcrProcessor, OcrProcessorConfig};
+use screensearch_api::alerts::AlertWatcher;
+use screensearch_api::redaction::Redactor;
+use screensearch_api::capture_store::{
+    CaptureStorage, CaptureStorageConfig, RemoteBackend, RemoteStorageConfig,
+};
+use screensearch_api::data_move::MoveRequest;
+use screensearch_api::documents::DocumentExtractor;
+use screensearch_api::timesheet::{ProjectRule, TimesheetConfig};
+use screensearch_api::workers::{
+    ArchivalConfig, CompactionConfig, MarkdownExportConfig, ReportSchedulerConfig,
+    SummarizerConfig,
+};
+use screensearch_api::{ApiConfig, ApiServer, TlsConfig};
+use screensearch_capture::{
+    CaptureConfig, CaptureControl, CaptureEngine, FrameQueue, MonitorInfo, OcrProcessor,
+    OcrProcessorConfig, ProcessedFrame, ScreenCapture, SensitiveAction, SensitiveContentFilter,
+};
 use screensearch_db::{DatabaseConfig, DatabaseManager};
+use screensearch_embeddings::EmbeddingEngine;
+
+use config_watcher::{ConfigReloader, LogFilterHandle};
+use hotkeys::HotkeyAction;
+use tray_status::{PipelineStatus, TrayState};
 
 // Version and update checking modules
+mod autostart;
+mod cli;
+mod config_watcher;
+mod disk_monitor;
+mod hotkeys;
+mod metrics_recorder;
+mod power_monitor;
+mod profiles;
+mod recovery;
+mod single_instance;
+mod tray_status;
 mod version;
 mod update_checker;
+mod updater;
 
 /// Application configuration loaded from config.toml
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct AppConfig {
     capture: CaptureSettings,
     ocr: OcrSettings,
     api: ApiSettings,
     database: DatabaseSettings,
-    /// Privacy controls configuration (flagship feature - implementation pending)
-    #[allow(dead_code)]
+    /// Privacy controls configuration
     privacy: PrivacySettings,
-    /// Performance management configuration (flagship feature - implementation pending)
-    #[allow(dead_code)]
+    /// Performance limits (only the memory budget is enforced so far)
     performance: PerformanceSettings,
     logging: LoggingSettings,
     storage: StorageSettings,
     #[serde(default = "default_embeddings_settings")]
     embeddings: EmbeddingsSettings,
+    #[serde(default)]
+    reports: ReportsSettings,
+    #[serde(default)]
+    summaries: SummariesSettings,
+    #[serde(default)]
+    documents: DocumentsSettings,
+    #[serde(default)]
+    compaction: CompactionSettings,
+    #[serde(default)]
+    archival: ArchivalSettings,
+    #[serde(default)]
+    markdown_export: MarkdownExportSettings,
+    #[serde(default)]
+    time_tracking: TimeTrackingSettings,
+    #[serde(default)]
+    llm: LlmSettings,
+    #[serde(default)]
+    updates: UpdateSettings,
+    #[serde(default)]
+    hotkeys: HotkeySettings,
+    /// Profiles besides the default, each with its own database and
+    /// captures, by name
+    #[serde(default)]
+    profiles: std::collections::BTreeMap<String, ProfileSettings>,
+}
+
+/// Settings of a profile, overriding the `[privacy]` ones when set
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default)]
+struct ProfileSettings {
+    /// Applications to exclude from capture, by process name
+    excluded_apps: Option<Vec<String>>,
+    /// What to do with frames showing sensitive content
+    sensitive_content: Option<SensitiveAction>,
 }
 
 fn default_embeddings_settings() -> EmbeddingsSettings {
@@ -54,18 +129,115 @@ fn default_embeddings_settings() -> EmbeddingsSettings {
         max_chunk_tokens: 256,
         chunk_overlap: 32,
         hybrid_search_alpha: 0.3,
-        max_context_chunks: 20,
+        max_context_chunks: 50,
+        recency_half_life_hours: default_recency_half_life_hours(),
+        context_tokens: default_context_tokens(),
+        model_context_tokens: std::collections::HashMap::new(),
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+fn default_context_tokens() -> usize {
+    2000
+}
+
+fn default_recency_half_life_hours() -> f32 {
+    24.0
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct StorageSettings {
+    /// Directory for the database, captures, logs and updates (empty = AppData
+    /// in production, the current directory in development)
+    #[serde(default)]
+    data_dir: String,
+    /// Keep the data directory and the models next to the executable, so
+    /// the application runs entirely from a removable drive
+    #[serde(default)]
+    portable: bool,
     format: String,
     jpeg_quality: u8,
     max_width: u32,
+    /// Free disk space in MB below which the oldest frames are deleted
+    /// ahead of the retention period (0 = never)
+    #[serde(default = "default_cleanup_below_mb")]
+    cleanup_below_mb: u64,
+    /// Free disk space in MB below which capture pauses (0 = never)
+    #[serde(default = "default_pause_below_mb")]
+    pause_below_mb: u64,
+    /// Where new captures are written: "local", "s3" or "azure"
+    #[serde(default = "default_storage_backend")]
+    backend: String,
+    /// Bucket or container of the "s3" and "azure" backends
+    #[serde(default)]
+    remote: RemoteStorageSettings,
+}
+
+fn default_storage_backend() -> String {
+    "local".to_string()
+}
+
+/// Bucket of an S3-compatible service, or Azure Blob Storage container
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct RemoteStorageSettings {
+    /// Bucket, or container for "azure"
+    bucket: String,
+    /// Key prefix of the captures in the bucket
+    prefix: String,
+    /// AWS region ("s3")
+    region: String,
+    /// Endpoint of a service other than AWS, such as MinIO ("s3", empty = AWS)
+    endpoint: String,
+    /// Storage account ("azure")
+    account: String,
+    /// Access key ID ("s3"; empty = from the environment)
+    access_key_id: String,
+    /// Secret access key, or account access key for "azure" (empty = from
+    /// the environment)
+    secret_access_key: String,
+    /// Size of the local cache of captures read from the bucket in MB
+    cache_mb: u64,
+}
+
+// Settings are logged when they change, so the secret is left out
+impl std::fmt::Debug for RemoteStorageSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteStorageSettings")
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .field("region", &self.region)
+            .field("endpoint", &self.endpoint)
+            .field("account", &self.account)
+            .field("access_key_id", &self.access_key_id)
+            .field("cache_mb", &self.cache_mb)
+            .finish_non_exhaustive()
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl Default for RemoteStorageSettings {
+    fn default() -> Self {
+        Self {
+            bucket: String::new(),
+            prefix: "screensearch".to_string(),
+            region: "us-east-1".to_string(),
+            endpoint: String::new(),
+            account: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            cache_mb: 1024,
+        }
+    }
+}
+
+fn default_cleanup_below_mb() -> u64 {
+    5120
+}
+
+fn default_pause_below_mb() -> u64 {
+    1024
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct CaptureSettings {
     interval_ms: u64,
     enable_frame_diff: bool,
@@ -76,7 +248,7 @@ struct CaptureSettings {
     draw_border: bool,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct OcrSettings {
     /// OCR engine selection (feature pending - currently uses Windows OCR only)
     #[allow(dead_code)]
@@ -89,9 +261,12 @@ struct OcrSettings {
     channel_buffer_size: usize,
     enable_metrics: bool,
     metrics_interval_secs: u64,
+    /// Languages to read, the first the default (empty = user profile)
+    #[serde(default)]
+    languages: Vec<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct ApiSettings {
     host: String,
     port: u16,
@@ -100,13 +275,45 @@ struct ApiSettings {
     cors_origin: String,
     #[serde(default = "default_auto_open_browser")]
     auto_open_browser: bool,
+    #[serde(default)]
+    tls: TlsSettings,
+    /// Named pipe or Unix socket to serve on instead of host:port (empty = TCP)
+    #[serde(default)]
+    local_socket: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct TlsSettings {
+    /// Serve the API over HTTPS
+    enabled: bool,
+    /// PEM certificate file (relative paths live in the app data directory)
+    cert_path: String,
+    /// PEM private key file (relative paths live in the app data directory)
+    key_path: String,
+    /// Generate a self-signed certificate on first run if the files are missing
+    self_signed: bool,
+    /// Extra host names/IPs for a generated certificate (e.g. the LAN address)
+    subject_alt_names: Vec<String>,
+}
+
+impl Default for TlsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cert_path: "tls/cert.pem".to_string(),
+            key_path: "tls/key.pem".to_string(),
+            self_signed: true,
+            subject_alt_names: Vec::new(),
+        }
+    }
 }
 
 fn default_auto_open_browser() -> bool {
     true // Maintain backward compatibility - enabled by default
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct DatabaseSettings {
     path: String,
     max_connections: u32,
@@ -116,36 +323,70 @@ struct DatabaseSettings {
     cache_size_kb: i32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct PrivacySettings {
-    /// Applications to exclude from capture (feature pending)
-    #[allow(dead_code)]
+    /// Applications to exclude from capture, by process name
     excluded_apps: Vec<String>,
     /// Pause capture when screen is locked (feature pending)
     #[allow(dead_code)]
     pause_on_lock: bool,
+    /// What to do with frames showing banking pages, password prompts,
+    /// payment cards or explicit content
+    #[serde(default)]
+    sensitive_content: SensitiveAction,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct PerformanceSettings {
     /// Maximum CPU usage percentage (feature pending)
     #[allow(dead_code)]
     max_cpu_percent: u8,
-    /// Maximum memory usage in MB (feature pending)
-    #[allow(dead_code)]
+    /// Maximum memory of queued frames and embedding batches in MB
+    /// (0 = unlimited)
     max_memory_mb: u64,
+    /// Capture interval on battery or in power saver mode, in milliseconds,
+    /// when longer than `capture.interval_ms` (0 = unchanged)
+    #[serde(default = "default_battery_interval_ms")]
+    battery_interval_ms: u64,
+    /// Pause embedding generation on battery or in power saver mode
+    #[serde(default = "default_battery_pause_workers")]
+    battery_pause_workers: bool,
+}
+
+fn default_battery_interval_ms() -> u64 {
+    10_000
 }
 
-#[derive(Debug, Clone, Deserialize)]
+fn default_battery_pause_workers() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct LoggingSettings {
     level: String,
+    /// Log line format: "text" or "json" (one JSON object per line)
+    #[serde(default)]
+    format: LogFormat,
     log_to_file: bool,
     log_file: String,
     max_log_size_mb: u64,
     log_rotation_count: u32,
+    /// OTLP/gRPC collector endpoint for trace export, e.g. "http://localhost:4317"
+    /// (empty = disabled)
+    #[serde(default)]
+    otlp_endpoint: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Format of log lines on the console and in the log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct EmbeddingsSettings {
     enabled: bool,
     batch_size: i64,
@@ -161,8 +402,272 @@ struct EmbeddingsSettings {
     chunk_overlap: usize,
     #[allow(dead_code)]
     hybrid_search_alpha: f32,
-    #[allow(dead_code)]
     max_context_chunks: usize,
+    /// Hours after which the weight of a report context chunk for being
+    /// recent halves (0 = no preference for recent chunks)
+    #[serde(default = "default_recency_half_life_hours")]
+    recency_half_life_hours: f32,
+    /// Tokens of screen content sent with a report prompt
+    #[serde(default = "default_context_tokens")]
+    context_tokens: usize,
+    /// `context_tokens` for specific models, by model name
+    #[serde(default)]
+    model_context_tokens: std::collections::HashMap<String, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct ReportsSettings {
+    enabled: bool,
+    provider_url: String,
+    api_key: Option<String>,
+    model: String,
+    /// Local time for daily reports ("HH:MM", empty = disabled)
+    daily_time: String,
+    /// Day of week for weekly reports (e.g. "fri")
+    weekly_day: String,
+    /// Local time for weekly reports ("HH:MM", empty = disabled)
+    weekly_time: String,
+}
+
+impl Default for ReportsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider_url: "http://localhost:11434/v1".to_string(),
+            api_key: None,
+            model: "llama3".to_string(),
+            daily_time: "18:00".to_string(),
+            weekly_day: "fri".to_string(),
+            weekly_time: "18:00".to_string(),
+        }
+    }
+}
+
+/// Hourly activity summaries, used for reports over long periods
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct SummariesSettings {
+    enabled: bool,
+    /// AI provider writing the summaries (empty = extractive summaries of
+    /// the apps and windows used, without a model)
+    provider_url: String,
+    api_key: Option<String>,
+    model: String,
+    /// Finished hours before now summarized if they are missing
+    lookback_hours: u32,
+    /// Reports over at least this many hours are built from the summaries
+    /// (0 = never)
+    report_min_hours: f32,
+}
+
+impl Default for SummariesSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            provider_url: String::new(),
+            api_key: None,
+            model: "llama3".to_string(),
+            lookback_hours: 24,
+            report_min_hours: 12.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct DocumentsSettings {
+    /// Extract receipts and invoices from captured frames
+    enabled: bool,
+}
+
+impl Default for DocumentsSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Encoding of old frames into video chunks
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct CompactionSettings {
+    enabled: bool,
+    /// Frames older than this many days are compacted
+    after_days: u32,
+    /// ffmpeg executable (a name on the PATH or a full path)
+    ffmpeg_path: String,
+    /// Frames per second of video
+    fps: u32,
+    /// x264 constant rate factor, 0-51 (lower = better quality)
+    crf: u32,
+}
+
+impl Default for CompactionSettings {
+    fn default() -> Self {
+        let defaults = CompactionConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            after_days: defaults.after_days,
+            ffmpeg_path: defaults.ffmpeg_path,
+            fps: defaults.fps,
+            crf: defaults.crf,
+        }
+    }
+}
+
+/// Moving old frame images into daily tar.zst archives
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct ArchivalSettings {
+    enabled: bool,
+    /// Days captured longer ago than this are archived
+    after_days: u32,
+    /// zstd compression level, 1-22
+    level: i32,
+}
+
+impl Default for ArchivalSettings {
+    fn default() -> Self {
+        let defaults = ArchivalConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            after_days: defaults.after_days,
+            level: defaults.level,
+        }
+    }
+}
+
+/// Markdown daily notes written into a notes vault
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct MarkdownExportSettings {
+    /// Export a note every day at `time`
+    enabled: bool,
+    /// Vault the notes are written to (empty = no export)
+    vault_dir: String,
+    /// Folder of the notes inside the vault
+    folder: String,
+    /// Local time of the daily export ("HH:MM")
+    time: String,
+    /// Screenshots embedded per note (0 = none)
+    screenshots: usize,
+}
+
+impl Default for MarkdownExportSettings {
+    fn default() -> Self {
+        let defaults = MarkdownExportConfig::default();
+        Self {
+            enabled: defaults.enabled,
+            vault_dir: String::new(),
+            folder: defaults.folder,
+            time: defaults.export_at.format("%H:%M").to_string(),
+            screenshots: defaults.screenshots,
+        }
+    }
+}
+
+/// Time entries exported as Toggl or Clockify CSV
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct TimeTrackingSettings {
+    /// Email written to each entry
+    email: String,
+    /// Mark entries as billable
+    billable: bool,
+    /// Entries shorter than this are left out
+    min_entry_minutes: i64,
+    /// Projects by app, window title or tag, the first match wins
+    projects: Vec<ProjectRule>,
+}
+
+impl Default for TimeTrackingSettings {
+    fn default() -> Self {
+        let defaults = TimesheetConfig::default();
+        Self {
+            email: defaults.email,
+            billable: defaults.billable,
+            min_entry_minutes: defaults.min_entry_minutes,
+            projects: defaults.projects,
+        }
+    }
+}
+
+/// Built-in model used by the AI provider "builtin"
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct LlmSettings {
+    /// Where to download the GGUF model from
+    model_url: String,
+    /// File name of the model in the models directory
+    model_file: String,
+    /// Context window in tokens, shared by the prompt and the answer
+    context_size: u32,
+    /// Generation threads (0 = llama.cpp default)
+    threads: u32,
+    /// Maximum tokens per answer
+    max_tokens: u32,
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        let defaults = screensearch_api::local_llm::LocalLlmConfig::default();
+        Self {
+            model_url: defaults.model_url,
+            model_file: defaults.model_file,
+            context_size: defaults.context_size,
+            threads: defaults.threads,
+            max_tokens: defaults.max_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct UpdateSettings {
+    /// Check for a newer release at startup
+    check: bool,
+    /// Download and verify newer releases without being asked; they
+    /// install on the next start
+    auto_download: bool,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            check: true,
+            auto_download: false,
+        }
+    }
+}
+
+/// Global hotkeys, see [`hotkeys`]
+///
+/// Each hotkey is written like "Ctrl+Shift+S"; an empty one is not
+/// registered.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+struct HotkeySettings {
+    enabled: bool,
+    /// Capture all monitors now and tag the frames with `capture_tag`
+    capture: String,
+    /// Tag for frames captured with the hotkey (empty = no tag)
+    capture_tag: String,
+    /// Open the web interface
+    search: String,
+    /// Pause or resume capture
+    pause: String,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capture: "Ctrl+Shift+S".to_string(),
+            capture_tag: "hotkey".to_string(),
+            search: "Ctrl+Shift+F".to_string(),
+            pause: "Ctrl+Shift+P".to_string(),
+        }
+    }
 }
 
 impl Default for AppConfig {
@@ -187,12 +692,15 @@ impl Default for AppConfig {
                 channel_buffer_size: 100,
                 enable_metrics: true,
                 metrics_interval_secs: 60,
+                languages: Vec::new(),
             },
             api: ApiSettings {
                 host: "127.0.0.1".to_string(),
                 port: 3131,
                 cors_origin: String::new(),
                 auto_open_browser: true, // Default to enabled for backward compatibility
+                tls: TlsSettings::default(),
+                local_socket: String::new(),
             },
             database: DatabaseSettings {
                 path: "screensearch.db".to_string(),
@@ -212,46 +720,116 @@ impl Default for AppConfig {
                     "Bank".to_string(),
                 ],
                 pause_on_lock: true,
+                sensitive_content: SensitiveAction::Off,
             },
             performance: PerformanceSettings {
                 max_cpu_percent: 5,
                 max_memory_mb: 500,
+                battery_interval_ms: default_battery_interval_ms(),
+                battery_pause_workers: default_battery_pause_workers(),
             },
             logging: LoggingSettings {
                 level: "info".to_string(),
+                format: LogFormat::Text,
                 log_to_file: true,
                 log_file: "screensearch.log".to_string(),
                 max_log_size_mb: 100,
                 log_rotation_count: 5,
+                otlp_endpoint: String::new(),
             },
             storage: StorageSettings {
+                data_dir: String::new(),
+                portable: false,
                 format: "jpeg".to_string(),
                 jpeg_quality: 80,
                 max_width: 1920,
+                cleanup_below_mb: default_cleanup_below_mb(),
+                pause_below_mb: default_pause_below_mb(),
+                backend: default_storage_backend(),
+                remote: RemoteStorageSettings::default(),
             },
             embeddings: default_embeddings_settings(),
+            reports: ReportsSettings::default(),
+            summaries: SummariesSettings::default(),
+            documents: DocumentsSettings::default(),
+            compaction: CompactionSettings::default(),
+            archival: ArchivalSettings::default(),
+            llm: LlmSettings::default(),
+            updates: UpdateSettings::default(),
+            hotkeys: HotkeySettings::default(),
+            profiles: Default::default(),
         }
     }
 }
 
+/// Configuration file name
+const CONFIG_PATH: &str = "config.toml";
+
 impl AppConfig {
-    /// Load configuration from file, falling back to defaults
-    fn load() -> Result<Self> {
-        let config_path = PathBuf::from("config.toml");
+    /// Configuration file used when none is given on the command line
+    ///
+    /// Prefers the working directory, then the executable's directory, which
+    /// is where the installer puts it and what applies when started at login.
+    fn default_path() -> PathBuf {
+        let local = PathBuf::from(CONFIG_PATH);
+        if local.exists() {
+            return local;
+        }
+
+        executable_dir()
+            .map(|dir| dir.join(CONFIG_PATH))
+            .filter(|path| path.exists())
+            .unwrap_or(local)
+    }
+
+    /// Configuration file of a `--portable` run: the one next to the executable
+    fn portable_path() -> PathBuf {
+        executable_dir()
+            .map(|dir| dir.join(CONFIG_PATH))
+            .unwrap_or_else(|| PathBuf::from(CONFIG_PATH))
+    }
+
+    /// Whether this is a portable install, by `storage.portable` or
+    /// `--portable`
+    fn is_portable(&self) -> bool {
+        self.storage.portable || PORTABLE.load(Ordering::Relaxed)
+    }
+
+    /// Apply the active profile's privacy settings over `[privacy]`
+    fn apply_profile(&mut self) {
+        let Some(profile) = self.profiles.get(active_profile()) else {
+            return;
+        };
+        if let Some(excluded_apps) = &profile.excluded_apps {
+            self.privacy.excluded_apps = excluded_apps.clone();
+        }
+        if let Some(sensitive_content) = profile.sensitive_content {
+            self.privacy.sensitive_content = sensitive_content;
+        }
+    }
 
+    /// Load configuration from file, falling back to defaults
+    fn load(config_path: &Path) -> Result<Self> {
         if config_path.exists() {
-            let content =
-                std::fs::read_to_string(&config_path).context("Failed to read config.toml")?;
-            let config: AppConfig =
-                toml::from_str(&content).context("Failed to parse config.toml")?;
-            info!("Loaded configuration from config.toml");
+            let config = Self::from_file(config_path)?;
+            info!("Loaded configuration from {}", config_path.display());
             Ok(config)
         } else {
-            warn!("config.toml not found, using default configuration");
+            warn!(
+                "{} not found, using default configuration",
+                config_path.display()
+            );
             Ok(Self::default())
         }
     }
 
+    /// Read and parse a configuration file
+    fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
     /// Convert to CaptureConfig
     fn capture_config(&self) -> CaptureConfig {
         CaptureConfig {
@@ -276,30 +854,74 @@ impl AppConfig {
             channel_buffer_size: self.ocr.channel_buffer_size,
             enable_metrics: self.ocr.enable_metrics,
             metrics_interval_secs: self.ocr.metrics_interval_secs,
+            languages: self.ocr.languages.clone(),
         }
     }
 
-    /// Convert to DatabaseConfig
-    fn database_config(&self) -> DatabaseConfig {
-        // Use AppData for database in production, current directory in development
-        let db_path = if cfg!(debug_assertions) {
-            // Development: use relative path
-            self.database.path.clone()
-        } else {
-            // Production: use AppData
-            if let Some(data_dir) = dirs::data_local_dir() {
-                let app_dir = data_dir.join("screensearch");
-                if let Err(e) = std::fs::create_dir_all(&app_dir) {
-                    warn!("Could not create AppData directory: {}", e);
-                    self.database.path.clone()
-                } else {
-                    app_dir.join(&self.database.path).to_string_lossy().to_string()
-                }
-            } else {
+    /// Directory for the database, captures, logs and updates
+    ///
+    /// `storage.data_dir` when set, otherwise AppData in production and the
+    /// current directory in development. Portable installs use the
+    /// executable's directory, which a relative `storage.data_dir` is then
+    /// resolved against.
+    fn data_dir(&self) -> PathBuf {
+        if self.is_portable() {
+            let Some(exe_dir) = executable_dir() else {
+                warn!("Could not determine the executable directory, using relative path");
+                return PathBuf::from(&self.storage.data_dir);
+            };
+            if self.storage.data_dir.is_empty() {
+                return exe_dir;
+            }
+            return exe_dir.join(&self.storage.data_dir);
+        }
+        if !self.storage.data_dir.is_empty() {
+            return PathBuf::from(&self.storage.data_dir);
+        }
+        if cfg!(debug_assertions) {
+            return PathBuf::new();
+        }
+
+        match dirs::data_local_dir() {
+            Some(data_dir) => data_dir.join("screensearch"),
+            None => {
                 warn!("Could not determine AppData directory, using relative path");
-                self.database.path.clone()
+                PathBuf::new()
             }
-        };
+        }
+    }
+
+    /// Absolute data directory that `move-data` can move
+    ///
+    /// Portable installs keep their data next to the executable, a debug
+    /// build without `storage.data_dir` uses the working directory, and a
+    /// database with an absolute path lives elsewhere, so none of them move.
+    fn movable_data_dir(&self) -> Result<PathBuf, &'static str> {
+        if self.is_portable() {
+            return Err("portable mode keeps the data next to the executable");
+        }
+        if Path::new(&self.database.path).is_absolute() {
+            return Err("database.path points outside the data directory");
+        }
+        let data_dir = self.data_dir();
+        if data_dir.as_os_str().is_empty() {
+            return Err("no data directory is set, the working directory is used");
+        }
+        Ok(std::path::absolute(&data_dir).unwrap_or(data_dir))
+    }
+
+    /// Convert to DatabaseConfig, for the active profile
+    fn database_config(&self) -> DatabaseConfig {
+        let data_dir = profiles::profile_dir(&self.data_dir(), active_profile());
+        if !data_dir.as_os_str().is_empty() {
+            if let Err(e) = std::fs::create_dir_all(&data_dir) {
+                warn!("Could not create data directory {}: {}", data_dir.display(), e);
+            }
+        }
+        let db_path = data_dir
+            .join(&self.database.path)
+            .to_string_lossy()
+            .to_string();
 
         DatabaseConfig {
             path: db_path,
@@ -311,24 +933,278 @@ impl AppConfig {
         }
     }
 
+    /// Convert to ReportSchedulerConfig, ignoring unparseable times
+    fn report_scheduler_config(&self) -> ReportSchedulerConfig {
+        let parse_time = |value: &str| {
+            if value.is_empty() {
+                return None;
+            }
+            match chrono::NaiveTime::parse_from_str(value, "%H:%M") {
+                Ok(time) => Some(time),
+                Err(e) => {
+                    warn!("Invalid report time '{}': {}", value, e);
+                    None
+                }
+            }
+        };
+
+        let defaults = ReportSchedulerConfig::default();
+        let weekly_on = self.reports.weekly_day.parse().unwrap_or_else(|_| {
+            warn!(
+                "Invalid report weekday '{}', using {:?}",
+                self.reports.weekly_day, defaults.weekly_on
+            );
+            defaults.weekly_on
+        });
+
+        ReportSchedulerConfig {
+            enabled: self.reports.enabled,
+            provider_url: self.reports.provider_url.clone(),
+            api_key: self.reports.api_key.clone(),
+            model: self.reports.model.clone(),
+            daily_at: parse_time(&self.reports.daily_time),
+            weekly_on,
+            weekly_at: parse_time(&self.reports.weekly_time),
+            check_interval_secs: defaults.check_interval_secs,
+        }
+    }
+
+    fn summarizer_config(&self) -> SummarizerConfig {
+        let provider_url = self.summaries.provider_url.trim();
+        SummarizerConfig {
+            enabled: self.summaries.enabled,
+            provider_url: (!provider_url.is_empty()).then(|| provider_url.to_string()),
+            api_key: self.summaries.api_key.clone(),
+            model: self.summaries.model.clone(),
+            lookback_hours: self.summaries.lookback_hours,
+            check_interval_secs: SummarizerConfig::default().check_interval_secs,
+        }
+    }
+
+    fn compaction_config(&self) -> CompactionConfig {
+        let defaults = CompactionConfig::default();
+        CompactionConfig {
+            enabled: self.compaction.enabled,
+            after_days: self.compaction.after_days.max(1),
+            ffmpeg_path: self.compaction.ffmpeg_path.clone(),
+            fps: self.compaction.fps.max(1),
+            crf: self.compaction.crf.min(51),
+            ..defaults
+        }
+    }
+
+    fn archival_config(&self) -> ArchivalConfig {
+        ArchivalConfig {
+            enabled: self.archival.enabled,
+            after_days: self.archival.after_days.max(1),
+            level: self.archival.level.clamp(1, 22),
+            ..ArchivalConfig::default()
+        }
+    }
+
+    fn markdown_export_config(&self) -> MarkdownExportConfig {
+        let settings = &self.markdown_export;
+        let defaults = MarkdownExportConfig::default();
+        let export_at = chrono::NaiveTime::parse_from_str(&settings.time, "%H:%M")
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Invalid markdown_export.time '{}': {}, using {}",
+                    settings.time,
+                    e,
+                    defaults.export_at.format("%H:%M")
+                );
+                defaults.export_at
+            });
+
+        // Other profiles write their own notes
+        let mut folder = settings.folder.trim_matches(['/', '\\']).to_string();
+        if active_profile() != screensearch_api::profiles::DEFAULT_PROFILE {
+            folder = format!("{}/{}", folder, active_profile());
+        }
+
+        MarkdownExportConfig {
+            enabled: settings.enabled,
+            vault_dir: PathBuf::from(settings.vault_dir.trim()),
+            folder,
+            export_at,
+            screenshots: settings.screenshots.min(50),
+            ..defaults
+        }
+    }
+
+    fn time_tracking_config(&self) -> TimesheetConfig {
+        let settings = &self.time_tracking;
+        TimesheetConfig {
+            email: settings.email.trim().to_string(),
+            billable: settings.billable,
+            projects: settings
+                .projects
+                .iter()
+                .filter(|rule| !rule.project.trim().is_empty())
+                .cloned()
+                .collect(),
+            min_entry_minutes: settings.min_entry_minutes.max(0),
+        }
+    }
+
+    /// Where the active profile's captures are written and read
+    fn capture_storage_config(&self) -> Result<CaptureStorageConfig> {
+        let remote = &self.storage.remote;
+        let optional = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        let backend = match self.storage.backend.to_lowercase().as_str() {
+            "" | "local" => {
+                return Ok(CaptureStorageConfig {
+                    captures_dir: captures_dir(),
+                    remote: None,
+                })
+            }
+            "s3" => RemoteBackend::S3 {
+                bucket: remote.bucket.clone(),
+                region: remote.region.clone(),
+                endpoint: optional(&remote.endpoint),
+                access_key_id: optional(&remote.access_key_id),
+                secret_access_key: optional(&remote.secret_access_key),
+            },
+            "azure" => RemoteBackend::Azure {
+                account: remote.account.clone(),
+                container: remote.bucket.clone(),
+                access_key: optional(&remote.secret_access_key),
+            },
+            other => anyhow::bail!(
+                "Unknown storage.backend \"{}\", expected \"local\", \"s3\" or \"azure\"",
+                other
+            ),
+        };
+        if remote.bucket.is_empty() {
+            anyhow::bail!(
+                "storage.remote.bucket is required by the {} backend",
+                self.storage.backend
+            );
+        }
+
+        // Other profiles keep their captures apart, like on disk
+        let mut prefix = remote.prefix.trim_matches('/').to_string();
+        if active_profile() != screensearch_api::profiles::DEFAULT_PROFILE {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(&format!("profiles/{}", active_profile()));
+        }
+
+        Ok(CaptureStorageConfig {
+            captures_dir: captures_dir(),
+            remote: Some(RemoteStorageConfig {
+                backend,
+                prefix,
+                cache_dir: captures_dir().with_file_name("capture_cache"),
+                cache_bytes: remote.cache_mb * 1024 * 1024,
+            }),
+        })
+    }
+
     /// Convert to ApiConfig with the correct database path
     fn api_config(&self, db_path: &str) -> ApiConfig {
         ApiConfig {
             host: self.api.host.clone(),
             port: self.api.port,
             database_path: db_path.to_string(),
+            tls: self.api.tls.enabled.then(|| TlsConfig {
+                cert_path: app_data_path(&self.api.tls.cert_path),
+                key_path: app_data_path(&self.api.tls.key_path),
+                self_signed: self.api.tls.self_signed,
+                subject_alt_names: self.api.tls.subject_alt_names.clone(),
+            }),
+            local_socket: (!self.api.local_socket.is_empty())
+                .then(|| self.api.local_socket.clone()),
         }
     }
+
+    /// Base URL of the API and web interface
+    fn api_url(&self) -> String {
+        let scheme = if self.api.tls.enabled { "https" } else { "http" };
+        format!("{}://{}:{}", scheme, self.api.host, self.api.port)
+    }
+}
+
+/// Directory of the running executable
+fn executable_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+/// Set by `--portable`, see [`AppConfig::is_portable`]
+static PORTABLE: AtomicBool = AtomicBool::new(false);
+
+/// Profile of this run, set once at startup
+static PROFILE: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+/// Name of the profile this run captures to
+fn active_profile() -> &'static str {
+    PROFILE
+        .get()
+        .map(String::as_str)
+        .unwrap_or(screensearch_api::profiles::DEFAULT_PROFILE)
+}
+
+/// Data directory of the running configuration, set once at startup
+static DATA_DIR: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+/// Resolve a relative data file path
+///
+/// Relative paths live in the data directory, like the database (see
+/// [`AppConfig::data_dir`]).
+fn app_data_path(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        return path;
+    }
+
+    DATA_DIR
+        .get_or_init(|| AppConfig::default().data_dir())
+        .join(path)
+}
+
+/// Logging handles that must live until shutdown
+struct TracingGuards {
+    /// Flushes the non-blocking log file writer on drop
+    _file_guard: Option<tracing_appender::non_blocking::WorkerGuard>,
+    /// OTLP span exporter; shut down to flush buffered spans
+    tracer_provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+    /// Replaces the log level filter when the config file changes
+    log_filter: LogFilterHandle,
+    /// Log file the rolling files are named after, when logging to a file
+    log_file: Option<PathBuf>,
 }
 
 /// Initialize tracing/logging subsystem
-fn init_tracing(config: &LoggingSettings) -> Result<Option<tracing_appender::non_blocking::WorkerGuard>> {
+///
+/// Logs go to stdout when `console` is set, as text or JSON lines. Must run
+/// inside the Tokio runtime when OTLP export is enabled, since the batch span
+/// exporter runs on it.
+fn init_tracing(config: &LoggingSettings, console: bool) -> Result<TracingGuards> {
+    use opentelemetry::trace::TracerProvider as _;
     use tracing_appender::rolling::{RollingFileAppender, Rotation};
+    use tracing_subscriber::Layer as _;
 
     let env_filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.level));
+    let (env_filter, log_filter) = tracing_subscriber::reload::Layer::new(env_filter);
 
-    if config.log_to_file {
+    let json = config.format == LogFormat::Json;
+    let stdout_layer = console.then(|| {
+        let layer = tracing_subscriber::fmt::layer()
+            .with_target(true)
+            .with_thread_ids(true)
+            .with_line_number(true);
+        if json {
+            layer.json().boxed()
+        } else {
+            layer.boxed()
+        }
+    });
+
+    let (file_layer, file_guard) = if config.log_to_file {
         // Parse log file path
         let log_path = PathBuf::from(&config.log_file);
         let log_dir = log_path
@@ -352,78 +1228,171 @@ fn init_tracing(config: &LoggingSettings) -> Result<Option<tracing_appender::non
 
         let (non_blocking_file, guard) = tracing_appender::non_blocking(file_appender);
 
-        // Log to both stdout and file
-        let stdout_layer = tracing_subscriber::fmt::layer()
-            .with_target(true)
-            .with_thread_ids(true)
-            .with_line_number(true);
-
         let file_layer = tracing_subscriber::fmt::layer()
             .with_target(true)
             .with_thread_ids(true)
             .with_line_number(true)
             .with_ansi(false)
             .with_writer(non_blocking_file);
+        let file_layer = if json {
+            file_layer.json().boxed()
+        } else {
+            file_layer.boxed()
+        };
 
-        tracing_subscriber::registry()
-            .with(env_filter)
-            .with(stdout_layer)
-            .with(file_layer)
-            .init();
+        (Some(file_layer), Some(guard))
+    } else {
+        (None, None)
+    };
 
+    let tracer_provider = if config.otlp_endpoint.is_empty() {
+        None
+    } else {
+        Some(init_otlp(&config.otlp_endpoint)?)
+    };
+    let otel_layer = tracer_provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(OTLP_SERVICE_NAME))
+    });
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(stdout_layer)
+        .with(file_layer)
+        .with(otel_layer)
+        .init();
+
+    if config.log_to_file {
         info!("File logging enabled: {}", config.log_file);
         info!("Log rotation: {} files, daily rotation", config.log_rotation_count);
+    }
+    if tracer_provider.is_some() {
+        info!("Exporting traces via OTLP to {}", config.otlp_endpoint);
+    }
 
-        Ok(Some(guard))
-    } else {
-        // Console-only logging
-        let fmt_layer = tracing_subscriber::fmt::layer()
-            .with_target(true)
-            .with_thread_ids(true)
-            .with_line_number(true);
+    Ok(TracingGuards {
+        _file_guard: file_guard,
+        tracer_provider,
+        log_filter,
+        log_file: config.log_to_file.then(|| PathBuf::from(&config.log_file)),
+    })
+}
 
-        tracing_subscriber::registry()
-            .with(env_filter)
-            .with(fmt_layer)
-            .init();
+/// Service name reported with exported spans
+const OTLP_SERVICE_NAME: &str = "screensearch";
 
-        Ok(None)
-    }
+/// Create a tracer provider exporting spans over OTLP/gRPC
+fn init_otlp(endpoint: &str) -> Result<opentelemetry_sdk::trace::TracerProvider> {
+    use opentelemetry_otlp::WithExportConfig;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .context("Failed to create OTLP span exporter")?;
+
+    let resource = opentelemetry_sdk::Resource::new([opentelemetry::KeyValue::new(
+        "service.name",
+        OTLP_SERVICE_NAME,
+    )]);
+
+    Ok(opentelemetry_sdk::trace::TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_config(opentelemetry_sdk::trace::Config::default().with_resource(resource))
+        .build())
 }
 
 use tray_icon::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     TrayIconBuilder,
 };
 use winit::event_loop::{ControlFlow, EventLoop};
 use crossbeam::channel::Receiver;
 
+/// Frames held outside the queues for the memory budget: one in OCR and one
+/// being stored
+const IN_FLIGHT_FRAMES: usize = 2;
+
+/// Memory budget reserved per frame of an embedding batch, for its text,
+/// tokens and vector
+const EMBEDDING_FRAME_BYTES: u64 = 64 * 1024;
+
+/// How long shutdown waits for queued frames to be processed and stored
+const DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How long shutdown spends embedding frames stored since the last run
+const EMBEDDING_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How often the progress of a data directory move is logged
+const MOVE_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
 struct App {
     config: AppConfig,
+    config_path: PathBuf,
+    log_filter: LogFilterHandle,
+    /// Log file served by `/logs`, when logging to a file
+    log_file: Option<PathBuf>,
+    status: Arc<PipelineStatus>,
     shutdown_tx: broadcast::Sender<()>,
+    /// Set when the app stopped to install an update, switch profiles or
+    /// move its data
+    restart: Arc<AtomicBool>,
+    /// Profile to restart with, after a switch
+    switch_to: Arc<std::sync::Mutex<Option<String>>>,
+    /// Data directory move to carry out after shutdown
+    move_to: std::sync::Mutex<Option<MoveRequest>>,
 }
 
 struct EventLoopState {
-    _tray_icon: tray_icon::TrayIcon,
-    menu_items: (MenuItem, MenuItem),
+    tray_icon: tray_icon::TrayIcon,
+    open_item: MenuItem,
+    pause_item: CheckMenuItem,
+    quit_item: MenuItem,
     menu_channel: &'static Receiver<tray_icon::menu::MenuEvent>,
     tray_channel: &'static Receiver<tray_icon::TrayIconEvent>,
     app_task: std::thread::JoinHandle<()>,
     shutdown_tx: tokio::sync::mpsc::Sender<()>,
+    pause_tx: tokio::sync::mpsc::UnboundedSender<bool>,
+    /// Profile submenu items with their profile, when there are several
+    profile_items: Vec<(CheckMenuItem, String)>,
+    profile_tx: tokio::sync::mpsc::UnboundedSender<String>,
     api_url: String,
+    /// Icon the state badges are drawn on
+    base_icon: image::RgbaImage,
+    status: Arc<PipelineStatus>,
+    /// State the icon and tooltip currently show
+    shown_state: Option<TrayState>,
 }
 
 impl App {
-    fn new(config: AppConfig) -> Self {
+    fn new(
+        config: AppConfig,
+        config_path: PathBuf,
+        log_filter: LogFilterHandle,
+        log_file: Option<PathBuf>,
+        status: Arc<PipelineStatus>,
+    ) -> Self {
         let (shutdown_tx, _) = broadcast::channel(10);
         Self {
             config,
+            config_path,
+            log_filter,
+            log_file,
+            status,
             shutdown_tx,
+            restart: Arc::new(AtomicBool::new(false)),
+            switch_to: Arc::new(std::sync::Mutex::new(None)),
+            move_to: std::sync::Mutex::new(None),
         }
     }
 
-    async fn run_with_signal(&self, mut external_shutdown: tokio::sync::mpsc::Receiver<()>) -> Result<()> {
+    async fn run_with_signal(
+        &self,
+        mut external_shutdown: tokio::sync::mpsc::Receiver<()>,
+        mut pause_requests: tokio::sync::mpsc::UnboundedReceiver<bool>,
+        mut profile_requests: tokio::sync::mpsc::UnboundedReceiver<String>,
+    ) -> Result<()> {
         info!("Starting ScreenSearch v{}", env!("CARGO_PKG_VERSION"));
+        info!("Using profile '{}'", active_profile());
         info!("Configuration loaded: {:?}", self.config);
 
         // Initialize database
@@ -435,17 +1404,145 @@ impl App {
                 .context("Failed to initialize database")?,
         );
 
+        // A sentinel left behind means the last run did not shut down cleanly
+        let (sentinel, unclean) = recovery::Sentinel::create(Path::new(&db_config.path))?;
+        let recovered = if unclean {
+            match recovery::recover(&db, &captures_dir()).await {
+                Ok(recovered) => Some(recovered),
+                Err(e) => {
+                    error!("Crash recovery failed: {:#}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         // Initialize OCR processor
         let ocr_config = self.config.ocr_config();
         let ocr_processor = Arc::new(OcrProcessor::new(ocr_config).await?);
 
-        // Initialize capture engine
-        let capture_config = self.config.capture_config();
-        let mut capture_engine = CaptureEngine::new(capture_config)?;
-
         // Initialize API server with the same database path
         let api_config = self.config.api_config(&db_config.path);
-        let api_server = ApiServer::new(api_config.clone()).await?;
+        let api_server = ApiServer::new(api_config).await?;
+        let capture_storage = Arc::new(
+            CaptureStorage::new(&self.config.capture_storage_config()?)
+                .context("Failed to open capture storage")?,
+        );
+        api_server.set_capture_storage(Arc::clone(&capture_storage));
+        let data_dir = self.config.data_dir();
+        api_server.set_setup_paths(screensearch_api::setup::SetupPaths {
+            config_path: std::path::absolute(&self.config_path)
+                .unwrap_or_else(|_| self.config_path.clone()),
+            data_dir: std::path::absolute(&data_dir).unwrap_or_else(|_| data_dir.clone()),
+            database_path: PathBuf::from(&db_config.path),
+        });
+        let data_move_control = api_server.data_move_control();
+        if let Ok(data_dir) = self.config.movable_data_dir() {
+            data_move_control.set_source(data_dir);
+        }
+        if let Some(log_file) = &self.log_file {
+            api_server.set_log_file(&std::path::absolute(log_file).unwrap_or(log_file.clone()));
+        }
+        api_server.set_rag_config(screensearch_api::handlers::rag_helpers::RagConfig {
+            recency_half_life_hours: self.config.embeddings.recency_half_life_hours.max(0.0),
+            context_tokens: self.config.embeddings.context_tokens,
+            model_context_tokens: self.config.embeddings.model_context_tokens.clone(),
+            max_context_chunks: self.config.embeddings.max_context_chunks.max(1),
+            summary_min_hours: if self.config.summaries.enabled {
+                self.config.summaries.report_min_hours.max(0.0)
+            } else {
+                0.0
+            },
+        });
+        api_server.set_timesheet_config(self.config.time_tracking_config());
+        api_server.set_local_llm_config(screensearch_api::local_llm::LocalLlmConfig {
+            model_url: self.config.llm.model_url.clone(),
+            model_file: self.config.llm.model_file.clone(),
+            context_size: self.config.llm.context_size,
+            threads: self.config.llm.threads,
+            max_tokens: self.config.llm.max_tokens.max(1),
+        });
+        let profile_control = api_server.profile_control();
+        profile_control.set_profiles(screensearch_api::profiles::Profiles {
+            active: active_profile().to_string(),
+            available: profiles::names(&self.config.profiles)
+                .into_iter()
+                .map(|name| {
+                    let dir = profiles::profile_dir(&data_dir, &name);
+                    screensearch_api::profiles::Profile {
+                        data_dir: std::path::absolute(&dir).unwrap_or(dir).display().to_string(),
+                        name,
+                    }
+                })
+                .collect(),
+        });
+        let orphans = match recovered {
+            Some((report, orphans)) => {
+                api_server.set_recovery_report(report);
+                orphans
+            }
+            None => Vec::new(),
+        };
+
+        // Initialize capture engine, sharing its runtime controls with the
+        // API so settings changes apply without a restart
+        let capture_config = self.config.capture_config();
+        let capture_control = api_server.capture_control();
+        capture_control.set_interval_ms(capture_config.interval_ms);
+        capture_control.set_monitor_indices(capture_config.monitor_indices.clone());
+        capture_control.set_config_excluded_apps(self.config.privacy.excluded_apps.clone());
+        capture_control.disk_space().set_thresholds_mb(
+            self.config.storage.cleanup_below_mb,
+            self.config.storage.pause_below_mb,
+        );
+        capture_control.power().set_settings(
+            self.config.performance.battery_interval_ms,
+            self.config.performance.battery_pause_workers,
+        );
+        let memory_budget = capture_control.memory_budget();
+        memory_budget.set_limit_mb(self.config.performance.max_memory_mb);
+        if self.config.embeddings.enabled {
+            memory_budget.set_reserved_bytes(
+                self.config.embeddings.batch_size.max(0) as u64 * EMBEDDING_FRAME_BYTES,
+            );
+        }
+        if let Err(e) = api_server.restore_capture_settings().await {
+            warn!("Failed to restore capture settings: {}", e);
+        }
+        let mut capture_engine =
+            CaptureEngine::with_control(capture_config, Arc::clone(&capture_control))?;
+
+        // Apply config.toml edits to the running pipeline
+        let (config_tx, config_rx) = tokio::sync::watch::channel(self.config.clone());
+        let reloader = ConfigReloader::new(
+            self.config_path.clone(),
+            config_tx,
+            Arc::clone(&capture_control),
+            self.log_filter.clone(),
+        );
+        if let Err(e) = reloader.spawn(self.shutdown_tx.subscribe()) {
+            warn!("Config hot-reload disabled: {:#}", e);
+        }
+
+        // Free space on the database and captures volumes when they fill up
+        let db_dir = Path::new(&db_config.path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        tokio::spawn(disk_monitor::run(
+            Arc::clone(&capture_control),
+            Arc::clone(&db),
+            Arc::clone(&capture_storage),
+            vec![db_dir, captures_dir()],
+            self.shutdown_tx.subscribe(),
+        ));
+
+        // Throttle capture and background work on battery
+        tokio::spawn(power_monitor::run(
+            Arc::clone(&capture_control),
+            self.shutdown_tx.subscribe(),
+        ));
 
         // Start background embedding worker
         if self.config.embeddings.enabled {
@@ -458,96 +1555,450 @@ impl App {
                 error!("Failed to start embedding worker: {}", e);
             }
         }
+        let embedding_engine = api_server.embedding_engine().await;
+
+        // Start scheduled report generation
+        api_server.start_report_scheduler(self.config.report_scheduler_config());
+
+        // Summarize each finished hour for reports over long periods
+        api_server.start_summarizer(self.config.summarizer_config());
+
+        // Periodically delete frames past the retention period
+        api_server.start_retention_worker();
+
+        // Encode old frames into video chunks
+        api_server.start_compaction_worker(self.config.compaction_config());
+
+        // Move old frame images into daily archives
+        api_server.start_archival_worker(self.config.archival_config());
+
+        // Write daily notes into the configured notes vault
+        api_server.start_markdown_export(self.config.markdown_export_config());
 
-        let (frame_tx, frame_rx) = tokio::sync::mpsc::channel(100);
+        // Run queued background jobs (embedding backfills, reports, retention,
+        // summaries and the other maintenance queued above)
+        api_server.start_job_queue();
+
+        // Published by the updater, install requests come from /admin/update
+        let update_control = api_server.update_control();
+
+        // One sub-queue per monitor, drained in turn, so a busy monitor
+        // cannot starve the others
+        let (frame_tx, frame_rx) = screensearch_capture::frame_queue(100);
         let (processed_tx, mut processed_rx) = tokio::sync::mpsc::channel(100);
+        let frame_queue = frame_tx.queue();
+        capture_control.set_frame_queue(Arc::clone(&frame_queue));
+        let processed_queue = processed_tx.downgrade();
+        let budget_queues = (Arc::clone(&frame_queue), processed_queue.clone());
+        let drain_queues = (Arc::clone(&frame_queue), processed_queue.clone());
+        let budget_control = Arc::clone(&capture_control);
+        let orphan_tx = frame_tx.clone();
         
-        let app_config_clone = self.config.clone();
         let db_clone = Arc::clone(&db);
+        let status = Arc::clone(&self.status);
+        let alerts = api_server.alert_watcher();
+        let redactor = api_server.redactor();
+        let documents = self
+            .config
+            .documents
+            .enabled
+            .then(|| api_server.document_extractor());
         let ocr_clone = Arc::clone(&ocr_processor);
+
+        // Global hotkeys act through the same controls as the tray and the API
+        let _hotkeys = if self.config.hotkeys.enabled {
+            let settings = &self.config.hotkeys;
+            let bindings = hotkeys::bindings([
+                (HotkeyAction::CaptureNow, settings.capture.as_str()),
+                (HotkeyAction::OpenSearch, settings.search.as_str()),
+                (HotkeyAction::TogglePause, settings.pause.as_str()),
+            ]);
+            let (hotkey_tx, hotkey_rx) = tokio::sync::mpsc::unbounded_channel();
+            let actions = HotkeyActions {
+                capture: ScreenCapture::new(self.config.capture_config())?,
+                capture_control: Arc::clone(&capture_control),
+                db: Arc::clone(&db),
+                storage: Arc::clone(&capture_storage),
+                alerts: Arc::clone(&alerts),
+                redactor: Arc::clone(&redactor),
+                documents: documents.clone(),
+                ocr: Arc::clone(&ocr_processor),
+                config: config_rx.clone(),
+                api_url: self.config.api_url(),
+            };
+            tokio::spawn(actions.run(hotkey_rx, self.shutdown_tx.subscribe()));
+            match hotkeys::register(bindings, hotkey_tx) {
+                Ok(hotkeys) => Some(hotkeys),
+                Err(e) => {
+                    warn!("Hotkeys disabled: {:#}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
         
         let mut shutdown_rx1 = self.shutdown_tx.subscribe();
-        let mut shutdown_rx2 = self.shutdown_tx.subscribe();
-        let mut shutdown_rx3 = self.shutdown_tx.subscribe();
-        let mut shutdown_rx4 = self.shutdown_tx.subscribe();
 
         capture_engine.start()?;
 
         let capture_handle = tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(3000));
             loop {
-                tokio::select! {
-                    _ = interval.tick() => {
-                        while let Some(frame) = capture_engine.try_get_frame() {
-                           if frame_tx.send(frame).await.is_err() { break; }
-                        }
-                    }
-                    _ = shutdown_rx1.recv() => {
-                        let _ = capture_engine.stop();
-                        break;
-                    }
+                let stopping = tokio::select! {
+                    _ = interval.tick() => false,
+                    _ = shutdown_rx1.recv() => true,
+                };
+                if stopping {
+                    // Stop capturing, but still hand over the frames already captured
+                    let _ = capture_engine.stop();
+                }
+
+                let budget = budget_control.memory_budget();
+                let mut queued = budget_queues.0.len()
+                    + tray_status::queued(&budget_queues.1)
+                    + IN_FLIGHT_FRAMES;
+                while let Some(frame) = capture_engine.try_get_frame() {
+                   // Over budget: drop the frame rather than queue it
+                   if !budget.admit(queued, frame.image.as_raw().len() as u64) { continue; }
+                   // A full queue drops the oldest frame of the busiest monitor
+                   match frame_tx.push(frame) {
+                       Ok(None) => queued += 1,
+                       Ok(Some(dropped)) => debug!("Frame queue full, dropped a frame of monitor {}", dropped.monitor_index),
+                       Err(_) => break,
+                   }
                 }
+                budget.update(queued, std::time::Instant::now());
+
+                // Dropping frame_tx lets OCR finish the queue and stop
+                if stopping { break; }
             }
         });
 
         let ocr_handle = ocr_clone.start_processing(frame_rx, processed_tx);
-        let ocr_shutdown = tokio::spawn(async move { let _ = shutdown_rx2.recv().await; });
 
-        let db_handle = tokio::spawn(async move {
-            loop {
-                tokio::select! {
-                    Some(processed) = processed_rx.recv() => {
-                         let storage_config = &app_config_clone.storage;
-                         if let Err(e) = store_processed_frame(&db_clone, processed, storage_config).await {
-                             error!("Failed to save frame: {}", e);
-                         }
+        // Run OCR again on images written just before a crash; the sender is
+        // dropped when done so it does not hold the frame queue open
+        tokio::task::spawn_blocking(move || {
+            for orphan in orphans {
+                match orphan.load() {
+                    Ok(frame) => {
+                        if orphan_tx.blocking_send(frame).is_err() {
+                            break;
+                        }
                     }
-                    _ = shutdown_rx3.recv() => break,
+                    Err(e) => warn!("Failed to adopt orphaned image: {:#}", e),
                 }
             }
         });
 
+        // Runs until OCR stops and its queue is empty
+        let db_handle = tokio::spawn(async move {
+            while let Some(processed) = processed_rx.recv().await {
+                 // Re-read per frame so config.toml edits apply to the next one
+                 let (storage_config, sensitive_filter) = {
+                     let config = config_rx.borrow();
+                     (
+                         config.storage.clone(),
+                         SensitiveContentFilter::new(config.privacy.sensitive_content),
+                     )
+                 };
+                 // Drop or blur sensitive frames before anything is written
+                 let Some(processed) = sensitive_filter.filter(processed) else {
+                     continue;
+                 };
+                 if let Err(e) = store_processed_frame(&db_clone, &capture_storage, &alerts, &redactor, documents.as_deref(), processed, &storage_config).await {
+                     error!("Failed to save frame: {}", e);
+                     status.record_error();
+                 }
+            }
+        });
+
         let api_handle = tokio::spawn(async move {
             if let Err(e) = api_server.run().await { error!("{}", e); }
-             let _ = shutdown_rx4.recv().await;
         });
         
+        // Keep a history of OCR and pipeline metrics for /stats/history
+        let metrics_queues = (Arc::clone(&frame_queue), processed_queue.clone());
+        tokio::spawn(metrics_recorder::run(
+            Arc::clone(&db),
+            ocr_processor.metrics().clone(),
+            Arc::clone(&capture_control),
+            move || metrics_queues.0.len() + tray_status::queued(&metrics_queues.1),
+            self.shutdown_tx.subscribe(),
+        ));
+
+        // Publish the pipeline state to the tray and apply its pause toggle
         let mut shutdown_rx6 = self.shutdown_tx.subscribe();
+        let status = Arc::clone(&self.status);
+        let ocr_errors = Arc::clone(&ocr_processor.metrics().errors);
+        let status_db = Arc::clone(&db);
+        let power_control = Arc::clone(&capture_control);
         tokio::spawn(async move {
-             let _ = shutdown_rx6.recv().await;
+            let mut interval = tokio::time::interval(tray_status::STATUS_INTERVAL);
+            let mut seen_ocr_errors = ocr_errors.load(std::sync::atomic::Ordering::Relaxed);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        let errors = ocr_errors.load(std::sync::atomic::Ordering::Relaxed);
+                        if errors > seen_ocr_errors {
+                            status.record_error();
+                            seen_ocr_errors = errors;
+                        }
+                    }
+                    Some(paused) = pause_requests.recv() => {
+                        info!("Capture {} from the tray", if paused { "paused" } else { "resumed" });
+                        set_capture_paused(&capture_control, &status_db, paused).await;
+                    }
+                    _ = shutdown_rx6.recv() => break,
+                }
+
+                let backlog = frame_queue.len() + tray_status::queued(&processed_queue);
+                status.update(
+                    capture_control.is_paused(),
+                    !capture_control.is_scheduled(),
+                    capture_control.disk_space().is_suspended(),
+                    backlog,
+                );
+            }
         });
 
-        if self.config.api.auto_open_browser {
-             let url = format!("http://{}:{}", api_config.host, api_config.port);
+        // The web interface is unreachable from a browser on a local socket
+        if self.config.api.auto_open_browser && self.config.api.local_socket.is_empty() {
+             let url = self.config.api_url();
              tokio::spawn(async move {
                 tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
                 let _ = webbrowser::open(&url);
              });
         }
 
-        // Check for updates in background
-        tokio::spawn(async {
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-            if let Some(update) = update_checker::check_updates().await {
-                info!("========================================");
-                info!("UPDATE AVAILABLE!");
-                info!("Current version: {}", version::VERSION);
-                info!("Latest version: {}", update.version);
-                info!("Download: {}", update.download_url);
-                info!("========================================");
+        // Check for updates in background and install them on request
+        let (restart_tx, mut restart_rx) = tokio::sync::oneshot::channel();
+        match updater::Updater::new(
+            update_control,
+            self.config.updates.check,
+            self.config.updates.auto_download,
+        ) {
+            Ok(updater) => {
+                tokio::spawn(updater.run(restart_tx, self.shutdown_tx.subscribe()));
             }
-        });
+            Err(e) => warn!("Updates disabled: {:#}", e),
+        }
 
         tokio::select! {
             _ = signal::ctrl_c() => info!("Ctrl+C"),
             _ = external_shutdown.recv() => info!("External Shutdown"),
+            Ok(()) = &mut restart_rx => {
+                info!("Restarting to install an update");
+                self.restart.store(true, Ordering::Relaxed);
+            }
+            name = profile_control.switch_requested() => self.switch_profile(name),
+            Some(name) = profile_requests.recv() => self.switch_profile(name),
+            request = data_move_control.move_requested() => {
+                info!("Restarting to move the data directory to {}", request.to.display());
+                *self.move_to.lock().unwrap_or_else(|e| e.into_inner()) = Some(request);
+                self.restart.store(true, Ordering::Relaxed);
+            }
+        }
+
+        // Leave embeddings for the next start when saving power
+        let embedding_engine =
+            embedding_engine.filter(|_| !power_control.power().workers_paused());
+        self.shutdown(
+            &db,
+            embedding_engine,
+            drain_queues,
+            [capture_handle, ocr_handle, db_handle],
+            sentinel,
+        )
+        .await;
+        api_handle.abort();
+
+        // Shut down cleanly, so an update to this version has proven itself
+        updater::confirm();
+
+        let request = self
+            .move_to
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(request) = request {
+            // Nothing may write to the database while it is copied
+            db.pool().close().await;
+            self.move_data_dir(request).await;
         }
 
-        let _ = self.shutdown_tx.send(());
-        let _ = tokio::join!(capture_handle, ocr_handle, ocr_shutdown, db_handle, api_handle);
-        
         Ok(())
     }
+
+    /// Move the data directory after shutdown, as requested through
+    /// `/admin/move-data`
+    ///
+    /// On failure the application restarts on its old data.
+    async fn move_data_dir(&self, request: MoveRequest) {
+        use screensearch_api::data_move;
+
+        let from = match self.config.movable_data_dir() {
+            Ok(from) => from,
+            Err(reason) => {
+                error!("The data directory cannot be moved: {}", reason);
+                return;
+            }
+        };
+        info!("Moving {} to {}", from.display(), request.to.display());
+
+        let mut logged = std::time::Instant::now();
+        let result = data_move::move_data(
+            &from,
+            &request.to,
+            &self.config.database.path,
+            &self.config_path,
+            |progress| {
+                if logged.elapsed() >= MOVE_PROGRESS_INTERVAL {
+                    logged = std::time::Instant::now();
+                    info!(
+                        "Copied {}/{} files ({} of {} MB)",
+                        progress.files_copied,
+                        progress.files_total,
+                        progress.bytes_copied / (1024 * 1024),
+                        progress.bytes_total / (1024 * 1024)
+                    );
+                }
+            },
+        )
+        .await;
+
+        match result {
+            Ok(report) => {
+                info!(
+                    files = report.files,
+                    paths_rewritten = report.paths_rewritten,
+                    "Moved the data directory to {}",
+                    report.to
+                );
+                if request.remove_old {
+                    // Files still open, such as the log file, may stay behind
+                    match data_move::remove_old(&from) {
+                        Ok(()) => info!("Deleted {}", from.display()),
+                        Err(e) => warn!("Failed to delete the old data directory: {:#}", e),
+                    }
+                }
+            }
+            Err(e) => error!(
+                "Failed to move the data directory, keeping {}: {:#}",
+                from.display(),
+                e
+            ),
+        }
+    }
+
+    /// Remember `name` for the next start and stop to restart with it
+    fn switch_profile(&self, name: String) {
+        info!("Restarting with profile '{}'", name);
+        if let Err(e) = profiles::save_active(&self.config.data_dir(), &name) {
+            warn!("Failed to remember the profile: {:#}", e);
+        }
+        *self.switch_to.lock().unwrap_or_else(|e| e.into_inner()) = Some(name);
+        self.restart.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop capture, then finish the work already captured before exiting
+    ///
+    /// Queued frames are processed and stored, recently stored frames are
+    /// embedded, the write-ahead log is checkpointed and the crash sentinel is
+    /// removed. Each step is bounded by a timeout, so a stalled stage cannot
+    /// keep the process alive.
+    async fn shutdown(
+        &self,
+        db: &Arc<DatabaseManager>,
+        embedding_engine: Option<Arc<EmbeddingEngine>>,
+        queues: (
+            Arc<FrameQueue>,
+            tokio::sync::mpsc::WeakSender<ProcessedFrame>,
+        ),
+        pipeline: [tokio::task::JoinHandle<()>; 3],
+        sentinel: recovery::Sentinel,
+    ) {
+        info!("Shutting down: stopping capture and finishing queued frames");
+        let _ = self.shutdown_tx.send(());
+
+        let aborts: Vec<_> = pipeline.iter().map(|task| task.abort_handle()).collect();
+        if tokio::time::timeout(DRAIN_TIMEOUT, futures::future::join_all(pipeline))
+            .await
+            .is_err()
+        {
+            let pending = queues.0.len() + tray_status::queued(&queues.1);
+            warn!(
+                "Gave up on {} queued frames after {}s",
+                pending,
+                DRAIN_TIMEOUT.as_secs()
+            );
+            aborts.iter().for_each(|task| task.abort());
+        }
+
+        if let Some(engine) = embedding_engine {
+            // Respect embeddings being switched off from the settings panel
+            let enabled = !matches!(
+                db.get_metadata("embeddings_enabled").await,
+                Ok(Some(value)) if value != "true"
+            );
+            if enabled {
+                info!("Embedding frames stored since the last run");
+                let flush = screensearch_api::workers::flush_embeddings(
+                    Arc::clone(db),
+                    engine,
+                    self.config.embeddings.batch_size,
+                );
+                match tokio::time::timeout(EMBEDDING_FLUSH_TIMEOUT, flush).await {
+                    Ok(Ok(frames)) => info!("Embedded {} frames", frames),
+                    Ok(Err(e)) => warn!("Failed to embed frames: {}", e),
+                    Err(_) => warn!(
+                        "Stopped embedding after {}s; the rest is embedded on the next start",
+                        EMBEDDING_FLUSH_TIMEOUT.as_secs()
+                    ),
+                }
+            }
+        }
+
+        match db.checkpoint().await {
+            Ok(()) => info!("Database checkpointed"),
+            Err(e) => warn!("Failed to checkpoint the database: {}", e),
+        }
+        sentinel.remove();
+        info!("Shutdown complete");
+    }
+}
+
+impl EventLoopState {
+    /// Show the pipeline state in the tray icon, tooltip and pause item
+    fn refresh_status(&mut self) {
+        let paused = self.status.is_paused();
+        if self.pause_item.is_checked() != paused {
+            self.pause_item.set_checked(paused);
+        }
+
+        let state = self.status.state();
+        if self.shown_state == Some(state) {
+            return;
+        }
+        self.shown_state = Some(state);
+
+        if let Err(e) = self.tray_icon.set_icon(Some(tray_icon_from(&state.icon(&self.base_icon)))) {
+            warn!("Failed to update tray icon: {}", e);
+        }
+        if let Err(e) = self.tray_icon.set_tooltip(Some(state.tooltip())) {
+            warn!("Failed to update tray tooltip: {}", e);
+        }
+    }
+}
+
+/// Convert an image to a tray icon
+fn tray_icon_from(image: &image::RgbaImage) -> tray_icon::Icon {
+    let (width, height) = image.dimensions();
+    tray_icon::Icon::from_rgba(image.as_raw().clone(), width, height).unwrap_or_else(|_| {
+        // Fallback to white square if dimensions invalid
+        tray_icon::Icon::from_rgba(vec![255u8; 4 * 32 * 32], 32, 32).unwrap()
+    })
 }
 
 impl winit::application::ApplicationHandler for EventLoopState {
@@ -569,15 +2020,31 @@ impl winit::application::ApplicationHandler for EventLoopState {
         event_loop: &winit::event_loop::ActiveEventLoop,
         _cause: winit::event::StartCause,
     ) {
-        event_loop.set_control_flow(ControlFlow::Wait);
+        // Wake up regularly to follow the pipeline state
+        event_loop.set_control_flow(ControlFlow::wait_duration(tray_status::STATUS_INTERVAL));
 
         // Process menu events
         while let Ok(event) = self.menu_channel.try_recv() {
-            if event.id == self.menu_items.0.id() {
+            if event.id == self.open_item.id() {
                 // Open Interface
                 info!("Opening web interface");
                 let _ = webbrowser::open(&self.api_url);
-            } else if event.id == self.menu_items.1.id() {
+            } else if event.id == self.pause_item.id() {
+                // Pause capture (the menu has already toggled the check mark)
+                let _ = self.pause_tx.send(self.pause_item.is_checked());
+            } else if let Some((_, name)) =
+                self.profile_items.iter().find(|(item, _)| event.id == item.id())
+            {
+                // Keep the check mark on the running profile until restarted
+                let name = name.clone();
+                for (item, item_name) in &self.profile_items {
+                    item.set_checked(item_name == active_profile());
+                }
+                if name != active_profile() {
+                    info!("Switching to profile '{}' from the tray", name);
+                    let _ = self.profile_tx.send(name);
+                }
+            } else if event.id == self.quit_item.id() {
                 // Quit
                 info!("Quit requested from tray menu");
                 let _ = self.shutdown_tx.blocking_send(());
@@ -606,6 +2073,8 @@ impl winit::application::ApplicationHandler for EventLoopState {
             }
         }
 
+        self.refresh_status();
+
         // Check if app task finished
         if self.app_task.is_finished() {
             info!("Application task completed");
@@ -618,14 +2087,153 @@ impl winit::application::ApplicationHandler for EventLoopState {
     }
 }
 
+/// Directory of frame images, in the active profile's directory
+fn captures_dir() -> PathBuf {
+    let data_dir = DATA_DIR.get_or_init(|| AppConfig::default().data_dir());
+    profiles::profile_dir(data_dir, active_profile()).join("captures")
+}
+
+/// Pause or resume capture
+///
+/// Persisted like the settings panel does, so it shows the same state.
+async fn set_capture_paused(control: &CaptureControl, db: &DatabaseManager, paused: bool) {
+    control.set_paused(paused);
+    if let Err(e) = db.set_capture_paused(paused).await {
+        warn!("Failed to save paused state: {}", e);
+    }
+}
+
+/// Handles the hotkey actions are carried out with
+struct HotkeyActions {
+    capture: ScreenCapture,
+    capture_control: Arc<CaptureControl>,
+    db: Arc<DatabaseManager>,
+    storage: Arc<CaptureStorage>,
+    alerts: Arc<AlertWatcher>,
+    redactor: Arc<Redactor>,
+    documents: Option<Arc<DocumentExtractor>>,
+    ocr: Arc<OcrProcessor>,
+    config: tokio::sync::watch::Receiver<AppConfig>,
+    api_url: String,
+}
+
+impl HotkeyActions {
+    /// Carry out hotkey presses until shutdown
+    async fn run(
+        self,
+        mut actions: tokio::sync::mpsc::UnboundedReceiver<HotkeyAction>,
+        mut shutdown: broadcast::Receiver<()>,
+    ) {
+        loop {
+            let action = tokio::select! {
+                Some(action) = actions.recv() => action,
+                _ = shutdown.recv() => break,
+            };
+
+            match action {
+                HotkeyAction::CaptureNow => match self.capture_now().await {
+                    Ok(stored) => info!("Captured {} frame(s) with a hotkey", stored),
+                    Err(e) => error!("Hotkey capture failed: {:#}", e),
+                },
+                HotkeyAction::OpenSearch => {
+                    info!("Opening web interface with a hotkey");
+                    let _ = webbrowser::open(&self.api_url);
+                }
+                HotkeyAction::TogglePause => {
+                    let paused = !self.capture_control.is_paused();
+                    info!("Capture {} with a hotkey", if paused { "paused" } else { "resumed" });
+                    set_capture_paused(&self.capture_control, &self.db, paused).await;
+                }
+            }
+        }
+    }
+
+    /// Capture every captured monitor now, even while paused, and tag the
+    /// stored frames with `hotkeys.capture_tag`
+    ///
+    /// Excluded apps and sensitive content are handled like in the capture
+    /// loop. Returns the number of frames stored.
+    async fn capture_now(&self) -> Result<usize> {
+        let (storage_config, sensitive_filter, tag_name) = {
+            let config = self.config.borrow();
+            (
+                config.storage.clone(),
+                SensitiveContentFilter::new(config.privacy.sensitive_content),
+                config.hotkeys.capture_tag.trim().to_string(),
+            )
+        };
+
+        let tag_id = if tag_name.is_empty() {
+            None
+        } else {
+            Some(match self.db.get_tag_by_name(&tag_name).await? {
+                Some(tag) => tag.id,
+                None => {
+                    self.db
+                        .create_tag(screensearch_db::NewTag {
+                            tag_name,
+                            description: Some("Captured with a hotkey".to_string()),
+                            color: None,
+                        })
+                        .await?
+                }
+            })
+        };
+
+        let mut stored = 0;
+        for monitor in MonitorInfo::enumerate()? {
+            if !self.capture_control.captures_monitor(monitor.index) {
+                continue;
+            }
+            let frame = self.capture.capture_frame(monitor.index).await?;
+            if self
+                .capture_control
+                .is_excluded(frame.active_process.as_deref())
+            {
+                continue;
+            }
+            // Frames without readable text are dropped like in the pipeline
+            let Some(processed) = self.ocr.process_frame(frame).await? else {
+                continue;
+            };
+            let Some(processed) = sensitive_filter.filter(processed) else {
+                continue;
+            };
+
+            let frame_id = store_processed_frame(
+                &self.db,
+                &self.storage,
+                &self.alerts,
+                &self.redactor,
+                self.documents.as_deref(),
+                processed,
+                &storage_config,
+            )
+            .await?;
+            if let Some(tag_id) = tag_id {
+                self.db.add_tag_to_frame(frame_id, tag_id).await?;
+            }
+            stored += 1;
+        }
+        Ok(stored)
+    }
+}
+
 async fn store_processed_frame(
     db: &DatabaseManager,
+    storage: &CaptureStorage,
+    alerts: &AlertWatcher,
+    redactor: &Redactor,
+    documents: Option<&DocumentExtractor>,
     processed: screensearch_capture::ProcessedFrame,
     config: &StorageSettings,
 ) -> Result<i64> {
      use screensearch_db::{NewFrame, NewOcrText};
     use image::DynamicImage;
 
+    // Mask redaction rule matches before any text or pixel is written
+    let processed = redactor.redact(processed).await;
+
     let mut image = DynamicImage::ImageRgba8(processed.frame.image.clone());
 
     if config.max_width > 0 && image.width() > config.max_width {
@@ -646,37 +2254,24 @@ async fn store_processed_frame(
         processed.frame.monitor_index, timestamp_str, ext
     );
 
-    // Use AppData for captures in production, current directory in development
-    let captures_dir = if cfg!(debug_assertions) {
-        PathBuf::from("captures")
-    } else {
-        if let Some(data_dir) = dirs::data_local_dir() {
-            data_dir.join("screensearch").join("captures")
-        } else {
-            PathBuf::from("captures")
-        }
-    };
-
-    let image_path = captures_dir.join(&image_filename);
-
-    if let Some(parent) = image_path.parent() {
-        tokio::fs::create_dir_all(parent).await?;
-    }
-
-    let mut file = std::fs::File::create(&image_path)?;
-    image.write_to(&mut file, format).context("Failed to save frame image")?;
+    let mut data = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut data, format).context("Failed to encode frame image")?;
+    let file_path = storage
+        .put(&image_filename, data.into_inner())
+        .await
+        .context("Failed to save frame image")?;
 
     let new_frame = NewFrame {
         timestamp: processed.frame.timestamp,
         device_name: format!("monitor-{}", processed.frame.monitor_index),
-        file_path: image_path.to_string_lossy().to_string(),
+        file_path,
         monitor_index: processed.frame.monitor_index as i32,
         width: image.width() as i32,
         height: image.height() as i32,
         offset_index: 0,
         chunk_id: None,
         active_window: processed.frame.active_window,
-        active_process: processed.frame.active_process,
+        active_process: processed.frame.active_process.clone(),
         browser_url: None,
         focused: Some(true),
     };
@@ -686,7 +2281,7 @@ async fn store_processed_frame(
         .await
         .context("Failed to insert frame")?;
 
-    for region in processed.ocr_result.regions {
+    for region in &processed.ocr_result.regions {
         let ocr_text = NewOcrText {
             frame_id,
             text: region.text.clone(),
@@ -710,26 +2305,163 @@ async fn store_processed_frame(
         db.insert_ocr_text(ocr_text).await?;
     }
 
+    // Evaluate keyword watch alerts against the stored text
+    let texts: Vec<String> = processed
+        .ocr_result
+        .regions
+        .iter()
+        .map(|region| region.text.clone())
+        .collect();
+    alerts
+        .check_frame(frame_id, processed.frame.active_process.as_deref(), &texts)
+        .await;
+
+    // Store receipts and invoices shown in the frame
+    if let Some(documents) = documents {
+        documents
+            .check_frame(
+                frame_id,
+                processed.frame.timestamp,
+                processed.frame.active_process.as_deref(),
+                &processed.ocr_result.regions,
+            )
+            .await;
+    }
+
     Ok(frame_id)
 }
 
+/// Flush buffered spans while the runtime exporting them is alive
+fn flush_spans(tracer_provider: Option<opentelemetry_sdk::trace::TracerProvider>) {
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            warn!("Failed to flush OTLP spans: {}", e);
+        }
+    }
+}
+
 fn main() -> Result<()> {
-    let config = AppConfig::load().unwrap_or_else(|_| AppConfig::default());
-    let _log_guard = init_tracing(&config.logging)?;
+    let cli = <cli::Cli as clap::Parser>::parse();
+    PORTABLE.store(cli.portable, Ordering::Relaxed);
+    let config_path = cli.config.clone().unwrap_or_else(|| {
+        if cli.portable {
+            AppConfig::portable_path()
+        } else {
+            AppConfig::default_path()
+        }
+    });
+    let mut config = AppConfig::load(&config_path).unwrap_or_else(|_| AppConfig::default());
+    let _ = DATA_DIR.set(config.data_dir());
+    let profile = profiles::select(cli.profile.as_deref(), &config.profiles, &config.data_dir())?;
+    let _ = PROFILE.set(profile);
+    config.apply_profile();
+    if config.is_portable() {
+        screensearch_embeddings::set_models_dir(app_data_path("models"));
+    }
+
+    let service = match cli.command {
+        None => false,
+        Some(cli::Command::Run { service }) => service,
+        Some(command) => return cli::execute(command, &config, &config_path),
+    };
+
+    // A second instance would store every frame again, so hand over to the
+    // running one instead
+    let instance = match single_instance::acquire() {
+        Ok(Some(guard)) => Some(guard),
+        Ok(None) => {
+            println!("ScreenSearch is already running");
+            if !service && config.api.local_socket.is_empty() {
+                let _ = webbrowser::open(&config.api_url());
+            }
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("Could not check for a running instance: {:#}", e);
+            None
+        }
+    };
 
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()
         .context("Failed to build Tokio runtime")?;
 
+    let tracing_guards = {
+        let _runtime_guard = runtime.enter();
+        let mut logging = config.logging.clone();
+        // No console when started at login, so always keep a log file
+        logging.log_to_file |= service;
+        // Stored with the other data files when there is no working
+        // directory to speak of, or nothing may be left outside the install
+        if service || config.is_portable() {
+            logging.log_file = app_data_path(&logging.log_file)
+                .to_string_lossy()
+                .to_string();
+        }
+        init_tracing(&logging, !service)?
+    };
+
+    // Install a downloaded update, or roll back one that keeps failing to start
+    match updater::on_startup() {
+        Ok(updater::Startup::Restart(exe)) => {
+            drop(instance);
+            return updater::relaunch(&exe, std::env::args_os().skip(1));
+        }
+        Ok(updater::Startup::Continue) => {}
+        Err(e) => error!("Failed to apply the update: {:#}", e),
+    }
+
+    let tracer_provider = tracing_guards.tracer_provider.clone();
+    let status = Arc::new(PipelineStatus::default());
+    let app = App::new(
+        config.clone(),
+        config_path,
+        tracing_guards.log_filter.clone(),
+        tracing_guards.log_file.clone(),
+        Arc::clone(&status),
+    );
+    let restart = Arc::clone(&app.restart);
+    let switch_to = Arc::clone(&app.switch_to);
+
+    if service {
+        info!("Running as a service without the tray icon");
+        // Held so the app keeps running until Ctrl+C or termination
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::mpsc::channel(1);
+        let (_pause_tx, pause_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (_profile_tx, profile_rx) = tokio::sync::mpsc::unbounded_channel();
+        let result = runtime.block_on(app.run_with_signal(shutdown_rx, pause_rx, profile_rx));
+        flush_spans(tracer_provider);
+        if result.is_ok() && restart.load(Ordering::Relaxed) {
+            return restart_app(instance, &switch_to);
+        }
+        return result;
+    }
+
     let event_loop = EventLoop::new()
         .context("Failed to build EventLoop")?;
 
     let tray_menu = Menu::new();
     let open_item = MenuItem::new("Open Interface", true, None);
+    let pause_item = CheckMenuItem::new("Pause capture", true, false, None);
     let quit_item = MenuItem::new("Quit ScreenSearch", true, None);
     
-    tray_menu.append_items(&[&open_item, &PredefinedMenuItem::separator(), &quit_item])?;
+    tray_menu.append_items(&[&open_item, &pause_item])?;
+
+    // Switch profiles from the tray when there are several
+    let profile_names = profiles::names(&config.profiles);
+    let mut profile_items = Vec::new();
+    if profile_names.len() > 1 {
+        let profile_menu = Submenu::new("Profile", true);
+        for name in profile_names {
+            let item = CheckMenuItem::new(&name, true, name == active_profile(), None);
+            profile_menu.append(&item)?;
+            profile_items.push((item, name));
+        }
+        tray_menu.append(&profile_menu)?;
+    }
+
+    tray_menu.append_items(&[&PredefinedMenuItem::separator(), &quit_item])?;
 
     // Load icon from assets - try multiple locations for installed vs development
     let icon_path = if let Ok(exe_path) = std::env::current_exe() {
@@ -748,56 +2480,59 @@ fn main() -> Result<()> {
         PathBuf::from("assets/icon.png")
     };
 
-    let icon = match image::open(&icon_path) {
-        Ok(img) => {
-            let rgba = img.into_rgba8();
-            let (width, height) = rgba.dimensions();
-            let rgba_vec = rgba.into_raw();
-            tray_icon::Icon::from_rgba(rgba_vec, width, height).unwrap_or_else(|_| {
-                // Fallback to white square if dimensions invalid
-                tray_icon::Icon::from_rgba(vec![255u8; 4 * 32 * 32], 32, 32).unwrap()
-            })
-        }
+    let base_icon = match image::open(&icon_path) {
+        Ok(img) => img.into_rgba8(),
         Err(e) => {
             error!("Failed to load icon from {:?}: {}", icon_path, e);
             // Fallback to white square
-            tray_icon::Icon::from_rgba(vec![255u8; 4 * 32 * 32], 32, 32).unwrap()
+            image::RgbaImage::from_pixel(32, 32, image::Rgba([255, 255, 255, 255]))
         }
     };
 
     let tray_icon = TrayIconBuilder::new()
         .with_menu(Box::new(tray_menu))
         .with_tooltip("ScreenSearch")
-        .with_icon(icon)
+        .with_icon(tray_icon_from(&base_icon))
         .build()
         .context("Failed to build TrayIcon")?;
 
-    let app = App::new(config.clone());
     let (shutdown_tx, shutdown_rx) = tokio::sync::mpsc::channel(1);
+    let (pause_tx, pause_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (profile_tx, profile_rx) = tokio::sync::mpsc::unbounded_channel();
 
     // Start app in background thread
     let app_task = std::thread::spawn(move || {
         runtime.block_on(async move {
-            if let Err(e) = app.run_with_signal(shutdown_rx).await {
+            if let Err(e) = app.run_with_signal(shutdown_rx, pause_rx, profile_rx).await {
                 error!("App error: {}", e);
             }
         });
+
+        flush_spans(tracer_provider);
     });
 
     // Get event channels
     let menu_channel = tray_icon::menu::MenuEvent::receiver();
     let tray_channel = tray_icon::TrayIconEvent::receiver();
-    let api_url = format!("http://{}:{}", config.api.host, config.api.port);
+    let api_url = config.api_url();
 
     // Create event loop state
     let mut event_loop_state = EventLoopState {
-        _tray_icon: tray_icon,
-        menu_items: (open_item, quit_item),
+        tray_icon,
+        open_item,
+        pause_item,
+        quit_item,
         menu_channel,
         tray_channel,
         app_task,
         shutdown_tx,
+        pause_tx,
+        profile_items,
+        profile_tx,
         api_url,
+        base_icon,
+        status,
+        shown_state: None,
     };
 
     info!("System Tray initialized. Running event loop...");
@@ -805,6 +2540,40 @@ fn main() -> Result<()> {
     // Use new ApplicationHandler API
     event_loop.run_app(&mut event_loop_state)?;
 
+    // Remove the tray icon, then let the app finish its queued work
+    let EventLoopState { app_task, .. } = event_loop_state;
+    if app_task.join().is_err() {
+        error!("Application thread panicked");
+    }
+
+    if restart.load(Ordering::Relaxed) {
+        return restart_app(instance, &switch_to);
+    }
     Ok(())
 }
 
+/// Start the application again, with the profile switched to if any
+///
+/// Installs a staged update first. Releases the single-instance lock, so the
+/// new process can take it.
+fn restart_app(
+    instance: Option<single_instance::InstanceGuard>,
+    switch_to: &std::sync::Mutex<Option<String>>,
+) -> Result<()> {
+    let exe = match updater::on_startup() {
+        Ok(updater::Startup::Restart(exe)) => exe,
+        Ok(updater::Startup::Continue) => std::env::current_exe()?,
+        Err(e) => {
+            error!("Failed to install the update: {:#}", e);
+            std::env::current_exe()?
+        }
+    };
+    drop(instance);
+
+    let profile = switch_to.lock().unwrap_or_else(|e| e.into_inner()).take();
+    updater::relaunch(
+        &exe,
+        profiles::relaunch_args(std::env::args_os().skip(1), profile.as_deref()),
+    )
+}
+
@@ -0,0 +1,76 @@
+//! Metrics history recorder
+//!
+//! Every [`SNAPSHOT_INTERVAL`] takes a snapshot of the OCR counters and the
+//! capture pipeline gauges and stores the change since the previous one in
+//! the `metrics_history` table, tagged with the app version, so
+//! `GET /stats/history` can compare performance across restarts, updates
+//! and settings changes.
+
+use chrono::Utc;
+use screensearch_api::metrics_history::{metric_samples, PipelineSnapshot};
+use screensearch_capture::{CaptureControl, OcrMetrics};
+use screensearch_db::DatabaseManager;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+use crate::version::VERSION;
+
+/// How often a snapshot is recorded
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Record snapshots until shutdown
+///
+/// `backlog` returns the number of frames waiting in the pipeline.
+pub async fn run(
+    db: Arc<DatabaseManager>,
+    metrics: OcrMetrics,
+    control: Arc<CaptureControl>,
+    backlog: impl Fn() -> usize + Send + 'static,
+    mut shutdown: broadcast::Receiver<()>,
+) {
+    let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+    // The first tick completes immediately and sets the baseline
+    interval.tick().await;
+    let mut previous = snapshot(&metrics, &control, backlog());
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.recv() => break,
+        }
+
+        let current = snapshot(&metrics, &control, backlog());
+        let samples = metric_samples(&previous, &current);
+        if let Err(e) = db
+            .insert_metric_samples(VERSION, current.taken_at, &samples)
+            .await
+        {
+            warn!("Failed to record metrics: {}", e);
+        }
+        previous = current;
+    }
+}
+
+fn snapshot(metrics: &OcrMetrics, control: &CaptureControl, backlog: usize) -> PipelineSnapshot {
+    let memory_budget = control.memory_budget();
+    let memory_budget = memory_budget.is_enabled().then(|| memory_budget.stats());
+    let disk_space = control.disk_space();
+    let disk_free_mb = disk_space.is_enabled().then(|| disk_space.stats().free_mb);
+
+    PipelineSnapshot {
+        taken_at: Utc::now(),
+        frames_processed: metrics.frames_processed.load(Ordering::Relaxed),
+        processing_time_ms: metrics.total_processing_time_ms.load(Ordering::Relaxed),
+        ocr_errors: metrics.errors.load(Ordering::Relaxed),
+        empty_frames: metrics.empty_frames.load(Ordering::Relaxed),
+        dropped_frames: memory_budget
+            .as_ref()
+            .map_or(0, |stats| stats.dropped_frames),
+        backlog,
+        memory_usage_mb: memory_budget.map(|stats| stats.usage_mb),
+        disk_free_mb,
+    }
+}
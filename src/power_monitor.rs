@@ -0,0 +1,120 @@
+//! Power source monitor
+//!
+//! Checks every [`CHECK_INTERVAL`] whether the machine runs on battery or in
+//! power saver mode. While it does, capture slows down to
+//! `performance.battery_interval_ms` and embedding generation pauses when
+//! `performance.battery_pause_workers` is set (see
+//! [`PowerSaving`](screensearch_capture::PowerSaving)), which `/health`
+//! reports.
+
+use anyhow::Result;
+use screensearch_capture::CaptureControl;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// How often the power source is checked
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Follow the power source until shutdown
+pub async fn run(control: Arc<CaptureControl>, mut shutdown: broadcast::Receiver<()>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    let mut failed = false;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.recv() => break,
+        }
+
+        let power = control.power();
+        if !power.is_enabled() {
+            continue;
+        }
+        match power_state() {
+            Ok((on_battery, power_saver)) => power.update(on_battery, power_saver),
+            // Warn once, the status rarely becomes readable later
+            Err(e) if !failed => {
+                warn!("Power status check failed: {:#}", e);
+                failed = true;
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// Whether the machine runs on battery and whether power saver is on
+#[cfg(target_os = "windows")]
+fn power_state() -> Result<(bool, bool)> {
+    use anyhow::Context;
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.context("Failed to get the power status")?;
+
+    // ACLineStatus is 0 on battery, 1 on AC and 255 when unknown, e.g. on
+    // desktops; SystemStatusFlag is 1 while battery saver is on
+    Ok((status.ACLineStatus == 0, status.SystemStatusFlag == 1))
+}
+
+/// Whether the machine runs on battery and whether power saver is on
+#[cfg(not(target_os = "windows"))]
+fn power_state() -> Result<(bool, bool)> {
+    let on_battery = on_battery(std::path::Path::new("/sys/class/power_supply"));
+    // Set to "low-power" by power-profiles-daemon's power saver profile
+    let power_saver = std::fs::read_to_string("/sys/firmware/acpi/platform_profile")
+        .is_ok_and(|profile| profile.trim() == "low-power");
+    Ok((on_battery, power_saver))
+}
+
+/// Whether a battery in the power supply class directory `dir` is
+/// discharging with no AC adapter online
+///
+/// Machines without a battery, or without the directory, run on AC.
+#[cfg(not(target_os = "windows"))]
+fn on_battery(dir: &std::path::Path) -> bool {
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).unwrap_or_default();
+    let Ok(supplies) = std::fs::read_dir(dir) else {
+        return false;
+    };
+
+    let (mut discharging, mut ac_online) = (false, false);
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        match read(path.join("type")).trim() {
+            "Battery" => discharging |= read(path.join("status")).trim() == "Discharging",
+            "Mains" | "USB" => ac_online |= read(path.join("online")).trim() == "1",
+            _ => {}
+        }
+    }
+    discharging && !ac_online
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_on_battery_from_power_supplies() {
+        use super::on_battery;
+
+        let dir = std::env::temp_dir().join(format!("screensearch-power-{}", std::process::id()));
+        let supply = |name: &str, files: &[(&str, &str)]| {
+            std::fs::create_dir_all(dir.join(name)).unwrap();
+            for (file, value) in files {
+                std::fs::write(dir.join(name).join(file), format!("{}\n", value)).unwrap();
+            }
+        };
+
+        assert!(!on_battery(&dir));
+        supply("BAT0", &[("type", "Battery"), ("status", "Discharging")]);
+        assert!(on_battery(&dir));
+        supply("AC", &[("type", "Mains"), ("online", "1")]);
+        assert!(!on_battery(&dir));
+        supply("AC", &[("type", "Mains"), ("online", "0")]);
+        assert!(on_battery(&dir));
+        supply("BAT0", &[("type", "Battery"), ("status", "Charging")]);
+        assert!(!on_battery(&dir));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
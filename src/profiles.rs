@@ -0,0 +1,164 @@
+//! Capture profiles
+//!
+//! The default profile keeps its database and captures in the data
+//! directory itself, so installs from before profiles keep their data. Each
+//! profile configured under `[profiles.<name>]` gets `profiles/<name>` in the
+//! data directory and may override the `[privacy]` settings.
+//!
+//! The profile to run is the one given with `--profile`, otherwise the one
+//! last switched to from the tray or `/profiles`, which is remembered in the
+//! data directory.
+
+use anyhow::{bail, Context, Result};
+use screensearch_api::profiles::{is_valid_name, DEFAULT_PROFILE};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// File in the data directory naming the profile last switched to
+const ACTIVE_FILE: &str = "active_profile";
+
+/// Names of the default profile and the configured ones, the default first
+///
+/// Configured names that cannot name a profile are left out.
+pub fn names<T>(configured: &BTreeMap<String, T>) -> Vec<String> {
+    std::iter::once(DEFAULT_PROFILE.to_string())
+        .chain(
+            configured
+                .keys()
+                .filter(|name| name.as_str() != DEFAULT_PROFILE && is_valid_name(name))
+                .cloned(),
+        )
+        .collect()
+}
+
+/// Directory for the database and captures of profile `name`
+pub fn profile_dir(data_dir: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        data_dir.to_path_buf()
+    } else {
+        data_dir.join("profiles").join(name)
+    }
+}
+
+/// Profile to run: `requested` with `--profile`, otherwise the remembered one
+///
+/// A remembered profile that is no longer configured falls back to the
+/// default one; a requested one is an error.
+pub fn select<T>(
+    requested: Option<&str>,
+    configured: &BTreeMap<String, T>,
+    data_dir: &Path,
+) -> Result<String> {
+    let is_known = |name: &str| {
+        name == DEFAULT_PROFILE || (is_valid_name(name) && configured.contains_key(name))
+    };
+
+    if let Some(name) = requested {
+        if !is_known(name) {
+            bail!(
+                "Unknown profile '{}'; profiles are configured under [profiles.<name>] \
+                 with lowercase letters, digits, '-' and '_'",
+                name
+            );
+        }
+        return Ok(name.to_string());
+    }
+
+    let remembered = std::fs::read_to_string(data_dir.join(ACTIVE_FILE)).unwrap_or_default();
+    let remembered = remembered.trim();
+    Ok(if is_known(remembered) {
+        remembered.to_string()
+    } else {
+        DEFAULT_PROFILE.to_string()
+    })
+}
+
+/// Remember `name` as the profile to run on the next start
+pub fn save_active(data_dir: &Path, name: &str) -> Result<()> {
+    if !data_dir.as_os_str().is_empty() {
+        std::fs::create_dir_all(data_dir)
+            .with_context(|| format!("Failed to create {}", data_dir.display()))?;
+    }
+    let path = data_dir.join(ACTIVE_FILE);
+    std::fs::write(&path, name).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Command-line arguments to restart with, switching to `profile` if given
+pub fn relaunch_args(
+    args: impl IntoIterator<Item = OsString>,
+    profile: Option<&str>,
+) -> Vec<OsString> {
+    let mut args: Vec<OsString> = args.into_iter().collect();
+    let Some(profile) = profile else {
+        return args;
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "--profile" {
+            let end = (i + 2).min(args.len());
+            args.drain(i..end);
+        } else if args[i].to_string_lossy().starts_with("--profile=") {
+            args.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    args.push("--profile".into());
+    args.push(profile.into());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_falls_back_to_default() {
+        let dir =
+            std::env::temp_dir().join(format!("screensearch-profiles-{}", std::process::id()));
+        let configured = BTreeMap::from([("work".to_string(), ())]);
+
+        assert_eq!(select(None, &configured, &dir).unwrap(), DEFAULT_PROFILE);
+        assert_eq!(select(Some("work"), &configured, &dir).unwrap(), "work");
+        assert!(select(Some("games"), &configured, &dir).is_err());
+        assert!(select(Some("../work"), &configured, &dir).is_err());
+
+        save_active(&dir, "work").unwrap();
+        assert_eq!(select(None, &configured, &dir).unwrap(), "work");
+        assert_eq!(
+            select(Some("default"), &configured, &dir).unwrap(),
+            DEFAULT_PROFILE
+        );
+
+        // A profile removed from the config is forgotten
+        save_active(&dir, "old").unwrap();
+        assert_eq!(select(None, &configured, &dir).unwrap(), DEFAULT_PROFILE);
+
+        assert_eq!(profile_dir(&dir, "work"), dir.join("profiles").join("work"));
+        assert_eq!(profile_dir(&dir, DEFAULT_PROFILE), dir);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_relaunch_args_replace_profile() {
+        let args = |list: &[&str]| list.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            relaunch_args(
+                args(&["run", "--profile", "work", "--service"]),
+                Some("personal")
+            ),
+            args(&["run", "--service", "--profile", "personal"])
+        );
+        assert_eq!(
+            relaunch_args(args(&["--profile=work", "run"]), Some("default")),
+            args(&["run", "--profile", "default"])
+        );
+        assert_eq!(
+            relaunch_args(args(&["run", "--profile", "work"]), None),
+            args(&["run", "--profile", "work"])
+        );
+    }
+}
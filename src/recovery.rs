@@ -0,0 +1,289 @@
+//! Recovery after an unclean shutdown
+//!
+//! A sentinel file next to the database exists while the application runs
+//! and is removed at the end of a clean shutdown. Finding it at startup means
+//! the previous run crashed or lost power, so before capture starts the
+//! database is integrity-checked and the capture files are reconciled with
+//! the frames table:
+//!
+//! - image files without a frame from the last [`ADOPT_WINDOW`] before the
+//!   newest capture were written just before the crash, and are queued for
+//!   OCR again so they are stored like any other frame ("adopted")
+//! - older image files without a frame are deleted
+//! - frames whose image file is missing are counted
+//!
+//! Findings are logged and reported by `/health`.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use screensearch_api::capture_store::is_remote_path;
+use screensearch_api::models::RecoveryReport;
+use screensearch_capture::CapturedFrame;
+use screensearch_db::DatabaseManager;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tracing::{error, info, warn};
+
+/// Orphaned image files captured this long before the newest capture are
+/// adopted; older ones are deleted
+pub const ADOPT_WINDOW: Duration = Duration::minutes(10);
+
+/// Marks a running instance; left behind when it does not shut down cleanly
+pub struct Sentinel {
+    path: PathBuf,
+}
+
+impl Sentinel {
+    /// Create the sentinel for the database at `db_path`
+    ///
+    /// Returns whether one was left behind by the previous run.
+    pub fn create(db_path: &Path) -> Result<(Self, bool)> {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(".running");
+        let path = PathBuf::from(path);
+
+        let unclean = path.exists();
+        std::fs::write(&path, std::process::id().to_string())
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok((Self { path }, unclean))
+    }
+
+    /// Remove the sentinel at the end of a clean shutdown
+    pub fn remove(self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("Failed to remove {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Image file without a frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orphan {
+    pub path: PathBuf,
+    pub monitor_index: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Orphan {
+    /// Load the image as a captured frame to process again
+    ///
+    /// The window and process are unknown; storing it writes the image back
+    /// to the same file name.
+    pub fn load(&self) -> Result<CapturedFrame> {
+        let image = image::open(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?
+            .into_rgba8();
+
+        Ok(CapturedFrame {
+            timestamp: self.timestamp,
+            monitor_index: self.monitor_index,
+            image,
+            active_window: None,
+            active_process: None,
+        })
+    }
+}
+
+/// Check the database and reconcile the capture files in `captures_dir`
+///
+/// Deletes old orphaned files and returns the ones to adopt.
+pub async fn recover(
+    db: &DatabaseManager,
+    captures_dir: &Path,
+) -> Result<(RecoveryReport, Vec<Orphan>)> {
+    warn!("The previous run did not shut down cleanly, checking the database");
+
+    let integrity_errors = db
+        .integrity_check()
+        .await
+        .context("Failed to run the integrity check")?;
+    if integrity_errors.is_empty() {
+        info!("Database integrity check passed");
+    } else {
+        error!(
+            "Database integrity check found {} problems: {}",
+            integrity_errors.len(),
+            integrity_errors.join("; ")
+        );
+    }
+
+    let stored: HashSet<OsString> = db
+        .get_frame_file_paths()
+        .await
+        .context("Failed to list frame files")?
+        .iter()
+        // Captures in a bucket have no file in the captures directory
+        .filter(|path| !is_remote_path(path))
+        .filter_map(|path| Path::new(path).file_name())
+        // Compacted frames point to video chunks, not capture files
+        .filter(|name| name.to_str().and_then(parse_capture_name).is_some())
+        .map(OsString::from)
+        .collect();
+    let files = capture_files(captures_dir)?;
+
+    let missing_files = stored
+        .iter()
+        .filter(|name| !files.iter().any(|file| file.path.file_name() == Some(name)))
+        .count();
+    let orphans: Vec<Orphan> = files
+        .into_iter()
+        .filter(|file| {
+            file.path
+                .file_name()
+                .is_some_and(|name| !stored.contains(name))
+        })
+        .collect();
+
+    let newest_frame = db.get_statistics().await?.newest_frame;
+    let (adopt, delete) = split_orphans(orphans, newest_frame);
+
+    let mut orphans_deleted = 0;
+    for orphan in &delete {
+        match std::fs::remove_file(&orphan.path) {
+            Ok(()) => orphans_deleted += 1,
+            Err(e) => warn!("Failed to delete {}: {}", orphan.path.display(), e),
+        }
+    }
+
+    let report = RecoveryReport {
+        checked_at: Utc::now(),
+        integrity_errors,
+        orphans_adopted: adopt.len(),
+        orphans_deleted,
+        missing_files,
+    };
+    info!(
+        "Recovery finished: {} orphaned images queued for OCR, {} deleted, {} frames missing their image",
+        report.orphans_adopted, report.orphans_deleted, report.missing_files
+    );
+
+    Ok((report, adopt))
+}
+
+/// Frame images in `dir`, recognized by their file name
+fn capture_files(dir: &Path) -> Result<Vec<Orphan>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let (monitor_index, timestamp) = parse_capture_name(path.file_name()?.to_str()?)?;
+            Some(Orphan {
+                path,
+                monitor_index,
+                timestamp,
+            })
+        })
+        .collect())
+}
+
+/// Parse `frame_<monitor>_<YYYYmmdd_HHMMSS_mmm>.<jpg|png>`
+fn parse_capture_name(name: &str) -> Option<(usize, DateTime<Utc>)> {
+    let stem = name
+        .strip_suffix(".jpg")
+        .or_else(|| name.strip_suffix(".png"))?;
+    let (monitor, timestamp) = stem.strip_prefix("frame_")?.split_once('_')?;
+
+    let monitor_index = monitor.parse().ok()?;
+    let timestamp = NaiveDateTime::parse_from_str(timestamp, "%Y%m%d_%H%M%S_%3f").ok()?;
+    Some((monitor_index, timestamp.and_utc()))
+}
+
+/// Split orphans into the ones to adopt and the ones to delete
+///
+/// Adopts those within [`ADOPT_WINDOW`] of the newest capture, stored or not.
+fn split_orphans(
+    orphans: Vec<Orphan>,
+    newest_frame: Option<DateTime<Utc>>,
+) -> (Vec<Orphan>, Vec<Orphan>) {
+    let newest = orphans
+        .iter()
+        .map(|orphan| orphan.timestamp)
+        .chain(newest_frame)
+        .max();
+    let Some(newest) = newest else {
+        return (Vec::new(), Vec::new());
+    };
+
+    orphans
+        .into_iter()
+        .partition(|orphan| orphan.timestamp >= newest - ADOPT_WINDOW)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn orphan(minutes_ago: i64, now: DateTime<Utc>) -> Orphan {
+        Orphan {
+            path: PathBuf::from(format!("frame_0_{}.jpg", minutes_ago)),
+            monitor_index: 0,
+            timestamp: now - Duration::minutes(minutes_ago),
+        }
+    }
+
+    #[test]
+    fn test_parse_capture_name() {
+        assert_eq!(
+            parse_capture_name("frame_1_20261015_093012_250.jpg"),
+            Some((
+                1,
+                Utc.with_ymd_and_hms(2026, 10, 15, 9, 30, 12).unwrap()
+                    + Duration::milliseconds(250)
+            ))
+        );
+        assert!(parse_capture_name("frame_0_20261015_093012_250.png").is_some());
+        assert_eq!(parse_capture_name("frame_0_20261015_093012_250.webp"), None);
+        assert_eq!(parse_capture_name("notes.jpg"), None);
+        assert_eq!(parse_capture_name("frame_x_20261015_093012_250.jpg"), None);
+    }
+
+    #[test]
+    fn test_split_orphans() {
+        let now = Utc::now();
+        let orphans = vec![orphan(1, now), orphan(30, now), orphan(9, now)];
+
+        let (adopt, delete) = split_orphans(orphans.clone(), Some(now));
+        assert_eq!(adopt, vec![orphan(1, now), orphan(9, now)]);
+        assert_eq!(delete, vec![orphan(30, now)]);
+
+        // Without stored frames the newest orphan marks the crash
+        let (adopt, delete) = split_orphans(orphans, None);
+        assert_eq!(adopt.len(), 2);
+        assert_eq!(delete.len(), 1);
+
+        // A crash long after the orphans were written
+        let (adopt, delete) = split_orphans(vec![orphan(60, now)], Some(now));
+        assert!(adopt.is_empty());
+        assert_eq!(delete.len(), 1);
+    }
+
+    #[test]
+    fn test_sentinel_detects_unclean_shutdown() {
+        let dir =
+            std::env::temp_dir().join(format!("screensearch-sentinel-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("screensearch.db");
+
+        let (sentinel, unclean) = Sentinel::create(&db_path).unwrap();
+        assert!(!unclean);
+        drop(sentinel);
+
+        // Not removed, as after a crash
+        let (sentinel, unclean) = Sentinel::create(&db_path).unwrap();
+        assert!(unclean);
+        sentinel.remove();
+
+        let (sentinel, unclean) = Sentinel::create(&db_path).unwrap();
+        assert!(!unclean);
+        sentinel.remove();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
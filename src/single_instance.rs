@@ -0,0 +1,86 @@
+//! Single-instance guard
+//!
+//! Two running instances would each run a capture loop against the same
+//! database and store every frame twice. The first instance holds a named
+//! mutex (Windows) or an exclusive lock on a file in the data directory
+//! (other platforms) until it exits, and later launches find it taken.
+
+use anyhow::Result;
+
+/// Held by the running instance; releases the lock when dropped
+pub struct InstanceGuard {
+    _lock: platform::Lock,
+}
+
+/// Claim the single instance, or `None` when another instance holds it
+pub fn acquire() -> Result<Option<InstanceGuard>> {
+    Ok(platform::try_lock()?.map(|lock| InstanceGuard { _lock: lock }))
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use anyhow::{Context, Result};
+    use windows::core::w;
+    use windows::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HANDLE};
+    use windows::Win32::System::Threading::CreateMutexW;
+
+    /// Named mutex handle; the mutex is destroyed with its last handle
+    pub struct Lock(HANDLE);
+
+    impl Drop for Lock {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = CloseHandle(self.0);
+            }
+        }
+    }
+
+    pub fn try_lock() -> Result<Option<Lock>> {
+        // "Local\" scopes the mutex to the login session, so other users
+        // on the same machine can each run their own instance
+        let handle = unsafe { CreateMutexW(None, false, w!("Local\\ScreenSearch")) }
+            .context("Failed to create the single-instance mutex")?;
+        let lock = Lock(handle);
+
+        // Opening an existing mutex succeeds but sets ERROR_ALREADY_EXISTS
+        let already_exists = unsafe { GetLastError() }
+            .err()
+            .is_some_and(|e| e.code() == ERROR_ALREADY_EXISTS.to_hresult());
+        Ok((!already_exists).then_some(lock))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use anyhow::{Context, Result};
+    use std::fs::{File, OpenOptions, TryLockError};
+
+    /// Lock file, unlocked by the OS when closed or the process exits
+    pub struct Lock {
+        _file: File,
+    }
+
+    pub fn try_lock() -> Result<Option<Lock>> {
+        let dir = dirs::data_local_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("screensearch");
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let path = dir.join("screensearch.lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Lock { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => {
+                Err(e).with_context(|| format!("Failed to lock {}", path.display()))
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Tray status indicator
+//!
+//! The capture pipeline publishes its state to [`PipelineStatus`], and the
+//! tray event loop shows it through the icon and tooltip: the normal icon
+//! while recording, a greyed-out icon while paused, and an amber or red
+//! badge when OCR falls behind or frames fail to be processed or saved. A
+//! greyed-out icon with a red badge means capture is paused because the disk
+//! is almost full. Outside the capture schedule the icon is greyed out as
+//! well, with its own tooltip.
+
+use image::{Rgba, RgbaImage};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How often the pipeline state is recomputed and the tray refreshed
+pub const STATUS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Frames waiting for OCR or storage before the tray reports a backlog
+pub const OCR_BACKLOG_FRAMES: usize = 10;
+
+/// How long the tray keeps showing an error after the last failed frame
+pub const ERROR_HOLD_SECS: u64 = 60;
+
+/// Badge colour for an OCR backlog
+const BACKLOG_COLOR: Rgba<u8> = Rgba([245, 158, 11, 255]);
+
+/// Badge colour for errors
+const ERROR_COLOR: Rgba<u8> = Rgba([220, 38, 38, 255]);
+
+/// State shown by the tray icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TrayState {
+    /// Capturing normally
+    Recording,
+
+    /// Capture paused from the tray or the settings panel
+    Paused,
+
+    /// OCR or storage is falling behind capture
+    OcrBacklog,
+
+    /// Frames recently failed OCR or could not be saved
+    Error,
+
+    /// Capture suspended until disk space is freed
+    LowDisk,
+
+    /// Capture off outside the hours of the capture schedule
+    OffSchedule,
+}
+
+impl TrayState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Paused,
+            2 => Self::OcrBacklog,
+            3 => Self::Error,
+            4 => Self::LowDisk,
+            5 => Self::OffSchedule,
+            _ => Self::Recording,
+        }
+    }
+
+    /// Tooltip text for this state
+    pub fn tooltip(&self) -> &'static str {
+        match self {
+            Self::Recording => "ScreenSearch - Recording",
+            Self::Paused => "ScreenSearch - Paused",
+            Self::OcrBacklog => "ScreenSearch - Recording (OCR is catching up)",
+            Self::Error => "ScreenSearch - Error processing frames (see log)",
+            Self::LowDisk => "ScreenSearch - Paused (disk almost full)",
+            Self::OffSchedule => "ScreenSearch - Paused (outside capture schedule)",
+        }
+    }
+
+    /// Render the tray icon for this state from the base icon
+    pub fn icon(&self, base: &RgbaImage) -> RgbaImage {
+        match self {
+            Self::Recording => base.clone(),
+            Self::Paused | Self::OffSchedule => greyed_out(base),
+            Self::OcrBacklog => with_badge(base, BACKLOG_COLOR),
+            Self::Error => with_badge(base, ERROR_COLOR),
+            Self::LowDisk => with_badge(&greyed_out(base), ERROR_COLOR),
+        }
+    }
+}
+
+/// Capture pipeline state shared with the tray
+#[derive(Debug, Default)]
+pub struct PipelineStatus {
+    /// Current [`TrayState`]
+    state: AtomicU8,
+
+    /// Whether capture is paused, shown as the tray menu check mark
+    paused: AtomicBool,
+
+    /// Unix time of the last failed frame (0 = none)
+    last_error_secs: AtomicU64,
+}
+
+impl PipelineStatus {
+    /// Current tray state
+    pub fn state(&self) -> TrayState {
+        TrayState::from_u8(self.state.load(Ordering::Relaxed))
+    }
+
+    /// Whether capture is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Record a frame that failed OCR or could not be saved
+    pub fn record_error(&self) {
+        self.last_error_secs
+            .store(chrono::Utc::now().timestamp() as u64, Ordering::Relaxed);
+    }
+
+    /// Recompute the state from the pipeline's current condition
+    pub fn update(&self, paused: bool, off_schedule: bool, low_disk: bool, backlog: usize) {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let last_error = self.last_error_secs.load(Ordering::Relaxed);
+        let recent_error = last_error > 0 && now.saturating_sub(last_error) < ERROR_HOLD_SECS;
+
+        let state = if low_disk {
+            TrayState::LowDisk
+        } else if recent_error {
+            TrayState::Error
+        } else if paused {
+            TrayState::Paused
+        } else if off_schedule {
+            TrayState::OffSchedule
+        } else if backlog >= OCR_BACKLOG_FRAMES {
+            TrayState::OcrBacklog
+        } else {
+            TrayState::Recording
+        };
+
+        self.paused.store(paused, Ordering::Relaxed);
+        self.state.store(state as u8, Ordering::Relaxed);
+    }
+}
+
+/// Number of messages waiting in a channel, or 0 once it is closed
+pub fn queued<T>(sender: &mpsc::WeakSender<T>) -> usize {
+    sender
+        .upgrade()
+        .map_or(0, |sender| sender.max_capacity() - sender.capacity())
+}
+
+/// Desaturate and fade an icon
+fn greyed_out(base: &RgbaImage) -> RgbaImage {
+    let mut icon = base.clone();
+    for pixel in icon.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        let luma = (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) as u8;
+        *pixel = Rgba([luma, luma, luma, (a as u16 * 3 / 5) as u8]);
+    }
+    icon
+}
+
+/// Draw a filled circle in the bottom-right corner of an icon
+fn with_badge(base: &RgbaImage, color: Rgba<u8>) -> RgbaImage {
+    let mut icon = base.clone();
+    let (width, height) = icon.dimensions();
+    let radius = (width.min(height) as f32 * 0.22).max(2.0);
+    let (cx, cy) = (width as f32 - radius - 1.0, height as f32 - radius - 1.0);
+
+    for (x, y, pixel) in icon.enumerate_pixels_mut() {
+        let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+        if dx * dx + dy * dy <= radius * radius {
+            *pixel = color;
+        }
+    }
+    icon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_priority() {
+        let status = PipelineStatus::default();
+        assert_eq!(status.state(), TrayState::Recording);
+
+        status.update(false, false, false, OCR_BACKLOG_FRAMES);
+        assert_eq!(status.state(), TrayState::OcrBacklog);
+
+        status.update(false, true, false, OCR_BACKLOG_FRAMES);
+        assert_eq!(status.state(), TrayState::OffSchedule);
+        assert!(!status.is_paused());
+
+        status.update(true, true, false, OCR_BACKLOG_FRAMES);
+        assert_eq!(status.state(), TrayState::Paused);
+        assert!(status.is_paused());
+
+        status.record_error();
+        status.update(true, false, false, 0);
+        assert_eq!(status.state(), TrayState::Error);
+        assert!(status.is_paused());
+
+        status.update(false, false, true, 0);
+        assert_eq!(status.state(), TrayState::LowDisk);
+        assert!(!status.is_paused());
+    }
+
+    #[test]
+    fn test_errors_expire() {
+        let status = PipelineStatus::default();
+        let long_ago = chrono::Utc::now().timestamp() as u64 - ERROR_HOLD_SECS - 1;
+        status.last_error_secs.store(long_ago, Ordering::Relaxed);
+
+        status.update(false, false, false, 0);
+        assert_eq!(status.state(), TrayState::Recording);
+    }
+
+    #[test]
+    fn test_state_icons() {
+        let base = RgbaImage::from_pixel(32, 32, Rgba([0, 120, 255, 255]));
+
+        assert_eq!(TrayState::Recording.icon(&base), base);
+
+        let paused = TrayState::Paused.icon(&base);
+        let [r, g, b, a] = paused.get_pixel(16, 16).0;
+        assert!(r == g && g == b);
+        assert!(a < 255);
+
+        let error = TrayState::Error.icon(&base);
+        assert_eq!(*error.get_pixel(26, 26), ERROR_COLOR);
+        assert_eq!(*error.get_pixel(4, 4), *base.get_pixel(4, 4));
+        assert_eq!(error.dimensions(), base.dimensions());
+    }
+}
@@ -13,6 +13,15 @@ pub struct UpdateInfo {
     pub download_url: String,
     pub release_notes: String,
     pub published_at: String,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// Downloadable file attached to a release
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    #[serde(rename = "browser_download_url")]
+    pub url: String,
 }
 
 /// GitHub release API response (partial)
@@ -22,6 +31,8 @@ struct GitHubRelease {
     html_url: String,
     body: Option<String>,
     published_at: String,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
 }
 
 /// Check for updates in the background
@@ -78,6 +89,7 @@ pub async fn check_updates() -> Option<UpdateInfo> {
             download_url: release.html_url,
             release_notes: release.body.unwrap_or_default(),
             published_at: release.published_at,
+            assets: release.assets,
         })
     } else {
         debug!("Already on latest version: {}", current_version.to_string());
@@ -95,12 +107,18 @@ mod tests {
             "tag_name": "v0.2.0",
             "html_url": "https://github.com/nicolasestrem/screensearch/releases/tag/v0.2.0",
             "body": "Release notes here",
-            "published_at": "2025-01-15T10:00:00Z"
+            "published_at": "2025-01-15T10:00:00Z",
+            "assets": [{
+                "name": "checksums.txt",
+                "browser_download_url": "https://github.com/nicolasestrem/screensearch/releases/download/v0.2.0/checksums.txt"
+            }]
         }"#;
 
         let release: GitHubRelease = serde_json::from_str(json).unwrap();
         assert_eq!(release.tag_name, "v0.2.0");
         assert!(release.body.is_some());
+        assert_eq!(release.assets[0].name, "checksums.txt");
+        assert!(release.assets[0].url.ends_with("/v0.2.0/checksums.txt"));
     }
 
     #[tokio::test]
@@ -0,0 +1,642 @@
+//! In-app updates
+//!
+//! Downloads the portable build of a newer release, checks the minisign
+//! signature of the release's `checksums.txt` against the public key built
+//! into this binary, verifies the download against the SHA-256 listed there,
+//! and stages the binary in the data directory. A staged update is installed when the application
+//! next starts, or right away when requested through `/admin/update`:
+//!
+//! 1. The running binary is renamed to `<exe>.old` and the staged binary
+//!    takes its place.
+//! 2. The new version counts its starts. Once it has run for
+//!    [`CONFIRM_AFTER`] or shut down cleanly, the old binary is deleted.
+//! 3. If it fails to get there [`MAX_START_ATTEMPTS`] times, the old binary
+//!    is restored and started instead, and that release is no longer
+//!    downloaded automatically.
+
+use crate::update_checker::{self, UpdateInfo};
+use crate::version::{Version, VERSION};
+use anyhow::{bail, Context, Result};
+use screensearch_api::updates::{UpdateControl, UpdateState};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, oneshot};
+use tracing::{error, info, warn};
+
+/// How long a new version must run before the update is kept
+pub const CONFIRM_AFTER: Duration = Duration::from_secs(120);
+
+/// Starts a new version gets to reach [`CONFIRM_AFTER`] before it is rolled back
+pub const MAX_START_ATTEMPTS: u32 = 3;
+
+/// Delay before the first update check, so it does not slow down startup
+const CHECK_DELAY: Duration = Duration::from_secs(5);
+
+/// Limit for downloading a release
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Binary inside the portable release archive
+const ARCHIVE_BINARY: &str = "screensearch.exe";
+
+/// Release asset with the SHA-256 of every other asset
+const CHECKSUMS_ASSET: &str = "checksums.txt";
+
+/// Release asset with the minisign signature of [`CHECKSUMS_ASSET`]
+const SIGNATURE_ASSET: &str = "checksums.txt.minisig";
+
+/// minisign public key that release checksums must be signed with
+///
+/// Set when building a release. Without it, the checksums could come from
+/// anyone able to publish a release, so updates are not downloaded at all.
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("SCREENSEARCH_UPDATE_PUBLIC_KEY");
+
+const STAGED_FILE: &str = "staged.json";
+const PENDING_FILE: &str = "pending.json";
+const ROLLED_BACK_FILE: &str = "rolled_back.json";
+
+/// Verified binary waiting to be installed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StagedUpdate {
+    version: String,
+    binary: PathBuf,
+    sha256: String,
+}
+
+/// Installed update that has not yet proven it starts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PendingUpdate {
+    version: String,
+    previous_version: String,
+    backup: PathBuf,
+    attempts: u32,
+}
+
+/// Release that failed to start and was rolled back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RolledBack {
+    version: String,
+}
+
+/// What to do after [`on_startup`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Startup {
+    /// Keep starting this binary
+    Continue,
+    /// The binary was replaced; start it with [`relaunch`] and exit
+    ///
+    /// Holds the path it was started from, since the running process may
+    /// report the renamed file as its own.
+    Restart(PathBuf),
+}
+
+/// Directory of staged binaries and update state
+fn updates_dir() -> PathBuf {
+    crate::app_data_path("updates")
+}
+
+/// Roll back an update that keeps failing to start, or install a staged one
+///
+/// Runs at startup and before restarting to install an update, while holding
+/// the single-instance lock.
+pub fn on_startup() -> Result<Startup> {
+    let dir = updates_dir();
+    let exe = std::env::current_exe().context("Failed to locate the running binary")?;
+
+    if let Some(mut pending) = read_json::<PendingUpdate>(&dir.join(PENDING_FILE))? {
+        if pending.version != VERSION {
+            // Replaced by other means, e.g. reinstalled
+            remove_file(&dir.join(PENDING_FILE));
+        } else {
+            pending.attempts += 1;
+            if pending.attempts > MAX_START_ATTEMPTS {
+                error!(
+                    "Version {} did not start {} times, rolling back to {}",
+                    pending.version, MAX_START_ATTEMPTS, pending.previous_version
+                );
+                rollback(&exe, &pending)?;
+                write_json(
+                    &dir.join(ROLLED_BACK_FILE),
+                    &RolledBack {
+                        version: pending.version,
+                    },
+                )?;
+                remove_file(&dir.join(PENDING_FILE));
+                return Ok(Startup::Restart(exe));
+            }
+
+            // Not installing on top of an update still being tried out
+            write_json(&dir.join(PENDING_FILE), &pending)?;
+            return Ok(Startup::Continue);
+        }
+    }
+
+    let Some(staged) = read_json::<StagedUpdate>(&dir.join(STAGED_FILE))? else {
+        return Ok(Startup::Continue);
+    };
+    // Removed first, so an update that cannot be installed is not retried
+    // on every start
+    remove_file(&dir.join(STAGED_FILE));
+
+    let newer = Version::parse(&staged.version)
+        .is_some_and(|version| version.is_newer_than(&Version::current()));
+    if !newer {
+        remove_file(&staged.binary);
+        return Ok(Startup::Continue);
+    }
+    // Checked again, the file sat in the data directory since the download
+    if sha256_file(&staged.binary)? != staged.sha256 {
+        warn!(
+            "Staged update {} was modified after it was verified, discarding it",
+            staged.version
+        );
+        remove_file(&staged.binary);
+        return Ok(Startup::Continue);
+    }
+
+    info!("Installing update {} (from {})", staged.version, VERSION);
+    let backup = with_suffix(&exe, ".old");
+    install(&exe, &staged.binary, &backup)?;
+    remove_file(&staged.binary);
+    write_json(
+        &dir.join(PENDING_FILE),
+        &PendingUpdate {
+            version: staged.version,
+            previous_version: VERSION.to_string(),
+            backup,
+            attempts: 0,
+        },
+    )?;
+    Ok(Startup::Restart(exe))
+}
+
+/// Keep the running version: delete the previous binary an update replaced
+pub fn confirm() {
+    // Left behind by a rollback, no longer running
+    if let Ok(exe) = std::env::current_exe() {
+        remove_file(&with_suffix(&exe, ".failed"));
+    }
+
+    let dir = updates_dir();
+    let Ok(Some(pending)) = read_json::<PendingUpdate>(&dir.join(PENDING_FILE)) else {
+        return;
+    };
+    if pending.version != VERSION {
+        return;
+    }
+
+    remove_file(&pending.backup);
+    remove_file(&dir.join(PENDING_FILE));
+    info!("Update to {} confirmed", VERSION);
+}
+
+/// Start `exe` with `args`, usually the arguments of this process
+pub fn relaunch(exe: &Path, args: impl IntoIterator<Item = std::ffi::OsString>) -> Result<()> {
+    std::process::Command::new(exe)
+        .args(args)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", exe.display()))?;
+    Ok(())
+}
+
+/// Replace `exe` with `binary`, keeping the original as `backup`
+///
+/// A running binary can be renamed but not overwritten on Windows.
+fn install(exe: &Path, binary: &Path, backup: &Path) -> Result<()> {
+    remove_file(backup);
+    std::fs::rename(exe, backup)
+        .with_context(|| format!("Failed to move {} aside", exe.display()))?;
+
+    if let Err(e) = std::fs::copy(binary, exe) {
+        let _ = std::fs::rename(backup, exe);
+        return Err(e).with_context(|| format!("Failed to write {}", exe.display()));
+    }
+    Ok(())
+}
+
+/// Put the previous binary back in place of the running one
+fn rollback(exe: &Path, pending: &PendingUpdate) -> Result<()> {
+    let failed = with_suffix(exe, ".failed");
+    remove_file(&failed);
+    std::fs::rename(exe, &failed)
+        .with_context(|| format!("Failed to move {} aside", exe.display()))?;
+
+    if let Err(e) = std::fs::rename(&pending.backup, exe) {
+        let _ = std::fs::rename(&failed, exe);
+        return Err(e).with_context(|| format!("Failed to restore {}", pending.backup.display()));
+    }
+    Ok(())
+}
+
+/// Checks for, downloads and stages updates, and restarts to install them
+pub struct Updater {
+    control: Arc<UpdateControl>,
+    client: reqwest::Client,
+    dir: PathBuf,
+    /// Check for a newer release at startup
+    check: bool,
+    /// Download and stage a newer release without being asked
+    auto_download: bool,
+}
+
+impl Updater {
+    pub fn new(control: Arc<UpdateControl>, check: bool, auto_download: bool) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .user_agent(format!("ScreenSearch/{}", VERSION))
+            .build()
+            .context("Failed to build the HTTP client")?;
+
+        Ok(Self {
+            control,
+            client,
+            dir: updates_dir(),
+            check,
+            auto_download,
+        })
+    }
+
+    /// Run until shutdown, or until a requested update is ready to install
+    ///
+    /// Sends on `restart` to ask for the restart that installs it.
+    pub async fn run(self, restart: oneshot::Sender<()>, mut shutdown: broadcast::Receiver<()>) {
+        let rolled_back = read_json::<RolledBack>(&self.dir.join(ROLLED_BACK_FILE))
+            .ok()
+            .flatten()
+            .map(|rolled_back| rolled_back.version);
+        self.control.update_status(|status| {
+            status.current_version = VERSION.to_string();
+            status.rolled_back_from = rolled_back.clone();
+        });
+
+        let confirm_at = tokio::time::sleep(CONFIRM_AFTER);
+        tokio::pin!(confirm_at);
+        let mut confirmed = false;
+
+        let check_at = tokio::time::sleep(CHECK_DELAY);
+        tokio::pin!(check_at);
+        let mut checked = !self.check;
+
+        let mut latest: Option<UpdateInfo> = None;
+        let mut staged: Option<String> = None;
+
+        loop {
+            tokio::select! {
+                _ = &mut check_at, if !checked => {
+                    checked = true;
+                    latest = self.check().await;
+                    let skipped = latest.as_ref().map(|update| &update.version) == rolled_back.as_ref();
+                    if let Some(update) = latest.as_ref().filter(|_| self.auto_download && !skipped) {
+                        staged = self.stage(update).await.then(|| update.version.clone());
+                    }
+                }
+                restart_now = self.control.install_requested() => {
+                    if latest.is_none() {
+                        latest = self.check().await;
+                    }
+                    let Some(update) = latest.as_ref() else {
+                        continue;
+                    };
+                    if staged.as_ref() != Some(&update.version) {
+                        staged = self.stage(update).await.then(|| update.version.clone());
+                    }
+                    if restart_now && staged.is_some() {
+                        info!("Restarting to install update {}", update.version);
+                        self.control.update_status(|status| status.state = UpdateState::Installing);
+                        let _ = restart.send(());
+                        return;
+                    }
+                }
+                _ = &mut confirm_at, if !confirmed => {
+                    confirmed = true;
+                    confirm();
+                }
+                _ = shutdown.recv() => return,
+            }
+        }
+    }
+
+    /// Look for a newer release and publish the result
+    async fn check(&self) -> Option<UpdateInfo> {
+        let latest = update_checker::check_updates().await;
+        match &latest {
+            Some(update) => info!(
+                "Update {} available: {} (POST /api/admin/update to install it)",
+                update.version, update.download_url
+            ),
+            None => info!("ScreenSearch {} is up to date", VERSION),
+        }
+
+        self.control.update_status(|status| {
+            status.state = if latest.is_some() {
+                UpdateState::Available
+            } else {
+                UpdateState::UpToDate
+            };
+            status.latest_version = latest.as_ref().map(|update| update.version.clone());
+            status.release_url = latest.as_ref().map(|update| update.download_url.clone());
+            status.error = None;
+        });
+        latest
+    }
+
+    /// Download, verify and stage `update`, publishing the progress
+    ///
+    /// Returns whether it is staged.
+    async fn stage(&self, update: &UpdateInfo) -> bool {
+        self.control
+            .update_status(|status| status.state = UpdateState::Downloading);
+
+        match self.download(update).await {
+            Ok(staged) => {
+                info!(
+                    "Update {} downloaded and verified, it installs on the next start",
+                    staged.version
+                );
+                self.control.update_status(|status| {
+                    status.state = UpdateState::Ready;
+                    status.error = None;
+                });
+                true
+            }
+            Err(e) => {
+                error!("Failed to download update {}: {:#}", update.version, e);
+                self.control.update_status(|status| {
+                    status.state = UpdateState::Failed;
+                    status.error = Some(format!("{:#}", e));
+                });
+                false
+            }
+        }
+    }
+
+    async fn download(&self, update: &UpdateInfo) -> Result<StagedUpdate> {
+        if !cfg!(windows) {
+            bail!(
+                "Updates are only published for Windows; download the release from {}",
+                update.download_url
+            );
+        }
+        let Some(public_key) = UPDATE_PUBLIC_KEY else {
+            bail!(
+                "This build has no key to check updates with; download the release from {}",
+                update.download_url
+            );
+        };
+
+        let archive_name = format!("ScreenSearch-v{}-Portable.zip", update.version);
+        let asset_url = |name: &str| {
+            update
+                .assets
+                .iter()
+                .find(|asset| asset.name == name)
+                .map(|asset| asset.url.clone())
+                .with_context(|| format!("Release {} has no {}", update.version, name))
+        };
+        let archive_url = asset_url(&archive_name)?;
+        let checksums_url = asset_url(CHECKSUMS_ASSET)?;
+        let signature_url = asset_url(SIGNATURE_ASSET)?;
+
+        let checksums = self.get(&checksums_url).await?;
+        let signature = self.get(&signature_url).await?;
+        verify_signature(&checksums, &String::from_utf8_lossy(&signature), public_key)?;
+        let checksums = String::from_utf8_lossy(&checksums);
+        let expected = expected_sha256(&checksums, &archive_name)
+            .with_context(|| format!("{} does not list {}", CHECKSUMS_ASSET, archive_name))?;
+
+        let archive = self.get(&archive_url).await?;
+        let actual = sha256_hex(&archive);
+        if actual != expected {
+            bail!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                archive_name,
+                expected,
+                actual
+            );
+        }
+
+        let dir = self.dir.clone();
+        let version = update.version.clone();
+        let staged = tokio::task::spawn_blocking(move || -> Result<StagedUpdate> {
+            std::fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+            let binary = dir.join(format!("screensearch-{}.exe", version));
+            extract_binary(&archive, &binary)?;
+
+            let staged = StagedUpdate {
+                sha256: sha256_file(&binary)?,
+                version,
+                binary,
+            };
+            write_json(&dir.join(STAGED_FILE), &staged)?;
+            Ok(staged)
+        })
+        .await
+        .context("Staging task failed")??;
+
+        Ok(staged)
+    }
+
+    async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(url)
+            .timeout(DOWNLOAD_TIMEOUT)
+            .send()
+            .await
+            .with_context(|| format!("Failed to download {}", url))?
+            .error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// Write the binary in the portable release `archive` to `path`
+fn extract_binary(archive: &[u8], path: &Path) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(archive))
+        .context("Failed to open the release archive")?;
+    let mut entry = archive
+        .by_name(ARCHIVE_BINARY)
+        .with_context(|| format!("The release archive has no {}", ARCHIVE_BINARY))?;
+
+    let mut binary = Vec::new();
+    entry.read_to_end(&mut binary)?;
+    std::fs::write(path, binary).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Check that `signature` is a minisign signature of `checksums` by `public_key`
+fn verify_signature(checksums: &[u8], signature: &str, public_key: &str) -> Result<()> {
+    let public_key = minisign_verify::PublicKey::from_base64(public_key.trim())
+        .context("Invalid update public key")?;
+    let signature = minisign_verify::Signature::decode(signature)
+        .with_context(|| format!("Invalid {}", SIGNATURE_ASSET))?;
+    public_key
+        .verify(checksums, &signature, false)
+        .with_context(|| format!("{} is not signed with the update key", CHECKSUMS_ASSET))
+}
+
+/// SHA-256 of `file_name` in a `checksums.txt` (`<hash>  <file name>` lines)
+fn expected_sha256(checksums: &str, file_name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        // Written by PowerShell, which starts UTF-8 files with a BOM
+        let mut fields = line.trim_start_matches('\u{feff}').split_whitespace();
+        let hash = fields.next()?;
+        (fields.next()? == file_name).then(|| hash.to_lowercase())
+    })
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(sha256_hex(&data))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    match std::fs::read(path) {
+        Ok(data) => {
+            Ok(Some(serde_json::from_slice(&data).with_context(|| {
+                format!("Failed to parse {}", path.display())
+            })?))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, serde_json::to_vec_pretty(value)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn remove_file(path: &Path) {
+    match std::fs::remove_file(path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!("Failed to remove {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "screensearch-updater-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_expected_sha256_from_checksums() {
+        let checksums = "\u{feff}AB12  ScreenSearch-v0.3.0-Setup-Full.exe\r\n\
+                         cd34  ScreenSearch-v0.3.0-Portable.zip\r\n";
+        assert_eq!(
+            expected_sha256(checksums, "ScreenSearch-v0.3.0-Setup-Full.exe").as_deref(),
+            Some("ab12")
+        );
+        assert_eq!(
+            expected_sha256(checksums, "ScreenSearch-v0.3.0-Portable.zip").as_deref(),
+            Some("cd34")
+        );
+        assert_eq!(expected_sha256(checksums, "checksums.txt"), None);
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        const PUBLIC_KEY: &str = "RWReXqLEAQIDBOpKbGPinFIKvvVQexMuxfmVR3auvr57kkIe6mkURtIs";
+        const SIGNATURE: &str = "untrusted comment: signature from minisign secret key\n\
+            RUReXqLEAQIDBMt2wJUKIip5L8GciL52J2kwfA/COFPwMBcTEMhKM8WtGCJ4kexEjPhE5ohDRGBXK7gKYDmqbuLaAZAo9W1r4As=\n\
+            trusted comment: timestamp:1760000000\tfile:checksums.txt\thashed\n\
+            86XLUUF6ZQLtx5F8Nz9pnzR+TSS7C9dXtuhR1f8qCoSWu7sMYokDOxtI3EG/VqHvRUrktFOqJB7NPNf591MkBQ==\n";
+        let checksums = b"ab12  ScreenSearch-v0.3.0-Portable.zip\n";
+
+        verify_signature(checksums, SIGNATURE, PUBLIC_KEY).unwrap();
+        assert!(verify_signature(
+            b"ef56  ScreenSearch-v0.3.0-Portable.zip\n",
+            SIGNATURE,
+            PUBLIC_KEY
+        )
+        .is_err());
+        assert!(verify_signature(checksums, "not a signature", PUBLIC_KEY).is_err());
+        assert!(verify_signature(
+            checksums,
+            SIGNATURE,
+            "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_extract_binary() {
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        archive.start_file("config.toml", options).unwrap();
+        archive.write_all(b"[capture]").unwrap();
+        archive.start_file(ARCHIVE_BINARY, options).unwrap();
+        archive.write_all(b"new binary").unwrap();
+        let archive = archive.finish().unwrap().into_inner();
+
+        let dir = temp_dir("extract");
+        let path = dir.join("screensearch-0.3.0.exe");
+        extract_binary(&archive, &path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new binary");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_install_and_rollback() {
+        let dir = temp_dir("install");
+        let exe = dir.join("screensearch.exe");
+        let staged = dir.join("screensearch-0.3.0.exe");
+        std::fs::write(&exe, "old").unwrap();
+        std::fs::write(&staged, "new").unwrap();
+
+        let backup = with_suffix(&exe, ".old");
+        install(&exe, &staged, &backup).unwrap();
+        assert_eq!(std::fs::read_to_string(&exe).unwrap(), "new");
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "old");
+
+        let pending = PendingUpdate {
+            version: "0.3.0".to_string(),
+            previous_version: "0.2.0".to_string(),
+            backup: backup.clone(),
+            attempts: MAX_START_ATTEMPTS + 1,
+        };
+        rollback(&exe, &pending).unwrap();
+        assert_eq!(std::fs::read_to_string(&exe).unwrap(), "old");
+        assert!(!backup.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}